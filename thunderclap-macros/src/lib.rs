@@ -5,22 +5,30 @@ mod widget;
 
 use {proc_macro::TokenStream, quote::quote, rooftop::RooftopData, widget::WidgetImpls};
 
-fn find_crate_name(attrs: &[syn::Attribute]) -> Option<syn::Ident> {
+/// Returns the argument of the `#[name(ident)]` attribute, if there is one.
+fn find_attr_ident(attrs: &[syn::Attribute], name: &str) -> syn::Result<Option<syn::Ident>> {
     for attr in attrs {
-        if attr.path.segments.first().map(|i| i.ident == "thunderclap_crate").unwrap_or(false) {
-            if let proc_macro2::TokenTree::Group(grp) =
-                attr.clone().tokens.into_iter().nth(0).unwrap()
-            {
-                if let proc_macro2::TokenTree::Ident(ident) =
-                    grp.stream().into_iter().nth(0).unwrap()
-                {
-                    return Some(ident);
-                }
-            }
+        if attr.path.segments.first().map(|i| i.ident == name).unwrap_or(false) {
+            return attr.parse_args().map(Some);
         }
     }
 
-    None
+    Ok(None)
+}
+
+fn find_crate_name(attrs: &[syn::Attribute]) -> syn::Result<syn::Ident> {
+    Ok(find_attr_ident(attrs, "thunderclap_crate")?
+        .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site())))
+}
+
+/// Reports that a derive macro was applied to an enum or union, at the span of its `enum`/`union` keyword.
+fn struct_only_error(data: &syn::Data, derive: &str) -> syn::Error {
+    let message = format!("derive({}) only supports structs", derive);
+    match data {
+        syn::Data::Struct(data) => syn::Error::new_spanned(data.struct_token, message),
+        syn::Data::Enum(data) => syn::Error::new_spanned(data.enum_token, message),
+        syn::Data::Union(data) => syn::Error::new_spanned(data.union_token, message),
+    }
 }
 
 enum IdentOrIndex {
@@ -28,18 +36,223 @@ enum IdentOrIndex {
     Index(syn::Index),
 }
 
+#[proc_macro_derive(
+    WidgetChildren,
+    attributes(
+        widget_child,
+        widget_children,
+        widget_children_trait,
+        widget_accessible,
+        widget_layer,
+        widget_id,
+        thunderclap_crate
+    )
+)]
+pub fn widget_children_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    impl_widget_children_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
+}
+
+fn impl_widget_children_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
+    match &ast.data {
+        syn::Data::Struct(ref data) => {
+            let crate_name = find_crate_name(&ast.attrs)?;
+            let trait_path = find_widget_children_trait(&ast.attrs)
+                .unwrap_or_else(|| syn::parse_quote! { #crate_name::base::WidgetChildren });
+            let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+            let name = &ast.ident;
+
+            let accessible = if chk_attrs_is_widget_accessible(&ast.attrs) {
+                quote! {
+                    #[inline]
+                    fn accessible(&self) -> Option<&dyn #crate_name::a11y::Accessible> {
+                        Some(self)
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            let mut children = Vec::new();
+            let mut children_mut = Vec::new();
+            let mut layer = quote! {};
+            let mut id = quote! {};
+
+            for (i, field) in data.fields.iter().enumerate() {
+                let member = match &field.ident {
+                    Some(ident) => quote! { self.#ident },
+                    None => {
+                        let index: syn::Index = i.into();
+                        quote! { self.#index }
+                    }
+                };
+
+                if chk_attrs_is_widget_layer(&field.attrs) {
+                    layer = quote! {
+                        #[inline]
+                        fn layer(&self) -> #crate_name::base::Layer {
+                            #member
+                        }
+                    };
+                }
+
+                if chk_attrs_is_widget_id(&field.attrs) {
+                    id = quote! {
+                        #[inline]
+                        fn id(&self) -> Option<&str> {
+                            #member.as_ref().map(|id| &id[..])
+                        }
+                    };
+                }
+
+                if chk_attrs_is_widget_child(&field.attrs) {
+                    children.push(quote! {
+                        children.push(&#member);
+                    });
+                    children_mut.push(quote! {
+                        children.push(&mut #member);
+                    });
+                } else if chk_attrs_is_widget_children(&field.attrs) {
+                    // Any collection which can be iterated by reference (e.g. `Vec`, `VecDeque` or `Option`).
+                    children.push(quote! {
+                        for child in ::std::iter::IntoIterator::into_iter(&#member) {
+                            children.push(child);
+                        }
+                    });
+                    children_mut.push(quote! {
+                        for child in ::std::iter::IntoIterator::into_iter(&mut #member) {
+                            children.push(child);
+                        }
+                    });
+                }
+            }
+
+            Ok({
+                quote! {
+                    impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+                        fn children(
+                            &self
+                        ) -> Vec<
+                            &dyn #trait_path<
+                                UpdateAux = Self::UpdateAux,
+                                GraphicalAux = Self::GraphicalAux,
+                                DisplayObject = Self::DisplayObject,
+                            >
+                        > {
+                            #[allow(unused_mut)]
+                            let mut children: Vec<
+                                &dyn #trait_path<
+                                    UpdateAux = Self::UpdateAux,
+                                    GraphicalAux = Self::GraphicalAux,
+                                    DisplayObject = Self::DisplayObject,
+                                >
+                            > = Vec::new();
+                            #(#children)*
+                            children
+                        }
+
+                        fn children_mut(
+                            &mut self
+                        ) -> Vec<
+                            &mut dyn #trait_path<
+                                UpdateAux = Self::UpdateAux,
+                                GraphicalAux = Self::GraphicalAux,
+                                DisplayObject = Self::DisplayObject,
+                            >
+                        > {
+                            #[allow(unused_mut)]
+                            let mut children: Vec<
+                                &mut dyn #trait_path<
+                                    UpdateAux = Self::UpdateAux,
+                                    GraphicalAux = Self::GraphicalAux,
+                                    DisplayObject = Self::DisplayObject,
+                                >
+                            > = Vec::new();
+                            #(#children_mut)*
+                            children
+                        }
+
+                        #accessible
+
+                        #layer
+
+                        #id
+                    }
+                }
+            }
+            .into())
+        }
+        _ => Err(struct_only_error(&ast.data, "WidgetChildren")),
+    }
+}
+
+fn chk_attrs_is_widget_child(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_child").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+fn chk_attrs_is_widget_children(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_children").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+fn chk_attrs_is_widget_accessible(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_accessible").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+fn chk_attrs_is_widget_layer(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_layer").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+fn chk_attrs_is_widget_id(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_id").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+fn find_widget_children_trait(attrs: &[syn::Attribute]) -> Option<syn::Path> {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_children_trait").unwrap_or(false) {
+            return attr.parse_args().ok();
+        }
+    }
+
+    None
+}
+
 #[proc_macro_derive(LayableWidget, attributes(widget_layout, thunderclap_crate))]
 pub fn layable_widget_macro_derive(input: TokenStream) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    impl_layable_widget_macro(ast)
+    impl_layable_widget_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
 }
 
-fn impl_layable_widget_macro(ast: syn::DeriveInput) -> TokenStream {
+fn impl_layable_widget_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
     match &ast.data {
         syn::Data::Struct(ref data) => {
-            let crate_name = find_crate_name(&ast.attrs)
-                .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site()));
+            let crate_name = find_crate_name(&ast.attrs)?;
             let mut layout_ident = None;
             let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
             let name = &ast.ident;
@@ -63,7 +276,12 @@ fn impl_layable_widget_macro(ast: syn::DeriveInput) -> TokenStream {
                         }
                     }
                 }
-                syn::Fields::Unit => panic!("Unit structs aren't capable of having a layout field"),
+                syn::Fields::Unit => {
+                    return Err(syn::Error::new_spanned(
+                        &ast.ident,
+                        "unit structs aren't capable of having a layout field",
+                    ))
+                }
             }
 
             if let Some(layout_ident) = layout_ident {
@@ -72,7 +290,7 @@ fn impl_layable_widget_macro(ast: syn::DeriveInput) -> TokenStream {
                     IdentOrIndex::Index(index) => quote! { self.#index },
                 };
 
-                {
+                Ok({
                     quote!{
                         impl #impl_generics #crate_name::base::LayableWidget for #name #ty_generics #where_clause {
                             #[inline]
@@ -86,12 +304,15 @@ fn impl_layable_widget_macro(ast: syn::DeriveInput) -> TokenStream {
                             }
                         }
                     }
-                }.into()
+                }.into())
             } else {
-                panic!("Could not find [widget_layout] attribute on any field")
+                Err(syn::Error::new_spanned(
+                    &ast.ident,
+                    "could not find a `#[widget_layout]` attribute on any field",
+                ))
             }
         }
-        _ => panic!("derive(LayableWidget) only supports structs."),
+        _ => Err(struct_only_error(&ast.data, "LayableWidget")),
     }
 }
 
@@ -106,16 +327,15 @@ fn chk_attrs_is_layout(attrs: &[syn::Attribute]) -> bool {
 
 #[proc_macro_derive(DropNotifier, attributes(widget_drop_event, thunderclap_crate))]
 pub fn drop_notifier_macro_derive(input: TokenStream) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    impl_drop_notifier_macro(ast)
+    impl_drop_notifier_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
 }
 
-fn impl_drop_notifier_macro(ast: syn::DeriveInput) -> TokenStream {
+fn impl_drop_notifier_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
     match &ast.data {
         syn::Data::Struct(ref data) => {
-            let crate_name = find_crate_name(&ast.attrs)
-                .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site()));
+            let crate_name = find_crate_name(&ast.attrs)?;
             let mut drop_event_ident = None;
             let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
             let name = &ast.ident;
@@ -140,7 +360,10 @@ fn impl_drop_notifier_macro(ast: syn::DeriveInput) -> TokenStream {
                     }
                 }
                 syn::Fields::Unit => {
-                    panic!("Unit structs aren't capable of having a drop event field")
+                    return Err(syn::Error::new_spanned(
+                        &ast.ident,
+                        "unit structs aren't capable of having a drop event field",
+                    ))
                 }
             }
 
@@ -150,7 +373,7 @@ fn impl_drop_notifier_macro(ast: syn::DeriveInput) -> TokenStream {
                     IdentOrIndex::Index(index) => quote! { self.#index },
                 };
 
-                {
+                Ok({
                     quote!{
                         impl #impl_generics #crate_name::base::DropNotifier for #name #ty_generics #where_clause {
                             #[inline(always)]
@@ -159,12 +382,15 @@ fn impl_drop_notifier_macro(ast: syn::DeriveInput) -> TokenStream {
                             }
                         }
                     }
-                }.into()
+                }.into())
             } else {
-                panic!("Could not find [widget_drop_event] attribute on any field")
+                Err(syn::Error::new_spanned(
+                    &ast.ident,
+                    "could not find a `#[widget_drop_event]` attribute on any field",
+                ))
             }
         }
-        _ => panic!("derive(DropNotifier) only supports structs."),
+        _ => Err(struct_only_error(&ast.data, "DropNotifier")),
     }
 }
 
@@ -179,16 +405,15 @@ fn chk_attrs_is_drop_event(attrs: &[syn::Attribute]) -> bool {
 
 #[proc_macro_derive(HasVisibility, attributes(widget_visibility, thunderclap_crate))]
 pub fn has_visibility_macro_derive(input: TokenStream) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    impl_has_visibility_macro(ast)
+    impl_has_visibility_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
 }
 
-fn impl_has_visibility_macro(ast: syn::DeriveInput) -> TokenStream {
+fn impl_has_visibility_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
     match &ast.data {
         syn::Data::Struct(ref data) => {
-            let crate_name = find_crate_name(&ast.attrs)
-                .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site()));
+            let crate_name = find_crate_name(&ast.attrs)?;
             let mut vis_ident = None;
             let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
             let name = &ast.ident;
@@ -213,7 +438,10 @@ fn impl_has_visibility_macro(ast: syn::DeriveInput) -> TokenStream {
                     }
                 }
                 syn::Fields::Unit => {
-                    panic!("Unit structs aren't capable of having a visibility field")
+                    return Err(syn::Error::new_spanned(
+                        &ast.ident,
+                        "unit structs aren't capable of having a visibility field",
+                    ))
                 }
             }
 
@@ -223,7 +451,7 @@ fn impl_has_visibility_macro(ast: syn::DeriveInput) -> TokenStream {
                     IdentOrIndex::Index(index) => quote! { self.#index },
                 };
 
-                {
+                Ok({
                     quote!{
                         impl #impl_generics #crate_name::base::HasVisibility for #name #ty_generics #where_clause {
                             #[inline]
@@ -237,12 +465,15 @@ fn impl_has_visibility_macro(ast: syn::DeriveInput) -> TokenStream {
                             }
                         }
                     }
-                }.into()
+                }.into())
             } else {
-                panic!("Could not find [widget_visibility] attribute on any field")
+                Err(syn::Error::new_spanned(
+                    &ast.ident,
+                    "could not find a `#[widget_visibility]` attribute on any field",
+                ))
             }
         }
-        _ => panic!("derive(HasVisibility) only supports structs."),
+        _ => Err(struct_only_error(&ast.data, "HasVisibility")),
     }
 }
 
@@ -257,20 +488,20 @@ fn chk_attrs_is_visibility(attrs: &[syn::Attribute]) -> bool {
 
 #[proc_macro_derive(Repaintable, attributes(repaint_target, thunderclap_crate))]
 pub fn repaintable_macro_derive(input: TokenStream) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    impl_repaintable_macro(ast)
+    impl_repaintable_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
 }
 
-fn impl_repaintable_macro(ast: syn::DeriveInput) -> TokenStream {
+fn impl_repaintable_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
     match &ast.data {
         syn::Data::Struct(ref data) => {
-            let crate_name = find_crate_name(&ast.attrs)
-                .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site()));
+            let crate_name = find_crate_name(&ast.attrs)?;
             let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
             let name = &ast.ident;
 
             let mut repaint_targets = Vec::new();
+            let mut dirty_checks = Vec::new();
 
             match &data.fields {
                 syn::Fields::Named(fields) => {
@@ -280,6 +511,9 @@ fn impl_repaintable_macro(ast: syn::DeriveInput) -> TokenStream {
                                 repaint_targets.push(quote! {
                                     self.#ident.repaint();
                                 });
+                                dirty_checks.push(quote! {
+                                    #crate_name::base::RepaintTarget::will_repaint(&self.#ident)
+                                });
                             }
                         }
                     }
@@ -291,14 +525,25 @@ fn impl_repaintable_macro(ast: syn::DeriveInput) -> TokenStream {
 
                             repaint_targets.push(quote! {
                                 self.#index.repaint();
-                            })
+                            });
+                            dirty_checks.push(quote! {
+                                #crate_name::base::RepaintTarget::will_repaint(&self.#index)
+                            });
                         }
                     }
                 }
                 _ => {}
             }
 
-            {
+            let is_dirty = dirty_checks
+                .into_iter()
+                .fold(None, |expr, check| match expr {
+                    Some(expr) => Some(quote! { #expr || #check }),
+                    None => Some(check),
+                })
+                .unwrap_or_else(|| quote! { false });
+
+            Ok({
                 quote!{
                     impl #impl_generics #crate_name::base::Repaintable for #name #ty_generics #where_clause {
                         #[inline]
@@ -309,11 +554,16 @@ fn impl_repaintable_macro(ast: syn::DeriveInput) -> TokenStream {
                                 child.repaint();
                             }
                         }
+
+                        #[inline]
+                        fn is_dirty(&self) -> bool {
+                            #is_dirty
+                        }
                     }
                 }
-            }.into()
+            }.into())
         }
-        _ => panic!("derive(Repaintable) only supports structs."),
+        _ => Err(struct_only_error(&ast.data, "Repaintable")),
     }
 }
 
@@ -331,21 +581,20 @@ fn chk_attrs_is_repaint_target(attrs: &[syn::Attribute]) -> bool {
     attributes(widget_position, widget_rect, widget_transform_callback, thunderclap_crate)
 )]
 pub fn movable_macro_derive(input: TokenStream) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    impl_movable_macro(ast)
+    impl_movable_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
 }
 
-fn impl_movable_macro(ast: syn::DeriveInput) -> TokenStream {
+fn impl_movable_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
     match &ast.data {
         syn::Data::Struct(ref data) => {
-            let crate_name = find_crate_name(&ast.attrs)
-                .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site()));
+            let crate_name = find_crate_name(&ast.attrs)?;
             let mut assignment = None;
             let mut return_val = None;
             let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
             let name = &ast.ident;
-            let callback = find_widget_transform_callback(&ast.attrs)
+            let callback = find_widget_transform_callback(&ast.attrs)?
                 .map(|ident| quote! { self.#ident(); })
                 .unwrap_or_else(|| quote! {});
 
@@ -396,12 +645,15 @@ fn impl_movable_macro(ast: syn::DeriveInput) -> TokenStream {
                     }
                 }
                 syn::Fields::Unit => {
-                    panic!("Unit structs aren't capable of having a position/rectangle field")
+                    return Err(syn::Error::new_spanned(
+                        &ast.ident,
+                        "unit structs aren't capable of having a position/rectangle field",
+                    ))
                 }
             }
 
             if let Some(assignment) = assignment {
-                {
+                Ok({
                     quote!{
                         impl #impl_generics #crate_name::base::Movable for #name #ty_generics #where_clause {
                             fn set_position(&mut self, position: #crate_name::geom::RelativePoint) {
@@ -416,12 +668,15 @@ fn impl_movable_macro(ast: syn::DeriveInput) -> TokenStream {
                             }
                         }
                     }
-                }.into()
+                }.into())
             } else {
-                panic!("Could not find [widget_position] or [widget_rect] attribute on any field")
+                Err(syn::Error::new_spanned(
+                    &ast.ident,
+                    "could not find a `#[widget_position]` or `#[widget_rect]` attribute on any field",
+                ))
             }
         }
-        _ => panic!("derive(Movable) only supports structs."),
+        _ => Err(struct_only_error(&ast.data, "Movable")),
     }
 }
 
@@ -443,28 +698,8 @@ fn chk_attrs_is_rect(attrs: &[syn::Attribute]) -> bool {
     false
 }
 
-fn find_widget_transform_callback(attrs: &[syn::Attribute]) -> Option<syn::Ident> {
-    for attr in attrs {
-        if attr
-            .path
-            .segments
-            .first()
-            .map(|i| i.ident == "widget_transform_callback")
-            .unwrap_or(false)
-        {
-            if let proc_macro2::TokenTree::Group(grp) =
-                attr.clone().tokens.into_iter().nth(0).unwrap()
-            {
-                if let proc_macro2::TokenTree::Ident(ident) =
-                    grp.stream().into_iter().nth(0).unwrap()
-                {
-                    return Some(ident);
-                }
-            }
-        }
-    }
-
-    None
+fn find_widget_transform_callback(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Ident>> {
+    find_attr_ident(attrs, "widget_transform_callback")
 }
 
 #[proc_macro_derive(
@@ -472,21 +707,20 @@ fn find_widget_transform_callback(attrs: &[syn::Attribute]) -> Option<syn::Ident
     attributes(widget_size, widget_rect, widget_transform_callback, thunderclap_crate)
 )]
 pub fn resizable_macro_derive(input: TokenStream) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    impl_resizable_macro(ast)
+    impl_resizable_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
 }
 
-fn impl_resizable_macro(ast: syn::DeriveInput) -> TokenStream {
+fn impl_resizable_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
     match &ast.data {
         syn::Data::Struct(ref data) => {
-            let crate_name = find_crate_name(&ast.attrs)
-                .unwrap_or_else(|| syn::Ident::new("thunderclap", proc_macro2::Span::call_site()));
+            let crate_name = find_crate_name(&ast.attrs)?;
             let mut assignment = None;
             let mut return_val = None;
             let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
             let name = &ast.ident;
-            let callback = find_widget_transform_callback(&ast.attrs)
+            let callback = find_widget_transform_callback(&ast.attrs)?
                 .map(|ident| quote! { self.#ident(); })
                 .unwrap_or_else(|| quote! {});
 
@@ -537,15 +771,19 @@ fn impl_resizable_macro(ast: syn::DeriveInput) -> TokenStream {
                     }
                 }
                 syn::Fields::Unit => {
-                    panic!("Unit structs aren't capable of having a position/rectangle field")
+                    return Err(syn::Error::new_spanned(
+                        &ast.ident,
+                        "unit structs aren't capable of having a position/rectangle field",
+                    ))
                 }
             }
 
             if let Some(assignment) = assignment {
-                {
+                Ok({
                     quote!{
                         impl #impl_generics #crate_name::base::Resizable for #name #ty_generics #where_clause {
                             fn set_size(&mut self, size: #crate_name::reclutch::display::Size) {
+                                let size = #crate_name::geom::clamp_size(size);
                                 #assignment
                                 #crate_name::base::Repaintable::repaint(self);
                                 #callback
@@ -557,12 +795,15 @@ fn impl_resizable_macro(ast: syn::DeriveInput) -> TokenStream {
                             }
                         }
                     }
-                }.into()
+                }.into())
             } else {
-                panic!("Could not find [widget_position] or [widget_rect] attribute on any field")
+                Err(syn::Error::new_spanned(
+                    &ast.ident,
+                    "could not find a `#[widget_position]` or `#[widget_rect]` attribute on any field",
+                ))
             }
         }
-        _ => panic!("derive(Movable) only supports structs."),
+        _ => Err(struct_only_error(&ast.data, "Resizable")),
     }
 }
 
@@ -575,6 +816,184 @@ fn chk_attrs_is_size(attrs: &[syn::Attribute]) -> bool {
     false
 }
 
+/// Implements the plumbing which lets a hand-written widget be used within `rooftop!` views;
+/// `WidgetDataTarget` and `WidgetConstructor` for its data type, and `DefaultWidgetData`/`DefaultEventQueue` for the widget.
+///
+/// The widget must be generic over its auxiliary types as `U` and `G`, store its data in a field marked `#[widget_data]`
+/// (of type `Observed<Data>`) and name its constructor with `#[widget_data_constructor]`.
+/// The event queue (of type `RcEventQueue<Event>`) is marked with `#[widget_event_queue]`, if the widget has one.
+///
+/// Data is initialized with `Default::default()` before the assignments of the view are applied,
+/// unless a function creating it from the theme is named with `#[widget_data_from_theme]`.
+///
+/// ```ignore
+/// #[derive(RooftopCompatible)]
+/// #[widget_data_constructor(new)] // fn new(data: Meter, theme: &dyn Theme, u_aux: &mut U) -> Self
+/// #[widget_data_from_theme(from_theme)] // fn from_theme(theme: &dyn Theme) -> Meter
+/// pub struct MeterWidget<U, G> {
+///     #[widget_data]
+///     data: Observed<Meter>,
+///     #[widget_event_queue]
+///     event_queue: RcEventQueue<MeterEvent>,
+///     // ... etc ...
+/// }
+/// ```
+#[proc_macro_derive(
+    RooftopCompatible,
+    attributes(
+        widget_data,
+        widget_event_queue,
+        widget_data_constructor,
+        widget_data_from_theme,
+        thunderclap_crate
+    )
+)]
+pub fn rooftop_compatible_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    impl_rooftop_compatible_macro(ast).unwrap_or_else(|error| error.to_compile_error().into())
+}
+
+fn impl_rooftop_compatible_macro(ast: syn::DeriveInput) -> syn::Result<TokenStream> {
+    match &ast.data {
+        syn::Data::Struct(ref data) => {
+            let crate_name = find_crate_name(&ast.attrs)?;
+            let name = &ast.ident;
+
+            for aux in &["U", "G"] {
+                if !ast.generics.type_params().any(|param| param.ident == aux) {
+                    return Err(syn::Error::new_spanned(
+                        &ast.ident,
+                        format!(
+                            "derive(RooftopCompatible) requires the widget to be generic over `{}`",
+                            aux
+                        ),
+                    ));
+                }
+            }
+
+            let constructor =
+                find_attr_ident(&ast.attrs, "widget_data_constructor")?.ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        &ast.ident,
+                        "could not find a `#[widget_data_constructor(...)]` attribute",
+                    )
+                })?;
+            let from_theme = match find_attr_ident(&ast.attrs, "widget_data_from_theme")? {
+                Some(from_theme) => quote! { Self::#from_theme(theme) },
+                None => quote! {
+                    let _ = theme;
+                    Default::default()
+                },
+            };
+
+            let mut widget_data = None;
+            let mut event_queue = None;
+            for (i, field) in data.fields.iter().enumerate() {
+                let member = match &field.ident {
+                    Some(ident) => quote! { self.#ident },
+                    None => {
+                        let index: syn::Index = i.into();
+                        quote! { self.#index }
+                    }
+                };
+
+                if chk_attrs_is_widget_data(&field.attrs) {
+                    widget_data = Some((member, type_argument(&field.ty, "Observed")?));
+                } else if chk_attrs_is_widget_event_queue(&field.attrs) {
+                    event_queue = Some((member, type_argument(&field.ty, "RcEventQueue")?));
+                }
+            }
+
+            let (data_member, data_ty) = widget_data.ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &ast.ident,
+                    "could not find a `#[widget_data]` attribute on any field",
+                )
+            })?;
+
+            let mut generics = ast.generics.clone();
+            let predicates = &mut generics.make_where_clause().predicates;
+            predicates.push(syn::parse_quote! { U: #crate_name::base::UpdateAuxiliary });
+            predicates.push(syn::parse_quote! { G: #crate_name::base::GraphicalAuxiliary });
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+            let event_queue_impl = event_queue.map(|(member, event_ty)| {
+                quote! {
+                    impl #impl_generics #crate_name::ui::DefaultEventQueue<#event_ty> for #name #ty_generics #where_clause {
+                        #[inline]
+                        fn default_event_queue(&self) -> &#crate_name::reclutch::event::RcEventQueue<#event_ty> {
+                            &#member
+                        }
+                    }
+                }
+            });
+
+            Ok({
+                quote! {
+                    impl #impl_generics #crate_name::ui::DefaultWidgetData<#data_ty> for #name #ty_generics #where_clause {
+                        #[inline]
+                        fn default_data(&mut self) -> &mut #crate_name::base::Observed<#data_ty> {
+                            &mut #data_member
+                        }
+                    }
+
+                    #event_queue_impl
+
+                    impl #impl_generics #crate_name::ui::WidgetDataTarget<U, G> for #data_ty #where_clause {
+                        type Target = #name #ty_generics;
+                    }
+
+                    impl #impl_generics #crate_name::ui::WidgetConstructor<U, G> for #data_ty #where_clause {
+                        fn from_theme(theme: &dyn #crate_name::draw::Theme) -> Self {
+                            #from_theme
+                        }
+
+                        fn construct(self, theme: &dyn #crate_name::draw::Theme, u_aux: &mut U) -> #name #ty_generics {
+                            #name::#constructor(self, theme, u_aux)
+                        }
+                    }
+                }
+            }
+            .into())
+        }
+        _ => Err(struct_only_error(&ast.data, "RooftopCompatible")),
+    }
+}
+
+/// Returns the type argument of a field type such as `Observed<T>`, failing at the span of the type otherwise.
+fn type_argument(ty: &syn::Type, expected: &str) -> syn::Result<syn::Type> {
+    if let syn::Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(ty)) = args.args.first() {
+                    return Ok(ty.clone());
+                }
+            }
+        }
+    }
+
+    Err(syn::Error::new_spanned(ty, format!("expected a field of type `{}<...>`", expected)))
+}
+
+fn chk_attrs_is_widget_data(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_data").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+fn chk_attrs_is_widget_event_queue(attrs: &[syn::Attribute]) -> bool {
+    for attr in attrs {
+        if attr.path.segments.first().map(|i| i.ident == "widget_event_queue").unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
 #[proc_macro]
 pub fn rooftop(stream: TokenStream) -> TokenStream {
     let data = syn::parse_macro_input!(stream as RooftopData);
@@ -648,17 +1067,44 @@ pub fn rooftop(stream: TokenStream) -> TokenStream {
 /// ```
 ///
 /// ## Generics
-/// Generics syntax is identical to Rust excluding support for a where clause;
+/// Generics syntax is identical to Rust, including where clauses;
 /// ```ignore
 /// widget! {
-///     struct GenericWidget<A: Debug + Display, B: ?Sized>
-///  /* where
-///         B: ?Sized, */ // unsupported syntax
+///     struct GenericWidget<A: Debug + Display, B>
+///     where
+///         B: ?Sized,
 ///     {
 ///         // ... etc ...
 ///     }
 /// }
 /// ```
+///
+/// Unless declared otherwise, the update and graphical auxiliary types are declared as `U` and `G`
+/// (ahead of the other type parameters), bound by `UpdateAuxiliary` and `GraphicalAuxiliary`.
+///
+/// Listing either among the generics opts into declaring them explicitly; only the listed ones are declared
+/// (as well as `U` if the widget operates a verb graph, and either if it's named in the fields).
+/// This places further bounds on them, or leaves out `G` for widgets which don't draw through a generic
+/// graphical auxiliary;
+/// ```ignore
+/// widget! {
+///     struct ClipboardWidget<U: HasClipboard, G> where G: HasFonts {
+///         // ... etc ...
+///     }
+/// }
+///
+/// widget! {
+///     struct CounterWidget<U> {
+///         // ... etc ...
+///     }
+/// }
+///
+/// impl<U: UpdateAuxiliary> Widget for CounterWidget<U> {
+///     type UpdateAux = U;
+///     type GraphicalAux = ();
+///     // ... etc ...
+/// }
+/// ```
 #[proc_macro]
 pub fn widget(stream: TokenStream) -> TokenStream {
     let data = syn::parse_macro_input!(stream as WidgetImpls);
@@ -72,6 +72,10 @@ fn parse_function(
 struct WidgetNode {
     type_name: syn::Ident,
     var_name: syn::Ident,
+    // Whether the widget was named with `as name`, in which case it's publicly accessible.
+    named: bool,
+    // Condition under which the widget is visible, if it's within an `if` block.
+    condition: Option<proc_macro2::TokenStream>,
     data_assignments: Vec<DataAssignment>,
     children: Vec<WidgetNode>,
 }
@@ -105,11 +109,20 @@ impl WidgetNode {
     }
 }
 
+/// State accumulated while parsing the widget tree of the view.
+#[derive(Default)]
+struct ParseContext {
+    bindings: Vec<proc_macro2::TokenStream>,
+    terminals: Vec<proc_macro2::TokenStream>,
+    lists: Vec<ListNode>,
+    // Number of unnamed widgets and lists so far, to name them uniquely.
+    count: u64,
+}
+
 fn parse_view(
     input: syn::parse::ParseStream,
-    bindings: &mut Vec<proc_macro2::TokenStream>,
-    terminals: &mut Vec<proc_macro2::TokenStream>,
-    count: &mut u64,
+    ctxt: &mut ParseContext,
+    condition: Option<&proc_macro2::TokenStream>,
 ) -> syn::Result<(WidgetNode, bool)> {
     let type_name = input.parse::<syn::Ident>()?;
     let assignments;
@@ -117,18 +130,19 @@ fn parse_view(
     let data_assignments: syn::punctuated::Punctuated<_, syn::Token![,]> =
         assignments.parse_terminated(DataAssignment::parse)?;
     let mut data_assignments: Vec<_> = data_assignments.into_iter().collect();
-    let var_name = if input.parse::<syn::Token![as]>().is_ok() {
+    let named = input.parse::<syn::Token![as]>().is_ok();
+    let var_name = if named {
         input.parse::<syn::Ident>()?
     } else {
-        *count += 1;
-        quote::format_ident!("unnamed_widget_{}", count)
+        ctxt.count += 1;
+        quote::format_ident!("unnamed_widget_{}", ctxt.count)
     };
 
     for assignment in &data_assignments {
         if assignment.binding {
             let value = assignment.value.clone();
             let var = assignment.var.clone();
-            bindings.push(quote! {
+            ctxt.bindings.push(quote! {
                 {
                     widget.#var_name.default_data().#var = #value;
                 }
@@ -144,8 +158,18 @@ fn parse_view(
         if input.parse::<syn::token::At>().is_ok() {
             let event_name = input.parse::<syn::Ident>()?;
             let handler_body = input.parse::<syn::Block>()?;
+            // `emit!(event)` emits an output event of the rooftop widget from within the handler.
             events.push(quote! {
                 #event_name => {
+                    #[allow(unused_macros)]
+                    macro_rules! emit {
+                        ($event:expr) => {
+                            {
+                                use thunderclap::reclutch::prelude::*;
+                                widget.event_queue.emit_owned($event);
+                            }
+                        };
+                    }
                     { #handler_body }
                 }
             });
@@ -155,7 +179,7 @@ fn parse_view(
     }
 
     if !events.is_empty() {
-        terminals.push({
+        ctxt.terminals.push({
             quote! {
                 std::stringify!(#var_name) => event in #var_name.default_event_queue() => {
                     #(#events)*
@@ -168,22 +192,146 @@ fn parse_view(
     if input.peek(syn::token::Brace) {
         let children_parse;
         syn::braced!(children_parse in input);
+        children = parse_children(&children_parse, &var_name, ctxt, condition)?;
+    }
 
-        let mut parse_child = true;
-        while parse_child {
-            if children_parse.is_empty() {
-                parse_child = false;
-            } else {
-                let (node, found_comma) = parse_view(&children_parse, bindings, terminals, count)?;
-                children.push(node);
-                parse_child = found_comma;
-            }
+    let found_comma = input.parse::<syn::Token![,]>().is_ok();
+
+    Ok((
+        WidgetNode {
+            type_name,
+            var_name,
+            named,
+            condition: condition.cloned(),
+            data_assignments,
+            children,
+        },
+        found_comma,
+    ))
+}
+
+fn parse_children(
+    input: syn::parse::ParseStream,
+    parent: &syn::Ident,
+    ctxt: &mut ParseContext,
+    condition: Option<&proc_macro2::TokenStream>,
+) -> syn::Result<Vec<WidgetNode>> {
+    let mut children = Vec::new();
+    let mut parse_child = true;
+    while parse_child {
+        if input.is_empty() {
+            parse_child = false;
+        } else if input.peek(syn::Token![if]) {
+            let found_comma = parse_conditional(input, parent, ctxt, condition, &mut children)?;
+            parse_child = found_comma;
+        } else if input.peek(syn::Token![for]) {
+            let found_comma = parse_list(input, parent, ctxt, condition)?;
+            parse_child = found_comma;
+        } else {
+            let (node, found_comma) = parse_view(input, ctxt, condition)?;
+            children.push(node);
+            parse_child = found_comma;
         }
     }
+    Ok(children)
+}
 
-    let found_comma = input.parse::<syn::Token![,]>().is_ok();
+/// Parses `if expr { ... } else { ... }` (where `else` is optional and may be followed by another `if`).
+///
+/// The widgets of every branch are all constructed and laid out as siblings,
+/// but only those of the branch matching the bound expression are visible.
+fn parse_conditional(
+    input: syn::parse::ParseStream,
+    parent: &syn::Ident,
+    ctxt: &mut ParseContext,
+    condition: Option<&proc_macro2::TokenStream>,
+    output: &mut Vec<WidgetNode>,
+) -> syn::Result<bool> {
+    input.parse::<syn::Token![if]>()?;
+    let branch_condition = syn::Expr::parse_without_eager_brace(input)?;
+
+    let (then_condition, else_condition) = match condition {
+        Some(condition) => (
+            quote! { (#condition) && (#branch_condition) },
+            quote! { (#condition) && !(#branch_condition) },
+        ),
+        None => (quote! { (#branch_condition) }, quote! { !(#branch_condition) }),
+    };
 
-    Ok((WidgetNode { type_name, var_name, data_assignments, children }, found_comma))
+    let then_parse;
+    syn::braced!(then_parse in input);
+    output.append(&mut parse_children(&then_parse, parent, ctxt, Some(&then_condition))?);
+
+    if input.parse::<syn::Token![else]>().is_ok() {
+        if input.peek(syn::Token![if]) {
+            return parse_conditional(input, parent, ctxt, Some(&else_condition), output);
+        }
+
+        let else_parse;
+        syn::braced!(else_parse in input);
+        output.append(&mut parse_children(&else_parse, parent, ctxt, Some(&else_condition))?);
+    }
+
+    Ok(input.parse::<syn::Token![,]>().is_ok())
+}
+
+/// Widgets declared with `for pattern in bind(expr) { Widget(...) }`, one for each item of a bound collection.
+#[derive(Debug, Clone)]
+struct ListNode {
+    type_name: syn::Ident,
+    var_name: syn::Ident,
+    // Layout the widgets are pushed into.
+    parent: syn::Ident,
+    pattern: syn::Pat,
+    collection: syn::Expr,
+    // Assignments evaluated for each item (whether or not they're wrapped in `bind`).
+    data_assignments: Vec<DataAssignment>,
+    condition: Option<proc_macro2::TokenStream>,
+}
+
+/// Parses `for pattern in bind(expr) { Widget(...) }`.
+///
+/// The item widget can't have children, event handlers or a name.
+fn parse_list(
+    input: syn::parse::ParseStream,
+    parent: &syn::Ident,
+    ctxt: &mut ParseContext,
+    condition: Option<&proc_macro2::TokenStream>,
+) -> syn::Result<bool> {
+    input.parse::<syn::Token![for]>()?;
+    let pattern = input.parse::<syn::Pat>()?;
+    input.parse::<syn::Token![in]>()?;
+    input.parse::<bind_syntax::bind>()?;
+    let collection;
+    syn::parenthesized!(collection in input);
+    let collection = collection.parse::<syn::Expr>()?;
+
+    let body;
+    syn::braced!(body in input);
+    let type_name = body.parse::<syn::Ident>()?;
+    let assignments;
+    syn::parenthesized!(assignments in body);
+    let data_assignments: syn::punctuated::Punctuated<_, syn::Token![,]> =
+        assignments.parse_terminated(DataAssignment::parse)?;
+    body.parse::<Option<syn::Token![,]>>()?;
+    if !body.is_empty() {
+        return Err(body.error(
+            "expected a single widget without children, event handlers or a name in `for` block",
+        ));
+    }
+
+    ctxt.count += 1;
+    ctxt.lists.push(ListNode {
+        type_name,
+        var_name: quote::format_ident!("unnamed_list_{}", ctxt.count),
+        parent: parent.clone(),
+        pattern,
+        collection,
+        data_assignments: data_assignments.into_iter().collect(),
+        condition: condition.cloned(),
+    });
+
+    Ok(input.parse::<syn::Token![,]>().is_ok())
 }
 
 fn flatten_widget_node_tree(root: &WidgetNode, output: &mut Vec<WidgetNode>) {
@@ -224,11 +372,13 @@ impl DataAssignment {
 #[derive(Debug)]
 pub(crate) struct RooftopData {
     struct_name: syn::Ident,
+    generics: syn::Generics,
     output_event: syn::Type,
     data_fields: DataFieldList,
     widget_tree_root: WidgetNode,
     bindings: Vec<proc_macro2::TokenStream>,
     terminals: Vec<proc_macro2::TokenStream>,
+    lists: Vec<ListNode>,
     functions: Vec<(syn::Ident, syn::Block)>,
     vis: Option<syn::Visibility>,
 }
@@ -238,15 +388,16 @@ impl syn::parse::Parse for RooftopData {
         let vis = input.parse::<syn::Visibility>().ok();
 
         input.parse::<syn::Token![struct]>()?;
-        let struct_name = input.parse()?;
+        let struct_name = input.parse::<syn::Ident>()?;
+        let mut generics = input.parse::<syn::Generics>()?;
         input.parse::<syn::Token![:]>()?;
         let output_event = input.parse()?;
+        generics.where_clause = input.parse()?;
         let struct_content;
         syn::braced!(struct_content in input);
 
         let mut parse_fn = struct_content.peek(syn::Token![fn]);
-        let mut view_body = None;
-        let mut data_fields = None;
+        let mut view = None;
         let mut other_functions = Vec::new();
         while parse_fn {
             let (fn_name, param_fields, body, next_fn) = parse_function(&struct_content)?;
@@ -254,8 +405,13 @@ impl syn::parse::Parse for RooftopData {
 
             match body {
                 FunctionBody::View(body) => {
-                    view_body = Some(body);
-                    data_fields = param_fields.unwrap().into();
+                    let data_fields = param_fields.ok_or_else(|| {
+                        syn::Error::new(
+                            fn_name.span(),
+                            "the data fields must be declared as parameters of `build`",
+                        )
+                    })?;
+                    view = Some((body, data_fields));
                 }
                 FunctionBody::Other(body) => {
                     other_functions.push((fn_name, body));
@@ -263,21 +419,23 @@ impl syn::parse::Parse for RooftopData {
             }
         }
 
-        let view_body = view_body.expect("no build() pseudo-function found");
+        let (view_body, data_fields) = view.ok_or_else(|| {
+            syn::Error::new(struct_name.span(), "no `build` pseudo-function found")
+        })?;
 
-        let mut bindings = Vec::new();
-        let mut terminals = Vec::new();
-        let mut count = 0;
-        let widget_tree_root = parse_view(&view_body, &mut bindings, &mut terminals, &mut count)?.0;
+        let mut ctxt = ParseContext::default();
+        let widget_tree_root = parse_view(&view_body, &mut ctxt, None)?.0;
+        let ParseContext { bindings, terminals, lists, .. } = ctxt;
 
         Ok(RooftopData {
             struct_name,
+            generics,
             output_event,
-            data_fields: data_fields
-                .expect("failed to find data fields (parameters of build() pseudo-function)"),
+            data_fields,
             widget_tree_root,
             bindings,
             terminals,
+            lists,
             functions: other_functions,
             vis,
         })
@@ -317,6 +475,25 @@ impl RooftopData {
 
         let widget_name = quote::format_ident!("{}Widget", struct_name);
 
+        // The widget takes the generic parameters of the data struct after `U` and `G` (but after any lifetimes).
+        let mut widget_generics = self.generics.clone();
+        let lifetimes = widget_generics
+            .params
+            .iter()
+            .filter(|param| matches!(param, syn::GenericParam::Lifetime(_)))
+            .count();
+        widget_generics.params.insert(lifetimes, syn::parse_quote!(U));
+        widget_generics.params.insert(lifetimes + 1, syn::parse_quote!(G));
+        widget_generics.where_clause = None;
+        let (widget_generics, widget_type_generics, _) = widget_generics.split_for_impl();
+        let (data_generics, data_type_generics, data_where_clause) = self.generics.split_for_impl();
+        let where_predicates: Vec<&syn::WherePredicate> = self
+            .generics
+            .where_clause
+            .iter()
+            .flat_map(|where_clause| where_clause.predicates.iter())
+            .collect();
+
         let crate_name = quote::format_ident!("thunderclap");
 
         let mut flattened_nodes = Vec::new();
@@ -371,6 +548,228 @@ impl RooftopData {
             })
             .collect();
 
+        let list_fields: Vec<proc_macro2::TokenStream> = self
+            .lists
+            .iter()
+            .map(|list| {
+                let name = &list.var_name;
+                let type_name = &list.type_name;
+                quote! {
+                    #[widget_children]
+                    #name: Vec<<#type_name as #crate_name::ui::WidgetDataTarget<U, G>>::Target>,
+                }
+            })
+            .collect();
+
+        let list_names: Vec<&syn::Ident> = self.lists.iter().map(|list| &list.var_name).collect();
+
+        // Item widgets are constructed from the data assignments evaluated for each item.
+        let list_item_constructors: Vec<proc_macro2::TokenStream> = self
+            .lists
+            .iter()
+            .map(|list| {
+                let type_name = &list.type_name;
+                let assignments: Vec<proc_macro2::TokenStream> = list
+                    .data_assignments
+                    .iter()
+                    .map(|assignment| {
+                        let var = &assignment.var;
+                        let value = &assignment.value;
+                        quote! {
+                            #var: #value,
+                        }
+                    })
+                    .collect();
+                quote! {
+                    #type_name {
+                        #(#assignments)*
+                        ..<#type_name as #crate_name::ui::WidgetConstructor<U, G>>::from_theme(theme)
+                    }
+                }
+            })
+            .collect();
+
+        let list_declarations: Vec<proc_macro2::TokenStream> = self
+            .lists
+            .iter()
+            .zip(&list_item_constructors)
+            .map(|(list, constructor)| {
+                let name = &list.var_name;
+                let parent = &list.parent;
+                let pattern = &list.pattern;
+                let collection = &list.collection;
+                quote! {
+                    let mut #name = Vec::new();
+                    {
+                        use #crate_name::base::Layout;
+                        let bind = &data;
+                        for #pattern in #collection {
+                            let mut child = #crate_name::ui::WidgetConstructor::<U, G>::construct(
+                                #constructor,
+                                theme,
+                                u_aux,
+                            );
+                            #parent.push(None, &mut child);
+                            #name.push(child);
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        // Existing item widgets are updated in place, then widgets are added or removed to match the amount of items.
+        let list_bindings: Vec<proc_macro2::TokenStream> = self
+            .lists
+            .iter()
+            .zip(&list_item_constructors)
+            .map(|(list, constructor)| {
+                let name = &list.var_name;
+                let parent = &list.parent;
+                let pattern = &list.pattern;
+                let collection = &list.collection;
+                let updates: Vec<proc_macro2::TokenStream> = list
+                    .data_assignments
+                    .iter()
+                    .map(|assignment| {
+                        let var = &assignment.var;
+                        let value = &assignment.value;
+                        quote! {
+                            child.default_data().#var = #value;
+                        }
+                    })
+                    .collect();
+                quote! {
+                    {
+                        use #crate_name::base::Layout;
+                        let mut index = 0;
+                        for #pattern in #collection {
+                            if let Some(child) = widget.#name.get_mut(index) {
+                                #(#updates)*
+                            } else {
+                                let theme = aux.theme().expect(
+                                    "`for` blocks of `rooftop!` require the update auxiliary to provide its theme",
+                                );
+                                let theme = &*theme;
+                                let mut child = #crate_name::ui::WidgetConstructor::<U, G>::construct(
+                                    #constructor,
+                                    theme,
+                                    aux,
+                                );
+                                widget.#parent.push(None, &mut child);
+                                widget.#name.push(child);
+                            }
+                            index += 1;
+                        }
+                        for mut child in widget.#name.drain(index..) {
+                            widget.#parent.remove(&mut child, false);
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        // Children are listed in reverse order of declaration (see `widgets_as_fields`).
+        let named_nodes: Vec<_> = flattened_nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.named)
+            .map(|(i, node)| (flattened_nodes.len() - 1 - i, node))
+            .collect();
+
+        let named_accessors: Vec<proc_macro2::TokenStream> = named_nodes
+            .iter()
+            .map(|(_, node)| {
+                let name = &node.var_name;
+                let type_name = &node.type_name;
+                let doc = format!("Returns the `{}` widget (declared with `as {}`).", name, name);
+                quote! {
+                    #[doc = #doc]
+                    #[inline]
+                    pub fn #name(&mut self) -> &mut <#type_name as #crate_name::ui::WidgetDataTarget<U, G>>::Target {
+                        &mut self.#name
+                    }
+                }
+            })
+            .collect();
+
+        let named_strings: Vec<String> =
+            named_nodes.iter().map(|(_, node)| node.var_name.to_string()).collect();
+
+        let named_widget_arms: Vec<proc_macro2::TokenStream> = named_nodes
+            .iter()
+            .map(|(_, node)| {
+                let name = &node.var_name;
+                let name_string = name.to_string();
+                quote! {
+                    #name_string => Some(&self.#name as _),
+                }
+            })
+            .collect();
+
+        let named_widget_mut_arms: Vec<proc_macro2::TokenStream> = named_nodes
+            .iter()
+            .map(|(_, node)| {
+                let name = &node.var_name;
+                let name_string = name.to_string();
+                quote! {
+                    #name_string => Some(&mut self.#name as _),
+                }
+            })
+            .collect();
+
+        let named_path_arms: Vec<proc_macro2::TokenStream> = named_nodes
+            .iter()
+            .map(|(index, node)| {
+                let name_string = node.var_name.to_string();
+                quote! {
+                    #name_string => Some(#crate_name::base::WidgetPath::from(vec![#index])),
+                }
+            })
+            .collect();
+
+        // Widgets within `if` blocks are shown or hidden whenever the data changes, along with the other bindings.
+        let mut visibility_bindings: Vec<proc_macro2::TokenStream> = flattened_nodes
+            .iter()
+            .filter_map(|node| {
+                let name = &node.var_name;
+                let condition = node.condition.as_ref()?;
+                Some(quote! {
+                    {
+                        use #crate_name::base::{HasVisibility, Repaintable};
+                        let visibility = if #condition {
+                            #crate_name::base::Visibility::Normal
+                        } else {
+                            #crate_name::base::Visibility::None
+                        };
+                        if widget.#name.visibility() != visibility {
+                            widget.#name.set_visibility(visibility);
+                            widget.#name.repaint();
+                        }
+                    }
+                })
+            })
+            .collect();
+        visibility_bindings.extend(self.lists.iter().filter_map(|list| {
+            let name = &list.var_name;
+            let condition = list.condition.as_ref()?;
+            Some(quote! {
+                {
+                    use #crate_name::base::{HasVisibility, Repaintable};
+                    let visibility = if #condition {
+                        #crate_name::base::Visibility::Normal
+                    } else {
+                        #crate_name::base::Visibility::None
+                    };
+                    for child in &mut widget.#name {
+                        if child.visibility() != visibility {
+                            child.set_visibility(visibility);
+                            child.repaint();
+                        }
+                    }
+                }
+            })
+        }));
+
         let bindings = &self.bindings;
         let terminals = &self.terminals;
 
@@ -389,18 +788,18 @@ impl RooftopData {
 
         {
             quote! {
-                #vis struct #struct_name {
+                #vis struct #struct_name #data_generics #data_where_clause {
                     #(#data_fields)*
                 }
 
-                impl #struct_name {
+                impl #data_generics #struct_name #data_type_generics #data_where_clause {
                     pub fn from_theme(theme: &dyn #crate_name::draw::Theme) -> Self {
                         #struct_name {
                             #(#data_field_init)*
                         }
                     }
 
-                    pub fn construct<U, G>(self, theme: &dyn #crate_name::draw::Theme, u_aux: &mut U) -> #widget_name<U, G>
+                    pub fn construct<U, G>(self, theme: &dyn #crate_name::draw::Theme, u_aux: &mut U) -> #widget_name #widget_type_generics
                     where
                         U: #crate_name::base::UpdateAuxiliary,
                         G: #crate_name::base::GraphicalAuxiliary,
@@ -408,16 +807,19 @@ impl RooftopData {
                         let mut data = #crate_name::base::Observed::new(self);
                         #(#widget_declarations)*
                         #define_layout;
+                        #(#list_declarations)*
 
                         use #crate_name::ui::DefaultEventQueue;
                         let mut graph = #crate_name::reclutch::verbgraph::verbgraph! {
-                            #widget_name<U, G> as widget,
+                            #widget_name #widget_type_generics as widget,
                             U as aux,
                             "bind" => event in &data.on_change => {
                                 change => {
                                     use #crate_name::{ui::DefaultWidgetData, base::WidgetChildren};
                                     let bind = &mut widget.data;
                                     #(#bindings)*
+                                    #(#list_bindings)*
+                                    #(#visibility_bindings)*
                                     for child in &mut widget.children_mut() {
                                         child.require_update(aux, "bind");
                                     }
@@ -446,6 +848,7 @@ impl RooftopData {
                             phantom_g: Default::default(),
 
                             #(#widget_names)*
+                            #(#list_names,)*
                         };
 
                         {
@@ -461,10 +864,11 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #widget_name<U, G>
+                impl #widget_generics #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     #[doc = "Auto-generated function by `rooftop!`, called automatically."]
                     fn widget_setup(&mut self, theme: &dyn #crate_name::draw::Theme, u_aux: &mut U) {
@@ -472,31 +876,82 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::ui::WidgetDataTarget<U, G> for #struct_name
+                impl #widget_generics #widget_name #widget_type_generics
+                where
+                    U: #crate_name::base::UpdateAuxiliary,
+                    G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
+                {
+                    #(#named_accessors)*
+
+                    #[doc = "Names of the widgets declared with `as name`, in order of declaration."]
+                    pub const NAMED_WIDGETS: &'static [&'static str] = &[#(#named_strings),*];
+
+                    #[doc = "Returns a widget declared with `as name` by its name, if there is one."]
+                    pub fn named_widget(
+                        &self,
+                        name: &str,
+                    ) -> Option<&dyn #crate_name::base::WidgetChildren<
+                        UpdateAux = U,
+                        GraphicalAux = G,
+                        DisplayObject = #crate_name::reclutch::display::DisplayCommand,
+                    >> {
+                        match name {
+                            #(#named_widget_arms)*
+                            _ => None,
+                        }
+                    }
+
+                    #[doc = "Returns a widget declared with `as name` by its name mutably, if there is one."]
+                    pub fn named_widget_mut(
+                        &mut self,
+                        name: &str,
+                    ) -> Option<&mut dyn #crate_name::base::WidgetChildren<
+                        UpdateAux = U,
+                        GraphicalAux = G,
+                        DisplayObject = #crate_name::reclutch::display::DisplayCommand,
+                    >> {
+                        match name {
+                            #(#named_widget_mut_arms)*
+                            _ => None,
+                        }
+                    }
+
+                    #[doc = "Returns the path of a widget declared with `as name` relative to this widget (see `base::WidgetPath`), if there is one."]
+                    pub fn named_widget_path(name: &str) -> Option<#crate_name::base::WidgetPath> {
+                        match name {
+                            #(#named_path_arms)*
+                            _ => None,
+                        }
+                    }
+                }
+
+                impl #widget_generics #crate_name::ui::WidgetDataTarget<U, G> for #struct_name #data_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
-                    type Target = #widget_name<U, G>;
+                    type Target = #widget_name #widget_type_generics;
                 }
 
                 #[derive(
-                    WidgetChildren,
+                    #crate_name::WidgetChildren,
                     LayableWidget,
                     DropNotifier,
                     HasVisibility,
                     Repaintable,
                     OperatesVerbGraph,
                 )]
-                #[widget_children_trait(base::WidgetChildren)]
                 #[thunderclap_crate(#crate_name)]
-                #vis struct #widget_name<U, G>
+                #vis struct #widget_name #widget_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     pub event_queue: #crate_name::reclutch::event::RcEventQueue<#output_event>,
-                    pub data: #crate_name::base::Observed<#struct_name>,
+                    pub data: #crate_name::base::Observed<#struct_name #data_type_generics>,
                     graph: #crate_name::reclutch::verbgraph::OptionVerbGraph<Self, U>,
                     parent_position: #crate_name::geom::AbsolutePoint,
 
@@ -510,15 +965,17 @@ impl RooftopData {
                     drop_event: #crate_name::reclutch::event::RcEventQueue<#crate_name::base::DropEvent>,
 
                     #(#widgets_as_fields)*
+                    #(#list_fields)*
 
                     phantom_themed: #crate_name::draw::PhantomThemed,
                     phantom_g: std::marker::PhantomData<G>,
                 }
 
-                impl<U, G> #widget_name<U, G>
+                impl #widget_generics #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     fn on_transform(&mut self) {
                         use #crate_name::{base::{Repaintable}, geom::ContextuallyRectangular};
@@ -527,20 +984,22 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::reclutch::verbgraph::HasVerbGraph for #widget_name<U, G>
+                impl #widget_generics #crate_name::reclutch::verbgraph::HasVerbGraph for #widget_name #widget_type_generics
                 where
                     U: base::UpdateAuxiliary,
                     G: base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     fn verb_graph(&mut self) -> &mut #crate_name::reclutch::verbgraph::OptionVerbGraph<Self, U> {
                         &mut self.graph
                     }
                 }
 
-                impl<U, G> #crate_name::reclutch::widget::Widget for #widget_name<U, G>
+                impl #widget_generics #crate_name::reclutch::widget::Widget for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     type UpdateAux = U;
                     type GraphicalAux = G;
@@ -571,10 +1030,11 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::base::Movable for #widget_name<U, G>
+                impl #widget_generics #crate_name::base::Movable for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     #[inline]
                     fn set_position(&mut self, position: #crate_name::geom::RelativePoint) {
@@ -587,10 +1047,11 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::base::Resizable for #widget_name<U, G>
+                impl #widget_generics #crate_name::base::Resizable for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     #[inline]
                     fn set_size(&mut self, size: #crate_name::reclutch::display::Size) {
@@ -603,10 +1064,11 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::geom::StoresParentPosition for #widget_name<U, G>
+                impl #widget_generics #crate_name::geom::StoresParentPosition for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     fn set_parent_position(&mut self, parent_pos: #crate_name::geom::AbsolutePoint) {
                         self.parent_position = parent_pos;
@@ -619,10 +1081,11 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::draw::HasTheme for #widget_name<U, G>
+                impl #widget_generics #crate_name::draw::HasTheme for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     #[inline]
                     fn theme(&mut self) -> &mut dyn #crate_name::draw::Themed {
@@ -632,10 +1095,11 @@ impl RooftopData {
                     fn resize_from_theme(&mut self) {}
                 }
 
-                impl<U, G> #crate_name::ui::DefaultEventQueue<#output_event> for #widget_name<U, G>
+                impl #widget_generics #crate_name::ui::DefaultEventQueue<#output_event> for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     #[inline]
                     fn default_event_queue(&self) -> &#crate_name::reclutch::event::RcEventQueue<#output_event> {
@@ -643,21 +1107,23 @@ impl RooftopData {
                     }
                 }
 
-                impl<U, G> #crate_name::ui::DefaultWidgetData<#struct_name> for #widget_name<U, G>
+                impl #widget_generics #crate_name::ui::DefaultWidgetData<#struct_name #data_type_generics> for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     #[inline]
-                    fn default_data(&mut self) -> &mut #crate_name::base::Observed<#struct_name> {
+                    fn default_data(&mut self) -> &mut #crate_name::base::Observed<#struct_name #data_type_generics> {
                         &mut self.data
                     }
                 }
 
-                impl<U, G> Drop for #widget_name<U, G>
+                impl #widget_generics Drop for #widget_name #widget_type_generics
                 where
                     U: #crate_name::base::UpdateAuxiliary,
                     G: #crate_name::base::GraphicalAuxiliary,
+                    #(#where_predicates,)*
                 {
                     fn drop(&mut self) {
                         use #crate_name::reclutch::prelude::*;
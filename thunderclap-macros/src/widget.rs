@@ -1,4 +1,4 @@
-use quote::quote;
+use quote::{quote, ToTokens};
 
 #[derive(Debug, Clone, Copy)]
 enum DeclType {
@@ -37,8 +37,7 @@ fn widget_children_decl(ty: DeclType) -> proc_macro2::TokenStream {
     match ty {
         DeclType::Meta => {
             quote! {
-                #[derive(WidgetChildren)]
-                #[widget_children_trait(thunderclap::base::WidgetChildren)]
+                #[derive(thunderclap::WidgetChildren)]
             }
         }
         DeclType::Field | DeclType::Impl | DeclType::InitField | DeclType::InitImpl => {
@@ -89,10 +88,8 @@ fn drop_notifier_decl(
         }
         DeclType::Impl => {
             quote! {
-                impl<U, G, #generic_list> Drop for #name<U, G, #generic_list>
+                impl<#generic_list> Drop for #name<#generic_list>
                 #where_clause
-                    U: thunderclap::base::UpdateAuxiliary,
-                    G: thunderclap::base::GraphicalAuxiliary,
                 {
                     fn drop(&mut self) {
                         use thunderclap::reclutch::prelude::*;
@@ -202,10 +199,8 @@ fn operates_verb_graph_decl(
         }
         DeclType::Impl => {
             quote! {
-                impl<U, G, #generic_list> thunderclap::reclutch::verbgraph::HasVerbGraph for #name<U, G, #generic_list>
+                impl<#generic_list> thunderclap::reclutch::verbgraph::HasVerbGraph for #name<#generic_list>
                 #where_clause
-                    U: thunderclap::base::UpdateAuxiliary,
-                    G: thunderclap::base::GraphicalAuxiliary,
                 {
                     #[inline]
                     fn verb_graph(&mut self) -> &mut thunderclap::reclutch::verbgraph::OptionVerbGraph<Self, U> {
@@ -216,7 +211,7 @@ fn operates_verb_graph_decl(
         }
         DeclType::InitField => {
             quote! {
-                graph: thunderclap::reclutch::verbgraph::OptionVerbGraph<#name<U, G, #generic_list>, U>
+                graph: thunderclap::reclutch::verbgraph::OptionVerbGraph<#name<#generic_list>, U>
             }
         }
         DeclType::InitImpl => {
@@ -242,10 +237,8 @@ fn stores_parent_position_decl(
         }
         DeclType::Impl => {
             quote! {
-                impl<U, G, #generic_list> thunderclap::geom::StoresParentPosition for #name<U, G, #generic_list>
+                impl<#generic_list> thunderclap::geom::StoresParentPosition for #name<#generic_list>
                 #where_clause
-                    U: thunderclap::base::UpdateAuxiliary,
-                    G: thunderclap::base::GraphicalAuxiliary,
                 {
                     fn set_parent_position(&mut self, parent_pos: thunderclap::geom::AbsolutePoint) {
                         use thunderclap::ui::core::CoreWidget;
@@ -285,10 +278,8 @@ fn event_queue_decl(
         }
         DeclType::Impl => {
             quote! {
-                impl<U, G, #generic_list> thunderclap::ui::DefaultEventQueue<#gty> for #name<U, G, #generic_list>
+                impl<#generic_list> thunderclap::ui::DefaultEventQueue<#gty> for #name<#generic_list>
                 #where_clause
-                    U: thunderclap::base::UpdateAuxiliary,
-                    G: thunderclap::base::GraphicalAuxiliary,
                 {
                     #[inline]
                     fn default_event_queue(&self) -> &thunderclap::reclutch::event::RcEventQueue<#gty> {
@@ -322,10 +313,8 @@ fn state_decl(
         }
         DeclType::Impl => {
             quote! {
-                impl<U, G, #generic_list> thunderclap::ui::DefaultWidgetData<#gty> for #name<U, G, #generic_list>
+                impl<#generic_list> thunderclap::ui::DefaultWidgetData<#gty> for #name<#generic_list>
                 #where_clause
-                    U: thunderclap::base::UpdateAuxiliary,
-                    G: thunderclap::base::GraphicalAuxiliary,
                 {
                     #[inline]
                     fn default_data(&mut self) -> &mut thunderclap::base::Observed<#gty> {
@@ -363,10 +352,8 @@ fn painter_decl(
         }
         DeclType::Impl => {
             quote! {
-                impl<U, G, #generic_list> thunderclap::draw::HasTheme for #name<U, G, #generic_list>
+                impl<#generic_list> thunderclap::draw::HasTheme for #name<#generic_list>
                 #where_clause
-                    U: thunderclap::base::UpdateAuxiliary,
-                    G: thunderclap::base::GraphicalAuxiliary,
                 {
                     #[inline]
                     fn theme(&mut self) -> &mut dyn thunderclap::draw::Themed {
@@ -393,24 +380,46 @@ fn painter_decl(
     }
 }
 
+/// Generic parameters of a widget, including the auxiliary types `U` and `G` (see `Generics::new`),
+/// with every bound moved to the where clause.
 #[derive(Debug, Clone)]
 struct Generics {
     params: proc_macro2::TokenStream,
     where_clause: proc_macro2::TokenStream,
+    /// Auxiliary types among `params`.
+    aux: Vec<syn::Ident>,
 }
 
-impl syn::parse::Parse for Generics {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let generics = input.parse::<syn::Generics>()?;
-        let params: Vec<syn::GenericParam> = generics.params.into_iter().collect();
+impl Generics {
+    /// Resolves the generics declared by a widget made of `fields`.
+    ///
+    /// If neither `U` nor `G` is declared, both are implicitly added. Otherwise only those declared are added,
+    /// along with those the widget refers to (`U` by operating a verb graph, or either by naming it in fields).
+    fn new(generics: syn::Generics, fields: &[WidgetField]) -> Self {
+        let declares = |name: &str| generics.type_params().any(|param| param.ident == name);
+        let explicit = declares("U") || declares("G");
+        let has_verb_graph = fields.iter().any(WidgetField::operates_verb_graph);
+        let aux: Vec<syn::Ident> = ["U", "G"]
+            .iter()
+            .filter(|&&name| {
+                !explicit
+                    || declares(name)
+                    || (name == "U" && has_verb_graph)
+                    || fields.iter().any(|field| field.mentions(name))
+                    || mentions(generics.where_clause.to_token_stream(), name)
+            })
+            .map(|name| quote::format_ident!("{}", name))
+            .collect();
+
         let mut where_clause: Vec<proc_macro2::TokenStream> = generics
             .where_clause
             .map(|x| x.predicates.into_iter().map(|x| quote! { #x, }).collect())
             .unwrap_or_default();
+        let mut lifetimes = Vec::new();
         let mut simple_params = Vec::new();
 
         // Move all bounds to the where clause
-        for param in &params {
+        for param in generics.params {
             match param {
                 syn::GenericParam::Type(p) => {
                     let ident = &p.ident;
@@ -421,7 +430,10 @@ impl syn::parse::Parse for Generics {
                         });
                     }
 
-                    simple_params.push(quote! { #ident });
+                    // The auxiliary types are placed ahead of the other type parameters
+                    if ident != "U" && ident != "G" {
+                        simple_params.push(quote! { #ident });
+                    }
                 }
                 syn::GenericParam::Lifetime(p) => {
                     let ident = &p.lifetime;
@@ -432,50 +444,68 @@ impl syn::parse::Parse for Generics {
                         });
                     }
 
-                    simple_params.push(quote! { #ident });
+                    lifetimes.push(quote! { #ident });
                 }
                 syn::GenericParam::Const(p) => simple_params.push(quote! { #p }),
             }
         }
 
-        Ok(Generics {
+        for ident in &aux {
+            where_clause.push(if ident == "U" {
+                quote! { U: thunderclap::base::UpdateAuxiliary, }
+            } else {
+                quote! { G: thunderclap::base::GraphicalAuxiliary, }
+            });
+        }
+
+        Generics {
             params: quote! {
-                #(#simple_params),*
+                #(#lifetimes,)* #(#aux,)* #(#simple_params),*
             },
             where_clause: quote! {
                 where #(#where_clause)*
             },
-        })
+            aux,
+        }
     }
 }
 
+/// Returns `true` if `tokens` contain the identifier `name`.
+fn mentions(tokens: proc_macro2::TokenStream, name: &str) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == name,
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), name),
+        _ => false,
+    })
+}
+
 fn decl_for(
     tr: WidgetTrait,
     ty: DeclType,
-    generics: Option<&Generics>,
+    generics: &Generics,
     name: &syn::Ident,
 ) -> proc_macro2::TokenStream {
-    let generic_list = generics.map(|x| x.params.clone()).unwrap_or(quote! {});
-    let where_clause = generics.map(|x| x.where_clause.clone()).unwrap_or(quote! { where });
+    let generic_list = &generics.params;
+    let where_clause = &generics.where_clause;
 
     match tr {
         WidgetTrait::WidgetChildren => widget_children_decl(ty),
         WidgetTrait::LayableWidget => layable_widget_decl(ty),
-        WidgetTrait::DropNotifier => drop_notifier_decl(ty, &generic_list, &where_clause, name),
+        WidgetTrait::DropNotifier => drop_notifier_decl(ty, generic_list, where_clause, name),
         WidgetTrait::HasVisibility => has_visibility_decl(ty),
         WidgetTrait::Repaintable => repaintable_decl(ty),
         WidgetTrait::Rectangular => rectangular_decl(ty),
         WidgetTrait::OperatesVerbGraph => {
-            operates_verb_graph_decl(ty, &generic_list, &where_clause, name)
+            operates_verb_graph_decl(ty, generic_list, where_clause, name)
         }
         WidgetTrait::StoresParentPosition => {
-            stores_parent_position_decl(ty, &generic_list, &where_clause, name)
+            stores_parent_position_decl(ty, generic_list, where_clause, name)
         }
         WidgetTrait::EventQueue(gty) => {
-            event_queue_decl(*gty, ty, &generic_list, &where_clause, name)
+            event_queue_decl(*gty, ty, generic_list, where_clause, name)
         }
-        WidgetTrait::State(gty) => state_decl(*gty, ty, &generic_list, &where_clause, name),
-        WidgetTrait::Painter(gty) => painter_decl(*gty, ty, &generic_list, &where_clause, name),
+        WidgetTrait::State(gty) => state_decl(*gty, ty, generic_list, where_clause, name),
+        WidgetTrait::Painter(gty) => painter_decl(*gty, ty, generic_list, where_clause, name),
     }
 }
 
@@ -489,24 +519,19 @@ struct WidgetImpl {
 }
 
 impl WidgetImpl {
-    fn new(field: WidgetField, generics: Option<&Generics>, name: &syn::Ident) -> Vec<Self> {
+    fn new(field: WidgetField, generics: &Generics, name: &syn::Ident) -> Vec<Self> {
         match field {
-            WidgetField::WidgetMax => [
-                "WidgetChildren",
-                "LayableWidget",
-                "DropNotifier",
-                "HasVisibility",
-                "Repaintable",
-                "Rectangular",
-                "OperatesVerbGraph",
-                "StoresParentPosition",
-            ]
-            .iter()
-            .map(|x| {
-                WidgetImpl::new(WidgetField::Pseudo(quote::format_ident!("{}", x)), generics, name)
+            WidgetField::WidgetMax => TRAITS
+                .iter()
+                .map(|x| {
+                    WidgetImpl::new(
+                        WidgetField::Pseudo(quote::format_ident!("{}", x)),
+                        generics,
+                        name,
+                    )
                     .remove(0)
-            })
-            .collect(),
+                })
+                .collect(),
             WidgetField::Pseudo(ident) => {
                 let tr = match &ident.to_string()[..] {
                     "WidgetChildren" => WidgetTrait::WidgetChildren,
@@ -517,7 +542,7 @@ impl WidgetImpl {
                     "Rectangular" => WidgetTrait::Rectangular,
                     "OperatesVerbGraph" => WidgetTrait::OperatesVerbGraph,
                     "StoresParentPosition" => WidgetTrait::StoresParentPosition,
-                    _ => panic!("Unknown trait '{}'", ident.to_string()),
+                    _ => unreachable!("unknown traits are rejected while parsing"),
                 };
 
                 vec![WidgetImpl {
@@ -535,7 +560,7 @@ impl WidgetImpl {
                     "EventQueue" => WidgetTrait::EventQueue(Box::new(ty)),
                     "State" => WidgetTrait::State(Box::new(ty)),
                     "Painter" => WidgetTrait::Painter(Box::new(ty)),
-                    _ => panic!("Unknown generic trait '{}'", ident.to_string()),
+                    _ => unreachable!("unknown traits are rejected while parsing"),
                 };
 
                 vec![WidgetImpl {
@@ -558,7 +583,7 @@ impl WidgetImpl {
                         let ty = field.ty;
 
                         quote! {
-                            #(#[#attrs])*
+                            #(#attrs)*
                             #vis #name: #ty
                         }
                     })
@@ -620,6 +645,51 @@ mod kw {
     syn::custom_keyword!(MAX);
 }
 
+const TRAITS: &[&str] = &[
+    "WidgetChildren",
+    "LayableWidget",
+    "DropNotifier",
+    "HasVisibility",
+    "Repaintable",
+    "Rectangular",
+    "OperatesVerbGraph",
+    "StoresParentPosition",
+];
+
+const GENERIC_TRAITS: &[&str] = &["EventQueue", "State", "Painter"];
+
+/// Parses the name of a trait implemented by `widget!`, failing at its span if it isn't one of `known`.
+fn parse_trait_name(input: syn::parse::ParseStream, known: &[&str]) -> syn::Result<syn::Ident> {
+    let ident = input.parse::<syn::Ident>()?;
+    if known.iter().any(|name| ident == name) {
+        Ok(ident)
+    } else {
+        Err(syn::Error::new(
+            ident.span(),
+            format!("unknown trait `{}`, expected one of: {}", ident, known.join(", ")),
+        ))
+    }
+}
+
+impl WidgetField {
+    fn operates_verb_graph(&self) -> bool {
+        match self {
+            WidgetField::WidgetMax => true,
+            WidgetField::Pseudo(ident) => ident == "OperatesVerbGraph",
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the declared fields or the type argument refer to the identifier `name`.
+    fn mentions(&self, name: &str) -> bool {
+        match self {
+            WidgetField::WidgetMax | WidgetField::Pseudo(_) => false,
+            WidgetField::Generic(b) => mentions(b.1.to_token_stream(), name),
+            WidgetField::Fields(fields) => mentions(fields.to_token_stream(), name),
+        }
+    }
+}
+
 impl syn::parse::Parse for WidgetField {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         if input.parse::<kw::widget>().is_ok()
@@ -628,11 +698,11 @@ impl syn::parse::Parse for WidgetField {
         {
             Ok(WidgetField::WidgetMax)
         } else if input.peek(syn::Ident) {
-            Ok(WidgetField::Pseudo(input.parse::<syn::Ident>()?))
+            Ok(WidgetField::Pseudo(parse_trait_name(input, TRAITS)?))
         } else if input.parse::<syn::Token![<]>().is_ok() {
             let ty = input.parse::<syn::Type>()?;
             input.parse::<syn::Token![>]>()?;
-            let name = input.parse::<syn::Ident>()?;
+            let name = parse_trait_name(input, GENERIC_TRAITS)?;
             Ok(WidgetField::Generic(Box::new((name, ty))))
         } else if input.peek(syn::token::Brace) {
             let content;
@@ -649,7 +719,7 @@ impl syn::parse::Parse for WidgetField {
 pub struct WidgetImpls {
     impls: Vec<WidgetImpl>,
     name: syn::Ident,
-    generics: Option<Generics>,
+    generics: Generics,
     vis: Option<syn::Visibility>,
     attrs: Vec<syn::Attribute>,
 }
@@ -657,9 +727,8 @@ pub struct WidgetImpls {
 impl WidgetImpls {
     pub fn compile(mut self) -> proc_macro2::TokenStream {
         let name = self.name;
-        let generic_list = self.generics.clone().map(|x| x.params).unwrap_or_default();
-        let where_clause =
-            self.generics.map(|x| x.where_clause).unwrap_or_else(|| quote! { where });
+        let generic_list = self.generics.params.clone();
+        let where_clause = self.generics.where_clause.clone();
 
         if self.impls.iter().find(|x| x.tr.as_ref().map_or(false, |y| y.is_painter())).is_none() {
             self.impls.push(WidgetImpl {
@@ -669,10 +738,8 @@ impl WidgetImpls {
                     painter: thunderclap::draw::PhantomThemed
                 },
                 impl_decl: quote! {
-                    impl<U, G, #generic_list> thunderclap::draw::HasTheme for #name<U, G, #generic_list>
+                    impl<#generic_list> thunderclap::draw::HasTheme for #name<#generic_list>
                     #where_clause
-                        U: thunderclap::base::UpdateAuxiliary,
-                        G: thunderclap::base::GraphicalAuxiliary,
                     {
                         #[inline]
                         fn theme(&mut self) -> &mut dyn thunderclap::draw::Themed {
@@ -685,7 +752,26 @@ impl WidgetImpls {
                 init_field_decl: Default::default(),
                 init_impl_decl: quote! {
                     painter: Default::default()
-                }
+                },
+            })
+        }
+
+        // Without a verb graph, nothing else necessarily refers to the auxiliary types.
+        let aux = &self.generics.aux;
+        if !aux.is_empty()
+            && !self.impls.iter().any(|x| x.tr == Some(WidgetTrait::OperatesVerbGraph))
+        {
+            self.impls.push(WidgetImpl {
+                tr: None,
+                meta_decl: Default::default(),
+                field_decl: quote! {
+                    phantom_aux: std::marker::PhantomData<fn() -> (#(#aux,)*)>
+                },
+                impl_decl: Default::default(),
+                init_field_decl: Default::default(),
+                init_impl_decl: quote! {
+                    phantom_aux: std::marker::PhantomData
+                },
             })
         }
 
@@ -705,35 +791,30 @@ impl WidgetImpls {
 
         let builder_name = quote::format_ident!("{}Builder", name);
 
+        // Imported anonymously, so that a module can declare several widgets.
         quote! {
-            use thunderclap::ui::core::CoreWidget;
+            use thunderclap::ui::core::CoreWidget as _;
 
-            #vis struct #builder_name<U, G, #generic_list>
+            #vis struct #builder_name<#generic_list>
             #where_clause
-                U: thunderclap::base::UpdateAuxiliary,
-                G: thunderclap::base::GraphicalAuxiliary,
             {
                 #(#init_fields),*
             }
 
-            impl<U, G, #generic_list> #builder_name<U, G, #generic_list>
+            impl<#generic_list> #builder_name<#generic_list>
             #where_clause
-                U: thunderclap::base::UpdateAuxiliary,
-                G: thunderclap::base::GraphicalAuxiliary,
             {
-                pub fn build(self) -> #name<U, G, #generic_list> {
+                pub fn build(self) -> #name<#generic_list> {
                     #name {
                         #(#init_impls),*
                     }
                 }
             }
 
-            #(#attrs)*
             #(#metas)*
-            #vis struct #name<U, G, #generic_list>
+            #(#attrs)*
+            #vis struct #name<#generic_list>
             #where_clause
-                U: thunderclap::base::UpdateAuxiliary,
-                G: thunderclap::base::GraphicalAuxiliary,
             {
                 #(#fields),*
             }
@@ -752,19 +833,23 @@ impl syn::parse::Parse for WidgetImpls {
         input.parse::<syn::Token![struct]>()?;
         let name = input.parse::<syn::Ident>()?;
 
-        let generics =
-            if input.peek(syn::Token![<]) { input.parse::<Generics>().ok() } else { None };
+        let mut generics = input.parse::<syn::Generics>()?;
+        generics.where_clause = input.parse()?;
 
         let struct_content;
         syn::braced!(struct_content in input);
-        let impls = struct_content
+        let fields: Vec<_> = struct_content
             .parse_terminated::<WidgetField, syn::Token![,]>(WidgetField::parse)?
             .into_iter()
-            .map(|field| WidgetImpl::new(field, generics.as_ref(), &name))
-            .fold(Vec::new(), |mut v, x| {
+            .collect();
+        let generics = Generics::new(generics, &fields);
+        let impls = fields.into_iter().map(|field| WidgetImpl::new(field, &generics, &name)).fold(
+            Vec::new(),
+            |mut v, x| {
                 v.extend(x.into_iter());
                 v
-            });
+            },
+        );
 
         Ok(WidgetImpls { impls, name, generics, vis, attrs })
     }
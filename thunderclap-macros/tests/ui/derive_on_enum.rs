@@ -0,0 +1,9 @@
+use thunderclap_macros::LayableWidget;
+
+#[derive(LayableWidget)]
+enum NotAStruct {
+    A,
+    B,
+}
+
+fn main() {}
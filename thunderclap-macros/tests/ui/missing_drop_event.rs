@@ -0,0 +1,8 @@
+use thunderclap_macros::DropNotifier;
+
+#[derive(DropNotifier)]
+struct MissingDropEvent {
+    drop_event: u32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use thunderclap_macros::LayableWidget;
+
+#[derive(LayableWidget)]
+struct MissingLayout {
+    layout: u32,
+}
+
+fn main() {}
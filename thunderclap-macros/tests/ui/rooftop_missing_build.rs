@@ -0,0 +1,7 @@
+thunderclap_macros::rooftop! {
+    struct NoBuild: () {
+        fn other() {}
+    }
+}
+
+fn main() {}
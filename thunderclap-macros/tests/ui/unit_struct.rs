@@ -0,0 +1,6 @@
+use thunderclap_macros::DropNotifier;
+
+#[derive(DropNotifier)]
+struct Unit;
+
+fn main() {}
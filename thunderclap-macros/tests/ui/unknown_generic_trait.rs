@@ -0,0 +1,7 @@
+thunderclap_macros::widget! {
+    pub struct UnknownGenericTraitWidget {
+        <()> Theme,
+    }
+}
+
+fn main() {}
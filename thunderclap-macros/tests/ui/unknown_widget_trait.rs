@@ -0,0 +1,8 @@
+thunderclap_macros::widget! {
+    pub struct UnknownTraitWidget {
+        Rectangular,
+        Clickable,
+    }
+}
+
+fn main() {}
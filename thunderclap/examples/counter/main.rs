@@ -2,7 +2,9 @@ use thunderclap::{
     app, base,
     reclutch::display::Color,
     themes::Primer,
-    ui::{Button, HStack, Label, Margins, ScrollBar, SideMargins, TextArea, VStack},
+    ui::{
+        Button, HStack, Label, Margins, OverflowBehavior, ScrollBar, SideMargins, TextArea, VStack,
+    },
 };
 
 #[macro_use]
@@ -21,7 +23,7 @@ rooftop! {
                 VStack(bottom_margin=5.0) {
                     Label(
                         text=bind(format!("Count: {}", bind.count).into()),
-                        wrap=false,
+                        overflow=OverflowBehavior::Clip,
                     ),
                     HStack(left_margin=5.0) {
                         Button(
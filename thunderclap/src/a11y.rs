@@ -0,0 +1,223 @@
+//! Accessibility tree, exposing the widget tree to assistive technologies (e.g. screen readers).
+//!
+//! Widgets describe themselves by implementing `Accessible` (their role, name, value and state),
+//! and return themselves from `WidgetChildren::accessible` (with the `#[widget_accessible]` attribute of
+//! `derive(WidgetChildren)`). `build_tree` walks the widget tree into a tree of `AccessibleNode`s; widgets
+//! which aren't accessible (e.g. layouts) are transparent, in that their accessible descendants take their place.
+//!
+//! Platform accessibility APIs (UI Automation, AT-SPI, NSAccessibility) are integrated by implementing
+//! `AccessibilityBackend`, which is handed the tree whenever it changes (see `app::App::set_accessibility_backend`,
+//! with the `accessibility` feature enabled).
+
+use {
+    crate::{
+        base::{self, HasVisibility},
+        draw::state::InteractionState,
+        geom::*,
+    },
+    reclutch::display::DisplayText,
+};
+
+/// Kind of user interface element a widget represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// The root of the tree.
+    Window,
+    /// Generic container of other elements.
+    Group,
+    Button,
+    CheckBox,
+    /// Static text.
+    Label,
+    /// Editable text.
+    TextInput,
+    /// Numeric value which can be incremented and decremented.
+    SpinButton,
+    ScrollBar,
+    List,
+    ListItem,
+    Tree,
+    TreeItem,
+    TabList,
+    Tab,
+    Tooltip,
+    ProgressIndicator,
+    Image,
+    /// Element which doesn't fit any other role.
+    Unknown,
+}
+
+bitflags::bitflags! {
+    /// State of an accessible element.
+    pub struct AccessibleState: u32 {
+        const FOCUSED = 1;
+        const HOVERED = 1 << 1;
+        const PRESSED = 1 << 2;
+        const DISABLED = 1 << 3;
+        const CHECKED = 1 << 4;
+        const SELECTED = 1 << 5;
+        const EXPANDED = 1 << 6;
+        const READ_ONLY = 1 << 7;
+    }
+}
+
+impl AccessibleState {
+    /// Returns the state corresponding to the interaction state of a widget (hovered, pressed and focused).
+    pub fn from_interaction(interaction: InteractionState) -> Self {
+        let mut state = AccessibleState::empty();
+        state.set(AccessibleState::HOVERED, interaction.contains(InteractionState::HOVERED));
+        state.set(AccessibleState::PRESSED, interaction.contains(InteractionState::PRESSED));
+        state.set(AccessibleState::FOCUSED, interaction.contains(InteractionState::FOCUSED));
+        state
+    }
+}
+
+impl Default for AccessibleState {
+    fn default() -> Self {
+        AccessibleState::empty()
+    }
+}
+
+/// Implemented by widgets which are exposed to assistive technologies.
+pub trait Accessible {
+    /// Returns the kind of element the widget represents.
+    fn role(&self) -> Role;
+    /// Returns the label of the element (e.g. the text of a button), which may be empty.
+    fn name(&self) -> String;
+    /// Returns the value of the element (e.g. the text of a text input), if it has one.
+    #[inline]
+    fn value(&self) -> Option<String> {
+        None
+    }
+    /// Returns the current state of the element.
+    #[inline]
+    fn state(&self) -> AccessibleState {
+        AccessibleState::empty()
+    }
+}
+
+/// Element of the accessibility tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibleNode {
+    pub role: Role,
+    pub name: String,
+    pub value: Option<String>,
+    /// Bounds of the widget, in logical window coordinates.
+    pub bounds: AbsoluteRect,
+    pub state: AccessibleState,
+    pub children: Vec<AccessibleNode>,
+}
+
+impl AccessibleNode {
+    /// Returns the node at `path`, where each element is the index of a child within its parent.
+    pub fn descendant(&self, path: &[usize]) -> Option<&AccessibleNode> {
+        path.iter().try_fold(self, |node, &index| node.children.get(index))
+    }
+
+    /// Returns the path of the first focused node, if any, in the format of `descendant`.
+    pub fn focused_path(&self) -> Option<Vec<usize>> {
+        if self.state.contains(AccessibleState::FOCUSED) {
+            return Some(Vec::new());
+        }
+
+        self.children.iter().enumerate().find_map(|(index, child)| {
+            child.focused_path().map(|mut path| {
+                path.insert(0, index);
+                path
+            })
+        })
+    }
+}
+
+/// Returns the accessible name of display text; shaped text can't be converted back to a string and has no name.
+pub fn text_name(text: &DisplayText) -> String {
+    match text {
+        DisplayText::Simple(text) => text.clone(),
+        DisplayText::Shaped(_) => String::new(),
+    }
+}
+
+/// Builds the accessibility tree of a widget tree, rooted at a `Role::Window` node named `name`.
+///
+/// Hidden widgets (i.e. `Visibility::Invisible` or `Visibility::None`) are omitted along with their descendants.
+pub fn build_tree<U, G, D>(
+    name: &str,
+    root: &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = D>,
+) -> AccessibleNode {
+    let mut children = Vec::new();
+    collect(root, &mut children);
+
+    AccessibleNode {
+        role: Role::Window,
+        name: name.to_string(),
+        value: None,
+        bounds: root.abs_bounds(),
+        state: AccessibleState::empty(),
+        children,
+    }
+}
+
+fn collect<U, G, D>(
+    widget: &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = D>,
+    nodes: &mut Vec<AccessibleNode>,
+) {
+    match widget.visibility() {
+        base::Visibility::Invisible | base::Visibility::None => return,
+        _ => {}
+    }
+
+    let mut children = Vec::new();
+    for child in widget.children() {
+        collect(child, &mut children);
+    }
+
+    match widget.accessible() {
+        Some(accessible) => nodes.push(AccessibleNode {
+            role: accessible.role(),
+            name: accessible.name(),
+            value: accessible.value(),
+            bounds: widget.abs_bounds(),
+            state: accessible.state(),
+            children,
+        }),
+        None => nodes.extend(children),
+    }
+}
+
+/// Exposes the accessibility tree to a platform accessibility API.
+pub trait AccessibilityBackend {
+    /// Invoked with the new tree whenever it changes.
+    fn update(&mut self, tree: &AccessibleNode);
+}
+
+/// Rebuilds the accessibility tree and hands it to a backend, but only when it has changed since the last update.
+pub struct AccessibilityBridge {
+    backend: Box<dyn AccessibilityBackend>,
+    tree: Option<AccessibleNode>,
+}
+
+impl AccessibilityBridge {
+    /// Creates a bridge to `backend`, which receives the tree on the first update.
+    pub fn new(backend: Box<dyn AccessibilityBackend>) -> Self {
+        AccessibilityBridge { backend, tree: None }
+    }
+
+    /// Returns the tree most recently handed to the backend, if any.
+    #[inline]
+    pub fn tree(&self) -> Option<&AccessibleNode> {
+        self.tree.as_ref()
+    }
+
+    /// Rebuilds the tree of `root`, handing it to the backend if it has changed.
+    pub fn update<U, G, D>(
+        &mut self,
+        name: &str,
+        root: &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = D>,
+    ) {
+        let tree = build_tree(name, root);
+        if self.tree.as_ref() != Some(&tree) {
+            self.backend.update(&tree);
+            self.tree = Some(tree);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Animation of widget properties over time.
+//!
+//! The `Animator` (accessed through `UpdateAuxiliary::animator_mut`) runs tweens, each of which progresses from 0 to 1
+//! over a duration, eased by a curve, and invokes a callback on every tick with the eased progress.
+//! The `app` module ticks the animator once per frame, before widgets are updated, and keeps presenting frames
+//! while any tween is running.
+//!
+//! Widgets usually don't need to register tweens directly; `Animated` holds a value which is interpolated towards a target:
+//! ```ignore
+//! // In construction:
+//! let opacity = Animated::new(0.0, Duration::from_millis(150), Easing::EaseOut);
+//!
+//! // In `update`:
+//! self.opacity.animate_to(1.0, aux.animator_mut());
+//! if self.opacity.take_changed() {
+//!     self.repaint();
+//! }
+//! ```
+//!
+//! With reduced motion enabled (`Animator::set_reduced_motion`), `Animated` values jump straight to their targets.
+
+use {
+    crate::draw,
+    reclutch::display::{Color, Point, Size, Vector},
+    std::{
+        cell::Cell,
+        rc::Rc,
+        time::{Duration, Instant},
+    },
+};
+
+/// Curve mapping the linear progress of a tween (from 0 to 1) to the eased progress.
+#[derive(Debug, Clone, Copy)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slow and accelerates (quadratic).
+    EaseIn,
+    /// Starts fast and decelerates (quadratic).
+    EaseOut,
+    /// Accelerates, then decelerates (cubic).
+    EaseInOut,
+    /// CSS-style cubic Bézier curve, given by the control points `(x1, y1)` and `(x2, y2)`.
+    /// The `x` coordinates should be within 0 to 1.
+    CubicBezier(f32, f32, f32, f32),
+    /// Custom easing function.
+    Custom(fn(f32) -> f32),
+}
+
+impl Easing {
+    /// Returns the eased progress for a linear progress `t` (from 0 to 1).
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let t = 2.0 * t - 2.0;
+                    0.5 * t * t * t + 1.0
+                }
+            }
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Custom(f) => f(t),
+        }
+    }
+}
+
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let bezier = |p1: f32, p2: f32, s: f32| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+    };
+
+    // The x coordinate is monotonic in `s` for control points within 0 to 1, so bisect for `s`.
+    let (mut lo, mut hi) = (0.0, 1.0);
+    let mut s = x;
+    for _ in 0..32 {
+        let bx = bezier(x1, x2, s);
+        if (bx - x).abs() < 1e-5 {
+            break;
+        } else if bx < x {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+
+    bezier(y1, y2, s)
+}
+
+/// Values which can be linearly interpolated.
+pub trait Lerp: Copy {
+    /// Interpolates between `self` (where `t` is 0) and `to` (where `t` is 1).
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    #[inline]
+    fn lerp(self, to: f32, t: f32) -> f32 {
+        self + (to - self) * t
+    }
+}
+
+/// Colors are interpolated in linear light, so that transitions don't dip through darker colors.
+impl Lerp for Color {
+    #[inline]
+    fn lerp(self, to: Color, t: f32) -> Color {
+        draw::ColorSpace::LinearSrgb.mix(self, to, t)
+    }
+}
+
+impl Lerp for Point {
+    #[inline]
+    fn lerp(self, to: Point, t: f32) -> Point {
+        Point::new(self.x.lerp(to.x, t), self.y.lerp(to.y, t))
+    }
+}
+
+impl Lerp for Vector {
+    #[inline]
+    fn lerp(self, to: Vector, t: f32) -> Vector {
+        Vector::new(self.x.lerp(to.x, t), self.y.lerp(to.y, t))
+    }
+}
+
+impl Lerp for Size {
+    #[inline]
+    fn lerp(self, to: Size, t: f32) -> Size {
+        Size::new(self.width.lerp(to.width, t), self.height.lerp(to.height, t))
+    }
+}
+
+/// Identifies a tween started by an `Animator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenId(u64);
+
+/// Progression over a duration, which invokes a callback on every tick.
+pub struct Tween {
+    duration: Duration,
+    delay: Duration,
+    easing: Easing,
+    on_tick: Box<dyn FnMut(f32)>,
+    on_finish: Option<Box<dyn FnOnce()>>,
+}
+
+impl Tween {
+    /// Creates a tween which invokes `on_tick` with the eased progress (from 0 to 1) on every tick.
+    /// The final tick happens once the whole duration has elapsed.
+    pub fn new(duration: Duration, easing: Easing, on_tick: impl FnMut(f32) + 'static) -> Self {
+        Tween {
+            duration,
+            delay: Duration::from_secs(0),
+            easing,
+            on_tick: Box::new(on_tick),
+            on_finish: None,
+        }
+    }
+
+    /// Sets the time waited before the tween starts.
+    pub fn delay(self, delay: Duration) -> Self {
+        Tween { delay, ..self }
+    }
+
+    /// Sets a callback invoked once the tween has finished (but not if it is cancelled).
+    pub fn on_finish(self, on_finish: impl FnOnce() + 'static) -> Self {
+        Tween { on_finish: Some(Box::new(on_finish)), ..self }
+    }
+}
+
+struct RunningTween {
+    id: TweenId,
+    start: Instant,
+    tween: Tween,
+}
+
+/// Runs tweens, driven by `tick`.
+#[derive(Default)]
+pub struct Animator {
+    tweens: Vec<RunningTween>,
+    next_id: u64,
+    reduced_motion: bool,
+}
+
+impl Animator {
+    /// Creates an animator without any tweens.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Starts a tween, which progresses from the moment it is started (plus its delay).
+    pub fn start(&mut self, tween: Tween) -> TweenId {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+        self.tweens.push(RunningTween { id, start: Instant::now() + tween.delay, tween });
+        id
+    }
+
+    /// Stops a tween without invoking any further callbacks.
+    /// Returns `false` if the tween had already finished.
+    pub fn cancel(&mut self, id: TweenId) -> bool {
+        let count = self.tweens.len();
+        self.tweens.retain(|running| running.id != id);
+        self.tweens.len() != count
+    }
+
+    /// Returns `true` if a tween is still running (or waiting on its delay).
+    pub fn is_running(&self, id: TweenId) -> bool {
+        self.tweens.iter().any(|running| running.id == id)
+    }
+
+    /// Sets whether the user prefers reduced motion, in which case `Animated` values change immediately instead of animating.
+    ///
+    /// Tweens started directly through `start` are unaffected.
+    pub fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        self.reduced_motion = reduced_motion;
+    }
+
+    /// Returns `true` if the user prefers reduced motion.
+    #[inline]
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    /// Returns `true` if any tween is running, in which case frames should keep being presented.
+    #[inline]
+    pub fn is_animating(&self) -> bool {
+        !self.tweens.is_empty()
+    }
+
+    /// Advances all the tweens to `now`, removing those which have finished.
+    pub fn tick(&mut self, now: Instant) {
+        let mut finished = Vec::new();
+        for running in &mut self.tweens {
+            if now < running.start {
+                continue;
+            }
+
+            let elapsed = (now - running.start).as_secs_f32();
+            let duration = running.tween.duration.as_secs_f32();
+            let t = if duration > 0.0 { (elapsed / duration).min(1.0) } else { 1.0 };
+            (running.tween.on_tick)(running.tween.easing.apply(t));
+
+            if t >= 1.0 {
+                finished.push(running.id);
+            }
+        }
+
+        for id in finished {
+            if let Some(idx) = self.tweens.iter().position(|running| running.id == id) {
+                if let Some(on_finish) = self.tweens.remove(idx).tween.on_finish {
+                    on_finish();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AnimatedValue<T: Copy> {
+    value: Cell<T>,
+    changed: Cell<bool>,
+}
+
+/// Value which is interpolated towards a target by tweens.
+#[derive(Debug)]
+pub struct Animated<T: Lerp + 'static> {
+    state: Rc<AnimatedValue<T>>,
+    target: T,
+    tween: Option<TweenId>,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl<T: Lerp + PartialEq + 'static> Animated<T> {
+    /// Creates a value which is animated over `duration` whenever its target changes.
+    pub fn new(value: T, duration: Duration, easing: Easing) -> Self {
+        Animated {
+            state: Rc::new(AnimatedValue { value: Cell::new(value), changed: Cell::new(false) }),
+            target: value,
+            tween: None,
+            duration,
+            easing,
+        }
+    }
+
+    /// Returns the current (interpolated) value.
+    #[inline]
+    pub fn get(&self) -> T {
+        self.state.value.get()
+    }
+
+    /// Returns the value being animated towards.
+    #[inline]
+    pub fn target(&self) -> T {
+        self.target
+    }
+
+    /// Animates from the current value to `target`, replacing any previous animation.
+    /// This does nothing if `target` is already the target, and changes the value immediately with reduced motion.
+    pub fn animate_to(&mut self, target: T, animator: &mut Animator) {
+        if target == self.target {
+            return;
+        } else if animator.reduced_motion() {
+            self.set(target, animator);
+            return;
+        }
+
+        if let Some(tween) = self.tween.take() {
+            animator.cancel(tween);
+        }
+
+        self.target = target;
+        let from = self.get();
+        let state = self.state.clone();
+        self.tween = Some(animator.start(Tween::new(self.duration, self.easing, move |t| {
+            state.value.set(from.lerp(target, t));
+            state.changed.set(true);
+        })));
+    }
+
+    /// Changes the value immediately, cancelling any animation.
+    pub fn set(&mut self, value: T, animator: &mut Animator) {
+        if let Some(tween) = self.tween.take() {
+            animator.cancel(tween);
+        }
+
+        self.target = value;
+        self.state.value.set(value);
+        self.state.changed.set(true);
+    }
+
+    /// Returns `true` if the value is being animated.
+    pub fn is_animating(&self, animator: &Animator) -> bool {
+        self.tween.map(|tween| animator.is_running(tween)).unwrap_or(false)
+    }
+
+    /// Returns `true` if the value has changed since this was last invoked (i.e. the widget should repaint).
+    #[inline]
+    pub fn take_changed(&self) -> bool {
+        self.state.changed.replace(false)
+    }
+}
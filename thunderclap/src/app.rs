@@ -1,77 +1,296 @@
 use {
-    crate::{base, draw, error::AppError, geom::*},
-    glutin::{
-        event::{self, DeviceEvent, Event, WindowEvent},
-        event_loop::{ControlFlow, EventLoop},
-        window::WindowBuilder,
-        ContextBuilder, PossiblyCurrent, WindowedContext,
-    },
+    crate::{anim, base, bridge, clipboard, draw, error::AppError, geom::*, profiling, telemetry},
+    glutin::event::{self, DeviceEvent, Event, WindowEvent},
     reclutch::{
         display::{
-            self, skia, Color, CommandGroup, DisplayCommand, GraphicsDisplay, Point, Size, Vector,
+            self, Color, CommandGroup, DisplayCommand, DisplayListBuilder, GraphicsDisplay, Point,
+            Size, TextDisplayItem, Vector,
         },
         event::RcEventQueue,
         prelude::*,
     },
+    std::{
+        ops::RangeInclusive,
+        rc::Rc,
+        time::{Duration, Instant},
+    },
 };
 
+pub mod backend;
+#[cfg(feature = "global-hotkeys")]
+pub mod global_hotkeys;
+#[cfg(feature = "notifications")]
+pub mod notifications;
+#[cfg(feature = "tray")]
+pub mod tray;
+
+pub use backend::{BackendEvent, ControlFlow, GlutinBackend, WindowingBackend};
+#[cfg(feature = "notifications")]
+pub use notifications::notify;
+
+/// Distance (in logical pixels) scrolled per line by a mouse wheel which scrolls in lines.
+const SCROLL_LINE_DISTANCE: f32 = 20.0;
+/// Interval at which external event sources are pumped while the event loop is otherwise idle.
+const SOURCE_PUMP_INTERVAL: Duration = Duration::from_millis(16);
+/// Offset between the z-orders of consecutive layers (see `App::push_layer`),
+/// leaving room for the z-orders of the regular widgets of each layer.
+const LAYER_Z_ORDER_STEP: i32 = 1 << 20;
+
 /// Creates an application with a given theme and root widget.
-/// The application uses the Skia OpenGL graphics backend.
+/// The application uses a glutin window and the Skia OpenGL graphics backend (see `GlutinBackend`).
 /// Small details of app creation can be controlled with `AppOptions`.
 pub fn create<R, T, TF, RF>(theme: TF, root: RF, opts: AppOptions) -> Result<App<R>, AppError>
 where
     R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
-    T: draw::Theme,
+    T: draw::Theme + 'static,
     TF: FnOnce(&mut GAux, &mut dyn GraphicsDisplay) -> T,
     RF: FnOnce(&mut UAux, &T) -> R,
 {
-    let event_loop = EventLoop::new();
-
-    let hidpi_factor = event_loop.primary_monitor().scale_factor();
-
-    let wb = WindowBuilder::new().with_title(opts.name).with_inner_size(
-        glutin::dpi::PhysicalSize::new(
-            opts.window_size.width as f64,
-            opts.window_size.width as f64,
-        )
-        .to_logical::<f64>(hidpi_factor),
-    );
-
-    let context = ContextBuilder::new().with_vsync(true).build_windowed(wb, &event_loop).unwrap();
-
-    let context = unsafe { context.make_current().unwrap() };
+    let backend = GlutinBackend::new(&opts);
+    create_with_backend(backend, theme, root, opts)
+}
 
-    let mut display =
-        skia::SkiaGraphicsDisplay::new_gl_framebuffer(&skia::SkiaOpenGlFramebuffer {
-            framebuffer_id: 0,
-            size: (opts.window_size.width as _, opts.window_size.height as _),
-        })?;
+/// Creates an application driven by a windowing backend, whose window is shown once the application has warmed up.
+///
+/// Options which concern the window itself (e.g. the window size or `vsync`) are up to `backend`.
+pub fn create_with_backend<R, B, T, TF, RF>(
+    mut backend: B,
+    theme: TF,
+    root: RF,
+    opts: AppOptions,
+) -> Result<App<R, B>, AppError>
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+    B: WindowingBackend,
+    T: draw::Theme + 'static,
+    TF: FnOnce(&mut GAux, &mut dyn GraphicsDisplay) -> T,
+    RF: FnOnce(&mut UAux, &T) -> R,
+{
+    let mut display = backend.create_display()?;
 
-    let g_aux = GAux { scale: hidpi_factor as _ };
-    let mut u_aux = UAux { window_queue: RcEventQueue::new(), cursor: Default::default(), g_aux };
+    // The window may have opened on another monitor than the primary one.
+    let g_aux = GAux {
+        scale: backend.scale_factor(),
+        #[cfg(feature = "images")]
+        image_resources: Some(Default::default()),
+    };
+    let mut u_aux = UAux::new(g_aux);
 
     let theme = theme(&mut u_aux.g_aux, &mut display);
     let root = root(&mut u_aux, &theme);
+    let theme: Rc<dyn draw::Theme> = Rc::new(theme);
+    u_aux.theme = Some(theme.clone());
+
+    #[cfg(feature = "crash-reports")]
+    {
+        if let Some(config) = opts.crash_reports {
+            crate::crash::install(config);
+        }
+        crate::crash::capture_theme(theme.as_ref());
+    }
 
     let mut app = App {
         root,
+        layers: Vec::new(),
         background: opts.background,
         u_aux,
+        size: backend.size(),
         display,
-        context,
-        size: opts.window_size,
-        event_loop,
+        backend,
 
         command_group_pre: CommandGroup::new(),
         command_group_post: CommandGroup::new(),
+        pacer: FramePacer::new(opts.render_mode, opts.target_fps, opts.low_power),
+        kiosk: opts.kiosk,
+        idle_timeout: opts.idle_timeout,
+        close_guard: None,
+        exit_hooks: Vec::new(),
+        #[cfg(feature = "accessibility")]
+        name: opts.name,
+        #[cfg(feature = "accessibility")]
+        accessibility: None,
     };
 
-    for _ in 0..opts.warmup {
+    // Give layouts bound to the window their initial size.
+    let initial_size = app.size / app.u_aux.g_aux.scale;
+    app.u_aux.window_queue.emit_owned(base::WindowEvent::Resized(initial_size));
+
+    warm_up(&mut app, theme.as_ref(), &opts.warmup);
+    app.backend.set_visible(true);
+
+    Ok(app)
+}
+
+/// Runs the warm-up phase described by `WarmupOptions` offscreen.
+///
+/// The whole window is left damaged, so that the first visible frame presents everything.
+fn warm_up<R, B>(app: &mut App<R, B>, theme: &dyn draw::Theme, opts: &WarmupOptions)
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+    B: WindowingBackend,
+{
+    let scale = app.u_aux.g_aux.scale;
+    let typography = &theme.data().typography;
+    let roles = [&typography.header, &typography.sub_header, &typography.body, &typography.button];
+    let glyphs: String = opts.glyphs.iter().cloned().flatten().collect();
+
+    let mut builder = DisplayListBuilder::new();
+    let mut push_text =
+        |text: &str, font: (display::ResourceReference, display::FontInfo), size| {
+            let mut item = TextDisplayItem {
+                text: text.to_string().into(),
+                font: font.0,
+                font_info: font.1,
+                size,
+                bottom_left: Default::default(),
+                color: Color::new(0.0, 0.0, 0.0, 1.0).into(),
+            };
+            item.set_top_left(Point::default());
+            builder.push_text(item, None);
+        };
+    for role in &roles {
+        if opts.theme_fonts {
+            for style in &[
+                draw::TextStyle::Regular,
+                draw::TextStyle::RegularItalic,
+                draw::TextStyle::Bold,
+                draw::TextStyle::BoldItalic,
+            ] {
+                push_text("Aa", role.typeface.pick(*style), role.size);
+            }
+        }
+        if !glyphs.is_empty() {
+            push_text(&glyphs, role.typeface.pick(role.style), role.size);
+        }
+    }
+
+    let text = builder.build();
+    if !text.is_empty() {
+        // Drawn at the scale of the window, so that the glyphs are rasterized at the size they're displayed at.
+        let mut commands =
+            vec![DisplayCommand::Save, DisplayCommand::Scale(Vector::new(scale, scale))];
+        commands.extend(text);
+        commands.push(DisplayCommand::Restore);
+        if let Ok(handle) =
+            app.display.push_command_group(&commands, display::ZOrder::default(), None, None)
+        {
+            let _ = app.display.present(None);
+            app.display.remove_command_group(handle);
+        }
+    }
+
+    for _ in 0..opts.frames {
+        app.u_aux.focus_tracker.update();
         app.root.update(&mut app.u_aux);
-        app.root.draw(&mut app.display, &mut app.u_aux.g_aux);
+        base::set_root_viewport(Some(AbsoluteRect::new(
+            Default::default(),
+            (app.size / scale).cast_unit(),
+        )));
+        base::invoke_draw(&mut app.root, &mut app.display, &mut app.u_aux.g_aux);
+    }
+    if opts.frames > 0 {
+        let _ = app.display.present(None);
     }
 
-    Ok(app)
+    base::damage_all();
+}
+
+/// Runs the shutdown sequence described in `App::on_exit`.
+fn shutdown<R>(
+    root: &mut R,
+    layers: &mut [RootLayer],
+    u_aux: &mut UAux,
+    display: &mut dyn GraphicsDisplay,
+    exit_hooks: impl Iterator<Item = Box<dyn FnOnce(&mut UAux)>>,
+) where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+{
+    for source in &mut u_aux.sources {
+        source.pump();
+    }
+    u_aux.window_queue.emit_owned(base::WindowEvent::Shutdown);
+    u_aux.focus_tracker.update();
+    update_layers(layers, u_aux);
+    root.update(u_aux);
+
+    for hook in exit_hooks {
+        hook(u_aux);
+    }
+
+    u_aux.sources.clear();
+    display.before_exit();
+}
+
+/// Updates the layers from the top down, skipping those whose root doesn't receive updates (as `base::invoke_update` does).
+fn update_layers(layers: &mut [RootLayer], u_aux: &mut UAux) {
+    for layer in layers.iter_mut().rev() {
+        match layer.root.visibility() {
+            base::Visibility::Static | base::Visibility::None => {}
+            _ => layer.root.update(u_aux),
+        }
+    }
+}
+
+/// Resolves the widget which receives the mouse events at `point`, within the root widget and the layers above it.
+fn pointer_target<R>(root: &R, layers: &[RootLayer], point: AbsolutePoint) -> base::PointerTarget
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+{
+    if layers.is_empty() {
+        return base::PointerTarget::new(root, point);
+    }
+
+    let mut trees: Vec<(
+        &dyn base::WidgetChildren<
+            UpdateAux = UAux,
+            GraphicalAux = GAux,
+            DisplayObject = DisplayCommand,
+        >,
+        _,
+    )> = vec![(root, base::LayerInput::PassThrough)];
+    trees.extend(layers.iter().map(|layer| {
+        // Hidden layers stay in the stack, so that tree indices match layer indices.
+        let input = match layer.root.visibility() {
+            base::Visibility::Invisible | base::Visibility::None => base::LayerInput::Ignore,
+            _ => layer.input,
+        };
+        (&*layer.root as _, input)
+    }));
+    base::PointerTarget::new_stacked(&trees, point)
+}
+
+/// Reloads the theme of the widget tree and the layers above it, then re-runs the layouts bound to the window
+/// by re-emitting its size.
+fn switch_theme<R>(
+    root: &mut R,
+    layers: &mut [RootLayer],
+    theme: &dyn draw::Theme,
+    u_aux: &mut UAux,
+    size: Size,
+) where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+{
+    base::reload_theme(root, theme, &u_aux.g_aux);
+    for layer in layers {
+        base::reload_theme(&mut *layer.root, theme, &u_aux.g_aux);
+    }
+    u_aux.window_queue.emit_owned(base::WindowEvent::Resized(size / u_aux.g_aux.scale));
+}
+
+/// Applies a new scale factor to the widget tree and the layers above it, re-measuring them from the theme,
+/// then notifies widgets of the change and re-runs the layouts bound to the window.
+fn change_scale<R>(root: &mut R, layers: &mut [RootLayer], u_aux: &mut UAux, scale: f32, size: Size)
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+{
+    u_aux.g_aux.scale = scale;
+    base::rescale(root);
+    for layer in layers {
+        base::rescale(&mut *layer.root);
+    }
+    base::damage_all();
+    u_aux.window_queue.emit_owned(base::WindowEvent::DpiChanged(scale));
+    u_aux.window_queue.emit_owned(base::WindowEvent::Resized(size / scale));
 }
 
 fn convert_modifiers(modifiers: event::ModifiersState) -> base::KeyModifiers {
@@ -83,86 +302,539 @@ fn convert_modifiers(modifiers: event::ModifiersState) -> base::KeyModifiers {
     }
 }
 
+fn convert_mouse_button(button: event::MouseButton) -> base::MouseButton {
+    match button {
+        event::MouseButton::Left => base::MouseButton::Left,
+        event::MouseButton::Middle => base::MouseButton::Middle,
+        event::MouseButton::Right => base::MouseButton::Right,
+        _ => base::MouseButton::Left,
+    }
+}
+
+fn convert_scroll_delta(delta: event::MouseScrollDelta) -> Vector {
+    match delta {
+        event::MouseScrollDelta::LineDelta(x, y) => {
+            Vector::new(x * SCROLL_LINE_DISTANCE, y * SCROLL_LINE_DISTANCE)
+        }
+        event::MouseScrollDelta::PixelDelta(delta) => Vector::new(delta.x as _, delta.y as _),
+    }
+}
+
 /// Settings on how an app should be created.
 #[derive(Debug, Clone)]
 pub struct AppOptions {
     /// The name of the application; usually translates to the window title.
     pub name: String,
-    /// The number warmup cycles (i.e. the amount of times `update` and `draw` should be called offscreen).
-    pub warmup: u32,
+    /// Work done offscreen before the window is shown.
+    pub warmup: WarmupOptions,
     /// The background color of the window.
     pub background: Color,
     /// Initial size of the app window.
     pub window_size: Size,
+    /// When frames are drawn (see `RenderMode`).
+    pub render_mode: RenderMode,
+    /// Maximum amount of frames presented per second, or `None` for no limit.
+    /// Doesn't apply to `RenderMode::FixedRate`, which has its own rate.
+    pub target_fps: Option<u32>,
+    /// Whether presentation is synchronized with the display refresh rate.
+    pub vsync: bool,
+    /// Whether the frame rate is halved while the window is unfocused.
+    /// If the frame rate isn't limited, the unfocused frame rate is capped at 30 FPS.
+    pub low_power: bool,
+    /// Kiosk mode settings, or `None` for a regular window.
+    pub kiosk: Option<KioskOptions>,
+    /// Time without user input after which `WindowEvent::Idle` is emitted (followed by `WindowEvent::Active`
+    /// once the user interacts again), or `None` to disable idle detection.
+    pub idle_timeout: Option<Duration>,
+    /// Where to write a crash report bundle if the app panics (see `crash`), or `None` to not write any.
+    #[cfg(feature = "crash-reports")]
+    pub crash_reports: Option<crate::crash::CrashReportConfig>,
 }
 
 impl Default for AppOptions {
     fn default() -> Self {
         AppOptions {
             name: "Thunderclap App".into(),
-            warmup: 2,
+            warmup: Default::default(),
             background: Color::new(1.0, 1.0, 1.0, 1.0),
             window_size: Size::new(500.0, 500.0),
+            render_mode: RenderMode::Continuous,
+            target_fps: None,
+            vsync: true,
+            low_power: false,
+            kiosk: None,
+            idle_timeout: None,
+            #[cfg(feature = "crash-reports")]
+            crash_reports: None,
         }
     }
 }
 
-/// Thunderclap/Reclutch based application.
-pub struct App<R>
+/// When the application draws frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// A frame is drawn on every iteration of the event loop, which never waits for events. This is the default.
+    Continuous,
+    /// A frame is only drawn when something has changed (see `base::is_damaged`) or an animation is running.
+    /// Otherwise the event loop waits for events (or the next timer), so an idle application doesn't use any CPU or GPU time.
+    ///
+    /// Opt-in, since widgets drawing anything which changes without damaging it (see `base::report_damage`)
+    /// would go stale.
+    OnDemand,
+    /// Frames are drawn at a fixed amount of frames per second, whether something has changed or not.
+    /// The event loop waits for events in between frames.
+    FixedRate(u32),
+}
+
+impl Default for RenderMode {
+    fn default() -> Self {
+        RenderMode::Continuous
+    }
+}
+
+/// Work done offscreen before the window is shown, so that the first visible frames don't flash or stall
+/// while fonts are loaded, text is shaped and the widget tree is recorded.
+#[derive(Debug, Clone)]
+pub struct WarmupOptions {
+    /// Whether every font of the theme's typography (each typeface in every style) is loaded by the display
+    /// backend ahead of time, instead of when text is first drawn in it.
+    pub theme_fonts: bool,
+    /// Characters which are shaped and rasterized ahead of time, in the typeface, style and size of every role
+    /// of the theme's typography (e.g. `body`).
+    pub glyphs: Vec<RangeInclusive<char>>,
+    /// Amount of update and draw cycles run offscreen, recording the command groups of the initial widget tree
+    /// (and settling layouts which take more than one update).
+    pub frames: u32,
+}
+
+impl WarmupOptions {
+    /// Skips the warm-up entirely; the window is shown as soon as it's created.
+    pub fn none() -> Self {
+        WarmupOptions { theme_fonts: false, glyphs: Vec::new(), frames: 0 }
+    }
+}
+
+impl Default for WarmupOptions {
+    /// Loads the theme fonts, pre-shapes printable ASCII and runs two cycles.
+    fn default() -> Self {
+        WarmupOptions { theme_fonts: true, glyphs: vec![' '..='~'], frames: 2 }
+    }
+}
+
+/// Settings for kiosk deployments, where the app is the only thing the user should be able to interact with.
+///
+/// In kiosk mode the window is fullscreen, borderless and always on top.
+/// The window can't be closed by the user unless a close guard (see `App::set_close_guard`) allows it,
+/// e.g. once an administrator has entered a passcode.
+#[derive(Debug, Clone)]
+pub struct KioskOptions {
+    /// Whether the cursor is confined to the window, and key presses while the logo key (i.e. Windows/Command key)
+    /// is held are withheld from widgets.
+    ///
+    /// Shortcuts handled by the OS itself (e.g. Alt+Tab or Ctrl+Alt+Delete) can't be intercepted by the app,
+    /// and should be restricted through the kiosk configuration of the OS.
+    pub suppress_shortcuts: bool,
+    /// Time without user input after which `WindowEvent::IdleTimeout` is emitted, or `None` to never time out.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for KioskOptions {
+    fn default() -> Self {
+        KioskOptions { suppress_shortcuts: true, idle_timeout: None }
+    }
+}
+
+/// Thunderclap/Reclutch based application, driven by a windowing backend.
+pub struct App<R, B = GlutinBackend>
 where
     R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+    B: WindowingBackend,
 {
     /// Root widget.
     pub root: R,
+    /// Widget trees drawn above the root widget, from the bottom up (see `push_layer`).
+    layers: Vec<RootLayer>,
     /// Background color.
     pub background: Color,
     /// Update auxiliary.
     pub u_aux: UAux,
-    /// Graphics display (Skia backend).
-    pub display: skia::SkiaGraphicsDisplay,
-    /// OpenGL context/window.
-    pub context: WindowedContext<PossiblyCurrent>,
+    /// Graphics display (Skia with `GlutinBackend`).
+    pub display: B::Display,
+    /// Window and event loop.
+    pub backend: B,
     size: Size,
-    event_loop: EventLoop<()>,
 
     command_group_pre: CommandGroup,
     command_group_post: CommandGroup,
+    pacer: FramePacer,
+    kiosk: Option<KioskOptions>,
+    idle_timeout: Option<Duration>,
+    close_guard: Option<Box<dyn FnMut(&mut UAux) -> bool>>,
+    exit_hooks: Vec<Box<dyn FnOnce(&mut UAux)>>,
+    #[cfg(feature = "accessibility")]
+    name: String,
+    #[cfg(feature = "accessibility")]
+    accessibility: Option<crate::a11y::AccessibilityBridge>,
+}
+
+/// Root widget of a layer.
+pub type LayerRoot = Box<
+    dyn base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+>;
+
+/// Widget tree drawn above the root widget of an application, with its own update and draw pass
+/// (see `App::push_layer`).
+pub struct RootLayer {
+    /// Root widget of the layer.
+    pub root: LayerRoot,
+    /// How the layer takes mouse input.
+    pub input: base::LayerInput,
+    clip_list: base::ClipList,
+}
+
+impl RootLayer {
+    /// Draws the widget tree of the layer, culled to `viewport`.
+    fn draw(
+        &mut self,
+        display: &mut dyn GraphicsDisplay,
+        g_aux: &mut GAux,
+        viewport: AbsoluteRect,
+    ) {
+        self.clip_list.set_viewport(Some(viewport));
+        self.clip_list.draw(&mut *self.root, display, g_aux);
+    }
 }
 
-impl<R> App<R>
+/// Decides when frames are drawn, and limits the rate at which they're presented.
+#[derive(Debug, Clone)]
+struct FramePacer {
+    mode: RenderMode,
+    target_fps: Option<u32>,
+    low_power: bool,
+    focused: bool,
+    last_frame: Instant,
+    // Deadline of a frame which has been requested, but deferred to keep to the frame rate.
+    pending: Option<Instant>,
+}
+
+impl FramePacer {
+    fn new(mode: RenderMode, target_fps: Option<u32>, low_power: bool) -> Self {
+        FramePacer {
+            mode,
+            target_fps,
+            low_power,
+            focused: true,
+            last_frame: Instant::now(),
+            pending: None,
+        }
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        let target_fps = match self.mode {
+            RenderMode::FixedRate(fps) => Some(fps),
+            RenderMode::Continuous | RenderMode::OnDemand => self.target_fps,
+        };
+        let fps = if self.low_power && !self.focused {
+            Some(target_fps.map(|fps| fps / 2).unwrap_or(30))
+        } else {
+            target_fps
+        };
+
+        fps.map(|fps| Duration::from_secs(1) / fps.max(1))
+    }
+
+    /// Returns `true` if a frame should be presented now, otherwise the frame is deferred.
+    fn request_frame(&mut self, now: Instant) -> bool {
+        let interval = match self.interval() {
+            Some(interval) => interval,
+            None => return true,
+        };
+
+        let deadline = self.last_frame + interval;
+        if now >= deadline {
+            // Keep frames aligned to the interval, unless we've fallen more than a frame behind.
+            self.last_frame = if now - deadline < interval { deadline } else { now };
+            self.pending = None;
+            true
+        } else {
+            self.pending = Some(deadline);
+            false
+        }
+    }
+
+    /// Returns `true` if a frame is wanted; in `RenderMode::OnDemand`, only if `damaged` or `animating`.
+    fn wants_frame(&self, animating: bool, damaged: bool) -> bool {
+        match self.mode {
+            RenderMode::OnDemand => animating || damaged,
+            RenderMode::Continuous | RenderMode::FixedRate(_) => true,
+        }
+    }
+
+    /// Returns how long to wait for events; frames are continuously requested while `animating`
+    /// (or regardless in `RenderMode::Continuous` and `RenderMode::FixedRate`), and the event loop wakes up
+    /// for the next deferred frame or timer.
+    fn control_flow(&self, animating: bool, next_timer: Option<Instant>) -> ControlFlow {
+        if self.wants_frame(animating, false) && self.pending.is_none() {
+            return ControlFlow::Poll;
+        }
+
+        match self.pending.into_iter().chain(next_timer).min() {
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        }
+    }
+}
+
+/// Detects when the user stops interacting with the window, through a timer which is restarted on input.
+#[derive(Debug)]
+struct IdleTracker {
+    timeout: Duration,
+    timer: Option<base::TimerId>,
+    idle: bool,
+}
+
+impl IdleTracker {
+    fn new(timeout: Duration, timers: &mut base::TimerManager) -> Self {
+        IdleTracker { timeout, timer: Some(timers.schedule_once(timeout)), idle: false }
+    }
+
+    /// Restarts the timeout. Returns `true` if the user was idle.
+    fn input(&mut self, timers: &mut base::TimerManager) -> bool {
+        if let Some(timer) = self.timer.take() {
+            timers.cancel(timer);
+        }
+        self.timer = Some(timers.schedule_once(self.timeout));
+        std::mem::replace(&mut self.idle, false)
+    }
+
+    /// Returns `true` if `timer` is the timeout, in which case the user is now idle.
+    fn elapsed(&mut self, timer: base::TimerId) -> bool {
+        if self.timer == Some(timer) {
+            self.timer = None;
+            self.idle = true;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<R, B> App<R, B>
 where
     R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+    B: WindowingBackend,
 {
-    /// Starts the event loop.
+    /// Sets a callback which decides whether the window may be closed when the user requests it;
+    /// closing is prevented if it returns `false`.
+    ///
+    /// Without a close guard, regular windows are always closed, whereas kiosk windows are never closed.
+    pub fn set_close_guard(&mut self, guard: impl FnMut(&mut UAux) -> bool + 'static) {
+        self.close_guard = Some(Box::new(guard));
+    }
+
+    /// Adds a callback which runs when the application exits, e.g. to flush persisted state or join worker threads.
+    ///
+    /// `start` never returns, so cleanup has to be registered here. Once the event loop exits, the application shuts down
+    /// in order:
+    /// 1. External sources are pumped one last time, then `WindowEvent::Shutdown` is emitted and the widget tree is updated,
+    ///    so that widgets can handle the remaining events and save their state.
+    /// 2. The exit hooks run, in the order they were added.
+    /// 3. External sources are dropped, disconnecting them.
+    /// 4. The display is notified that the application is exiting (see `GraphicsDisplay::before_exit`).
+    pub fn on_exit(&mut self, hook: impl FnOnce(&mut UAux) + 'static) {
+        self.exit_hooks.push(Box::new(hook));
+    }
+
+    /// Pushes an independent widget tree above the root widget and the previously pushed layers
+    /// (e.g. a game HUD above the tools of an editor), created by `root` with the theme of the application.
+    ///
+    /// Every layer has its own update and draw pass. Layers are updated from the top down before the root widget,
+    /// so that the top-most layers handle events first, and drawn from the bottom up. Mouse input falls through
+    /// the layers according to `input` (see `base::LayerInput`), whereas keyboard input goes to the focused widget,
+    /// whichever layer it's in.
+    ///
+    /// The regular widgets of a layer are drawn above those of the layers beneath it, but overlays and tooltips
+    /// (see `base::Layer`) of any layer are drawn above the regular widgets of every layer.
+    ///
+    /// Layers are set up before the event loop starts; to show or hide a layer afterwards, change the visibility
+    /// of its root.
+    pub fn push_layer<L>(
+        &mut self,
+        root: impl FnOnce(&mut UAux, &dyn draw::Theme) -> L,
+        input: base::LayerInput,
+    ) where
+        L: base::WidgetChildren<
+                UpdateAux = UAux,
+                GraphicalAux = GAux,
+                DisplayObject = DisplayCommand,
+            > + 'static,
+    {
+        let theme = self.u_aux.theme.clone().expect("the application always provides its theme");
+        let root = root(&mut self.u_aux, theme.as_ref());
+        let mut clip_list = base::ClipList::default();
+        clip_list.set_z_offset((self.layers.len() as i32 + 1) * LAYER_Z_ORDER_STEP);
+        self.layers.push(RootLayer { root: Box::new(root), input, clip_list });
+
+        // Give layouts of the new layer bound to the window their initial size.
+        let size = self.size / self.u_aux.g_aux.scale;
+        self.u_aux.window_queue.emit_owned(base::WindowEvent::Resized(size));
+    }
+
+    /// Removes the top-most layer, returning its root widget.
+    pub fn pop_layer(&mut self) -> Option<LayerRoot> {
+        let layer = self.layers.pop()?;
+        base::damage_all();
+        Some(layer.root)
+    }
+
+    /// Returns the layers above the root widget, from the bottom up.
+    #[inline]
+    pub fn layers(&self) -> &[RootLayer] {
+        &self.layers
+    }
+
+    /// Returns the layers above the root widget mutably, from the bottom up (e.g. to change how they take input).
+    #[inline]
+    pub fn layers_mut(&mut self) -> &mut [RootLayer] {
+        &mut self.layers
+    }
+
+    /// Exposes the widget tree to a platform accessibility API, by handing `backend` the accessibility tree
+    /// (named after the app) whenever it changes.
+    #[cfg(feature = "accessibility")]
+    pub fn set_accessibility_backend(
+        &mut self,
+        backend: Box<dyn crate::a11y::AccessibilityBackend>,
+    ) {
+        self.accessibility = Some(crate::a11y::AccessibilityBridge::new(backend));
+    }
+
+    /// Switches the theme of the whole widget tree, without rebuilding it.
+    ///
+    /// Every widget loads its painter from `theme` and is resized from it, the layouts bound to the window are re-run,
+    /// and the window background is set to the background of the theme's color scheme.
+    /// Widget data derived from the previous theme (e.g. colors set by `WidgetConstructor::from_theme`) is left as is.
+    ///
+    /// Once the event loop has started, use `UpdateAuxiliary::set_theme` instead.
+    pub fn set_theme(&mut self, theme: Box<dyn draw::Theme>) {
+        self.background = theme.data().scheme.background;
+        switch_theme(&mut self.root, &mut self.layers, theme.as_ref(), &mut self.u_aux, self.size);
+        self.command_group_pre.repaint();
+        #[cfg(feature = "crash-reports")]
+        crate::crash::capture_theme(theme.as_ref());
+        base::damage_all();
+        self.u_aux.theme = Some(theme.into());
+    }
+
+    /// Starts the event loop of the backend.
+    ///
+    /// `f` is invoked with every event the application handles; it can override how the backend waits for
+    /// the next event by returning a `ControlFlow`.
     pub fn start<F>(self, mut f: F) -> !
     where
-        F: 'static + FnMut(Event<()>) -> Option<ControlFlow>,
+        F: 'static + FnMut(BackendEvent) -> Option<ControlFlow>,
         R: 'static,
     {
         let App {
             mut root,
-            background,
+            mut layers,
+            mut background,
             mut u_aux,
             mut display,
-            context,
+            mut backend,
             mut size,
-            event_loop,
 
             mut command_group_pre,
             mut command_group_post,
+            mut pacer,
+            kiosk,
+            idle_timeout,
+            mut close_guard,
+            mut exit_hooks,
+            #[cfg(feature = "accessibility")]
+            name,
+            #[cfg(feature = "accessibility")]
+            mut accessibility,
         } = self;
 
+        let confine_cursor = kiosk.as_ref().map(|kiosk| kiosk.suppress_shortcuts).unwrap_or(false);
+        let mut kiosk_idle = kiosk
+            .as_ref()
+            .and_then(|kiosk| kiosk.idle_timeout)
+            .map(|timeout| IdleTracker::new(timeout, &mut u_aux.timers));
+        let mut session_idle =
+            idle_timeout.map(|timeout| IdleTracker::new(timeout, &mut u_aux.timers));
+
+        if confine_cursor {
+            backend.set_cursor_grab(true);
+        }
+
         let mut modifiers =
             base::KeyModifiers { shift: false, ctrl: false, alt: false, logo: false };
 
-        event_loop.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Wait;
+        // Cursor position from when the pointer was locked.
+        let mut locked_at: Option<AbsolutePoint> = None;
+
+        // Outlines of layout issues, drawn above everything else.
+        #[cfg(feature = "debug-geometry")]
+        let mut debug_overlay = CommandGroup::new();
+
+        backend.run(move |backend, event| {
+            #[cfg(feature = "crash-reports")]
+            {
+                if !matches!(event, BackendEvent::EventsCleared | BackendEvent::RedrawRequested) {
+                    crate::crash::record_event(|| format!("{:?}", event));
+                }
+            }
+
+            let mut control_flow =
+                pacer.control_flow(u_aux.animator.is_animating(), u_aux.next_deadline());
 
             match event {
-                Event::MainEventsCleared => context.window().request_redraw(),
-                Event::RedrawRequested(..) => {
+                BackendEvent::EventsCleared => {
+                    for source in &mut u_aux.sources {
+                        source.pump();
+                    }
+                    u_aux.sources.retain(|source| source.is_connected());
+                    #[cfg(feature = "tray")]
+                    {
+                        if let Some(tray) = &mut u_aux.tray {
+                            tray.pump(&u_aux.window_queue, u_aux.theme.as_deref());
+                        }
+                    }
+                    #[cfg(feature = "global-hotkeys")]
+                    {
+                        if let Some(hotkeys) = &mut u_aux.global_hotkeys {
+                            hotkeys.pump(&u_aux.window_queue);
+                        }
+                    }
+                    #[cfg(feature = "notifications")]
+                    {
+                        if let Some(notifications) = &mut u_aux.notifications {
+                            notifications.pump(&u_aux.window_queue);
+                        }
+                    }
+
+                    let now = Instant::now();
+                    for timer in u_aux.timers.poll(now) {
+                        if kiosk_idle.as_mut().map(|idle| idle.elapsed(timer)).unwrap_or(false) {
+                            u_aux.window_queue.emit_owned(base::WindowEvent::IdleTimeout);
+                        } else if session_idle
+                            .as_mut()
+                            .map(|idle| idle.elapsed(timer))
+                            .unwrap_or(false)
+                        {
+                            u_aux.window_queue.emit_owned(base::WindowEvent::Idle);
+                        } else {
+                            u_aux.window_queue.emit_owned(base::WindowEvent::Timer(timer));
+                        }
+                    }
+                    u_aux.animator.tick(now);
+                }
+                BackendEvent::RedrawRequested => {
                     if display.size().0 != size.width as _ || display.size().1 != size.height as _ {
                         display.resize((size.width as _, size.height as _)).unwrap();
+                        base::damage_all();
                     }
 
                     command_group_pre.push(
@@ -180,7 +852,32 @@ where
                         None,
                     );
 
+                    let viewport = AbsoluteRect::new(
+                        Default::default(),
+                        (size / u_aux.g_aux.scale).cast_unit(),
+                    );
+                    base::set_root_viewport(Some(viewport));
                     base::invoke_draw(&mut root, &mut display, &mut u_aux.g_aux);
+                    for layer in &mut layers {
+                        layer.draw(&mut display, &mut u_aux.g_aux, viewport);
+                    }
+
+                    #[cfg(feature = "crash-reports")]
+                    crate::crash::capture_tree(&root);
+
+                    #[cfg(feature = "debug-geometry")]
+                    {
+                        // Outlines of resolved issues have to be erased as well.
+                        base::damage_all();
+                        debug_overlay.repaint();
+                        debug_overlay.push(
+                            &mut display,
+                            &layout_issue_overlay(),
+                            display::ZOrder(std::i32::MAX - 1),
+                            false,
+                            None,
+                        );
+                    }
 
                     command_group_post.push(
                         &mut display,
@@ -190,110 +887,578 @@ where
                         None,
                     );
 
+                    // The whole frame is presented, since the surface is double-buffered and the back buffer doesn't
+                    // keep the previous frame; the damage only decided whether this frame was drawn at all.
+                    base::take_damage();
                     display.present(None).unwrap();
 
-                    context.swap_buffers().unwrap();
+                    #[cfg(feature = "images")]
+                    {
+                        if let Some(image_resources) = &mut u_aux.g_aux.image_resources {
+                            image_resources.end_frame(&mut display);
+                        }
+                    }
+
+                    backend.swap_buffers().unwrap();
                 }
-                Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => {
-                    *control_flow = ControlFlow::Exit;
+                BackendEvent::Destroyed => {
+                    shutdown(
+                        &mut root,
+                        &mut layers,
+                        &mut u_aux,
+                        &mut display,
+                        exit_hooks.drain(..),
+                    );
+                    return ControlFlow::Exit;
+                }
+                BackendEvent::CloseRequested => {
+                    let close = match &mut close_guard {
+                        Some(guard) => guard(&mut u_aux),
+                        None => kiosk.is_none(),
+                    };
+
+                    if close {
+                        control_flow = ControlFlow::Exit;
+                    }
                 }
-                Event::WindowEvent {
-                    event: WindowEvent::ScaleFactorChanged { scale_factor: hidpi_factor, .. },
-                    ..
-                } => {
-                    u_aux.g_aux.scale = hidpi_factor as _;
-                    let window_size = context.window().inner_size();
-                    size = Size::new(window_size.width as _, window_size.height as _);
+                BackendEvent::ScaleFactorChanged(scale, window_size) => {
+                    // The window is resized to keep its logical size on the new monitor.
+                    size = window_size;
 
                     command_group_pre.repaint();
+                    change_scale(&mut root, &mut layers, &mut u_aux, scale, size);
                 }
-                Event::WindowEvent { event: WindowEvent::Resized(window_size), .. } => {
-                    size = Size::new(window_size.width as _, window_size.height as _);
+                BackendEvent::Resized(window_size) => {
+                    size = window_size;
+                    // The display is resized by the next frame, even if no widget has moved.
+                    base::damage_all();
+                    u_aux
+                        .window_queue
+                        .emit_owned(base::WindowEvent::Resized(size / u_aux.g_aux.scale));
                 }
-                Event::DeviceEvent {
-                    event: DeviceEvent::ModifiersChanged(key_modifiers), ..
-                } => {
-                    modifiers = convert_modifiers(key_modifiers);
+                BackendEvent::ModifiersChanged(key_modifiers) => {
+                    modifiers = key_modifiers;
                 }
-                Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, .. } => {
-                    let position = position.to_logical::<f64>(u_aux.g_aux.scale as f64);
-                    let position = Point::new(position.x as _, position.y as _);
+                BackendEvent::CursorMoved(_) if locked_at.is_some() => {
+                    // Hold the cursor in place; movement is reported through `MouseMotion`.
+                    backend.set_cursor_position(locked_at.unwrap());
+                    return control_flow;
+                }
+                BackendEvent::MouseMotion(delta) if locked_at.is_some() => {
+                    u_aux.window_queue.emit_owned(base::WindowEvent::MouseDelta(
+                        base::ConsumableEvent::new((delta / u_aux.g_aux.scale, modifiers)),
+                    ));
+                }
+                BackendEvent::CursorMoved(position) => {
+                    let position = position / u_aux.g_aux.scale;
 
                     u_aux.cursor = position.cast_unit();
+                    u_aux.pointer_target = Some(pointer_target(&root, &layers, u_aux.cursor));
 
                     u_aux.window_queue.emit_owned(base::WindowEvent::MouseMove(
                         base::ConsumableEvent::new((position.cast_unit(), modifiers)),
                     ));
                 }
-                Event::WindowEvent {
-                    event: WindowEvent::MouseInput { state, button, .. }, ..
-                } => {
-                    let mouse_button = match button {
-                        event::MouseButton::Left => base::MouseButton::Left,
-                        event::MouseButton::Middle => base::MouseButton::Middle,
-                        event::MouseButton::Right => base::MouseButton::Right,
-                        _ => base::MouseButton::Left,
-                    };
-
+                BackendEvent::MouseInput { button, pressed } => {
                     u_aux.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+                    u_aux.pointer_target = Some(pointer_target(&root, &layers, u_aux.cursor));
 
-                    u_aux.window_queue.emit_owned(match state {
-                        event::ElementState::Pressed => base::WindowEvent::MousePress(
-                            base::ConsumableEvent::new((u_aux.cursor, mouse_button, modifiers)),
-                        ),
-                        event::ElementState::Released => base::WindowEvent::MouseRelease(
-                            base::ConsumableEvent::new((u_aux.cursor, mouse_button, modifiers)),
-                        ),
+                    let event = base::ConsumableEvent::new((u_aux.cursor, button, modifiers));
+                    u_aux.window_queue.emit_owned(if pressed {
+                        base::WindowEvent::MousePress(event)
+                    } else {
+                        base::WindowEvent::MouseRelease(event)
                     });
                 }
-                Event::WindowEvent { event: WindowEvent::ReceivedCharacter(character), .. } => {
+                BackendEvent::MouseWheel(delta) => {
+                    u_aux.pointer_target = Some(pointer_target(&root, &layers, u_aux.cursor));
+                    u_aux.window_queue.emit_owned(base::WindowEvent::MouseWheel(
+                        base::ConsumableEvent::new((u_aux.cursor, delta, modifiers)),
+                    ));
+                }
+                BackendEvent::TextInput(character) => {
                     u_aux.window_queue.emit_owned(base::WindowEvent::TextInput(
                         base::ConsumableEvent::new(character),
                     ));
                 }
-                Event::WindowEvent {
-                    event:
-                        WindowEvent::KeyboardInput {
-                            input: event::KeyboardInput { virtual_keycode, state, .. },
-                            ..
-                        },
-                    ..
-                } => {
-                    if let Some(virtual_keycode) = virtual_keycode {
-                        let key_input: base::KeyInput = virtual_keycode.into();
-
-                        u_aux.window_queue.emit_owned(match state {
-                            event::ElementState::Pressed => base::WindowEvent::KeyPress(
-                                base::ConsumableEvent::new((key_input, modifiers)),
-                            ),
-                            event::ElementState::Released => base::WindowEvent::KeyRelease(
-                                base::ConsumableEvent::new((key_input, modifiers)),
-                            ),
+                BackendEvent::KeyInput { key, pressed } => {
+                    let logo = modifiers.logo
+                        || key == base::KeyInput::LWin
+                        || key == base::KeyInput::RWin;
+
+                    // Withhold OS shortcuts from widgets in kiosk mode.
+                    if !(confine_cursor && logo) {
+                        let event = base::ConsumableEvent::new((key, modifiers));
+                        u_aux.window_queue.emit_owned(if pressed {
+                            base::WindowEvent::KeyPress(event)
+                        } else {
+                            base::WindowEvent::KeyRelease(event)
                         });
                     }
                 }
-                Event::WindowEvent { event: WindowEvent::Focused(false), .. } => {
-                    u_aux.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+                BackendEvent::Focused(focused) => {
+                    pacer.focused = focused;
+                    if !focused {
+                        // Don't hold onto the pointer while the user is in another window.
+                        u_aux.pointer_lock = false;
+                        u_aux.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+                    }
+                }
+                BackendEvent::MouseMotion(_) | BackendEvent::Touch(_) => return control_flow,
+            }
+
+            // Restart the idle timeouts on any user input.
+            if event.is_input() {
+                if let Some(idle) = &mut kiosk_idle {
+                    idle.input(&mut u_aux.timers);
+                }
+                if let Some(idle) = &mut session_idle {
+                    if idle.input(&mut u_aux.timers) {
+                        u_aux.window_queue.emit_owned(base::WindowEvent::Active);
+                    }
                 }
-                _ => return,
             }
 
-            if let Some(cf) = f(event) {
-                *control_flow = cf;
+            let overridden = f(event);
+            if let Some(cf) = overridden {
+                control_flow = cf;
             }
 
+            if let Some(theme) = u_aux.theme_request.take() {
+                background = theme.data().scheme.background;
+                switch_theme(&mut root, &mut layers, theme.as_ref(), &mut u_aux, size);
+                command_group_pre.repaint();
+                #[cfg(feature = "crash-reports")]
+                crate::crash::capture_theme(theme.as_ref());
+                base::damage_all();
+                backend.request_redraw();
+                u_aux.theme = Some(theme.into());
+            }
+
+            u_aux.focus_tracker.update();
+            update_layers(&mut layers, &mut u_aux);
             root.update(&mut u_aux);
+
+            #[cfg(feature = "accessibility")]
+            {
+                if let Some(accessibility) = &mut accessibility {
+                    accessibility.update(&name, &root);
+                }
+            }
+
+            if u_aux.pointer_lock != locked_at.is_some() {
+                backend.set_cursor_grab(u_aux.pointer_lock || confine_cursor);
+                backend.set_cursor_visible(!u_aux.pointer_lock);
+                locked_at = if u_aux.pointer_lock { Some(u_aux.cursor) } else { None };
+            }
+
+            // Frames are scheduled once the events of this iteration have been handled,
+            // so that the damage of updating the widget tree is known.
+            if event == BackendEvent::EventsCleared {
+                let animating = u_aux.animator.is_animating();
+                let damaged = base::is_damaged(&root)
+                    || layers.iter().any(|layer| {
+                        !layer.clip_list.pending_damage(layer.root.as_ref()).is_empty()
+                    });
+                if pacer.wants_frame(animating, damaged) && pacer.request_frame(Instant::now()) {
+                    backend.request_redraw();
+                }
+                if overridden.is_none() {
+                    control_flow = pacer.control_flow(animating, u_aux.next_deadline());
+                }
+            }
+
+            control_flow
         })
     }
 }
 
+/// Creates a UI which is driven by an existing event loop and renderer instead of owning them,
+/// e.g. to embed Thunderclap widgets in a game or an existing winit application.
+///
+/// `size` is the size (in physical pixels) of the area the UI covers, and `scale` the scale factor of the window.
+/// The host forwards its events to `Embedded::handle_event`, invokes `Embedded::update` on every iteration of its
+/// event loop, and `Embedded::draw` whenever it renders a frame.
+pub fn embed<R, T, TF, RF>(
+    display: &mut dyn GraphicsDisplay,
+    theme: TF,
+    root: RF,
+    size: Size,
+    scale: f32,
+) -> Embedded<R>
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+    T: draw::Theme + 'static,
+    TF: FnOnce(&mut GAux, &mut dyn GraphicsDisplay) -> T,
+    RF: FnOnce(&mut UAux, &T) -> R,
+{
+    let g_aux = GAux {
+        scale,
+        #[cfg(feature = "images")]
+        image_resources: Some(Default::default()),
+    };
+    let mut u_aux = UAux::new(g_aux);
+
+    let theme = theme(&mut u_aux.g_aux, display);
+    let root = root(&mut u_aux, &theme);
+    u_aux.theme = Some(Rc::new(theme));
+
+    // Give layouts bound to the window their initial size.
+    u_aux.window_queue.emit_owned(base::WindowEvent::Resized(size / scale));
+
+    Embedded {
+        root,
+        u_aux,
+        size,
+        modifiers: base::KeyModifiers { shift: false, ctrl: false, alt: false, logo: false },
+        command_group_pre: CommandGroup::new(),
+        command_group_post: CommandGroup::new(),
+    }
+}
+
+/// Thunderclap UI embedded in the event loop and renderer of a host application (see `embed`).
+///
+/// As with `App`, there can only be one embedded UI, since `base::invoke_draw` tracks a single widget tree.
+pub struct Embedded<R>
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+{
+    /// Root widget.
+    pub root: R,
+    /// Update auxiliary.
+    pub u_aux: UAux,
+    size: Size,
+    modifiers: base::KeyModifiers,
+    command_group_pre: CommandGroup,
+    command_group_post: CommandGroup,
+}
+
+impl<R> Embedded<R>
+where
+    R: base::WidgetChildren<UpdateAux = UAux, GraphicalAux = GAux, DisplayObject = DisplayCommand>,
+{
+    /// Translates an event of the host into window events of the UI, which are handled by the next `update`.
+    ///
+    /// The UI is assumed to cover the whole window; window resizes resize the UI (see `resize`).
+    /// Returns `false` if the event isn't relevant to the UI.
+    pub fn handle_event<T>(&mut self, event: &Event<T>) -> bool {
+        let modifiers = self.modifiers;
+        match event {
+            Event::WindowEvent { event: WindowEvent::Resized(size), .. } => {
+                self.resize(Size::new(size.width as _, size.height as _));
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size },
+                ..
+            } => {
+                self.size = Size::new(new_inner_size.width as _, new_inner_size.height as _);
+                self.command_group_pre.repaint();
+                change_scale(
+                    &mut self.root,
+                    &mut [],
+                    &mut self.u_aux,
+                    *scale_factor as _,
+                    self.size,
+                );
+            }
+            Event::DeviceEvent { event: DeviceEvent::ModifiersChanged(key_modifiers), .. } => {
+                self.modifiers = convert_modifiers(*key_modifiers);
+            }
+            Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, .. } => {
+                let position = position.to_logical::<f64>(self.u_aux.g_aux.scale as f64);
+                let position = AbsolutePoint::new(position.x as _, position.y as _);
+
+                self.u_aux.cursor = position;
+                self.u_aux.pointer_target = Some(base::PointerTarget::new(&self.root, position));
+                self.u_aux.window_queue.emit_owned(base::WindowEvent::MouseMove(
+                    base::ConsumableEvent::new((position, modifiers)),
+                ));
+            }
+            Event::WindowEvent { event: WindowEvent::MouseInput { state, button, .. }, .. } => {
+                let cursor = self.u_aux.cursor;
+                let mouse_button = convert_mouse_button(*button);
+
+                self.u_aux.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+                self.u_aux.pointer_target = Some(base::PointerTarget::new(&self.root, cursor));
+                self.u_aux.window_queue.emit_owned(match state {
+                    event::ElementState::Pressed => base::WindowEvent::MousePress(
+                        base::ConsumableEvent::new((cursor, mouse_button, modifiers)),
+                    ),
+                    event::ElementState::Released => base::WindowEvent::MouseRelease(
+                        base::ConsumableEvent::new((cursor, mouse_button, modifiers)),
+                    ),
+                });
+            }
+            Event::WindowEvent { event: WindowEvent::MouseWheel { delta, .. }, .. } => {
+                let cursor = self.u_aux.cursor;
+                let delta = convert_scroll_delta(*delta);
+
+                self.u_aux.pointer_target = Some(base::PointerTarget::new(&self.root, cursor));
+                self.u_aux.window_queue.emit_owned(base::WindowEvent::MouseWheel(
+                    base::ConsumableEvent::new((cursor, delta, modifiers)),
+                ));
+            }
+            Event::WindowEvent { event: WindowEvent::ReceivedCharacter(character), .. } => {
+                self.u_aux.window_queue.emit_owned(base::WindowEvent::TextInput(
+                    base::ConsumableEvent::new(*character),
+                ));
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            event::KeyboardInput {
+                                virtual_keycode: Some(virtual_keycode), state, ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                let key_input: base::KeyInput = (*virtual_keycode).into();
+                self.u_aux.window_queue.emit_owned(match state {
+                    event::ElementState::Pressed => base::WindowEvent::KeyPress(
+                        base::ConsumableEvent::new((key_input, modifiers)),
+                    ),
+                    event::ElementState::Released => base::WindowEvent::KeyRelease(
+                        base::ConsumableEvent::new((key_input, modifiers)),
+                    ),
+                });
+            }
+            Event::WindowEvent { event: WindowEvent::Focused(focused), .. } => {
+                if !focused {
+                    self.u_aux.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+                }
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Resizes the UI to `size` (in physical pixels).
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.u_aux
+            .window_queue
+            .emit_owned(base::WindowEvent::Resized(size / self.u_aux.g_aux.scale));
+    }
+
+    /// Runs timers, animations and external sources, then updates the widget tree
+    /// with the events handled since the last update.
+    pub fn update(&mut self) {
+        for source in &mut self.u_aux.sources {
+            source.pump();
+        }
+        self.u_aux.sources.retain(|source| source.is_connected());
+        #[cfg(feature = "tray")]
+        {
+            if let Some(tray) = &mut self.u_aux.tray {
+                tray.pump(&self.u_aux.window_queue, self.u_aux.theme.as_deref());
+            }
+        }
+        #[cfg(feature = "global-hotkeys")]
+        {
+            if let Some(hotkeys) = &mut self.u_aux.global_hotkeys {
+                hotkeys.pump(&self.u_aux.window_queue);
+            }
+        }
+        #[cfg(feature = "notifications")]
+        {
+            if let Some(notifications) = &mut self.u_aux.notifications {
+                notifications.pump(&self.u_aux.window_queue);
+            }
+        }
+
+        let now = Instant::now();
+        for timer in self.u_aux.timers.poll(now) {
+            self.u_aux.window_queue.emit_owned(base::WindowEvent::Timer(timer));
+        }
+        self.u_aux.animator.tick(now);
+
+        if let Some(theme) = self.u_aux.theme_request.take() {
+            switch_theme(&mut self.root, &mut [], theme.as_ref(), &mut self.u_aux, self.size);
+            self.command_group_pre.repaint();
+            base::damage_all();
+            self.u_aux.theme = Some(theme.into());
+        }
+
+        self.u_aux.focus_tracker.update();
+        self.root.update(&mut self.u_aux);
+    }
+
+    /// Draws the UI into `display`, above any content the host has drawn into it, without presenting it.
+    ///
+    /// Returns the area (in physical pixels) which changed since the last frame, or `None` if nothing changed.
+    /// Presenting only that area is valid only if the host's surface still holds the previous frame.
+    pub fn draw(&mut self, display: &mut dyn GraphicsDisplay) -> Option<display::Rect> {
+        let scale = self.u_aux.g_aux.scale;
+
+        self.command_group_pre.push(
+            display,
+            &[DisplayCommand::Save, DisplayCommand::Scale(Vector::new(scale, scale))],
+            display::ZOrder(std::i32::MIN),
+            false,
+            None,
+        );
+
+        base::set_root_viewport(Some(AbsoluteRect::new(
+            Default::default(),
+            (self.size / scale).cast_unit(),
+        )));
+        base::invoke_draw(&mut self.root, display, &mut self.u_aux.g_aux);
+
+        self.command_group_post.push(
+            display,
+            &[DisplayCommand::Restore],
+            display::ZOrder(std::i32::MAX),
+            false,
+            None,
+        );
+
+        #[cfg(feature = "images")]
+        {
+            if let Some(image_resources) = &mut self.u_aux.g_aux.image_resources {
+                image_resources.end_frame(display);
+            }
+        }
+
+        base::take_damage()
+            .bounds()
+            .map(|damage| damage.cast_unit().scale(scale, scale).round_out())
+    }
+
+    /// Returns the earliest time at which `update` should be invoked even without any events,
+    /// for the next timer or to pump external sources.
+    #[inline]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.u_aux.next_deadline()
+    }
+
+    /// Returns `true` while animations are running, in which case the host should update and draw continuously.
+    #[inline]
+    pub fn is_animating(&self) -> bool {
+        self.u_aux.animator.is_animating()
+    }
+
+    /// Returns `true` if something has changed since the last frame, in which case the host should draw the UI.
+    /// Hosts which render on demand only need to draw while this or `is_animating` returns `true`.
+    #[inline]
+    pub fn is_damaged(&self) -> bool {
+        base::is_damaged(&self.root)
+    }
+
+    /// Returns `true` if the cursor was over a widget (other than the root, which typically covers the whole UI)
+    /// as of the last mouse event, in which case the host should ignore the mouse input.
+    pub fn is_pointer_over_ui(&self) -> bool {
+        self.u_aux
+            .pointer_target
+            .as_ref()
+            .and_then(|target| target.path())
+            .map(|path| path.depth() > 0)
+            .unwrap_or(false)
+    }
+
+    /// Runs the shutdown sequence (see `App::on_exit`) before the host exits.
+    pub fn shutdown(&mut self, display: &mut dyn GraphicsDisplay) {
+        shutdown(&mut self.root, &mut [], &mut self.u_aux, display, std::iter::empty());
+    }
+}
+
 /// Rudimentary update auxiliary.
 pub struct UAux {
     pub window_queue: RcEventQueue<base::WindowEvent>,
+    pub focus_tracker: base::FocusTracker,
     pub cursor: AbsolutePoint,
+    pub pointer_lock: bool,
+    /// Widget which receives the mouse event being dispatched, resolved before each mouse event is emitted.
+    pub pointer_target: Option<base::PointerTarget>,
+    pub timers: base::TimerManager,
+    pub animator: anim::Animator,
+    pub event_bus: base::EventBus,
+    /// Sink which user interactions are recorded to, or `None` to opt out of telemetry.
+    pub telemetry: Option<Box<dyn telemetry::TelemetrySink>>,
+    /// Profiler which measurements (e.g. input latencies) are recorded to, or `None` to not profile.
+    pub profiler: Option<profiling::Profiler>,
+    /// Clipboard used by text widgets; local to the application by default, which can be replaced to integrate the system clipboard.
+    pub clipboard: Box<dyn clipboard::Clipboard>,
+    /// Theme requested through `UpdateAuxiliary::set_theme`, which is switched to before the next update.
+    pub theme_request: Option<Box<dyn draw::Theme>>,
+    /// Current theme of the widget tree, provided through `UpdateAuxiliary::theme`.
+    pub theme: Option<Rc<dyn draw::Theme>>,
+    /// External event sources, pumped every frame and dropped once disconnected.
+    pub sources: Vec<Box<dyn bridge::ExternalSource>>,
+    /// Pool which runs background work, whose threads are started by the first task.
+    pub task_pool: bridge::TaskPool,
+    #[cfg(feature = "scripting")]
+    pub script_host: Option<Box<dyn crate::script::ScriptHost>>,
+    /// Tray icon of the application, pumped every frame.
+    #[cfg(feature = "tray")]
+    pub tray: Option<tray::Tray>,
+    /// System-wide hotkeys of the application, pumped every frame.
+    #[cfg(feature = "global-hotkeys")]
+    pub global_hotkeys: Option<global_hotkeys::GlobalHotkeys>,
+    /// Notifications of the application, pumped every frame (see `notify`).
+    #[cfg(feature = "notifications")]
+    pub notifications: Option<notifications::Notifications>,
     pub g_aux: GAux,
 }
 
+impl UAux {
+    fn new(g_aux: GAux) -> Self {
+        let window_queue = RcEventQueue::new();
+        let focus_tracker = base::FocusTracker::new(&window_queue);
+        UAux {
+            window_queue,
+            focus_tracker,
+            cursor: Default::default(),
+            pointer_lock: false,
+            pointer_target: None,
+            timers: base::TimerManager::new(),
+            animator: anim::Animator::new(),
+            event_bus: base::EventBus::new(),
+            telemetry: None,
+            profiler: None,
+            clipboard: Box::new(clipboard::LocalClipboard::new()),
+            theme_request: None,
+            theme: None,
+            sources: Vec::new(),
+            task_pool: Default::default(),
+            #[cfg(feature = "scripting")]
+            script_host: None,
+            #[cfg(feature = "tray")]
+            tray: None,
+            #[cfg(feature = "global-hotkeys")]
+            global_hotkeys: None,
+            #[cfg(feature = "notifications")]
+            notifications: None,
+            g_aux,
+        }
+    }
+
+    /// Returns the earliest time at which the event loop should wake up;
+    /// for the next timer or, if there are any external sources (or a tray icon, global hotkeys or notifications)
+    /// or background tasks, to pump them.
+    fn next_deadline(&self) -> Option<Instant> {
+        #[allow(unused_mut)]
+        let mut idle = self.sources.is_empty() && self.task_pool.is_idle();
+        #[cfg(feature = "tray")]
+        {
+            idle &= self.tray.is_none();
+        }
+        #[cfg(feature = "global-hotkeys")]
+        {
+            idle &= self.global_hotkeys.is_none();
+        }
+        #[cfg(feature = "notifications")]
+        {
+            idle &= self.notifications.is_none();
+        }
+
+        let pump = if idle { None } else { Some(Instant::now() + SOURCE_PUMP_INTERVAL) };
+        self.timers.next_deadline().into_iter().chain(pump).min()
+    }
+}
+
 impl base::UpdateAuxiliary for UAux {
     #[inline]
     fn window_queue(&self) -> &RcEventQueue<base::WindowEvent> {
@@ -314,11 +1479,111 @@ impl base::UpdateAuxiliary for UAux {
     fn graphical_mut(&mut self) -> &mut dyn base::GraphicalAuxiliary {
         &mut self.g_aux
     }
+
+    #[inline]
+    fn focus_tracker(&self) -> &base::FocusTracker {
+        &self.focus_tracker
+    }
+
+    #[inline]
+    fn focus_tracker_mut(&mut self) -> &mut base::FocusTracker {
+        &mut self.focus_tracker
+    }
+
+    #[inline]
+    fn timers(&self) -> &base::TimerManager {
+        &self.timers
+    }
+
+    #[inline]
+    fn timers_mut(&mut self) -> &mut base::TimerManager {
+        &mut self.timers
+    }
+
+    #[inline]
+    fn animator(&self) -> &anim::Animator {
+        &self.animator
+    }
+
+    #[inline]
+    fn animator_mut(&mut self) -> &mut anim::Animator {
+        &mut self.animator
+    }
+
+    #[inline]
+    fn event_bus(&self) -> &base::EventBus {
+        &self.event_bus
+    }
+
+    #[inline]
+    fn event_bus_mut(&mut self) -> &mut base::EventBus {
+        &mut self.event_bus
+    }
+
+    #[inline]
+    fn profiler(&mut self) -> Option<&mut profiling::Profiler> {
+        self.profiler.as_mut()
+    }
+
+    #[inline]
+    fn telemetry_sink(&mut self) -> Option<&mut dyn telemetry::TelemetrySink> {
+        self.telemetry.as_mut().map(|sink| sink.as_mut() as _)
+    }
+
+    #[inline]
+    fn task_pool(&self) -> Option<&bridge::TaskPool> {
+        Some(&self.task_pool)
+    }
+
+    #[inline]
+    fn clipboard(&mut self) -> Option<&mut dyn clipboard::Clipboard> {
+        Some(self.clipboard.as_mut())
+    }
+
+    #[cfg(feature = "scripting")]
+    #[inline]
+    fn script_host(&mut self) -> Option<&mut dyn crate::script::ScriptHost> {
+        self.script_host.as_mut().map(|host| host.as_mut() as _)
+    }
+
+    #[inline]
+    fn pointer_target(&self) -> Option<&base::PointerTarget> {
+        self.pointer_target.as_ref()
+    }
+
+    #[inline]
+    fn set_pointer_lock(&mut self, locked: bool) {
+        self.pointer_lock = locked;
+    }
+
+    #[inline]
+    fn pointer_locked(&self) -> bool {
+        self.pointer_lock
+    }
+
+    #[inline]
+    fn set_theme(&mut self, theme: Box<dyn draw::Theme>) {
+        self.theme_request = Some(theme);
+    }
+
+    #[inline]
+    fn theme(&self) -> Option<Rc<dyn draw::Theme>> {
+        self.theme.clone()
+    }
+
+    #[inline]
+    fn add_external_source(&mut self, source: Box<dyn bridge::ExternalSource>) {
+        self.sources.push(source);
+    }
 }
 
 /// Rudimentary graphical auxiliary.
 pub struct GAux {
     pub scale: f32,
+    /// Manager of image resources, following the default `ImagePolicy`.
+    /// Set to `None` to let images manage their own resources.
+    #[cfg(feature = "images")]
+    pub image_resources: Option<crate::resources::ImageResources>,
 }
 
 impl base::GraphicalAuxiliary for GAux {
@@ -326,4 +1591,10 @@ impl base::GraphicalAuxiliary for GAux {
     fn scaling(&self) -> f32 {
         self.scale
     }
+
+    #[cfg(feature = "images")]
+    #[inline]
+    fn image_resources(&mut self) -> Option<&mut crate::resources::ImageResources> {
+        self.image_resources.as_mut()
+    }
 }
@@ -0,0 +1,286 @@
+//! Windowing backends, which own the window and the event loop on behalf of `App`.
+//!
+//! The application logic (updating and drawing the widget tree, frame pacing, idle timeouts, etc.) is the same for
+//! every backend; a backend only translates the events of its platform into `BackendEvent`s and carries out requests
+//! of the application, such as presenting a frame or grabbing the cursor.
+//!
+//! `app::create` uses `GlutinBackend`, whereas `app::create_with_backend` takes any backend, e.g. one based on raw
+//! Win32 or SDL, or an offscreen backend which replays recorded events in tests.
+
+use {
+    super::{convert_modifiers, convert_mouse_button, convert_scroll_delta, AppOptions},
+    crate::{base, error::AppError, geom::*},
+    glutin::{
+        event::{self, DeviceEvent, Event, WindowEvent},
+        event_loop::{self, EventLoop},
+        window::{Fullscreen, WindowBuilder},
+        ContextBuilder, PossiblyCurrent, WindowedContext,
+    },
+    reclutch::display::{skia, GraphicsDisplay, Point, Size, Vector},
+    std::time::Instant,
+};
+
+/// How a backend waits for events once it has handed the current ones to the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Hands `BackendEvent::EventsCleared` to the application again right away.
+    Poll,
+    /// Waits for the next event.
+    Wait,
+    /// Waits for the next event, but no longer than until a deadline.
+    WaitUntil(Instant),
+    /// Exits the event loop, after handing `BackendEvent::Destroyed` to the application.
+    Exit,
+}
+
+/// Platform-independent event of a window or its event loop.
+///
+/// Positions and sizes are in physical pixels unless noted otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackendEvent {
+    /// The events received so far have been handed out; the application runs its timers and schedules frames.
+    EventsCleared,
+    /// A frame is due, either requested through `WindowingBackend::request_redraw` or by the platform
+    /// (e.g. because the window was uncovered).
+    RedrawRequested,
+    /// The event loop is about to exit; this is the last event.
+    Destroyed,
+    /// The user asked to close the window.
+    CloseRequested,
+    /// The window has been resized.
+    Resized(Size),
+    /// The scale factor of the window has changed, and the window has been resized to a size (to keep its logical size).
+    ScaleFactorChanged(f32, Size),
+    /// The window gained or lost focus.
+    Focused(bool),
+    /// The held modifier keys have changed.
+    ModifiersChanged(base::KeyModifiers),
+    /// The cursor moved within the window.
+    CursorMoved(Point),
+    /// The mouse moved, regardless of the cursor (which is held in place while the pointer is locked).
+    MouseMotion(Vector),
+    /// A mouse button was pressed or released.
+    MouseInput { button: base::MouseButton, pressed: bool },
+    /// The mouse wheel (or touchpad) scrolled, in logical pixels.
+    MouseWheel(Vector),
+    /// A finger touched or moved on the window.
+    Touch(Point),
+    /// A character was typed.
+    TextInput(char),
+    /// A key was pressed or released.
+    KeyInput { key: base::KeyInput, pressed: bool },
+}
+
+impl BackendEvent {
+    /// Returns `true` if the event is input of the user (as opposed to e.g. window management).
+    pub fn is_input(&self) -> bool {
+        match self {
+            BackendEvent::CursorMoved(_)
+            | BackendEvent::MouseMotion(_)
+            | BackendEvent::MouseInput { .. }
+            | BackendEvent::MouseWheel(_)
+            | BackendEvent::Touch(_)
+            | BackendEvent::TextInput(_)
+            | BackendEvent::KeyInput { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// Window and event loop which drive an application.
+pub trait WindowingBackend: Sized + 'static {
+    /// Graphics display drawing into the window.
+    type Display: GraphicsDisplay;
+
+    /// Creates the graphics display drawing into the window, at the size of the window.
+    fn create_display(&mut self) -> Result<Self::Display, AppError>;
+    /// Returns the size of the window.
+    fn size(&self) -> Size;
+    /// Returns the scale factor of the window (i.e. of the monitor it's on).
+    fn scale_factor(&self) -> f32;
+
+    /// Changes the title of the window.
+    fn set_title(&mut self, title: &str);
+    /// Shows or hides the window.
+    fn set_visible(&mut self, visible: bool);
+    /// Confines the cursor to the window, or releases it.
+    fn set_cursor_grab(&mut self, grab: bool);
+    /// Shows or hides the cursor while it's over the window.
+    fn set_cursor_visible(&mut self, visible: bool);
+    /// Moves the cursor to a position within the window, in logical pixels.
+    fn set_cursor_position(&mut self, position: AbsolutePoint);
+
+    /// Requests `BackendEvent::RedrawRequested`, once the current events have been handed out.
+    fn request_redraw(&mut self);
+    /// Shows the frame which has been presented to the display.
+    fn swap_buffers(&mut self) -> Result<(), AppError>;
+
+    /// Runs the event loop, handing every event to `handler`, which returns how to wait for the next one.
+    fn run<F>(self, handler: F) -> !
+    where
+        F: 'static + FnMut(&mut Self, BackendEvent) -> ControlFlow;
+}
+
+/// Backend of a winit window with an OpenGL context (through glutin), drawn into by Skia.
+pub struct GlutinBackend {
+    /// OpenGL context/window.
+    pub context: WindowedContext<PossiblyCurrent>,
+    event_loop: Option<EventLoop<()>>,
+}
+
+impl GlutinBackend {
+    /// Creates the window described by `opts` (which is hidden until the application has warmed up).
+    pub fn new(opts: &AppOptions) -> Self {
+        let event_loop = EventLoop::new();
+
+        let hidpi_factor = event_loop.primary_monitor().scale_factor();
+
+        // The window is shown once warmed up, so that the first frames aren't visibly incomplete.
+        let mut wb = WindowBuilder::new()
+            .with_title(opts.name.clone())
+            .with_inner_size(
+                glutin::dpi::PhysicalSize::new(
+                    opts.window_size.width as f64,
+                    opts.window_size.width as f64,
+                )
+                .to_logical::<f64>(hidpi_factor),
+            )
+            .with_visible(false);
+
+        if opts.kiosk.is_some() {
+            wb = wb
+                .with_fullscreen(Some(Fullscreen::Borderless(event_loop.primary_monitor())))
+                .with_decorations(false)
+                .with_always_on_top(true);
+        }
+
+        let context =
+            ContextBuilder::new().with_vsync(opts.vsync).build_windowed(wb, &event_loop).unwrap();
+
+        let context = unsafe { context.make_current().unwrap() };
+
+        GlutinBackend { context, event_loop: Some(event_loop) }
+    }
+
+    fn translate(event: Event<()>) -> Option<BackendEvent> {
+        Some(match event {
+            Event::MainEventsCleared => BackendEvent::EventsCleared,
+            Event::RedrawRequested(..) => BackendEvent::RedrawRequested,
+            Event::LoopDestroyed => BackendEvent::Destroyed,
+            Event::DeviceEvent { event: DeviceEvent::ModifiersChanged(modifiers), .. } => {
+                BackendEvent::ModifiersChanged(convert_modifiers(modifiers))
+            }
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                BackendEvent::MouseMotion(Vector::new(delta.0 as _, delta.1 as _))
+            }
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => BackendEvent::CloseRequested,
+                WindowEvent::Resized(size) => {
+                    BackendEvent::Resized(Size::new(size.width as _, size.height as _))
+                }
+                WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                    BackendEvent::ScaleFactorChanged(
+                        scale_factor as _,
+                        Size::new(new_inner_size.width as _, new_inner_size.height as _),
+                    )
+                }
+                WindowEvent::Focused(focused) => BackendEvent::Focused(focused),
+                WindowEvent::CursorMoved { position, .. } => {
+                    BackendEvent::CursorMoved(Point::new(position.x as _, position.y as _))
+                }
+                WindowEvent::MouseInput { state, button, .. } => BackendEvent::MouseInput {
+                    button: convert_mouse_button(button),
+                    pressed: state == event::ElementState::Pressed,
+                },
+                WindowEvent::MouseWheel { delta, .. } => {
+                    BackendEvent::MouseWheel(convert_scroll_delta(delta))
+                }
+                WindowEvent::Touch(touch) => {
+                    BackendEvent::Touch(Point::new(touch.location.x as _, touch.location.y as _))
+                }
+                WindowEvent::ReceivedCharacter(character) => BackendEvent::TextInput(character),
+                WindowEvent::KeyboardInput {
+                    input:
+                        event::KeyboardInput { virtual_keycode: Some(virtual_keycode), state, .. },
+                    ..
+                } => BackendEvent::KeyInput {
+                    key: virtual_keycode.into(),
+                    pressed: state == event::ElementState::Pressed,
+                },
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+impl WindowingBackend for GlutinBackend {
+    type Display = skia::SkiaGraphicsDisplay;
+
+    fn create_display(&mut self) -> Result<Self::Display, AppError> {
+        let size = self.size();
+        Ok(skia::SkiaGraphicsDisplay::new_gl_framebuffer(&skia::SkiaOpenGlFramebuffer {
+            framebuffer_id: 0,
+            size: (size.width as _, size.height as _),
+        })?)
+    }
+
+    fn size(&self) -> Size {
+        let size = self.context.window().inner_size();
+        Size::new(size.width as _, size.height as _)
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.context.window().scale_factor() as _
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.context.window().set_title(title);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.context.window().set_visible(visible);
+    }
+
+    fn set_cursor_grab(&mut self, grab: bool) {
+        let _ = self.context.window().set_cursor_grab(grab);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.context.window().set_cursor_visible(visible);
+    }
+
+    fn set_cursor_position(&mut self, position: AbsolutePoint) {
+        let _ = self.context.window().set_cursor_position(glutin::dpi::LogicalPosition::new(
+            position.x as f64,
+            position.y as f64,
+        ));
+    }
+
+    fn request_redraw(&mut self) {
+        self.context.window().request_redraw();
+    }
+
+    fn swap_buffers(&mut self) -> Result<(), AppError> {
+        self.context.swap_buffers().map_err(|error| AppError::BackendError(error.to_string()))
+    }
+
+    fn run<F>(mut self, mut handler: F) -> !
+    where
+        F: 'static + FnMut(&mut Self, BackendEvent) -> ControlFlow,
+    {
+        let event_loop = self.event_loop.take().expect("the event loop only runs once");
+        event_loop.run(move |event, _, control_flow| {
+            if let Some(event) = GlutinBackend::translate(event) {
+                *control_flow = match handler(&mut self, event) {
+                    ControlFlow::Poll => event_loop::ControlFlow::Poll,
+                    ControlFlow::Wait => event_loop::ControlFlow::Wait,
+                    ControlFlow::WaitUntil(deadline) => {
+                        event_loop::ControlFlow::WaitUntil(deadline)
+                    }
+                    ControlFlow::Exit => event_loop::ControlFlow::Exit,
+                };
+            }
+        })
+    }
+}
@@ -0,0 +1,440 @@
+//! System-wide hotkeys, which are delivered even while the window isn't focused (e.g. to summon a launcher),
+//! and the backend trait through which they're registered.
+//!
+//! Hotkeys are registered with the platform by a `HotkeyBackend`. The `global-hotkeys-x11` feature provides
+//! `X11Hotkeys`, which grabs keys with `XGrabKey`; there's no `RegisterHotKey` backend for Windows yet.
+//! Wayland doesn't let applications grab keys globally: without XWayland `X11Hotkeys::new` fails with
+//! `HotkeyError::Unsupported`, and through XWayland the grabs only see keys pressed while an X11 window is focused.
+//!
+//! Once `GlobalHotkeys` is installed into `UAux::global_hotkeys`, the application pumps it every frame,
+//! emitting `WindowEvent::GlobalHotkey` with the identifier returned by `GlobalHotkeys::register`
+//! whenever a hotkey is pressed:
+//!
+//! ```ignore
+//! let mut hotkeys = GlobalHotkeys::new(backend);
+//! let alt = KeyModifiers { shift: false, ctrl: false, alt: true, logo: false };
+//! let summon = hotkeys.register(Hotkey::new(KeyInput::Space, alt))?;
+//! app.u_aux.global_hotkeys = Some(hotkeys);
+//! ```
+
+use {
+    crate::{base, error::HotkeyError},
+    reclutch::{event::RcEventQueue, prelude::*},
+};
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+use {
+    std::{
+        os::raw::{c_int, c_uint},
+        sync::atomic::{AtomicU8, Ordering},
+    },
+    x11_dl::{keysym, xlib},
+};
+
+/// Key combination of a global hotkey.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub key: base::KeyInput,
+    pub modifiers: base::KeyModifiers,
+}
+
+impl Hotkey {
+    /// Creates a hotkey pressing `key` while holding `modifiers`.
+    pub fn new(key: base::KeyInput, modifiers: base::KeyModifiers) -> Self {
+        Hotkey { key, modifiers }
+    }
+}
+
+/// Integrates the global hotkey API of a platform.
+pub trait HotkeyBackend {
+    /// Registers a hotkey with the platform, to be reported as `id` by `poll`.
+    fn register(&mut self, id: base::HotkeyId, hotkey: &Hotkey) -> Result<(), HotkeyError>;
+    /// Unregisters a hotkey registered as `id`.
+    fn unregister(&mut self, id: base::HotkeyId);
+    /// Returns the next hotkey pressed since the last poll, if any.
+    fn poll(&mut self) -> Option<base::HotkeyId>;
+}
+
+/// Global hotkeys of the application, registered with a `HotkeyBackend` and forwarded into the window queue.
+///
+/// Every hotkey is unregistered once this is dropped.
+pub struct GlobalHotkeys {
+    backend: Box<dyn HotkeyBackend>,
+    hotkeys: Vec<(base::HotkeyId, Hotkey)>,
+    next_id: u64,
+}
+
+impl GlobalHotkeys {
+    /// Creates a registry of global hotkeys, without any hotkeys.
+    pub fn new(backend: Box<dyn HotkeyBackend>) -> Self {
+        GlobalHotkeys { backend, hotkeys: Vec::new(), next_id: 0 }
+    }
+
+    /// Registers a hotkey, returning its identifier in `WindowEvent::GlobalHotkey`.
+    ///
+    /// Fails if the hotkey is already registered (by this application, or another application if the platform
+    /// reports it), or if the platform doesn't support global hotkeys.
+    pub fn register(&mut self, hotkey: Hotkey) -> Result<base::HotkeyId, HotkeyError> {
+        if self.hotkeys.iter().any(|(_, registered)| *registered == hotkey) {
+            return Err(HotkeyError::AlreadyRegistered);
+        }
+
+        let id = base::HotkeyId(self.next_id);
+        self.backend.register(id, &hotkey)?;
+        self.next_id += 1;
+        self.hotkeys.push((id, hotkey));
+        Ok(id)
+    }
+
+    /// Unregisters a hotkey, returning its key combination if it was registered.
+    pub fn unregister(&mut self, id: base::HotkeyId) -> Option<Hotkey> {
+        let index = self.hotkeys.iter().position(|(registered, _)| *registered == id)?;
+        self.backend.unregister(id);
+        Some(self.hotkeys.remove(index).1)
+    }
+
+    /// Returns the key combination of a registered hotkey.
+    pub fn hotkey(&self, id: base::HotkeyId) -> Option<&Hotkey> {
+        self.hotkeys.iter().find(|(registered, _)| *registered == id).map(|(_, hotkey)| hotkey)
+    }
+
+    /// Returns the registered hotkeys, in order of registration.
+    pub fn hotkeys(&self) -> impl Iterator<Item = (base::HotkeyId, &Hotkey)> {
+        self.hotkeys.iter().map(|(id, hotkey)| (*id, hotkey))
+    }
+
+    /// Emits the hotkeys pressed since the last pump into `queue`, returning how many were emitted.
+    ///
+    /// Invoked every frame by the application.
+    pub fn pump(&mut self, queue: &RcEventQueue<base::WindowEvent>) -> usize {
+        let mut count = 0;
+        while let Some(id) = self.backend.poll() {
+            // Presses which raced with unregistering the hotkey are dropped.
+            if self.hotkey(id).is_some() {
+                queue.emit_owned(base::WindowEvent::GlobalHotkey(id));
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl Drop for GlobalHotkeys {
+    fn drop(&mut self) {
+        for (id, _) in self.hotkeys.drain(..) {
+            self.backend.unregister(id);
+        }
+    }
+}
+
+/// Error code of the last failed `XGrabKey` request, or zero.
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+static X11_GRAB_ERROR: AtomicU8 = AtomicU8::new(0);
+
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+unsafe extern "C" fn record_grab_error(
+    _display: *mut xlib::Display,
+    event: *mut xlib::XErrorEvent,
+) -> c_int {
+    // Major opcode of `XGrabKey` requests.
+    const X_GRAB_KEY: u8 = 33;
+
+    if (*event).request_code == X_GRAB_KEY {
+        X11_GRAB_ERROR.store((*event).error_code, Ordering::SeqCst);
+    }
+    0
+}
+
+/// Returns the keysym of a key, if X11 has one.
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+fn x11_keysym(key: base::KeyInput) -> Option<c_uint> {
+    use base::KeyInput::*;
+
+    Some(match key {
+        Key1 => keysym::XK_1,
+        Key2 => keysym::XK_2,
+        Key3 => keysym::XK_3,
+        Key4 => keysym::XK_4,
+        Key5 => keysym::XK_5,
+        Key6 => keysym::XK_6,
+        Key7 => keysym::XK_7,
+        Key8 => keysym::XK_8,
+        Key9 => keysym::XK_9,
+        Key0 => keysym::XK_0,
+        A => keysym::XK_a,
+        B => keysym::XK_b,
+        C => keysym::XK_c,
+        D => keysym::XK_d,
+        E => keysym::XK_e,
+        F => keysym::XK_f,
+        G => keysym::XK_g,
+        H => keysym::XK_h,
+        I => keysym::XK_i,
+        J => keysym::XK_j,
+        K => keysym::XK_k,
+        L => keysym::XK_l,
+        M => keysym::XK_m,
+        N => keysym::XK_n,
+        O => keysym::XK_o,
+        P => keysym::XK_p,
+        Q => keysym::XK_q,
+        R => keysym::XK_r,
+        S => keysym::XK_s,
+        T => keysym::XK_t,
+        U => keysym::XK_u,
+        V => keysym::XK_v,
+        W => keysym::XK_w,
+        X => keysym::XK_x,
+        Y => keysym::XK_y,
+        Z => keysym::XK_z,
+        Escape => keysym::XK_Escape,
+        F1 => keysym::XK_F1,
+        F2 => keysym::XK_F2,
+        F3 => keysym::XK_F3,
+        F4 => keysym::XK_F4,
+        F5 => keysym::XK_F5,
+        F6 => keysym::XK_F6,
+        F7 => keysym::XK_F7,
+        F8 => keysym::XK_F8,
+        F9 => keysym::XK_F9,
+        F10 => keysym::XK_F10,
+        F11 => keysym::XK_F11,
+        F12 => keysym::XK_F12,
+        F13 => keysym::XK_F13,
+        F14 => keysym::XK_F14,
+        F15 => keysym::XK_F15,
+        F16 => keysym::XK_F16,
+        F17 => keysym::XK_F17,
+        F18 => keysym::XK_F18,
+        F19 => keysym::XK_F19,
+        F20 => keysym::XK_F20,
+        F21 => keysym::XK_F21,
+        F22 => keysym::XK_F22,
+        F23 => keysym::XK_F23,
+        F24 => keysym::XK_F24,
+        Snapshot => keysym::XK_Print,
+        Scroll => keysym::XK_Scroll_Lock,
+        Pause => keysym::XK_Pause,
+        Insert => keysym::XK_Insert,
+        Home => keysym::XK_Home,
+        Delete => keysym::XK_Delete,
+        End => keysym::XK_End,
+        PageDown => keysym::XK_Page_Down,
+        PageUp => keysym::XK_Page_Up,
+        Left => keysym::XK_Left,
+        Up => keysym::XK_Up,
+        Right => keysym::XK_Right,
+        Down => keysym::XK_Down,
+        Back => keysym::XK_BackSpace,
+        Return => keysym::XK_Return,
+        Space => keysym::XK_space,
+        Compose => keysym::XK_Multi_key,
+        Caret => keysym::XK_asciicircum,
+        Numlock => keysym::XK_Num_Lock,
+        Numpad0 => keysym::XK_KP_0,
+        Numpad1 => keysym::XK_KP_1,
+        Numpad2 => keysym::XK_KP_2,
+        Numpad3 => keysym::XK_KP_3,
+        Numpad4 => keysym::XK_KP_4,
+        Numpad5 => keysym::XK_KP_5,
+        Numpad6 => keysym::XK_KP_6,
+        Numpad7 => keysym::XK_KP_7,
+        Numpad8 => keysym::XK_KP_8,
+        Numpad9 => keysym::XK_KP_9,
+        Add => keysym::XK_KP_Add,
+        Apostrophe => keysym::XK_apostrophe,
+        Apps => keysym::XK_Menu,
+        At => keysym::XK_at,
+        Backslash => keysym::XK_backslash,
+        Calculator => keysym::XF86XK_Calculator,
+        Capital => keysym::XK_Caps_Lock,
+        Colon => keysym::XK_colon,
+        Comma => keysym::XK_comma,
+        Decimal => keysym::XK_KP_Decimal,
+        Divide => keysym::XK_KP_Divide,
+        Equals => keysym::XK_equal,
+        Grave => keysym::XK_grave,
+        LAlt => keysym::XK_Alt_L,
+        LBracket => keysym::XK_bracketleft,
+        LControl => keysym::XK_Control_L,
+        LShift => keysym::XK_Shift_L,
+        LWin => keysym::XK_Super_L,
+        Mail => keysym::XF86XK_Mail,
+        MediaStop => keysym::XF86XK_AudioStop,
+        Minus => keysym::XK_minus,
+        Multiply => keysym::XK_KP_Multiply,
+        Mute => keysym::XF86XK_AudioMute,
+        NextTrack => keysym::XF86XK_AudioNext,
+        NumpadEnter => keysym::XK_KP_Enter,
+        NumpadEquals => keysym::XK_KP_Equal,
+        Period => keysym::XK_period,
+        PlayPause => keysym::XF86XK_AudioPlay,
+        PrevTrack => keysym::XF86XK_AudioPrev,
+        RAlt => keysym::XK_Alt_R,
+        RBracket => keysym::XK_bracketright,
+        RControl => keysym::XK_Control_R,
+        RShift => keysym::XK_Shift_R,
+        RWin => keysym::XK_Super_R,
+        Semicolon => keysym::XK_semicolon,
+        Slash => keysym::XK_slash,
+        Sleep => keysym::XF86XK_Sleep,
+        Subtract => keysym::XK_KP_Subtract,
+        Sysrq => keysym::XK_Sys_Req,
+        Tab => keysym::XK_Tab,
+        Underline => keysym::XK_underscore,
+        VolumeDown => keysym::XF86XK_AudioLowerVolume,
+        VolumeUp => keysym::XF86XK_AudioRaiseVolume,
+        WebBack => keysym::XF86XK_Back,
+        WebFavorites => keysym::XF86XK_Favorites,
+        WebForward => keysym::XF86XK_Forward,
+        WebHome => keysym::XF86XK_HomePage,
+        WebRefresh => keysym::XF86XK_Refresh,
+        WebSearch => keysym::XF86XK_Search,
+        WebStop => keysym::XF86XK_Stop,
+        Copy => keysym::XF86XK_Copy,
+        Paste => keysym::XF86XK_Paste,
+        Cut => keysym::XF86XK_Cut,
+        _ => return None,
+    })
+}
+
+/// Grabs hotkeys with `XGrabKey` on the root window of a connection of its own to the X server.
+///
+/// The connection is opened through `libX11` at runtime, so applications built with this still start without X11
+/// (e.g. on Wayland-only sessions, where `X11Hotkeys::new` fails with `HotkeyError::Unsupported`).
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+pub struct X11Hotkeys {
+    xlib: xlib::Xlib,
+    display: *mut xlib::Display,
+    root: xlib::Window,
+    // Registered hotkeys, as key codes and modifier masks.
+    grabs: Vec<(base::HotkeyId, c_int, c_uint)>,
+}
+
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+impl X11Hotkeys {
+    /// Modifiers which don't affect whether a hotkey matches, i.e. Caps Lock and Num Lock.
+    const IGNORED_MODIFIERS: [c_uint; 4] =
+        [0, xlib::LockMask, xlib::Mod2Mask, xlib::LockMask | xlib::Mod2Mask];
+    const MODIFIERS: c_uint = xlib::ShiftMask | xlib::ControlMask | xlib::Mod1Mask | xlib::Mod4Mask;
+
+    /// Connects to the X server of the `DISPLAY` environment variable.
+    pub fn new() -> Result<Self, HotkeyError> {
+        let xlib = xlib::Xlib::open().map_err(|_| HotkeyError::Unsupported)?;
+        let display = unsafe { (xlib.XOpenDisplay)(std::ptr::null()) };
+        if display.is_null() {
+            return Err(HotkeyError::Unsupported);
+        }
+        let root = unsafe { (xlib.XDefaultRootWindow)(display) };
+
+        Ok(X11Hotkeys { xlib, display, root, grabs: Vec::new() })
+    }
+
+    fn ungrab(&self, keycode: c_int, modifiers: c_uint) {
+        for ignored in &Self::IGNORED_MODIFIERS {
+            unsafe {
+                (self.xlib.XUngrabKey)(self.display, keycode, modifiers | ignored, self.root)
+            };
+        }
+    }
+}
+
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+impl HotkeyBackend for X11Hotkeys {
+    fn register(&mut self, id: base::HotkeyId, hotkey: &Hotkey) -> Result<(), HotkeyError> {
+        let keysym = x11_keysym(hotkey.key).ok_or_else(|| {
+            HotkeyError::PlatformError(format!("{:?} has no X11 key symbol", hotkey.key))
+        })?;
+        let keycode = unsafe { (self.xlib.XKeysymToKeycode)(self.display, keysym as _) } as c_int;
+        if keycode == 0 {
+            return Err(HotkeyError::PlatformError(format!(
+                "{:?} isn't on the keyboard layout",
+                hotkey.key
+            )));
+        }
+
+        let mut modifiers = 0;
+        for (held, mask) in &[
+            (hotkey.modifiers.shift, xlib::ShiftMask),
+            (hotkey.modifiers.ctrl, xlib::ControlMask),
+            (hotkey.modifiers.alt, xlib::Mod1Mask),
+            (hotkey.modifiers.logo, xlib::Mod4Mask),
+        ] {
+            if *held {
+                modifiers |= mask;
+            }
+        }
+
+        // Grab errors are reported asynchronously, so they're caught by a temporary error handler after syncing.
+        X11_GRAB_ERROR.store(0, Ordering::SeqCst);
+        let error = unsafe {
+            let previous_handler = (self.xlib.XSetErrorHandler)(Some(record_grab_error));
+            for ignored in &Self::IGNORED_MODIFIERS {
+                (self.xlib.XGrabKey)(
+                    self.display,
+                    keycode,
+                    modifiers | ignored,
+                    self.root,
+                    xlib::False,
+                    xlib::GrabModeAsync,
+                    xlib::GrabModeAsync,
+                );
+            }
+            (self.xlib.XSync)(self.display, xlib::False);
+            (self.xlib.XSetErrorHandler)(previous_handler);
+            X11_GRAB_ERROR.load(Ordering::SeqCst)
+        };
+
+        match error {
+            0 => {
+                self.grabs.push((id, keycode, modifiers));
+                Ok(())
+            }
+            error => {
+                self.ungrab(keycode, modifiers);
+                if error == xlib::BadAccess {
+                    // Another application grabbed the hotkey.
+                    Err(HotkeyError::AlreadyRegistered)
+                } else {
+                    Err(HotkeyError::PlatformError(format!(
+                        "XGrabKey failed with error code {}",
+                        error
+                    )))
+                }
+            }
+        }
+    }
+
+    fn unregister(&mut self, id: base::HotkeyId) {
+        if let Some(index) = self.grabs.iter().position(|(grabbed, _, _)| *grabbed == id) {
+            let (_, keycode, modifiers) = self.grabs.remove(index);
+            self.ungrab(keycode, modifiers);
+            unsafe { (self.xlib.XFlush)(self.display) };
+        }
+    }
+
+    fn poll(&mut self) -> Option<base::HotkeyId> {
+        while unsafe { (self.xlib.XPending)(self.display) } > 0 {
+            let mut event = xlib::XEvent { pad: [0; 24] };
+            unsafe { (self.xlib.XNextEvent)(self.display, &mut event) };
+
+            if event.get_type() == xlib::KeyPress {
+                let event = xlib::XKeyEvent::from(event);
+                let modifiers = event.state & Self::MODIFIERS;
+                let pressed = self.grabs.iter().find(|(_, keycode, grabbed)| {
+                    *keycode == event.keycode as c_int && *grabbed == modifiers
+                });
+                if let Some((id, _, _)) = pressed {
+                    return Some(*id);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(all(feature = "global-hotkeys-x11", unix, not(target_os = "macos")))]
+impl Drop for X11Hotkeys {
+    fn drop(&mut self) {
+        unsafe { (self.xlib.XCloseDisplay)(self.display) };
+    }
+}
@@ -0,0 +1,329 @@
+//! Notifications with buttons whose activation is routed back into the application, and the backend trait
+//! through which they're posted to the OS.
+//!
+//! Notifications are posted to the OS by a `NotificationBackend`, which integrates a platform API.
+//! `FreedesktopNotifications` posts them to an `org.freedesktop.Notifications` server (Linux and BSD desktops)
+//! and is enabled with the `notifications-freedesktop` feature; other platforms (e.g. the notification center
+//! of macOS) have no backend yet, so their notifications are shown within the application.
+//! Everything else here is platform-independent and only enabled with the `notifications` feature.
+//!
+//! Notifications which can't be posted to the OS (because there's no backend, or the backend failed) fall back
+//! to being shown within the application: they're kept in `Notifications::in_app` and announced with
+//! `NotificationEvent::InApp`, so that the application can show them (e.g. as toasts) and report their actions
+//! with `Notifications::invoke_action` and `Notifications::dismiss`.
+//!
+//! Everything the user does with a notification is emitted as `WindowEvent::Notification`:
+//!
+//! ```ignore
+//! let id = app::notify(
+//!     &mut u_aux,
+//!     "Download complete",
+//!     "report.pdf was saved to Downloads",
+//!     vec![NotificationAction::new("open", "Open")],
+//! );
+//! ```
+
+use {
+    super::UAux,
+    crate::{
+        base::{self, NotificationEvent, NotificationId},
+        error::NotificationError,
+    },
+    reclutch::{event::RcEventQueue, prelude::*},
+};
+
+/// Button of a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NotificationAction {
+    /// Identifies the action in `NotificationEvent::ActionInvoked`.
+    pub id: String,
+    pub label: String,
+}
+
+impl NotificationAction {
+    /// Creates an action labelled `label`.
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        NotificationAction { id: id.into(), label: label.into() }
+    }
+}
+
+/// Contents of a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    pub actions: Vec<NotificationAction>,
+}
+
+/// Integrates the notification API of a platform.
+pub trait NotificationBackend {
+    /// Posts a notification, whose events are to be reported as `id` by `poll`.
+    fn post(
+        &mut self,
+        id: NotificationId,
+        notification: &Notification,
+    ) -> Result<(), NotificationError>;
+    /// Withdraws a posted notification, if it's still shown.
+    fn withdraw(&mut self, id: NotificationId);
+    /// Returns the next event of a posted notification since the last poll, if any.
+    fn poll(&mut self) -> Option<NotificationEvent>;
+}
+
+/// Notifications of the application, posted to a `NotificationBackend` or shown within the application.
+pub struct Notifications {
+    backend: Option<Box<dyn NotificationBackend>>,
+    in_app: Vec<(NotificationId, Notification)>,
+    pending: Vec<NotificationEvent>,
+    next_id: u64,
+}
+
+impl Notifications {
+    /// Creates notifications posted to `backend`.
+    pub fn new(backend: Box<dyn NotificationBackend>) -> Self {
+        Notifications { backend: Some(backend), ..Notifications::in_app_only() }
+    }
+
+    /// Creates notifications which are always shown within the application.
+    pub fn in_app_only() -> Self {
+        Notifications { backend: None, in_app: Vec::new(), pending: Vec::new(), next_id: 0 }
+    }
+
+    /// Posts a notification to the OS, falling back to showing it within the application if that fails.
+    pub fn post(&mut self, notification: Notification) -> NotificationId {
+        let id = NotificationId(self.next_id);
+        self.next_id += 1;
+
+        let posted = match &mut self.backend {
+            Some(backend) => backend.post(id, &notification).is_ok(),
+            None => false,
+        };
+        if !posted {
+            self.in_app.push((id, notification));
+            self.pending.push(NotificationEvent::InApp(id));
+        }
+
+        id
+    }
+
+    /// Returns the notifications shown within the application, from the oldest to the newest.
+    pub fn in_app(&self) -> impl Iterator<Item = (NotificationId, &Notification)> {
+        self.in_app.iter().map(|(id, notification)| (*id, notification))
+    }
+
+    /// Reports that the user invoked an action of a notification shown within the application, which is then removed.
+    ///
+    /// Does nothing if the notification isn't shown within the application.
+    pub fn invoke_action(&mut self, id: NotificationId, action: impl Into<String>) {
+        if self.remove_in_app(id) {
+            self.pending
+                .push(NotificationEvent::ActionInvoked { notification: id, action: action.into() });
+        }
+    }
+
+    /// Removes a notification, whether it's posted to the OS or shown within the application.
+    ///
+    /// Notifications shown within the application emit `NotificationEvent::Dismissed`;
+    /// the backend reports whether notifications it withdraws emit it as well.
+    pub fn dismiss(&mut self, id: NotificationId) {
+        if self.remove_in_app(id) {
+            self.pending.push(NotificationEvent::Dismissed(id));
+        } else if let Some(backend) = &mut self.backend {
+            backend.withdraw(id);
+        }
+    }
+
+    fn remove_in_app(&mut self, id: NotificationId) -> bool {
+        let len = self.in_app.len();
+        self.in_app.retain(|(shown, _)| *shown != id);
+        self.in_app.len() != len
+    }
+
+    /// Emits the notification events since the last pump into `queue`, returning how many were emitted.
+    ///
+    /// Invoked every frame by the application.
+    pub fn pump(&mut self, queue: &RcEventQueue<base::WindowEvent>) -> usize {
+        if let Some(backend) = &mut self.backend {
+            while let Some(event) = backend.poll() {
+                self.pending.push(event);
+            }
+        }
+
+        let count = self.pending.len();
+        for event in self.pending.drain(..) {
+            queue.emit_owned(base::WindowEvent::Notification(event));
+        }
+        count
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Notifications::in_app_only()
+    }
+}
+
+/// Posts a notification through `UAux::notifications`, falling back to showing it within the application
+/// (installing in-app notifications if there are none yet).
+pub fn notify(
+    u_aux: &mut UAux,
+    title: impl Into<String>,
+    body: impl Into<String>,
+    actions: Vec<NotificationAction>,
+) -> NotificationId {
+    u_aux.notifications.get_or_insert_with(Notifications::in_app_only).post(Notification {
+        title: title.into(),
+        body: body.into(),
+        actions,
+    })
+}
+
+/// Action key which the notification server invokes when the notification itself is clicked.
+#[cfg(all(feature = "notifications-freedesktop", unix, not(target_os = "macos")))]
+const FREEDESKTOP_DEFAULT_ACTION: &str = "default";
+
+/// Signal of the notification server, identifying the notification by its server ID.
+#[cfg(all(feature = "notifications-freedesktop", unix, not(target_os = "macos")))]
+enum FreedesktopSignal {
+    ActionInvoked(u32, String),
+    Closed(u32),
+}
+
+/// Posts notifications to an `org.freedesktop.Notifications` server over the D-Bus session bus
+/// (as provided by most Linux and BSD desktops).
+#[cfg(all(feature = "notifications-freedesktop", unix, not(target_os = "macos")))]
+pub struct FreedesktopNotifications {
+    proxy: zbus::blocking::Proxy<'static>,
+    app_name: String,
+    // Notifications which are still shown, with the ID the server assigned to them.
+    shown: Vec<(NotificationId, u32)>,
+    signals: std::sync::mpsc::Receiver<FreedesktopSignal>,
+}
+
+#[cfg(all(feature = "notifications-freedesktop", unix, not(target_os = "macos")))]
+impl FreedesktopNotifications {
+    /// Connects to the notification server of the session, posting notifications on behalf of `app_name`.
+    pub fn new(app_name: impl Into<String>) -> Result<Self, NotificationError> {
+        let platform_error =
+            |error: zbus::Error| NotificationError::PlatformError(error.to_string());
+
+        let connection = zbus::blocking::Connection::session().map_err(platform_error)?;
+        let proxy = zbus::blocking::Proxy::new(
+            &connection,
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications",
+        )
+        .map_err(platform_error)?;
+
+        // Signals are received on a thread of their own, since receiving them blocks.
+        let incoming = proxy.receive_all_signals().map_err(platform_error)?;
+        let (sender, signals) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for message in incoming {
+                let header = message.header();
+                let body = message.body();
+                let signal = match header.member().map(|member| member.as_str()) {
+                    Some("ActionInvoked") => body
+                        .deserialize::<(u32, String)>()
+                        .ok()
+                        .map(|(id, action)| FreedesktopSignal::ActionInvoked(id, action)),
+                    Some("NotificationClosed") => body
+                        .deserialize::<(u32, u32)>()
+                        .ok()
+                        .map(|(id, _reason)| FreedesktopSignal::Closed(id)),
+                    _ => None,
+                };
+
+                if let Some(signal) = signal {
+                    if sender.send(signal).is_err() {
+                        // The backend was dropped.
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(FreedesktopNotifications {
+            proxy,
+            app_name: app_name.into(),
+            shown: Vec::new(),
+            signals,
+        })
+    }
+
+    fn take_shown(&mut self, server_id: u32) -> Option<NotificationId> {
+        let index = self.shown.iter().position(|(_, shown)| *shown == server_id)?;
+        Some(self.shown.remove(index).0)
+    }
+}
+
+#[cfg(all(feature = "notifications-freedesktop", unix, not(target_os = "macos")))]
+impl NotificationBackend for FreedesktopNotifications {
+    fn post(
+        &mut self,
+        id: NotificationId,
+        notification: &Notification,
+    ) -> Result<(), NotificationError> {
+        // Actions are passed as a flat list of keys and labels.
+        let mut actions = vec![FREEDESKTOP_DEFAULT_ACTION, ""];
+        for action in &notification.actions {
+            actions.push(&action.id);
+            actions.push(&action.label);
+        }
+        let hints = std::collections::HashMap::<&str, zbus::zvariant::Value>::new();
+
+        let server_id: u32 = self
+            .proxy
+            .call(
+                "Notify",
+                &(
+                    &self.app_name,
+                    0u32,
+                    "",
+                    &notification.title,
+                    &notification.body,
+                    actions,
+                    hints,
+                    // The server decides when the notification expires.
+                    -1i32,
+                ),
+            )
+            .map_err(|error| NotificationError::PlatformError(error.to_string()))?;
+
+        self.shown.push((id, server_id));
+        Ok(())
+    }
+
+    fn withdraw(&mut self, id: NotificationId) {
+        if let Some(&(_, server_id)) = self.shown.iter().find(|(shown, _)| *shown == id) {
+            // The server emits `NotificationClosed` in turn.
+            let _ = self.proxy.call::<_, _, ()>("CloseNotification", &(server_id,));
+        }
+    }
+
+    fn poll(&mut self) -> Option<NotificationEvent> {
+        // Signals of other applications' notifications (or of notifications already closed) are skipped.
+        while let Ok(signal) = self.signals.try_recv() {
+            let event = match signal {
+                FreedesktopSignal::ActionInvoked(server_id, action) => {
+                    // The notification is closed once an action is invoked, which isn't reported as a dismissal.
+                    self.take_shown(server_id).map(|id| {
+                        if action == FREEDESKTOP_DEFAULT_ACTION {
+                            NotificationEvent::Clicked(id)
+                        } else {
+                            NotificationEvent::ActionInvoked { notification: id, action }
+                        }
+                    })
+                }
+                FreedesktopSignal::Closed(server_id) => {
+                    self.take_shown(server_id).map(NotificationEvent::Dismissed)
+                }
+            };
+
+            if event.is_some() {
+                return event;
+            }
+        }
+        None
+    }
+}
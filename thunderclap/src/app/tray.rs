@@ -0,0 +1,432 @@
+//! System tray (or status bar) icon with a menu, for applications which keep running in the background,
+//! and the backend trait through which it's shown.
+//!
+//! The icon and its menu are described with `TrayIcon` and `TrayMenu`, and shown by a `TrayBackend` which integrates
+//! the platform's tray API. Each backend has a feature of its own, on top of the platform-independent `tray` feature:
+//! - `tray-freedesktop`: `FreedesktopTray`, a `StatusNotifierItem` for Linux and BSD desktops.
+//!
+//! The notification area of Windows and `NSStatusItem` of macOS don't have a backend yet.
+//!
+//! A `Tray` is installed into `UAux::tray`, after which the application pumps it every frame:
+//! - Changes to the icon, tooltip or menu are handed to the backend once per frame.
+//! - The menu is styled after the color scheme of the current theme, and restyled when the theme is switched.
+//! - The user activating the icon or a menu item emits `WindowEvent::TrayActivated` into the window queue.
+//!
+//! ```ignore
+//! let mut tray = Tray::new(backend, TrayIcon::new(pixels, (32, 32)));
+//! tray.set_menu(TrayMenu::new().item("show", "Show window").separator().item("quit", "Quit"));
+//! app.u_aux.tray = Some(tray);
+//! ```
+
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+use crate::error::TrayError;
+use {
+    crate::{base, draw},
+    reclutch::{display::Color, event::RcEventQueue, prelude::*},
+    std::sync::Arc,
+};
+
+/// Image of a tray icon, as 8-bit RGBA pixels.
+///
+/// Cloning an icon is cheap, as the pixels are shared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrayIcon {
+    pub pixels: Arc<Vec<u8>>,
+    /// Width and height of the icon, in pixels.
+    pub size: (u32, u32),
+}
+
+impl TrayIcon {
+    /// Creates an icon from 8-bit RGBA pixels.
+    pub fn new(pixels: impl Into<Arc<Vec<u8>>>, size: (u32, u32)) -> Self {
+        TrayIcon { pixels: pixels.into(), size }
+    }
+}
+
+/// Entry of a `TrayMenu`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayMenuItem {
+    /// Item which can be activated, identified by `id` in `base::TrayActivation::MenuItem`.
+    Action {
+        id: String,
+        label: String,
+        enabled: bool,
+        /// Whether the item shows a check mark, or `None` if it isn't checkable.
+        checked: Option<bool>,
+    },
+    /// Line separating groups of items.
+    Separator,
+    /// Item opening a nested menu.
+    Submenu { label: String, enabled: bool, menu: TrayMenu },
+}
+
+/// Menu shown by a tray icon (typically when it's right-clicked).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrayMenu {
+    pub items: Vec<TrayMenuItem>,
+}
+
+impl TrayMenu {
+    /// Creates an empty menu.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends an enabled action.
+    pub fn item(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.items.push(TrayMenuItem::Action {
+            id: id.into(),
+            label: label.into(),
+            enabled: true,
+            checked: None,
+        });
+        self
+    }
+
+    /// Appends an enabled, checkable action.
+    pub fn check_item(
+        mut self,
+        id: impl Into<String>,
+        label: impl Into<String>,
+        checked: bool,
+    ) -> Self {
+        self.items.push(TrayMenuItem::Action {
+            id: id.into(),
+            label: label.into(),
+            enabled: true,
+            checked: Some(checked),
+        });
+        self
+    }
+
+    /// Appends a separator.
+    pub fn separator(mut self) -> Self {
+        self.items.push(TrayMenuItem::Separator);
+        self
+    }
+
+    /// Appends an enabled submenu.
+    pub fn submenu(mut self, label: impl Into<String>, menu: TrayMenu) -> Self {
+        self.items.push(TrayMenuItem::Submenu { label: label.into(), enabled: true, menu });
+        self
+    }
+
+    /// Returns the action identified by `id`, searching submenus as well (e.g. to toggle its check mark).
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut TrayMenuItem> {
+        for item in &mut self.items {
+            match item {
+                TrayMenuItem::Action { id: item_id, .. } if item_id == id => return Some(item),
+                TrayMenuItem::Submenu { menu, .. } => {
+                    if let Some(item) = menu.find_mut(id) {
+                        return Some(item);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Colors of a tray menu, derived from the color scheme of the theme.
+///
+/// Platforms which don't support custom menu colors (e.g. macOS) ignore the style.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrayMenuStyle {
+    pub background: Color,
+    pub foreground: Color,
+    /// Background of the item under the cursor.
+    pub highlight: Color,
+    /// Foreground of the item under the cursor.
+    pub over_highlight: Color,
+}
+
+impl TrayMenuStyle {
+    /// Derives the style from the color scheme of a theme.
+    pub fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let scheme = theme.data().scheme;
+        TrayMenuStyle {
+            background: scheme.control_outset,
+            foreground: scheme.over_control_outset,
+            highlight: scheme.primary,
+            over_highlight: scheme.over_primary,
+        }
+    }
+}
+
+/// Integrates the tray API of a platform.
+pub trait TrayBackend {
+    /// Shows the icon in the tray, or replaces the shown icon.
+    fn set_icon(&mut self, icon: &TrayIcon);
+    /// Sets the text shown when hovering the icon.
+    fn set_tooltip(&mut self, tooltip: &str);
+    /// Sets the menu of the icon (and its style), or removes the menu if `menu` is empty.
+    fn set_menu(&mut self, menu: &TrayMenu, style: &TrayMenuStyle);
+    /// Returns the next activation since the last poll, if any.
+    fn poll(&mut self) -> Option<base::TrayActivation>;
+    /// Removes the icon from the tray; invoked once the tray is dropped.
+    fn remove(&mut self);
+}
+
+/// Tray icon of the application, handing changes to a `TrayBackend` and forwarding activations into the window queue.
+pub struct Tray {
+    backend: Box<dyn TrayBackend>,
+    icon: TrayIcon,
+    tooltip: String,
+    menu: TrayMenu,
+    style: Option<TrayMenuStyle>,
+    icon_changed: bool,
+    tooltip_changed: bool,
+    menu_changed: bool,
+}
+
+impl Tray {
+    /// Creates a tray icon without a tooltip or menu, shown by `backend` once the application pumps it.
+    pub fn new(backend: Box<dyn TrayBackend>, icon: TrayIcon) -> Self {
+        Tray {
+            backend,
+            icon,
+            tooltip: String::new(),
+            menu: TrayMenu::new(),
+            style: None,
+            icon_changed: true,
+            tooltip_changed: false,
+            menu_changed: false,
+        }
+    }
+
+    /// Returns the icon.
+    #[inline]
+    pub fn icon(&self) -> &TrayIcon {
+        &self.icon
+    }
+
+    /// Replaces the icon (e.g. to reflect a status).
+    pub fn set_icon(&mut self, icon: TrayIcon) {
+        if icon != self.icon {
+            self.icon = icon;
+            self.icon_changed = true;
+        }
+    }
+
+    /// Returns the tooltip.
+    #[inline]
+    pub fn tooltip(&self) -> &str {
+        &self.tooltip
+    }
+
+    /// Sets the text shown when hovering the icon.
+    pub fn set_tooltip(&mut self, tooltip: impl Into<String>) {
+        let tooltip = tooltip.into();
+        if tooltip != self.tooltip {
+            self.tooltip = tooltip;
+            self.tooltip_changed = true;
+        }
+    }
+
+    /// Returns the menu.
+    #[inline]
+    pub fn menu(&self) -> &TrayMenu {
+        &self.menu
+    }
+
+    /// Returns the menu mutably; it's handed to the backend again in the next pump.
+    pub fn menu_mut(&mut self) -> &mut TrayMenu {
+        self.menu_changed = true;
+        &mut self.menu
+    }
+
+    /// Replaces the menu.
+    pub fn set_menu(&mut self, menu: TrayMenu) {
+        self.menu = menu;
+        self.menu_changed = true;
+    }
+
+    /// Hands the pending changes to the backend, styling the menu after `theme`,
+    /// then emits the activations since the last pump into `queue`.
+    ///
+    /// Invoked every frame by the application.
+    pub fn pump(
+        &mut self,
+        queue: &RcEventQueue<base::WindowEvent>,
+        theme: Option<&dyn draw::Theme>,
+    ) -> usize {
+        if let Some(style) = theme.map(TrayMenuStyle::from_theme) {
+            if self.style != Some(style) {
+                self.style = Some(style);
+                self.menu_changed = true;
+            }
+        }
+
+        if self.icon_changed {
+            self.icon_changed = false;
+            self.backend.set_icon(&self.icon);
+        }
+        if self.tooltip_changed {
+            self.tooltip_changed = false;
+            self.backend.set_tooltip(&self.tooltip);
+        }
+        if self.menu_changed {
+            if let Some(style) = &self.style {
+                self.menu_changed = false;
+                self.backend.set_menu(&self.menu, style);
+            }
+        }
+
+        let mut count = 0;
+        while let Some(activation) = self.backend.poll() {
+            queue.emit_owned(base::WindowEvent::TrayActivated(activation));
+            count += 1;
+        }
+        count
+    }
+}
+
+impl Drop for Tray {
+    fn drop(&mut self) {
+        self.backend.remove();
+    }
+}
+
+/// State of a `StatusNotifierItem`, read by the `ksni` service which exports it.
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+struct StatusNotifierItem {
+    id: String,
+    icon: Option<ksni::Icon>,
+    tooltip: String,
+    menu: TrayMenu,
+    activations: std::sync::mpsc::Sender<base::TrayActivation>,
+}
+
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+impl StatusNotifierItem {
+    fn build_menu(menu: &TrayMenu) -> Vec<ksni::MenuItem<Self>> {
+        menu.items
+            .iter()
+            .map(|item| match item {
+                TrayMenuItem::Action { id, label, enabled, checked } => {
+                    let id = id.clone();
+                    let activate = Box::new(move |this: &mut Self| {
+                        let _ = this.activations.send(base::TrayActivation::MenuItem(id.clone()));
+                    });
+                    match checked {
+                        Some(checked) => ksni::menu::CheckmarkItem {
+                            label: label.clone(),
+                            enabled: *enabled,
+                            checked: *checked,
+                            activate,
+                            ..Default::default()
+                        }
+                        .into(),
+                        None => ksni::menu::StandardItem {
+                            label: label.clone(),
+                            enabled: *enabled,
+                            activate,
+                            ..Default::default()
+                        }
+                        .into(),
+                    }
+                }
+                TrayMenuItem::Separator => ksni::MenuItem::Separator,
+                TrayMenuItem::Submenu { label, enabled, menu } => ksni::menu::SubMenu {
+                    label: label.clone(),
+                    enabled: *enabled,
+                    submenu: Self::build_menu(menu),
+                    ..Default::default()
+                }
+                .into(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+impl ksni::Tray for StatusNotifierItem {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let _ = self.activations.send(base::TrayActivation::Icon);
+    }
+
+    fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+        self.icon.iter().cloned().collect()
+    }
+
+    fn tool_tip(&self) -> ksni::ToolTip {
+        ksni::ToolTip { title: self.tooltip.clone(), ..Default::default() }
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        Self::build_menu(&self.menu)
+    }
+}
+
+/// Shows the tray icon as a `StatusNotifierItem` over the D-Bus session bus, which is supported by
+/// KDE Plasma and by most other Linux desktops (GNOME through an extension).
+///
+/// The menu is rendered by the desktop, so it's never styled after the theme.
+/// Desktops don't report double-clicks, so `TrayActivation::IconDoubleClick` is never emitted.
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+pub struct FreedesktopTray {
+    handle: ksni::blocking::Handle<StatusNotifierItem>,
+    activations: std::sync::mpsc::Receiver<base::TrayActivation>,
+}
+
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+impl FreedesktopTray {
+    /// Registers a tray item identified by `id` (typically the name of the application) with the desktop.
+    pub fn new(id: impl Into<String>) -> Result<Self, TrayError> {
+        use ksni::blocking::TrayMethods;
+
+        let (sender, activations) = std::sync::mpsc::channel();
+        let handle = StatusNotifierItem {
+            id: id.into(),
+            icon: None,
+            tooltip: String::new(),
+            menu: TrayMenu::default(),
+            activations: sender,
+        }
+        .spawn()
+        .map_err(|error| match error {
+            ksni::Error::Dbus(error) => TrayError::PlatformError(error.to_string()),
+            // The desktop doesn't host `StatusNotifierItem`s.
+            _ => TrayError::Unsupported,
+        })?;
+
+        Ok(FreedesktopTray { handle, activations })
+    }
+}
+
+#[cfg(all(feature = "tray-freedesktop", unix, not(target_os = "macos")))]
+impl TrayBackend for FreedesktopTray {
+    fn set_icon(&mut self, icon: &TrayIcon) {
+        // The icon is expected as ARGB, in network byte order.
+        let data = icon
+            .pixels
+            .chunks_exact(4)
+            .flat_map(|rgba| [rgba[3], rgba[0], rgba[1], rgba[2]])
+            .collect();
+        let icon = ksni::Icon { width: icon.size.0 as _, height: icon.size.1 as _, data };
+        self.handle.update(move |item| item.icon = Some(icon));
+    }
+
+    fn set_tooltip(&mut self, tooltip: &str) {
+        let tooltip = tooltip.to_owned();
+        self.handle.update(move |item| item.tooltip = tooltip);
+    }
+
+    fn set_menu(&mut self, menu: &TrayMenu, _style: &TrayMenuStyle) {
+        let menu = menu.clone();
+        self.handle.update(move |item| item.menu = menu);
+    }
+
+    fn poll(&mut self) -> Option<base::TrayActivation> {
+        self.activations.try_recv().ok()
+    }
+
+    fn remove(&mut self) {
+        self.handle.shutdown().wait();
+    }
+}
@@ -1,17 +1,23 @@
 use {
-    crate::{draw, geom::*},
+    crate::{anim, bridge, clipboard, draw, geom::*, profiling, telemetry},
     reclutch::{
-        display::{Color, CommandGroup, DisplayClip, DisplayCommand, GraphicsDisplay, Rect, Size},
-        event::RcEventQueue,
+        display::{
+            Color, CommandGroup, CommandGroupHandle, DisplayClip, DisplayCommand, GraphicsDisplay,
+            Rect, ResourceDescriptor, ResourceReference, Size, Vector, ZOrder,
+        },
+        error,
+        event::{RcEventListener, RcEventQueue},
         prelude::*,
         verbgraph,
         widget::Widget,
     },
     std::{
-        cell::RefCell,
+        any::{Any, TypeId},
+        cell::{Cell, RefCell},
         collections::{HashMap, HashSet},
-        rc::Rc,
+        rc::{Rc, Weak},
         sync::Mutex,
+        time::{Duration, Instant},
     },
 };
 
@@ -79,6 +85,16 @@ macro_rules! lazy_propagate {
 /// #[widget_children_trait(thunderclap::base::WidgetChildren)]
 /// struct MyWidget;
 /// ```
+///
+/// Alternatively, `thunderclap::WidgetChildren` implements this trait by default,
+/// and also accepts collections of children (marked with `#[widget_children]`):
+/// ```ignore
+/// #[derive(thunderclap::WidgetChildren)]
+/// struct MyWidget<U, G> {
+///     #[widget_children]
+///     items: Vec<ButtonWidget<U, G>>,
+/// }
+/// ```
 pub trait WidgetChildren:
     Widget
     + draw::HasTheme
@@ -112,12 +128,82 @@ pub trait WidgetChildren:
     > {
         Vec::new()
     }
+
+    /// Returns the area which the children are confined to, if any (e.g. the visible area of scrolled content).
+    ///
+    /// Descendants which lie entirely outside this area are culled by `invoke_draw`.
+    #[inline]
+    fn viewport(&self) -> Option<AbsoluteRect> {
+        None
+    }
+
+    /// Returns the name of the widget type, without the module path or generic parameters (e.g. `"ButtonWidget"`).
+    #[inline]
+    fn widget_kind(&self) -> &'static str {
+        crate::telemetry::kind_name::<Self>()
+    }
+
+    /// Returns the widget as an accessible element, or `None` if it's transparent to assistive technologies
+    /// (in which case its children are exposed in its place).
+    ///
+    /// Implemented by `derive(WidgetChildren)` with the `#[widget_accessible]` attribute.
+    #[inline]
+    fn accessible(&self) -> Option<&dyn crate::a11y::Accessible> {
+        None
+    }
+
+    /// Returns the layer the widget (and its descendants) is drawn in; see `Layer`.
+    ///
+    /// Implemented by `derive(WidgetChildren)` from a field with the `#[widget_layer]` attribute.
+    #[inline]
+    fn layer(&self) -> Layer {
+        Layer::Base
+    }
+
+    /// Returns the ID which the widget can be found by (see `find_by_id`), if any.
+    ///
+    /// Implemented by `derive(WidgetChildren)` from an `Option<String>` field with the `#[widget_id]` attribute.
+    #[inline]
+    fn id(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Implemented by widgets that can be repainted.
 pub trait Repaintable: Widget {
     /// Repaints the widget (typically means invoking `repaint` on the inner command group).
     fn repaint(&mut self);
+
+    /// Returns `true` if the content of the widget itself (i.e. excluding children) will be repainted
+    /// the next time it's drawn, in which case its bounds are damaged (see `DamageRegion`).
+    ///
+    /// Implemented by `derive(Repaintable)` from the `#[repaint_target]` command groups.
+    /// The conservative default reports the widget as always dirty.
+    #[inline]
+    fn is_dirty(&self) -> bool {
+        true
+    }
+}
+
+/// Implemented by the fields marked with `#[repaint_target]` in `derive(Repaintable)`, i.e. command groups and
+/// child widgets, to implement `Repaintable::is_dirty`.
+pub trait RepaintTarget {
+    /// Returns `true` if the target will be repainted the next time it's drawn.
+    fn will_repaint(&self) -> bool;
+}
+
+impl RepaintTarget for CommandGroup {
+    #[inline]
+    fn will_repaint(&self) -> bool {
+        CommandGroup::will_repaint(self)
+    }
+}
+
+impl<T: Repaintable + ?Sized> RepaintTarget for T {
+    #[inline]
+    fn will_repaint(&self) -> bool {
+        self.is_dirty()
+    }
 }
 
 /// Implemented by widgets that can be moved/positioned.
@@ -208,6 +294,81 @@ pub trait UpdateAuxiliary: 'static {
     fn graphical(&self) -> &dyn GraphicalAuxiliary;
     /// Returns the respective graphical auxiliary mutably.
     fn graphical_mut(&mut self) -> &mut dyn GraphicalAuxiliary;
+    /// Returns the focus tracker, which manages keyboard focus order, immutably.
+    fn focus_tracker(&self) -> &FocusTracker;
+    /// Returns the focus tracker, which manages keyboard focus order, mutably.
+    fn focus_tracker_mut(&mut self) -> &mut FocusTracker;
+    /// Returns the timer manager, which schedules `WindowEvent::Timer` events, immutably.
+    fn timers(&self) -> &TimerManager;
+    /// Returns the timer manager, which schedules `WindowEvent::Timer` events, mutably.
+    fn timers_mut(&mut self) -> &mut TimerManager;
+    /// Returns the animator, which runs tweens of widget properties, immutably.
+    fn animator(&self) -> &anim::Animator;
+    /// Returns the animator, which runs tweens of widget properties, mutably.
+    fn animator_mut(&mut self) -> &mut anim::Animator;
+    /// Returns the event bus, through which widgets publish events to the subscribers of a topic, immutably.
+    fn event_bus(&self) -> &EventBus;
+    /// Returns the event bus, through which widgets publish events to the subscribers of a topic, mutably.
+    fn event_bus_mut(&mut self) -> &mut EventBus;
+    /// Requests the pointer to be locked (hidden and held in place, with movement reported through
+    /// `WindowEvent::MouseDelta`) or unlocked, e.g. for the duration of a scrub interaction.
+    ///
+    /// Implementations which don't support pointer locking can ignore this.
+    fn set_pointer_lock(&mut self, _locked: bool) {}
+    /// Returns `true` if the pointer is locked (or a lock has been requested).
+    fn pointer_locked(&self) -> bool {
+        false
+    }
+    /// Requests the theme of the whole widget tree to be switched (e.g. toggling between light and dark),
+    /// which the application applies before the next update (see `reload_theme`).
+    ///
+    /// Implementations which don't support switching themes can ignore this.
+    fn set_theme(&mut self, _theme: Box<dyn draw::Theme>) {}
+    /// Returns the current theme of the widget tree, if the application provides it,
+    /// e.g. to construct widgets after the widget tree itself has been constructed.
+    fn theme(&self) -> Option<Rc<dyn draw::Theme>> {
+        None
+    }
+    /// Hands an external event source (e.g. a `bridge::ChannelBridge`) to the application, which pumps it every frame
+    /// until it disconnects.
+    ///
+    /// Implementations which don't pump external sources drop the source.
+    fn add_external_source(&mut self, _source: Box<dyn bridge::ExternalSource>) {}
+    /// Returns the maximum time between the clicks of a double (or triple) click.
+    fn double_click_time(&self) -> Duration {
+        DOUBLE_CLICK_TIME
+    }
+    /// Returns the profiler which measurements (e.g. input latencies) are recorded to, if the application profiles itself.
+    fn profiler(&mut self) -> Option<&mut profiling::Profiler> {
+        None
+    }
+    /// Returns the sink which user interactions are recorded to, if the application has opted into telemetry.
+    fn telemetry_sink(&mut self) -> Option<&mut dyn telemetry::TelemetrySink> {
+        None
+    }
+    /// Returns the clipboard, if the application provides one.
+    fn clipboard(&mut self) -> Option<&mut dyn clipboard::Clipboard> {
+        None
+    }
+    /// Returns the pool which runs background work (e.g. asynchronous validation rules, see `validate`),
+    /// if the application provides one.
+    ///
+    /// Without a pool, such work runs in place, blocking the update.
+    fn task_pool(&self) -> Option<&bridge::TaskPool> {
+        None
+    }
+    /// Returns the scripting engine which script handlers are dispatched to, if any.
+    #[cfg(feature = "scripting")]
+    fn script_host(&mut self) -> Option<&mut dyn crate::script::ScriptHost> {
+        None
+    }
+    /// Returns the widget which receives the mouse event currently being dispatched, if the application
+    /// resolves it (see `PointerTarget`).
+    ///
+    /// Without a target, every widget handles mouse events within its bounds, even where it's occluded.
+    fn pointer_target(&self) -> Option<&PointerTarget> {
+        None
+    }
 }
 
 /// Trait required for any type passed as the `GraphicalAux` type (seen as `G` in the widget type parameters)
@@ -215,6 +376,21 @@ pub trait UpdateAuxiliary: 'static {
 pub trait GraphicalAuxiliary: 'static {
     /// Returns the HiDPI scaling factor.
     fn scaling(&self) -> f32;
+
+    /// Returns `true` if the display supports backdrop filters (i.e. filtering what's already drawn beneath a widget).
+    /// Widgets which blur their backdrop fall back to translucent backgrounds otherwise.
+    #[inline]
+    fn supports_backdrop_filters(&self) -> bool {
+        true
+    }
+
+    /// Returns the manager of image resources, through which images are uploaded within a memory budget,
+    /// or `None` if images manage their own resources.
+    #[cfg(feature = "images")]
+    #[inline]
+    fn image_resources(&mut self) -> Option<&mut crate::resources::ImageResources> {
+        None
+    }
 }
 
 /// Propagates `update` to the children of a widget.
@@ -227,7 +403,12 @@ pub fn invoke_update<U: UpdateAuxiliary, G>(
     aux: &mut U,
 ) {
     // Iterate in reverse because most visually forefront widgets should get events first.
-    for child in widget.children_mut().into_iter().rev() {
+    let mut children = widget.children_mut();
+    children.reverse();
+    // Subtrees drawn in higher layers are in front of their siblings, regardless of order (the sort is stable).
+    children.sort_by_key(|child| std::cmp::Reverse(top_layer(&**child)));
+
+    for child in children {
         match child.visibility() {
             Visibility::Static | Visibility::None => {}
             _ => child.update(aux),
@@ -235,10 +416,59 @@ pub fn invoke_update<U: UpdateAuxiliary, G>(
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Recursively loads `theme` into the painters of `widget` and its children, then resizes them from the theme.
+///
+/// This is used to switch or reload themes at runtime (see `themes::DynamicWatcher`).
+/// Note that widget data derived from the previous theme (e.g. colors set by `WidgetConstructor::from_theme`) is left as is.
+pub fn reload_theme<U, G>(
+    widget: &mut dyn WidgetChildren<
+        UpdateAux = U,
+        GraphicalAux = G,
+        DisplayObject = DisplayCommand,
+    >,
+    theme: &dyn draw::Theme,
+    aux: &dyn GraphicalAuxiliary,
+) {
+    widget.theme().load_theme(theme, aux);
+    widget.resize_from_theme();
+    widget.repaint();
+
+    for child in widget.children_mut() {
+        reload_theme(child, theme, aux);
+    }
+}
+
+/// Recursively resizes `widget` and its children from the theme, then repaints them.
+///
+/// This is used when the scale factor changes; sizes and font sizes are in logical pixels, which the display scales,
+/// but text is measured anew since glyph metrics don't scale linearly with hinting.
+pub fn rescale<U, G>(
+    widget: &mut dyn WidgetChildren<
+        UpdateAux = U,
+        GraphicalAux = G,
+        DisplayObject = DisplayCommand,
+    >,
+) {
+    widget.resize_from_theme();
+    widget.repaint();
+
+    for child in widget.children_mut() {
+        rescale(child);
+    }
+}
+
+#[derive(Clone, Debug)]
 struct ConsumableEventInner<T> {
     marker: RefCell<bool>,
     data: T,
+    timestamp: Instant,
+}
+
+// The timestamp is left out, so that events are compared by their data (and consumption) alone.
+impl<T: PartialEq> PartialEq for ConsumableEventInner<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.marker == other.marker && self.data == other.data
+    }
 }
 
 /// Event data that can be "consumed". This is needed for events such as clicking and typing.
@@ -258,9 +488,26 @@ struct ConsumableEventInner<T> {
 pub struct ConsumableEvent<T>(Rc<ConsumableEventInner<T>>);
 
 impl<T> ConsumableEvent<T> {
-    /// Creates a unconsumed event, initialized with `val`.
+    /// Creates a unconsumed event, initialized with `val` and stamped with the current time.
+    ///
+    /// Input events are created as soon as they arrive from the OS, so the timestamp is their arrival time.
     pub fn new(val: T) -> Self {
-        ConsumableEvent(Rc::new(ConsumableEventInner { marker: RefCell::new(true), data: val }))
+        ConsumableEvent::with_timestamp(val, Instant::now())
+    }
+
+    /// Creates a unconsumed event, initialized with `val` and stamped with the time it arrived at.
+    pub fn with_timestamp(val: T, timestamp: Instant) -> Self {
+        ConsumableEvent(Rc::new(ConsumableEventInner {
+            marker: RefCell::new(true),
+            data: val,
+            timestamp,
+        }))
+    }
+
+    /// Returns the time the event arrived at (see `profiling::record_latency`).
+    #[inline]
+    pub fn timestamp(&self) -> Instant {
+        self.0.timestamp
     }
 
     /// Returns the event data as long as **both** the following conditions are satisfied:
@@ -309,6 +556,14 @@ pub enum WindowEvent {
     /// The user moved the cursor.
     #[event_key(mouse_move)]
     MouseMove(ConsumableEvent<(AbsolutePoint, KeyModifiers)>),
+    /// The user moved the mouse while the pointer is locked (see `UpdateAuxiliary::set_pointer_lock`).
+    /// Holds the relative movement, since the cursor itself doesn't move.
+    #[event_key(mouse_delta)]
+    MouseDelta(ConsumableEvent<(Vector, KeyModifiers)>),
+    /// The user scrolled the mouse wheel (or touchpad) with the cursor at a position.
+    /// Holds the scroll amount in logical pixels, where positive values scroll up/left.
+    #[event_key(mouse_wheel)]
+    MouseWheel(ConsumableEvent<(AbsolutePoint, Vector, KeyModifiers)>),
     /// Emitted when a text input is received.
     #[event_key(text_input)]
     TextInput(ConsumableEvent<char>),
@@ -323,6 +578,77 @@ pub enum WindowEvent {
     /// the local "focused" flag (which should ideally be stored as `draw::state::InteractionState`).
     #[event_key(clear_focus)]
     ClearFocus,
+    /// Emitted when the window is resized (or its scale factor changes), as well as once when the application starts.
+    /// Holds the new size of the window in logical pixels.
+    #[event_key(resized)]
+    Resized(Size),
+    /// Emitted when the scale factor of the window changes (e.g. when it's moved to a monitor with a different DPI),
+    /// before the `Resized` it causes. Holds the new scale factor (see `GraphicalAuxiliary::scaling`),
+    /// such that widgets which cache measurements in physical pixels can recompute them.
+    #[event_key(dpi_changed)]
+    DpiChanged(f32),
+    /// Emitted when a timer scheduled with the `TimerManager` elapses.
+    #[event_key(timer)]
+    Timer(TimerId),
+    /// Emitted once the user hasn't interacted with the window for the kiosk idle timeout
+    /// (see `app::KioskOptions::idle_timeout`), e.g. to reset to a home screen.
+    #[event_key(idle_timeout)]
+    IdleTimeout,
+    /// Emitted once the user hasn't interacted with the window for the idle timeout (see `app::AppOptions::idle_timeout`),
+    /// e.g. to hide sensitive data or show a lock screen.
+    #[event_key(idle)]
+    Idle,
+    /// Emitted when the user interacts with the window again after `Idle`.
+    #[event_key(active)]
+    Active,
+    /// Emitted once when the application is about to exit, e.g. to save unsaved state.
+    /// This is the last event widgets receive (see `app::App::on_exit` for the whole shutdown sequence).
+    #[event_key(shutdown)]
+    Shutdown,
+    /// Emitted when the user activates the tray icon of the application or an item of its menu
+    /// (see `app::tray`, with the `tray` feature enabled).
+    #[event_key(tray_activated)]
+    TrayActivated(TrayActivation),
+    /// Emitted when the user presses a global hotkey, even if the window isn't focused
+    /// (see `app::global_hotkeys`, with the `global-hotkeys` feature enabled).
+    #[event_key(global_hotkey)]
+    GlobalHotkey(HotkeyId),
+    /// Emitted when something happens to a notification of the application
+    /// (see `app::notifications`, with the `notifications` feature enabled).
+    #[event_key(notification)]
+    Notification(NotificationEvent),
+}
+
+/// Identifies a hotkey registered with `app::global_hotkeys::GlobalHotkeys`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HotkeyId(pub(crate) u64);
+
+/// Identifies a notification posted with `app::notifications::Notifications`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NotificationId(pub(crate) u64);
+
+/// What happened to a notification (see `WindowEvent::Notification`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NotificationEvent {
+    /// The notification couldn't be posted to the OS, and is to be shown within the application instead.
+    InApp(NotificationId),
+    /// The user clicked the notification itself.
+    Clicked(NotificationId),
+    /// The user invoked an action of the notification; holds the identifier of the action.
+    ActionInvoked { notification: NotificationId, action: String },
+    /// The notification was dismissed or expired.
+    Dismissed(NotificationId),
+}
+
+/// What the user activated in the tray (see `WindowEvent::TrayActivated`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TrayActivation {
+    /// The icon itself was clicked.
+    Icon,
+    /// The icon was double-clicked.
+    IconDoubleClick,
+    /// An item of the menu was activated; holds the identifier of the item.
+    MenuItem(String),
 }
 
 // Most of these are copied from `winit`.
@@ -679,106 +1005,1860 @@ macro_rules! observe {
     };
 }
 
-lazy_static::lazy_static! {
-    // Frame counter used by `invoke_draw`, resets back to 0 after 60 frames.
-    // This is used to only clean up `CLIP_LIST` every 60 frames.
-    static ref DRAW_COUNTER: Mutex<u8> = Mutex::new(0);
-    // Map of pre/post command groups loosely linked to a widget by using the memory address as a unique identifier.
-    static ref CLIP_LIST: Mutex<HashMap<usize, (CommandGroup, CommandGroup)>> =
-        Mutex::new(HashMap::new());
+/// Event emitted by a `FocusTracker` when keyboard focus moves between widgets.
+///
+/// Each event holds the ID of the `FocusHandle` it's addressed to.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FocusEvent {
+    /// The widget should gain focus.
+    #[event_key(focus)]
+    Focus(u64),
+    /// The widget should lose focus.
+    #[event_key(blur)]
+    Blur(u64),
+    /// The focused widget should be activated, as if it were clicked (spatial navigation "A" button).
+    #[event_key(activate)]
+    Activate(u64),
+    /// The user wants to go back (spatial navigation "B" button).
+    /// Holds the ID of the focused widget, if any; this is typically handled by the application rather than a widget.
+    #[event_key(back)]
+    Back(Option<u64>),
 }
 
-fn invoke_draw_impl<U, G: GraphicalAuxiliary>(
-    widget: &mut dyn WidgetChildren<
-        UpdateAux = U,
-        GraphicalAux = G,
-        DisplayObject = DisplayCommand,
-    >,
-    display: &mut dyn GraphicsDisplay,
-    aux: &mut G,
-    clip_list: &mut HashMap<usize, (CommandGroup, CommandGroup)>,
-    checked: &mut Option<HashSet<usize>>,
-) {
-    if widget.visibility() != Visibility::Invisible && widget.visibility() != Visibility::None {
-        // we're not dereferencing the pointer so it's fine... right?
-        #[allow(clippy::cast_ptr_alignment)]
-        let id = widget as *const _ as *const usize as _;
-        let (clip, restore) =
-            clip_list.entry(id).or_insert_with(|| (CommandGroup::new(), CommandGroup::new()));
-        let clip_rect = widget.abs_bounds();
-        clip.repaint();
-        restore.repaint();
-        // later on when partial repainting is implemented, this plays an important role in
-        // making sure it works correctly. Essentially it forces widgets to be exact and explicit
-        // in reporting their paint boundaries, otherwise it gets clipped.
-        clip.push(
-            display,
-            &[
-                DisplayCommand::Save,
-                DisplayCommand::Clip(DisplayClip::Rectangle {
-                    rect: clip_rect.cast_unit(),
-                    antialias: true,
-                }),
-                DisplayCommand::Save,
-            ],
-            Default::default(),
-            false,
-            None,
-        );
+/// Direction in which focus is moved by spatial navigation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavigationDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-        widget.draw(display, aux);
+/// Registration of a widget within a `FocusTracker`.
+///
+/// The widget is removed from the focus order when the handle is dropped.
+#[derive(Debug)]
+pub struct FocusHandle {
+    id: u64,
+    order: Weak<RefCell<Vec<u64>>>,
+    rects: Weak<RefCell<HashMap<u64, AbsoluteRect>>>,
+    text_inputs: Weak<RefCell<HashSet<u64>>>,
+}
 
-        restore.push(
-            display,
-            &[DisplayCommand::Restore, DisplayCommand::Restore],
-            Default::default(),
-            false,
-            None,
-        );
+impl FocusHandle {
+    /// Returns the ID of the widget within the focus order.
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
 
-        if let Some(ref mut checked) = *checked {
-            checked.insert(id);
+    /// Updates the absolute rectangle of the widget, used by spatial navigation.
+    /// Widgets should invoke this whenever they're moved or resized (i.e. in `on_transform`).
+    pub fn set_rect(&self, rect: AbsoluteRect) {
+        if let Some(rects) = self.rects.upgrade() {
+            rects.borrow_mut().insert(self.id, rect);
         }
     }
+}
 
-    for child in widget.children_mut() {
-        invoke_draw_impl(child, display, aux, clip_list, checked);
+impl Drop for FocusHandle {
+    fn drop(&mut self) {
+        if let Some(order) = self.order.upgrade() {
+            let id = self.id;
+            order.borrow_mut().retain(|other| *other != id);
+        }
+        if let Some(rects) = self.rects.upgrade() {
+            rects.borrow_mut().remove(&self.id);
+        }
+        if let Some(text_inputs) = self.text_inputs.upgrade() {
+            text_inputs.borrow_mut().remove(&self.id);
+        }
     }
 }
 
-/// Recursively invokes `draw`.
-/// This will invoke draw (with some extra steps, see below)
-/// for `widget`, then invoke `invoke_draw` all of `widget`s children.
+/// Manages keyboard focus between interactive widgets.
 ///
-/// Extra processing steps:
-/// - Skip if widget visibility is `Invisible` or `None`.
-/// - Clip to absolute widget bounds.
-/// - Add widget position to auxiliary tracer.
-pub fn invoke_draw<U, G: GraphicalAuxiliary>(
-    widget: &mut dyn WidgetChildren<
-        UpdateAux = U,
-        GraphicalAux = G,
-        DisplayObject = DisplayCommand,
-    >,
-    display: &mut dyn GraphicsDisplay,
-    aux: &mut G,
-) {
-    let mut draw_counter = DRAW_COUNTER.lock().unwrap();
-    let mut clip_list = CLIP_LIST.lock().unwrap();
+/// Widgets register themselves (receiving a `FocusHandle`), which appends them to the focus order.
+/// Tab and Shift+Tab move focus forwards and backwards through the order respectively,
+/// emitting `FocusEvent`s which widgets listen to (see [`focus_handler`](../ui/fn.focus_handler.html)).
+///
+/// With spatial navigation enabled (see `set_spatial_navigation`), suited to game-pad, remote or kiosk interfaces:
+///
+/// | Key                 | Effect                                                                 |
+/// |---------------------|------------------------------------------------------------------------|
+/// | Arrow keys          | Move focus to the nearest widget in that direction (see `navigate`).   |
+/// | Enter / Space       | Activate the focused widget ("A" button, see `activate`).              |
+/// | Escape / Backspace  | Go back ("B" button, see `back`).                                      |
+///
+/// These keys are left to text input widgets (see `register_text_input`) while one of them is focused,
+/// since they edit text there; Tab still moves focus out of them.
+///
+/// Other input devices (e.g. game-pads) can drive `navigate`, `activate` and `back` directly.
+///
+/// `update` must be invoked before the widget tree is updated.
+#[derive(Debug)]
+pub struct FocusTracker {
+    order: Rc<RefCell<Vec<u64>>>,
+    rects: Rc<RefCell<HashMap<u64, AbsoluteRect>>>,
+    text_inputs: Rc<RefCell<HashSet<u64>>>,
+    spatial: bool,
+    // Direction of the last spatial movement (`navigate`), or `None` if focus last moved through the focus order.
+    // Widgets refusing focus are skipped onto their spatial neighbor in this direction.
+    direction: Option<NavigationDirection>,
+    next_id: u64,
+    focused: Option<u64>,
+    // Whether focus last moved backwards through the focus order (Shift+Tab).
+    // Widgets refusing focus are skipped onto the previous widget instead of the next while this is set.
+    reverse: bool,
+    skipped: usize,
+    queue: RcEventQueue<FocusEvent>,
+    window_listener: RcEventListener<WindowEvent>,
+}
+
+impl FocusTracker {
+    /// Creates a focus tracker which handles key presses from `window_queue`.
+    pub fn new(window_queue: &RcEventQueue<WindowEvent>) -> Self {
+        FocusTracker {
+            order: Default::default(),
+            rects: Default::default(),
+            text_inputs: Default::default(),
+            spatial: false,
+            direction: None,
+            next_id: 0,
+            focused: None,
+            reverse: false,
+            skipped: 0,
+            queue: RcEventQueue::new(),
+            window_listener: window_queue.listen(),
+        }
+    }
+
+    /// Appends a new widget to the end of the focus order.
+    pub fn register(&mut self) -> FocusHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.order.borrow_mut().push(id);
+        FocusHandle {
+            id,
+            order: Rc::downgrade(&self.order),
+            rects: Rc::downgrade(&self.rects),
+            text_inputs: Rc::downgrade(&self.text_inputs),
+        }
+    }
+
+    /// Appends a new text input widget (e.g. `TextArea`) to the end of the focus order.
+    ///
+    /// While it's focused, the keys of spatial navigation are left to the widget.
+    pub fn register_text_input(&mut self) -> FocusHandle {
+        let handle = self.register();
+        self.text_inputs.borrow_mut().insert(handle.id);
+        handle
+    }
+
+    /// Enables or disables spatial navigation from the keyboard (see the [type documentation](struct.FocusTracker.html)).
+    #[inline]
+    pub fn set_spatial_navigation(&mut self, spatial: bool) {
+        self.spatial = spatial;
+    }
+
+    /// Returns `true` if spatial navigation from the keyboard is enabled.
+    #[inline(always)]
+    pub fn spatial_navigation(&self) -> bool {
+        self.spatial
+    }
+
+    /// Returns the queue where `FocusEvent`s are emitted.
+    #[inline(always)]
+    pub fn focus_queue(&self) -> &RcEventQueue<FocusEvent> {
+        &self.queue
+    }
+
+    /// Returns the ID of the currently focused widget, if any.
+    #[inline(always)]
+    pub fn focused(&self) -> Option<u64> {
+        self.focused
+    }
+
+    /// Moves focus to the widget with the ID `id`.
+    pub fn focus(&mut self, id: u64) {
+        self.skipped = 0;
+        self.direction = None;
+        self.move_focus(id);
+    }
 
-    // Every 60 frames clean up CLIP_LIST.
-    // To do so, gather information on which widget ptrs have been maintained.
-    let mut checked = if *draw_counter >= 60 { Some(HashSet::new()) } else { None };
+    /// Moves focus to the geometrically nearest widget in a direction from the focused widget.
+    /// Widgets which haven't reported their rectangle (see `FocusHandle::set_rect`) are ignored.
+    ///
+    /// If no widget is focused, the first widget in the focus order is focused.
+    /// Returns `false` if there's no widget in that direction.
+    pub fn navigate(&mut self, direction: NavigationDirection) -> bool {
+        self.skipped = 0;
+        let focused = match self.focused {
+            Some(focused) => focused,
+            None => {
+                self.focus_next();
+                return self.focused.is_some();
+            }
+        };
+
+        self.direction = Some(direction);
+        match self.spatial_neighbor(focused, direction) {
+            Some(id) => {
+                self.move_focus(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activates the focused widget, as if it were clicked. Returns `false` if no widget is focused.
+    pub fn activate(&mut self) -> bool {
+        match self.focused {
+            Some(focused) => {
+                self.queue.emit_owned(FocusEvent::Activate(focused));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Emits `FocusEvent::Back`.
+    pub fn back(&mut self) {
+        self.queue.emit_owned(FocusEvent::Back(self.focused));
+    }
+
+    /// Moves focus to the next widget in the focus order, wrapping around to the first.
+    pub fn focus_next(&mut self) {
+        self.reverse = false;
+        self.direction = None;
+        self.skipped = 0;
+        if let Some(id) = self.neighbor(self.focused, false) {
+            self.move_focus(id);
+        }
+    }
+
+    /// Moves focus to the previous widget in the focus order, wrapping around to the last.
+    pub fn focus_prev(&mut self) {
+        self.reverse = true;
+        self.direction = None;
+        self.skipped = 0;
+        if let Some(id) = self.neighbor(self.focused, true) {
+            self.move_focus(id);
+        }
+    }
+
+    /// Invoked by a widget which can't accept focus (e.g. because it's disabled)
+    /// upon receiving `FocusEvent::Focus`.
+    /// Focus moves onto the following widget, in the direction focus was last moved in.
+    pub fn skip(&mut self, id: u64) {
+        if self.focused != Some(id) {
+            return;
+        }
+
+        self.skipped += 1;
+        // Every widget has refused focus.
+        if self.skipped >= self.order.borrow().len() {
+            self.skipped = 0;
+            self.focused = None;
+            return;
+        }
+
+        let next = match self.direction {
+            Some(direction) => self.spatial_neighbor(id, direction),
+            None => self.neighbor(Some(id), self.reverse),
+        };
+
+        match next {
+            Some(next) => self.move_focus(next),
+            // Nothing further in that direction can accept focus.
+            None => {
+                self.skipped = 0;
+                self.focused = None;
+            }
+        }
+    }
+
+    /// Records that a widget has gained focus without the tracker (e.g. by being clicked).
+    ///
+    /// No events are emitted, since the widget has already focused itself.
+    pub fn notify_focused(&mut self, id: Option<u64>) {
+        if id.is_some() {
+            self.focused = id;
+        }
+    }
+
+    /// Handles Tab/Shift+Tab key presses, spatial navigation keys (if enabled and no text input is focused)
+    /// and focus clearing.
+    pub fn update(&mut self) {
+        for event in self.window_listener.peek() {
+            match event {
+                WindowEvent::KeyPress(event) => {
+                    if let Some((_, modifiers)) = event.with(|(key, modifiers)| {
+                        *key == KeyInput::Tab && !modifiers.ctrl && !modifiers.alt
+                    }) {
+                        if modifiers.shift {
+                            self.focus_prev();
+                        } else {
+                            self.focus_next();
+                        }
+                    } else if self.spatial && !self.text_input_focused() {
+                        if let Some((key, _)) = event.with(|(key, modifiers)| {
+                            !modifiers.shift
+                                && !modifiers.ctrl
+                                && !modifiers.alt
+                                && spatial_key(*key)
+                        }) {
+                            match key {
+                                KeyInput::Up => {
+                                    self.navigate(NavigationDirection::Up);
+                                }
+                                KeyInput::Down => {
+                                    self.navigate(NavigationDirection::Down);
+                                }
+                                KeyInput::Left => {
+                                    self.navigate(NavigationDirection::Left);
+                                }
+                                KeyInput::Right => {
+                                    self.navigate(NavigationDirection::Right);
+                                }
+                                KeyInput::Return | KeyInput::NumpadEnter | KeyInput::Space => {
+                                    self.activate();
+                                }
+                                _ => self.back(),
+                            }
+                        }
+                    }
+                }
+                WindowEvent::ClearFocus => self.focused = None,
+                _ => {}
+            }
+        }
+    }
+
+    fn text_input_focused(&self) -> bool {
+        self.focused.map_or(false, |focused| self.text_inputs.borrow().contains(&focused))
+    }
+
+    fn neighbor(&self, from: Option<u64>, reverse: bool) -> Option<u64> {
+        let order = self.order.borrow();
+        if order.is_empty() {
+            return None;
+        }
+
+        let last = order.len() - 1;
+        let index = match from.and_then(|from| order.iter().position(|id| *id == from)) {
+            Some(index) if reverse => {
+                if index == 0 {
+                    last
+                } else {
+                    index - 1
+                }
+            }
+            Some(index) => {
+                if index == last {
+                    0
+                } else {
+                    index + 1
+                }
+            }
+            None if reverse => last,
+            None => 0,
+        };
+
+        Some(order[index])
+    }
+
+    fn spatial_neighbor(&self, from: u64, direction: NavigationDirection) -> Option<u64> {
+        let rects = self.rects.borrow();
+        let from_rect = rects.get(&from)?;
+        let origin = from_rect.center();
+
+        let order = self.order.borrow();
+        order
+            .iter()
+            .filter(|id| **id != from)
+            .filter_map(|id| Some((*id, rects.get(id)?)))
+            .filter_map(|(id, rect)| {
+                let center = rect.center();
+                // Distance along the direction, and offset perpendicular to it.
+                let (distance, offset) = match direction {
+                    NavigationDirection::Up => (origin.y - center.y, center.x - origin.x),
+                    NavigationDirection::Down => (center.y - origin.y, center.x - origin.x),
+                    NavigationDirection::Left => (origin.x - center.x, center.y - origin.y),
+                    NavigationDirection::Right => (center.x - origin.x, center.y - origin.y),
+                };
+                if distance <= 0.0 {
+                    return None;
+                }
+                // Prefer widgets in line with the focused widget over closer widgets off to the side.
+                Some((id, distance + 2.0 * offset.abs()))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(id, _)| id)
+    }
 
-    invoke_draw_impl(widget, display, aux, &mut clip_list, &mut checked);
+    fn move_focus(&mut self, id: u64) {
+        if let Some(old) = self.focused.replace(id) {
+            if old != id {
+                self.queue.emit_owned(FocusEvent::Blur(old));
+            }
+        }
+        self.queue.emit_owned(FocusEvent::Focus(id));
+    }
+}
 
-    // Perform cleanup (checked is only contains a value if on 60th frame).
-    if let Some(checked) = checked {
-        *draw_counter = 0;
-        clip_list.retain(|widget_ptr, _| checked.contains(widget_ptr));
+fn spatial_key(key: KeyInput) -> bool {
+    match key {
+        KeyInput::Up
+        | KeyInput::Down
+        | KeyInput::Left
+        | KeyInput::Right
+        | KeyInput::Return
+        | KeyInput::NumpadEnter
+        | KeyInput::Space
+        | KeyInput::Escape
+        | KeyInput::Back => true,
+        _ => false,
     }
+}
+
+/// Identifies a timer scheduled with a `TimerManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
 
-    *draw_counter += 1;
+#[derive(Debug, Clone, Copy)]
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    interval: Option<Duration>,
+}
+
+/// Schedules one-shot and repeating timers, e.g. for cursor blinking or tooltip delays.
+///
+/// Timers are serviced by the event loop, which emits `WindowEvent::Timer` with the timer ID
+/// each time a timer elapses (see `poll`).
+#[derive(Debug, Default)]
+pub struct TimerManager {
+    timers: Vec<Timer>,
+    next_id: u64,
+}
+
+impl TimerManager {
+    /// Creates a timer manager without any timers.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn schedule(&mut self, delay: Duration, interval: Option<Duration>) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.push(Timer { id, deadline: Instant::now() + delay, interval });
+        id
+    }
+
+    /// Schedules a timer which elapses once, after `delay`.
+    pub fn schedule_once(&mut self, delay: Duration) -> TimerId {
+        self.schedule(delay, None)
+    }
+
+    /// Schedules a timer which elapses every `interval`, until it is cancelled.
+    pub fn schedule_repeating(&mut self, interval: Duration) -> TimerId {
+        self.schedule(interval, Some(interval))
+    }
+
+    /// Cancels a timer. Returns `false` if the timer isn't scheduled (e.g. a one-shot timer which has elapsed).
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != count
+    }
+
+    /// Returns `true` if a timer is scheduled.
+    pub fn is_scheduled(&self, id: TimerId) -> bool {
+        self.timers.iter().any(|timer| timer.id == id)
+    }
+
+    /// Returns the earliest time at which a timer elapses, which the event loop should wake up for.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Returns the timers which have elapsed by `now`, removing one-shot timers and rescheduling repeating timers.
+    ///
+    /// A repeating timer which has elapsed several times since the last poll is only returned once.
+    pub fn poll(&mut self, now: Instant) -> Vec<TimerId> {
+        let mut elapsed = Vec::new();
+        self.timers.retain(|timer| {
+            if timer.deadline > now {
+                return true;
+            }
+            elapsed.push(timer.id);
+            timer.interval.is_some()
+        });
+
+        // The remaining timers which have elapsed are repeating.
+        for timer in self.timers.iter_mut().filter(|timer| timer.deadline <= now) {
+            let interval = timer.interval.unwrap_or_default();
+            timer.deadline += interval;
+            // Skip ticks which were missed entirely, rather than elapsing in quick succession.
+            if timer.deadline <= now {
+                timer.deadline = now + interval;
+            }
+        }
+
+        elapsed
+    }
+}
+
+/// Topic of an `EventBus`; the type of the events, and their key (`Event::get_key`).
+type TopicId = (TypeId, &'static str);
+
+/// Receiving end of a `Subscription`, kept by the `EventBus`.
+struct Subscriber {
+    /// `RcEventQueue` of the event type of the topic, listened to by the subscription only.
+    queue: Box<dyn Any>,
+    /// Cleared once the subscribing widget is dropped; dead once the subscription is dropped.
+    active: Weak<Cell<bool>>,
+    owner: Option<RcEventListener<DropEvent>>,
+}
+
+impl Subscriber {
+    /// Returns `false` if the subscription has been dropped or its owner has been dropped.
+    fn retain(&self) -> bool {
+        let active = match self.active.upgrade() {
+            Some(active) => active,
+            None => return false,
+        };
+        if self.owner.as_ref().map(|owner| !owner.peek().is_empty()).unwrap_or(false) {
+            active.set(false);
+        }
+        active.get()
+    }
+}
+
+/// Typed publish/subscribe channel between widgets which aren't otherwise connected, e.g. siblings.
+///
+/// Events are published to topics; a topic is the type of the events together with their key (as given by
+/// `#[event_key(...)]`), so that subscribers can pick out single variants of event enums.
+/// The bus is accessed through `UpdateAuxiliary::event_bus_mut`:
+/// ```ignore
+/// // In construction (the subscription is stored in the widget, and ends once it's dropped):
+/// let selections = u_aux.event_bus_mut().subscribe::<ListEvent>("select");
+///
+/// // In a handler of another widget:
+/// aux.event_bus_mut().publish(ListEvent::Select(index));
+///
+/// // In update:
+/// for event in self.selections.peek() {
+///     // ...
+/// }
+/// ```
+#[derive(Default)]
+pub struct EventBus {
+    topics: HashMap<TopicId, Vec<Subscriber>>,
+}
+
+impl EventBus {
+    /// Creates an event bus without any subscriptions.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Subscribes to the events of type `T` keyed `key`, until the returned subscription is dropped.
+    pub fn subscribe<T: verbgraph::Event + Clone + 'static>(
+        &mut self,
+        key: &'static str,
+    ) -> Subscription<T> {
+        self.add_subscriber(key, None)
+    }
+
+    /// Subscribes `owner` to the events of type `T` keyed `key`, until either the returned subscription or `owner` is dropped.
+    ///
+    /// This allows subscriptions to be handed out to (and outlive) widgets other than the owner, e.g. its parent.
+    pub fn subscribe_for<T: verbgraph::Event + Clone + 'static>(
+        &mut self,
+        key: &'static str,
+        owner: &impl DropNotifier,
+    ) -> Subscription<T> {
+        self.add_subscriber(key, Some(owner.drop_event().listen()))
+    }
+
+    fn add_subscriber<T: verbgraph::Event + Clone + 'static>(
+        &mut self,
+        key: &'static str,
+        owner: Option<RcEventListener<DropEvent>>,
+    ) -> Subscription<T> {
+        let queue = RcEventQueue::new();
+        let listener = queue.listen();
+        let active = Rc::new(Cell::new(true));
+        self.topics.entry((TypeId::of::<T>(), key)).or_insert_with(Vec::new).push(Subscriber {
+            queue: Box::new(queue),
+            active: Rc::downgrade(&active),
+            owner,
+        });
+        Subscription { listener, active }
+    }
+
+    /// Publishes an event to the subscribers of its topic, returning how many subscribers it was delivered to.
+    ///
+    /// Subscriptions which have ended are removed.
+    pub fn publish<T: verbgraph::Event + Clone + 'static>(&mut self, event: T) -> usize {
+        let topic = (TypeId::of::<T>(), event.get_key());
+        let subscribers = match self.topics.get_mut(&topic) {
+            Some(subscribers) => subscribers,
+            None => return 0,
+        };
+
+        subscribers.retain(Subscriber::retain);
+        for subscriber in subscribers.iter() {
+            if let Some(queue) = subscriber.queue.downcast_ref::<RcEventQueue<T>>() {
+                queue.emit_owned(event.clone());
+            }
+        }
+
+        let count = subscribers.len();
+        if count == 0 {
+            self.topics.remove(&topic);
+        }
+        count
+    }
+
+    /// Returns the amount of active subscriptions to the events of type `T` keyed `key`.
+    pub fn subscriber_count<T: verbgraph::Event + Clone + 'static>(
+        &self,
+        key: &'static str,
+    ) -> usize {
+        self.topics
+            .get(&(TypeId::of::<T>(), key))
+            .map(|subscribers| subscribers.iter().filter(|subscriber| subscriber.retain()).count())
+            .unwrap_or(0)
+    }
+
+    /// Removes the subscriptions which have ended, across all topics.
+    ///
+    /// Ended subscriptions are otherwise only removed once their topic is published to.
+    pub fn prune(&mut self) {
+        self.topics.retain(|_, subscribers| {
+            subscribers.retain(Subscriber::retain);
+            !subscribers.is_empty()
+        });
+    }
+}
+
+/// Subscription to a topic of an `EventBus`, which receives the events published to the topic.
+///
+/// Dropping the subscription unsubscribes from the topic.
+pub struct Subscription<T: verbgraph::Event + Clone + 'static> {
+    listener: RcEventListener<T>,
+    active: Rc<Cell<bool>>,
+}
+
+impl<T: verbgraph::Event + Clone + 'static> Subscription<T> {
+    /// Returns the events received since the last peek.
+    #[inline]
+    pub fn peek(&self) -> Vec<T> {
+        self.listener.peek()
+    }
+
+    /// Invokes `f` with the events received since the last peek.
+    #[inline]
+    pub fn with<R>(&self, f: impl FnOnce(&[T]) -> R) -> R {
+        self.listener.with(f)
+    }
+
+    /// Returns `false` once the widget owning the subscription (see `EventBus::subscribe_for`) has been dropped
+    /// and the bus has noticed, after which no more events are received.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+}
+
+/// Default maximum time between the clicks of a double (or triple) click (see `UpdateAuxiliary::double_click_time`).
+pub const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+/// Maximum distance (in logical pixels) between the clicks of a double (or triple) click.
+pub const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
+/// Counts successive clicks at the same position, to detect double and triple clicks.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ClickCounter {
+    last: Option<(Instant, AbsolutePoint)>,
+    count: u32,
+}
+
+impl ClickCounter {
+    /// Registers a click at `position`, returning the amount of successive clicks (e.g. `2` for a double click).
+    ///
+    /// Clicks are successive if they are within `timeout` and `DOUBLE_CLICK_DISTANCE` of the previous click.
+    pub fn click(&mut self, position: AbsolutePoint, timeout: Duration) -> u32 {
+        let now = Instant::now();
+        let successive = self
+            .last
+            .map(|(time, last)| {
+                now.duration_since(time) <= timeout
+                    && (position - last).length() <= DOUBLE_CLICK_DISTANCE
+            })
+            .unwrap_or(false);
+
+        self.count = if successive { self.count + 1 } else { 1 };
+        self.last = Some((now, position));
+        self.count
+    }
+
+    /// Returns the amount of successive clicks up to (and including) the last click.
+    #[inline]
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Forgets the previous clicks, so that the next click is counted as a single click.
+    pub fn reset(&mut self) {
+        *self = Default::default();
+    }
+}
+
+/// Rate-limits noisy events (e.g. text changes, scrolling or resizing) by only passing on the last event
+/// once none have arrived for a delay.
+///
+/// Events are pushed from one terminal, and the result is received from the `timer` terminal of the window queue:
+/// ```ignore
+/// "search" => event in &search.event_queue => {
+///     user_modify => {
+///         if let TextAreaEvent::UserModify(text) = event {
+///             obj.search_debounce.push(text.clone(), aux.timers_mut());
+///         }
+///     }
+/// }
+/// "timers" => event in aux.window_queue() => {
+///     timer => {
+///         if let Some(text) = obj.search_debounce.poll(*event) {
+///             obj.search(&text);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Debounce<T> {
+    delay: Duration,
+    timer: Option<TimerId>,
+    pending: Option<T>,
+}
+
+impl<T> Debounce<T> {
+    /// Creates a debouncer which passes on events once `delay` has passed without another event.
+    pub fn new(delay: Duration) -> Self {
+        Debounce { delay, timer: None, pending: None }
+    }
+
+    /// Returns the delay after the last event.
+    #[inline]
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Records an event, replacing any pending event and restarting the delay.
+    pub fn push(&mut self, event: T, timers: &mut TimerManager) {
+        if let Some(timer) = self.timer.take() {
+            timers.cancel(timer);
+        }
+        self.pending = Some(event);
+        self.timer = Some(timers.schedule_once(self.delay));
+    }
+
+    /// Returns the pending event if `timer` (from `WindowEvent::Timer`) is the timer of this debouncer.
+    pub fn poll(&mut self, timer: TimerId) -> Option<T> {
+        if self.timer != Some(timer) {
+            return None;
+        }
+        self.timer = None;
+        self.pending.take()
+    }
+
+    /// Discards the pending event, if any.
+    pub fn cancel(&mut self, timers: &mut TimerManager) {
+        if let Some(timer) = self.timer.take() {
+            timers.cancel(timer);
+        }
+        self.pending = None;
+    }
+
+    /// Returns `true` if an event is waiting to be passed on.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Rate-limits noisy events by passing on at most one event per interval.
+///
+/// The first event is passed on immediately. Events arriving within the interval after that are held back,
+/// with only the last of them passed on (from the `timer` terminal of the window queue) once the interval has passed.
+/// Usage is the same as that of [`Debounce`](struct.Debounce.html), except that `push` may also return an event to handle right away.
+#[derive(Debug, Clone)]
+pub struct Throttle<T> {
+    interval: Duration,
+    timer: Option<TimerId>,
+    pending: Option<T>,
+}
+
+impl<T> Throttle<T> {
+    /// Creates a throttle which passes on at most one event per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Throttle { interval, timer: None, pending: None }
+    }
+
+    /// Returns the minimum interval between events.
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Records an event, returning it if it can be passed on immediately.
+    /// Otherwise, it replaces any event held back until the interval has passed.
+    pub fn push(&mut self, event: T, timers: &mut TimerManager) -> Option<T> {
+        if self.timer.is_some() {
+            self.pending = Some(event);
+            None
+        } else {
+            self.timer = Some(timers.schedule_once(self.interval));
+            Some(event)
+        }
+    }
+
+    /// Returns the event held back during the interval if `timer` (from `WindowEvent::Timer`) is the timer of this throttle.
+    ///
+    /// Passing on a held back event starts another interval.
+    pub fn poll(&mut self, timer: TimerId, timers: &mut TimerManager) -> Option<T> {
+        if self.timer != Some(timer) {
+            return None;
+        }
+        self.timer = None;
+        let event = self.pending.take()?;
+        self.timer = Some(timers.schedule_once(self.interval));
+        Some(event)
+    }
+
+    /// Discards the held back event (if any) and ends the interval, so that the next event is passed on immediately.
+    pub fn cancel(&mut self, timers: &mut TimerManager) {
+        if let Some(timer) = self.timer.take() {
+            timers.cancel(timer);
+        }
+        self.pending = None;
+    }
+
+    /// Returns `true` if an event is held back.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+lazy_static::lazy_static! {
+    // Clipping command groups of the root widget tree.
+    static ref CLIP_LIST: Mutex<ClipList> = Mutex::new(ClipList::default());
+    // Area of the window to be repainted in the current frame.
+    static ref DAMAGE: Mutex<DamageRegion> = Mutex::new(DamageRegion::full());
+}
+
+/// Maximum amount of disjoint rectangles in a damage region, beyond which they're merged into their bounds.
+const MAX_DAMAGE_RECTS: usize = 16;
+
+/// Area which has changed since the last frame and needs to be repainted, as a set of disjoint rectangles.
+///
+/// `invoke_draw` damages the bounds of widgets which are dirty (see `Repaintable::is_dirty`), have moved or resized,
+/// or have appeared or disappeared. Anything else drawn outside of the widget tree must be reported with
+/// `report_damage` (or `damage_all`). Once the frame is drawn, the application takes the region with `take_damage`;
+/// hosts whose surface keeps the previous frame may then present only the damaged area.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DamageRegion {
+    rects: Vec<AbsoluteRect>,
+    full: bool,
+}
+
+impl DamageRegion {
+    /// Creates an empty region.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a region covering everything.
+    pub fn full() -> Self {
+        DamageRegion { rects: Vec::new(), full: true }
+    }
+
+    /// Adds a rectangle to the region, merging it with any rectangles it overlaps.
+    pub fn add(&mut self, rect: AbsoluteRect) {
+        if self.full || is_degenerate(&rect) {
+            return;
+        }
+
+        let mut rect = rect;
+        while let Some(index) = self.rects.iter().position(|other| other.intersects(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(index));
+        }
+        self.rects.push(rect);
+
+        if self.rects.len() > MAX_DAMAGE_RECTS {
+            let bounds = self.bounds();
+            self.rects.clear();
+            self.rects.extend(bounds);
+        }
+    }
+
+    /// Extends the region to cover everything.
+    pub fn add_all(&mut self) {
+        self.rects.clear();
+        self.full = true;
+    }
+
+    /// Adds all of another region to this region.
+    pub fn merge(&mut self, other: &DamageRegion) {
+        if other.full {
+            self.add_all();
+        } else {
+            for &rect in &other.rects {
+                self.add(rect);
+            }
+        }
+    }
+
+    /// Returns `true` if nothing is damaged.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.rects.is_empty()
+    }
+
+    /// Returns `true` if everything is damaged.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Returns the disjoint rectangles of the region (empty if the region is full).
+    #[inline]
+    pub fn rects(&self) -> &[AbsoluteRect] {
+        &self.rects
+    }
+
+    /// Returns the union of the rectangles of the region, or `None` if the region is empty or full.
+    pub fn bounds(&self) -> Option<AbsoluteRect> {
+        self.rects.iter().fold(None, |bounds, rect| {
+            Some(bounds.map(|bounds: AbsoluteRect| bounds.union(rect)).unwrap_or(*rect))
+        })
+    }
+
+    /// Returns `true` if `rect` overlaps the region.
+    pub fn intersects(&self, rect: &AbsoluteRect) -> bool {
+        self.full || self.rects.iter().any(|damaged| damaged.intersects(rect))
+    }
+}
+
+/// Reports that an area of the window must be repainted in the next frame.
+///
+/// Only needed for content which isn't drawn by a widget of the tree, as `invoke_draw` tracks the damage of widgets.
+pub fn report_damage(rect: AbsoluteRect) {
+    DAMAGE.lock().unwrap().add(rect);
+}
+
+/// Reports that the whole window must be repainted in the next frame (e.g. after changing the background).
+pub fn damage_all() {
+    DAMAGE.lock().unwrap().add_all();
+}
+
+/// Returns the area damaged since the region was last taken, leaving the region empty.
+///
+/// Typically invoked by the application once a frame has been drawn, so that the next frame starts undamaged.
+pub fn take_damage() -> DamageRegion {
+    std::mem::take(&mut *DAMAGE.lock().unwrap())
+}
+
+/// Returns `true` if anything would be repainted by drawing the root widget tree `widget` with `invoke_draw`,
+/// i.e. if any damage has been reported or any widget of the tree is dirty, has moved, or has appeared or disappeared.
+///
+/// Used by the application to only draw frames when something has changed (see `app::RenderMode::OnDemand`).
+pub fn is_damaged<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+) -> bool {
+    !DAMAGE.lock().unwrap().is_empty()
+        || !CLIP_LIST.lock().unwrap().pending_damage(widget).is_empty()
+}
+
+/// Sets the area (typically the window) outside of which widgets in the root widget tree are culled by `invoke_draw`.
+///
+/// `None` disables culling of the root widget tree.
+pub fn set_root_viewport(viewport: Option<AbsoluteRect>) {
+    CLIP_LIST.lock().unwrap().set_viewport(viewport);
+}
+
+/// Map of pre/post (clip/restore) command groups for a widget tree, loosely linked to each widget
+/// by using the memory address as a unique identifier.
+///
+/// `invoke_draw` uses a global clip list. Widgets which draw a subtree themselves
+/// (e.g. [`ErrorBoundary`](../ui/struct.ErrorBoundary.html)) can own a separate one.
+///
+/// The clip list also remembers where each widget was last drawn, so that `draw` can damage the areas widgets
+/// have moved from or disappeared from (see `DamageRegion`).
+#[derive(Default)]
+pub struct ClipList {
+    groups: HashMap<usize, ClipEntry>,
+    // Frame counter which resets back to 0 after 60 frames.
+    // This is used to only clean up `groups` every 60 frames.
+    draw_counter: u8,
+    viewport: Option<AbsoluteRect>,
+    z_offset: i32,
+}
+
+#[derive(Default)]
+struct ClipEntry {
+    clip: CommandGroup,
+    restore: CommandGroup,
+    // Bounds of the widget when it was last drawn, or `None` if it wasn't drawn in the last frame.
+    bounds: Option<AbsoluteRect>,
+    // Whether the widget pushes its own command group, i.e. whether `Repaintable::is_dirty` is meaningful.
+    // Widgets which never push their command group (e.g. layouts) are always dirty.
+    draws: bool,
+}
+
+impl ClipList {
+    /// Sets the area outside of which widgets are culled.
+    #[inline]
+    pub fn set_viewport(&mut self, viewport: Option<AbsoluteRect>) {
+        self.viewport = viewport;
+    }
+
+    /// Returns the area outside of which widgets are culled.
+    #[inline]
+    pub fn viewport(&self) -> Option<AbsoluteRect> {
+        self.viewport
+    }
+
+    /// Sets the offset added to the z-order of every command group pushed while drawing,
+    /// so that a widget tree can be drawn above (or below) another tree drawn with a different clip list.
+    #[inline]
+    pub fn set_z_offset(&mut self, z_offset: i32) {
+        self.z_offset = z_offset;
+    }
+
+    /// Returns the offset added to the z-order of every command group pushed while drawing.
+    #[inline]
+    pub fn z_offset(&self) -> i32 {
+        self.z_offset
+    }
+
+    /// Returns the area which drawing `widget` would damage (see `DamageRegion`), i.e. the bounds of the widgets which
+    /// are dirty, have moved, or have appeared or disappeared since `widget` was last drawn with this clip list.
+    ///
+    /// Damage reported with `report_damage` isn't included.
+    pub fn pending_damage<U, G>(
+        &self,
+        widget: &dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    ) -> DamageRegion {
+        self.collect_damage(widget).0
+    }
+
+    /// Damages the bounds of the widgets which are dirty, have moved, or have appeared or disappeared,
+    /// also returning which widgets are drawn.
+    fn collect_damage<U, G>(
+        &self,
+        widget: &dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    ) -> (DamageRegion, HashSet<usize>) {
+        let mut damage = DamageRegion::new();
+        let mut visited = HashSet::new();
+        collect_damage(widget, &self.groups, &mut visited, &mut damage, self.viewport);
+        for (id, entry) in &self.groups {
+            if !visited.contains(id) {
+                if let Some(bounds) = entry.bounds {
+                    damage.add(bounds);
+                }
+            }
+        }
+        (damage, visited)
+    }
+
+    /// Recursively invokes `draw` on `widget` and its children, as described in `invoke_draw`.
+    pub fn draw<U, G: GraphicalAuxiliary>(
+        &mut self,
+        widget: &mut dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+        display: &mut dyn GraphicsDisplay,
+        aux: &mut G,
+    ) {
+        // Every 60 frames clean up the groups.
+        // To do so, gather information on which widget ptrs have been maintained.
+        let mut checked = if self.draw_counter >= 60 { Some(HashSet::new()) } else { None };
+
+        // Damage has to be known up front, to re-push the clipping groups of every widget it overlaps.
+        let (damage, visited) = self.collect_damage(&*widget);
+        for (id, entry) in self.groups.iter_mut() {
+            if !visited.contains(id) {
+                entry.bounds = None;
+            }
+        }
+
+        let damage = {
+            let mut global = DAMAGE.lock().unwrap();
+            global.merge(&damage);
+            global.clone()
+        };
+
+        let mut offset;
+        let display: &mut dyn GraphicsDisplay = if self.z_offset == 0 {
+            display
+        } else {
+            offset = LayeredDisplay { inner: display, offset: self.z_offset };
+            &mut offset
+        };
+
+        let mut deferred = Vec::new();
+        invoke_draw_impl(
+            widget,
+            display,
+            aux,
+            &mut self.groups,
+            &mut checked,
+            &damage,
+            self.viewport,
+            Layer::Base,
+            &mut deferred,
+        );
+
+        // Draw the deferred subtrees from the lowest layer up; they may defer to even higher layers in turn.
+        while let Some(layer) = deferred.iter().map(|subtree| subtree.layer).min() {
+            let (current, rest): (Vec<_>, Vec<_>) =
+                deferred.into_iter().partition(|subtree| subtree.layer == layer);
+            deferred = rest;
+
+            for subtree in current {
+                invoke_draw_impl(
+                    subtree.widget,
+                    display,
+                    aux,
+                    &mut self.groups,
+                    &mut checked,
+                    &damage,
+                    subtree.viewport,
+                    layer,
+                    &mut deferred,
+                );
+            }
+        }
+
+        // Perform cleanup (checked is only contains a value if on 60th frame).
+        if let Some(checked) = checked {
+            self.draw_counter = 0;
+            self.groups.retain(|widget_ptr, _| checked.contains(widget_ptr));
+        }
+
+        self.draw_counter += 1;
+    }
+}
+
+/// Returns `true` if `widget` itself (as opposed to its children) is drawn, as described in `invoke_draw`.
+fn is_drawn<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    bounds: &AbsoluteRect,
+    viewport: Option<AbsoluteRect>,
+) -> bool {
+    // Widgets without area are skipped, as clipping to (let alone painting) a degenerate rectangle
+    // produces garbage in some backends.
+    !is_degenerate(bounds)
+        && viewport.map(|viewport| viewport.intersects(bounds)).unwrap_or(true)
+        && widget.visibility() != Visibility::Invisible
+        && widget.visibility() != Visibility::None
+}
+
+/// Returns the area the children of `widget` are confined to, or `None` within `Some` if they're entirely culled.
+fn child_viewport<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    viewport: Option<AbsoluteRect>,
+) -> Option<Option<AbsoluteRect>> {
+    match (viewport, widget.viewport()) {
+        (Some(outer), Some(inner)) => outer.intersection(&inner).map(Some),
+        (outer, inner) => Some(outer.or(inner)),
+    }
+}
+
+/// Identifies a widget by its memory address.
+// we're not dereferencing the pointer so it's fine... right?
+#[allow(clippy::cast_ptr_alignment)]
+#[inline]
+fn widget_id<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+) -> usize {
+    widget as *const _ as *const usize as _
+}
+
+/// Damages the bounds of the widgets which are dirty, have moved or have appeared since the last frame,
+/// recording which widgets are drawn in `visited`.
+fn collect_damage<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    clip_list: &HashMap<usize, ClipEntry>,
+    visited: &mut HashSet<usize>,
+    damage: &mut DamageRegion,
+    viewport: Option<AbsoluteRect>,
+) {
+    let bounds = widget.abs_bounds();
+    if is_drawn(widget, &bounds, viewport) {
+        let id = widget_id(widget);
+        visited.insert(id);
+        match clip_list.get(&id) {
+            Some(ClipEntry { bounds: Some(last), draws, .. }) if *last == bounds => {
+                if *draws && widget.is_dirty() {
+                    damage.add(bounds);
+                }
+            }
+            Some(ClipEntry { bounds: last, .. }) => {
+                if let Some(last) = last {
+                    damage.add(*last);
+                }
+                damage.add(bounds);
+            }
+            None => damage.add(bounds),
+        }
+    }
+
+    let viewport = match child_viewport(widget, viewport) {
+        Some(viewport) => viewport,
+        None => return,
+    };
+
+    for child in widget.children() {
+        collect_damage(child, clip_list, visited, damage, viewport);
+    }
+}
+
+/// Subtree whose drawing is deferred until the layers beneath it are drawn.
+struct DeferredSubtree<'a, U, G> {
+    widget:
+        &'a mut dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    layer: Layer,
+    viewport: Option<AbsoluteRect>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn invoke_draw_impl<'a, U, G: GraphicalAuxiliary>(
+    widget: &'a mut dyn WidgetChildren<
+        UpdateAux = U,
+        GraphicalAux = G,
+        DisplayObject = DisplayCommand,
+    >,
+    display: &mut dyn GraphicsDisplay,
+    aux: &mut G,
+    clip_list: &mut HashMap<usize, ClipEntry>,
+    checked: &mut Option<HashSet<usize>>,
+    damage: &DamageRegion,
+    viewport: Option<AbsoluteRect>,
+    layer: Layer,
+    deferred: &mut Vec<DeferredSubtree<'a, U, G>>,
+) {
+    let widget_layer = widget.layer();
+    if widget_layer > layer {
+        deferred.push(DeferredSubtree { widget, layer: widget_layer, viewport });
+        return;
+    }
+
+    let bounds = widget.abs_bounds();
+    check_rect(&bounds, widget.widget_kind());
+
+    if is_drawn(&*widget, &bounds, viewport) {
+        let mut layered;
+        let display: &mut dyn GraphicsDisplay = if layer == Layer::Base {
+            &mut *display
+        } else {
+            layered = LayeredDisplay { inner: &mut *display, offset: layer.z_order().0 };
+            &mut layered
+        };
+
+        let id = widget_id(&*widget);
+        let entry = clip_list.entry(id).or_default();
+        let clip_rect = bounds;
+        // Clipping groups outside of the damage are kept as is.
+        if entry.bounds != Some(bounds) || damage.intersects(&bounds) {
+            entry.clip.repaint();
+            entry.restore.repaint();
+        }
+        entry.bounds = Some(bounds);
+        // This plays an important role in making partial repainting work correctly.
+        // Essentially it forces widgets to be exact and explicit in reporting their paint boundaries,
+        // otherwise it gets clipped (and their damage wouldn't cover what they paint).
+        entry.clip.push(
+            display,
+            &[
+                DisplayCommand::Save,
+                DisplayCommand::Clip(DisplayClip::Rectangle {
+                    rect: clip_rect.cast_unit(),
+                    antialias: true,
+                }),
+                DisplayCommand::Save,
+            ],
+            Default::default(),
+            false,
+            None,
+        );
+
+        widget.draw(display, aux);
+        // A widget which is still dirty after drawing doesn't push its command group.
+        entry.draws |= !widget.is_dirty();
+
+        entry.restore.push(
+            display,
+            &[DisplayCommand::Restore, DisplayCommand::Restore],
+            Default::default(),
+            false,
+            None,
+        );
+
+        if let Some(ref mut checked) = *checked {
+            checked.insert(id);
+        }
+    }
+
+    let viewport = match child_viewport(&*widget, viewport) {
+        Some(viewport) => viewport,
+        // The children are confined to an area which is entirely culled.
+        None => return,
+    };
+
+    for child in widget.children_mut() {
+        invoke_draw_impl(
+            child, display, aux, clip_list, checked, damage, viewport, layer, deferred,
+        );
+    }
+}
+
+/// Recursively invokes `draw`.
+/// This will invoke draw (with some extra steps, see below)
+/// for `widget`, then invoke `invoke_draw` all of `widget`s children.
+///
+/// Extra processing steps:
+/// - Skip if widget visibility is `Invisible` or `None`.
+/// - Skip (but still process children) if the widget bounds lie outside the viewport
+///   (see `set_root_viewport` and `WidgetChildren::viewport`).
+/// - Skip (but still process children) if the widget bounds are empty or degenerate (see `geom::is_degenerate`).
+/// - Clip to absolute widget bounds.
+/// - Add widget position to auxiliary tracer.
+/// - Damage the bounds of widgets which are dirty, moved, appeared or disappeared (see `DamageRegion`),
+///   only re-pushing the clipping command groups which overlap the damage.
+/// - Defer widgets in higher layers until the lower layers are drawn, offsetting their z-order (see `Layer`).
+pub fn invoke_draw<U, G: GraphicalAuxiliary>(
+    widget: &mut dyn WidgetChildren<
+        UpdateAux = U,
+        GraphicalAux = G,
+        DisplayObject = DisplayCommand,
+    >,
+    display: &mut dyn GraphicsDisplay,
+    aux: &mut G,
+) {
+    CLIP_LIST.lock().unwrap().draw(widget, display, aux);
+}
+
+/// Z-order which the command groups of widgets in `Layer::Overlay` are offset by.
+///
+/// Widgets shouldn't push command groups at this z-order themselves (which would offset them twice),
+/// but rather be drawn in the layer (see `WidgetChildren::layer`).
+pub const OVERLAY_Z_ORDER: ZOrder = ZOrder(std::i32::MAX / 2);
+
+/// Z-order which the command groups of widgets in `Layer::Tooltip` are offset by (see `OVERLAY_Z_ORDER`).
+pub const TOOLTIP_Z_ORDER: ZOrder = ZOrder(std::i32::MAX / 2 + std::i32::MAX / 4);
+
+/// Named layer which widgets are drawn into (see `WidgetChildren::layer`).
+///
+/// Widgets are otherwise drawn in tree order, so that a widget (e.g. a popup) can't be drawn above the siblings
+/// following it. `invoke_draw` draws every layer above the previous one, and the subtree of a widget is drawn
+/// in its own layer or the layer of its parent, whichever is higher. Likewise, `invoke_update` passes events
+/// to subtrees containing higher layers first, so that input is handled by the widgets visibly in front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// Regular widgets.
+    Base,
+    /// Content above regular widgets, e.g. popups, menus and dialogs.
+    Overlay,
+    /// Content above everything else, e.g. tooltips.
+    Tooltip,
+}
+
+impl Layer {
+    /// Returns the z-order which the command groups of widgets in this layer are offset by.
+    pub fn z_order(self) -> ZOrder {
+        match self {
+            Layer::Base => ZOrder::default(),
+            Layer::Overlay => OVERLAY_Z_ORDER,
+            Layer::Tooltip => TOOLTIP_Z_ORDER,
+        }
+    }
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Layer::Base
+    }
+}
+
+/// Returns the highest layer within the subtree of `widget`.
+pub fn top_layer<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+) -> Layer {
+    widget.children().into_iter().map(top_layer).fold(widget.layer(), Layer::max)
+}
+
+/// Display which offsets the z-order of command groups, so that widgets drawn in a layer needn't be aware of it.
+struct LayeredDisplay<'a> {
+    inner: &'a mut dyn GraphicsDisplay,
+    offset: i32,
+}
+
+impl LayeredDisplay<'_> {
+    #[inline]
+    fn offset(&self, z_order: ZOrder) -> ZOrder {
+        // The topmost z-orders are reserved by the application (for the debug overlay and final restore).
+        ZOrder(z_order.0.saturating_add(self.offset).min(std::i32::MAX - 2))
+    }
+}
+
+impl GraphicsDisplay for LayeredDisplay<'_> {
+    fn resize(&mut self, size: (u32, u32)) -> Result<(), Box<dyn std::error::Error>> {
+        self.inner.resize(size)
+    }
+
+    fn new_resource(
+        &mut self,
+        descriptor: ResourceDescriptor,
+    ) -> Result<ResourceReference, error::ResourceError> {
+        self.inner.new_resource(descriptor)
+    }
+
+    fn remove_resource(&mut self, reference: ResourceReference) {
+        self.inner.remove_resource(reference)
+    }
+
+    fn push_command_group(
+        &mut self,
+        commands: &[DisplayCommand],
+        z_order: ZOrder,
+        protected: Option<bool>,
+        always_alive: Option<bool>,
+    ) -> Result<CommandGroupHandle, Box<dyn std::error::Error>> {
+        let z_order = self.offset(z_order);
+        self.inner.push_command_group(commands, z_order, protected, always_alive)
+    }
+
+    fn get_command_group(&self, handle: CommandGroupHandle) -> Option<Vec<DisplayCommand>> {
+        self.inner.get_command_group(handle)
+    }
+
+    fn modify_command_group(
+        &mut self,
+        handle: CommandGroupHandle,
+        commands: &[DisplayCommand],
+        z_order: ZOrder,
+        protected: Option<bool>,
+        always_alive: Option<bool>,
+    ) {
+        let z_order = self.offset(z_order);
+        self.inner.modify_command_group(handle, commands, z_order, protected, always_alive)
+    }
+
+    fn maintain_command_group(&mut self, handle: CommandGroupHandle) {
+        self.inner.maintain_command_group(handle)
+    }
+
+    fn remove_command_group(&mut self, handle: CommandGroupHandle) -> Option<Vec<DisplayCommand>> {
+        self.inner.remove_command_group(handle)
+    }
+
+    fn before_exit(&mut self) {
+        self.inner.before_exit()
+    }
+
+    fn present(&mut self, cull: Option<Rect>) -> Result<(), error::DisplayError> {
+        self.inner.present(cull)
+    }
+}
+
+/// Location of a widget within a widget tree, as the index of the child taken at every level from the root
+/// (in the order of `WidgetChildren::children`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WidgetPath(Vec<usize>);
+
+impl WidgetPath {
+    /// Creates the path of the root widget.
+    #[inline]
+    pub fn root() -> Self {
+        WidgetPath(Vec::new())
+    }
+
+    /// Returns the child index taken at every level, starting from the root.
+    #[inline]
+    pub fn indices(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// Returns the amount of levels below the root (zero for the root itself).
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the path of the `index`th child of the widget at this path.
+    pub fn child(&self, index: usize) -> Self {
+        let mut path = self.clone();
+        path.0.push(index);
+        path
+    }
+
+    /// Returns the path of the widget at `path` relative to the widget at this path.
+    pub fn join(&self, path: &WidgetPath) -> Self {
+        let mut joined = self.clone();
+        joined.0.extend_from_slice(&path.0);
+        joined
+    }
+
+    /// Returns the path of the parent, or `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.0.split_last()?;
+        Some(WidgetPath(parent.to_vec()))
+    }
+
+    /// Returns `true` if the widget at this path is `ancestor` or one of its descendants.
+    #[inline]
+    pub fn starts_with(&self, ancestor: &WidgetPath) -> bool {
+        self.0.starts_with(&ancestor.0)
+    }
+
+    /// Returns the widget at this path within the tree of `root`, if it still exists.
+    pub fn resolve<'a, U, G>(
+        &self,
+        root: &'a dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    ) -> Option<
+        &'a dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        let mut widget = root;
+        for &index in &self.0 {
+            widget = widget.children().into_iter().nth(index)?;
+        }
+        Some(widget)
+    }
+
+    /// Returns the widget at this path within the tree of `root` mutably, if it still exists.
+    pub fn resolve_mut<'a, U, G>(
+        &self,
+        root: &'a mut dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    ) -> Option<
+        &'a mut dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        let mut widget = root;
+        for &index in &self.0 {
+            widget = widget.children_mut().into_iter().nth(index)?;
+        }
+        Some(widget)
+    }
+}
+
+impl From<Vec<usize>> for WidgetPath {
+    #[inline]
+    fn from(indices: Vec<usize>) -> Self {
+        WidgetPath(indices)
+    }
+}
+
+impl std::fmt::Display for WidgetPath {
+    /// Formats the path as its indices separated by dots (e.g. `0.2.1`), or `root` for the root.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "root");
+        }
+        for (i, index) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Visits `root` and its descendants in tree order (i.e. every widget before its children),
+/// invoking `visitor` with each widget and its path from `root`.
+///
+/// The descendants of a widget are skipped if `visitor` returns `false` for it.
+pub fn visit<U, G>(
+    root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    mut visitor: impl FnMut(
+        &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+        &WidgetPath,
+    ) -> bool,
+) {
+    fn visit_impl<U, G>(
+        widget: &dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+        path: &mut WidgetPath,
+        visitor: &mut dyn FnMut(
+            &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+            &WidgetPath,
+        ) -> bool,
+    ) {
+        if !visitor(widget, path) {
+            return;
+        }
+
+        for (i, child) in widget.children().into_iter().enumerate() {
+            path.0.push(i);
+            visit_impl(child, path, visitor);
+            path.0.pop();
+        }
+    }
+
+    visit_impl(root, &mut WidgetPath::root(), &mut visitor);
+}
+
+/// Returns the paths of every widget within the tree of `root` (including `root` itself) matching `predicate`,
+/// in tree order.
+///
+/// For instance, the second button within a dialog:
+/// ```ignore
+/// let dialog = base::find_by_id(&root, "dialog").unwrap();
+/// let buttons = base::find_all(dialog.resolve(&root).unwrap(), |widget| widget.widget_kind() == "ButtonWidget");
+/// let second_button = buttons.get(1).map(|path| dialog.join(path));
+/// ```
+pub fn find_all<U, G>(
+    root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    mut predicate: impl FnMut(
+        &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    ) -> bool,
+) -> Vec<WidgetPath> {
+    let mut paths = Vec::new();
+    visit(root, |widget, path| {
+        if predicate(widget) {
+            paths.push(path.clone());
+        }
+        true
+    });
+    paths
+}
+
+/// Returns the path of the first widget within the tree of `root` (including `root` itself) matching `predicate`,
+/// in tree order.
+pub fn find<U, G>(
+    root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    mut predicate: impl FnMut(
+        &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    ) -> bool,
+) -> Option<WidgetPath> {
+    let mut found = None;
+    visit(root, |widget, path| {
+        if found.is_none() && predicate(widget) {
+            found = Some(path.clone());
+        }
+        found.is_none()
+    });
+    found
+}
+
+/// Returns the path of the first widget within the tree of `root` (including `root` itself) whose ID
+/// (see `WidgetChildren::id`) is `id`.
+pub fn find_by_id<U, G>(
+    root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    id: &str,
+) -> Option<WidgetPath> {
+    find(root, |widget| widget.id() == Some(id))
+}
+
+/// Returns the paths of every widget under `point`, ordered from the top-most widget to the bottom-most.
+///
+/// A widget is under the point if it's drawn (as described in `invoke_draw`), takes input (i.e. its visibility isn't
+/// `Static`) and its absolute bounds contain the point.
+/// Widgets are ordered as they appear on screen: widgets in higher layers (see `Layer`) come first,
+/// then widgets drawn later come before the widgets they're drawn above (i.e. children before their parent
+/// and later siblings before earlier siblings).
+pub fn hit_test<U, G>(
+    root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    point: AbsolutePoint,
+) -> Vec<WidgetPath> {
+    let mut hits = Vec::new();
+    let mut order = 0;
+    collect_hits(root, point, &mut Vec::new(), Layer::Base, None, &mut order, &mut hits);
+    hits.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)));
+    hits.into_iter().map(|(_, _, path)| path).collect()
+}
+
+/// Records the widgets under `point` with their layer and draw order.
+fn collect_hits<U, G>(
+    widget: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    point: AbsolutePoint,
+    path: &mut Vec<usize>,
+    layer: Layer,
+    viewport: Option<AbsoluteRect>,
+    order: &mut usize,
+    hits: &mut Vec<(Layer, usize, WidgetPath)>,
+) {
+    let layer = layer.max(widget.layer());
+    let bounds = widget.abs_bounds();
+    if is_drawn(widget, &bounds, viewport)
+        && widget.visibility() != Visibility::Static
+        && viewport.map(|viewport| viewport.contains(point)).unwrap_or(true)
+        && bounds.contains(point)
+    {
+        hits.push((layer, *order, WidgetPath(path.clone())));
+    }
+    *order += 1;
+
+    let viewport = match child_viewport(widget, viewport) {
+        Some(viewport) => viewport,
+        None => return,
+    };
+
+    for (i, child) in widget.children().into_iter().enumerate() {
+        path.push(i);
+        collect_hits(child, point, path, layer, viewport, order, hits);
+        path.pop();
+    }
+}
+
+/// The widget which receives mouse input at a point, being the top-most widget under it (see `hit_test`),
+/// along with its ancestors.
+///
+/// The application resolves the target before emitting each mouse event, and provides it through
+/// `UpdateAuxiliary::pointer_target`, so that widgets occluded by another widget don't handle the event
+/// even though it lies within their bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerTarget {
+    point: AbsolutePoint,
+    path: Option<WidgetPath>,
+    tree: usize,
+    ids: Vec<usize>,
+    // Whether widgets other than the top-most widget and its ancestors are occluded even if no widget was hit.
+    exclusive: bool,
+}
+
+/// How a widget tree within a stack of independent trees takes mouse input (see `PointerTarget::new_stacked`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerInput {
+    /// Widgets of the tree take input where they're hit; elsewhere, input falls through to the trees beneath.
+    PassThrough,
+    /// Widgets of the tree take input where they're hit, and the trees beneath never take mouse input
+    /// (e.g. a modal tool layer).
+    Block,
+    /// Widgets of the tree never take mouse input, which always falls through to the trees beneath
+    /// (e.g. a HUD which only displays information).
+    Ignore,
+}
+
+impl Default for LayerInput {
+    #[inline]
+    fn default() -> Self {
+        LayerInput::PassThrough
+    }
+}
+
+impl PointerTarget {
+    /// Resolves the target at `point` within the tree of `root`.
+    pub fn new<U, G>(
+        root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+        point: AbsolutePoint,
+    ) -> Self {
+        let path = hit_test(root, point).into_iter().next();
+        let ids = path.as_ref().map(|path| path_ids(root, path)).unwrap_or_default();
+
+        PointerTarget { point, path, tree: 0, ids, exclusive: false }
+    }
+
+    /// Resolves the target at `point` within a stack of independent widget trees, ordered from the bottom up,
+    /// according to the `LayerInput` of each tree.
+    ///
+    /// Unlike a target within a single tree, widgets are occluded even if no widget was hit at `point`,
+    /// as the point may lie within widgets of a tree which ignores input.
+    pub fn new_stacked<U, G>(
+        trees: &[(
+            &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+            LayerInput,
+        )],
+        point: AbsolutePoint,
+    ) -> Self {
+        for (tree, &(root, input)) in trees.iter().enumerate().rev() {
+            if input == LayerInput::Ignore {
+                continue;
+            }
+
+            if let Some(path) = hit_test(root, point).into_iter().next() {
+                let ids = path_ids(root, &path);
+                return PointerTarget { point, path: Some(path), tree, ids, exclusive: true };
+            }
+
+            if input == LayerInput::Block {
+                break;
+            }
+        }
+
+        PointerTarget { point, path: None, tree: 0, ids: Vec::new(), exclusive: true }
+    }
+
+    /// Returns the point the target was resolved at.
+    #[inline]
+    pub fn point(&self) -> AbsolutePoint {
+        self.point
+    }
+
+    /// Returns the path of the top-most widget, or `None` if no widget is under the point.
+    #[inline]
+    pub fn path(&self) -> Option<&WidgetPath> {
+        self.path.as_ref()
+    }
+
+    /// Returns the index of the tree the path of the top-most widget is relative to,
+    /// within the stack the target was resolved in (always 0 for a single tree).
+    #[inline]
+    pub fn tree(&self) -> usize {
+        self.tree
+    }
+
+    /// Returns `true` unless `widget` is occluded at `point`, i.e. `point` is where the target was resolved
+    /// and `widget` is neither the top-most widget nor one of its ancestors.
+    ///
+    /// Events at any other point (e.g. a synthesized event) aren't judged, as the target doesn't apply to them.
+    pub fn accepts<W: ?Sized>(&self, widget: &W, point: AbsolutePoint) -> bool {
+        point != self.point
+            || (self.path.is_none() && !self.exclusive)
+            || self.ids.contains(&(widget as *const W as *const () as usize))
+    }
+}
+
+/// Returns the IDs (see `widget_id`) of the widget at `path` within the tree of `root`, and of its ancestors.
+fn path_ids<U, G>(
+    root: &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    path: &WidgetPath,
+) -> Vec<usize> {
+    let mut widget = root;
+    let mut ids = vec![widget_id(widget)];
+    for &index in path.indices() {
+        widget = match widget.children().into_iter().nth(index) {
+            Some(child) => child,
+            None => break,
+        };
+        ids.push(widget_id(widget));
+    }
+    ids
 }
 
 /// Creates a color from 3 unsigned 8-bit components and an `f32` alpha.
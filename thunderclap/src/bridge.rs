@@ -0,0 +1,249 @@
+//! Bridges external event sources (e.g. sensor readings or network messages received on another thread)
+//! into event queues which widgets can bind to.
+//!
+//! A [`ChannelBridge`](struct.ChannelBridge.html) forwards the messages of a channel receiver into an `RcEventQueue`.
+//! Once listeners have been created (or the queue has been bound to a verb graph, for types deriving `Event`),
+//! the bridge is handed to the application (see `UpdateAuxiliary::add_external_source`), which pumps it every frame:
+//!
+//! ```ignore
+//! let (sender, receiver) = std::sync::mpsc::channel();
+//! std::thread::spawn(move || loop {
+//!     sender.send(read_temperature()).unwrap();
+//! });
+//!
+//! let bridge = ChannelBridge::new(receiver);
+//! let temperatures = bridge.listen();
+//! u_aux.add_external_source(Box::new(bridge));
+//!
+//! // Later, when updating the widget which stores the listener:
+//! for temperature in temperatures.peek() {
+//!     self.gauge.data.value = temperature;
+//! }
+//! ```
+
+use {
+    reclutch::event::{RcEventListener, RcEventQueue},
+    std::{
+        cell::RefCell,
+        panic,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc, Arc, Mutex,
+        },
+        thread,
+    },
+};
+
+/// Outcome of receiving from a channel without blocking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Received<T> {
+    /// A message was received.
+    Item(T),
+    /// No message is available yet.
+    Empty,
+    /// The channel has been disconnected and no messages remain.
+    Disconnected,
+}
+
+/// Non-blocking receiving end of a channel.
+pub trait ExternalReceiver {
+    type Item;
+
+    /// Returns the next message if one is available, without blocking.
+    fn try_next(&mut self) -> Received<Self::Item>;
+}
+
+impl<T> ExternalReceiver for mpsc::Receiver<T> {
+    type Item = T;
+
+    fn try_next(&mut self) -> Received<T> {
+        match self.try_recv() {
+            Ok(item) => Received::Item(item),
+            Err(mpsc::TryRecvError::Empty) => Received::Empty,
+            Err(mpsc::TryRecvError::Disconnected) => Received::Disconnected,
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl<T> ExternalReceiver for crossbeam_channel::Receiver<T> {
+    type Item = T;
+
+    fn try_next(&mut self) -> Received<T> {
+        match self.try_recv() {
+            Ok(item) => Received::Item(item),
+            Err(crossbeam_channel::TryRecvError::Empty) => Received::Empty,
+            Err(crossbeam_channel::TryRecvError::Disconnected) => Received::Disconnected,
+        }
+    }
+}
+
+/// Source of events from outside the widget tree, pumped every frame by the application.
+pub trait ExternalSource {
+    /// Forwards the pending events into the event queue, returning how many were forwarded.
+    fn pump(&mut self) -> usize;
+    /// Returns `false` once no more events can arrive, after which the source can be dropped.
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+/// Forwards the messages of a channel receiver into an event queue.
+pub struct ChannelBridge<R: ExternalReceiver> {
+    receiver: R,
+    queue: RcEventQueue<R::Item>,
+    limit: Option<usize>,
+    connected: bool,
+}
+
+impl<R: ExternalReceiver> ChannelBridge<R> {
+    /// Creates a bridge which forwards every pending message on each pump.
+    pub fn new(receiver: R) -> Self {
+        ChannelBridge { receiver, queue: RcEventQueue::new(), limit: None, connected: true }
+    }
+
+    /// Limits the amount of messages forwarded per pump (i.e. per frame), so that a flood of messages
+    /// doesn't stall the application. The remaining messages are forwarded on the following frames.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns the event queue which messages are forwarded into.
+    #[inline]
+    pub fn queue(&self) -> &RcEventQueue<R::Item> {
+        &self.queue
+    }
+
+    /// Creates a listener of the event queue.
+    #[inline]
+    pub fn listen(&self) -> RcEventListener<R::Item> {
+        self.queue.listen()
+    }
+
+    /// Returns the receiver.
+    #[inline]
+    pub fn receiver(&self) -> &R {
+        &self.receiver
+    }
+}
+
+impl<R: ExternalReceiver> ExternalSource for ChannelBridge<R> {
+    fn pump(&mut self) -> usize {
+        let mut count = 0;
+        while self.limit.map(|limit| count < limit).unwrap_or(true) {
+            match self.receiver.try_next() {
+                Received::Item(item) => {
+                    self.queue.emit_owned(item);
+                    count += 1;
+                }
+                Received::Empty => break,
+                Received::Disconnected => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+        count
+    }
+
+    #[inline]
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Fixed amount of worker threads which run tasks off the UI thread (e.g. asynchronous validators, see `validate`).
+///
+/// The workers are started by the first task. Results are sent back over a channel, which the UI polls
+/// (e.g. through a `ChannelBridge`).
+pub struct TaskPool {
+    threads: usize,
+    sender: RefCell<Option<mpsc::Sender<Task>>>,
+    workers: RefCell<Vec<thread::JoinHandle<()>>>,
+    active: Arc<AtomicUsize>,
+}
+
+impl TaskPool {
+    /// Creates a pool of `threads` worker threads (at least one).
+    pub fn new(threads: usize) -> Self {
+        TaskPool {
+            threads: threads.max(1),
+            sender: RefCell::new(None),
+            workers: RefCell::new(Vec::new()),
+            active: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Queues a task, which runs on the first idle worker.
+    pub fn spawn(&self, task: impl FnOnce() + Send + 'static) {
+        let mut sender = self.sender.borrow_mut();
+        let sender = sender.get_or_insert_with(|| self.start());
+
+        self.active.fetch_add(1, Ordering::SeqCst);
+        let active = ActiveTask(self.active.clone());
+        let _ = sender.send(Box::new(move || {
+            let _active = active;
+            task();
+        }));
+    }
+
+    /// Returns `true` if no task is queued or running.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        self.active.load(Ordering::SeqCst) == 0
+    }
+
+    /// Returns the amount of worker threads.
+    #[inline]
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    fn start(&self) -> mpsc::Sender<Task> {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        self.workers.borrow_mut().extend((0..self.threads).map(|_| {
+            let receiver = receiver.clone();
+            thread::spawn(move || loop {
+                // The lock is released before the task runs, so that the other workers can take tasks meanwhile.
+                let task = receiver.lock().unwrap().recv();
+                match task {
+                    // A panicking task is reported by the panic hook, but doesn't take the worker down.
+                    Ok(task) => {
+                        let _ = panic::catch_unwind(panic::AssertUnwindSafe(task));
+                    }
+                    Err(_) => break,
+                }
+            })
+        }));
+        sender
+    }
+}
+
+/// Marks a task as finished when dropped, even if the task panics.
+struct ActiveTask(Arc<AtomicUsize>);
+
+impl Drop for ActiveTask {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        TaskPool::new(4)
+    }
+}
+
+impl Drop for TaskPool {
+    fn drop(&mut self) {
+        // Disconnecting the channel stops the workers once they've finished the queued tasks.
+        self.sender.borrow_mut().take();
+        for worker in self.workers.borrow_mut().drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
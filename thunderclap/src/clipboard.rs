@@ -0,0 +1,87 @@
+//! Clipboard abstraction, used by text widgets to exchange text with the rest of the system.
+//!
+//! The clipboard is provided by the update auxiliary (see `UpdateAuxiliary::clipboard`), which is `None` by default.
+//! Besides the regular clipboard, some platforms (X11 and Wayland desktops) have a *primary selection*:
+//! selecting text copies it into the primary selection and middle-clicking pastes it.
+//! Widgets only use the primary selection if `Clipboard::supports_primary_selection` returns `true`.
+
+/// Buffer of a clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardKind {
+    /// Regular clipboard, written to by copying (e.g. Ctrl + C).
+    Clipboard,
+    /// Primary selection, written to by selecting text (X11 convention).
+    Primary,
+}
+
+/// Reads and writes text from and to the clipboard.
+pub trait Clipboard {
+    /// Returns the text in a clipboard buffer, if any.
+    fn text(&mut self, kind: ClipboardKind) -> Option<String>;
+    /// Replaces the contents of a clipboard buffer with text.
+    fn set_text(&mut self, kind: ClipboardKind, text: String);
+    /// Returns `true` if the primary selection is supported, which is only the case on platforms following the X11 convention.
+    ///
+    /// If not supported, the primary selection shouldn't be read from nor written to.
+    #[inline]
+    fn supports_primary_selection(&self) -> bool {
+        false
+    }
+}
+
+/// Returns `true` if the target platform follows the X11 primary selection convention (i.e. Unix-like desktops other than macOS).
+#[inline]
+pub fn platform_has_primary_selection() -> bool {
+    cfg!(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "android"))))
+}
+
+/// Clipboard local to the application, which isn't shared with other applications.
+///
+/// This serves as a fallback where no system clipboard is integrated (as well as for testing).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalClipboard {
+    clipboard: Option<String>,
+    primary: Option<String>,
+    primary_selection: bool,
+}
+
+impl LocalClipboard {
+    /// Creates an empty clipboard, which supports the primary selection if the platform has one.
+    pub fn new() -> Self {
+        LocalClipboard::with_primary_selection(platform_has_primary_selection())
+    }
+
+    /// Creates an empty clipboard, overriding whether the primary selection is supported.
+    pub fn with_primary_selection(primary_selection: bool) -> Self {
+        LocalClipboard { clipboard: None, primary: None, primary_selection }
+    }
+}
+
+impl Default for LocalClipboard {
+    fn default() -> Self {
+        LocalClipboard::new()
+    }
+}
+
+impl Clipboard for LocalClipboard {
+    fn text(&mut self, kind: ClipboardKind) -> Option<String> {
+        match kind {
+            ClipboardKind::Clipboard => self.clipboard.clone(),
+            ClipboardKind::Primary if self.primary_selection => self.primary.clone(),
+            ClipboardKind::Primary => None,
+        }
+    }
+
+    fn set_text(&mut self, kind: ClipboardKind, text: String) {
+        match kind {
+            ClipboardKind::Clipboard => self.clipboard = Some(text),
+            ClipboardKind::Primary if self.primary_selection => self.primary = Some(text),
+            ClipboardKind::Primary => {}
+        }
+    }
+
+    #[inline]
+    fn supports_primary_selection(&self) -> bool {
+        self.primary_selection
+    }
+}
@@ -0,0 +1,222 @@
+//! Crash report bundles, capturing the state of the UI when the application panics.
+//!
+//! Once `install` is invoked, the application keeps track of its recent events, the widget tree as of the last
+//! frame and the current theme (see `app::App`). If the application then panics, the panic handler writes a bundle
+//! into a new `crash-<unix time>` directory within `CrashReportConfig::directory`, containing:
+//! - `panic.txt`: the panic message and location.
+//! - `events.txt`: the most recent window events, with the time since installation.
+//! - `tree.txt`: the widget tree as of the last frame (in the format of `test::layout_snapshot`).
+//! - `theme.txt`: the color scheme, contrast and layout direction of the theme.
+//! - `screenshot.png`: the last frame, if a screenshot capture is set (see `set_screenshot_capture`).
+//!
+//! Note that the events include text typed by the user; bundles should only be collected with their consent.
+//! The previous panic hook (e.g. the one printing the panic to stderr) is invoked after the bundle is written.
+//! Panics are reported before they unwind, so panics later caught by an `ErrorBoundary` are reported as well.
+
+use {
+    crate::{base, draw, test},
+    reclutch::display::DisplayCommand,
+    std::{
+        cell::RefCell,
+        collections::VecDeque,
+        fmt::Write as _,
+        fs, io,
+        panic::{self, PanicInfo},
+        path::{Path, PathBuf},
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Configuration of the crash reports.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CrashReportConfig {
+    /// Directory in which the bundles are written; created if it doesn't exist.
+    pub directory: PathBuf,
+    /// Amount of recent events kept in the bundle.
+    pub event_capacity: usize,
+}
+
+impl CrashReportConfig {
+    /// Creates a configuration writing bundles into `directory`, keeping the 256 most recent events.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        CrashReportConfig { directory: directory.into(), event_capacity: 256 }
+    }
+}
+
+struct CrashContext {
+    config: CrashReportConfig,
+    start: Instant,
+    events: VecDeque<String>,
+    tree: String,
+    theme: String,
+    screenshot: Option<Box<dyn FnMut() -> Option<Vec<u8>>>>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<CrashContext>> = RefCell::new(None);
+}
+
+/// Installs the crash report panic handler for panics on the current thread (typically the UI thread).
+///
+/// Invoking `install` again replaces the configuration, keeping the captured state.
+pub fn install(config: CrashReportConfig) {
+    let installed = CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let installed = context.is_some();
+        match &mut *context {
+            Some(context) => context.config = config,
+            None => {
+                *context = Some(CrashContext {
+                    config,
+                    start: Instant::now(),
+                    events: VecDeque::new(),
+                    tree: String::new(),
+                    theme: String::new(),
+                    screenshot: None,
+                })
+            }
+        }
+        installed
+    });
+
+    if !installed {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            match write_bundle(info) {
+                Ok(Some(path)) => eprintln!("crash report written to {}", path.display()),
+                Ok(None) => {}
+                Err(error) => eprintln!("failed to write crash report: {}", error),
+            }
+            previous(info);
+        }));
+    }
+}
+
+/// Returns `true` if crash reports are installed on the current thread.
+pub fn is_installed() -> bool {
+    CONTEXT.with(|context| context.try_borrow().map(|context| context.is_some()).unwrap_or(false))
+}
+
+/// Sets a function capturing the last frame as PNG bytes, invoked while writing a bundle.
+///
+/// The display backend can't read back what it presented, so the capture is left to the application
+/// (e.g. reading the front buffer of its OpenGL context).
+pub fn set_screenshot_capture(capture: impl FnMut() -> Option<Vec<u8>> + 'static) {
+    with_context(|context| context.screenshot = Some(Box::new(capture)));
+}
+
+/// Records an event into the trace of recent events, if crash reports are installed.
+///
+/// The description is only formatted if it's recorded.
+pub fn record_event(description: impl FnOnce() -> String) {
+    with_context(|context| {
+        if context.config.event_capacity == 0 {
+            return;
+        }
+        while context.events.len() >= context.config.event_capacity {
+            context.events.pop_front();
+        }
+        let elapsed = context.start.elapsed();
+        context.events.push_back(format!(
+            "{}.{:03} {}",
+            elapsed.as_secs(),
+            elapsed.subsec_millis(),
+            description()
+        ));
+    });
+}
+
+/// Captures the widget tree under `root`, if crash reports are installed.
+pub fn capture_tree<U, G>(
+    root: &dyn base::WidgetChildren<
+        UpdateAux = U,
+        GraphicalAux = G,
+        DisplayObject = DisplayCommand,
+    >,
+) {
+    with_context(|context| context.tree = test::layout_snapshot(root));
+}
+
+/// Captures the theme, if crash reports are installed.
+pub fn capture_theme(theme: &dyn draw::Theme) {
+    with_context(|context| {
+        let data = theme.data();
+        context.theme = format!(
+            "contrast: {:?}\ndirection: {:?}\nscheme: {:#?}\n",
+            data.contrast, data.direction, data.scheme
+        );
+    });
+}
+
+fn with_context(f: impl FnOnce(&mut CrashContext)) {
+    CONTEXT.with(|context| {
+        if let Ok(mut context) = context.try_borrow_mut() {
+            if let Some(context) = &mut *context {
+                f(context);
+            }
+        }
+    });
+}
+
+/// Writes the bundle of a panic, returning its directory (or `None` if crash reports aren't installed).
+fn write_bundle(info: &PanicInfo) -> io::Result<Option<PathBuf>> {
+    // The context may be inaccessible if the panic occurred while capturing state.
+    let result = CONTEXT.try_with(|context| match context.try_borrow_mut() {
+        Ok(mut context) => match &mut *context {
+            Some(context) => write_context(context, info).map(Some),
+            None => Ok(None),
+        },
+        Err(_) => Ok(None),
+    });
+
+    result.unwrap_or(Ok(None))
+}
+
+fn write_context(context: &mut CrashContext, info: &PanicInfo) -> io::Result<PathBuf> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+    let path = unique_directory(&context.config.directory, time);
+    fs::create_dir_all(&path)?;
+
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let mut panic_text = format!("{}\n", message);
+    if let Some(location) = info.location() {
+        let _ = writeln!(
+            panic_text,
+            "at {}:{}:{}",
+            location.file(),
+            location.line(),
+            location.column()
+        );
+    }
+
+    let mut events = String::new();
+    for event in &context.events {
+        let _ = writeln!(events, "{}", event);
+    }
+
+    fs::write(path.join("panic.txt"), panic_text)?;
+    fs::write(path.join("events.txt"), events)?;
+    fs::write(path.join("tree.txt"), &context.tree)?;
+    fs::write(path.join("theme.txt"), &context.theme)?;
+    if let Some(screenshot) = context.screenshot.as_mut().and_then(|capture| capture()) {
+        fs::write(path.join("screenshot.png"), screenshot)?;
+    }
+
+    Ok(path)
+}
+
+/// Returns a path for a new bundle directory which doesn't exist yet.
+fn unique_directory(directory: &Path, time: u64) -> PathBuf {
+    let mut path = directory.join(format!("crash-{}", time));
+    let mut suffix = 1;
+    while path.exists() {
+        path = directory.join(format!("crash-{}-{}", time, suffix));
+        suffix += 1;
+    }
+    path
+}
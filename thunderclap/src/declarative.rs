@@ -0,0 +1,92 @@
+//! Builds widget trees at runtime from a serialized description, such as UI fragments provided by plugins or downloaded.
+//!
+//! A description lists the widget type, properties, layout data (for the parent layout), optional ID and children of each widget.
+//! Widget types are looked up by name in a [`WidgetFactory`](../factory/struct.WidgetFactory.html).
+//!
+//! # Example
+//! ```ignore
+//! let factory = WidgetFactory::with_core_widgets();
+//! let mut fragment = declarative::build_ron(
+//!     &factory,
+//!     r#"(
+//!         type: "VStack",
+//!         children: [
+//!             (type: "Label", properties: { "text": "Name" }),
+//!             (type: "TextArea", id: Some("name"), properties: { "placeholder": "Jane Doe" }),
+//!             (type: "Button", id: Some("submit"), properties: { "text": "Submit" }, layout: { "top_margin": 5.0 }),
+//!         ],
+//!     )"#,
+//!     &theme,
+//!     u_aux,
+//! )?;
+//!
+//! let submit = fragment.get_mut::<ButtonWidget<_, _>>("submit").unwrap();
+//! ```
+//!
+//! Descriptions are `serde` types, so other formats (e.g. JSON) can be deserialized and passed to `build`.
+
+use {
+    crate::{
+        base, draw,
+        error::DeclarativeError,
+        factory::{DynamicWidget, Properties, WidgetFactory},
+    },
+    serde::{Deserialize, Serialize},
+};
+
+/// Serialized description of a widget and its children.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WidgetDescription {
+    /// Name of the widget type in the `WidgetFactory`.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// ID used to find the widget once built.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Properties of the widget data.
+    #[serde(default)]
+    pub properties: Properties,
+    /// Layout data, used when the widget is pushed into the parent layout.
+    #[serde(default)]
+    pub layout: Properties,
+    #[serde(default)]
+    pub children: Vec<WidgetDescription>,
+}
+
+/// Builds a widget tree from a description.
+pub fn build<U, G>(
+    factory: &WidgetFactory<U, G>,
+    description: &WidgetDescription,
+    theme: &dyn draw::Theme,
+    u_aux: &mut U,
+) -> Result<DynamicWidget<U, G>, DeclarativeError>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    let children = description
+        .children
+        .iter()
+        .map(|child| build(factory, child, theme, u_aux))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut widget =
+        factory.create(&description.type_name, &description.properties, children, theme, u_aux)?;
+    widget.set_id(description.id.clone());
+    widget.set_layout_data(description.layout.clone());
+    Ok(widget)
+}
+
+/// Builds a widget tree from a description in RON (Rusty Object Notation).
+pub fn build_ron<U, G>(
+    factory: &WidgetFactory<U, G>,
+    source: &str,
+    theme: &dyn draw::Theme,
+    u_aux: &mut U,
+) -> Result<DynamicWidget<U, G>, DeclarativeError>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    build(factory, &ron::de::from_str(source)?, theme, u_aux)
+}
@@ -1,16 +1,23 @@
 //! Simple theme framework based on Flutter.
 
 pub mod state;
+pub mod text_cache;
 
 use {
     crate::{base, geom::*},
-    reclutch::display::{Color, DisplayCommand, FontInfo, ResourceReference, Size},
+    reclutch::display::{
+        Color, DisplayCommand, FontInfo, Gradient, Point, Rect, ResourceReference, Size, StyleColor,
+    },
 };
 
 /// Implemented by types which are capable of changing themes.
 pub trait Themed {
     /// Updates `self` from `theme`.
     fn load_theme(&mut self, theme: &dyn Theme, aux: &dyn base::GraphicalAuxiliary);
+    /// Sets the style overrides merged over the theme, which are kept across theme changes.
+    ///
+    /// Ignored by types which don't support style overrides.
+    fn set_style(&mut self, _style: StyleOverrides) {}
 }
 
 /// Empty `Themed` type to assist in satisfying `HasTheme` required by `WidgetChildren`
@@ -35,18 +42,114 @@ pub trait Painter<T> {
     fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect;
     /// Returns a list of display commands which visualize `state`.
     fn draw(&mut self, state: T) -> Vec<DisplayCommand>;
+    /// Returns the style overrides applied to each state, if the painter supports style overrides.
+    fn style(&self) -> Option<&StyleOverrides> {
+        None
+    }
+    /// Sets the style overrides applied to each state (ignored if unsupported).
+    fn set_style(&mut self, _style: StyleOverrides) {}
+}
+
+/// Per-widget deviations from the theme, merged over the state of a widget before it's painted,
+/// so that individual widgets can be styled without writing a custom `Painter`.
+///
+/// Unset fields fall back to the theme (i.e. the widget data derived from the theme).
+/// Style overrides are attached to a widget through `Themed::set_style`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleOverrides {
+    /// Foreground (e.g. text) color.
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub corner_radius: Option<f32>,
+    pub typeface: Option<TypefaceStyle>,
+}
+
+impl StyleOverrides {
+    /// Returns `true` if no fields are overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Default::default()
+    }
+
+    /// Returns the overrides of `self` merged with `other`, where the fields set in `other` take precedence.
+    pub fn merge(&self, other: &StyleOverrides) -> StyleOverrides {
+        StyleOverrides {
+            color: other.color.or(self.color),
+            background: other.background.or(self.background),
+            corner_radius: other.corner_radius.or(self.corner_radius),
+            typeface: other.typeface.clone().or_else(|| self.typeface.clone()),
+        }
+    }
+}
+
+/// Painter state which style overrides can be merged into.
+///
+/// Fields which don't apply to the state are ignored.
+pub trait Stylable {
+    /// Merges `style` over the state.
+    fn apply_style(&mut self, style: &StyleOverrides);
+}
+
+/// Wraps a painter of a `Stylable` state to support style overrides.
+///
+/// Themes wrap their painters in this (e.g. `Box::new(Styled::new(ButtonPainter))`).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Styled<P> {
+    painter: P,
+    style: StyleOverrides,
+}
+
+impl<P> Styled<P> {
+    /// Wraps `painter`, initially without overrides.
+    pub fn new(painter: P) -> Self {
+        Styled { painter, style: Default::default() }
+    }
+}
+
+impl<T: Stylable, P: Painter<T>> Painter<T> for Styled<P> {
+    fn invoke(&self, theme: &dyn Theme) -> Box<dyn Painter<T>> {
+        // The overrides are re-applied by `Themed::load_theme`.
+        self.painter.invoke(theme)
+    }
+
+    fn size_hint(&self, mut state: T) -> Size {
+        state.apply_style(&self.style);
+        self.painter.size_hint(state)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        self.painter.paint_hint(rect)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        self.painter.mouse_hint(rect)
+    }
+
+    fn draw(&mut self, mut state: T) -> Vec<DisplayCommand> {
+        state.apply_style(&self.style);
+        self.painter.draw(state)
+    }
+
+    #[inline]
+    fn style(&self) -> Option<&StyleOverrides> {
+        Some(&self.style)
+    }
+
+    #[inline]
+    fn set_style(&mut self, style: StyleOverrides) {
+        self.style = style;
+    }
 }
 
-/// Lightens a color by a specified amount
+/// Lightens a color by a specified amount, blending it towards white in linear light.
 pub fn lighten(color: Color, amount: f32) -> Color {
-    use reclutch::palette::Shade;
-    Color::from_linear(color.into_linear().lighten(amount))
+    let white = Color::new(1.0, 1.0, 1.0, color.alpha);
+    ColorSpace::LinearSrgb.mix(color, white, amount)
 }
 
-/// Darkens a color by a specified amount
+/// Darkens a color by a specified amount, scaling down its linear light (which preserves its hue).
 pub fn darken(color: Color, amount: f32) -> Color {
-    use reclutch::palette::Shade;
-    Color::from_linear(color.into_linear().darken(amount))
+    let black = Color::new(0.0, 0.0, 0.0, color.alpha);
+    ColorSpace::LinearSrgb.mix(color, black, amount)
 }
 
 /// Darkens or lightens a color to contrast the theme.
@@ -70,6 +173,237 @@ pub fn with_opacity(color: Color, opacity: f32) -> Color {
     Color::new(color.red, color.green, color.blue, opacity)
 }
 
+/// Number of stops inserted between each pair of gradient stops to approximate blending in another color space,
+/// as display backends blend gradients in sRGB.
+const GRADIENT_SUBDIVISIONS: usize = 8;
+
+/// Returns a linear gradient from `start` to `end` blended in linear light, or the color of the first stop
+/// if the gradient is degenerate (i.e. its ends coincide or aren't finite), which would otherwise produce NaNs
+/// in the display backend.
+pub fn linear_gradient(start: Point, end: Point, stops: Vec<(f64, Color)>) -> StyleColor {
+    linear_gradient_in(start, end, stops, ColorSpace::LinearSrgb)
+}
+
+/// Returns a linear gradient like `linear_gradient`, blended in `space`.
+pub fn linear_gradient_in(
+    start: Point,
+    end: Point,
+    stops: Vec<(f64, Color)>,
+    space: ColorSpace,
+) -> StyleColor {
+    let finite =
+        start.x.is_finite() && start.y.is_finite() && end.x.is_finite() && end.y.is_finite();
+    if finite && (end - start).square_length() > std::f32::EPSILON {
+        StyleColor::LinearGradient(Gradient { start, end, stops: space.gradient_stops(&stops) })
+    } else {
+        StyleColor::Color(
+            stops.first().map(|stop| stop.1).unwrap_or_else(|| Color::new(0.0, 0.0, 0.0, 0.0)),
+        )
+    }
+}
+
+/// Returns `count` colors evenly blended in linear light from `start` to `end` (inclusive), e.g. for intensity scales.
+pub fn swatch(start: Color, end: Color, count: usize) -> Vec<Color> {
+    match count {
+        0 => Vec::new(),
+        1 => vec![end],
+        _ => (0..count)
+            .map(|i| ColorSpace::LinearSrgb.mix(start, end, i as f32 / (count - 1) as f32))
+            .collect(),
+    }
+}
+
+/// Blends from `from` (where `t` is 0) to `to` (where `t` is 1) in the Oklab color space.
+///
+/// Unlike blending the RGB components, the perceived lightness changes evenly, so the blend
+/// doesn't pass through muddy or overly dark colors (e.g. when fading between interaction states).
+#[inline]
+pub fn mix(from: Color, to: Color, t: f32) -> Color {
+    ColorSpace::Oklab.mix(from, to, t)
+}
+
+/// Color space in which colors are blended.
+///
+/// Colors are stored (and handed to the display) with sRGB-encoded components, which aren't proportional
+/// to the intensity of light. Blending them directly darkens and desaturates the middle of the blend,
+/// so colors are converted to another space, blended, then converted back to sRGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// The sRGB-encoded components, as stored; only useful to match blends computed elsewhere (e.g. by CSS).
+    Srgb,
+    /// Linear light, in which blending is physically correct (e.g. for shading and gradients).
+    LinearSrgb,
+    /// Perceptually uniform space, in which the perceived lightness of a blend changes evenly.
+    Oklab,
+}
+
+impl ColorSpace {
+    /// Converts a color to its components in this space, followed by its (straight) alpha.
+    pub fn to_components(self, color: Color) -> [f32; 4] {
+        let rgb = [color.red, color.green, color.blue];
+        let [x, y, z] = match self {
+            ColorSpace::Srgb => rgb,
+            ColorSpace::LinearSrgb => map_rgb(rgb, srgb_to_linear),
+            ColorSpace::Oklab => linear_to_oklab(map_rgb(rgb, srgb_to_linear)),
+        };
+        [x, y, z, color.alpha]
+    }
+
+    /// Converts components in this space (followed by alpha) to a color, clamping it to the sRGB gamut.
+    pub fn from_components(self, components: [f32; 4]) -> Color {
+        let [x, y, z, alpha] = components;
+        let clamp = |c: f32| c.max(0.0).min(1.0);
+        let [r, g, b] = match self {
+            ColorSpace::Srgb => [x, y, z],
+            ColorSpace::LinearSrgb => map_rgb([x, y, z], |c| linear_to_srgb(clamp(c))),
+            ColorSpace::Oklab => map_rgb(oklab_to_linear([x, y, z]), |c| linear_to_srgb(clamp(c))),
+        };
+        Color::new(clamp(r), clamp(g), clamp(b), clamp(alpha))
+    }
+
+    /// Blends from `from` (where `t` is 0) to `to` (where `t` is 1) in this space.
+    pub fn mix(self, from: Color, to: Color, t: f32) -> Color {
+        let t = t.max(0.0).min(1.0);
+        let (from, to) = (self.to_components(from), self.to_components(to));
+        let mut mixed = [0.0; 4];
+        for (mixed, (from, to)) in mixed.iter_mut().zip(from.iter().zip(&to)) {
+            *mixed = from + (to - from) * t;
+        }
+        self.from_components(mixed)
+    }
+
+    /// Returns gradient stops which the display (blending in sRGB) renders as if they were blended in this space,
+    /// by inserting intermediate stops between stops of differing colors.
+    pub fn gradient_stops(self, stops: &[(f64, Color)]) -> Vec<(f64, Color)> {
+        if self == ColorSpace::Srgb {
+            return stops.to_vec();
+        }
+
+        let mut expanded = Vec::with_capacity(stops.len() * GRADIENT_SUBDIVISIONS);
+        for (i, &(offset, color)) in stops.iter().enumerate() {
+            expanded.push((offset, color));
+            if let Some(&(next_offset, next_color)) = stops.get(i + 1) {
+                if next_color == color || next_offset <= offset {
+                    continue;
+                }
+                for step in 1..GRADIENT_SUBDIVISIONS {
+                    let t = step as f64 / GRADIENT_SUBDIVISIONS as f64;
+                    expanded.push((
+                        offset + (next_offset - offset) * t,
+                        self.mix(color, next_color, t as f32),
+                    ));
+                }
+            }
+        }
+        expanded
+    }
+}
+
+/// Converts an sRGB-encoded component (in the range 0 to 1) to linear light.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a component in linear light (in the range 0 to 1) to sRGB encoding.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[inline]
+fn map_rgb(rgb: [f32; 3], f: impl Fn(f32) -> f32) -> [f32; 3] {
+    [f(rgb[0]), f(rgb[1]), f(rgb[2])]
+}
+
+fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn oklab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    let l = (lab[0] + 0.396_337_78 * lab[1] + 0.215_803_76 * lab[2]).powi(3);
+    let m = (lab[0] - 0.105_561_346 * lab[1] - 0.063_854_17 * lab[2]).powi(3);
+    let s = (lab[0] - 0.089_484_18 * lab[1] - 1.291_485_5 * lab[2]).powi(3);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+/// Tonal palette of a single hue, from the lightest (50) to the darkest (900) shade, as used by the Material color system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorSwatch {
+    shades: [Color; 10],
+}
+
+impl ColorSwatch {
+    /// Shade names, in the same order as the shades.
+    pub const SHADES: [u16; 10] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+    /// Creates a swatch from all ten shades, ordered from 50 to 900.
+    #[inline]
+    pub fn new(shades: [Color; 10]) -> Self {
+        ColorSwatch { shades }
+    }
+
+    /// Generates a swatch around `base`, which becomes the 500 shade.
+    ///
+    /// Lighter shades are blended towards white and darker shades towards black (see `mix`).
+    pub fn from_base(base: Color) -> Self {
+        let white = Color::new(1.0, 1.0, 1.0, base.alpha);
+        let black = Color::new(0.0, 0.0, 0.0, base.alpha);
+        let lighter = [0.12, 0.3, 0.5, 0.7, 0.85];
+        let darker = [0.12, 0.25, 0.38, 0.55];
+
+        let mut shades = [base; 10];
+        for (shade, &t) in shades.iter_mut().zip(&lighter) {
+            *shade = mix(white, base, t);
+        }
+        for (shade, &t) in shades[6..].iter_mut().zip(&darker) {
+            *shade = mix(base, black, t);
+        }
+
+        ColorSwatch { shades }
+    }
+
+    /// Returns a shade by name (e.g. `700`), rounding to the nearest shade.
+    pub fn shade(&self, shade: u16) -> Color {
+        let index = Self::SHADES
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &name)| (i32::from(name) - i32::from(shade)).abs())
+            .map(|(index, _)| index)
+            .unwrap_or(5);
+        self.shades[index]
+    }
+
+    /// Returns the 500 shade, i.e. the base color of the swatch.
+    #[inline]
+    pub fn base(&self) -> Color {
+        self.shades[5]
+    }
+
+    /// Returns all the shades, ordered from 50 to 900.
+    #[inline]
+    pub fn shades(&self) -> &[Color; 10] {
+        &self.shades
+    }
+}
+
 /// A consistent palette of colors used throughout the UI.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ColorScheme {
@@ -95,6 +429,12 @@ pub struct ColorScheme {
     pub over_control_outset: Color,
     /// A color which appears clearly over `control_inset`.
     pub over_control_inset: Color,
+    /// Highlight behind selected text or items; typically translucent.
+    pub selection: Color,
+    /// Color of the text caret.
+    pub caret: Color,
+    /// Color of links within text.
+    pub link: Color,
 }
 
 /// A single typeface in 2 weights and italics.
@@ -166,11 +506,47 @@ pub struct Typography {
 
 /// The "contrast" mode of a theme, i.e. light or dark.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThemeContrast {
     Light,
     Dark,
 }
 
+/// Horizontal direction in which content flows, e.g. right-to-left for Arabic or Hebrew user interfaces.
+///
+/// Painters mirror directional visuals (such as disclosure triangles or progress fills) for `RightToLeft`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayoutDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Default for LayoutDirection {
+    #[inline]
+    fn default() -> Self {
+        LayoutDirection::LeftToRight
+    }
+}
+
+impl LayoutDirection {
+    /// Returns `true` if the direction is `RightToLeft`.
+    #[inline]
+    pub fn is_rtl(self) -> bool {
+        self == LayoutDirection::RightToLeft
+    }
+
+    /// Mirrors the horizontal coordinate `x` within `rect` if the direction is `RightToLeft`.
+    #[inline]
+    pub fn mirror_x(self, x: f32, rect: Rect) -> f32 {
+        if self.is_rtl() {
+            rect.min_x() + rect.max_x() - x
+        } else {
+            x
+        }
+    }
+}
+
 /// Various information about a theme, including color scheme and fonts.
 #[derive(Debug, Clone)]
 pub struct ThemeData {
@@ -180,18 +556,58 @@ pub struct ThemeData {
     pub typography: Typography,
     /// Contras mode of the theme.
     pub contrast: ThemeContrast,
+    /// Default layout direction of widgets.
+    pub direction: LayoutDirection,
 }
 
 /// Factory to create colors or `Painter`s which paint widgets with a specific visual theme.
 pub trait Theme {
     /// Constructs a painter for a button.
     fn button(&self) -> Box<dyn Painter<state::ButtonState>>;
+    /// Constructs a painter for a label (the text is laid out by the label itself).
+    fn label(&self) -> Box<dyn Painter<state::LabelState>>;
     /// Constructs a painter for a checkbox.
     fn checkbox(&self) -> Box<dyn Painter<state::CheckboxState>>;
+    /// Constructs a painter for a numeric input.
+    fn numeric_input(&self) -> Box<dyn Painter<state::NumericInputState>>;
     /// Constructs a painter for a text area.
     fn text_area(&self) -> Box<dyn Painter<state::TextAreaState>>;
     /// Constructs a painter for a scroll bar.
     fn scroll_bar(&self) -> Box<dyn Painter<state::ScrollBarState>>;
+    /// Constructs a painter for a tree view.
+    fn tree_view(&self) -> Box<dyn Painter<state::TreeViewState>>;
+    /// Constructs a painter for a list view (the background and row highlights; rows are widgets of their own).
+    fn list_view(&self) -> Box<dyn Painter<state::ListViewState>>;
+    /// Constructs a painter for a tab view (the tab strip; pages are widgets of their own).
+    fn tab(&self) -> Box<dyn Painter<state::TabState>>;
+    /// Constructs a painter for a tour overlay.
+    fn tour(&self) -> Box<dyn Painter<state::TourState>>;
+    /// Constructs a painter for a tooltip.
+    fn tooltip(&self) -> Box<dyn Painter<state::TooltipState>>;
+    /// Constructs a painter for a blur panel (the background; the content is a widget of its own).
+    fn blur_panel(&self) -> Box<dyn Painter<state::BlurPanelState>>;
+    /// Constructs a painter for a card (the slots are widgets of their own).
+    fn card(&self) -> Box<dyn Painter<state::CardState>>;
+    /// Constructs a painter for an error boundary fallback view.
+    fn error_boundary(&self) -> Box<dyn Painter<state::ErrorBoundaryState>>;
+    /// Constructs a painter for a gauge.
+    #[cfg(feature = "extra-widgets")]
+    fn gauge(&self) -> Box<dyn Painter<state::GaugeState>>;
+    /// Constructs a painter for a radial progress indicator.
+    #[cfg(feature = "extra-widgets")]
+    fn radial_progress(&self) -> Box<dyn Painter<state::RadialProgressState>>;
+    /// Constructs a painter for a calendar heatmap.
+    #[cfg(feature = "extra-widgets")]
+    fn calendar_heatmap(&self) -> Box<dyn Painter<state::CalendarHeatmapState>>;
+    /// Constructs a painter for a gradient editor.
+    #[cfg(feature = "extra-widgets")]
+    fn gradient_editor(&self) -> Box<dyn Painter<state::GradientEditorState>>;
+    /// Constructs a painter for a transform gizmo.
+    #[cfg(feature = "extra-widgets")]
+    fn transform_gizmo(&self) -> Box<dyn Painter<state::TransformGizmoState>>;
+    /// Constructs a painter for a ruler.
+    #[cfg(feature = "extra-widgets")]
+    fn ruler(&self) -> Box<dyn Painter<state::RulerState>>;
 
     fn data(&self) -> &ThemeData;
 }
@@ -207,7 +623,16 @@ pub trait HasTheme {
 
 impl<T> Themed for Box<dyn Painter<T>> {
     fn load_theme(&mut self, theme: &dyn Theme, _aux: &dyn base::GraphicalAuxiliary) {
+        let style = self.style().cloned();
         *self = self.invoke(theme);
+        if let Some(style) = style {
+            Painter::set_style(self.as_mut(), style);
+        }
+    }
+
+    #[inline]
+    fn set_style(&mut self, style: StyleOverrides) {
+        Painter::set_style(self.as_mut(), style);
     }
 }
 
@@ -219,4 +644,9 @@ where
         self.theme().load_theme(theme, aux);
         self.resize_from_theme();
     }
+
+    fn set_style(&mut self, style: StyleOverrides) {
+        self.theme().set_style(style);
+        self.resize_from_theme();
+    }
 }
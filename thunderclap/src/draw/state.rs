@@ -3,7 +3,16 @@
 //! These are simply the fields relevant to rendering, existing only
 //! in the scope of the `draw` method.
 
-use crate::{geom::*, ui};
+use {
+    crate::{
+        anim,
+        draw::{Stylable, StyleOverrides},
+        geom::*,
+        ui,
+    },
+    reclutch::display::TextDisplayItem,
+    std::time::Duration,
+};
 
 /// Visually relevant states of a [`Button`](../ui/struct.Button.html).
 #[derive(Debug, Clone)]
@@ -11,6 +20,29 @@ pub struct ButtonState {
     pub rect: AbsoluteRect,
     pub data: ui::Button,
     pub interaction: InteractionState,
+    /// Progress of the hover transition, from 0 (not hovered) to 1 (hovered).
+    pub hover: f32,
+    /// Progress of the press feedback, from 0 (released) to 1 (pressed).
+    pub press: f32,
+    /// Ripple of the last press, if the button has been pressed.
+    pub ripple: Option<Ripple>,
+    /// Corner radius overriding that of the theme, if any (see `StyleOverrides`).
+    pub corner_radius: Option<f32>,
+}
+
+impl Stylable for ButtonState {
+    fn apply_style(&mut self, style: &StyleOverrides) {
+        if let Some(color) = style.color {
+            self.data.color = color;
+        }
+        if let Some(background) = style.background {
+            self.data.background = background;
+        }
+        if let Some(typeface) = &style.typeface {
+            self.data.typeface = typeface.clone();
+        }
+        self.corner_radius = style.corner_radius.or(self.corner_radius);
+    }
 }
 
 bitflags::bitflags! {
@@ -21,12 +53,163 @@ bitflags::bitflags! {
     }
 }
 
+/// Progress of the hover and press transitions of a widget, animated whenever its `InteractionState` changes.
+///
+/// Painters blend between the resting, hovered and pressed colors by the progress (e.g. with `draw::mix`),
+/// so that the colors fade rather than snap. With reduced motion, the progress changes immediately.
+#[derive(Debug)]
+pub struct InteractionTransition {
+    hover: anim::Animated<f32>,
+    press: anim::Animated<f32>,
+}
+
+impl InteractionTransition {
+    /// Creates a transition which takes `duration` to fade between interaction states.
+    pub fn new(duration: Duration, easing: anim::Easing) -> Self {
+        InteractionTransition {
+            hover: anim::Animated::new(0.0, duration, easing),
+            press: anim::Animated::new(0.0, duration, easing),
+        }
+    }
+
+    /// Animates towards `interaction`, returning `true` if the progress has changed (i.e. the widget should repaint).
+    pub fn update(&mut self, interaction: InteractionState, animator: &mut anim::Animator) -> bool {
+        let progress = |flag| if interaction.contains(flag) { 1.0 } else { 0.0 };
+        self.hover.animate_to(progress(InteractionState::HOVERED), animator);
+        self.press.animate_to(progress(InteractionState::PRESSED), animator);
+        // Both must be taken to reset the change flags.
+        let hover_changed = self.hover.take_changed();
+        self.press.take_changed() || hover_changed
+    }
+
+    /// Returns the hover progress, from 0 (not hovered) to 1 (hovered).
+    #[inline]
+    pub fn hover(&self) -> f32 {
+        self.hover.get()
+    }
+
+    /// Returns the press progress, from 0 (released) to 1 (pressed).
+    #[inline]
+    pub fn press(&self) -> f32 {
+        self.press.get()
+    }
+}
+
+/// Ripple spreading from the point where a widget was pressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ripple {
+    /// Point where the widget was pressed.
+    pub origin: AbsolutePoint,
+    /// Progress of the ripple, from 0 (just pressed) to 1 (fully spread).
+    pub progress: f32,
+}
+
+/// Ripple feedback of a widget, animated from the press position whenever the widget is pressed.
+///
+/// Themes which draw press feedback as a ripple (e.g. `themes::Material`) use the ripple of the widget state,
+/// whereas other themes ignore it. With reduced motion, the ripple spreads immediately.
+#[derive(Debug)]
+pub struct RippleTransition {
+    origin: Option<AbsolutePoint>,
+    progress: anim::Animated<f32>,
+    restart: bool,
+}
+
+impl RippleTransition {
+    /// Creates a ripple which takes `duration` to spread.
+    pub fn new(duration: Duration, easing: anim::Easing) -> Self {
+        RippleTransition {
+            origin: None,
+            progress: anim::Animated::new(0.0, duration, easing),
+            restart: false,
+        }
+    }
+
+    /// Starts a new ripple at `origin`, which is animated from the next `update`.
+    pub fn start(&mut self, origin: AbsolutePoint) {
+        self.origin = Some(origin);
+        self.restart = true;
+    }
+
+    /// Animates the ripple, returning `true` if the progress has changed (i.e. the widget should repaint).
+    pub fn update(&mut self, animator: &mut anim::Animator) -> bool {
+        if self.restart {
+            self.restart = false;
+            self.progress.set(0.0, animator);
+            self.progress.animate_to(1.0, animator);
+        }
+        self.progress.take_changed()
+    }
+
+    /// Returns the current ripple, if there has been a press.
+    #[inline]
+    pub fn ripple(&self) -> Option<Ripple> {
+        self.origin.map(|origin| Ripple { origin, progress: self.progress.get() })
+    }
+}
+
+/// Visually relevant states of a [`Label`](../ui/struct.Label.html).
+#[derive(Debug, Clone)]
+pub struct LabelState {
+    pub rect: AbsoluteRect,
+    pub data: ui::Label,
+    /// Text laid out by the label; one run per line, or for rich text, one run per span within a line.
+    pub runs: Vec<TextDisplayItem>,
+    /// Bounds of each line of text.
+    pub lines: Vec<AbsoluteRect>,
+}
+
 /// Visually relevant states of a [`Checkbox`](../ui/struct.Checkbox.html).
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct CheckboxState {
     pub rect: AbsoluteRect,
     pub data: ui::Checkbox,
     pub interaction: InteractionState,
+    /// Progress of the hover transition, from 0 (not hovered) to 1 (hovered).
+    pub hover: f32,
+    /// Progress of the press transition, from 0 (released) to 1 (pressed).
+    pub press: f32,
+    /// Corner radius overriding that of the theme, if any (see `StyleOverrides`).
+    pub corner_radius: Option<f32>,
+}
+
+impl Stylable for CheckboxState {
+    fn apply_style(&mut self, style: &StyleOverrides) {
+        if let Some(color) = style.color {
+            self.data.foreground = color;
+        }
+        if let Some(background) = style.background {
+            self.data.background = background;
+        }
+        self.corner_radius = style.corner_radius.or(self.corner_radius);
+    }
+}
+
+/// Visually relevant states of a [`NumericInput`](../ui/struct.NumericInput.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericInputState {
+    pub rect: AbsoluteRect,
+    pub data: ui::NumericInput,
+    pub interaction: InteractionState,
+    /// Whether the user is dragging to change the value.
+    pub scrubbing: bool,
+    /// Corner radius overriding that of the theme, if any (see `StyleOverrides`).
+    pub corner_radius: Option<f32>,
+}
+
+impl Stylable for NumericInputState {
+    fn apply_style(&mut self, style: &StyleOverrides) {
+        if let Some(color) = style.color {
+            self.data.color = color;
+        }
+        if let Some(background) = style.background {
+            self.data.background = background;
+        }
+        if let Some(typeface) = &style.typeface {
+            self.data.typeface = typeface.clone();
+        }
+        self.corner_radius = style.corner_radius.or(self.corner_radius);
+    }
 }
 
 /// Visually relevant states of a [`TextArea`](../ui/struct.TextArea.html).
@@ -35,6 +218,32 @@ pub struct TextAreaState {
     pub rect: AbsoluteRect,
     pub data: ui::TextArea,
     pub interaction: InteractionState,
+    /// Layout of the visual lines (only in multi-line mode, otherwise empty).
+    pub lines: Vec<TextLine>,
+    /// Index into `lines` of the line the cursor is on.
+    pub cursor_line: usize,
+    /// Vertical scroll offset of the lines.
+    pub scroll: f32,
+}
+
+impl Stylable for TextAreaState {
+    fn apply_style(&mut self, style: &StyleOverrides) {
+        if let Some(color) = style.color {
+            self.data.color = color;
+        }
+        if let Some(typeface) = &style.typeface {
+            self.data.typeface = typeface.clone();
+        }
+    }
+}
+
+/// Layout of a single visual line of a multi-line [`TextArea`](../ui/struct.TextArea.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    /// Byte range of the line within the text, excluding the line break.
+    pub range: std::ops::Range<usize>,
+    /// Rectangle of the line, ignoring scroll.
+    pub rect: AbsoluteRect,
 }
 
 /// Text which can either be display normally or as placeholder.
@@ -49,4 +258,177 @@ pub struct ScrollBarState {
     pub data: ui::ScrollBar,
     pub scroll_bar: AbsoluteRect,
     pub interaction: InteractionState,
+    /// Opacity of the scroll bar, which fades between `ScrollBar::idle_opacity` and 1 on hover.
+    pub opacity: f32,
+}
+
+/// Visually relevant states of a [`TreeView`](../ui/struct.TreeView.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeViewState {
+    pub rect: AbsoluteRect,
+    pub data: ui::TreeView,
+    /// Index of the visible row under the mouse, if any.
+    pub hovered: Option<usize>,
+    pub interaction: InteractionState,
+}
+
+/// Visually relevant states of a [`ListView`](../ui/struct.ListView.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListViewState {
+    pub rect: AbsoluteRect,
+    pub data: ui::ListView,
+    /// Rectangle of the selected row, if any (which may lie outside `rect`).
+    pub selected: Option<AbsoluteRect>,
+    /// Rectangle of the row under the mouse, if any.
+    pub hovered: Option<AbsoluteRect>,
+}
+
+/// Visually relevant states of a [`TabView`](../ui/struct.TabView.html).
+///
+/// The selected (active) tab is given by `data.selected`, and disabled tabs by `data.is_tab_disabled`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabState {
+    pub rect: AbsoluteRect,
+    pub data: ui::TabView,
+    /// Index of the tab under the mouse, if any.
+    pub hovered: Option<usize>,
+    /// Whether the mouse is over the close button of the hovered tab.
+    pub close_hovered: bool,
+}
+
+/// Button within a tour popover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TourButton {
+    Next,
+    Skip,
+}
+
+/// Visually relevant states of a [`Tour`](../ui/struct.Tour.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TourState {
+    pub rect: AbsoluteRect,
+    pub data: ui::Tour,
+    /// Layout of the popover; `None` when passed to `size_hint`, or if the tour isn't active.
+    pub layout: Option<ui::TourLayout>,
+    /// Popover button under the mouse, if any.
+    pub hovered: Option<TourButton>,
+}
+
+/// Visually relevant states of a [`Tooltip`](../ui/struct.Tooltip.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TooltipState {
+    /// Rectangle of the wrapped child.
+    pub rect: AbsoluteRect,
+    pub data: ui::Tooltip,
+    /// Rectangle of the tooltip; `None` when passed to `size_hint`, or if the tooltip isn't shown.
+    pub tooltip: Option<AbsoluteRect>,
+}
+
+/// Visually relevant states of a [`BlurPanel`](../ui/struct.BlurPanel.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurPanelState {
+    pub rect: AbsoluteRect,
+    pub data: ui::BlurPanel,
+    /// Whether the backdrop can be blurred; otherwise the painter falls back to a translucent background.
+    pub backdrop: bool,
+}
+
+/// Visually relevant states of a [`Card`](../ui/struct.Card.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CardState {
+    pub rect: AbsoluteRect,
+    pub data: ui::Card,
+    /// Area of the header (including padding), if the card has a header.
+    pub header: Option<AbsoluteRect>,
+    /// Area of the footer (including padding), if the card has a footer.
+    pub footer: Option<AbsoluteRect>,
+}
+
+impl Stylable for CardState {
+    fn apply_style(&mut self, style: &StyleOverrides) {
+        if let Some(color) = style.color {
+            self.data.border = color;
+        }
+        if let Some(background) = style.background {
+            self.data.background = background;
+        }
+        if let Some(corner_radius) = style.corner_radius {
+            self.data.corner_radius = corner_radius;
+        }
+    }
+}
+
+/// Visually relevant states of an [`ErrorBoundary`](../ui/struct.ErrorBoundary.html) fallback view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorBoundaryState {
+    pub rect: AbsoluteRect,
+    pub data: ui::ErrorBoundary,
+    /// Message of the error which caused the failure.
+    pub error: String,
+    /// Interaction state of the retry button.
+    pub interaction: InteractionState,
+}
+
+/// Visually relevant states of a [`Gauge`](../ui/struct.Gauge.html).
+#[cfg(feature = "extra-widgets")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaugeState {
+    pub rect: AbsoluteRect,
+    pub data: ui::Gauge,
+}
+
+/// Visually relevant states of a [`RadialProgress`](../ui/struct.RadialProgress.html).
+#[cfg(feature = "extra-widgets")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadialProgressState {
+    pub rect: AbsoluteRect,
+    pub data: ui::RadialProgress,
+    /// Rotation of an indeterminate indicator, from 0.0 to 1.0.
+    pub phase: f32,
+}
+
+/// Visually relevant states of a [`CalendarHeatmap`](../ui/struct.CalendarHeatmap.html).
+#[cfg(feature = "extra-widgets")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarHeatmapState {
+    pub rect: AbsoluteRect,
+    pub data: ui::CalendarHeatmap,
+    /// Index of the day cell under the mouse, if any.
+    pub hovered: Option<usize>,
+}
+
+/// Visually relevant states of a [`GradientEditor`](../ui/struct.GradientEditor.html).
+#[cfg(feature = "extra-widgets")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientEditorState {
+    pub rect: AbsoluteRect,
+    pub data: ui::GradientEditor,
+    /// Index of the selected stop, if any.
+    pub selected: Option<usize>,
+    /// Whether the selected stop is dragged far enough away to be removed once released.
+    pub removing: bool,
+}
+
+/// Visually relevant states of a [`TransformGizmo`](../ui/struct.TransformGizmo.html).
+#[cfg(feature = "extra-widgets")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformGizmoState {
+    pub rect: AbsoluteRect,
+    pub data: ui::TransformGizmo,
+    /// Handle under the mouse, if any.
+    pub hovered: Option<ui::GizmoHandle>,
+    /// Handle being dragged, if any.
+    pub active: Option<ui::GizmoHandle>,
+}
+
+/// Visually relevant states of a [`Ruler`](../ui/struct.Ruler.html).
+#[cfg(feature = "extra-widgets")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RulerState {
+    pub rect: AbsoluteRect,
+    pub data: ui::Ruler,
+    /// Canvas coordinate of the cursor along the ruler, if it's alongside the ruler.
+    pub cursor: Option<f32>,
+    /// Index of the guide being dragged, if any.
+    pub dragged: Option<usize>,
 }
@@ -0,0 +1,143 @@
+//! Cache of text measurements, shared by the widgets of a thread.
+//!
+//! Measuring text shapes it, which is costly enough to dominate the layout of text-heavy widgets (e.g. wrapping
+//! a label re-measures every candidate line). Measurements are therefore cached by font, size and text, within
+//! the bounds configured by `profiling::configure_text_cache`; the hit rate and memory use of the cache can be
+//! inspected with `profiling::text_cache_stats`.
+
+use {
+    crate::profiling::{EvictionPolicy, TextCacheConfig, TextCacheStats},
+    indexmap::IndexMap,
+    reclutch::display::{Color, FontInfo, ResourceReference, TextDisplayItem},
+    std::cell::RefCell,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MeasureKey {
+    font: ResourceReference,
+    size: u32,
+    text: String,
+}
+
+/// Bounded map of text measurements, ordered by eviction priority (the front is evicted first).
+#[derive(Debug)]
+pub(crate) struct TextCache {
+    config: TextCacheConfig,
+    entries: IndexMap<MeasureKey, f32>,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl TextCache {
+    fn new() -> Self {
+        TextCache {
+            config: Default::default(),
+            entries: IndexMap::new(),
+            bytes: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    fn get(&mut self, key: &MeasureKey) -> Option<f32> {
+        let width = match self.config.policy {
+            // Re-inserting moves the entry to the back, keeping the cache ordered by recent use.
+            EvictionPolicy::LeastRecentlyUsed => {
+                let (key, width) = self.entries.shift_remove_entry(key)?;
+                self.entries.insert(key, width);
+                width
+            }
+            EvictionPolicy::FirstInFirstOut => *self.entries.get(key)?,
+        };
+        self.hits += 1;
+        Some(width)
+    }
+
+    fn insert(&mut self, key: MeasureKey, width: f32) {
+        if self.config.max_entries == 0 || key.text.len() > self.config.max_bytes {
+            return;
+        }
+
+        self.bytes += key.text.len();
+        self.entries.insert(key, width);
+        self.trim();
+    }
+
+    /// Evicts entries until the cache is within its configured bounds.
+    fn trim(&mut self) {
+        while self.entries.len() > self.config.max_entries || self.bytes > self.config.max_bytes {
+            match self.entries.shift_remove_index(0) {
+                Some((key, _)) => {
+                    self.bytes -= key.text.len();
+                    self.evictions += 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    pub(crate) fn configure(&mut self, config: TextCacheConfig) {
+        self.config = config;
+        self.trim();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    pub(crate) fn stats(&self) -> TextCacheStats {
+        TextCacheStats {
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+
+    pub(crate) fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+        self.evictions = 0;
+    }
+}
+
+thread_local! {
+    pub(crate) static TEXT_CACHE: RefCell<TextCache> = RefCell::new(TextCache::new());
+}
+
+/// Returns the width of `text` rendered in `font` at `size` (in pixels), measuring it only if it isn't cached.
+pub fn measure_width(font: &(ResourceReference, FontInfo), size: f32, text: &str) -> f32 {
+    if text.is_empty() {
+        return 0.0;
+    }
+
+    let key = MeasureKey { font: font.0, size: size.to_bits(), text: text.to_string() };
+    if let Some(width) = TEXT_CACHE.with(|cache| cache.borrow_mut().get(&key)) {
+        return width;
+    }
+
+    let width = TextDisplayItem {
+        text: text.to_string().into(),
+        font: font.0,
+        font_info: font.1.clone(),
+        size,
+        bottom_left: Default::default(),
+        color: Color::new(0.0, 0.0, 0.0, 1.0).into(),
+    }
+    .bounds()
+    .map(|bounds| bounds.size.width)
+    .unwrap_or(0.0);
+
+    TEXT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.misses += 1;
+        cache.insert(key, width);
+    });
+
+    width
+}
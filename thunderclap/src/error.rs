@@ -8,6 +8,8 @@ pub enum AppError {
     SkiaError(#[from] error::SkiaError),
     #[error("{0}")]
     ResourceError(#[from] error::ResourceError),
+    #[error("windowing backend error: {0}")]
+    BackendError(String),
 }
 
 #[cfg(feature = "default-themes")]
@@ -17,4 +19,104 @@ pub enum ThemeError {
     ResourceError(#[from] error::ResourceError),
     #[error("{0}")]
     FontError(#[from] error::FontError),
+    #[cfg(feature = "dynamic-themes")]
+    #[error("{0}")]
+    ParseError(#[from] ron::de::Error),
+    #[cfg(feature = "dynamic-themes")]
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[cfg(feature = "core-widgets")]
+#[derive(Error, Debug)]
+pub enum FactoryError {
+    #[error("unknown widget type \"{0}\"")]
+    UnknownWidget(String),
+    #[error("invalid value for property \"{property}\" of {widget}")]
+    InvalidProperty { widget: String, property: String },
+    #[error("{0} cannot have children")]
+    UnexpectedChildren(String),
+}
+
+#[cfg(feature = "declarative")]
+#[derive(Error, Debug)]
+pub enum DeclarativeError {
+    #[error("{0}")]
+    ParseError(#[from] ron::de::Error),
+    #[error("{0}")]
+    FactoryError(#[from] FactoryError),
+}
+
+#[cfg(feature = "global-hotkeys")]
+#[derive(Error, Debug)]
+pub enum HotkeyError {
+    #[error("the hotkey is already registered")]
+    AlreadyRegistered,
+    #[error("global hotkeys aren't supported on this platform")]
+    Unsupported,
+    #[error("failed to register the hotkey: {0}")]
+    PlatformError(String),
+}
+
+#[cfg(feature = "tray")]
+#[derive(Error, Debug)]
+pub enum TrayError {
+    #[error("the desktop doesn't show tray icons")]
+    Unsupported,
+    #[error("failed to show the tray icon: {0}")]
+    PlatformError(String),
+}
+
+#[cfg(feature = "notifications")]
+#[derive(Error, Debug)]
+pub enum NotificationError {
+    #[error("notifications aren't supported on this platform")]
+    Unsupported,
+    #[error("the user hasn't permitted notifications")]
+    PermissionDenied,
+    #[error("failed to post the notification: {0}")]
+    PlatformError(String),
+}
+
+#[cfg(feature = "scripting")]
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("unknown script handler \"{0}\"")]
+    UnknownHandler(String),
+    #[error("script handler \"{handler}\" failed: {message}")]
+    HandlerError { handler: String, message: String },
+}
+
+#[cfg(feature = "extra-widgets")]
+#[derive(Error, Debug)]
+pub enum DashboardLayoutError {
+    #[error("invalid dashboard card \"{0}\" (expected \"key:column,row,widthxheight\")")]
+    InvalidCard(String),
+    #[error("the dashboard card \"{0}\" appears more than once")]
+    DuplicateKey(String),
+}
+
+#[cfg(feature = "extra-widgets")]
+#[derive(Error, Debug)]
+pub enum PrintError {
+    #[error("the document hasn't been paginated yet")]
+    NotPaginated,
+    #[error("failed to export the page: {0}")]
+    ExportError(Box<dyn std::error::Error>),
+}
+
+#[cfg(feature = "forms")]
+#[derive(Error, Debug)]
+pub enum FormError {
+    #[error("failed to reflect the form value: {0}")]
+    ReflectError(String),
+    #[error("the form has no field named \"{0}\"")]
+    UnknownField(String),
+}
+
+#[cfg(feature = "validation-regex")]
+#[derive(Error, Debug)]
+pub enum ValidationError {
+    #[error("invalid validation pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
 }
@@ -0,0 +1,796 @@
+//! Registry of widget constructors by name, so that widgets can be discovered and created at runtime.
+//!
+//! A `WidgetFactory` is shared by everything which creates widgets from a type name rather than a Rust type,
+//! such as the [declarative](../declarative/index.html) loader, a command palette listing insertable widgets
+//! or an inspector editing a widget tree.
+//!
+//! Widget types are created from a map of `Properties` (and the children, for layouts) and wrapped in a `DynamicWidget`,
+//! which can be downcast back to the concrete widget type.
+//!
+//! # Registering widgets from other crates
+//! Crates providing widgets can expose a registration function, which applications call on their factory:
+//! ```ignore
+//! pub fn register<U: UpdateAuxiliary, G: GraphicalAuxiliary>(factory: &mut WidgetFactory<U, G>) {
+//!     factory.register_widget("ColorWheel", |properties, theme, u_aux| {
+//!         let mut data = <ColorWheel as WidgetConstructor<U, G>>::from_theme(theme);
+//!         properties.assign("hue", &mut data.hue, Value::as_f32)?;
+//!         Ok(data.construct(theme, u_aux))
+//!     });
+//! }
+//!
+//! let mut factory = WidgetFactory::with_core_widgets();
+//! color_wheel::register(&mut factory);
+//!
+//! for info in factory.widgets() {
+//!     println!("{} (layout: {})", info.name(), info.is_layout());
+//! }
+//! ```
+
+use {
+    crate::{
+        base::{self, Layout, Rectangular, Resizable, WidgetChildren},
+        draw,
+        error::FactoryError,
+        geom::*,
+        ui,
+    },
+    indexmap::IndexMap,
+    reclutch::{
+        display::{Color, DisplayCommand},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{any::Any, collections::BTreeMap},
+};
+
+#[cfg(feature = "declarative")]
+use serde::{Deserialize, Serialize};
+
+/// Value of a property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "declarative", derive(Serialize, Deserialize), serde(untagged))]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Returns the value as a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a number.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Number(value) => Some(*value as _),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a list of 3 or 4 numbers (from 0.0 to 1.0) as an RGB(A) color.
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            Value::List(list) if list.len() == 3 || list.len() == 4 => {
+                let channels: Option<Vec<_>> = list.iter().map(Value::as_f32).collect();
+                let channels = channels?;
+                Some(Color::new(
+                    channels[0],
+                    channels[1],
+                    channels[2],
+                    channels.get(3).copied().unwrap_or(1.0),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a string naming a variant of `Align` (e.g. `"Middle"`) as an `Align`.
+    pub fn as_align(&self) -> Option<ui::Align> {
+        match self.as_str()? {
+            "Begin" => Some(ui::Align::Begin),
+            "Middle" => Some(ui::Align::Middle),
+            "End" => Some(ui::Align::End),
+            "Stretch" => Some(ui::Align::Stretch),
+            _ => None,
+        }
+    }
+}
+
+/// Properties of a widget, by name.
+pub type Properties = BTreeMap<String, Value>;
+
+/// Properties of a specific widget, which can be assigned to the widget data.
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyMap<'a> {
+    widget: &'a str,
+    properties: &'a Properties,
+}
+
+impl<'a> PropertyMap<'a> {
+    /// Creates a property map for properties of a widget of type `widget`.
+    pub fn new(widget: &'a str, properties: &'a Properties) -> Self {
+        PropertyMap { widget, properties }
+    }
+
+    /// Returns the value of a property, if it's given.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&'a Value> {
+        self.properties.get(name)
+    }
+
+    /// Returns `true` if no properties are given.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Assigns a property to `target` if it's given, converting the value with `convert`.
+    ///
+    /// Returns `FactoryError::InvalidProperty` if the value can't be converted.
+    pub fn assign<T>(
+        &self,
+        name: &str,
+        target: &mut T,
+        convert: impl FnOnce(&Value) -> Option<T>,
+    ) -> Result<(), FactoryError> {
+        if let Some(value) = self.properties.get(name) {
+            *target = convert(value).ok_or_else(|| FactoryError::InvalidProperty {
+                widget: self.widget.to_string(),
+                property: name.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Object-safe interface of widgets created by a factory, allowing them to be downcast to their concrete type.
+pub trait DynWidget<U, G>:
+    WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand> + Rectangular
+{
+    fn as_children(
+        &self,
+    ) -> &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>;
+    fn as_children_mut(
+        &mut self,
+    ) -> &mut dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<U, G, W> DynWidget<U, G> for W
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+{
+    #[inline]
+    fn as_children(
+        &self,
+    ) -> &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand> {
+        self
+    }
+
+    #[inline]
+    fn as_children_mut(
+        &mut self,
+    ) -> &mut dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+    {
+        self
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Widget created by a `WidgetFactory`, which wraps the constructed widget (see `inner`) alongside its children."]
+    #[doc = "The wrapper takes on the rectangle of the inner widget, and can be pushed into layouts."]
+    pub struct DynamicWidget {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        {
+            id: Option<String>,
+            type_name: String,
+            layout_data: Properties,
+            inner: Box<dyn DynWidget<U, G>>,
+            nodes: Vec<DynamicWidget<U, G>>,
+        },
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for DynamicWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        let size = self.rect.size;
+        if self.inner.rect() != RelativeRect::new(Default::default(), size) {
+            self.inner.set_rect(RelativeRect::new(Default::default(), size));
+        }
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+impl<U, G> DynamicWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn new(
+        type_name: &str,
+        mut inner: Box<dyn DynWidget<U, G>>,
+        nodes: Vec<DynamicWidget<U, G>>,
+    ) -> Self {
+        let rect = inner.rect();
+        inner.set_rect(RelativeRect::new(Default::default(), rect.size));
+
+        let mut widget = DynamicWidgetBuilder {
+            rect,
+            graph: vg::VerbGraph::default().into(),
+
+            id: None,
+            type_name: type_name.to_string(),
+            layout_data: Properties::new(),
+            inner,
+            nodes,
+        }
+        .build();
+
+        let rect = widget.abs_rect();
+        widget.set_ctxt_rect(rect);
+        widget
+    }
+
+    /// Returns the ID used to find the widget, if any.
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|id| &id[..])
+    }
+
+    /// Changes the ID used to find the widget.
+    #[inline]
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.id = id;
+    }
+
+    /// Returns the name of the widget type.
+    #[inline]
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the layout data used when the widget is pushed into a parent layout.
+    #[inline]
+    pub fn layout_data(&self) -> PropertyMap {
+        PropertyMap::new(&self.type_name, &self.layout_data)
+    }
+
+    /// Changes the layout data used when the widget is pushed into a parent layout.
+    /// This has no effect once the widget has been pushed.
+    #[inline]
+    pub fn set_layout_data(&mut self, layout_data: Properties) {
+        self.layout_data = layout_data;
+    }
+
+    /// Returns the widget constructed by the factory.
+    #[inline]
+    pub fn inner(&self) -> &dyn DynWidget<U, G> {
+        &*self.inner
+    }
+
+    /// Returns the widget constructed by the factory mutably.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut dyn DynWidget<U, G> {
+        &mut *self.inner
+    }
+
+    /// Returns the children.
+    #[inline]
+    pub fn nodes(&self) -> &[DynamicWidget<U, G>] {
+        &self.nodes
+    }
+
+    /// Returns the children mutably.
+    #[inline]
+    pub fn nodes_mut(&mut self) -> &mut [DynamicWidget<U, G>] {
+        &mut self.nodes
+    }
+
+    /// Returns this widget or the first descendant with a given ID.
+    pub fn find(&self, id: &str) -> Option<&DynamicWidget<U, G>> {
+        if self.id() == Some(id) {
+            Some(self)
+        } else {
+            self.nodes.iter().filter_map(|node| node.find(id)).next()
+        }
+    }
+
+    /// Returns this widget or the first descendant with a given ID, mutably.
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut DynamicWidget<U, G>> {
+        if self.id() == Some(id) {
+            Some(self)
+        } else {
+            self.nodes.iter_mut().filter_map(|node| node.find_mut(id)).next()
+        }
+    }
+
+    /// Returns the inner widget with a given ID, if it's of type `W`.
+    pub fn get<W: 'static>(&self, id: &str) -> Option<&W> {
+        self.find(id)?.inner.as_any().downcast_ref()
+    }
+
+    /// Returns the inner widget with a given ID mutably, if it's of type `W`.
+    pub fn get_mut<W: 'static>(&mut self, id: &str) -> Option<&mut W> {
+        self.find_mut(id)?.inner.as_any_mut().downcast_mut()
+    }
+}
+
+impl<U, G> Widget for DynamicWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+        }
+
+        base::invoke_update(self, aux);
+
+        // the inner widget may have resized itself (e.g. a stack fitting its children)
+        let size = self.inner.size();
+        if size != self.size() {
+            self.set_size(size);
+        }
+    }
+}
+
+impl<U, G> WidgetChildren for DynamicWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        std::iter::once(self.inner.as_children())
+            .chain(self.nodes.iter().map(|node| node as _))
+            .collect()
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        std::iter::once(self.inner.as_children_mut())
+            .chain(self.nodes.iter_mut().map(|node| node as _))
+            .collect()
+    }
+
+    #[inline]
+    fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|id| &id[..])
+    }
+}
+
+type Constructor<U, G> = Box<
+    dyn Fn(
+        PropertyMap,
+        &mut [DynamicWidget<U, G>],
+        &dyn draw::Theme,
+        &mut U,
+    ) -> Result<Box<dyn DynWidget<U, G>>, FactoryError>,
+>;
+
+/// Information about a registered widget type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WidgetInfo {
+    name: String,
+    layout: bool,
+}
+
+impl WidgetInfo {
+    /// Returns the name the widget type is registered under.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the widget type accepts children.
+    #[inline]
+    pub fn is_layout(&self) -> bool {
+        self.layout
+    }
+}
+
+struct Entry<U, G> {
+    info: WidgetInfo,
+    constructor: Constructor<U, G>,
+}
+
+/// Constructors of widget types by name.
+pub struct WidgetFactory<U, G> {
+    entries: IndexMap<String, Entry<U, G>>,
+}
+
+impl<U, G> Default for WidgetFactory<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn default() -> Self {
+        WidgetFactory { entries: IndexMap::new() }
+    }
+}
+
+impl<U, G> WidgetFactory<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Creates an empty factory.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a factory with the core widgets registered by their type name (e.g. `"Button"`, `"VStack"`).
+    pub fn with_core_widgets() -> Self {
+        let mut factory = WidgetFactory::new();
+        register_core_widgets(&mut factory);
+        factory
+    }
+
+    fn insert(&mut self, name: String, layout: bool, constructor: Constructor<U, G>) {
+        self.entries.insert(name.clone(), Entry { info: WidgetInfo { name, layout }, constructor });
+    }
+
+    /// Registers a widget type which doesn't have children.
+    /// Registering a name twice replaces the previous constructor.
+    pub fn register_widget<W, F>(&mut self, name: impl Into<String>, constructor: F)
+    where
+        W: DynWidget<U, G>,
+        F: Fn(PropertyMap, &dyn draw::Theme, &mut U) -> Result<W, FactoryError> + 'static,
+    {
+        let name = name.into();
+        let type_name = name.clone();
+        self.insert(
+            name,
+            false,
+            Box::new(
+                move |properties: PropertyMap,
+                      children: &mut [DynamicWidget<U, G>],
+                      theme: &dyn draw::Theme,
+                      u_aux: &mut U| {
+                    if !children.is_empty() {
+                        return Err(FactoryError::UnexpectedChildren(type_name.clone()));
+                    }
+                    Ok(Box::new(constructor(properties, theme, u_aux)?) as Box<dyn DynWidget<U, G>>)
+                },
+            ),
+        );
+    }
+
+    /// Registers a layout widget type, which is given the children to push into itself.
+    /// Registering a name twice replaces the previous constructor.
+    pub fn register_layout<W, F>(&mut self, name: impl Into<String>, constructor: F)
+    where
+        W: DynWidget<U, G>,
+        F: Fn(
+                PropertyMap,
+                &mut [DynamicWidget<U, G>],
+                &dyn draw::Theme,
+                &mut U,
+            ) -> Result<W, FactoryError>
+            + 'static,
+    {
+        self.insert(
+            name.into(),
+            true,
+            Box::new(
+                move |properties: PropertyMap,
+                      children: &mut [DynamicWidget<U, G>],
+                      theme: &dyn draw::Theme,
+                      u_aux: &mut U| {
+                    Ok(Box::new(constructor(properties, children, theme, u_aux)?)
+                        as Box<dyn DynWidget<U, G>>)
+                },
+            ),
+        );
+    }
+
+    /// Returns `true` if a widget type has been registered under `name`.
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Returns information about the widget type registered under `name`.
+    #[inline]
+    pub fn info(&self, name: &str) -> Option<&WidgetInfo> {
+        self.entries.get(name).map(|entry| &entry.info)
+    }
+
+    /// Returns an iterator over the registered widget types, in the order they were registered.
+    pub fn widgets(&self) -> impl Iterator<Item = &WidgetInfo> {
+        self.entries.values().map(|entry| &entry.info)
+    }
+
+    /// Creates a widget of the type registered under `name`.
+    ///
+    /// `children` is only accepted by layouts, which push them into themselves
+    /// (using the layout data of each child, see `DynamicWidget::set_layout_data`).
+    pub fn create(
+        &self,
+        name: &str,
+        properties: &Properties,
+        mut children: Vec<DynamicWidget<U, G>>,
+        theme: &dyn draw::Theme,
+        u_aux: &mut U,
+    ) -> Result<DynamicWidget<U, G>, FactoryError> {
+        let entry =
+            self.entries.get(name).ok_or_else(|| FactoryError::UnknownWidget(name.to_string()))?;
+
+        let inner =
+            (entry.constructor)(PropertyMap::new(name, properties), &mut children, theme, u_aux)?;
+
+        Ok(DynamicWidget::new(name, inner, children))
+    }
+}
+
+fn to_string(value: &Value) -> Option<String> {
+    value.as_str().map(ToString::to_string)
+}
+
+fn to_usize(value: &Value) -> Option<usize> {
+    value.as_f32().filter(|value| *value >= 0.0).map(|value| value as _)
+}
+
+fn to_text_align(value: &Value) -> Option<ui::TextAlign> {
+    match value.as_str()? {
+        "Left" => Some(ui::TextAlign::Left),
+        "Middle" => Some(ui::TextAlign::Middle),
+        "Right" => Some(ui::TextAlign::Right),
+        _ => None,
+    }
+}
+
+fn to_overflow_behavior(value: &Value) -> Option<ui::OverflowBehavior> {
+    match value.as_str()? {
+        "Wrap" => Some(ui::OverflowBehavior::Wrap),
+        "Clip" => Some(ui::OverflowBehavior::Clip),
+        "Ellipsis" => Some(ui::OverflowBehavior::Ellipsis(ui::EllipsisPosition::End)),
+        "MiddleEllipsis" => Some(ui::OverflowBehavior::Ellipsis(ui::EllipsisPosition::Middle)),
+        "FadeOut" => Some(ui::OverflowBehavior::FadeOut),
+        _ => None,
+    }
+}
+
+fn to_flex_direction(value: &Value) -> Option<ui::FlexDirection> {
+    match value.as_str()? {
+        "Row" => Some(ui::FlexDirection::Row),
+        "RowReverse" => Some(ui::FlexDirection::RowReverse),
+        "Column" => Some(ui::FlexDirection::Column),
+        "ColumnReverse" => Some(ui::FlexDirection::ColumnReverse),
+        _ => None,
+    }
+}
+
+fn to_flex_wrap(value: &Value) -> Option<ui::FlexWrap> {
+    match value.as_str()? {
+        "NoWrap" => Some(ui::FlexWrap::NoWrap),
+        "Wrap" => Some(ui::FlexWrap::Wrap),
+        "WrapReverse" => Some(ui::FlexWrap::WrapReverse),
+        _ => None,
+    }
+}
+
+fn to_justify_content(value: &Value) -> Option<ui::JustifyContent> {
+    match value.as_str()? {
+        "Start" => Some(ui::JustifyContent::Start),
+        "End" => Some(ui::JustifyContent::End),
+        "Center" => Some(ui::JustifyContent::Center),
+        "SpaceBetween" => Some(ui::JustifyContent::SpaceBetween),
+        "SpaceAround" => Some(ui::JustifyContent::SpaceAround),
+        "SpaceEvenly" => Some(ui::JustifyContent::SpaceEvenly),
+        _ => None,
+    }
+}
+
+/// Pushes children into a layout, converting the layout data of each child with `item`
+/// (children without layout data are pushed with `None`).
+pub fn push_children<U, G, L>(
+    layout: &mut L,
+    children: &mut [DynamicWidget<U, G>],
+    item: impl Fn(PropertyMap) -> Result<L::PushData, FactoryError>,
+) -> Result<(), FactoryError>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    L: Layout,
+{
+    for child in children {
+        let data = child.layout_data();
+        let data = if data.is_empty() { None } else { Some(item(data)?) };
+        layout.push(data, child);
+    }
+    Ok(())
+}
+
+fn register_core_widgets<U, G>(factory: &mut WidgetFactory<U, G>)
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    factory.register_widget("Button", |properties, theme, u_aux| {
+        let mut data = <ui::Button as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("text", &mut data.text, |v| to_string(v).map(Into::into))?;
+        properties.assign("color", &mut data.color, Value::as_color)?;
+        properties.assign("background", &mut data.background, Value::as_color)?;
+        properties.assign("focus", &mut data.focus, Value::as_color)?;
+        properties.assign("disabled", &mut data.disabled, Value::as_bool)?;
+        Ok(data.construct(theme, u_aux))
+    });
+
+    factory.register_widget("Label", |properties, theme, u_aux| {
+        let mut data = <ui::Label as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("text", &mut data.text, |v| to_string(v).map(Into::into))?;
+        properties.assign("color", &mut data.color, Value::as_color)?;
+        properties.assign("align", &mut data.align, to_text_align)?;
+        properties.assign("overflow", &mut data.overflow, to_overflow_behavior)?;
+        Ok(data.construct(theme, u_aux))
+    });
+
+    factory.register_widget("Checkbox", |properties, theme, u_aux| {
+        let mut data = <ui::Checkbox as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("foreground", &mut data.foreground, Value::as_color)?;
+        properties.assign("background", &mut data.background, Value::as_color)?;
+        properties.assign("focus", &mut data.focus, Value::as_color)?;
+        properties.assign("checked", &mut data.checked, Value::as_bool)?;
+        properties.assign("disabled", &mut data.disabled, Value::as_bool)?;
+        Ok(data.construct(theme, u_aux))
+    });
+
+    factory.register_widget("TextArea", |properties, theme, u_aux| {
+        let mut data = <ui::TextArea as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("text", &mut data.text, to_string)?;
+        properties.assign("placeholder", &mut data.placeholder, to_string)?;
+        properties.assign("color", &mut data.color, Value::as_color)?;
+        properties.assign("placeholder_color", &mut data.placeholder_color, Value::as_color)?;
+        properties.assign("cursor_color", &mut data.cursor_color, Value::as_color)?;
+        properties.assign("disabled", &mut data.disabled, Value::as_bool)?;
+        properties.assign("multiline", &mut data.multiline, Value::as_bool)?;
+        data.cursor = data.text.len();
+        Ok(data.construct(theme, u_aux))
+    });
+
+    factory.register_widget("NumericInput", |properties, theme, u_aux| {
+        let mut data = <ui::NumericInput as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("value", &mut data.value, Value::as_f32)?;
+        properties.assign("min", &mut data.min, Value::as_f32)?;
+        properties.assign("max", &mut data.max, Value::as_f32)?;
+        properties.assign("step", &mut data.step, Value::as_f32)?;
+        properties.assign("scrub_speed", &mut data.scrub_speed, Value::as_f32)?;
+        properties.assign("precision", &mut data.precision, to_usize)?;
+        properties.assign("color", &mut data.color, Value::as_color)?;
+        properties.assign("background", &mut data.background, Value::as_color)?;
+        properties.assign("focus", &mut data.focus, Value::as_color)?;
+        properties.assign("disabled", &mut data.disabled, Value::as_bool)?;
+        Ok(data.construct(theme, u_aux))
+    });
+
+    factory.register_layout("VStack", |properties, children, theme, u_aux| {
+        let mut data = <ui::VStack as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("top_margin", &mut data.top_margin, Value::as_f32)?;
+        properties.assign("bottom_margin", &mut data.bottom_margin, Value::as_f32)?;
+        properties.assign("alignment", &mut data.alignment, Value::as_align)?;
+        properties.assign("fill_window", &mut data.fill_window, Value::as_bool)?;
+
+        let mut layout = data.construct(theme, u_aux);
+        push_children(&mut layout, children, |layout_data| {
+            let mut item = ui::VStackItem {
+                top_margin: data.top_margin,
+                bottom_margin: data.bottom_margin,
+                alignment: data.alignment,
+            };
+            layout_data.assign("top_margin", &mut item.top_margin, Value::as_f32)?;
+            layout_data.assign("bottom_margin", &mut item.bottom_margin, Value::as_f32)?;
+            layout_data.assign("alignment", &mut item.alignment, Value::as_align)?;
+            Ok(item)
+        })?;
+        Ok(layout)
+    });
+
+    factory.register_layout("HStack", |properties, children, theme, u_aux| {
+        let mut data = <ui::HStack as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("left_margin", &mut data.left_margin, Value::as_f32)?;
+        properties.assign("right_margin", &mut data.right_margin, Value::as_f32)?;
+        properties.assign("alignment", &mut data.alignment, Value::as_align)?;
+        properties.assign("fill_window", &mut data.fill_window, Value::as_bool)?;
+
+        let mut layout = data.construct(theme, u_aux);
+        push_children(&mut layout, children, |layout_data| {
+            let mut item = ui::HStackItem {
+                left_margin: data.left_margin,
+                right_margin: data.right_margin,
+                alignment: data.alignment,
+            };
+            layout_data.assign("left_margin", &mut item.left_margin, Value::as_f32)?;
+            layout_data.assign("right_margin", &mut item.right_margin, Value::as_f32)?;
+            layout_data.assign("alignment", &mut item.alignment, Value::as_align)?;
+            Ok(item)
+        })?;
+        Ok(layout)
+    });
+
+    factory.register_layout("Flex", |properties, children, theme, u_aux| {
+        let mut data = <ui::Flex as ui::WidgetConstructor<U, G>>::from_theme(theme);
+        properties.assign("direction", &mut data.direction, to_flex_direction)?;
+        properties.assign("wrap", &mut data.wrap, to_flex_wrap)?;
+        properties.assign("justify_content", &mut data.justify_content, to_justify_content)?;
+        properties.assign("align_items", &mut data.align_items, Value::as_align)?;
+        properties.assign("gap", &mut data.gap, Value::as_f32)?;
+        properties.assign("grow", &mut data.grow, Value::as_f32)?;
+        properties.assign("shrink", &mut data.shrink, Value::as_f32)?;
+        properties.assign("fill_window", &mut data.fill_window, Value::as_bool)?;
+
+        let mut layout = data.construct(theme, u_aux);
+        push_children(&mut layout, children, |layout_data| {
+            let mut item =
+                ui::FlexItem { grow: data.grow, shrink: data.shrink, ..Default::default() };
+            layout_data.assign("grow", &mut item.grow, Value::as_f32)?;
+            layout_data.assign("shrink", &mut item.shrink, Value::as_f32)?;
+            layout_data.assign("basis", &mut item.basis, |v| v.as_f32().map(Some))?;
+            layout_data.assign("align_self", &mut item.align_self, |v| v.as_align().map(Some))?;
+            Ok(item)
+        })?;
+        Ok(layout)
+    });
+}
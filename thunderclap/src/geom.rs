@@ -1,6 +1,19 @@
 //! Widget positioning module.
+//!
+//! Layouts and painters may compute degenerate rectangles (e.g. a margin larger than the available space,
+//! or a window minimized to zero size). Sizes are clamped to be non-negative by `Resizable` implementations
+//! (see `clamp_size`), widgets with empty bounds aren't drawn, and painters shrink rectangles with `deflate`
+//! rather than `inflate` with negative amounts. With the `debug-geometry` feature, `check_rect` panics on
+//! negative or non-finite rectangles, to track down where they originate.
+//!
+//! The `debug-geometry` feature also validates the arrangements of the stack layouts (see `check_arrangement`):
+//! children which unintentionally overlap or don't fit within the layout are logged, and outlined by the debug overlay
+//! (see `layout_issue_overlay`) which the application draws above everything else.
 
-use crate::base;
+use {
+    crate::base,
+    reclutch::euclid::{Point2D, Rect, Size2D},
+};
 
 /// Unit of absolute widget space.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -154,3 +167,191 @@ fn update_parent_positions<U, G, D>(
         update_parent_positions(child);
     }
 }
+
+/// Replaces non-finite (i.e. NaN or infinite) dimensions with zero and clamps negative dimensions to zero.
+#[inline]
+pub fn clamp_size<T>(size: Size2D<f32, T>) -> Size2D<f32, T> {
+    let clamp = |x: f32| if x.is_finite() { x.max(0.0) } else { 0.0 };
+    Size2D::new(clamp(size.width), clamp(size.height))
+}
+
+/// Replaces non-finite coordinates with zero and clamps the size (see `clamp_size`).
+#[inline]
+pub fn clamp_rect<T>(rect: Rect<f32, T>) -> Rect<f32, T> {
+    let finite = |x: f32| if x.is_finite() { x } else { 0.0 };
+    Rect::new(Point2D::new(finite(rect.origin.x), finite(rect.origin.y)), clamp_size(rect.size))
+}
+
+/// Returns `true` if a rectangle has no area (including negative or non-finite sizes), in which case nothing should be drawn in it.
+#[inline]
+pub fn is_degenerate<T>(rect: &Rect<f32, T>) -> bool {
+    // Written so that NaN sizes are degenerate too.
+    !(rect.size.width > 0.0 && rect.size.height > 0.0)
+        || !rect.origin.x.is_finite()
+        || !rect.origin.y.is_finite()
+}
+
+/// Shrinks a rectangle by `width` horizontally and `height` vertically on each side, keeping its center.
+///
+/// Unlike `inflate` with negative amounts, the size never becomes negative.
+#[inline]
+pub fn deflate<T>(rect: Rect<f32, T>, width: f32, height: f32) -> Rect<f32, T> {
+    let width = width.min(rect.size.width / 2.0).max(0.0);
+    let height = height.min(rect.size.height / 2.0).max(0.0);
+    rect.inflate(-width, -height)
+}
+
+/// With the `debug-geometry` feature, panics if a rectangle has a negative or non-finite component;
+/// `context` describes where the rectangle comes from (e.g. the kind of widget). Otherwise does nothing.
+#[inline]
+#[allow(unused_variables)]
+pub fn check_rect<T>(rect: &Rect<f32, T>, context: &str) {
+    #[cfg(feature = "debug-geometry")]
+    {
+        assert!(
+            rect.origin.x.is_finite()
+                && rect.origin.y.is_finite()
+                && rect.size.width.is_finite()
+                && rect.size.height.is_finite(),
+            "non-finite rectangle in {}: {:?}",
+            context,
+            (rect.origin.x, rect.origin.y, rect.size.width, rect.size.height),
+        );
+        assert!(
+            rect.size.width >= 0.0 && rect.size.height >= 0.0,
+            "negative rectangle size in {}: {}x{}",
+            context,
+            rect.size.width,
+            rect.size.height,
+        );
+    }
+}
+
+/// Problem with the arrangement of a layout, found by `validate_arrangement`.
+///
+/// Children are identified by their index in the order they were pushed into the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutIssue {
+    /// Two children overlap in `area`.
+    Overlap { first: usize, second: usize, area: AbsoluteRect },
+    /// A child, at `rect`, extends beyond the layout.
+    Overflow { child: usize, rect: AbsoluteRect },
+}
+
+/// Returns the overlapping siblings and the children which don't fit in `parent`, out of `children` laid out by a layout
+/// which shouldn't overlap them (e.g. a stack).
+///
+/// Children with a degenerate rectangle are ignored, as are overlaps and overflows narrower than a hundredth of a pixel
+/// (i.e. rounding errors of adjacent children).
+pub fn validate_arrangement(parent: AbsoluteRect, children: &[AbsoluteRect]) -> Vec<LayoutIssue> {
+    const TOLERANCE: f32 = 0.01;
+
+    let mut issues = Vec::new();
+    let tolerant_parent = parent.inflate(TOLERANCE, TOLERANCE);
+    for (i, rect) in children.iter().enumerate() {
+        if is_degenerate(rect) {
+            continue;
+        }
+
+        if !tolerant_parent.contains_rect(rect) {
+            issues.push(LayoutIssue::Overflow { child: i, rect: *rect });
+        }
+
+        for (j, other) in children.iter().enumerate().skip(i + 1) {
+            if let Some(area) = rect.intersection(other) {
+                if area.size.width > TOLERANCE && area.size.height > TOLERANCE {
+                    issues.push(LayoutIssue::Overlap { first: i, second: j, area });
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Issues of every layout, by the address of the layout, along with the kind of layout.
+#[cfg(feature = "debug-geometry")]
+type LayoutIssueMap = std::collections::HashMap<usize, (&'static str, Vec<LayoutIssue>)>;
+
+#[cfg(feature = "debug-geometry")]
+thread_local! {
+    static LAYOUT_ISSUES: std::cell::RefCell<LayoutIssueMap> = Default::default();
+}
+
+/// With the `debug-geometry` feature, validates the arrangement of a layout (see `validate_arrangement`), logging its issues
+/// whenever they change and keeping them for `layout_issues`. `layout` identifies the layout (e.g. its address) and `kind` names it.
+/// Otherwise does nothing.
+#[inline]
+#[allow(unused_variables)]
+pub fn check_arrangement(
+    layout: usize,
+    kind: &'static str,
+    parent: AbsoluteRect,
+    children: &[AbsoluteRect],
+) {
+    #[cfg(feature = "debug-geometry")]
+    {
+        let issues = validate_arrangement(parent, children);
+        LAYOUT_ISSUES.with(|layouts| {
+            let mut layouts = layouts.borrow_mut();
+            if layouts
+                .get(&layout)
+                .map(|(_, previous)| previous != &issues)
+                .unwrap_or(!issues.is_empty())
+            {
+                for issue in &issues {
+                    eprintln!("layout issue in {}: {:?}", kind, issue);
+                }
+            }
+
+            if issues.is_empty() {
+                layouts.remove(&layout);
+            } else {
+                layouts.insert(layout, (kind, issues));
+            }
+        });
+    }
+}
+
+/// Returns the current issues of every layout checked by `check_arrangement`, along with the kind of layout.
+#[cfg(feature = "debug-geometry")]
+pub fn layout_issues() -> Vec<(&'static str, LayoutIssue)> {
+    LAYOUT_ISSUES.with(|layouts| {
+        layouts
+            .borrow()
+            .values()
+            .flat_map(|(kind, issues)| issues.iter().map(move |issue| (*kind, *issue)))
+            .collect()
+    })
+}
+
+/// Forgets the issues of every layout (e.g. once the widget tree has been replaced).
+#[cfg(feature = "debug-geometry")]
+pub fn clear_layout_issues() {
+    LAYOUT_ISSUES.with(|layouts| layouts.borrow_mut().clear());
+}
+
+/// Returns display commands outlining the current layout issues; overlaps in red and overflowing children in orange.
+#[cfg(feature = "debug-geometry")]
+pub fn layout_issue_overlay() -> Vec<reclutch::display::DisplayCommand> {
+    use reclutch::display::{
+        Color, DisplayListBuilder, GraphicsDisplayPaint, GraphicsDisplayStroke,
+    };
+
+    let mut builder = DisplayListBuilder::new();
+    for (_, issue) in layout_issues() {
+        let (rect, color) = match issue {
+            LayoutIssue::Overlap { area, .. } => (area, Color::new(1.0, 0.0, 0.0, 0.8)),
+            LayoutIssue::Overflow { rect, .. } => (rect, Color::new(1.0, 0.5, 0.0, 0.8)),
+        };
+        builder.push_rectangle(
+            rect.cast_unit(),
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: 2.0,
+                color: color.into(),
+                ..Default::default()
+            }),
+            None,
+        );
+    }
+    builder.build()
+}
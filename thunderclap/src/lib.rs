@@ -10,22 +10,44 @@ extern crate thunderclap_macros;
 
 pub use thunderclap_macros::{
     rooftop, widget, DropNotifier, HasVisibility, LayableWidget, Movable, Repaintable, Resizable,
+    RooftopCompatible, WidgetChildren,
 };
 
 pub use paste;
 
+pub mod a11y;
+pub mod anim;
 #[macro_use]
 pub mod base;
+pub mod bridge;
+pub mod clipboard;
+#[cfg(feature = "crash-reports")]
+pub mod crash;
 pub mod draw;
 pub mod error;
 pub mod geom;
+pub mod pipe;
+pub mod snap;
 #[cfg(feature = "core-widgets")]
 pub mod ui;
 
 #[cfg(feature = "app")]
 pub mod app;
+#[cfg(feature = "declarative")]
+pub mod declarative;
+#[cfg(feature = "core-widgets")]
+pub mod factory;
+pub mod profiling;
+#[cfg(feature = "images")]
+pub mod resources;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod telemetry;
+#[cfg(feature = "core-widgets")]
+pub mod test;
 #[cfg(feature = "default-themes")]
 pub mod themes;
+pub mod validate;
 
 pub mod prelude {
     pub use crate::{
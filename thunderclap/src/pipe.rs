@@ -0,0 +1,226 @@
+//! Stateful combinators for the events handled by verb graph terminals.
+//!
+//! The handlers of a terminal are keyed by event name, and react to every event of that name.
+//! A `Pipeline` stored in the widget narrows down which of those events are reacted to, and when:
+//! ```ignore
+//! // In construction:
+//! let releases = Pipeline::new()
+//!     .filter(|event: &InteractionEvent| matches!(event, InteractionEvent::Released(_)))
+//!     .throttle(Duration::from_millis(100));
+//!
+//! // In a handler:
+//! if let Some(event) = obj.releases.push(event, aux) {
+//!     // ...
+//! }
+//! ```
+//!
+//! Most stages produce their output as the input is pushed, but the rate limiting stages (`throttle` and `debounce`,
+//! built on `base::Throttle` and `base::Debounce`) may hold on to their input until a timer elapses.
+//! Held output is produced by `Pipeline::poll`, which the widget invokes from the `timer` terminal of the window queue:
+//! ```ignore
+//! "timers" => event in aux.window_queue() => {
+//!     timer => {
+//!         if let Some(event) = obj.releases.poll(*event, aux) {
+//!             // ...
+//!         }
+//!     }
+//! }
+//! ```
+
+use {
+    crate::base::{self, TimerId, TimerManager},
+    std::time::Duration,
+};
+
+/// A single step of a `Pipeline`, turning input into (possibly delayed) output.
+pub trait PipelineStage<In, Out> {
+    /// Processes input, returning the output it produces immediately, if any.
+    ///
+    /// Stages which hold back output schedule a timer with `timers` to produce it once due.
+    fn push(&mut self, value: In, timers: &mut TimerManager) -> Option<Out>;
+    /// Returns output which was held back and is now due, if `timer` (from `WindowEvent::Timer`) is a timer
+    /// of this stage.
+    fn poll(&mut self, _timer: TimerId, _timers: &mut TimerManager) -> Option<Out> {
+        None
+    }
+    /// Returns `true` if output is held back, to be returned by a later `poll`.
+    fn is_pending(&self) -> bool {
+        false
+    }
+}
+
+struct Identity;
+
+impl<T> PipelineStage<T, T> for Identity {
+    #[inline]
+    fn push(&mut self, value: T, _timers: &mut TimerManager) -> Option<T> {
+        Some(value)
+    }
+}
+
+struct Chain<In, Mid, Out> {
+    first: Box<dyn PipelineStage<In, Mid>>,
+    second: Box<dyn PipelineStage<Mid, Out>>,
+}
+
+impl<In, Mid, Out> PipelineStage<In, Out> for Chain<In, Mid, Out> {
+    fn push(&mut self, value: In, timers: &mut TimerManager) -> Option<Out> {
+        let value = self.first.push(value, timers)?;
+        self.second.push(value, timers)
+    }
+
+    fn poll(&mut self, timer: TimerId, timers: &mut TimerManager) -> Option<Out> {
+        if let Some(value) = self.first.poll(timer, timers) {
+            if let Some(value) = self.second.push(value, timers) {
+                return Some(value);
+            }
+        }
+        self.second.poll(timer, timers)
+    }
+
+    fn is_pending(&self) -> bool {
+        self.first.is_pending() || self.second.is_pending()
+    }
+}
+
+struct Filter<F>(F);
+
+impl<T, F: FnMut(&T) -> bool> PipelineStage<T, T> for Filter<F> {
+    #[inline]
+    fn push(&mut self, value: T, _timers: &mut TimerManager) -> Option<T> {
+        if (self.0)(&value) {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+struct Map<F>(F);
+
+impl<In, Out, F: FnMut(In) -> Option<Out>> PipelineStage<In, Out> for Map<F> {
+    #[inline]
+    fn push(&mut self, value: In, _timers: &mut TimerManager) -> Option<Out> {
+        (self.0)(value)
+    }
+}
+
+impl<T> PipelineStage<T, T> for base::Throttle<T> {
+    #[inline]
+    fn push(&mut self, value: T, timers: &mut TimerManager) -> Option<T> {
+        base::Throttle::push(self, value, timers)
+    }
+
+    #[inline]
+    fn poll(&mut self, timer: TimerId, timers: &mut TimerManager) -> Option<T> {
+        base::Throttle::poll(self, timer, timers)
+    }
+
+    #[inline]
+    fn is_pending(&self) -> bool {
+        base::Throttle::is_pending(self)
+    }
+}
+
+impl<T> PipelineStage<T, T> for base::Debounce<T> {
+    #[inline]
+    fn push(&mut self, value: T, timers: &mut TimerManager) -> Option<T> {
+        base::Debounce::push(self, value, timers);
+        None
+    }
+
+    #[inline]
+    fn poll(&mut self, timer: TimerId, _timers: &mut TimerManager) -> Option<T> {
+        base::Debounce::poll(self, timer)
+    }
+
+    #[inline]
+    fn is_pending(&self) -> bool {
+        base::Debounce::is_pending(self)
+    }
+}
+
+/// Chain of stages which events handled by a widget are pushed through, such as filtering, mapping and rate limiting.
+pub struct Pipeline<In, Out> {
+    stage: Box<dyn PipelineStage<In, Out>>,
+}
+
+impl<T: 'static> Pipeline<T, T> {
+    /// Creates a pipeline which passes all input through unchanged.
+    pub fn new() -> Self {
+        Pipeline { stage: Box::new(Identity) }
+    }
+}
+
+impl<T: 'static> Default for Pipeline<T, T> {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+impl<In: 'static, Out: 'static> Pipeline<In, Out> {
+    /// Appends a stage to the pipeline.
+    pub fn then<Next: 'static>(
+        self,
+        stage: impl PipelineStage<Out, Next> + 'static,
+    ) -> Pipeline<In, Next> {
+        Pipeline { stage: Box::new(Chain { first: self.stage, second: Box::new(stage) }) }
+    }
+
+    /// Only lets through output for which `predicate` returns `true`.
+    pub fn filter(self, predicate: impl FnMut(&Out) -> bool + 'static) -> Self {
+        self.then(Filter(predicate))
+    }
+
+    /// Transforms output with `f`.
+    pub fn map<Next: 'static>(
+        self,
+        mut f: impl FnMut(Out) -> Next + 'static,
+    ) -> Pipeline<In, Next> {
+        self.then(Map(move |value| Some(f(value))))
+    }
+
+    /// Transforms output with `f`, dropping the output for which it returns `None`.
+    pub fn filter_map<Next: 'static>(
+        self,
+        f: impl FnMut(Out) -> Option<Next> + 'static,
+    ) -> Pipeline<In, Next> {
+        self.then(Map(f))
+    }
+
+    /// Lets through at most one output per `interval` (see `base::Throttle`).
+    ///
+    /// The first output is let through immediately; of the output arriving within the interval after that,
+    /// the last is produced by `poll` once the interval has passed.
+    pub fn throttle(self, interval: Duration) -> Self {
+        self.then(base::Throttle::new(interval))
+    }
+
+    /// Holds on to output until no more output arrives for `delay`, then only lets through the last output
+    /// (see `base::Debounce`).
+    ///
+    /// The output is produced by `poll`, rather than `push`.
+    pub fn debounce(self, delay: Duration) -> Self {
+        self.then(base::Debounce::new(delay))
+    }
+
+    /// Pushes input through the pipeline, returning the output it produces immediately, if any.
+    pub fn push<U: base::UpdateAuxiliary>(&mut self, value: In, aux: &mut U) -> Option<Out> {
+        self.stage.push(value, aux.timers_mut())
+    }
+
+    /// Returns output which was held back and is now due, if `timer` (from `WindowEvent::Timer`) is a timer
+    /// of the pipeline.
+    ///
+    /// Widgets with pipelines which hold back output (i.e. with `throttle` or `debounce`) invoke this from the
+    /// `timer` terminal of the window queue.
+    pub fn poll<U: base::UpdateAuxiliary>(&mut self, timer: TimerId, aux: &mut U) -> Option<Out> {
+        self.stage.poll(timer, aux.timers_mut())
+    }
+
+    /// Returns `true` if output is held back, to be returned by a later `poll`.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.stage.is_pending()
+    }
+}
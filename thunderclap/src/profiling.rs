@@ -0,0 +1,243 @@
+//! Opt-in profiling of the application, e.g. to diagnose sluggish input handling.
+//!
+//! Measurements are only recorded if the update auxiliary provides a `Profiler` (see `UpdateAuxiliary::profiler`),
+//! which is `None` by default.
+//!
+//! Input events (`base::ConsumableEvent`) are stamped with the time they arrived from the OS. Once a widget handles
+//! an event, the time since its arrival is recorded into a histogram of the event key (e.g. `"mouse_press"`):
+//!
+//! ```ignore
+//! u_aux.profiler = Some(Profiler::new());
+//!
+//! // Later on...
+//! if let Some(presses) = u_aux.profiler.as_ref().and_then(|profiler| profiler.latency("mouse_press")) {
+//!     println!("press latency: mean {:?}, 95th percentile {:?}", presses.mean(), presses.percentile(0.95));
+//! }
+//! ```
+//!
+//! Widgets using the interaction terminals of `ui` are recorded automatically.
+//! Other widgets can record the events they handle with `record_latency`.
+//!
+//! Text measurements are cached per thread (see `draw::text_cache`). The bounds of the cache, which keep the memory
+//! use of long-running applications in check, are set with `configure_text_cache`, and its effectiveness is
+//! reported by `text_cache_stats`. The glyph atlas is owned by the display backend, which doesn't expose its
+//! occupancy, so it isn't reported here.
+
+use {
+    crate::{base, draw::text_cache::TEXT_CACHE},
+    std::{
+        collections::BTreeMap,
+        time::{Duration, Instant},
+    },
+};
+
+/// Upper bounds of the histogram buckets, in microseconds; the last bucket holds everything longer.
+const BUCKET_BOUNDS: [u64; 12] =
+    [250, 500, 1_000, 2_000, 4_000, 8_000, 16_000, 33_000, 66_000, 133_000, 266_000, 533_000];
+
+/// Histogram of durations, in exponentially growing buckets from a quarter of a millisecond to half a second.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKET_BOUNDS.len() + 1],
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        LatencyHistogram {
+            buckets: [0; BUCKET_BOUNDS.len() + 1],
+            count: 0,
+            total: Duration::from_secs(0),
+            max: Duration::from_secs(0),
+        }
+    }
+
+    /// Adds a duration to the histogram.
+    pub fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros();
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|&bound| micros <= bound as u128)
+            .unwrap_or(BUCKET_BOUNDS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    /// Returns the amount of durations recorded.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the longest duration recorded.
+    #[inline]
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the mean of the durations recorded, or zero if none were recorded.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::from_secs(0)
+        } else {
+            self.total / self.count as u32
+        }
+    }
+
+    /// Returns an upper bound of the duration below which a `fraction` (from 0 to 1) of the recorded durations lie,
+    /// i.e. the upper bound of the bucket containing the percentile (or the longest duration, for the last bucket).
+    pub fn percentile(&self, fraction: f64) -> Duration {
+        let target = (self.count as f64 * fraction.max(0.0).min(1.0)).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                return match BUCKET_BOUNDS.get(bucket) {
+                    Some(&bound) => Duration::from_micros(bound).min(self.max),
+                    None => self.max,
+                };
+            }
+        }
+        self.max
+    }
+
+    /// Returns the buckets as their upper bound (`None` for the last, unbounded bucket) and the amount of durations within them.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        self.buckets.iter().enumerate().map(|(bucket, &count)| {
+            (BUCKET_BOUNDS.get(bucket).map(|&bound| Duration::from_micros(bound)), count)
+        })
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram::new()
+    }
+}
+
+/// Collects measurements of the application.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    latencies: BTreeMap<&'static str, LatencyHistogram>,
+}
+
+impl Profiler {
+    /// Creates a profiler without any measurements.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records that an event of kind `event` (e.g. `"mouse_press"`) was handled `latency` after it arrived.
+    pub fn record_latency(&mut self, event: &'static str, latency: Duration) {
+        self.latencies.entry(event).or_default().record(latency);
+    }
+
+    /// Returns the latency histogram of a kind of event, if any were recorded.
+    #[inline]
+    pub fn latency(&self, event: &str) -> Option<&LatencyHistogram> {
+        self.latencies.get(event)
+    }
+
+    /// Returns the latency histograms of every kind of event recorded, ordered by kind.
+    pub fn latencies(&self) -> impl Iterator<Item = (&'static str, &LatencyHistogram)> {
+        self.latencies.iter().map(|(&event, histogram)| (event, histogram))
+    }
+
+    /// Discards all measurements.
+    pub fn reset(&mut self) {
+        self.latencies.clear();
+    }
+}
+
+/// Records the latency of an event handled now, if the auxiliary provides a profiler.
+pub fn record_latency<U: base::UpdateAuxiliary + ?Sized, T>(
+    aux: &mut U,
+    event: &'static str,
+    data: &base::ConsumableEvent<T>,
+) {
+    if let Some(profiler) = aux.profiler() {
+        profiler.record_latency(event, Instant::now().saturating_duration_since(data.timestamp()));
+    }
+}
+
+/// Which entries of a full cache are evicted first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvictionPolicy {
+    /// Evicts the entries which were used the longest time ago.
+    LeastRecentlyUsed,
+    /// Evicts the entries which were inserted the longest time ago, regardless of use.
+    /// Lookups are marginally cheaper than `LeastRecentlyUsed`.
+    FirstInFirstOut,
+}
+
+/// Bounds of the text measurement cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextCacheConfig {
+    /// Maximum amount of cached measurements; zero disables the cache.
+    pub max_entries: usize,
+    /// Maximum total length (in bytes) of the measured text kept by the cache.
+    pub max_bytes: usize,
+    pub policy: EvictionPolicy,
+}
+
+impl Default for TextCacheConfig {
+    fn default() -> Self {
+        TextCacheConfig {
+            max_entries: 4096,
+            max_bytes: 256 * 1024,
+            policy: EvictionPolicy::LeastRecentlyUsed,
+        }
+    }
+}
+
+/// Snapshot of the state of the text measurement cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextCacheStats {
+    /// Amount of cached measurements.
+    pub entries: usize,
+    /// Total length (in bytes) of the measured text kept by the cache.
+    pub bytes: usize,
+    /// Amount of measurements served from the cache.
+    pub hits: u64,
+    /// Amount of measurements which weren't cached.
+    pub misses: u64,
+    /// Amount of measurements evicted to stay within the bounds of the cache.
+    pub evictions: u64,
+}
+
+impl TextCacheStats {
+    /// Returns the fraction (from 0 to 1) of measurements served from the cache, or zero if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Sets the bounds of the text measurement cache of the current thread, evicting entries to fit the new bounds.
+pub fn configure_text_cache(config: TextCacheConfig) {
+    TEXT_CACHE.with(|cache| cache.borrow_mut().configure(config));
+}
+
+/// Returns the state of the text measurement cache of the current thread.
+pub fn text_cache_stats() -> TextCacheStats {
+    TEXT_CACHE.with(|cache| cache.borrow().stats())
+}
+
+/// Resets the hit, miss and eviction counts of the text measurement cache of the current thread.
+pub fn reset_text_cache_stats() {
+    TEXT_CACHE.with(|cache| cache.borrow_mut().reset_stats());
+}
+
+/// Discards every cached text measurement of the current thread (e.g. after unloading fonts).
+pub fn clear_text_cache() {
+    TEXT_CACHE.with(|cache| cache.borrow_mut().clear());
+}
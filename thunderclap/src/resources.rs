@@ -0,0 +1,185 @@
+//! Management of the display resources of images, keeping their GPU memory within a budget.
+//!
+//! Images drawn through an `ImageResources` (provided by the graphical auxiliary, see
+//! `GraphicalAuxiliary::image_resources`) are shared between every widget displaying the same `ImageSource`,
+//! and uploaded according to an `ImagePolicy`:
+//! - Images larger than the maximum texture dimension are downscaled when they're uploaded.
+//! - Once the memory of the uploaded images exceeds the budget, the least recently drawn images are removed
+//!   from the display. Evicted images are uploaded again if they're drawn later on.
+//!
+//! The application ends every frame with `ImageResources::end_frame`, which is where images are evicted.
+
+use {
+    crate::ui::ImageSource,
+    reclutch::display::{GraphicsDisplay, ResourceDescriptor, ResourceReference},
+    std::collections::HashMap,
+};
+
+/// Limits on the images uploaded to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImagePolicy {
+    /// Maximum width and height (in pixels) of uploaded images; larger images are downscaled, keeping their
+    /// aspect ratio.
+    pub max_dimension: Option<u32>,
+    /// Maximum amount of memory (in bytes) of the uploaded images, beyond which the least recently drawn images
+    /// are evicted.
+    pub budget: Option<usize>,
+}
+
+impl Default for ImagePolicy {
+    fn default() -> Self {
+        ImagePolicy { max_dimension: Some(8192), budget: Some(512 * 1024 * 1024) }
+    }
+}
+
+struct ManagedImage {
+    // Kept alive so that the address identifying the source isn't reused.
+    _source: ImageSource,
+    resource: ResourceReference,
+    memory: usize,
+    last_drawn: u64,
+}
+
+/// Uploads images to the display according to an `ImagePolicy`, tracking their memory.
+pub struct ImageResources {
+    policy: ImagePolicy,
+    images: HashMap<usize, ManagedImage>,
+    memory: usize,
+    frame: u64,
+    evictions: u64,
+}
+
+impl ImageResources {
+    /// Creates a manager without any images.
+    pub fn new(policy: ImagePolicy) -> Self {
+        ImageResources { policy, images: HashMap::new(), memory: 0, frame: 0, evictions: 0 }
+    }
+
+    /// Returns the policy images are uploaded with.
+    #[inline]
+    pub fn policy(&self) -> ImagePolicy {
+        self.policy
+    }
+
+    /// Changes the policy, evicting images to fit a smaller budget.
+    ///
+    /// Images which are already uploaded aren't downscaled to a smaller maximum dimension.
+    pub fn set_policy(&mut self, display: &mut dyn GraphicsDisplay, policy: ImagePolicy) {
+        self.policy = policy;
+        self.evict(display);
+    }
+
+    /// Returns the resource of an image, uploading it if it isn't already, and marks it as drawn this frame.
+    ///
+    /// Returns `None` if the display failed to create the resource.
+    pub fn image(
+        &mut self,
+        display: &mut dyn GraphicsDisplay,
+        source: &ImageSource,
+    ) -> Option<ResourceReference> {
+        let frame = self.frame;
+        if let Some(image) = self.images.get_mut(&source.id()) {
+            image.last_drawn = frame;
+            return Some(image.resource.clone());
+        }
+
+        let (data, (width, height)) = source.image_data_within(self.policy.max_dimension);
+        let resource = display.new_resource(ResourceDescriptor::Image(data)).ok()?;
+        // Textures are uploaded as 8-bit RGBA.
+        let memory = width as usize * height as usize * 4;
+
+        self.memory += memory;
+        self.images.insert(
+            source.id(),
+            ManagedImage {
+                _source: source.clone(),
+                resource: resource.clone(),
+                memory,
+                last_drawn: frame,
+            },
+        );
+
+        Some(resource)
+    }
+
+    /// Removes an image from the display, if it was uploaded.
+    pub fn release(&mut self, display: &mut dyn GraphicsDisplay, source: &ImageSource) {
+        if let Some(image) = self.images.remove(&source.id()) {
+            self.memory -= image.memory;
+            display.remove_resource(image.resource);
+        }
+    }
+
+    /// Removes every image from the display.
+    pub fn clear(&mut self, display: &mut dyn GraphicsDisplay) {
+        for (_, image) in self.images.drain() {
+            display.remove_resource(image.resource);
+        }
+        self.memory = 0;
+    }
+
+    /// Evicts images beyond the budget and starts a new frame.
+    ///
+    /// Invoked once every frame has been drawn.
+    pub fn end_frame(&mut self, display: &mut dyn GraphicsDisplay) {
+        self.evict(display);
+        self.frame += 1;
+    }
+
+    /// Removes the least recently drawn images until the memory is within budget.
+    /// Images drawn in the current frame are never evicted, even if they alone exceed the budget.
+    fn evict(&mut self, display: &mut dyn GraphicsDisplay) {
+        let budget = match self.policy.budget {
+            Some(budget) => budget,
+            None => return,
+        };
+
+        while self.memory > budget {
+            let oldest = self
+                .images
+                .iter()
+                .filter(|(_, image)| image.last_drawn < self.frame)
+                .min_by_key(|(_, image)| image.last_drawn)
+                .map(|(&id, _)| id);
+
+            match oldest.and_then(|id| self.images.remove(&id)) {
+                Some(image) => {
+                    self.memory -= image.memory;
+                    self.evictions += 1;
+                    display.remove_resource(image.resource);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Returns the total memory (in bytes) of the uploaded images.
+    #[inline]
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// Returns the amount of uploaded images.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Returns `true` if no images are uploaded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Returns the amount of images which have been evicted to stay within budget.
+    #[inline]
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+}
+
+impl Default for ImageResources {
+    fn default() -> Self {
+        ImageResources::new(Default::default())
+    }
+}
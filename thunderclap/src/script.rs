@@ -0,0 +1,258 @@
+//! Integration point for user-embedded scripting engines.
+//!
+//! Tools built on Thunderclap can let users attach scripts (or recorded macros) to widget events without changing widget code.
+//! The scripting engine implements `ScriptHost`, which receives the ID of the script handler alongside the event as JSON.
+//! Event queues are then bound to handler IDs with `ScriptBindings`, which forwards events to the host when dispatched:
+//! ```ignore
+//! let mut bindings = ScriptBindings::new();
+//! bindings.bind("on_save", save_button.event_queue());
+//!
+//! // In `update`:
+//! if let Some(host) = aux.script_host() {
+//!     if let Err(error) = bindings.dispatch(host) {
+//!         eprintln!("script error: {}", error);
+//!     }
+//! }
+//! ```
+//!
+//! Each event is serialized as a JSON object with an `"event"` field naming the event key (e.g. `"press"`),
+//! alongside any event data (see `ScriptEvent`).
+
+use {
+    crate::{error::ScriptError, geom::*, ui},
+    reclutch::event::{RcEventListener, RcEventQueue},
+    serde_json::{json, Value},
+};
+
+/// Scripting engine which runs script handlers.
+pub trait ScriptHost {
+    /// Runs the script handler identified by `handler_id`, passing it an event serialized as JSON.
+    fn call(&mut self, handler_id: &str, event_json: &str) -> Result<(), ScriptError>;
+}
+
+/// Event which can be passed to script handlers.
+pub trait ScriptEvent: Clone + 'static {
+    /// Returns the event as a JSON object with an `"event"` field naming the event key.
+    fn to_script_json(&self) -> Value;
+}
+
+fn point(point: AbsolutePoint) -> Value {
+    json!({ "x": point.x, "y": point.y })
+}
+
+trait Binding {
+    fn dispatch(&self, host: &mut dyn ScriptHost) -> Result<(), ScriptError>;
+}
+
+struct QueueBinding<E: ScriptEvent> {
+    handler_id: String,
+    listener: RcEventListener<E>,
+}
+
+impl<E: ScriptEvent> Binding for QueueBinding<E> {
+    fn dispatch(&self, host: &mut dyn ScriptHost) -> Result<(), ScriptError> {
+        let mut result = Ok(());
+        for event in self.listener.peek() {
+            let call = host.call(&self.handler_id, &event.to_script_json().to_string());
+            // Keep dispatching the remaining events, reporting the first error.
+            if result.is_ok() {
+                result = call;
+            }
+        }
+        result
+    }
+}
+
+/// Event queues bound to script handlers.
+#[derive(Default)]
+pub struct ScriptBindings {
+    bindings: Vec<Box<dyn Binding>>,
+}
+
+impl ScriptBindings {
+    /// Creates an empty list of bindings.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Binds an event queue to a script handler; events emitted from now on are forwarded on `dispatch`.
+    pub fn bind<E: ScriptEvent>(&mut self, handler_id: impl Into<String>, queue: &RcEventQueue<E>) {
+        self.bindings.push(Box::new(QueueBinding {
+            handler_id: handler_id.into(),
+            listener: queue.listen(),
+        }));
+    }
+
+    /// Removes all the bindings.
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Forwards the events emitted since the last dispatch to the script host.
+    ///
+    /// All events are forwarded even if a handler fails, in which case the first error is returned.
+    pub fn dispatch(&self, host: &mut dyn ScriptHost) -> Result<(), ScriptError> {
+        let mut result = Ok(());
+        for binding in &self.bindings {
+            let dispatch = binding.dispatch(host);
+            if result.is_ok() {
+                result = dispatch;
+            }
+        }
+        result
+    }
+}
+
+impl ScriptEvent for ui::ButtonEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::ButtonEvent::Press(pos) => json!({ "event": "press", "position": point(pos) }),
+            ui::ButtonEvent::Release(pos) => json!({ "event": "release", "position": point(pos) }),
+            ui::ButtonEvent::DoubleClick(pos) => {
+                json!({ "event": "double_click", "position": point(pos) })
+            }
+            ui::ButtonEvent::BeginHover(pos) => {
+                json!({ "event": "begin_hover", "position": point(pos) })
+            }
+            ui::ButtonEvent::EndHover(pos) => {
+                json!({ "event": "end_hover", "position": point(pos) })
+            }
+            ui::ButtonEvent::Focus => json!({ "event": "focus" }),
+            ui::ButtonEvent::Blur => json!({ "event": "blur" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::CheckboxEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::CheckboxEvent::Press(pos) => json!({ "event": "press", "position": point(pos) }),
+            ui::CheckboxEvent::Release(pos) => {
+                json!({ "event": "release", "position": point(pos) })
+            }
+            ui::CheckboxEvent::Check(pos) => json!({ "event": "check", "position": point(pos) }),
+            ui::CheckboxEvent::Uncheck(pos) => {
+                json!({ "event": "uncheck", "position": point(pos) })
+            }
+            ui::CheckboxEvent::BeginHover(pos) => {
+                json!({ "event": "begin_hover", "position": point(pos) })
+            }
+            ui::CheckboxEvent::EndHover(pos) => {
+                json!({ "event": "end_hover", "position": point(pos) })
+            }
+            ui::CheckboxEvent::Focus => json!({ "event": "focus" }),
+            ui::CheckboxEvent::Blur => json!({ "event": "blur" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::TextAreaEvent {
+    fn to_script_json(&self) -> Value {
+        match self {
+            ui::TextAreaEvent::Focus => json!({ "event": "focus" }),
+            ui::TextAreaEvent::Blur => json!({ "event": "blur" }),
+            ui::TextAreaEvent::UserModify(text) => json!({ "event": "user_modify", "text": text }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::NumericInputEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::NumericInputEvent::UserModify(value) => {
+                json!({ "event": "user_modify", "value": value })
+            }
+            ui::NumericInputEvent::BeginScrub => json!({ "event": "begin_scrub" }),
+            ui::NumericInputEvent::EndScrub => json!({ "event": "end_scrub" }),
+            ui::NumericInputEvent::BeginHover(pos) => {
+                json!({ "event": "begin_hover", "position": point(pos) })
+            }
+            ui::NumericInputEvent::EndHover(pos) => {
+                json!({ "event": "end_hover", "position": point(pos) })
+            }
+            ui::NumericInputEvent::Focus => json!({ "event": "focus" }),
+            ui::NumericInputEvent::Blur => json!({ "event": "blur" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::ScrollBarEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::ScrollBarEvent::BeginScroll => json!({ "event": "begin_scroll" }),
+            ui::ScrollBarEvent::EndScroll => json!({ "event": "end_scroll" }),
+            ui::ScrollBarEvent::Scroll(position) => json!({
+                "event": "scroll",
+                "amount": position.amount,
+                "amount_range": [position.amount_range.0, position.amount_range.1],
+            }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::TreeViewEvent {
+    fn to_script_json(&self) -> Value {
+        match self {
+            ui::TreeViewEvent::Expand(path) => json!({ "event": "expand", "path": path }),
+            ui::TreeViewEvent::Collapse(path) => json!({ "event": "collapse", "path": path }),
+            ui::TreeViewEvent::Select(path) => json!({ "event": "select", "path": path }),
+            ui::TreeViewEvent::Focus => json!({ "event": "focus" }),
+            ui::TreeViewEvent::Blur => json!({ "event": "blur" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::ListViewEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::ListViewEvent::Select(index) => json!({ "event": "select", "index": index }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::TabViewEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::TabViewEvent::Switch(index) => json!({ "event": "switch", "index": index }),
+            ui::TabViewEvent::Close(index) => json!({ "event": "close", "index": index }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::TourEvent {
+    fn to_script_json(&self) -> Value {
+        match *self {
+            ui::TourEvent::Step(step) => json!({ "event": "step", "step": step }),
+            ui::TourEvent::Skip(step) => json!({ "event": "skip", "step": step }),
+            ui::TourEvent::Finish => json!({ "event": "finish" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::TooltipEvent {
+    fn to_script_json(&self) -> Value {
+        match self {
+            ui::TooltipEvent::Show => json!({ "event": "show" }),
+            ui::TooltipEvent::Hide => json!({ "event": "hide" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::ErrorBoundaryEvent {
+    fn to_script_json(&self) -> Value {
+        match self {
+            ui::ErrorBoundaryEvent::Error(message) => {
+                json!({ "event": "error", "message": message })
+            }
+            ui::ErrorBoundaryEvent::Retry => json!({ "event": "retry" }),
+        }
+    }
+}
+
+impl ScriptEvent for ui::LazyEvent {
+    fn to_script_json(&self) -> Value {
+        match self {
+            ui::LazyEvent::Construct => json!({ "event": "construct" }),
+        }
+    }
+}
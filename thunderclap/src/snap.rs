@@ -0,0 +1,441 @@
+//! Snapping of rectangles and points dragged around editor canvases, to a grid and into alignment with other rectangles.
+//!
+//! A `Snapper` holds what to snap to; the grid, the rectangles to align with (typically the siblings of what's dragged)
+//! and fixed guide lines (e.g. dragged out of a ruler). Snapping returns the adjusted position along with the guides
+//! showing what it aligned with, which a `GuideOverlay` (kept as a child of the dragged widget) draws above
+//! regular widgets:
+//! ```ignore
+//! let snapper = Snapper::new().grid(Size::new(8.0, 8.0)).targets(sibling_rects);
+//!
+//! // While dragging:
+//! let snapped = snapper.snap_rect(dragged_rect);
+//! dragged_rect = snapped.value;
+//! self.guide_overlay.set_guides(snapped.guides);
+//! ```
+//!
+//! Snapping is coordinate space agnostic, as long as everything given to the snapper is in the same space.
+
+use {
+    crate::{
+        base::{self, HasVisibility},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{
+            Color, DisplayCommand, DisplayListBuilder, GraphicsDisplay, GraphicsDisplayPaint,
+            GraphicsDisplayStroke, Point, Rect, Size, Vector, VectorPathBuilder,
+        },
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Orientation of a guide line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GuideAxis {
+    /// Vertical line at an X coordinate, which horizontal positions snap to.
+    Vertical,
+    /// Horizontal line at a Y coordinate, which vertical positions snap to.
+    Horizontal,
+}
+
+/// Infinite guide line which positions snap to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuideLine {
+    pub axis: GuideAxis,
+    /// X coordinate of vertical lines, or Y coordinate of horizontal lines.
+    pub position: f32,
+}
+
+impl GuideLine {
+    /// Creates a vertical guide line at `x`.
+    #[inline]
+    pub fn vertical(x: f32) -> Self {
+        GuideLine { axis: GuideAxis::Vertical, position: x }
+    }
+
+    /// Creates a horizontal guide line at `y`.
+    #[inline]
+    pub fn horizontal(y: f32) -> Self {
+        GuideLine { axis: GuideAxis::Horizontal, position: y }
+    }
+}
+
+/// Segment of a guide line showing an alignment, spanning the aligned rectangles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guide {
+    pub line: GuideLine,
+    /// Start of the segment along the line (Y coordinate of vertical lines, X coordinate of horizontal lines).
+    pub start: f32,
+    /// End of the segment along the line.
+    pub end: f32,
+}
+
+/// Result of snapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapped<T> {
+    /// The snapped rectangle or point.
+    pub value: T,
+    /// How far the rectangle or point was moved by snapping.
+    pub offset: Vector,
+    /// Guides showing the alignments snapped to (grid snapping isn't shown).
+    pub guides: Vec<Guide>,
+}
+
+/// Snaps rectangles and points to a grid, other rectangles and guide lines.
+///
+/// Alignment takes precedence over the grid; an axis is only snapped to the grid if nothing is within `threshold`
+/// to align with along it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapper {
+    /// Cell size of the grid, or `None` to not snap to a grid.
+    pub grid: Option<Size>,
+    /// Origin of the grid.
+    pub grid_origin: Point,
+    /// Maximum distance positions are moved to align them.
+    pub threshold: f32,
+    /// Rectangles which edges and centers are aligned with.
+    pub targets: Vec<Rect>,
+    /// Guide lines which edges, centers and points are aligned with.
+    pub lines: Vec<GuideLine>,
+}
+
+impl Default for Snapper {
+    fn default() -> Self {
+        Snapper {
+            grid: None,
+            grid_origin: Point::new(0.0, 0.0),
+            threshold: 6.0,
+            targets: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+/// Alignment of a single axis; the distance to move, and what it aligns with.
+struct AxisSnap {
+    delta: f32,
+    /// Positions (along the axis) aligned with, and the extents (across the axis) of what's aligned with them.
+    aligned: Vec<(f32, Option<(f32, f32)>)>,
+}
+
+impl Snapper {
+    /// Creates a snapper without a grid or anything to align with.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the cell size of the grid.
+    pub fn grid(mut self, size: Size) -> Self {
+        self.grid = Some(size);
+        self
+    }
+
+    /// Sets the maximum distance positions are moved to align them.
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the rectangles to align with.
+    pub fn targets(mut self, targets: Vec<Rect>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Adds a guide line to align with.
+    pub fn line(mut self, line: GuideLine) -> Self {
+        self.lines.push(line);
+        self
+    }
+
+    /// Snaps a rectangle being moved, aligning its edges or center with those of the targets and the guide lines.
+    ///
+    /// Targets equal to `rect` are ignored, so that the rectangle being moved can be left among the targets.
+    pub fn snap_rect(&self, rect: Rect) -> Snapped<Rect> {
+        let targets: Vec<Rect> =
+            self.targets.iter().copied().filter(|&target| target != rect).collect();
+
+        let x = self.snap_axis(
+            &[rect.min_x(), rect.center().x, rect.max_x()],
+            &targets,
+            GuideAxis::Vertical,
+            rect.origin.x,
+        );
+        let y = self.snap_axis(
+            &[rect.min_y(), rect.center().y, rect.max_y()],
+            &targets,
+            GuideAxis::Horizontal,
+            rect.origin.y,
+        );
+
+        let offset = Vector::new(x.delta, y.delta);
+        let snapped = rect.translate(offset);
+        Snapped {
+            value: snapped,
+            offset,
+            guides: guides(&x, GuideAxis::Vertical, (snapped.min_y(), snapped.max_y()))
+                .chain(guides(&y, GuideAxis::Horizontal, (snapped.min_x(), snapped.max_x())))
+                .collect(),
+        }
+    }
+
+    /// Snaps a point being dragged (e.g. a corner of a rectangle being resized), aligning it with the edges and centers
+    /// of the targets and the guide lines.
+    pub fn snap_point(&self, point: Point) -> Snapped<Point> {
+        let x = self.snap_axis(&[point.x], &self.targets, GuideAxis::Vertical, point.x);
+        let y = self.snap_axis(&[point.y], &self.targets, GuideAxis::Horizontal, point.y);
+
+        let offset = Vector::new(x.delta, y.delta);
+        let snapped = point + offset;
+        Snapped {
+            value: snapped,
+            offset,
+            guides: guides(&x, GuideAxis::Vertical, (snapped.y, snapped.y))
+                .chain(guides(&y, GuideAxis::Horizontal, (snapped.x, snapped.x)))
+                .collect(),
+        }
+    }
+
+    /// Finds the smallest movement within `threshold` which aligns one of `positions` with the edges or centers
+    /// of `targets` or a guide line, falling back to moving `grid_position` onto the grid.
+    fn snap_axis(
+        &self,
+        positions: &[f32],
+        targets: &[Rect],
+        axis: GuideAxis,
+        grid_position: f32,
+    ) -> AxisSnap {
+        let mut candidates = Vec::new();
+        for target in targets {
+            let (anchors, extent) = match axis {
+                GuideAxis::Vertical => (
+                    [target.min_x(), target.center().x, target.max_x()],
+                    (target.min_y(), target.max_y()),
+                ),
+                GuideAxis::Horizontal => (
+                    [target.min_y(), target.center().y, target.max_y()],
+                    (target.min_x(), target.max_x()),
+                ),
+            };
+            candidates.extend(anchors.iter().map(|&anchor| (anchor, Some(extent))));
+        }
+        candidates.extend(
+            self.lines.iter().filter(|line| line.axis == axis).map(|line| (line.position, None)),
+        );
+
+        let delta = candidates
+            .iter()
+            .flat_map(|&(anchor, _)| positions.iter().map(move |&position| anchor - position))
+            .filter(|delta| delta.abs() <= self.threshold)
+            .min_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap_or(std::cmp::Ordering::Equal));
+
+        match delta {
+            Some(delta) => {
+                // Every alignment resulting from the movement is shown, not just the closest.
+                let aligned = candidates
+                    .into_iter()
+                    .filter(|&(anchor, _)| {
+                        positions
+                            .iter()
+                            .any(|&position| (position + delta - anchor).abs() <= ALIGN_EPSILON)
+                    })
+                    .collect();
+                AxisSnap { delta, aligned }
+            }
+            None => {
+                let (cell, origin) = match axis {
+                    GuideAxis::Vertical => (self.grid.map(|grid| grid.width), self.grid_origin.x),
+                    GuideAxis::Horizontal => {
+                        (self.grid.map(|grid| grid.height), self.grid_origin.y)
+                    }
+                };
+                AxisSnap { delta: grid_delta(grid_position, cell, origin), aligned: Vec::new() }
+            }
+        }
+    }
+}
+
+/// Returns the distance from `position` to the nearest grid line.
+fn grid_delta(position: f32, cell: Option<f32>, origin: f32) -> f32 {
+    match cell {
+        Some(cell) if cell > 0.0 => ((position - origin) / cell).round() * cell + origin - position,
+        _ => 0.0,
+    }
+}
+
+/// Tolerance of positions considered aligned, for floating-point error.
+const ALIGN_EPSILON: f32 = 0.01;
+
+/// Creates the guides of an axis, spanning `extent` (the snapped rectangle or point) and the aligned targets.
+fn guides(
+    snap: &AxisSnap,
+    axis: GuideAxis,
+    extent: (f32, f32),
+) -> impl Iterator<Item = Guide> + '_ {
+    snap.aligned.iter().map(move |&(position, target)| {
+        let (start, end) = match target {
+            Some((start, end)) => (start.min(extent.0), end.max(extent.1)),
+            None => extent,
+        };
+        Guide { line: GuideLine { axis, position }, start, end }
+    })
+}
+
+/// Returns the end points of a guide.
+fn guide_points(guide: &Guide) -> (Point, Point) {
+    match guide.line.axis {
+        GuideAxis::Vertical => (
+            Point::new(guide.line.position, guide.start),
+            Point::new(guide.line.position, guide.end),
+        ),
+        GuideAxis::Horizontal => (
+            Point::new(guide.start, guide.line.position),
+            Point::new(guide.end, guide.line.position),
+        ),
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Draws guides in `Layer::Overlay`, above regular widgets."]
+    #[doc = ""]
+    #[doc = "Widgets which snap keep one of these as a child, updating it as they're dragged."]
+    #[doc = "The guides are positioned relative to the parent widget."]
+    pub struct GuideOverlay {
+        WidgetChildren,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        {
+            #[widget_layer]
+            layer: base::Layer,
+            guides: Vec<Guide>,
+            /// Color of the guide lines.
+            pub color: Color,
+            pub thickness: f32,
+        },
+    }
+}
+
+impl<U, G> GuideOverlay<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Creates an overlay without any guides, which draws guides in `color`.
+    ///
+    /// The overlay is `Static`, so that it doesn't take mouse input from the widgets underneath the guides.
+    pub fn new(color: Color) -> Self {
+        let mut overlay = GuideOverlayBuilder {
+            rect: Default::default(),
+            graph: vg::VerbGraph::default().into(),
+
+            layer: base::Layer::Overlay,
+            guides: Vec::new(),
+            color,
+            thickness: 1.0,
+        }
+        .build();
+        overlay.set_visibility(base::Visibility::Static);
+        overlay
+    }
+
+    /// Returns the guides currently shown.
+    #[inline]
+    pub fn guides(&self) -> &[Guide] {
+        &self.guides
+    }
+
+    /// Replaces the guides shown, repainting if they changed.
+    pub fn set_guides(&mut self, guides: Vec<Guide>) {
+        if guides != self.guides {
+            // The rectangle spans the guides, so that it's empty (and nothing is drawn) without any guides.
+            let rect: RelativeRect = Rect::from_points(
+                guides.iter().map(guide_points).flat_map(|(start, end)| vec![start, end]),
+            )
+            .cast_unit();
+
+            self.guides = guides;
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    /// Changes the color of the guide lines, repainting if it changed.
+    pub fn set_color(&mut self, color: Color) {
+        if color != self.color {
+            self.color = color;
+            self.command_group.repaint();
+        }
+    }
+
+    /// Hides all the guides (e.g. once dragging stops).
+    #[inline]
+    pub fn clear(&mut self) {
+        self.set_guides(Vec::new());
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for GuideOverlay<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.command_group.repaint();
+    }
+}
+
+impl<U, G> Widget for GuideOverlay<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        // Straight guides have no width of their own.
+        self.rect.inflate(self.thickness, self.thickness).cast_unit()
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let origin = self.parent_position().to_vector().cast_unit();
+        let (guides, color, thickness) = (&self.guides, self.color, self.thickness);
+        self.command_group.push_with(
+            display,
+            || {
+                let mut builder = DisplayListBuilder::new();
+                for guide in guides {
+                    let (start, end) = guide_points(guide);
+
+                    let mut path = VectorPathBuilder::new();
+                    path.move_to(start + origin);
+                    path.line_to(end + origin);
+                    builder.push_path(
+                        path.build(),
+                        false,
+                        GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                            thickness,
+                            color: color.into(),
+                            ..Default::default()
+                        }),
+                        None,
+                    );
+                }
+                builder.build()
+            },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Opt-in telemetry of user interactions, e.g. for usage analytics or heatmaps of which widgets are used.
+//!
+//! Interactions are only recorded if the update auxiliary provides a `TelemetrySink` (see `UpdateAuxiliary::telemetry_sink`),
+//! which is `None` by default. Records are anonymized; they describe the kind of widget and event,
+//! never the widget contents (e.g. text) or cursor positions.
+//!
+//! Widgets using `ui::basic_interaction_handler` and `ui::focus_handler` are recorded automatically.
+//! Other widgets can report their own interactions with `record`.
+
+use {crate::base, std::time::SystemTime};
+
+/// An anonymized user interaction with a widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InteractionRecord {
+    /// Kind of widget interacted with (e.g. `"ButtonWidget"`).
+    pub widget_kind: &'static str,
+    /// Key of the interaction event (e.g. `"press"`).
+    pub event: &'static str,
+    /// Time at which the interaction occurred.
+    pub timestamp: SystemTime,
+}
+
+/// Receives interaction records.
+pub trait TelemetrySink {
+    /// Invoked for every recorded interaction.
+    fn record(&mut self, record: InteractionRecord);
+}
+
+/// Records an interaction if the auxiliary provides a telemetry sink.
+pub fn record<U: base::UpdateAuxiliary + ?Sized>(
+    aux: &mut U,
+    widget_kind: &'static str,
+    event: &'static str,
+) {
+    if let Some(sink) = aux.telemetry_sink() {
+        sink.record(InteractionRecord { widget_kind, event, timestamp: SystemTime::now() });
+    }
+}
+
+/// Returns the name of a type, without the module path or generic parameters
+/// (e.g. `"ButtonWidget"` rather than `"thunderclap::ui::button::ButtonWidget<U, G>"`).
+pub fn kind_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = &name[..name.find('<').unwrap_or(name.len())];
+    &name[name.rfind("::").map(|idx| idx + 2).unwrap_or(0)..]
+}
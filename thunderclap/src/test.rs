@@ -0,0 +1,1034 @@
+//! Utilities for testing widget logic without a window or a GPU.
+//!
+//! `MockTheme` is a theme with deterministic sizes and colors, whose typefaces aren't loaded into a display,
+//! and `RecordingDisplay` is a `GraphicsDisplay` which doesn't render anything, but records the command groups
+//! submitted to it so that they can be inspected:
+//! ```ignore
+//! let theme = MockTheme::new();
+//! let mut display = RecordingDisplay::new();
+//!
+//! let mut button = Button { text: "OK".into(), ..Button::from_theme(&theme) }.construct(&theme, &mut u_aux);
+//! base::invoke_draw(&mut button, &mut display, &mut g_aux);
+//!
+//! assert_eq!(display.command_group_count(), 1);
+//! display.assert_draws_text("OK");
+//! ```
+//!
+//! Interactions are tested headlessly with `HeadlessAux`, an update auxiliary which synthesizes window events
+//! (e.g. clicks and typed text), and `EventRecorder`, which records the events a widget emits:
+//! ```ignore
+//! let mut aux = HeadlessAux::new();
+//! let mut button = Button { text: "OK".into(), ..Button::from_theme(&theme) }.construct(&theme, &mut aux);
+//! let mut presses = EventRecorder::new(&button.event_queue);
+//!
+//! aux.click(button.abs_rect().center());
+//! aux.update(&mut button);
+//!
+//! presses.assert_emitted(|event| matches!(event, ButtonEvent::Press(_)));
+//! ```
+
+use {
+    crate::{
+        anim, base, bridge, clipboard,
+        draw::{self, state},
+        geom::*,
+    },
+    reclutch::{
+        display::{
+            Color, CommandGroupHandle, DisplayCommand, DisplayItem, DisplayListBuilder,
+            DisplayText, FontInfo, GraphicsDisplay, GraphicsDisplayItem, GraphicsDisplayPaint,
+            Rect, ResourceDescriptor, ResourceReference, Size, StyleColor, TextDisplayItem, Vector,
+            ZOrder,
+        },
+        error,
+        event::{RcEventListener, RcEventQueue},
+        widget::Widget,
+    },
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt::Debug,
+        sync::Arc,
+        time::Instant,
+    },
+};
+
+/// Returns the color `MockTheme` painters fill the widget bounds with.
+#[inline]
+pub fn mock_fill() -> Color {
+    Color::new(0.5, 0.5, 0.5, 1.0)
+}
+
+/// Returns the color of text drawn by `MockTheme` painters.
+#[inline]
+pub fn mock_text() -> Color {
+    Color::new(0.0, 0.0, 0.0, 1.0)
+}
+
+/// Theme with deterministic sizes and colors, for tests.
+///
+/// Every painter fills the widget bounds with [`mock_fill`](fn.mock_fill.html) and, for widgets which
+/// display text (buttons, text areas and tooltips), draws the text in [`mock_text`](fn.mock_text.html).
+/// Widgets with an intrinsic size (e.g. buttons) are given a fixed size hint; others keep their current size.
+///
+/// Typefaces are parsed from an embedded font, but aren't loaded into a display,
+/// so they refer to the placeholder resource `ResourceReference::Font(0)`.
+#[derive(Debug, Clone)]
+pub struct MockTheme {
+    data: draw::ThemeData,
+}
+
+impl MockTheme {
+    /// Creates a mock theme (with a light contrast and a left-to-right layout direction).
+    pub fn new() -> Self {
+        let font = Arc::new(include_bytes!("themes/assets/Inter-Regular.ttf").to_vec());
+        let font = (
+            ResourceReference::Font(0),
+            FontInfo::from_data(font, 0).expect("embedded font is valid"),
+        );
+        let typeface = draw::Typeface {
+            regular: font.clone(),
+            italic: font.clone(),
+            bold: font.clone(),
+            bold_italic: font,
+        };
+        let style = |size| draw::TypefaceStyle {
+            typeface: typeface.clone(),
+            size,
+            style: draw::TextStyle::Regular,
+        };
+
+        let white = Color::new(1.0, 1.0, 1.0, 1.0);
+        let black = Color::new(0.0, 0.0, 0.0, 1.0);
+
+        MockTheme {
+            data: draw::ThemeData {
+                scheme: draw::ColorScheme {
+                    background: white,
+                    error: Color::new(1.0, 0.0, 0.0, 1.0),
+                    focus: Color::new(0.0, 0.0, 1.0, 1.0),
+                    primary: Color::new(0.0, 1.0, 0.0, 1.0),
+                    control_outset: Color::new(0.9, 0.9, 0.9, 1.0),
+                    control_inset: white,
+                    over_error: white,
+                    over_focus: white,
+                    over_primary: white,
+                    over_control_outset: black,
+                    over_control_inset: black,
+                    selection: Color::new(0.0, 0.0, 1.0, 0.25),
+                    caret: black,
+                    link: Color::new(0.0, 0.0, 1.0, 1.0),
+                },
+                typography: draw::Typography {
+                    header: style(32.0),
+                    sub_header: style(24.0),
+                    body: style(16.0),
+                    button: style(12.0),
+                },
+                contrast: draw::ThemeContrast::Light,
+                direction: draw::LayoutDirection::LeftToRight,
+            },
+        }
+    }
+
+    /// Returns the theme data mutably, e.g. to test widgets against a dark or right-to-left theme.
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut draw::ThemeData {
+        &mut self.data
+    }
+}
+
+impl Default for MockTheme {
+    fn default() -> Self {
+        MockTheme::new()
+    }
+}
+
+/// Painter states which `MockPainter` can paint.
+trait MockState {
+    fn rect(&self) -> AbsoluteRect;
+
+    /// Returns the text displayed by the widget, if any.
+    fn text(&self) -> Option<(DisplayText, draw::TypefaceStyle)> {
+        None
+    }
+}
+
+macro_rules! mock_state {
+    ($($state:ty),* $(,)?) => {
+        $(
+            impl MockState for $state {
+                #[inline]
+                fn rect(&self) -> AbsoluteRect {
+                    self.rect
+                }
+            }
+        )*
+    };
+}
+
+mock_state!(
+    state::CheckboxState,
+    state::NumericInputState,
+    state::ScrollBarState,
+    state::TreeViewState,
+    state::ListViewState,
+    state::TabState,
+    state::TourState,
+    state::BlurPanelState,
+    state::CardState,
+    state::ErrorBoundaryState,
+);
+
+#[cfg(feature = "extra-widgets")]
+mock_state!(
+    state::GaugeState,
+    state::RadialProgressState,
+    state::CalendarHeatmapState,
+    state::GradientEditorState,
+    state::TransformGizmoState,
+    state::RulerState,
+);
+
+impl MockState for state::ButtonState {
+    #[inline]
+    fn rect(&self) -> AbsoluteRect {
+        self.rect
+    }
+
+    fn text(&self) -> Option<(DisplayText, draw::TypefaceStyle)> {
+        Some((self.data.text.clone(), self.data.typeface.clone()))
+    }
+}
+
+impl MockState for state::TextAreaState {
+    #[inline]
+    fn rect(&self) -> AbsoluteRect {
+        self.rect
+    }
+
+    fn text(&self) -> Option<(DisplayText, draw::TypefaceStyle)> {
+        Some((self.data.text.clone().into(), self.data.typeface.clone()))
+    }
+}
+
+impl MockState for state::TooltipState {
+    #[inline]
+    fn rect(&self) -> AbsoluteRect {
+        self.tooltip.unwrap_or(self.rect)
+    }
+
+    fn text(&self) -> Option<(DisplayText, draw::TypefaceStyle)> {
+        Some((self.data.text.clone().into(), self.data.typeface.clone()))
+    }
+}
+
+/// Painter of `MockTheme`.
+#[derive(Debug, Clone, Copy)]
+struct MockPainter {
+    /// Size hint, or `None` to keep the current size.
+    size: Option<Size>,
+}
+
+impl MockPainter {
+    fn boxed<S: MockState + 'static>(size: Option<Size>) -> Box<dyn draw::Painter<S>> {
+        Box::new(MockPainter { size })
+    }
+}
+
+impl<S: MockState + 'static> draw::Painter<S> for MockPainter {
+    fn invoke(&self, _theme: &dyn draw::Theme) -> Box<dyn draw::Painter<S>> {
+        Box::new(*self)
+    }
+
+    fn size_hint(&self, state: S) -> Size {
+        self.size.unwrap_or_else(|| state.rect().size.cast_unit())
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: S) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect().cast_unit();
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_rectangle(rect, GraphicsDisplayPaint::Fill(mock_fill().into()), None);
+
+        if let Some((text, typeface)) = state.text() {
+            let font = typeface.typeface.pick(typeface.style);
+            let mut text_item = TextDisplayItem {
+                text,
+                font: font.0,
+                font_info: font.1,
+                size: typeface.size,
+                bottom_left: Default::default(),
+                color: mock_text().into(),
+            };
+            text_item.set_top_left(rect.origin);
+            builder.push_text(text_item, None);
+        }
+
+        builder.build()
+    }
+}
+
+/// Label painter of `MockTheme`, which draws the laid out text as-is (without a fill).
+#[derive(Debug, Clone, Copy)]
+struct MockLabelPainter;
+
+impl draw::Painter<state::LabelState> for MockLabelPainter {
+    fn invoke(&self, _theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::LabelState>> {
+        Box::new(*self)
+    }
+
+    fn size_hint(&self, state: state::LabelState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::LabelState) -> Vec<DisplayCommand> {
+        let mut builder = DisplayListBuilder::new();
+        for run in state.runs {
+            builder.push_text(run, None);
+        }
+        builder.build()
+    }
+}
+
+impl draw::Theme for MockTheme {
+    fn button(&self) -> Box<dyn draw::Painter<state::ButtonState>> {
+        MockPainter::boxed(Some(Size::new(100.0, 30.0)))
+    }
+
+    fn label(&self) -> Box<dyn draw::Painter<state::LabelState>> {
+        Box::new(MockLabelPainter)
+    }
+
+    fn checkbox(&self) -> Box<dyn draw::Painter<state::CheckboxState>> {
+        MockPainter::boxed(Some(Size::new(20.0, 20.0)))
+    }
+
+    fn numeric_input(&self) -> Box<dyn draw::Painter<state::NumericInputState>> {
+        MockPainter::boxed(Some(Size::new(100.0, 30.0)))
+    }
+
+    fn text_area(&self) -> Box<dyn draw::Painter<state::TextAreaState>> {
+        MockPainter::boxed(Some(Size::new(200.0, 30.0)))
+    }
+
+    fn scroll_bar(&self) -> Box<dyn draw::Painter<state::ScrollBarState>> {
+        MockPainter::boxed(None)
+    }
+
+    fn tree_view(&self) -> Box<dyn draw::Painter<state::TreeViewState>> {
+        MockPainter::boxed(Some(Size::new(200.0, 200.0)))
+    }
+
+    fn list_view(&self) -> Box<dyn draw::Painter<state::ListViewState>> {
+        MockPainter::boxed(None)
+    }
+
+    fn tab(&self) -> Box<dyn draw::Painter<state::TabState>> {
+        MockPainter::boxed(None)
+    }
+
+    fn tour(&self) -> Box<dyn draw::Painter<state::TourState>> {
+        MockPainter::boxed(Some(Size::new(200.0, 100.0)))
+    }
+
+    fn tooltip(&self) -> Box<dyn draw::Painter<state::TooltipState>> {
+        MockPainter::boxed(Some(Size::new(100.0, 20.0)))
+    }
+
+    fn blur_panel(&self) -> Box<dyn draw::Painter<state::BlurPanelState>> {
+        MockPainter::boxed(None)
+    }
+
+    fn card(&self) -> Box<dyn draw::Painter<state::CardState>> {
+        MockPainter::boxed(None)
+    }
+
+    fn error_boundary(&self) -> Box<dyn draw::Painter<state::ErrorBoundaryState>> {
+        MockPainter::boxed(None)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gauge(&self) -> Box<dyn draw::Painter<state::GaugeState>> {
+        MockPainter::boxed(Some(Size::new(100.0, 100.0)))
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn radial_progress(&self) -> Box<dyn draw::Painter<state::RadialProgressState>> {
+        MockPainter::boxed(Some(Size::new(30.0, 30.0)))
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn calendar_heatmap(&self) -> Box<dyn draw::Painter<state::CalendarHeatmapState>> {
+        MockPainter::boxed(None)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gradient_editor(&self) -> Box<dyn draw::Painter<state::GradientEditorState>> {
+        MockPainter::boxed(Some(Size::new(200.0, 40.0)))
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn transform_gizmo(&self) -> Box<dyn draw::Painter<state::TransformGizmoState>> {
+        MockPainter::boxed(None)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn ruler(&self) -> Box<dyn draw::Painter<state::RulerState>> {
+        MockPainter::boxed(Some(Size::new(200.0, 20.0)))
+    }
+
+    fn data(&self) -> &draw::ThemeData {
+        &self.data
+    }
+}
+
+/// Command group submitted to a `RecordingDisplay`.
+#[derive(Clone)]
+pub struct RecordedCommandGroup {
+    pub commands: Vec<DisplayCommand>,
+    pub z_order: ZOrder,
+}
+
+/// `GraphicsDisplay` which doesn't render anything, but records the command groups submitted to it.
+///
+/// Resources can be created, but their data is discarded.
+#[derive(Default)]
+pub struct RecordingDisplay {
+    command_groups: BTreeMap<u64, RecordedCommandGroup>,
+    resources: BTreeSet<u64>,
+    next_id: u64,
+    size: (u32, u32),
+    presented: usize,
+}
+
+impl RecordingDisplay {
+    /// Creates a display without any command groups, which has a size of 0 by 0.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the command groups currently submitted, in the order they were first pushed.
+    pub fn command_groups(&self) -> impl Iterator<Item = &RecordedCommandGroup> {
+        self.command_groups.values()
+    }
+
+    /// Returns the amount of command groups currently submitted.
+    #[inline]
+    pub fn command_group_count(&self) -> usize {
+        self.command_groups.len()
+    }
+
+    /// Returns the commands of all the command groups, in the order they would be rendered (i.e. by z-order).
+    pub fn commands(&self) -> Vec<DisplayCommand> {
+        let mut groups: Vec<&RecordedCommandGroup> = self.command_groups.values().collect();
+        // Stable, so that groups of equal z-order remain in push order.
+        groups.sort_by_key(|group| group.z_order.0);
+        groups.into_iter().flat_map(|group| group.commands.iter().cloned()).collect()
+    }
+
+    /// Returns the amount of resources currently loaded.
+    #[inline]
+    pub fn resource_count(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Returns the size given to the last `resize`.
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the amount of times the display was presented.
+    #[inline]
+    pub fn present_count(&self) -> usize {
+        self.presented
+    }
+
+    /// Removes all the command groups and resources.
+    pub fn clear(&mut self) {
+        self.command_groups.clear();
+        self.resources.clear();
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+impl GraphicsDisplay for RecordingDisplay {
+    fn resize(&mut self, size: (u32, u32)) -> Result<(), Box<dyn std::error::Error>> {
+        self.size = size;
+        Ok(())
+    }
+
+    fn new_resource(
+        &mut self,
+        descriptor: ResourceDescriptor,
+    ) -> Result<ResourceReference, error::ResourceError> {
+        let id = self.next_id();
+        let reference = match descriptor {
+            ResourceDescriptor::Image(_) => ResourceReference::Image(id),
+            ResourceDescriptor::Font(_) => ResourceReference::Font(id),
+        };
+        self.resources.insert(id);
+        Ok(reference)
+    }
+
+    fn remove_resource(&mut self, reference: ResourceReference) {
+        match reference {
+            ResourceReference::Image(id) | ResourceReference::Font(id) => {
+                self.resources.remove(&id);
+            }
+        }
+    }
+
+    fn push_command_group(
+        &mut self,
+        commands: &[DisplayCommand],
+        z_order: ZOrder,
+        _protected: Option<bool>,
+        _always_alive: Option<bool>,
+    ) -> Result<CommandGroupHandle, Box<dyn std::error::Error>> {
+        let id = self.next_id();
+        self.command_groups
+            .insert(id, RecordedCommandGroup { commands: commands.to_vec(), z_order });
+        Ok(CommandGroupHandle::new(id))
+    }
+
+    fn get_command_group(&self, handle: CommandGroupHandle) -> Option<Vec<DisplayCommand>> {
+        self.command_groups.get(&handle.id()).map(|group| group.commands.clone())
+    }
+
+    fn modify_command_group(
+        &mut self,
+        handle: CommandGroupHandle,
+        commands: &[DisplayCommand],
+        z_order: ZOrder,
+        _protected: Option<bool>,
+        _always_alive: Option<bool>,
+    ) {
+        if let Some(group) = self.command_groups.get_mut(&handle.id()) {
+            group.commands = commands.to_vec();
+            group.z_order = z_order;
+        }
+    }
+
+    fn maintain_command_group(&mut self, _handle: CommandGroupHandle) {}
+
+    fn remove_command_group(&mut self, handle: CommandGroupHandle) -> Option<Vec<DisplayCommand>> {
+        self.command_groups.remove(&handle.id()).map(|group| group.commands)
+    }
+
+    fn before_exit(&mut self) {}
+
+    fn present(&mut self, _cull: Option<Rect>) -> Result<(), error::DisplayError> {
+        self.presented += 1;
+        Ok(())
+    }
+}
+
+/// Tolerance used when comparing coordinates and color components.
+const EPSILON: f32 = 0.01;
+
+fn rects_match(a: Rect, b: Rect) -> bool {
+    (a.origin.x - b.origin.x).abs() <= EPSILON
+        && (a.origin.y - b.origin.y).abs() <= EPSILON
+        && (a.size.width - b.size.width).abs() <= EPSILON
+        && (a.size.height - b.size.height).abs() <= EPSILON
+}
+
+fn colors_match(a: Color, b: Color) -> bool {
+    (a.red - b.red).abs() <= EPSILON
+        && (a.green - b.green).abs() <= EPSILON
+        && (a.blue - b.blue).abs() <= EPSILON
+        && (a.alpha - b.alpha).abs() <= EPSILON
+}
+
+/// Returns the text items within `commands`.
+pub fn text_items(commands: &[DisplayCommand]) -> impl Iterator<Item = &TextDisplayItem> {
+    commands.iter().filter_map(|command| match command {
+        DisplayCommand::Item(DisplayItem::Text(item), _) => Some(item),
+        _ => None,
+    })
+}
+
+/// Returns the texts drawn by `commands` (excluding shaped text, which can't be converted back to a string).
+pub fn drawn_texts(commands: &[DisplayCommand]) -> Vec<String> {
+    text_items(commands)
+        .filter_map(|item| match &item.text {
+            DisplayText::Simple(text) => Some(text.clone()),
+            DisplayText::Shaped(_) => None,
+        })
+        .collect()
+}
+
+/// Returns `true` if `commands` draw `text` (exactly, rather than as part of a longer text).
+pub fn draws_text(commands: &[DisplayCommand], text: &str) -> bool {
+    drawn_texts(commands).iter().any(|drawn| drawn == text)
+}
+
+/// Returns `true` if `commands` fill `rect` (either as a rectangle or a rounded rectangle) with the solid `color`.
+///
+/// Coordinates are compared as given in the commands, i.e. transformations (such as `DisplayCommand::Scale`) aren't applied.
+pub fn fills_rect(commands: &[DisplayCommand], rect: AbsoluteRect, color: Color) -> bool {
+    let rect: Rect = rect.cast_unit();
+    commands.iter().any(|command| match command {
+        DisplayCommand::Item(DisplayItem::Graphics(item), _) => match item {
+            GraphicsDisplayItem::Rectangle {
+                rect: filled,
+                paint: GraphicsDisplayPaint::Fill(StyleColor::Color(fill)),
+            }
+            | GraphicsDisplayItem::RoundRectangle {
+                rect: filled,
+                paint: GraphicsDisplayPaint::Fill(StyleColor::Color(fill)),
+                ..
+            } => rects_match(*filled, rect) && colors_match(*fill, color),
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+/// Assertions on the recorded commands, for readable widget and painter tests:
+/// ```ignore
+/// display.assert_draws_text("Count: 3");
+/// display.assert_rect_filled(button.abs_rect(), theme.data().scheme.control_outset);
+/// ```
+impl RecordingDisplay {
+    /// Panics if `text` isn't drawn by any command group.
+    pub fn assert_draws_text(&self, text: &str) {
+        let commands = self.commands();
+        if !draws_text(&commands, text) {
+            panic!("expected {:?} to be drawn, drawn texts are {:?}", text, drawn_texts(&commands));
+        }
+    }
+
+    /// Panics if `text` is drawn by any command group.
+    pub fn assert_not_draws_text(&self, text: &str) {
+        if draws_text(&self.commands(), text) {
+            panic!("expected {:?} not to be drawn", text);
+        }
+    }
+
+    /// Panics if `rect` isn't filled with the solid `color` by any command group.
+    pub fn assert_rect_filled(&self, rect: AbsoluteRect, color: Color) {
+        if !fills_rect(&self.commands(), rect, color) {
+            panic!("expected {:?} to be filled with {:?}", rect, color);
+        }
+    }
+}
+
+/// Graphical auxiliary for tests, with a scale factor of 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockGraphicalAuxiliary {
+    pub scale: f32,
+    /// Whether to report support for backdrop filters, e.g. to test fallback backgrounds.
+    pub backdrop_filters: bool,
+}
+
+impl Default for MockGraphicalAuxiliary {
+    fn default() -> Self {
+        MockGraphicalAuxiliary { scale: 1.0, backdrop_filters: true }
+    }
+}
+
+impl base::GraphicalAuxiliary for MockGraphicalAuxiliary {
+    #[inline]
+    fn scaling(&self) -> f32 {
+        self.scale
+    }
+
+    #[inline]
+    fn supports_backdrop_filters(&self) -> bool {
+        self.backdrop_filters
+    }
+}
+
+/// Update auxiliary for tests, which drives widgets without a window by synthesizing window events.
+///
+/// Synthesized events are queued until the widgets are updated (see `update`), in the order they were synthesized,
+/// so a whole interaction (e.g. a click, which is a press followed by a release) can be queued before updating.
+pub struct HeadlessAux {
+    pub window_queue: RcEventQueue<base::WindowEvent>,
+    pub focus_tracker: base::FocusTracker,
+    pub timers: base::TimerManager,
+    pub animator: anim::Animator,
+    pub event_bus: base::EventBus,
+    pub clipboard: clipboard::LocalClipboard,
+    /// Theme requested through `UpdateAuxiliary::set_theme`, which is left to the test to apply.
+    pub theme_request: Option<Box<dyn draw::Theme>>,
+    /// External sources added through `UpdateAuxiliary::add_external_source`, pumped by `update`.
+    pub sources: Vec<Box<dyn bridge::ExternalSource>>,
+    pub pointer_lock: bool,
+    /// Modifiers held during synthesized input events.
+    pub modifiers: base::KeyModifiers,
+    /// Position of the cursor, as of the last synthesized mouse event.
+    pub cursor: AbsolutePoint,
+    pub g_aux: MockGraphicalAuxiliary,
+}
+
+impl HeadlessAux {
+    /// Creates an auxiliary without any queued events, whose clipboard supports the primary selection.
+    pub fn new() -> Self {
+        let window_queue = RcEventQueue::new();
+        let focus_tracker = base::FocusTracker::new(&window_queue);
+        HeadlessAux {
+            window_queue,
+            focus_tracker,
+            timers: base::TimerManager::new(),
+            animator: anim::Animator::new(),
+            event_bus: base::EventBus::new(),
+            clipboard: clipboard::LocalClipboard::with_primary_selection(true),
+            theme_request: None,
+            sources: Vec::new(),
+            pointer_lock: false,
+            modifiers: base::KeyModifiers { shift: false, ctrl: false, alt: false, logo: false },
+            cursor: Default::default(),
+            g_aux: Default::default(),
+        }
+    }
+
+    /// Updates the focus tracker, then `root`, processing the queued events.
+    pub fn update<W>(&mut self, root: &mut W)
+    where
+        W: base::WidgetChildren<UpdateAux = Self, GraphicalAux = MockGraphicalAuxiliary>,
+    {
+        for source in &mut self.sources {
+            source.pump();
+        }
+        self.sources.retain(|source| source.is_connected());
+
+        self.focus_tracker.update();
+        root.update(self);
+    }
+
+    /// Queues the events of the timers elapsed by `now` and advances the animations to `now`,
+    /// so that time-based behaviour can be tested without waiting.
+    pub fn advance_to(&mut self, now: Instant) -> &mut Self {
+        for timer in self.timers.poll(now) {
+            self.window_queue.emit_owned(base::WindowEvent::Timer(timer));
+        }
+        self.animator.tick(now);
+        self
+    }
+
+    /// Queues the cursor moving to `position`.
+    pub fn move_mouse(&mut self, position: AbsolutePoint) -> &mut Self {
+        self.cursor = position;
+        self.window_queue.emit_owned(base::WindowEvent::MouseMove(base::ConsumableEvent::new((
+            position,
+            self.modifiers,
+        ))));
+        self
+    }
+
+    /// Queues `button` being pressed at `position` (after moving the cursor there).
+    ///
+    /// Like the application, focus is cleared beforehand, so that the pressed widget can take it.
+    pub fn press(&mut self, position: AbsolutePoint, button: base::MouseButton) -> &mut Self {
+        self.move_mouse(position);
+        self.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+        self.window_queue.emit_owned(base::WindowEvent::MousePress(base::ConsumableEvent::new((
+            position,
+            button,
+            self.modifiers,
+        ))));
+        self
+    }
+
+    /// Queues `button` being released at `position` (after moving the cursor there).
+    pub fn release(&mut self, position: AbsolutePoint, button: base::MouseButton) -> &mut Self {
+        self.move_mouse(position);
+        self.window_queue.emit_owned(base::WindowEvent::ClearFocus);
+        self.window_queue.emit_owned(base::WindowEvent::MouseRelease(base::ConsumableEvent::new(
+            (position, button, self.modifiers),
+        )));
+        self
+    }
+
+    /// Queues a left click (i.e. a press and a release) at `position`.
+    pub fn click(&mut self, position: AbsolutePoint) -> &mut Self {
+        self.press(position, base::MouseButton::Left).release(position, base::MouseButton::Left)
+    }
+
+    /// Queues a left press at `start`, a cursor movement to `end` and a left release at `end`.
+    pub fn drag(&mut self, start: AbsolutePoint, end: AbsolutePoint) -> &mut Self {
+        self.press(start, base::MouseButton::Left)
+            .move_mouse(end)
+            .release(end, base::MouseButton::Left)
+    }
+
+    /// Queues the mouse wheel being scrolled by `delta` (in logical pixels, where positive values scroll up/left)
+    /// with the cursor at `position`.
+    pub fn scroll(&mut self, position: AbsolutePoint, delta: Vector) -> &mut Self {
+        self.move_mouse(position);
+        self.window_queue.emit_owned(base::WindowEvent::MouseWheel(base::ConsumableEvent::new((
+            position,
+            delta,
+            self.modifiers,
+        ))));
+        self
+    }
+
+    /// Queues `key` being pressed and released.
+    pub fn key(&mut self, key: base::KeyInput) -> &mut Self {
+        self.window_queue.emit_owned(base::WindowEvent::KeyPress(base::ConsumableEvent::new((
+            key,
+            self.modifiers,
+        ))));
+        self.window_queue.emit_owned(base::WindowEvent::KeyRelease(base::ConsumableEvent::new((
+            key,
+            self.modifiers,
+        ))));
+        self
+    }
+
+    /// Queues every character of `text` as text input, as if typed.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.window_queue
+                .emit_owned(base::WindowEvent::TextInput(base::ConsumableEvent::new(c)));
+        }
+        self
+    }
+
+    /// Queues the window being resized to `size` (in logical pixels), re-running the layouts bound to the window.
+    pub fn resize(&mut self, size: Size) -> &mut Self {
+        self.window_queue.emit_owned(base::WindowEvent::Resized(size));
+        self
+    }
+
+    /// Changes the scale factor to `scale`, re-measuring `root` and queuing `DpiChanged`,
+    /// as if the window moved to a monitor with a different DPI.
+    pub fn rescale<W>(&mut self, root: &mut W, scale: f32) -> &mut Self
+    where
+        W: base::WidgetChildren<
+            UpdateAux = Self,
+            GraphicalAux = MockGraphicalAuxiliary,
+            DisplayObject = DisplayCommand,
+        >,
+    {
+        self.g_aux.scale = scale;
+        base::rescale(root);
+        self.window_queue.emit_owned(base::WindowEvent::DpiChanged(scale));
+        self
+    }
+}
+
+impl Default for HeadlessAux {
+    fn default() -> Self {
+        HeadlessAux::new()
+    }
+}
+
+impl base::UpdateAuxiliary for HeadlessAux {
+    #[inline]
+    fn window_queue(&self) -> &RcEventQueue<base::WindowEvent> {
+        &self.window_queue
+    }
+
+    #[inline]
+    fn window_queue_mut(&mut self) -> &mut RcEventQueue<base::WindowEvent> {
+        &mut self.window_queue
+    }
+
+    #[inline]
+    fn graphical(&self) -> &dyn base::GraphicalAuxiliary {
+        &self.g_aux
+    }
+
+    #[inline]
+    fn graphical_mut(&mut self) -> &mut dyn base::GraphicalAuxiliary {
+        &mut self.g_aux
+    }
+
+    #[inline]
+    fn focus_tracker(&self) -> &base::FocusTracker {
+        &self.focus_tracker
+    }
+
+    #[inline]
+    fn focus_tracker_mut(&mut self) -> &mut base::FocusTracker {
+        &mut self.focus_tracker
+    }
+
+    #[inline]
+    fn timers(&self) -> &base::TimerManager {
+        &self.timers
+    }
+
+    #[inline]
+    fn timers_mut(&mut self) -> &mut base::TimerManager {
+        &mut self.timers
+    }
+
+    #[inline]
+    fn animator(&self) -> &anim::Animator {
+        &self.animator
+    }
+
+    #[inline]
+    fn animator_mut(&mut self) -> &mut anim::Animator {
+        &mut self.animator
+    }
+
+    #[inline]
+    fn event_bus(&self) -> &base::EventBus {
+        &self.event_bus
+    }
+
+    #[inline]
+    fn event_bus_mut(&mut self) -> &mut base::EventBus {
+        &mut self.event_bus
+    }
+
+    #[inline]
+    fn set_pointer_lock(&mut self, locked: bool) {
+        self.pointer_lock = locked;
+    }
+
+    #[inline]
+    fn pointer_locked(&self) -> bool {
+        self.pointer_lock
+    }
+
+    #[inline]
+    fn set_theme(&mut self, theme: Box<dyn draw::Theme>) {
+        self.theme_request = Some(theme);
+    }
+
+    #[inline]
+    fn add_external_source(&mut self, source: Box<dyn bridge::ExternalSource>) {
+        self.sources.push(source);
+    }
+
+    #[inline]
+    fn clipboard(&mut self) -> Option<&mut dyn clipboard::Clipboard> {
+        Some(&mut self.clipboard)
+    }
+}
+
+/// Records the events emitted into an event queue (e.g. the `event_queue` of a widget), for assertions.
+pub struct EventRecorder<T: Clone + 'static> {
+    listener: RcEventListener<T>,
+    events: Vec<T>,
+}
+
+impl<T: Clone + Debug + 'static> EventRecorder<T> {
+    /// Starts recording the events emitted into `queue` from now on.
+    pub fn new(queue: &RcEventQueue<T>) -> Self {
+        EventRecorder { listener: queue.listen(), events: Vec::new() }
+    }
+
+    /// Returns all the events recorded so far, in the order they were emitted.
+    pub fn events(&mut self) -> &[T] {
+        self.events.extend(self.listener.peek());
+        &self.events
+    }
+
+    /// Forgets the events recorded so far.
+    pub fn clear(&mut self) {
+        self.events();
+        self.events.clear();
+    }
+
+    /// Panics if no recorded event matches `predicate`.
+    pub fn assert_emitted(&mut self, predicate: impl Fn(&T) -> bool) {
+        let events = self.events();
+        if !events.iter().any(predicate) {
+            panic!("expected a matching event to be emitted, emitted events are {:?}", events);
+        }
+    }
+
+    /// Panics if any recorded event matches `predicate`.
+    pub fn assert_not_emitted(&mut self, predicate: impl Fn(&T) -> bool) {
+        if let Some(event) = self.events().iter().find(|event| predicate(event)) {
+            panic!("expected no matching event to be emitted, but {:?} was", event);
+        }
+    }
+
+    /// Panics unless exactly `count` events have been recorded.
+    pub fn assert_count(&mut self, count: usize) {
+        let events = self.events();
+        if events.len() != count {
+            panic!("expected {} events to be emitted, emitted events are {:?}", count, events);
+        }
+    }
+}
+
+/// Returns a textual snapshot of the widget tree under `root`, for snapshot testing of layouts.
+///
+/// Every widget is written on its own line (indented by depth) with its type, its path from `root`
+/// (the child indices, which identify the widget within the tree) and its absolute bounds, rounded to one decimal.
+/// Widgets which aren't visible are marked with their visibility. For example:
+/// ```text
+/// VStackWidget [] (0.0, 0.0) 200.0x60.0
+///   ButtonWidget [0] (0.0, 0.0) 100.0x30.0
+///   LabelWidget [1] (0.0, 30.0) 80.0x30.0 Invisible
+/// ```
+/// The snapshot only depends on the tree and its layout, so it's stable across runs.
+/// Layouts are only applied once the widgets are updated, so `root` should be updated before taking the snapshot.
+pub fn layout_snapshot<U, G>(
+    root: &dyn base::WidgetChildren<
+        UpdateAux = U,
+        GraphicalAux = G,
+        DisplayObject = DisplayCommand,
+    >,
+) -> String {
+    fn write_widget<U, G>(
+        widget: &dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+        path: &mut Vec<usize>,
+        snapshot: &mut String,
+    ) {
+        let bounds = widget.abs_bounds();
+        let path_text = path.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(".");
+
+        snapshot.push_str(&"  ".repeat(path.len()));
+        snapshot.push_str(&format!(
+            "{} [{}] ({:.1}, {:.1}) {:.1}x{:.1}",
+            widget.widget_kind(),
+            path_text,
+            bounds.origin.x,
+            bounds.origin.y,
+            bounds.size.width,
+            bounds.size.height,
+        ));
+        if widget.visibility() != base::Visibility::Normal {
+            snapshot.push_str(&format!(" {:?}", widget.visibility()));
+        }
+        snapshot.push('\n');
+
+        for (index, child) in widget.children().into_iter().enumerate() {
+            path.push(index);
+            write_widget(child, path, snapshot);
+            path.pop();
+        }
+    }
+
+    let mut snapshot = String::new();
+    write_widget(root, &mut Vec::new(), &mut snapshot);
+    snapshot
+}
@@ -1 +1,329 @@
+//! RON-based theme which can be reloaded at runtime.
+//!
+//! # Example
+//! ```ignore
+//! let mut theme = Dynamic::from_file(&mut display, "theme.ron")?;
+//! let mut watcher = DynamicWatcher::polling("theme.ron");
+//!
+//! // Once per frame:
+//! if let Err(error) = watcher.update(&mut theme, &mut root, &g_aux) {
+//!     eprintln!("failed to reload theme: {}", error);
+//! }
+//! ```
 
+use {
+    super::{Dynamic, Primer},
+    crate::{
+        base::{self, WidgetChildren},
+        draw::{self, state},
+        error,
+    },
+    reclutch::display::{Color, DisplayCommand, GraphicsDisplay},
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
+};
+
+/// Color as 8-bit red, green and blue components alongside an alpha between 0 and 1, e.g. `(36, 41, 46, 1.0)`.
+pub type ColorDescription = (u8, u8, u8, f32);
+
+/// Serialized color scheme, see `draw::ColorScheme`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SchemeDescription {
+    pub background: ColorDescription,
+    pub error: ColorDescription,
+    pub focus: ColorDescription,
+    pub primary: ColorDescription,
+    pub control_outset: ColorDescription,
+    pub control_inset: ColorDescription,
+    pub over_error: ColorDescription,
+    pub over_focus: ColorDescription,
+    pub over_primary: ColorDescription,
+    pub over_control_outset: ColorDescription,
+    pub over_control_inset: ColorDescription,
+    pub selection: ColorDescription,
+    pub caret: ColorDescription,
+    pub link: ColorDescription,
+}
+
+/// Text sizes of the typography, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextSizes {
+    pub header: f32,
+    pub sub_header: f32,
+    pub body: f32,
+    pub button: f32,
+}
+
+/// Serialized description of a [`Dynamic`](struct.Dynamic.html) theme.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThemeDescription {
+    pub scheme: SchemeDescription,
+    pub contrast: draw::ThemeContrast,
+    #[serde(default)]
+    pub direction: draw::LayoutDirection,
+    /// Text sizes, which default to those of `Primer`.
+    #[serde(default)]
+    pub text_sizes: Option<TextSizes>,
+}
+
+fn color((r, g, b, a): ColorDescription) -> Color {
+    base::color_from_urgba(r, g, b, a)
+}
+
+impl ThemeDescription {
+    /// Applies the description to `data`, keeping its typefaces.
+    fn apply(&self, data: &mut draw::ThemeData) {
+        let scheme = &self.scheme;
+        data.scheme = draw::ColorScheme {
+            background: color(scheme.background),
+            error: color(scheme.error),
+            focus: color(scheme.focus),
+            primary: color(scheme.primary),
+            control_outset: color(scheme.control_outset),
+            control_inset: color(scheme.control_inset),
+            over_error: color(scheme.over_error),
+            over_focus: color(scheme.over_focus),
+            over_primary: color(scheme.over_primary),
+            over_control_outset: color(scheme.over_control_outset),
+            over_control_inset: color(scheme.over_control_inset),
+            selection: color(scheme.selection),
+            caret: color(scheme.caret),
+            link: color(scheme.link),
+        };
+        data.contrast = self.contrast;
+        data.direction = self.direction;
+
+        if let Some(sizes) = self.text_sizes {
+            data.typography.header.size = sizes.header;
+            data.typography.sub_header.size = sizes.sub_header;
+            data.typography.body.size = sizes.body;
+            data.typography.button.size = sizes.button;
+        }
+    }
+}
+
+impl Dynamic {
+    /// Creates a theme from the RON source of a `ThemeDescription`.
+    pub fn new(display: &mut dyn GraphicsDisplay, source: &str) -> Result<Self, error::ThemeError> {
+        let base = Primer::new(display)?;
+        let mut data = draw::Theme::data(&base).clone();
+        ron::de::from_str::<ThemeDescription>(source)?.apply(&mut data);
+        Ok(Dynamic { base, data })
+    }
+
+    /// Creates a theme from a RON file describing a `ThemeDescription`.
+    pub fn from_file(
+        display: &mut dyn GraphicsDisplay,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, error::ThemeError> {
+        Dynamic::new(display, &fs::read_to_string(path)?)
+    }
+
+    /// Re-parses the theme from the RON source of a `ThemeDescription`.
+    ///
+    /// If the source fails to parse, the theme is left unchanged.
+    pub fn reload(&mut self, source: &str) -> Result<(), error::ThemeError> {
+        let description = ron::de::from_str::<ThemeDescription>(source)?;
+        let mut data = draw::Theme::data(&self.base).clone();
+        description.apply(&mut data);
+        self.data = data;
+        Ok(())
+    }
+}
+
+/// Notifies a [`DynamicWatcher`](struct.DynamicWatcher.html) of changes to a theme file.
+///
+/// This keeps file-watching pluggable; [`ModifiedTimeNotifier`](struct.ModifiedTimeNotifier.html) polls the
+/// modification time of the file, whereas an implementation based on a file-watching crate can avoid polling.
+/// Closures returning `bool` also implement this trait.
+pub trait ThemeNotifier {
+    /// Returns `true` if the theme file has changed since the last call.
+    fn changed(&mut self) -> bool;
+}
+
+impl<F: FnMut() -> bool> ThemeNotifier for F {
+    #[inline]
+    fn changed(&mut self) -> bool {
+        self()
+    }
+}
+
+/// Notifier which compares the modification time of a file every time it's polled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedTimeNotifier {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ModifiedTimeNotifier {
+    /// Creates a notifier for the file at `path`, which is considered unchanged as of now.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = Self::modified(&path);
+        ModifiedTimeNotifier { path, modified }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+}
+
+impl ThemeNotifier for ModifiedTimeNotifier {
+    fn changed(&mut self) -> bool {
+        let modified = Self::modified(&self.path);
+        if modified != self.modified {
+            self.modified = modified;
+            modified.is_some()
+        } else {
+            false
+        }
+    }
+}
+
+/// Reloads a [`Dynamic`](struct.Dynamic.html) theme whenever its file changes, and applies it to a widget tree.
+#[derive(Debug)]
+pub struct DynamicWatcher<N: ThemeNotifier> {
+    path: PathBuf,
+    notifier: N,
+}
+
+impl DynamicWatcher<ModifiedTimeNotifier> {
+    /// Creates a watcher which polls the modification time of the file at `path`.
+    pub fn polling(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        DynamicWatcher { notifier: ModifiedTimeNotifier::new(path.clone()), path }
+    }
+}
+
+impl<N: ThemeNotifier> DynamicWatcher<N> {
+    /// Creates a watcher for the file at `path`, which is re-read whenever `notifier` reports a change.
+    pub fn new(path: impl Into<PathBuf>, notifier: N) -> Self {
+        DynamicWatcher { path: path.into(), notifier }
+    }
+
+    /// Returns the path of the theme file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// If the notifier reports a change, re-parses the theme file into `theme` and reloads the theme of `root`
+    /// and all its descendants (see `base::reload_theme`).
+    ///
+    /// Returns `true` if the theme was reloaded.
+    /// If the file can't be read or parsed, both the theme and the widget tree are left unchanged.
+    pub fn update<U, G>(
+        &mut self,
+        theme: &mut Dynamic,
+        root: &mut dyn WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+        aux: &dyn base::GraphicalAuxiliary,
+    ) -> Result<bool, error::ThemeError> {
+        if !self.notifier.changed() {
+            return Ok(false);
+        }
+
+        theme.reload(&fs::read_to_string(&self.path)?)?;
+        base::reload_theme(root, theme, aux);
+        Ok(true)
+    }
+}
+
+impl draw::Theme for Dynamic {
+    fn button(&self) -> Box<dyn draw::Painter<state::ButtonState>> {
+        self.base.button()
+    }
+
+    fn label(&self) -> Box<dyn draw::Painter<state::LabelState>> {
+        self.base.label()
+    }
+
+    fn checkbox(&self) -> Box<dyn draw::Painter<state::CheckboxState>> {
+        self.base.checkbox()
+    }
+
+    fn numeric_input(&self) -> Box<dyn draw::Painter<state::NumericInputState>> {
+        self.base.numeric_input()
+    }
+
+    fn text_area(&self) -> Box<dyn draw::Painter<state::TextAreaState>> {
+        self.base.text_area()
+    }
+
+    fn scroll_bar(&self) -> Box<dyn draw::Painter<state::ScrollBarState>> {
+        self.base.scroll_bar()
+    }
+
+    fn tree_view(&self) -> Box<dyn draw::Painter<state::TreeViewState>> {
+        self.base.tree_view()
+    }
+
+    fn list_view(&self) -> Box<dyn draw::Painter<state::ListViewState>> {
+        self.base.list_view()
+    }
+
+    fn tab(&self) -> Box<dyn draw::Painter<state::TabState>> {
+        self.base.tab()
+    }
+
+    fn tour(&self) -> Box<dyn draw::Painter<state::TourState>> {
+        self.base.tour()
+    }
+
+    fn tooltip(&self) -> Box<dyn draw::Painter<state::TooltipState>> {
+        self.base.tooltip()
+    }
+
+    fn blur_panel(&self) -> Box<dyn draw::Painter<state::BlurPanelState>> {
+        self.base.blur_panel()
+    }
+
+    fn card(&self) -> Box<dyn draw::Painter<state::CardState>> {
+        self.base.card()
+    }
+
+    fn error_boundary(&self) -> Box<dyn draw::Painter<state::ErrorBoundaryState>> {
+        self.base.error_boundary()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gauge(&self) -> Box<dyn draw::Painter<state::GaugeState>> {
+        self.base.gauge()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn radial_progress(&self) -> Box<dyn draw::Painter<state::RadialProgressState>> {
+        self.base.radial_progress()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn calendar_heatmap(&self) -> Box<dyn draw::Painter<state::CalendarHeatmapState>> {
+        self.base.calendar_heatmap()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gradient_editor(&self) -> Box<dyn draw::Painter<state::GradientEditorState>> {
+        self.base.gradient_editor()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn transform_gizmo(&self) -> Box<dyn draw::Painter<state::TransformGizmoState>> {
+        self.base.transform_gizmo()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn ruler(&self) -> Box<dyn draw::Painter<state::RulerState>> {
+        self.base.ruler()
+    }
+
+    fn data(&self) -> &draw::ThemeData {
+        &self.data
+    }
+}
@@ -0,0 +1,636 @@
+use {
+    super::{primer::check_mark_icon, Material, Primer},
+    crate::{
+        base,
+        draw::{self, state, ColorSwatch},
+        error,
+        geom::*,
+    },
+    reclutch::display::{
+        self, Color, DisplayCommand, DisplayListBuilder, Filter, GraphicsDisplay,
+        GraphicsDisplayPaint, GraphicsDisplayStroke, Point, Rect, Size, StyleColor,
+        TextDisplayItem, Vector,
+    },
+};
+
+/// Style of the text fields (i.e. text areas) painted by the Material theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaterialTextField {
+    /// Tinted container with an underline which is highlighted on focus.
+    Filled,
+    /// Transparent container with an outline which is highlighted on focus.
+    Outlined,
+}
+
+impl Default for MaterialTextField {
+    fn default() -> Self {
+        MaterialTextField::Filled
+    }
+}
+
+impl Material {
+    /// Creates an instance of the Material theme, with the standard indigo primary and pink secondary colors.
+    pub fn new(display: &mut dyn GraphicsDisplay) -> Result<Self, error::ThemeError> {
+        Material::with_palette(
+            display,
+            ColorSwatch::from_base(base::color_from_urgba(63, 81, 181, 1.0)),
+            ColorSwatch::from_base(base::color_from_urgba(255, 64, 129, 1.0)),
+            draw::ThemeContrast::Light,
+        )
+    }
+
+    /// Creates an instance of the Material theme from a primary and secondary swatch.
+    ///
+    /// The color scheme is derived from the swatches following the Material color system;
+    /// with `ThemeContrast::Dark`, lighter shades are used over a dark surface.
+    pub fn with_palette(
+        display: &mut dyn GraphicsDisplay,
+        primary: ColorSwatch,
+        secondary: ColorSwatch,
+        contrast: draw::ThemeContrast,
+    ) -> Result<Self, error::ThemeError> {
+        // The typefaces and the painters of the widgets without a Material counterpart are shared with Primer.
+        let base = Primer::new(display)?;
+
+        let mut typography = draw::Theme::data(&base).typography.clone();
+        typography.header.size = 34.0;
+        typography.sub_header.size = 24.0;
+        typography.body.size = 16.0;
+        typography.button.size = 14.0;
+
+        Ok(Material {
+            data: draw::ThemeData {
+                scheme: color_scheme(&primary, contrast),
+                typography,
+                contrast,
+                direction: draw::LayoutDirection::LeftToRight,
+            },
+            base,
+            primary,
+            secondary,
+            text_field: MaterialTextField::Filled,
+        })
+    }
+
+    /// Returns the primary swatch.
+    #[inline]
+    pub fn primary(&self) -> &ColorSwatch {
+        &self.primary
+    }
+
+    /// Returns the secondary (accent) swatch, which fills checked checkboxes.
+    #[inline]
+    pub fn secondary(&self) -> &ColorSwatch {
+        &self.secondary
+    }
+
+    /// Sets the style of text fields painted from now on.
+    pub fn set_text_field(&mut self, text_field: MaterialTextField) {
+        self.text_field = text_field;
+    }
+
+    /// Sets the default layout direction of widgets created from the theme (e.g. right-to-left for Arabic or Hebrew).
+    pub fn set_direction(&mut self, direction: draw::LayoutDirection) {
+        self.data.direction = direction;
+    }
+
+    /// Returns the shade of `swatch` used for accents over the surface of the theme.
+    fn accent(&self, swatch: &ColorSwatch) -> Color {
+        match self.data.contrast {
+            draw::ThemeContrast::Light => swatch.base(),
+            draw::ThemeContrast::Dark => swatch.shade(200),
+        }
+    }
+}
+
+fn color_scheme(primary: &ColorSwatch, contrast: draw::ThemeContrast) -> draw::ColorScheme {
+    let white = base::color_from_urgba(255, 255, 255, 1.0);
+    match contrast {
+        draw::ThemeContrast::Light => draw::ColorScheme {
+            background: white,
+            error: base::color_from_urgba(176, 0, 32, 1.0),
+            focus: draw::with_opacity(primary.base(), 0.3),
+            primary: primary.base(),
+            control_outset: primary.base(),
+            control_inset: white,
+            over_error: white,
+            over_focus: white,
+            over_primary: white,
+            over_control_outset: white,
+            over_control_inset: base::color_from_urgba(0, 0, 0, 0.87),
+            selection: draw::with_opacity(primary.shade(200), 0.5),
+            caret: primary.base(),
+            link: primary.shade(700),
+        },
+        draw::ThemeContrast::Dark => {
+            let black = base::color_from_urgba(0, 0, 0, 0.87);
+            draw::ColorScheme {
+                background: base::color_from_urgba(18, 18, 18, 1.0),
+                error: base::color_from_urgba(207, 102, 121, 1.0),
+                focus: draw::with_opacity(primary.shade(200), 0.3),
+                primary: primary.shade(200),
+                control_outset: primary.shade(200),
+                control_inset: base::color_from_urgba(30, 30, 30, 1.0),
+                over_error: black,
+                over_focus: black,
+                over_primary: black,
+                over_control_outset: black,
+                over_control_inset: base::color_from_urgba(255, 255, 255, 0.87),
+                selection: draw::with_opacity(primary.shade(200), 0.3),
+                caret: primary.shade(200),
+                link: primary.shade(100),
+            }
+        }
+    }
+}
+
+/// Pushes the key and ambient shadows of a surface raised by `elevation` (in density-independent pixels).
+fn push_elevation(builder: &mut DisplayListBuilder, rect: Rect, radii: [f32; 4], elevation: f32) {
+    if elevation <= 0.0 {
+        return;
+    }
+
+    // Key light, casting a shadow below the surface
+    builder.push_round_rectangle(
+        rect.translate(Vector::new(0.0, elevation / 2.0)),
+        radii,
+        GraphicsDisplayPaint::Fill(Color::new(0.0, 0.0, 0.0, 0.2).into()),
+        Some(Filter::Blur(elevation / 2.0, elevation / 2.0)),
+    );
+
+    // Ambient light, casting a soft shadow all around
+    builder.push_round_rectangle(
+        rect,
+        radii,
+        GraphicsDisplayPaint::Fill(Color::new(0.0, 0.0, 0.0, 0.12).into()),
+        Some(Filter::Blur(elevation, elevation)),
+    );
+}
+
+impl draw::Theme for Material {
+    fn button(&self) -> Box<dyn draw::Painter<state::ButtonState>> {
+        Box::new(draw::Styled::new(ButtonPainter))
+    }
+
+    fn label(&self) -> Box<dyn draw::Painter<state::LabelState>> {
+        self.base.label()
+    }
+
+    fn checkbox(&self) -> Box<dyn draw::Painter<state::CheckboxState>> {
+        Box::new(draw::Styled::new(CheckboxPainter { accent: self.accent(&self.secondary) }))
+    }
+
+    fn numeric_input(&self) -> Box<dyn draw::Painter<state::NumericInputState>> {
+        self.base.numeric_input()
+    }
+
+    fn text_area(&self) -> Box<dyn draw::Painter<state::TextAreaState>> {
+        Box::new(draw::Styled::new(TextFieldPainter {
+            variant: self.text_field,
+            inner: self.base.text_area(),
+        }))
+    }
+
+    fn scroll_bar(&self) -> Box<dyn draw::Painter<state::ScrollBarState>> {
+        self.base.scroll_bar()
+    }
+
+    fn tree_view(&self) -> Box<dyn draw::Painter<state::TreeViewState>> {
+        self.base.tree_view()
+    }
+
+    fn list_view(&self) -> Box<dyn draw::Painter<state::ListViewState>> {
+        self.base.list_view()
+    }
+
+    fn tab(&self) -> Box<dyn draw::Painter<state::TabState>> {
+        self.base.tab()
+    }
+
+    fn tour(&self) -> Box<dyn draw::Painter<state::TourState>> {
+        self.base.tour()
+    }
+
+    fn tooltip(&self) -> Box<dyn draw::Painter<state::TooltipState>> {
+        self.base.tooltip()
+    }
+
+    fn blur_panel(&self) -> Box<dyn draw::Painter<state::BlurPanelState>> {
+        self.base.blur_panel()
+    }
+
+    fn card(&self) -> Box<dyn draw::Painter<state::CardState>> {
+        Box::new(draw::Styled::new(CardPainter))
+    }
+
+    fn error_boundary(&self) -> Box<dyn draw::Painter<state::ErrorBoundaryState>> {
+        self.base.error_boundary()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gauge(&self) -> Box<dyn draw::Painter<state::GaugeState>> {
+        self.base.gauge()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn radial_progress(&self) -> Box<dyn draw::Painter<state::RadialProgressState>> {
+        self.base.radial_progress()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn calendar_heatmap(&self) -> Box<dyn draw::Painter<state::CalendarHeatmapState>> {
+        self.base.calendar_heatmap()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gradient_editor(&self) -> Box<dyn draw::Painter<state::GradientEditorState>> {
+        self.base.gradient_editor()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn transform_gizmo(&self) -> Box<dyn draw::Painter<state::TransformGizmoState>> {
+        self.base.transform_gizmo()
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn ruler(&self) -> Box<dyn draw::Painter<state::RulerState>> {
+        self.base.ruler()
+    }
+
+    fn data(&self) -> &draw::ThemeData {
+        &self.data
+    }
+}
+
+/// Contained button, which raises on hover and press, with a ripple spreading from the press position.
+struct ButtonPainter;
+
+impl ButtonPainter {
+    fn make_text_item(&self, state: &state::ButtonState, color: StyleColor) -> TextDisplayItem {
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+        let mut text_item = TextDisplayItem {
+            text: state.data.text.clone(),
+            font: typeface.0,
+            font_info: typeface.1,
+            size: state.data.typeface.size,
+            bottom_left: Default::default(),
+            color,
+        };
+
+        text_item.set_top_left(display::center(
+            text_item.bounds().unwrap().size,
+            state.rect.cast_unit(),
+        ));
+
+        text_item
+    }
+}
+
+impl draw::Painter<state::ButtonState> for ButtonPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::ButtonState>> {
+        theme.button()
+    }
+
+    fn size_hint(&self, state: state::ButtonState) -> Size {
+        let size = self.make_text_item(&state, Color::default().into()).bounds().unwrap().size;
+        Size::new(size.width + 32.0, (size.height + 16.0).max(36.0))
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // account for the elevation shadow
+        rect.inflate(12.0, 12.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::ButtonState) -> Vec<DisplayCommand> {
+        let rect = base::sharp_align(state.rect.cast_unit());
+        let radii = [state.corner_radius.unwrap_or(4.0); 4];
+
+        let mut builder = DisplayListBuilder::new();
+
+        if state.data.disabled {
+            builder.push_round_rectangle(
+                rect,
+                radii,
+                GraphicsDisplayPaint::Fill(draw::with_opacity(state.data.color, 0.12).into()),
+                None,
+            );
+            builder.push_text(
+                self.make_text_item(&state, draw::with_opacity(state.data.color, 0.38).into()),
+                None,
+            );
+            return builder.build();
+        }
+
+        let focused = state.interaction.contains(state::InteractionState::FOCUSED);
+        // A pressed button is always hovered, even if the cursor has since left.
+        let hover = state.hover.max(state.press);
+
+        push_elevation(&mut builder, rect, radii, 2.0 + 2.0 * hover + 4.0 * state.press);
+
+        // Background, with the state overlays of the text color
+        let overlay = 0.08 * hover + if focused { 0.12 } else { 0.0 };
+        builder.push_round_rectangle(
+            rect,
+            radii,
+            GraphicsDisplayPaint::Fill(
+                draw::mix(state.data.background, state.data.color, overlay).into(),
+            ),
+            None,
+        );
+
+        let mut commands = builder.build();
+
+        // Ripple, fading out once the button is released
+        if let Some(ripple) = state.ripple {
+            let opacity = 0.24 * state.press.max(1.0 - ripple.progress);
+            if opacity > 0.0 {
+                let origin: Point = ripple.origin.cast_unit();
+                let reach = [
+                    rect.origin,
+                    Point::new(rect.max_x(), rect.min_y()),
+                    Point::new(rect.min_x(), rect.max_y()),
+                    Point::new(rect.max_x(), rect.max_y()),
+                ]
+                .iter()
+                .map(|corner| (*corner - origin).length())
+                .fold(0.0, f32::max);
+                let radius = reach * ripple.progress;
+
+                let mut builder = DisplayListBuilder::new();
+                builder.push_round_rectangle_clip(rect, radii);
+                builder.push_round_rectangle(
+                    Rect::new(
+                        origin - Vector::new(radius, radius),
+                        Size::new(radius, radius) * 2.0,
+                    ),
+                    [radius; 4],
+                    GraphicsDisplayPaint::Fill(
+                        draw::with_opacity(state.data.color, opacity).into(),
+                    ),
+                    None,
+                );
+
+                commands.push(DisplayCommand::Save);
+                commands.extend(builder.build());
+                commands.push(DisplayCommand::Restore);
+            }
+        }
+
+        let mut label = DisplayListBuilder::new();
+        label.push_text(self.make_text_item(&state, state.data.color.into()), None);
+        commands.extend(label.build());
+
+        commands
+    }
+}
+
+struct CheckboxPainter {
+    accent: Color,
+}
+
+impl draw::Painter<state::CheckboxState> for CheckboxPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::CheckboxState>> {
+        theme.checkbox()
+    }
+
+    fn size_hint(&self, _state: state::CheckboxState) -> Size {
+        Size::new(18.0, 18.0)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // account for the hover halo
+        rect.inflate(11.0, 11.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        RelativeRect::new(rect.origin, Size::new(18.0, 18.0).cast_unit())
+    }
+
+    fn draw(&mut self, state: state::CheckboxState) -> Vec<DisplayCommand> {
+        let rect =
+            base::sharp_align(Rect::new(state.rect.origin.cast_unit(), Size::new(18.0, 18.0)));
+        let radii = [state.corner_radius.unwrap_or(2.0); 4];
+        let (accent, foreground) = if state.data.disabled {
+            (
+                draw::with_opacity(state.data.foreground, 0.38),
+                draw::with_opacity(state.data.foreground, 0.38),
+            )
+        } else {
+            (self.accent, draw::with_opacity(state.data.foreground, 0.54))
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Halo around the box, on hover and focus
+        let focused = state.interaction.contains(state::InteractionState::FOCUSED);
+        let halo = (0.04 * state.hover + 0.08 * state.press).max(if focused { 0.12 } else { 0.0 });
+        if halo > 0.0 && !state.data.disabled {
+            let halo_color = if state.data.checked { accent } else { state.data.foreground };
+            builder.push_round_rectangle(
+                rect.inflate(11.0, 11.0),
+                [20.0; 4],
+                GraphicsDisplayPaint::Fill(draw::with_opacity(halo_color, halo).into()),
+                None,
+            );
+        }
+
+        if state.data.checked {
+            builder.push_round_rectangle(
+                rect,
+                radii,
+                GraphicsDisplayPaint::Fill(accent.into()),
+                None,
+            );
+            builder.push_path(
+                check_mark_icon(deflate(rect, 3.5, 4.0)),
+                false,
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 2.0,
+                    color: state.data.background.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        } else {
+            builder.push_round_rectangle(
+                deflate(rect, 1.0, 1.0),
+                radii,
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 2.0,
+                    color: foreground.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
+
+/// Filled or outlined container around the text of a text area, which is painted by the Primer text area painter.
+struct TextFieldPainter {
+    variant: MaterialTextField,
+    inner: Box<dyn draw::Painter<state::TextAreaState>>,
+}
+
+impl TextFieldPainter {
+    /// Horizontal and vertical padding between the container and the text.
+    const PADDING: (f32, f32) = (12.0, 8.0);
+}
+
+impl draw::Painter<state::TextAreaState> for TextFieldPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::TextAreaState>> {
+        theme.text_area()
+    }
+
+    fn size_hint(&self, state: state::TextAreaState) -> Size {
+        self.inner.size_hint(state)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // the container surrounds the text
+        rect.inflate(Self::PADDING.0 + 1.0, Self::PADDING.1 + 1.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect.inflate(Self::PADDING.0, Self::PADDING.1)
+    }
+
+    fn draw(&mut self, state: state::TextAreaState) -> Vec<DisplayCommand> {
+        let container =
+            base::sharp_align(state.rect.cast_unit().inflate(Self::PADDING.0, Self::PADDING.1));
+        let focused = state.interaction.contains(state::InteractionState::FOCUSED);
+        let hovered = state.interaction.contains(state::InteractionState::HOVERED);
+
+        // The caret color is the primary color of the theme, which also highlights the focused field.
+        let (indicator, thickness) = if state.data.disabled {
+            (draw::with_opacity(state.data.color, 0.12), 1.0)
+        } else if focused {
+            (state.data.cursor_color, 2.0)
+        } else if hovered {
+            (draw::with_opacity(state.data.color, 0.87), 1.0)
+        } else {
+            (draw::with_opacity(state.data.color, 0.42), 1.0)
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        match self.variant {
+            MaterialTextField::Filled => {
+                let tint = if hovered && !focused { 0.09 } else { 0.06 };
+                builder.push_round_rectangle(
+                    container,
+                    [4.0, 4.0, 0.0, 0.0],
+                    GraphicsDisplayPaint::Fill(draw::with_opacity(state.data.color, tint).into()),
+                    None,
+                );
+                builder.push_rectangle(
+                    Rect::new(
+                        Point::new(container.min_x(), container.max_y() - thickness),
+                        Size::new(container.size.width, thickness),
+                    ),
+                    GraphicsDisplayPaint::Fill(indicator.into()),
+                    None,
+                );
+            }
+            MaterialTextField::Outlined => {
+                builder.push_round_rectangle(
+                    container,
+                    [4.0; 4],
+                    GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                        thickness,
+                        color: indicator.into(),
+                        ..Default::default()
+                    }),
+                    None,
+                );
+            }
+        }
+
+        let mut commands = builder.build();
+        commands.extend(self.inner.draw(state));
+        commands
+    }
+}
+
+/// Card raised by its elevation; cards without elevation are outlined instead.
+struct CardPainter;
+
+impl draw::Painter<state::CardState> for CardPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::CardState>> {
+        theme.card()
+    }
+
+    fn size_hint(&self, state: state::CardState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::CardState) -> Vec<DisplayCommand> {
+        let rect = base::sharp_align(state.rect.cast_unit());
+        let radius = state.data.corner_radius;
+        let divider = GraphicsDisplayStroke {
+            thickness: 1.0,
+            color: draw::with_opacity(state.data.border, 0.5).into(),
+            ..Default::default()
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        push_elevation(&mut builder, rect, [radius; 4], state.data.elevation);
+
+        builder.push_round_rectangle(
+            rect,
+            [radius; 4],
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        if let Some(header) = state.header {
+            let header = base::sharp_align(header.cast_unit());
+            builder.push_line(
+                Point::new(header.min_x(), header.max_y()),
+                Point::new(header.max_x(), header.max_y()),
+                divider.clone(),
+                None,
+            );
+        }
+
+        if let Some(footer) = state.footer {
+            let footer = base::sharp_align(footer.cast_unit());
+            builder.push_line(
+                footer.origin,
+                Point::new(footer.max_x(), footer.min_y()),
+                divider.clone(),
+                None,
+            );
+        }
+
+        if state.data.elevation <= 0.0 {
+            builder.push_round_rectangle(
+                rect,
+                [radius; 4],
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 1.0,
+                    color: state.data.border.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
@@ -1,16 +1,43 @@
 //! A collection of various themes to quickly get up and running with Thunderclap.
 
-use crate::draw::ThemeData;
+use crate::draw::{ColorSwatch, ThemeData};
 
+#[cfg(feature = "dynamic-themes")]
 mod dynamic;
+mod material;
 mod primer;
 
+#[cfg(feature = "dynamic-themes")]
+pub use dynamic::{
+    ColorDescription, DynamicWatcher, ModifiedTimeNotifier, SchemeDescription, TextSizes,
+    ThemeDescription, ThemeNotifier,
+};
+pub use material::MaterialTextField;
+
 /// GitHub's "Primer" theme, based off the CSS widgets.
 pub struct Primer {
     data: ThemeData,
 }
 
-/// Theme generated from a RON (Rusty Object Notation) file.
+/// Google's "Material Design" theme, with elevation shadows, ripple feedback on buttons and
+/// filled or outlined text fields.
+///
+/// The color scheme is built from a primary and secondary [`ColorSwatch`](../draw/struct.ColorSwatch.html).
+/// Widgets without a Material counterpart are painted as in `Primer`.
+pub struct Material {
+    base: Primer,
+    data: ThemeData,
+    primary: ColorSwatch,
+    secondary: ColorSwatch,
+    text_field: MaterialTextField,
+}
+
+/// Theme generated from a RON (Rusty Object Notation) file, described by a [`ThemeDescription`](struct.ThemeDescription.html).
+///
+/// The file describes the color scheme, contrast, layout direction and text sizes,
+/// whereas the typeface and painters are those of `Primer`.
+#[cfg(feature = "dynamic-themes")]
 pub struct Dynamic {
-    //data: ThemeData,
+    base: Primer,
+    data: ThemeData,
 }
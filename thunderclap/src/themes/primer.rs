@@ -5,16 +5,18 @@ use {
         draw::{self, state},
         error,
         geom::*,
+        ui,
     },
     reclutch::display::{
-        self, Color, DisplayCommand, DisplayListBuilder, Filter, FontInfo, Gradient,
-        GraphicsDisplay, GraphicsDisplayPaint, GraphicsDisplayStroke, Rect, ResourceData,
-        ResourceDescriptor, ResourceReference, SharedData, Size, StyleColor, TextDisplayItem,
-        Vector, VectorPath, VectorPathBuilder,
+        self, Color, DisplayCommand, DisplayListBuilder, Filter, FontInfo, GraphicsDisplay,
+        GraphicsDisplayPaint, GraphicsDisplayStroke, Point, Rect, ResourceData, ResourceDescriptor,
+        ResourceReference, SharedData, Size, StyleColor, TextDisplayItem, Vector, VectorPath,
+        VectorPathBuilder,
     },
+    std::f32::consts::PI,
 };
 
-fn check_mark_icon(rect: Rect) -> VectorPath {
+pub(super) fn check_mark_icon(rect: Rect) -> VectorPath {
     let mut builder = VectorPathBuilder::new();
 
     // start at top-right
@@ -27,6 +29,26 @@ fn check_mark_icon(rect: Rect) -> VectorPath {
     builder.build()
 }
 
+/// Approximates a circular arc with line segments.
+/// Angles are in radians, measured clockwise from the positive X axis.
+fn arc_path(center: Point, radius: f32, start_angle: f32, end_angle: f32) -> VectorPath {
+    let mut builder = VectorPathBuilder::new();
+
+    // roughly one segment every 4 degrees, which is indistinguishable from a true arc at UI sizes
+    let segments = (((end_angle - start_angle).abs() / (PI / 45.0)).ceil() as usize).max(1);
+    for i in 0..=segments {
+        let angle = start_angle + (end_angle - start_angle) * (i as f32 / segments as f32);
+        let point = center + Vector::new(angle.cos(), angle.sin()) * radius;
+        if i == 0 {
+            builder.move_to(point);
+        } else {
+            builder.line_to(point);
+        }
+    }
+
+    builder.build()
+}
+
 impl Primer {
     /// Creates an instance of the GitHub Primer theme.
     pub fn new(display: &mut dyn GraphicsDisplay) -> Result<Self, error::ThemeError> {
@@ -72,6 +94,9 @@ impl Primer {
                     over_primary: base::color_from_urgba(255, 255, 255, 1.0),
                     over_control_outset: base::color_from_urgba(36, 41, 46, 1.0),
                     over_control_inset: base::color_from_urgba(36, 41, 46, 1.0),
+                    selection: base::color_from_urgba(3, 102, 214, 0.25),
+                    caret: base::color_from_urgba(36, 41, 46, 1.0),
+                    link: base::color_from_urgba(3, 102, 214, 1.0),
                 },
                 typography: draw::Typography {
                     header: draw::TypefaceStyle {
@@ -96,28 +121,104 @@ impl Primer {
                     },
                 },
                 contrast: draw::ThemeContrast::Light,
+                direction: draw::LayoutDirection::LeftToRight,
             },
         })
     }
+
+    /// Sets the default layout direction of widgets created from the theme (e.g. right-to-left for Arabic or Hebrew).
+    pub fn set_direction(&mut self, direction: draw::LayoutDirection) {
+        self.data.direction = direction;
+    }
 }
 
 impl draw::Theme for Primer {
     fn button(&self) -> Box<dyn draw::Painter<state::ButtonState>> {
-        Box::new(ButtonPainter)
+        Box::new(draw::Styled::new(ButtonPainter))
+    }
+
+    fn label(&self) -> Box<dyn draw::Painter<state::LabelState>> {
+        Box::new(LabelPainter)
     }
 
     fn checkbox(&self) -> Box<dyn draw::Painter<state::CheckboxState>> {
-        Box::new(CheckboxPainter)
+        Box::new(draw::Styled::new(CheckboxPainter))
+    }
+
+    fn numeric_input(&self) -> Box<dyn draw::Painter<state::NumericInputState>> {
+        Box::new(draw::Styled::new(NumericInputPainter))
     }
 
     fn text_area(&self) -> Box<dyn draw::Painter<state::TextAreaState>> {
-        Box::new(TextAreaPainter)
+        Box::new(draw::Styled::new(TextAreaPainter))
     }
 
     fn scroll_bar(&self) -> Box<dyn draw::Painter<state::ScrollBarState>> {
         Box::new(ScrollBarPainter)
     }
 
+    fn tree_view(&self) -> Box<dyn draw::Painter<state::TreeViewState>> {
+        Box::new(TreeViewPainter)
+    }
+
+    fn list_view(&self) -> Box<dyn draw::Painter<state::ListViewState>> {
+        Box::new(ListViewPainter)
+    }
+
+    fn tab(&self) -> Box<dyn draw::Painter<state::TabState>> {
+        Box::new(TabPainter)
+    }
+
+    fn tour(&self) -> Box<dyn draw::Painter<state::TourState>> {
+        Box::new(TourPainter)
+    }
+
+    fn tooltip(&self) -> Box<dyn draw::Painter<state::TooltipState>> {
+        Box::new(TooltipPainter)
+    }
+
+    fn blur_panel(&self) -> Box<dyn draw::Painter<state::BlurPanelState>> {
+        Box::new(BlurPanelPainter)
+    }
+
+    fn card(&self) -> Box<dyn draw::Painter<state::CardState>> {
+        Box::new(draw::Styled::new(CardPainter))
+    }
+
+    fn error_boundary(&self) -> Box<dyn draw::Painter<state::ErrorBoundaryState>> {
+        Box::new(ErrorBoundaryPainter)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gauge(&self) -> Box<dyn draw::Painter<state::GaugeState>> {
+        Box::new(GaugePainter)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn radial_progress(&self) -> Box<dyn draw::Painter<state::RadialProgressState>> {
+        Box::new(RadialProgressPainter)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn calendar_heatmap(&self) -> Box<dyn draw::Painter<state::CalendarHeatmapState>> {
+        Box::new(CalendarHeatmapPainter)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn gradient_editor(&self) -> Box<dyn draw::Painter<state::GradientEditorState>> {
+        Box::new(GradientEditorPainter)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn transform_gizmo(&self) -> Box<dyn draw::Painter<state::TransformGizmoState>> {
+        Box::new(TransformGizmoPainter)
+    }
+
+    #[cfg(feature = "extra-widgets")]
+    fn ruler(&self) -> Box<dyn draw::Painter<state::RulerState>> {
+        Box::new(RulerPainter)
+    }
+
     fn data(&self) -> &draw::ThemeData {
         &self.data
     }
@@ -175,6 +276,7 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
     }
 
     fn draw(&mut self, state: state::ButtonState) -> Vec<DisplayCommand> {
+        let radii = [state.corner_radius.unwrap_or(3.5); 4];
         let (background, border, text, focus) = if state.data.disabled {
             (
                 draw::strengthen(state.data.background, 0.2, state.data.contrast).into(),
@@ -182,41 +284,37 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
                 draw::weaken(state.data.color, 0.4, state.data.contrast).into(),
                 state.data.focus.into(),
             )
-        } else if state.interaction.contains(state::InteractionState::PRESSED) {
-            let background = draw::strengthen(state.data.background, 0.2, state.data.contrast);
-            (
-                background.into(),
-                draw::weaken(state.data.color, 0.3, state.data.contrast).into(),
-                state.data.color.into(),
-                state.data.focus.into(),
-            )
-        } else if state.interaction.contains(state::InteractionState::HOVERED) {
-            let background = draw::strengthen(state.data.background, 0.1, state.data.contrast);
-
-            (
-                StyleColor::LinearGradient(Gradient {
-                    start: state.rect.origin.cast_unit(),
-                    end: state.rect.origin.cast_unit() + Size::new(0.0, state.rect.size.height),
-                    stops: vec![
-                        (0.0, draw::lighten(background, 0.1)),
-                        (0.9, draw::darken(background, 0.1)),
-                    ],
-                }),
-                draw::weaken(state.data.color, 0.3, state.data.contrast).into(),
-                state.data.color.into(),
-                state.data.focus.into(),
-            )
         } else {
+            let contrast = state.data.contrast;
+            // A pressed button is always hovered, even if the cursor has since left.
+            let hover = state.hover.max(state.press);
+            // The background fades in on hover, then darkens and flattens as the press feedback progresses.
+            let background = draw::mix(
+                draw::mix(
+                    state.data.background,
+                    draw::strengthen(state.data.background, 0.1, contrast),
+                    hover,
+                ),
+                draw::strengthen(state.data.background, 0.2, contrast),
+                state.press,
+            );
+            let spread = 0.1 * (1.0 - state.press);
+
             (
-                StyleColor::LinearGradient(Gradient {
-                    start: state.rect.origin.cast_unit(),
-                    end: state.rect.origin.cast_unit() + Size::new(0.0, state.rect.size.height),
-                    stops: vec![
-                        (0.0, draw::lighten(state.data.background, 0.1)),
-                        (0.9, draw::darken(state.data.background, 0.1)),
+                draw::linear_gradient(
+                    state.rect.origin.cast_unit(),
+                    state.rect.origin.cast_unit() + Size::new(0.0, state.rect.size.height),
+                    vec![
+                        (0.0, draw::lighten(background, spread)),
+                        (0.9, draw::darken(background, spread)),
                     ],
-                }),
-                draw::weaken(state.data.color, 0.4, state.data.contrast).into(),
+                ),
+                draw::mix(
+                    draw::weaken(state.data.color, 0.4, contrast),
+                    draw::weaken(state.data.color, 0.3, contrast),
+                    hover,
+                )
+                .into(),
                 state.data.color.into(),
                 state.data.focus.into(),
             )
@@ -229,7 +327,7 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
         // Background
         builder.push_round_rectangle(
             base::sharp_align(state.rect.cast_unit()),
-            [3.5; 4],
+            radii,
             GraphicsDisplayPaint::Fill(background),
             None,
         );
@@ -237,7 +335,7 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
         // Border
         builder.push_round_rectangle(
             base::sharp_align(state.rect.cast_unit()),
-            [3.5; 4],
+            radii,
             GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
                 thickness: 1.0 / 3.0,
                 color: border,
@@ -255,7 +353,7 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
         {
             builder.push_round_rectangle(
                 base::sharp_align(state.rect.cast_unit()).inflate(1.5, 1.5),
-                [3.5; 4],
+                radii,
                 GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
                     thickness: 3.5,
                     color: focus,
@@ -266,14 +364,14 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
         }
 
         // Pressed inset shadow
-        if state.interaction.contains(state::InteractionState::PRESSED) {
-            builder.push_round_rectangle_clip(base::sharp_align(state.rect.cast_unit()), [3.5; 4]);
+        if state.press > 0.0 {
+            builder.push_round_rectangle_clip(base::sharp_align(state.rect.cast_unit()), radii);
             builder.push_round_rectangle(
                 state.rect.cast_unit().inflate(10.0, 10.0).translate(Vector::new(0.0, 7.0)),
                 [10.0; 4],
                 GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
                     thickness: 10.0,
-                    color: Color::new(0.0, 0.0, 0.0, 0.2).into(),
+                    color: Color::new(0.0, 0.0, 0.0, 0.2 * state.press).into(),
                     ..Default::default()
                 }),
                 Some(Filter::Blur(3.0, 3.0)),
@@ -284,6 +382,51 @@ impl draw::Painter<state::ButtonState> for ButtonPainter {
     }
 }
 
+struct LabelPainter;
+
+impl draw::Painter<state::LabelState> for LabelPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::LabelState>> {
+        theme.label()
+    }
+
+    fn size_hint(&self, state: state::LabelState) -> Size {
+        // The label sizes itself to the bounds of its text.
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::LabelState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+        let fade_out = state.data.overflow == ui::OverflowBehavior::FadeOut;
+        let fade_width = (state.data.typeface.size * 2.0).min(rect.size.width);
+
+        let mut builder = DisplayListBuilder::new();
+        builder.push_rectangle_clip(rect, true);
+        for mut run in state.runs {
+            let overflows =
+                run.bounds().map(|bounds| bounds.max_x() > rect.max_x()).unwrap_or(false);
+            if fade_out && overflows {
+                if let StyleColor::Color(color) = run.color {
+                    run.color = draw::linear_gradient(
+                        Point::new(rect.max_x() - fade_width, rect.origin.y),
+                        Point::new(rect.max_x(), rect.origin.y),
+                        vec![(0.0, color), (1.0, draw::with_opacity(color, 0.0))],
+                    );
+                }
+            }
+            builder.push_text(run, None);
+        }
+        builder.build()
+    }
+}
+
 struct CheckboxPainter;
 
 impl draw::Painter<state::CheckboxState> for CheckboxPainter {
@@ -306,6 +449,7 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
     fn draw(&mut self, mut state: state::CheckboxState) -> Vec<DisplayCommand> {
         state.rect.size = Size::new(20.0, 20.0).cast_unit();
         state.rect = base::sharp_align(state.rect.cast_unit()).cast_unit();
+        let radii = [state.corner_radius.unwrap_or(3.5); 4];
 
         let (background, foreground, border, focus) = if state.data.checked {
             (
@@ -314,27 +458,24 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
                 draw::weaken(state.data.foreground, 0.4, state.data.contrast).into(),
                 state.data.focus.into(),
             )
-        } else if state.interaction.contains(state::InteractionState::HOVERED) {
-            (
-                draw::strengthen(state.data.background, 0.05, state.data.contrast),
-                base::color_from_urgba(0, 0, 0, 0.0).into(),
-                draw::weaken(state.data.foreground, 0.4, state.data.contrast).into(),
-                state.data.focus.into(),
-            )
         } else {
             (
-                state.data.background,
+                draw::mix(
+                    state.data.background,
+                    draw::strengthen(state.data.background, 0.05, state.data.contrast),
+                    state.hover,
+                ),
                 base::color_from_urgba(0, 0, 0, 0.0).into(),
                 draw::weaken(state.data.foreground, 0.4, state.data.contrast).into(),
                 state.data.focus.into(),
             )
         };
 
-        let background = if state.interaction.contains(state::InteractionState::PRESSED) {
-            draw::strengthen(background, 0.2, state.data.contrast)
-        } else {
-            background
-        }
+        let background = draw::mix(
+            background,
+            draw::strengthen(background, 0.2, state.data.contrast),
+            state.press,
+        )
         .into();
 
         let mut builder = DisplayListBuilder::new();
@@ -342,7 +483,7 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
         // Background
         builder.push_round_rectangle(
             state.rect.cast_unit(),
-            [3.5; 4],
+            radii,
             GraphicsDisplayPaint::Fill(background),
             None,
         );
@@ -350,7 +491,7 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
         // Border
         builder.push_round_rectangle(
             state.rect.cast_unit(),
-            [3.5; 4],
+            radii,
             GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
                 thickness: 1.0 / 3.0,
                 color: border,
@@ -361,7 +502,7 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
 
         // Foreground (check mark)
         builder.push_path(
-            check_mark_icon(state.rect.cast_unit().inflate(-4.0, -4.0)),
+            check_mark_icon(deflate(state.rect.cast_unit(), 4.0, 4.0)),
             false,
             GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
                 thickness: 2.5,
@@ -377,7 +518,7 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
         {
             builder.push_round_rectangle(
                 state.rect.cast_unit().inflate(1.5, 1.5),
-                [3.5; 4],
+                radii,
                 GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
                     thickness: 3.5,
                     color: focus,
@@ -391,6 +532,153 @@ impl draw::Painter<state::CheckboxState> for CheckboxPainter {
     }
 }
 
+struct NumericInputPainter;
+
+impl NumericInputPainter {
+    fn make_text_item(
+        &self,
+        state: &state::NumericInputState,
+        text: String,
+        color: StyleColor,
+    ) -> TextDisplayItem {
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+        let mut text_item = TextDisplayItem {
+            text: text.into(),
+            font: typeface.0,
+            font_info: typeface.1,
+            size: state.data.typeface.size,
+            bottom_left: Default::default(),
+            color,
+        };
+
+        text_item.set_top_left(display::center(
+            text_item.bounds().unwrap().size,
+            state.rect.cast_unit(),
+        ));
+
+        text_item
+    }
+}
+
+impl draw::Painter<state::NumericInputState> for NumericInputPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::NumericInputState>> {
+        theme.numeric_input()
+    }
+
+    fn size_hint(&self, state: state::NumericInputState) -> Size {
+        // fit the widest of the bounds so that the field doesn't resize as the value changes
+        let widest = |value: f32| {
+            let text = format!("{:.*}", state.data.precision, value);
+            self.make_text_item(&state, text, Color::default().into()).bounds().unwrap()
+        };
+        let (min, max) = (widest(state.data.min), widest(state.data.max));
+        let bounds = if min.size.width > max.size.width { min } else { max };
+
+        Size::new((bounds.size.width + 20.0).max(60.0), bounds.size.height + 10.0)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // account for focus border
+        rect.inflate(3.25, 3.25)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::NumericInputState) -> Vec<DisplayCommand> {
+        let radii = [state.corner_radius.unwrap_or(3.5); 4];
+        let rect = base::sharp_align(state.rect.cast_unit());
+
+        let (background, text) = if state.data.disabled {
+            (
+                draw::strengthen(state.data.background, 0.1, state.data.contrast),
+                draw::weaken(state.data.color, 0.4, state.data.contrast),
+            )
+        } else if state.interaction.contains(state::InteractionState::HOVERED) || state.scrubbing {
+            (draw::strengthen(state.data.background, 0.05, state.data.contrast), state.data.color)
+        } else {
+            (state.data.background, state.data.color)
+        };
+
+        let border = if state.scrubbing {
+            state.data.focus
+        } else {
+            draw::weaken(state.data.color, 0.4, state.data.contrast)
+        };
+
+        let range = state.data.max - state.data.min;
+        let progress = if range > 0.0 {
+            ((state.data.value - state.data.min) / range).max(0.0).min(1.0)
+        } else {
+            0.0
+        };
+
+        let text_item = self.make_text_item(&state, state.data.display_text(), text.into());
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Background
+        builder.push_round_rectangle(
+            rect,
+            radii,
+            GraphicsDisplayPaint::Fill(background.into()),
+            None,
+        );
+
+        // Border
+        builder.push_round_rectangle(
+            rect,
+            radii,
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: 1.0 / 3.0,
+                color: border.into(),
+                ..Default::default()
+            }),
+            None,
+        );
+
+        // Focus rect
+        if state.interaction.contains(state::InteractionState::FOCUSED) && !state.scrubbing {
+            builder.push_round_rectangle(
+                rect.inflate(1.5, 1.5),
+                radii,
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 3.5,
+                    color: state.data.focus.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.push_round_rectangle_clip(rect, radii);
+
+        // Value, relative to the bounds
+        if progress > 0.0 {
+            builder.push_rectangle(
+                Rect::new(rect.origin, Size::new(rect.size.width * progress, rect.size.height)),
+                GraphicsDisplayPaint::Fill(draw::with_opacity(state.data.focus, 0.2).into()),
+                None,
+            );
+        }
+
+        // Text
+        builder.push_text(text_item, None);
+
+        builder.build()
+    }
+}
+
+/// Returns the horizontal position after the first `offset` bytes of a text item, given the left of the text item.
+fn text_x(text_item: &TextDisplayItem, left: f32, offset: usize) -> f32 {
+    if offset == 0 {
+        left
+    } else {
+        text_item.limited_bounds(offset).map(|bounds| bounds.max_x()).unwrap_or(left)
+    }
+}
+
 struct TextAreaPainter;
 
 impl TextAreaPainter {
@@ -415,6 +703,77 @@ impl TextAreaPainter {
 
         text_item
     }
+
+    /// Draws the visible lines of a multi-line text area.
+    fn draw_lines(&self, state: state::TextAreaState) -> Vec<DisplayCommand> {
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+        let focused = state.interaction.contains(state::InteractionState::FOCUSED);
+        let selection = ui::selection_range(state.data.anchor, state.data.cursor);
+
+        let mut builder = DisplayListBuilder::new();
+        builder.push_rectangle_clip(state.rect.cast_unit(), true);
+
+        for (i, line) in state.lines.iter().enumerate() {
+            let rect = line.rect.translate(Vector::new(0.0, -state.scroll).cast_unit());
+            if rect.max_y() < state.rect.origin.y || rect.origin.y > state.rect.max_y() {
+                continue;
+            }
+
+            let mut text_item = TextDisplayItem {
+                text: state.data.text[line.range.clone()].to_string().into(),
+                font: typeface.0.clone(),
+                font_info: typeface.1.clone(),
+                size: state.data.typeface.size,
+                bottom_left: Default::default(),
+                color: state.data.color.into(),
+            };
+            text_item.set_top_left(rect.origin.cast_unit());
+
+            // Selection highlight of the part of the selection on this line
+            if let Some(selection) = &selection {
+                let start = selection.start.max(line.range.start);
+                let end = selection.end.min(line.range.end);
+                if start < end {
+                    let x_at =
+                        |index: usize| text_x(&text_item, rect.origin.x, index - line.range.start);
+                    builder.push_rectangle(
+                        Rect::new(
+                            Point::new(x_at(start), rect.origin.y),
+                            Size::new(x_at(end) - x_at(start), rect.size.height),
+                        ),
+                        GraphicsDisplayPaint::Fill(state.data.selection_color.into()),
+                        None,
+                    );
+                }
+            }
+
+            if focused && i == state.cursor_line {
+                let offset = state.data.cursor - line.range.start;
+                let x = if offset == 0 {
+                    rect.origin.x
+                } else {
+                    text_item.limited_bounds(offset).unwrap().max_x()
+                };
+
+                builder.push_line(
+                    Point::new(x + 1.0, rect.origin.y),
+                    Point::new(x + 1.0, rect.max_y()),
+                    GraphicsDisplayStroke {
+                        thickness: 1.0,
+                        color: state.data.cursor_color.into(),
+                        ..Default::default()
+                    },
+                    None,
+                );
+            }
+
+            if !line.range.is_empty() {
+                builder.push_text(text_item, None);
+            }
+        }
+
+        builder.build()
+    }
 }
 
 impl draw::Painter<state::TextAreaState> for TextAreaPainter {
@@ -439,6 +798,10 @@ impl draw::Painter<state::TextAreaState> for TextAreaPainter {
     }
 
     fn draw(&mut self, state: state::TextAreaState) -> Vec<DisplayCommand> {
+        if !state.lines.is_empty() && !state.data.text.is_empty() {
+            return self.draw_lines(state);
+        }
+
         let text = if state.data.text.is_empty() {
             state.data.placeholder_color
         } else {
@@ -459,6 +822,26 @@ impl draw::Painter<state::TextAreaState> for TextAreaPainter {
 
         builder.push_rectangle_clip(state.rect.cast_unit(), true);
 
+        // Selection highlight
+        if let Some(selection) = ui::selection_range(state.data.anchor, state.data.cursor)
+            .filter(|_| !state.data.text.is_empty())
+        {
+            let bounds = text_item.bounds().unwrap();
+            let origin = state.rect.origin.x;
+            let (start, end) = (
+                text_x(&text_item, origin, selection.start),
+                text_x(&text_item, origin, selection.end),
+            );
+            builder.push_rectangle(
+                Rect::new(
+                    Point::new(start, bounds.origin.y),
+                    Size::new(end - start, bounds.size.height),
+                ),
+                GraphicsDisplayPaint::Fill(state.data.selection_color.into()),
+                None,
+            );
+        }
+
         if let Some((a, b)) = cursor {
             builder.push_line(
                 a + Size::new(1.0, 0.0),
@@ -506,8 +889,10 @@ impl draw::Painter<state::ScrollBarState> for ScrollBarPainter {
         } else {
             state.data.foreground
         };
+        let foreground = draw::with_opacity(foreground, foreground.alpha * state.opacity);
 
         let border = draw::weaken(state.data.foreground, 0.4, state.data.contrast);
+        let border = draw::with_opacity(border, border.alpha * state.opacity);
 
         let mut builder = DisplayListBuilder::new();
 
@@ -522,7 +907,9 @@ impl draw::Painter<state::ScrollBarState> for ScrollBarPainter {
         builder.push_round_rectangle(
             state.rect.cast_unit(),
             [3.5; 4],
-            GraphicsDisplayPaint::Fill(draw::with_opacity(state.data.background, 0.75).into()),
+            GraphicsDisplayPaint::Fill(
+                draw::with_opacity(state.data.background, 0.75 * state.opacity).into(),
+            ),
             None,
         );
 
@@ -549,3 +936,1524 @@ impl draw::Painter<state::ScrollBarState> for ScrollBarPainter {
         builder.build()
     }
 }
+
+/// Angle at which the gauge arc begins (bottom-left).
+#[cfg(feature = "extra-widgets")]
+const GAUGE_START_ANGLE: f32 = 0.75 * PI;
+/// Angle covered by the gauge arc.
+#[cfg(feature = "extra-widgets")]
+const GAUGE_SWEEP: f32 = 1.5 * PI;
+
+struct TreeViewPainter;
+
+impl draw::Painter<state::TreeViewState> for TreeViewPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::TreeViewState>> {
+        theme.tree_view()
+    }
+
+    fn size_hint(&self, state: state::TreeViewState) -> Size {
+        Size::new(200.0, state.data.rows().len().max(1) as f32 * state.data.row_height)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect.inflate(3.0, 3.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::TreeViewState) -> Vec<DisplayCommand> {
+        let rect: Rect = base::sharp_align(state.rect.cast_unit());
+        let direction = state.data.direction;
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+        let foreground = if state.data.disabled {
+            draw::weaken(state.data.foreground, 0.5, state.data.contrast)
+        } else {
+            state.data.foreground
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_rectangle(
+            rect,
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        // Focus rect; drawn outside the rows so the highlights never cover it
+        if state.interaction.contains(state::InteractionState::FOCUSED) {
+            builder.push_rectangle(
+                rect.inflate(1.5, 1.5),
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 1.5,
+                    color: state.data.focus.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.push_rectangle_clip(rect, true);
+
+        for (i, row) in state.data.rows().into_iter().enumerate() {
+            let row_rect = Rect::new(
+                Point::new(rect.origin.x, rect.origin.y + i as f32 * state.data.row_height),
+                Size::new(rect.size.width, state.data.row_height),
+            );
+
+            // Row highlight
+            if state.data.selected.as_ref() == Some(&row.path) {
+                builder.push_rectangle(
+                    row_rect,
+                    GraphicsDisplayPaint::Fill(state.data.selection.into()),
+                    None,
+                );
+            } else if state.hovered == Some(i) && !state.data.disabled {
+                builder.push_rectangle(
+                    row_rect,
+                    GraphicsDisplayPaint::Fill(
+                        draw::strengthen(state.data.background, 0.05, state.data.contrast).into(),
+                    ),
+                    None,
+                );
+            }
+
+            let indent = rect.origin.x + row.depth as f32 * state.data.indent;
+            let middle = row_rect.center().y;
+            // Mirrors horizontal offsets for right-to-left layouts.
+            let sign = if direction.is_rtl() { -1.0 } else { 1.0 };
+
+            // Disclosure triangle; pointing towards the label when collapsed and down when expanded.
+            if row.expandable {
+                let center =
+                    Point::new(direction.mirror_x(indent + state.data.indent / 2.0, rect), middle);
+                let mut path = VectorPathBuilder::new();
+                if row.expanded {
+                    path.move_to(center + Vector::new(-4.0, -2.0));
+                    path.line_to(center + Vector::new(4.0, -2.0));
+                    path.line_to(center + Vector::new(0.0, 3.0));
+                } else {
+                    path.move_to(center + Vector::new(-2.0 * sign, -4.0));
+                    path.line_to(center + Vector::new(3.0 * sign, 0.0));
+                    path.line_to(center + Vector::new(-2.0 * sign, 4.0));
+                }
+                path.close();
+
+                builder.push_path(
+                    path.build(),
+                    true,
+                    GraphicsDisplayPaint::Fill(
+                        draw::weaken(foreground, 0.3, state.data.contrast).into(),
+                    ),
+                    None,
+                );
+            }
+
+            // Label
+            let mut text_item = TextDisplayItem {
+                text: row.label.into(),
+                font: typeface.0.clone(),
+                font_info: typeface.1.clone(),
+                size: state.data.typeface.size,
+                bottom_left: Default::default(),
+                color: foreground.into(),
+            };
+            let text_size = text_item.bounds().unwrap().size;
+            let text_start = direction.mirror_x(indent + state.data.indent + 4.0, rect);
+            text_item.set_top_left(Point::new(
+                if direction.is_rtl() { text_start - text_size.width } else { text_start },
+                middle - text_size.height / 2.0,
+            ));
+            builder.push_text(text_item, None);
+        }
+
+        builder.build()
+    }
+}
+
+struct ListViewPainter;
+
+impl draw::Painter<state::ListViewState> for ListViewPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::ListViewState>> {
+        theme.list_view()
+    }
+
+    fn size_hint(&self, state: state::ListViewState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::ListViewState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_rectangle(
+            rect,
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        builder.push_rectangle_clip(rect, true);
+
+        if let Some(hovered) = state.hovered.filter(|_| !state.data.disabled) {
+            if Some(hovered) != state.selected {
+                builder.push_rectangle(
+                    hovered.cast_unit(),
+                    GraphicsDisplayPaint::Fill(
+                        draw::strengthen(state.data.background, 0.05, state.data.contrast).into(),
+                    ),
+                    None,
+                );
+            }
+        }
+
+        if let Some(selected) = state.selected {
+            builder.push_rectangle(
+                selected.cast_unit(),
+                GraphicsDisplayPaint::Fill(state.data.selection.into()),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
+
+struct TabPainter;
+
+impl draw::Painter<state::TabState> for TabPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::TabState>> {
+        theme.tab()
+    }
+
+    fn size_hint(&self, state: state::TabState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::TabState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+        let strip = Rect::new(
+            rect.origin,
+            Size::new(rect.size.width, state.data.tab_height.min(rect.size.height)),
+        );
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_rectangle(
+            strip,
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        // Border between the strip and the content area
+        builder.push_line(
+            Point::new(strip.min_x(), strip.max_y() - 0.5),
+            Point::new(strip.max_x(), strip.max_y() - 0.5),
+            GraphicsDisplayStroke {
+                thickness: 1.0,
+                color: draw::strengthen(state.data.background, 0.1, state.data.contrast).into(),
+                ..Default::default()
+            },
+            None,
+        );
+
+        let mut commands = builder.build();
+
+        for (i, tab) in state.data.tabs.iter().enumerate() {
+            let tab_rect: Rect = base::sharp_align(state.data.tab_rect(state.rect, i).cast_unit());
+            let active = i == state.data.selected;
+            let disabled = state.data.is_tab_disabled(i);
+            let hovered = state.hovered == Some(i) && !disabled;
+
+            let background = if active {
+                state.data.active
+            } else if hovered {
+                draw::strengthen(state.data.background, 0.05, state.data.contrast)
+            } else {
+                state.data.background
+            };
+            let foreground = if disabled {
+                draw::weaken(state.data.foreground, 0.5, state.data.contrast)
+            } else if active {
+                state.data.foreground
+            } else {
+                draw::weaken(state.data.foreground, 0.2, state.data.contrast)
+            };
+
+            let mut builder = DisplayListBuilder::new();
+            builder.push_rectangle_clip(tab_rect, true);
+
+            builder.push_rectangle(tab_rect, GraphicsDisplayPaint::Fill(background.into()), None);
+
+            // Indicator underneath the selected tab
+            if active {
+                builder.push_rectangle(
+                    Rect::new(
+                        Point::new(tab_rect.origin.x, tab_rect.max_y() - 2.0),
+                        Size::new(tab_rect.size.width, 2.0),
+                    ),
+                    GraphicsDisplayPaint::Fill(state.data.indicator.into()),
+                    None,
+                );
+            }
+
+            // Label; clipped so that it doesn't run underneath the close button
+            let close: Rect = state.data.close_rect(tab_rect.cast_unit()).cast_unit();
+            let label_end = if tab.closable { close.origin.x - 4.0 } else { tab_rect.max_x() };
+            let mut label = DisplayListBuilder::new();
+            label.push_rectangle_clip(
+                Rect::new(
+                    tab_rect.origin,
+                    Size::new((label_end - tab_rect.origin.x).max(0.0), tab_rect.size.height),
+                ),
+                true,
+            );
+            let mut text_item = TextDisplayItem {
+                text: tab.label.clone().into(),
+                font: typeface.0.clone(),
+                font_info: typeface.1.clone(),
+                size: state.data.typeface.size,
+                bottom_left: Default::default(),
+                color: foreground.into(),
+            };
+            let text_height = text_item.bounds().unwrap().size.height;
+            text_item.set_top_left(Point::new(
+                tab_rect.origin.x + state.data.padding,
+                tab_rect.center().y - text_height / 2.0,
+            ));
+            label.push_text(text_item, None);
+
+            // Close button; a cross, with a circular highlight while hovered
+            let mut close_button = DisplayListBuilder::new();
+            if tab.closable {
+                if hovered && state.close_hovered {
+                    close_button.push_round_rectangle(
+                        close,
+                        [close.size.width / 2.0; 4],
+                        GraphicsDisplayPaint::Fill(
+                            draw::strengthen(background, 0.1, state.data.contrast).into(),
+                        ),
+                        None,
+                    );
+                }
+
+                let cross = close.inflate(-close.size.width * 0.3, -close.size.height * 0.3);
+                let stroke = GraphicsDisplayStroke {
+                    thickness: 1.5,
+                    color: foreground.into(),
+                    ..Default::default()
+                };
+                close_button.push_line(cross.origin, cross.max(), stroke.clone(), None);
+                close_button.push_line(
+                    Point::new(cross.max_x(), cross.min_y()),
+                    Point::new(cross.min_x(), cross.max_y()),
+                    stroke,
+                    None,
+                );
+            }
+
+            commands.push(DisplayCommand::Save);
+            commands.extend(builder.build());
+            commands.push(DisplayCommand::Save);
+            commands.extend(label.build());
+            commands.push(DisplayCommand::Restore);
+            commands.extend(close_button.build());
+            commands.push(DisplayCommand::Restore);
+        }
+
+        commands
+    }
+}
+
+struct TourPainter;
+
+impl TourPainter {
+    fn make_text_item(
+        &self,
+        text: &str,
+        typeface: &draw::TypefaceStyle,
+        color: Color,
+    ) -> TextDisplayItem {
+        let font = typeface.typeface.pick(typeface.style);
+        TextDisplayItem {
+            text: text.to_string().into(),
+            font: font.0,
+            font_info: font.1,
+            size: typeface.size,
+            bottom_left: Default::default(),
+            color: color.into(),
+        }
+    }
+
+    fn current_step<'a>(&self, state: &'a state::TourState) -> Option<&'a ui::TourStep> {
+        state.data.current.and_then(|current| state.data.steps.get(current))
+    }
+}
+
+impl draw::Painter<state::TourState> for TourPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::TourState>> {
+        theme.tour()
+    }
+
+    fn size_hint(&self, state: state::TourState) -> Size {
+        let padding = state.data.padding;
+        let (title, text) = match self.current_step(&state) {
+            Some(step) => (
+                self.make_text_item(&step.title, &state.data.title_typeface, state.data.foreground)
+                    .bounds()
+                    .unwrap()
+                    .size,
+                self.make_text_item(&step.text, &state.data.typeface, state.data.foreground)
+                    .bounds()
+                    .unwrap()
+                    .size,
+            ),
+            None => (Size::zero(), Size::zero()),
+        };
+        let buttons = state.data.button_size.width * 2.0 + padding;
+
+        Size::new(
+            title.width.max(text.width).max(buttons) + padding * 2.0,
+            title.height + text.height + state.data.button_size.height + padding * 4.0,
+        )
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::TourState) -> Vec<DisplayCommand> {
+        let (layout, step) = match (state.layout, self.current_step(&state)) {
+            (Some(layout), Some(step)) => (layout, step),
+            _ => return Vec::new(),
+        };
+
+        let rect: Rect = state.rect.cast_unit();
+        let padding = state.data.padding;
+        let dim = GraphicsDisplayPaint::Fill(state.data.dim.into());
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Dim everything except the cutout, by surrounding it with 4 rectangles.
+        match layout.cutout {
+            Some(cutout) => {
+                let cutout: Rect = cutout.cast_unit();
+                let cutout = cutout.intersection(&rect).unwrap_or(cutout);
+                builder.push_rectangle(
+                    Rect::new(
+                        rect.origin,
+                        Size::new(rect.size.width, cutout.origin.y - rect.origin.y),
+                    ),
+                    dim.clone(),
+                    None,
+                );
+                builder.push_rectangle(
+                    Rect::new(
+                        Point::new(rect.origin.x, cutout.max_y()),
+                        Size::new(rect.size.width, rect.max_y() - cutout.max_y()),
+                    ),
+                    dim.clone(),
+                    None,
+                );
+                builder.push_rectangle(
+                    Rect::new(
+                        Point::new(rect.origin.x, cutout.origin.y),
+                        Size::new(cutout.origin.x - rect.origin.x, cutout.size.height),
+                    ),
+                    dim.clone(),
+                    None,
+                );
+                builder.push_rectangle(
+                    Rect::new(
+                        Point::new(cutout.max_x(), cutout.origin.y),
+                        Size::new(rect.max_x() - cutout.max_x(), cutout.size.height),
+                    ),
+                    dim,
+                    None,
+                );
+
+                builder.push_round_rectangle(
+                    base::sharp_align(cutout),
+                    [3.5; 4],
+                    GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                        thickness: 2.0,
+                        color: state.data.highlight.into(),
+                        ..Default::default()
+                    }),
+                    None,
+                );
+            }
+            None => builder.push_rectangle(rect, dim, None),
+        }
+
+        // Popover
+        let popover = base::sharp_align(layout.popover.cast_unit());
+        builder.push_round_rectangle(
+            popover.translate(Vector::new(0.0, 2.0)),
+            [3.5; 4],
+            GraphicsDisplayPaint::Fill(Color::new(0.0, 0.0, 0.0, 0.2).into()),
+            Some(Filter::Blur(4.0, 4.0)),
+        );
+        builder.push_round_rectangle(
+            popover,
+            [3.5; 4],
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        let mut title =
+            self.make_text_item(&step.title, &state.data.title_typeface, state.data.foreground);
+        let title_height = title.bounds().unwrap().size.height;
+        title.set_top_left(popover.origin + Vector::new(padding, padding));
+        builder.push_text(title, None);
+
+        let mut text = self.make_text_item(&step.text, &state.data.typeface, state.data.foreground);
+        text.set_top_left(popover.origin + Vector::new(padding, padding * 2.0 + title_height));
+        builder.push_text(text, None);
+
+        // Buttons
+        let is_last = state.data.current.map(|current| current + 1 >= state.data.steps.len());
+        let buttons = [
+            (
+                layout.skip_button,
+                state::TourButton::Skip,
+                &state.data.skip_text,
+                state.data.background,
+                state.data.foreground,
+            ),
+            (
+                layout.next_button,
+                state::TourButton::Next,
+                if is_last.unwrap_or(true) {
+                    &state.data.finish_text
+                } else {
+                    &state.data.next_text
+                },
+                state.data.highlight,
+                state.data.background,
+            ),
+        ];
+
+        for (button_rect, button, label, background, foreground) in buttons.iter() {
+            let button_rect = base::sharp_align(button_rect.cast_unit());
+            let background = if state.hovered == Some(*button) {
+                draw::strengthen(*background, 0.1, state.data.contrast)
+            } else {
+                *background
+            };
+
+            builder.push_round_rectangle(
+                button_rect,
+                [3.5; 4],
+                GraphicsDisplayPaint::Fill(background.into()),
+                None,
+            );
+            builder.push_round_rectangle(
+                button_rect,
+                [3.5; 4],
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 1.0 / 3.0,
+                    color: draw::weaken(state.data.foreground, 0.6, state.data.contrast).into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+
+            let mut text_item = self.make_text_item(label, &state.data.title_typeface, *foreground);
+            text_item.set_top_left(display::center(text_item.bounds().unwrap().size, button_rect));
+            builder.push_text(text_item, None);
+        }
+
+        builder.build()
+    }
+}
+
+struct BlurPanelPainter;
+
+impl draw::Painter<state::BlurPanelState> for BlurPanelPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::BlurPanelState>> {
+        theme.blur_panel()
+    }
+
+    fn size_hint(&self, state: state::BlurPanelState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::BlurPanelState) -> Vec<DisplayCommand> {
+        let rect = base::sharp_align(state.rect.cast_unit());
+        let radii = [state.data.corner_radius; 4];
+
+        let mut builder = DisplayListBuilder::new();
+
+        let tint = if state.backdrop {
+            builder.push_round_rectangle_backdrop(
+                rect,
+                radii,
+                Filter::Blur(state.data.blur, state.data.blur),
+            );
+            state.data.tint
+        } else {
+            state.data.fallback_tint
+        };
+
+        builder.push_round_rectangle(rect, radii, GraphicsDisplayPaint::Fill(tint.into()), None);
+
+        if state.data.border.alpha > 0.0 {
+            builder.push_round_rectangle(
+                rect,
+                radii,
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 1.0 / 3.0,
+                    color: state.data.border.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
+
+struct CardPainter;
+
+impl draw::Painter<state::CardState> for CardPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::CardState>> {
+        theme.card()
+    }
+
+    fn size_hint(&self, state: state::CardState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::CardState) -> Vec<DisplayCommand> {
+        let rect = base::sharp_align(state.rect.cast_unit());
+        let radius = state.data.corner_radius;
+        let border = GraphicsDisplayStroke {
+            thickness: 1.0 / 3.0,
+            color: state.data.border.into(),
+            ..Default::default()
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Drop shadow
+        if state.data.elevation > 0.0 {
+            builder.push_round_rectangle(
+                rect.translate(Vector::new(0.0, state.data.elevation / 2.0)),
+                [radius; 4],
+                GraphicsDisplayPaint::Fill(Color::new(0.0, 0.0, 0.0, 0.15).into()),
+                Some(Filter::Blur(state.data.elevation, state.data.elevation)),
+            );
+        }
+
+        // Background
+        builder.push_round_rectangle(
+            rect,
+            [radius; 4],
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        // Header and footer backgrounds, with a divider towards the body
+        if let Some(header) = state.header {
+            let header = base::sharp_align(header.cast_unit());
+            builder.push_round_rectangle(
+                header,
+                [radius, radius, 0.0, 0.0],
+                GraphicsDisplayPaint::Fill(state.data.header_background.into()),
+                None,
+            );
+            builder.push_line(
+                Point::new(header.min_x(), header.max_y()),
+                Point::new(header.max_x(), header.max_y()),
+                border.clone(),
+                None,
+            );
+        }
+
+        if let Some(footer) = state.footer {
+            let footer = base::sharp_align(footer.cast_unit());
+            builder.push_round_rectangle(
+                footer,
+                [0.0, 0.0, radius, radius],
+                GraphicsDisplayPaint::Fill(state.data.header_background.into()),
+                None,
+            );
+            builder.push_line(
+                footer.origin,
+                Point::new(footer.max_x(), footer.min_y()),
+                border.clone(),
+                None,
+            );
+        }
+
+        // Border
+        builder.push_round_rectangle(rect, [radius; 4], GraphicsDisplayPaint::Stroke(border), None);
+
+        builder.build()
+    }
+}
+
+struct TooltipPainter;
+
+impl TooltipPainter {
+    fn make_text_item(&self, state: &state::TooltipState) -> TextDisplayItem {
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+        TextDisplayItem {
+            text: state.data.text.clone().into(),
+            font: typeface.0,
+            font_info: typeface.1,
+            size: state.data.typeface.size,
+            bottom_left: Default::default(),
+            color: state.data.foreground.into(),
+        }
+    }
+}
+
+impl draw::Painter<state::TooltipState> for TooltipPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::TooltipState>> {
+        theme.tooltip()
+    }
+
+    fn size_hint(&self, state: state::TooltipState) -> Size {
+        let text = self.make_text_item(&state).bounds().unwrap().size;
+        let padding = state.data.padding;
+        Size::new(text.width + padding * 2.0, text.height + padding * 2.0)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // account for drop shadow
+        rect.inflate(4.0, 4.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::TooltipState) -> Vec<DisplayCommand> {
+        let tooltip = match state.tooltip {
+            Some(tooltip) => base::sharp_align(tooltip.cast_unit()),
+            None => return Vec::new(),
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_round_rectangle(
+            tooltip.translate(Vector::new(0.0, 1.0)),
+            [3.5; 4],
+            GraphicsDisplayPaint::Fill(Color::new(0.0, 0.0, 0.0, 0.2).into()),
+            Some(Filter::Blur(3.0, 3.0)),
+        );
+        builder.push_round_rectangle(
+            tooltip,
+            [3.5; 4],
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+        builder.push_round_rectangle(
+            tooltip,
+            [3.5; 4],
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: 1.0 / 3.0,
+                color: draw::weaken(state.data.foreground, 0.6, state.data.contrast).into(),
+                ..Default::default()
+            }),
+            None,
+        );
+
+        let mut text = self.make_text_item(&state);
+        text.set_top_left(tooltip.origin + Vector::new(state.data.padding, state.data.padding));
+        builder.push_text(text, None);
+
+        builder.build()
+    }
+}
+
+struct ErrorBoundaryPainter;
+
+impl ErrorBoundaryPainter {
+    const BUTTON_WIDTH: f32 = 80.0;
+    const BUTTON_HEIGHT: f32 = 28.0;
+
+    fn make_text_item(
+        &self,
+        state: &state::ErrorBoundaryState,
+        text: &str,
+        color: Color,
+    ) -> TextDisplayItem {
+        let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+        TextDisplayItem {
+            text: text.to_string().into(),
+            font: typeface.0,
+            font_info: typeface.1,
+            size: state.data.typeface.size,
+            bottom_left: Default::default(),
+            color: color.into(),
+        }
+    }
+
+    /// The retry button sits just below the center.
+    fn button_rect(&self, rect: Rect) -> Rect {
+        Rect::new(
+            Point::new(rect.center().x - Self::BUTTON_WIDTH / 2.0, rect.center().y + 8.0),
+            Size::new(Self::BUTTON_WIDTH, Self::BUTTON_HEIGHT),
+        )
+    }
+}
+
+impl draw::Painter<state::ErrorBoundaryState> for ErrorBoundaryPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::ErrorBoundaryState>> {
+        theme.error_boundary()
+    }
+
+    fn size_hint(&self, state: state::ErrorBoundaryState) -> Size {
+        let title = self.make_text_item(&state, &state.data.title, state.data.foreground);
+        Size::new(
+            title.bounds().unwrap().size.width.max(Self::BUTTON_WIDTH) + 32.0,
+            (Self::BUTTON_HEIGHT + 48.0) * 2.0,
+        )
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        self.button_rect(rect.cast_unit()).cast_unit()
+    }
+
+    fn draw(&mut self, state: state::ErrorBoundaryState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+        let center = rect.center();
+        let button = base::sharp_align(self.button_rect(rect));
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_rectangle(
+            rect,
+            GraphicsDisplayPaint::Fill(state.data.background.into()),
+            None,
+        );
+
+        // Title
+        let mut title = self.make_text_item(&state, &state.data.title, state.data.foreground);
+        let title_size = title.bounds().unwrap().size;
+        title.set_top_left(Point::new(center.x - title_size.width / 2.0, center.y - 40.0));
+        builder.push_text(title, None);
+
+        // Details
+        if state.data.show_details {
+            let mut details = self.make_text_item(
+                &state,
+                &state.error,
+                draw::weaken(state.data.foreground, 0.4, state.data.contrast),
+            );
+            let details_size = details.bounds().unwrap().size;
+            details.set_top_left(Point::new(
+                center.x - details_size.width / 2.0,
+                center.y - 36.0 + title_size.height,
+            ));
+            builder.push_text(details, None);
+        }
+
+        // Retry button
+        let button_background = if state.interaction.contains(state::InteractionState::PRESSED) {
+            draw::strengthen(state.data.button_background, 0.2, state.data.contrast)
+        } else if state.interaction.contains(state::InteractionState::HOVERED) {
+            draw::strengthen(state.data.button_background, 0.05, state.data.contrast)
+        } else {
+            state.data.button_background
+        };
+
+        builder.push_round_rectangle(
+            button,
+            [3.5; 4],
+            GraphicsDisplayPaint::Fill(button_background.into()),
+            None,
+        );
+        builder.push_round_rectangle(
+            button,
+            [3.5; 4],
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: 1.0 / 3.0,
+                color: draw::weaken(state.data.foreground, 0.4, state.data.contrast).into(),
+                ..Default::default()
+            }),
+            None,
+        );
+
+        let mut retry = self.make_text_item(&state, &state.data.retry_text, state.data.foreground);
+        retry.set_top_left(display::center(retry.bounds().unwrap().size, button));
+        builder.push_text(retry, None);
+
+        if state.interaction.contains(state::InteractionState::FOCUSED) {
+            builder.push_round_rectangle(
+                button.inflate(1.5, 1.5),
+                [3.5; 4],
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 3.5,
+                    color: state.data.focus.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "extra-widgets")]
+struct GaugePainter;
+
+#[cfg(feature = "extra-widgets")]
+impl draw::Painter<state::GaugeState> for GaugePainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::GaugeState>> {
+        theme.gauge()
+    }
+
+    fn size_hint(&self, _state: state::GaugeState) -> Size {
+        Size::new(120.0, 120.0)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::GaugeState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+        let center = rect.center();
+        let radius = (rect.size.width.min(rect.size.height) / 2.0 - 4.0).max(0.0);
+        let angle_of = |value: f32| {
+            let angle = GAUGE_START_ANGLE + GAUGE_SWEEP * state.data.normalize(value);
+            // Mirror the angle about the vertical axis for right-to-left layouts.
+            if state.data.direction.is_rtl() {
+                PI - angle
+            } else {
+                angle
+            }
+        };
+        let tick_color = draw::weaken(state.data.foreground, 0.3, state.data.contrast);
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Arc track
+        builder.push_path(
+            arc_path(center, radius, GAUGE_START_ANGLE, GAUGE_START_ANGLE + GAUGE_SWEEP),
+            false,
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: 6.0,
+                color: state.data.background.into(),
+                ..Default::default()
+            }),
+            None,
+        );
+
+        // Colored zones
+        for zone in &state.data.zones {
+            builder.push_path(
+                arc_path(center, radius, angle_of(zone.start), angle_of(zone.end)),
+                false,
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 6.0,
+                    color: zone.color.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        // Ticks and tick labels
+        if state.data.ticks > 0 {
+            let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+            let step = (state.data.max - state.data.min) / state.data.ticks as f32;
+            let precision = if step.fract().abs() <= std::f32::EPSILON { 0 } else { 1 };
+
+            for i in 0..=state.data.ticks {
+                let value = state.data.min + step * i as f32;
+                let angle = angle_of(value);
+                let direction = Vector::new(angle.cos(), angle.sin());
+
+                builder.push_line(
+                    center + direction * (radius - 10.0),
+                    center + direction * (radius - 4.0),
+                    GraphicsDisplayStroke {
+                        thickness: 1.0,
+                        color: tick_color.into(),
+                        ..Default::default()
+                    },
+                    None,
+                );
+
+                if state.data.tick_labels {
+                    let mut text_item = TextDisplayItem {
+                        text: format!("{:.*}", precision, value).into(),
+                        font: typeface.0.clone(),
+                        font_info: typeface.1.clone(),
+                        size: state.data.typeface.size,
+                        bottom_left: Default::default(),
+                        color: tick_color.into(),
+                    };
+
+                    let size = text_item.bounds().unwrap().size;
+                    text_item.set_top_left(
+                        center + direction * (radius - 22.0)
+                            - Vector::new(size.width / 2.0, size.height / 2.0),
+                    );
+
+                    builder.push_text(text_item, None);
+                }
+            }
+        }
+
+        // Needle
+        let angle = angle_of(state.data.value);
+        builder.push_line(
+            center,
+            center + Vector::new(angle.cos(), angle.sin()) * (radius - 8.0),
+            GraphicsDisplayStroke {
+                thickness: 2.0,
+                color: state.data.foreground.into(),
+                ..Default::default()
+            },
+            None,
+        );
+
+        // Needle hub
+        builder.push_round_rectangle(
+            Rect::new(center - Vector::new(4.0, 4.0), Size::new(8.0, 8.0)),
+            [4.0; 4],
+            GraphicsDisplayPaint::Fill(state.data.foreground.into()),
+            None,
+        );
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "extra-widgets")]
+struct RadialProgressPainter;
+
+#[cfg(feature = "extra-widgets")]
+impl draw::Painter<state::RadialProgressState> for RadialProgressPainter {
+    fn invoke(
+        &self,
+        theme: &dyn draw::Theme,
+    ) -> Box<dyn draw::Painter<state::RadialProgressState>> {
+        theme.radial_progress()
+    }
+
+    fn size_hint(&self, _state: state::RadialProgressState) -> Size {
+        Size::new(32.0, 32.0)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::RadialProgressState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+        let center = rect.center();
+        let radius =
+            (rect.size.width.min(rect.size.height) / 2.0 - state.data.thickness / 2.0).max(0.0);
+
+        // Progress starts at the top and fills clockwise (or counter-clockwise for right-to-left layouts).
+        let top = -PI / 2.0;
+        let (start, end) = match state.data.progress {
+            Some(progress) => (top, top + 2.0 * PI * progress.max(0.0).min(1.0)),
+            None => {
+                let start = top + 2.0 * PI * state.phase;
+                (start, start + PI / 2.0)
+            }
+        };
+        let (start, end) =
+            if state.data.direction.is_rtl() { (PI - end, PI - start) } else { (start, end) };
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Ring track
+        builder.push_path(
+            arc_path(center, radius, 0.0, 2.0 * PI),
+            true,
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: state.data.thickness,
+                color: state.data.background.into(),
+                ..Default::default()
+            }),
+            None,
+        );
+
+        // Progress arc
+        if end > start {
+            builder.push_path(
+                arc_path(center, radius, start, end),
+                false,
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: state.data.thickness,
+                    color: state.data.foreground.into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "extra-widgets")]
+struct CalendarHeatmapPainter;
+
+#[cfg(feature = "extra-widgets")]
+impl draw::Painter<state::CalendarHeatmapState> for CalendarHeatmapPainter {
+    fn invoke(
+        &self,
+        theme: &dyn draw::Theme,
+    ) -> Box<dyn draw::Painter<state::CalendarHeatmapState>> {
+        theme.calendar_heatmap()
+    }
+
+    fn size_hint(&self, state: state::CalendarHeatmapState) -> Size {
+        let stride = state.data.cell_size + state.data.cell_spacing;
+        Size::new(
+            (state.data.weeks() as f32 * stride - state.data.cell_spacing).max(0.0),
+            7.0 * stride - state.data.cell_spacing,
+        )
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // Tooltips can extend past the grid.
+        rect.inflate(64.0, 32.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::CalendarHeatmapState) -> Vec<DisplayCommand> {
+        let origin = state.rect.origin;
+
+        let mut builder = DisplayListBuilder::new();
+
+        for (i, day) in state.data.days.iter().enumerate() {
+            builder.push_round_rectangle(
+                state.data.cell_rect(origin, i).cast_unit(),
+                [2.0; 4],
+                GraphicsDisplayPaint::Fill(state.data.color_of(day.value).into()),
+                None,
+            );
+        }
+
+        if let Some(hovered) = state.hovered.filter(|&hovered| hovered < state.data.days.len()) {
+            let cell: Rect = state.data.cell_rect(origin, hovered).cast_unit();
+
+            builder.push_round_rectangle(
+                base::sharp_align(cell),
+                [2.0; 4],
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: 1.0,
+                    color: draw::strengthen(
+                        state.data.color_of(state.data.days[hovered].value),
+                        0.3,
+                        state.data.contrast,
+                    )
+                    .into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+
+            let typeface = state.data.typeface.typeface.pick(state.data.typeface.style);
+            let mut text_item = TextDisplayItem {
+                text: state.data.days[hovered].label.clone().into(),
+                font: typeface.0,
+                font_info: typeface.1,
+                size: state.data.typeface.size,
+                bottom_left: Default::default(),
+                color: state.data.tooltip_foreground.into(),
+            };
+
+            // Tooltip is centered above the hovered cell.
+            let text_size = text_item.bounds().unwrap().size;
+            let tooltip = Rect::new(
+                Point::new(
+                    cell.center().x - text_size.width / 2.0 - 6.0,
+                    cell.origin.y - text_size.height - 12.0,
+                ),
+                Size::new(text_size.width + 12.0, text_size.height + 8.0),
+            );
+            text_item.set_top_left(tooltip.origin + Vector::new(6.0, 4.0));
+
+            builder.push_round_rectangle(
+                tooltip,
+                [3.0; 4],
+                GraphicsDisplayPaint::Fill(state.data.tooltip_background.into()),
+                None,
+            );
+            builder.push_text(text_item, None);
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "extra-widgets")]
+struct GradientEditorPainter;
+
+#[cfg(feature = "extra-widgets")]
+impl draw::Painter<state::GradientEditorState> for GradientEditorPainter {
+    fn invoke(
+        &self,
+        theme: &dyn draw::Theme,
+    ) -> Box<dyn draw::Painter<state::GradientEditorState>> {
+        theme.gradient_editor()
+    }
+
+    fn size_hint(&self, state: state::GradientEditorState) -> Size {
+        Size::new(200.0, state.data.bar_height + state.data.handle_size + 4.0)
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect.inflate(1.0, 1.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::GradientEditorState) -> Vec<DisplayCommand> {
+        let bar: Rect = state.data.bar_rect(state.rect).cast_unit();
+
+        let mut builder = DisplayListBuilder::new();
+
+        // Checkerboard behind the bar, so that translucent stops are discernible.
+        let light = Color::new(1.0, 1.0, 1.0, 1.0);
+        let dark = Color::new(0.8, 0.8, 0.8, 1.0);
+        let cell = bar.size.height / 2.0;
+        if cell > 0.0 {
+            let columns = (bar.size.width / cell).ceil() as usize;
+            for column in 0..columns {
+                for row in 0..2 {
+                    let origin = bar.origin + Size::new(column as f32 * cell, row as f32 * cell);
+                    let width = cell.min(bar.max_x() - origin.x);
+                    builder.push_rectangle(
+                        Rect::new(origin, Size::new(width, cell)),
+                        GraphicsDisplayPaint::Fill(
+                            if (column + row) % 2 == 0 { light } else { dark }.into(),
+                        ),
+                        None,
+                    );
+                }
+            }
+        }
+
+        builder.push_rectangle(
+            bar,
+            GraphicsDisplayPaint::Fill(
+                state
+                    .data
+                    .gradient
+                    .to_style(bar.origin, bar.origin + Size::new(bar.size.width, 0.0)),
+            ),
+            None,
+        );
+        builder.push_rectangle(
+            base::sharp_align(bar),
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness: 1.0,
+                color: state.data.border.into(),
+                ..Default::default()
+            }),
+            None,
+        );
+
+        // The selected handle is drawn last, so that it's on top of overlapping handles.
+        let mut order: Vec<usize> = (0..state.data.gradient.stops.len()).collect();
+        if let Some(selected) = state.selected.filter(|&selected| selected < order.len()) {
+            order.retain(|&index| index != selected);
+            order.push(selected);
+        }
+
+        for index in order {
+            let selected = state.selected == Some(index);
+            let handle: Rect = state.data.handle_rect(state.rect, index).cast_unit();
+            let opacity = if selected && state.removing { 0.4 } else { 1.0 };
+            let color = state.data.gradient.stops[index].color;
+
+            builder.push_round_rectangle(
+                handle,
+                [2.0; 4],
+                GraphicsDisplayPaint::Fill(draw::with_opacity(color, color.alpha * opacity).into()),
+                None,
+            );
+            builder.push_round_rectangle(
+                base::sharp_align(handle),
+                [2.0; 4],
+                GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                    thickness: if selected { 2.0 } else { 1.0 },
+                    color: draw::with_opacity(
+                        if selected { state.data.selection } else { state.data.border },
+                        opacity,
+                    )
+                    .into(),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "extra-widgets")]
+struct TransformGizmoPainter;
+
+#[cfg(feature = "extra-widgets")]
+impl draw::Painter<state::TransformGizmoState> for TransformGizmoPainter {
+    fn invoke(
+        &self,
+        theme: &dyn draw::Theme,
+    ) -> Box<dyn draw::Painter<state::TransformGizmoState>> {
+        theme.transform_gizmo()
+    }
+
+    fn size_hint(&self, state: state::TransformGizmoState) -> Size {
+        state.rect.size.cast_unit()
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        // handles of targets along the edges of the canvas stick out of it
+        rect.inflate(40.0, 40.0)
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect.inflate(40.0, 40.0)
+    }
+
+    fn draw(&mut self, state: state::TransformGizmoState) -> Vec<DisplayCommand> {
+        let origin = state.rect.origin;
+        let data = &state.data;
+        let position = |handle| -> Point { data.handle_position(origin, handle).cast_unit() };
+        let stroke = |thickness| {
+            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                thickness,
+                color: data.foreground.into(),
+                ..Default::default()
+            })
+        };
+        let fill = |handle| {
+            let highlighted = state.active == Some(handle)
+                || (state.active.is_none() && state.hovered == Some(handle));
+            GraphicsDisplayPaint::Fill(
+                if highlighted { data.foreground } else { data.background }.into(),
+            )
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        let mut outline = VectorPathBuilder::new();
+        outline.move_to(position(ui::GizmoHandle::Resize(ui::ResizeHandle::TopLeft)));
+        for &corner in &[
+            ui::ResizeHandle::TopRight,
+            ui::ResizeHandle::BottomRight,
+            ui::ResizeHandle::BottomLeft,
+        ] {
+            outline.line_to(position(ui::GizmoHandle::Resize(corner)));
+        }
+        builder.push_path(
+            outline.build(),
+            true,
+            stroke(if state.active == Some(ui::GizmoHandle::Move) { 2.0 } else { 1.0 }),
+            None,
+        );
+
+        let rotate = position(ui::GizmoHandle::Rotate);
+        let mut stem = VectorPathBuilder::new();
+        stem.move_to(position(ui::GizmoHandle::Resize(ui::ResizeHandle::Top)));
+        stem.line_to(rotate);
+        builder.push_path(stem.build(), false, stroke(1.0), None);
+
+        let radius = data.handle_size / 2.0;
+        for &resize in &ui::ResizeHandle::ALL {
+            let handle = ui::GizmoHandle::Resize(resize);
+            let rect = Rect::new(
+                position(handle) - Vector::new(radius, radius),
+                Size::new(data.handle_size, data.handle_size),
+            );
+            builder.push_rectangle(rect, fill(handle), None);
+            builder.push_rectangle(base::sharp_align(rect), stroke(1.0), None);
+        }
+
+        let circle = || arc_path(rotate, radius, 0.0, PI * 2.0);
+        builder.push_path(circle(), true, fill(ui::GizmoHandle::Rotate), None);
+        builder.push_path(circle(), true, stroke(1.0), None);
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "extra-widgets")]
+struct RulerPainter;
+
+#[cfg(feature = "extra-widgets")]
+impl draw::Painter<state::RulerState> for RulerPainter {
+    fn invoke(&self, theme: &dyn draw::Theme) -> Box<dyn draw::Painter<state::RulerState>> {
+        theme.ruler()
+    }
+
+    fn size_hint(&self, state: state::RulerState) -> Size {
+        match state.data.orientation {
+            ui::Orientation::Horizontal => Size::new(200.0, state.data.thickness),
+            ui::Orientation::Vertical => Size::new(state.data.thickness, 200.0),
+        }
+    }
+
+    fn paint_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn mouse_hint(&self, rect: RelativeRect) -> RelativeRect {
+        rect
+    }
+
+    fn draw(&mut self, state: state::RulerState) -> Vec<DisplayCommand> {
+        let rect: Rect = state.rect.cast_unit();
+        let data = &state.data;
+        let horizontal = data.orientation == ui::Orientation::Horizontal;
+        let (start, length, thickness) = if horizontal {
+            (rect.origin.x, rect.size.width, rect.size.height)
+        } else {
+            (rect.origin.y, rect.size.height, rect.size.width)
+        };
+
+        // Maps a distance along the ruler and a distance from its outer edge to a point.
+        let point = |along: f32, across: f32| {
+            if horizontal {
+                Point::new(along, rect.origin.y + across)
+            } else {
+                Point::new(rect.origin.x + across, along)
+            }
+        };
+        let line = |builder: &mut DisplayListBuilder, along: f32, from: f32, color: Color| {
+            let along = along.round() + 0.5;
+            builder.push_line(
+                point(along, from),
+                point(along, thickness),
+                GraphicsDisplayStroke { thickness: 1.0, color: color.into(), ..Default::default() },
+                None,
+            );
+        };
+
+        let mut builder = DisplayListBuilder::new();
+
+        builder.push_rectangle(rect, GraphicsDisplayPaint::Fill(data.background.into()), None);
+        builder.push_line(
+            point(start, thickness - 0.5),
+            point(start + length, thickness - 0.5),
+            GraphicsDisplayStroke {
+                thickness: 1.0,
+                color: draw::weaken(data.foreground, 0.5, data.contrast).into(),
+                ..Default::default()
+            },
+            None,
+        );
+
+        // Labelled ticks at every step, with unlabelled ticks at every fifth of a step between them.
+        let step = data.tick_step();
+        let minor_step = step / 5.0;
+        let typeface = data.typeface.typeface.pick(data.typeface.style);
+        let precision = (-step.log10().floor()).max(0.0) as usize;
+        let end = data.offset + length / data.scale.max(std::f32::EPSILON);
+        let mut tick = (data.offset / minor_step).floor() as i64;
+        while minor_step > 0.0 && minor_step.is_finite() && (tick as f32) * minor_step <= end {
+            let value = tick as f32 * minor_step;
+            let along = data.to_absolute(state.rect, value);
+            if tick % 5 == 0 {
+                line(&mut builder, along, 0.0, data.foreground);
+
+                let mut text_item = TextDisplayItem {
+                    text: format!("{:.*}", precision, value).into(),
+                    font: typeface.0.clone(),
+                    font_info: typeface.1.clone(),
+                    size: data.typeface.size,
+                    bottom_left: Default::default(),
+                    color: data.foreground.into(),
+                };
+                text_item.set_top_left(point(along + 2.0, 1.0));
+                builder.push_text(text_item, None);
+            } else {
+                line(
+                    &mut builder,
+                    along,
+                    thickness * 0.7,
+                    draw::weaken(data.foreground, 0.3, data.contrast),
+                );
+            }
+            tick += 1;
+        }
+
+        for (index, &guide) in data.guides.iter().enumerate() {
+            let along = data.to_absolute(state.rect, guide);
+            let color = if state.dragged == Some(index) {
+                draw::strengthen(data.guide, 0.2, data.contrast)
+            } else {
+                data.guide
+            };
+            line(&mut builder, along, 0.0, color);
+        }
+
+        if let Some(cursor) = state.cursor {
+            line(&mut builder, data.to_absolute(state.rect, cursor), 0.0, data.cursor);
+        }
+
+        builder.build()
+    }
+}
@@ -0,0 +1,236 @@
+use {
+    crate::{
+        base::{self, Repaintable, WidgetChildren},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Frosted background which blurs whatever is drawn beneath it, e.g. for sidebars and overlays.
+///
+/// Since the panel wraps a child (its content), it's constructed with [`wrap`](struct.BlurPanel.html#method.wrap)
+/// rather than through `WidgetConstructor`.
+/// If the display backend doesn't support backdrop filters (see `GraphicalAuxiliary::supports_backdrop_filters`),
+/// the backdrop isn't blurred and `fallback_tint` is drawn instead of `tint`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurPanel {
+    /// Blur radius of the backdrop, in pixels.
+    pub blur: f32,
+    /// Translucent color drawn over the blurred backdrop.
+    pub tint: Color,
+    /// Color drawn over the backdrop if it can't be blurred; this should be more opaque than `tint`
+    /// so that the content stays legible.
+    pub fallback_tint: Color,
+    /// Color of the border, which is drawn if it isn't fully transparent.
+    pub border: Color,
+    pub corner_radius: f32,
+    pub contrast: draw::ThemeContrast,
+}
+
+impl BlurPanel {
+    pub fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        BlurPanel {
+            blur: 20.0,
+            tint: draw::with_opacity(data.scheme.background, 0.6),
+            fallback_tint: draw::with_opacity(data.scheme.background, 0.92),
+            border: draw::with_opacity(
+                draw::strengthen(data.scheme.background, 0.2, data.contrast),
+                0.5,
+            ),
+            corner_radius: 0.0,
+            contrast: data.contrast,
+        }
+    }
+
+    /// Wraps `child`, which the panel takes the place of (i.e. the panel widget takes on the rectangle of `child`).
+    pub fn wrap<U, G, W>(
+        self,
+        child: W,
+        theme: &dyn draw::Theme,
+        _u_aux: &mut U,
+    ) -> BlurPanelWidget<U, G, W>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+            + base::Rectangular
+            + 'static,
+    {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            BlurPanelWidget<U, G, W> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.repaint();
+                }
+            }
+        };
+
+        let rect = child.rect();
+        let mut child = child;
+        child.set_rect(RelativeRect::new(Default::default(), rect.size));
+
+        BlurPanelWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter: theme.blur_panel(),
+
+            child,
+            backdrop: true,
+        }
+        .build()
+    }
+}
+
+impl<U, G, W> ui::core::CoreWidget<state::BlurPanelState> for BlurPanelWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn derive_state(&self) -> state::BlurPanelState {
+        state::BlurPanelState { rect: self.abs_rect(), data: *self.data, backdrop: self.backdrop }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Wraps a child widget, drawing a blurred copy of what's beneath the panel as its background."]
+    pub struct BlurPanelWidget<
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static
+    > {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <BlurPanel> State,
+        <state::BlurPanelState> Painter,
+
+        {
+            child: W,
+            // Whether the backdrop was blurred as of the last draw.
+            backdrop: bool,
+        },
+    }
+}
+
+impl<U, G, W> BlurPanelWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    /// Returns the wrapped child.
+    #[inline]
+    pub fn child(&self) -> &W {
+        &self.child
+    }
+
+    /// Returns the wrapped child mutably.
+    #[inline]
+    pub fn child_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+}
+
+impl<U, G, W> Widget for BlurPanelWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.child.set_ctxt_rect(RelativeRect::new(Default::default(), rect.size.cast_unit()));
+        }
+
+        base::invoke_update(self, aux);
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) {
+        let backdrop = aux.supports_backdrop_filters();
+        if backdrop != self.backdrop {
+            self.backdrop = backdrop;
+            self.command_group.repaint();
+        }
+
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
+
+impl<U, G, W> WidgetChildren for BlurPanelWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        vec![&self.child]
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        vec![&mut self.child]
+    }
+}
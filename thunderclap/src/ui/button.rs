@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        a11y, anim,
         base::{self, Repaintable},
         draw::{self, state, HasTheme},
         geom::*,
@@ -13,8 +14,14 @@ use {
         verbgraph as vg,
         widget::Widget,
     },
+    std::time::Duration,
 };
 
+/// Duration of the transition between interaction states (e.g. the released and pressed appearance).
+const TRANSITION_DURATION: Duration = Duration::from_millis(120);
+/// Duration of the ripple spreading from the press position.
+const RIPPLE_DURATION: Duration = Duration::from_millis(400);
+
 /// Events emitted by a button.
 #[derive(Event, Debug, Clone, Copy, PartialEq)]
 pub enum ButtonEvent {
@@ -24,6 +31,9 @@ pub enum ButtonEvent {
     /// Emitted when the checkbox is released.
     #[event_key(release)]
     Release(AbsolutePoint),
+    /// Emitted after the second press of a double click.
+    #[event_key(double_click)]
+    DoubleClick(AbsolutePoint),
     /// Emitted when the mouse enters the checkbox boundaries.
     #[event_key(begin_hover)]
     BeginHover(AbsolutePoint),
@@ -58,19 +68,67 @@ where
         self.data.disabled
     }
 
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        Some(self.focus.id())
+    }
+
+    #[inline]
+    fn click_counter(&mut self) -> Option<&mut base::ClickCounter> {
+        Some(&mut self.clicks)
+    }
+
     fn on_interaction_event(&mut self, event: ui::InteractionEvent) {
         self.repaint();
+        if let ui::InteractionEvent::Pressed(pos) = event {
+            self.ripple.start(pos);
+        }
         self.event_queue.emit_owned(match event {
             ui::InteractionEvent::Pressed(pos) => ButtonEvent::Press(pos),
             ui::InteractionEvent::Released(pos) => ButtonEvent::Release(pos),
             ui::InteractionEvent::BeginHover(pos) => ButtonEvent::BeginHover(pos),
             ui::InteractionEvent::EndHover(pos) => ButtonEvent::EndHover(pos),
+            ui::InteractionEvent::DoubleClick(pos) => ButtonEvent::DoubleClick(pos),
             ui::InteractionEvent::Focus => ButtonEvent::Focus,
             ui::InteractionEvent::Blur => ButtonEvent::Blur,
         });
     }
 }
 
+impl<U, G> a11y::Accessible for ButtonWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn role(&self) -> a11y::Role {
+        a11y::Role::Button
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        a11y::text_name(&self.data.text)
+    }
+
+    fn state(&self) -> a11y::AccessibleState {
+        let mut state = a11y::AccessibleState::from_interaction(self.interaction);
+        state.set(a11y::AccessibleState::DISABLED, self.data.disabled);
+        state
+    }
+}
+
+impl<U, G> ui::Recyclable for ButtonWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn recycle(&mut self) {
+        self.interaction = state::InteractionState::empty();
+        self.clicks.reset();
+        self.repaint();
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Button {
     pub text: DisplayText,
@@ -130,6 +188,10 @@ where
             "interaction",
             ui::basic_interaction_handler::<ButtonWidget<U, G>, U>().bind(u_aux.window_queue()),
         );
+        graph = graph.add(
+            "focus",
+            ui::focus_handler::<ButtonWidget<U, G>, U>().bind(u_aux.focus_tracker().focus_queue()),
+        );
 
         let painter = theme.button();
         let rect = RelativeRect::new(
@@ -139,6 +201,10 @@ where
                     rect: Default::default(),
                     data: data.clone(),
                     interaction: state::InteractionState::empty(),
+                    hover: 0.0,
+                    press: 0.0,
+                    ripple: None,
+                    corner_radius: None,
                 })
                 .cast_unit(),
         );
@@ -151,6 +217,13 @@ where
             painter,
 
             interaction: state::InteractionState::empty(),
+            focus: u_aux.focus_tracker_mut().register(),
+            transition: state::InteractionTransition::new(
+                TRANSITION_DURATION,
+                anim::Easing::EaseOut,
+            ),
+            ripple: state::RippleTransition::new(RIPPLE_DURATION, anim::Easing::EaseOut),
+            clicks: Default::default(),
         }
         .build()
     }
@@ -166,17 +239,23 @@ where
             rect: self.abs_rect(),
             data: self.data.clone(),
             interaction: self.interaction,
+            hover: self.transition.hover(),
+            press: self.transition.press(),
+            ripple: self.ripple.ripple(),
+            corner_radius: None,
         }
     }
 
     fn on_transform(&mut self) {
         self.repaint();
+        self.focus.set_rect(self.abs_rect());
         self.layout.notify(self.abs_rect());
     }
 }
 
 use crate as thunderclap;
 crate::widget! {
+    #[widget_accessible]
     pub struct ButtonWidget {
         widget::MAX,
 
@@ -186,6 +265,10 @@ crate::widget! {
 
         {
             interaction: state::InteractionState,
+            focus: base::FocusHandle,
+            transition: state::InteractionTransition,
+            ripple: state::RippleTransition,
+            clicks: base::ClickCounter,
         },
     }
 }
@@ -209,6 +292,11 @@ where
         graph.update_all(self, aux);
         self.graph = Some(graph);
 
+        let transitioned = self.transition.update(self.interaction, aux.animator_mut());
+        if self.ripple.update(aux.animator_mut()) || transitioned {
+            self.command_group.repaint();
+        }
+
         if let Some(rect) = self.layout.receive() {
             self.set_ctxt_rect(rect);
             self.command_group.repaint();
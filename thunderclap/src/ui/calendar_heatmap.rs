@@ -0,0 +1,328 @@
+//! Calendar heatmap widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state, HasTheme},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect, Size},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// A single day within a calendar heatmap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarDay {
+    /// Value of the day, which determines the color of the cell.
+    pub value: f32,
+    /// Text displayed in the tooltip when the cell is hovered, e.g. "3 commits on 2020-02-14".
+    pub label: String,
+}
+
+/// Events emitted by a calendar heatmap.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum CalendarHeatmapEvent {
+    /// A day cell has been clicked. Holds the index of the day.
+    #[event_key(click)]
+    Click(usize),
+    /// The mouse has moved onto a day cell, or off all cells.
+    #[event_key(hover)]
+    Hover(Option<usize>),
+}
+
+/// Grid of colored day cells, arranged in columns of weeks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarHeatmap {
+    /// Series of consecutive days.
+    pub days: Vec<CalendarDay>,
+    /// Row (from 0 to 6) of the first day, i.e. the weekday it falls on.
+    pub first_weekday: usize,
+    /// Value which maps to the strongest color, or `None` to use the largest value in `days`.
+    pub max: Option<f32>,
+    /// Colors of the cells, from empty (values at or below zero) to strongest.
+    pub swatch: Vec<Color>,
+    /// Width and height of a single cell.
+    pub cell_size: f32,
+    /// Gap between cells.
+    pub cell_spacing: f32,
+    /// Typeface used in tooltips.
+    pub typeface: draw::TypefaceStyle,
+    /// Background color of tooltips.
+    pub tooltip_background: Color,
+    /// Text color of tooltips.
+    pub tooltip_foreground: Color,
+    /// Contrast mode of the swatch.
+    pub contrast: draw::ThemeContrast,
+}
+
+impl CalendarHeatmap {
+    /// Returns the amount of week columns required to display all the days.
+    pub fn weeks(&self) -> usize {
+        (self.first_weekday.min(6) + self.days.len() + 6) / 7
+    }
+
+    /// Returns the rectangle of the cell for the day at `index`, relative to `origin`.
+    pub fn cell_rect(&self, origin: AbsolutePoint, index: usize) -> AbsoluteRect {
+        let position = self.first_weekday.min(6) + index;
+        let stride = self.cell_size + self.cell_spacing;
+        AbsoluteRect::new(
+            AbsolutePoint::new(
+                origin.x + (position / 7) as f32 * stride,
+                origin.y + (position % 7) as f32 * stride,
+            ),
+            Size::new(self.cell_size, self.cell_size).cast_unit(),
+        )
+    }
+
+    /// Returns the index of the day whose cell contains `point`, if any.
+    pub fn cell_at(&self, origin: AbsolutePoint, point: AbsolutePoint) -> Option<usize> {
+        let stride = self.cell_size + self.cell_spacing;
+        if stride <= 0.0 || point.x < origin.x || point.y < origin.y {
+            return None;
+        }
+
+        let column = ((point.x - origin.x) / stride) as usize;
+        let row = ((point.y - origin.y) / stride) as usize;
+        if row >= 7 {
+            return None;
+        }
+
+        let index = (column * 7 + row).checked_sub(self.first_weekday.min(6))?;
+        if index < self.days.len() && self.cell_rect(origin, index).contains(point) {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the swatch color for `value`.
+    ///
+    /// Values at or below zero use the first color, and the rest are spread evenly over the remaining colors.
+    pub fn color_of(&self, value: f32) -> Color {
+        let levels = self.swatch.len();
+        if levels == 0 {
+            return Color::new(0.0, 0.0, 0.0, 0.0);
+        }
+
+        let max =
+            self.max.unwrap_or_else(|| self.days.iter().map(|day| day.value).fold(0.0, f32::max));
+
+        if value <= 0.0 || max <= 0.0 || levels == 1 {
+            self.swatch[0]
+        } else {
+            let level = ((value / max).min(1.0) * (levels - 1) as f32).ceil() as usize;
+            self.swatch[level.max(1).min(levels - 1)]
+        }
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for CalendarHeatmap
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = CalendarHeatmapWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for CalendarHeatmap
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+
+        let mut swatch = vec![draw::strengthen(data.scheme.control_outset, 0.05, data.contrast)];
+        swatch.extend(draw::swatch(
+            draw::weaken(data.scheme.primary, 0.4, data.contrast),
+            draw::strengthen(data.scheme.primary, 0.3, data.contrast),
+            4,
+        ));
+
+        CalendarHeatmap {
+            days: Vec::new(),
+            first_weekday: 0,
+            max: None,
+            swatch,
+            cell_size: 10.0,
+            cell_spacing: 3.0,
+            typeface: data.typography.button.clone(),
+            tooltip_background: data.scheme.over_control_outset,
+            tooltip_foreground: data.scheme.control_outset,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> CalendarHeatmapWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            CalendarHeatmapWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.resize_from_theme();
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph = graph
+            .add("calendar_heatmap", calendar_heatmap_handler::<U, G>().bind(u_aux.window_queue()));
+
+        let painter = theme.calendar_heatmap();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::CalendarHeatmapState {
+                    rect: Default::default(),
+                    data: data.clone(),
+                    hovered: None,
+                })
+                .cast_unit(),
+        );
+
+        CalendarHeatmapWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter,
+
+            hovered: None,
+            pressed: None,
+        }
+        .build()
+    }
+}
+
+/// Tracks the hovered cell and emits click events.
+fn calendar_heatmap_handler<U, G>(
+) -> vg::UnboundQueueHandler<CalendarHeatmapWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        CalendarHeatmapWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_move => {
+            let origin = obj.abs_rect().origin;
+            let hovered = obj.data.cell_at(origin, event.get().0);
+            if hovered != obj.hovered {
+                obj.hovered = hovered;
+                obj.command_group.repaint();
+                obj.event_queue.emit_owned(CalendarHeatmapEvent::Hover(hovered));
+            }
+        }
+
+        mouse_press => {
+            let origin = obj.abs_rect().origin;
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                *button == base::MouseButton::Left && obj.data.cell_at(origin, *pos).is_some()
+            }) {
+                obj.pressed = obj.data.cell_at(origin, *pos);
+            }
+        }
+
+        mouse_release => {
+            if let Some(pressed) = obj.pressed {
+                let (pos, button, _) = event.get();
+                if button == base::MouseButton::Left {
+                    obj.pressed = None;
+                    // Only a click if the mouse was released over the same cell it was pressed on.
+                    if obj.data.cell_at(obj.abs_rect().origin, pos) == Some(pressed) {
+                        obj.event_queue.emit_owned(CalendarHeatmapEvent::Click(pressed));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::CalendarHeatmapState> for CalendarHeatmapWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::CalendarHeatmapState {
+        state::CalendarHeatmapState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            hovered: self.hovered,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct CalendarHeatmapWidget {
+        widget::MAX,
+
+        <CalendarHeatmapEvent> EventQueue,
+        <CalendarHeatmap> State,
+        <state::CalendarHeatmapState> Painter,
+
+        {
+            hovered: Option<usize>,
+            pressed: Option<usize>,
+        },
+    }
+}
+
+impl<U, G> Widget for CalendarHeatmapWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+
+        // The data may have shrunk underneath the hovered cell.
+        if self.hovered.map(|hovered| hovered >= self.data.days.len()).unwrap_or(false) {
+            self.hovered = None;
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use {
+    crate::{
+        base::{self, Repaintable, Resizable, WidgetChildren},
+        draw::{self, state},
+        factory::DynWidget,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect, Size},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Boxed slot widget of a card.
+pub type CardSlot<U, G> = Box<dyn DynWidget<U, G>>;
+
+/// Rounded, bordered and elevated container with optional header, body and footer slots,
+/// serving as the basic building block of dashboards.
+///
+/// Slots are filled through the widget (e.g. [`set_body`](struct.CardWidget.html#method.set_body)).
+/// The header and footer keep their height and span the width of the card (less the padding),
+/// whereas the body fills the remaining area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Card {
+    pub background: Color,
+    /// Background of the header and footer.
+    pub header_background: Color,
+    /// Color of the border, as well as the dividers between the slots.
+    pub border: Color,
+    pub corner_radius: f32,
+    /// Blur radius of the drop shadow; `0.0` draws no shadow.
+    pub elevation: f32,
+    /// Space between the edges of each slot area and the slot widget.
+    pub padding: f32,
+    pub contrast: draw::ThemeContrast,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Card
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = CardWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Card
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Card {
+            background: data.scheme.background,
+            header_background: draw::strengthen(data.scheme.background, 0.03, data.contrast),
+            border: draw::strengthen(data.scheme.background, 0.12, data.contrast),
+            corner_radius: 6.0,
+            elevation: 4.0,
+            padding: 16.0,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, _u_aux: &mut U) -> CardWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            CardWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.dirty = true;
+                }
+            }
+        };
+
+        CardWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+            painter: theme.card(),
+
+            header: None,
+            body: None,
+            footer: None,
+            dirty: true,
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::CardState> for CardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::CardState {
+        state::CardState {
+            rect: self.abs_rect(),
+            data: *self.data,
+            header: self.header_rect(),
+            footer: self.footer_rect(),
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.dirty = true;
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Container with header, body and footer slots, see `Card`."]
+    pub struct CardWidget {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <Card> State,
+        <state::CardState> Painter,
+
+        {
+            header: Option<CardSlot<U, G>>,
+            body: Option<CardSlot<U, G>>,
+            footer: Option<CardSlot<U, G>>,
+            dirty: bool,
+        },
+    }
+}
+
+impl<U, G> CardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Sets the header slot, returning the previous header.
+    pub fn set_header(&mut self, header: impl DynWidget<U, G> + 'static) -> Option<CardSlot<U, G>> {
+        self.dirty = true;
+        self.header.replace(Box::new(header))
+    }
+
+    /// Sets the body slot, returning the previous body.
+    pub fn set_body(&mut self, body: impl DynWidget<U, G> + 'static) -> Option<CardSlot<U, G>> {
+        self.dirty = true;
+        self.body.replace(Box::new(body))
+    }
+
+    /// Sets the footer slot, returning the previous footer.
+    pub fn set_footer(&mut self, footer: impl DynWidget<U, G> + 'static) -> Option<CardSlot<U, G>> {
+        self.dirty = true;
+        self.footer.replace(Box::new(footer))
+    }
+
+    /// Removes the header slot.
+    pub fn take_header(&mut self) -> Option<CardSlot<U, G>> {
+        self.dirty = true;
+        self.header.take()
+    }
+
+    /// Removes the body slot.
+    pub fn take_body(&mut self) -> Option<CardSlot<U, G>> {
+        self.dirty = true;
+        self.body.take()
+    }
+
+    /// Removes the footer slot.
+    pub fn take_footer(&mut self) -> Option<CardSlot<U, G>> {
+        self.dirty = true;
+        self.footer.take()
+    }
+
+    /// Returns the header slot, if any.
+    pub fn header(&self) -> Option<&dyn DynWidget<U, G>> {
+        self.header.as_ref().map(|header| header.as_ref())
+    }
+
+    /// Returns the header slot mutably, if any.
+    pub fn header_mut(&mut self) -> Option<&mut (dyn DynWidget<U, G> + 'static)> {
+        self.header.as_mut().map(|header| header.as_mut())
+    }
+
+    /// Returns the body slot, if any.
+    pub fn body(&self) -> Option<&dyn DynWidget<U, G>> {
+        self.body.as_ref().map(|body| body.as_ref())
+    }
+
+    /// Returns the body slot mutably, if any.
+    pub fn body_mut(&mut self) -> Option<&mut (dyn DynWidget<U, G> + 'static)> {
+        self.body.as_mut().map(|body| body.as_mut())
+    }
+
+    /// Returns the footer slot, if any.
+    pub fn footer(&self) -> Option<&dyn DynWidget<U, G>> {
+        self.footer.as_ref().map(|footer| footer.as_ref())
+    }
+
+    /// Returns the footer slot mutably, if any.
+    pub fn footer_mut(&mut self) -> Option<&mut (dyn DynWidget<U, G> + 'static)> {
+        self.footer.as_mut().map(|footer| footer.as_mut())
+    }
+
+    /// Returns the absolute rectangle of the header area (including padding), if there is a header.
+    pub fn header_rect(&self) -> Option<AbsoluteRect> {
+        let rect = self.abs_rect();
+        self.header.as_ref().map(|header| {
+            let height = (header.size().height + self.data.padding * 2.0).min(rect.size.height);
+            AbsoluteRect::new(rect.origin, Size::new(rect.size.width, height).cast_unit())
+        })
+    }
+
+    /// Returns the absolute rectangle of the footer area (including padding), if there is a footer.
+    pub fn footer_rect(&self) -> Option<AbsoluteRect> {
+        let rect = self.abs_rect();
+        self.footer.as_ref().map(|footer| {
+            let height = (footer.size().height + self.data.padding * 2.0).min(rect.size.height);
+            AbsoluteRect::new(
+                AbsolutePoint::new(rect.origin.x, rect.max_y() - height),
+                Size::new(rect.size.width, height).cast_unit(),
+            )
+        })
+    }
+
+    /// Returns the absolute rectangle of the body area (including padding), which is the area between the header and footer.
+    pub fn body_rect(&self) -> AbsoluteRect {
+        let rect = self.abs_rect();
+        let top = self.header_rect().map(|header| header.max_y()).unwrap_or_else(|| rect.min_y());
+        let bottom =
+            self.footer_rect().map(|footer| footer.min_y()).unwrap_or_else(|| rect.max_y());
+        AbsoluteRect::new(
+            AbsolutePoint::new(rect.origin.x, top),
+            Size::new(rect.size.width, (bottom - top).max(0.0)).cast_unit(),
+        )
+    }
+
+    /// Positions and sizes the slots within their areas.
+    fn update_slots(&mut self) {
+        let position = self.abs_rect().origin;
+        let padding = self.data.padding;
+        let width = (self.rect.size.width - padding * 2.0).max(0.0);
+        let body_rect = self.body_rect();
+        let footer_rect = self.footer_rect();
+
+        if let Some(header) = &mut self.header {
+            let height = header.size().height;
+            header.set_parent_position(position);
+            header.set_size(Size::new(width, height));
+            header.set_ctxt_position(RelativePoint::new(padding, padding).into());
+        }
+
+        if let Some(body) = &mut self.body {
+            body.set_parent_position(position);
+            body.set_size(Size::new(width, (body_rect.size.height - padding * 2.0).max(0.0)));
+            body.set_ctxt_position(
+                RelativePoint::new(padding, body_rect.min_y() - position.y + padding).into(),
+            );
+        }
+
+        if let (Some(footer), Some(footer_rect)) = (&mut self.footer, footer_rect) {
+            let height = footer.size().height;
+            footer.set_parent_position(position);
+            footer.set_size(Size::new(width, height));
+            footer.set_ctxt_position(
+                RelativePoint::new(padding, footer_rect.min_y() - position.y + padding).into(),
+            );
+        }
+    }
+}
+
+impl<U, G> Widget for CardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        // The drop shadow extends past the painted rectangle.
+        let elevation = self.data.elevation * 2.0;
+        self.painter.paint_hint(self.rect).inflate(elevation, elevation).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.dirty = true;
+        }
+
+        if self.dirty {
+            self.dirty = false;
+            self.update_slots();
+            self.repaint();
+        }
+
+        base::invoke_update(self, aux);
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
+
+impl<U, G> WidgetChildren for CardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        self.header
+            .iter()
+            .chain(&self.body)
+            .chain(&self.footer)
+            .map(|slot| slot.as_children())
+            .collect()
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        self.header
+            .iter_mut()
+            .chain(&mut self.body)
+            .chain(&mut self.footer)
+            .map(|slot| slot.as_children_mut())
+            .collect()
+    }
+}
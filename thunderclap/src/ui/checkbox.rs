@@ -1,5 +1,6 @@
 use {
     crate::{
+        a11y, anim,
         base::{self, Repaintable},
         draw::{self, state},
         geom::*,
@@ -10,8 +11,12 @@ use {
         prelude::*,
         verbgraph as vg,
     },
+    std::time::Duration,
 };
 
+/// Duration of the transition between interaction states (e.g. the resting and hovered appearance).
+const TRANSITION_DURATION: Duration = Duration::from_millis(120);
+
 /// Events emitted by a checkbox.
 #[derive(Event, Debug, Clone, Copy, PartialEq)]
 pub enum CheckboxEvent {
@@ -61,6 +66,11 @@ where
         self.data.disabled
     }
 
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        Some(self.focus.id())
+    }
+
     fn on_interaction_event(&mut self, event: ui::InteractionEvent) {
         self.repaint();
         match event {
@@ -82,6 +92,7 @@ where
             ui::InteractionEvent::EndHover(pos) => {
                 self.event_queue.emit_owned(CheckboxEvent::EndHover(pos));
             }
+            ui::InteractionEvent::DoubleClick(_) => {}
             ui::InteractionEvent::Focus => {
                 self.event_queue.emit_owned(CheckboxEvent::Focus);
             }
@@ -92,6 +103,40 @@ where
     }
 }
 
+impl<U, G> a11y::Accessible for CheckboxWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn role(&self) -> a11y::Role {
+        a11y::Role::CheckBox
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        String::new()
+    }
+
+    fn state(&self) -> a11y::AccessibleState {
+        let mut state = a11y::AccessibleState::from_interaction(self.interaction);
+        state.set(a11y::AccessibleState::CHECKED, self.data.checked);
+        state.set(a11y::AccessibleState::DISABLED, self.data.disabled);
+        state
+    }
+}
+
+impl<U, G> ui::Recyclable for CheckboxWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn recycle(&mut self) {
+        self.interaction = state::InteractionState::empty();
+        self.repaint();
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Checkbox {
     pub foreground: Color,
@@ -140,6 +185,11 @@ where
             "handler",
             ui::basic_interaction_handler::<CheckboxWidget<U, G>, U>().bind(u_aux.window_queue()),
         );
+        graph = graph.add(
+            "focus",
+            ui::focus_handler::<CheckboxWidget<U, G>, U>()
+                .bind(u_aux.focus_tracker().focus_queue()),
+        );
 
         let painter = theme.checkbox();
         let rect = RelativeRect::new(
@@ -149,6 +199,9 @@ where
                     rect: Default::default(),
                     data: *data,
                     interaction: state::InteractionState::empty(),
+                    hover: 0.0,
+                    press: 0.0,
+                    corner_radius: None,
                 })
                 .cast_unit(),
         );
@@ -162,6 +215,11 @@ where
             painter,
 
             interaction: state::InteractionState::empty(),
+            focus: u_aux.focus_tracker_mut().register(),
+            transition: state::InteractionTransition::new(
+                TRANSITION_DURATION,
+                anim::Easing::EaseOut,
+            ),
         }
         .build()
     }
@@ -177,17 +235,22 @@ where
             rect: self.abs_rect(),
             data: *self.data,
             interaction: self.interaction,
+            hover: self.transition.hover(),
+            press: self.transition.press(),
+            corner_radius: None,
         }
     }
 
     fn on_transform(&mut self) {
         self.repaint();
+        self.focus.set_rect(self.abs_rect());
         self.layout.notify(self.abs_rect());
     }
 }
 
 use crate as thunderclap;
 crate::widget! {
+    #[widget_accessible]
     pub struct CheckboxWidget {
         widget::MAX,
 
@@ -197,6 +260,8 @@ crate::widget! {
 
         {
             interaction: state::InteractionState,
+            focus: base::FocusHandle,
+            transition: state::InteractionTransition,
         },
     }
 }
@@ -230,6 +295,10 @@ where
             });
         }
 
+        if self.transition.update(self.interaction, aux.animator_mut()) {
+            self.command_group.repaint();
+        }
+
         if let Some(rect) = self.layout.receive() {
             self.set_ctxt_rect(rect);
             self.command_group.repaint();
@@ -0,0 +1,316 @@
+use {
+    crate::{
+        base::{self, Movable, Repaintable, WidgetChildren},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{
+            CommandGroup, DisplayClip, DisplayCommand, GraphicsDisplay, Point, Rect,
+            VectorPathBuilder,
+        },
+        event::{RcEventListener, RcEventQueue},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Shape which the child of a [`Clip`](struct.Clip.html) is confined to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipShape {
+    /// Rectangle with rounded corners.
+    /// Holds the radii of the top-left, top-right, bottom-right and bottom-left corners, respectively.
+    RoundRectangle([f32; 4]),
+    /// Arbitrary closed path, given as the points of a polygon relative to the top-left of the clip.
+    Polygon(Vec<Point>),
+}
+
+impl ClipShape {
+    /// Rectangle with the same radius for every corner.
+    #[inline]
+    pub fn rounded(radius: f32) -> Self {
+        ClipShape::RoundRectangle([radius; 4])
+    }
+
+    /// Returns `true` if `point` lies within the shape, with the shape placed at `rect`.
+    pub fn contains(&self, rect: AbsoluteRect, point: AbsolutePoint) -> bool {
+        match self {
+            ClipShape::RoundRectangle(radii) => {
+                if !rect.contains(point) {
+                    return false;
+                }
+
+                // Center of each corner arc, alongside the direction of the corner from that center.
+                let corners = [
+                    (radii[0], rect.min_x() + radii[0], rect.min_y() + radii[0], -1.0, -1.0),
+                    (radii[1], rect.max_x() - radii[1], rect.min_y() + radii[1], 1.0, -1.0),
+                    (radii[2], rect.max_x() - radii[2], rect.max_y() - radii[2], 1.0, 1.0),
+                    (radii[3], rect.min_x() + radii[3], rect.max_y() - radii[3], -1.0, 1.0),
+                ];
+
+                corners.iter().all(|&(radius, cx, cy, sx, sy)| {
+                    let (dx, dy) = (point.x - cx, point.y - cy);
+                    // Only points beyond the arc center on both axes lie within the corner.
+                    dx * sx <= 0.0 || dy * sy <= 0.0 || dx * dx + dy * dy <= radius * radius
+                })
+            }
+            ClipShape::Polygon(points) => {
+                // Even-odd rule; cast a ray to the right of the point and count the crossed edges.
+                let (x, y) = (point.x - rect.origin.x, point.y - rect.origin.y);
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > y) != (b.y > y) && x < a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y) {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    fn display_clip(&self, rect: Rect) -> DisplayClip {
+        match self {
+            ClipShape::RoundRectangle(radii) => DisplayClip::RoundRectangle { rect, radii: *radii },
+            ClipShape::Polygon(points) => {
+                let mut builder = VectorPathBuilder::new();
+                for (i, point) in points.iter().enumerate() {
+                    let point = rect.origin + point.to_vector();
+                    if i == 0 {
+                        builder.move_to(point);
+                    } else {
+                        builder.line_to(point);
+                    }
+                }
+                DisplayClip::Path { path: builder.build(), is_closed: true }
+            }
+        }
+    }
+}
+
+/// Clips the rendering and hit-testing of a child to a shape, e.g. to place images or lists inside rounded cards.
+///
+/// Since the clip has to filter the window events received by the child, the child is built by a closure given to
+/// [`wrap`](struct.Clip.html#method.wrap), during which `UpdateAuxiliary::window_queue` is swapped out for a queue owned by the clip.
+/// Mouse presses and wheel events outside the shape aren't forwarded to the child;
+/// cursor movement and releases always are, so that hover and press states of the child still end.
+///
+/// Note that the child is drawn by the clip itself (in between the clip and restore commands),
+/// hence it isn't returned from `children`/`children_mut`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    pub shape: ClipShape,
+}
+
+impl Clip {
+    /// Wraps the child built by `child`, which the clip takes the place of (i.e. the clip takes on the rectangle of the child).
+    pub fn wrap<U, G, W>(
+        self,
+        child: impl FnOnce(&mut U) -> W,
+        u_aux: &mut U,
+    ) -> ClipWidget<U, G, W>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+            + base::Rectangular
+            + 'static,
+    {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            ClipWidget<U, G, W> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.repaint();
+                }
+            }
+        };
+
+        let window_listener = u_aux.window_queue().listen();
+
+        let mut child_queue = RcEventQueue::new();
+        std::mem::swap(u_aux.window_queue_mut(), &mut child_queue);
+        let mut child = child(u_aux);
+        std::mem::swap(u_aux.window_queue_mut(), &mut child_queue);
+
+        let rect = child.rect();
+        child.set_rect(RelativeRect::new(Default::default(), rect.size));
+
+        ClipWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+
+            child,
+            child_queue,
+            window_listener,
+            clip_list: Default::default(),
+            restore_group: CommandGroup::new(),
+        }
+        .build()
+    }
+}
+
+impl<U, G, W> ui::core::CoreWidget<()> for ClipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.update_child_position();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Clips the rendering and hit-testing of a child widget to a shape."]
+    pub struct ClipWidget<
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static
+    > {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <Clip> State,
+
+        {
+            child: W,
+            // Window queue which the child listens to, receiving the events which pass the hit-test.
+            child_queue: RcEventQueue<base::WindowEvent>,
+            window_listener: RcEventListener<base::WindowEvent>,
+            clip_list: base::ClipList,
+            restore_group: CommandGroup,
+        },
+    }
+}
+
+impl<U, G, W> ClipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    /// Returns the wrapped child.
+    #[inline]
+    pub fn child(&self) -> &W {
+        &self.child
+    }
+
+    /// Returns the wrapped child mutably.
+    #[inline]
+    pub fn child_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+
+    /// The child isn't listed in `children_mut`, so its parent position has to be propagated manually.
+    fn update_child_position(&mut self) {
+        self.child.set_parent_position(self.abs_rect().origin);
+        let child_position = self.child.position();
+        self.child.set_ctxt_position(child_position.into());
+    }
+
+    /// Forwards the window events which pass the hit-test to the child.
+    fn forward_events(&mut self) {
+        let rect = self.abs_rect();
+        for event in self.window_listener.peek() {
+            let inside = match &event {
+                base::WindowEvent::MousePress(event) => {
+                    self.data.shape.contains(rect, event.get().0)
+                }
+                base::WindowEvent::MouseWheel(event) => {
+                    self.data.shape.contains(rect, event.get().0)
+                }
+                _ => true,
+            };
+
+            // Consumable events are shared, so the child consuming a forwarded event consumes the original too.
+            if inside {
+                self.child_queue.emit_owned(event);
+            }
+        }
+    }
+}
+
+impl<U, G, W> Widget for ClipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.child.set_ctxt_rect(RelativeRect::new(Default::default(), rect.size.cast_unit()));
+        }
+
+        self.forward_events();
+        self.child.update(aux);
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) {
+        // Like the clip groups of `invoke_draw`, these are re-pushed every frame to stay ordered around the child.
+        self.command_group.repaint();
+        self.restore_group.repaint();
+
+        let clip = self.data.shape.display_clip(self.abs_rect().cast_unit());
+        self.command_group.push(
+            display,
+            &[DisplayCommand::Save, DisplayCommand::Clip(clip)],
+            Default::default(),
+            false,
+            None,
+        );
+
+        self.clip_list.draw(&mut self.child, display, aux);
+
+        self.restore_group.push(
+            display,
+            &[DisplayCommand::Restore],
+            Default::default(),
+            false,
+            None,
+        );
+    }
+}
+
+impl<U, G, W> WidgetChildren for ClipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+}
@@ -0,0 +1,616 @@
+//! Dashboard layout widget.
+
+use {
+    crate::{
+        base::{self, Resizable},
+        draw, error,
+        geom::*,
+        ui,
+    },
+    indexmap::IndexMap,
+    reclutch::{
+        display::{DisplayCommand, Rect, Size},
+        event::{bidir_single::Queue as BidirSingleEventQueue, RcEventListener},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{fmt, str::FromStr},
+};
+
+/// Cells of a dashboard grid occupied by a card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashboardCell {
+    pub column: u32,
+    pub row: u32,
+    /// Number of columns spanned.
+    pub width: u32,
+    /// Number of rows spanned.
+    pub height: u32,
+}
+
+impl DashboardCell {
+    /// Creates a cell spanning `width` columns and `height` rows from `column` and `row`.
+    pub fn new(column: u32, row: u32, width: u32, height: u32) -> Self {
+        DashboardCell { column, row, width, height }
+    }
+
+    /// Returns `true` if the cells share any part of the grid.
+    pub fn overlaps(&self, other: &DashboardCell) -> bool {
+        self.column < other.column + other.width
+            && other.column < self.column + self.width
+            && self.row < other.row + other.height
+            && other.row < self.row + self.height
+    }
+
+    /// Returns the row following the last row spanned.
+    #[inline]
+    pub fn bottom(&self) -> u32 {
+        self.row + self.height
+    }
+}
+
+impl Default for DashboardCell {
+    fn default() -> Self {
+        DashboardCell::new(0, 0, 1, 1)
+    }
+}
+
+/// Card of a dashboard; its key and cells. This is the push data of the `Dashboard` layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashboardItem {
+    /// Identifies the card within a `DashboardLayout`.
+    /// Keys can't contain `:` or `;`, which separate them in the compact description of a layout.
+    pub key: String,
+    pub cell: DashboardCell,
+}
+
+impl DashboardItem {
+    /// Creates a card keyed `key` occupying `cell`.
+    pub fn new(key: impl Into<String>, cell: DashboardCell) -> Self {
+        DashboardItem { key: key.into(), cell }
+    }
+}
+
+/// Arrangement of the cards of a dashboard, which can be saved and restored (see `DashboardWidget::set_layout`).
+///
+/// Layouts are described compactly as `key:column,row,widthxheight` per card, separated by `;`, e.g.
+/// `cpu:0,0,2x1;memory:2,0,1x2` (see the `Display` and `FromStr` implementations).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashboardLayout {
+    pub cards: Vec<DashboardItem>,
+}
+
+impl DashboardLayout {
+    /// Returns the cells occupied by the card keyed `key`, if it's in the layout.
+    pub fn cell(&self, key: &str) -> Option<DashboardCell> {
+        self.cards.iter().find(|card| card.key == key).map(|card| card.cell)
+    }
+
+    /// Moves cards out of the way of the card at `index`, by pushing overlapping cards down.
+    ///
+    /// Cards are pushed in order from the top, so that their vertical order is preserved.
+    pub fn resolve_collisions(&mut self, index: usize) {
+        resolve_collisions(self.cards.iter_mut().map(|card| &mut card.cell).collect(), index);
+    }
+}
+
+/// Pushes overlapping cells down, keeping the cell at `fixed` in place.
+fn resolve_collisions(mut cells: Vec<&mut DashboardCell>, fixed: usize) {
+    let mut order: Vec<usize> = (0..cells.len()).filter(|&index| index != fixed).collect();
+    order.sort_by_key(|&index| (cells[index].row, cells[index].column));
+
+    let mut placed = vec![*cells[fixed]];
+    for index in order {
+        let mut cell = *cells[index];
+        while let Some(bottom) =
+            placed.iter().filter(|other| other.overlaps(&cell)).map(DashboardCell::bottom).max()
+        {
+            cell.row = bottom;
+        }
+        *cells[index] = cell;
+        placed.push(cell);
+    }
+}
+
+impl fmt::Display for DashboardLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, card) in self.cards.iter().enumerate() {
+            if index > 0 {
+                write!(f, ";")?;
+            }
+            let cell = card.cell;
+            write!(f, "{}:{},{},{}x{}", card.key, cell.column, cell.row, cell.width, cell.height)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DashboardLayout {
+    type Err = error::DashboardLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cards: Vec<DashboardItem> = Vec::new();
+        for card in s.split(';').map(str::trim).filter(|card| !card.is_empty()) {
+            let invalid = || error::DashboardLayoutError::InvalidCard(card.to_string());
+
+            let mut parts = card.splitn(2, ':');
+            let key =
+                parts.next().map(str::trim).filter(|key| !key.is_empty()).ok_or_else(invalid)?;
+            let numbers: Vec<u32> = parts
+                .next()
+                .ok_or_else(invalid)?
+                .split(|c| c == ',' || c == 'x')
+                .map(|number| number.trim().parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?;
+            let cell = match numbers[..] {
+                [column, row, width, height] if width > 0 && height > 0 => {
+                    DashboardCell::new(column, row, width, height)
+                }
+                _ => return Err(invalid()),
+            };
+
+            if cards.iter().any(|other| other.key == key) {
+                return Err(error::DashboardLayoutError::DuplicateKey(key.to_string()));
+            }
+            cards.push(DashboardItem::new(key, cell));
+        }
+        Ok(DashboardLayout { cards })
+    }
+}
+
+/// Events emitted by a dashboard.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum DashboardEvent {
+    /// The user has started dragging the card with a key, to move or resize it.
+    #[event_key(drag_start)]
+    DragStart(String),
+    /// The user has finished rearranging the cards, resulting in a layout.
+    #[event_key(rearrange)]
+    Rearrange(DashboardLayout),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DragMode {
+    Move,
+    Resize,
+}
+
+#[derive(Debug, Clone)]
+struct Drag {
+    id: u64,
+    mode: DragMode,
+    start: AbsolutePoint,
+    /// Cells of all the cards when the drag started, which collisions are resolved from.
+    original: Vec<(u64, DashboardCell)>,
+}
+
+#[derive(Debug)]
+struct ChildData {
+    item: DashboardItem,
+    evq: BidirSingleEventQueue<AbsoluteRect, AbsoluteRect>,
+    drop_listener: RcEventListener<base::DropEvent>,
+    rect: AbsoluteRect,
+    original_rect: AbsoluteRect,
+    id: u64,
+}
+
+/// Layout which arranges cards in a grid of equally wide columns and equally tall rows, where each card spans
+/// a rectangle of cells.
+///
+/// Cards are moved by dragging the strip along their top (`handle_height` tall), and resized by dragging
+/// their bottom-right corner (`resize_handle` wide). Cards in the way of the dragged card are pushed down.
+/// Once dragging finishes, `DashboardEvent::Rearrange` is emitted with the new layout, which can be saved
+/// and later restored with `DashboardWidget::set_layout`.
+///
+/// The dashboard is as wide as its rectangle, and as tall as the rows spanned by its cards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dashboard {
+    /// Number of columns.
+    pub columns: u32,
+    /// Height of a row.
+    pub row_height: f32,
+    /// Space between columns and between rows.
+    pub spacing: f32,
+    /// Height of the strip along the top of cards which moves them when dragged.
+    pub handle_height: f32,
+    /// Size of the bottom-right corner of cards which resizes them when dragged.
+    pub resize_handle: f32,
+}
+
+impl Dashboard {
+    /// Returns the width of a column within a dashboard `width` wide.
+    pub fn column_width(&self, width: f32) -> f32 {
+        let columns = self.columns.max(1) as f32;
+        ((width - self.spacing * (columns - 1.0)) / columns).max(0.0)
+    }
+
+    /// Returns the rectangle of `cell` within a dashboard at `rect`.
+    pub fn cell_rect(&self, rect: AbsoluteRect, cell: DashboardCell) -> AbsoluteRect {
+        let column_width = self.column_width(rect.size.width);
+        AbsoluteRect::new(
+            AbsolutePoint::new(
+                rect.origin.x + cell.column as f32 * (column_width + self.spacing),
+                rect.origin.y + cell.row as f32 * (self.row_height + self.spacing),
+            ),
+            Size::new(
+                cell.width as f32 * column_width + (cell.width.max(1) - 1) as f32 * self.spacing,
+                cell.height as f32 * self.row_height
+                    + (cell.height.max(1) - 1) as f32 * self.spacing,
+            )
+            .cast_unit(),
+        )
+    }
+
+    /// Returns how many whole columns and rows `delta` spans within a dashboard `width` wide, rounded to the nearest.
+    fn cell_delta(
+        &self,
+        width: f32,
+        delta: reclutch::euclid::Vector2D<f32, AbsoluteUnit>,
+    ) -> (i64, i64) {
+        let column_step = self.column_width(width) + self.spacing;
+        let row_step = self.row_height + self.spacing;
+        (
+            if column_step > 0.0 { (delta.x / column_step).round() as i64 } else { 0 },
+            if row_step > 0.0 { (delta.y / row_step).round() as i64 } else { 0 },
+        )
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Dashboard
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = DashboardWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Dashboard
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(_theme: &dyn draw::Theme) -> Self {
+        Dashboard {
+            columns: 4,
+            row_height: 160.0,
+            spacing: 12.0,
+            handle_height: 32.0,
+            resize_handle: 16.0,
+        }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, u_aux: &mut U) -> DashboardWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            DashboardWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.dirty = true; } }
+        };
+
+        graph = graph.add("drag", drag_handler::<U, G>().bind(u_aux.window_queue()));
+
+        DashboardWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+
+            cards: IndexMap::new(),
+            next_card_id: 0,
+            drag: None,
+            dirty: true,
+        }
+        .build()
+    }
+}
+
+/// Moves and resizes cards as they're dragged.
+fn drag_handler<U, G>() -> vg::UnboundQueueHandler<DashboardWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        DashboardWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            // Cards receive the press rather than the dashboard, so occlusion isn't checked.
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                *button == base::MouseButton::Left && obj.drag_mode_at(*pos).is_some()
+            }) {
+                if let Some((id, mode)) = obj.drag_mode_at(*pos) {
+                    obj.drag = Some(Drag {
+                        id,
+                        mode,
+                        start: *pos,
+                        original: obj.cards.values().map(|card| (card.id, card.item.cell)).collect(),
+                    });
+                    let key = obj.cards[&id].item.key.clone();
+                    obj.event_queue.emit_owned(DashboardEvent::DragStart(key));
+                }
+            }
+        }
+
+        mouse_move => {
+            if let Some(drag) = obj.drag.take() {
+                obj.drag_to(&drag, event.get().0);
+                obj.drag = Some(drag);
+            }
+        }
+
+        mouse_release => {
+            if event.get().1 == base::MouseButton::Left {
+                if let Some(drag) = obj.drag.take() {
+                    let changed = drag
+                        .original
+                        .iter()
+                        .any(|(id, cell)| obj.cards.get(id).map(|card| card.item.cell != *cell).unwrap_or(false));
+                    if changed {
+                        obj.event_queue.emit_owned(DashboardEvent::Rearrange(obj.layout()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for DashboardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.dirty = true;
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Abstract layout widget which arranges children as drag-rearrangeable cards in a grid (see `Dashboard`)."]
+    pub struct DashboardWidget {
+        widget::MAX,
+
+        <DashboardEvent> EventQueue,
+        <Dashboard> State,
+
+        {
+            cards: IndexMap<u64, ChildData>,
+            next_card_id: u64,
+            drag: Option<Drag>,
+            dirty: bool,
+        }
+    }
+}
+
+impl<U, G> DashboardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Returns the current arrangement of the cards, in the order they were pushed.
+    pub fn layout(&self) -> DashboardLayout {
+        DashboardLayout { cards: self.cards.values().map(|card| card.item.clone()).collect() }
+    }
+
+    /// Rearranges the cards as described by `layout`, e.g. to restore a saved layout.
+    ///
+    /// Cards which aren't in the layout keep their cells, and keys in the layout without a card are ignored.
+    pub fn set_layout(&mut self, layout: &DashboardLayout) {
+        for card in self.cards.values_mut() {
+            if let Some(cell) = layout.cell(&card.item.key) {
+                card.item.cell = cell;
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Returns the key of the card being dragged, if any.
+    pub fn dragged_card(&self) -> Option<&str> {
+        let id = self.drag.as_ref()?.id;
+        self.cards.get(&id).map(|card| card.item.key.as_str())
+    }
+
+    /// Returns the card and how it's dragged from `pos`, if `pos` is over the handle of a card.
+    fn drag_mode_at(&self, pos: AbsolutePoint) -> Option<(u64, DragMode)> {
+        // Later cards are drawn above earlier cards.
+        self.cards.values().rev().find_map(|card| {
+            let rect = card.rect;
+            if !rect.contains(pos) {
+                None
+            } else if pos.x >= rect.max_x() - self.data.resize_handle
+                && pos.y >= rect.max_y() - self.data.resize_handle
+            {
+                Some((card.id, DragMode::Resize))
+            } else if pos.y < rect.origin.y + self.data.handle_height {
+                Some((card.id, DragMode::Move))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Moves or resizes the dragged card by the whole cells the cursor has moved, then pushes the other cards
+    /// out of its way (from where they were when the drag started).
+    fn drag_to(&mut self, drag: &Drag, pos: AbsolutePoint) {
+        let columns = self.data.columns.max(1);
+        let (columns_moved, rows_moved) =
+            self.data.cell_delta(self.rect.size.width, pos - drag.start);
+        let start = match drag.original.iter().find(|(id, _)| *id == drag.id) {
+            Some((_, cell)) => *cell,
+            None => return,
+        };
+
+        let offset = |value: u32, delta: i64| (value as i64 + delta).max(0) as u32;
+        let mut cell = start;
+        match drag.mode {
+            DragMode::Move => {
+                cell.column =
+                    offset(start.column, columns_moved).min(columns.saturating_sub(start.width));
+                cell.row = offset(start.row, rows_moved);
+            }
+            DragMode::Resize => {
+                cell.width = offset(start.width, columns_moved)
+                    .max(1)
+                    .min(columns - start.column.min(columns - 1));
+                cell.height = offset(start.height, rows_moved).max(1);
+            }
+        }
+
+        let mut cells: Vec<(u64, DashboardCell)> = drag.original.clone();
+        let fixed = match cells.iter().position(|(id, _)| *id == drag.id) {
+            Some(fixed) => fixed,
+            None => return,
+        };
+        cells[fixed].1 = cell;
+        resolve_collisions(cells.iter_mut().map(|(_, cell)| cell).collect(), fixed);
+
+        for (id, cell) in cells {
+            if let Some(card) = self.cards.get_mut(&id) {
+                if card.item.cell != cell {
+                    card.item.cell = cell;
+                    self.dirty = true;
+                }
+            }
+        }
+    }
+
+    fn resize_to_fit(&mut self) {
+        let rows = self.cards.values().map(|card| card.item.cell.bottom()).max().unwrap_or(0);
+        let height = if rows > 0 {
+            rows as f32 * self.data.row_height + (rows - 1) as f32 * self.data.spacing
+        } else {
+            0.0
+        };
+        self.set_size(Size::new(self.rect.size.width, height));
+    }
+}
+
+impl<U, G> base::Layout for DashboardWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type PushData = DashboardItem;
+
+    fn push(&mut self, data: Option<DashboardItem>, child: &mut impl base::LayableWidget) {
+        self.dirty = true;
+
+        let id = self.next_card_id;
+        self.next_card_id += 1;
+
+        let evq = BidirSingleEventQueue::new();
+
+        child.listen_to_layout(base::WidgetLayoutEventsInner { id, evq: evq.secondary() });
+
+        let rect = child.abs_rect();
+
+        // Cards without an item are keyed by their ID, and placed below the other cards.
+        let item = data.unwrap_or_else(|| {
+            let row = self.cards.values().map(|card| card.item.cell.bottom()).max().unwrap_or(0);
+            DashboardItem::new(id.to_string(), DashboardCell::new(0, row, 1, 1))
+        });
+
+        self.cards.insert(
+            id,
+            ChildData {
+                item,
+                evq,
+                drop_listener: child.drop_event().listen(),
+                rect,
+                original_rect: rect,
+                id,
+            },
+        );
+
+        self.resize_to_fit();
+    }
+
+    fn remove(&mut self, child: &mut impl base::LayableWidget, restore_original: bool) {
+        if let Some(data) = child.layout_id().and_then(|id| self.cards.remove(&id)) {
+            child.listen_to_layout(None);
+            if restore_original {
+                child.set_ctxt_rect(data.original_rect);
+            }
+            self.dirty = true;
+        }
+    }
+}
+
+impl<U, G> Widget for DashboardWidget<U, G>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.dirty = true;
+        }
+
+        {
+            let mut removals = Vec::new();
+            let dirty = &mut self.dirty;
+            for (_, data) in &mut self.cards {
+                if !data.drop_listener.peek().is_empty() {
+                    removals.push(data.id);
+                    *dirty = true;
+                    continue;
+                }
+
+                // The grid decides the rectangles of cards, so their own changes are discarded.
+                if data.evq.retrieve_newest().is_some() {
+                    *dirty = true;
+                }
+            }
+            for removal in removals {
+                self.cards.remove(&removal);
+            }
+        }
+
+        if self.dirty {
+            self.resize_to_fit();
+            let abs_rect = self.abs_rect();
+            for (_, data) in &mut self.cards {
+                let rect = self.data.cell_rect(abs_rect, data.item.cell);
+                if rect != data.rect {
+                    data.evq.emit_owned(rect);
+                    data.rect = rect;
+                }
+            }
+
+            let rects: Vec<AbsoluteRect> = self.cards.values().map(|data| data.rect).collect();
+            check_arrangement(
+                self as *const Self as usize,
+                base::WidgetChildren::widget_kind(self),
+                abs_rect,
+                &rects,
+            );
+
+            self.dirty = false;
+        }
+    }
+}
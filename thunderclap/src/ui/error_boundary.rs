@@ -0,0 +1,383 @@
+use {
+    crate::{
+        base::{self, Movable, Repaintable, WidgetChildren},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{
+        any::Any,
+        cell::RefCell,
+        panic::{self, AssertUnwindSafe},
+        rc::Rc,
+    },
+};
+
+/// Events emitted by an error boundary.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum ErrorBoundaryEvent {
+    /// Emitted when the child fails, either by panicking or by reporting an error.
+    #[event_key(error)]
+    Error(String),
+    /// Emitted when the child is rebuilt after a failure.
+    #[event_key(retry)]
+    Retry,
+}
+
+/// Handle given to the child of an error boundary, through which the child can report errors.
+///
+/// Reporting an error has the same effect as the child panicking.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorReporter(Rc<RefCell<Option<String>>>);
+
+impl ErrorReporter {
+    /// Reports an error, causing the error boundary to swap in the fallback view.
+    pub fn report(&self, error: impl Into<String>) {
+        self.0.borrow_mut().get_or_insert_with(|| error.into());
+    }
+
+    fn take(&self) -> Option<String> {
+        self.0.borrow_mut().take()
+    }
+}
+
+/// Boxed child subtree of an error boundary.
+pub type BoundaryChild<U, G> =
+    Box<dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>>;
+
+/// Isolates a child subtree so that if it fails, a fallback view with a retry button is shown instead
+/// of taking down the whole UI.
+///
+/// The child fails if its `update` or `draw` panics, or if it reports an error through its `ErrorReporter`.
+/// Retrying rebuilds the child from the factory given to [`set_child`](struct.ErrorBoundaryWidget.html#method.set_child).
+///
+/// Note that the child is updated and drawn by the error boundary itself (so that panics can be caught),
+/// hence it isn't returned from `children`/`children_mut`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorBoundary {
+    /// Heading of the fallback view.
+    pub title: String,
+    /// Text of the retry button.
+    pub retry_text: String,
+    /// Whether the error message is displayed in the fallback view.
+    pub show_details: bool,
+    pub typeface: draw::TypefaceStyle,
+    pub foreground: Color,
+    pub background: Color,
+    /// Background of the retry button.
+    pub button_background: Color,
+    pub focus: Color,
+    pub contrast: draw::ThemeContrast,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for ErrorBoundary
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = ErrorBoundaryWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for ErrorBoundary
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        ErrorBoundary {
+            title: "Something went wrong".into(),
+            retry_text: "Retry".into(),
+            show_details: true,
+            typeface: data.typography.button.clone(),
+            foreground: data.scheme.over_control_inset,
+            background: data.scheme.control_inset,
+            button_background: data.scheme.control_outset,
+            focus: data.scheme.focus,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> ErrorBoundaryWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            ErrorBoundaryWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.command_group.repaint(); } }
+        };
+
+        graph = graph.add(
+            "handler",
+            ui::basic_interaction_handler::<ErrorBoundaryWidget<U, G>, U>()
+                .bind(u_aux.window_queue()),
+        );
+
+        graph = graph.add(
+            "focus",
+            ui::focus_handler::<ErrorBoundaryWidget<U, G>, U>()
+                .bind(u_aux.focus_tracker().focus_queue()),
+        );
+
+        ErrorBoundaryWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+            painter: theme.error_boundary(),
+
+            child: None,
+            factory: None,
+            reporter: Default::default(),
+            error: None,
+            retry: false,
+            clip_list: Default::default(),
+            interaction: state::InteractionState::empty(),
+            focus: u_aux.focus_tracker_mut().register(),
+        }
+        .build()
+    }
+}
+
+/// Extracts the message from a panic payload.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Ok(message) = payload.downcast::<String>() {
+        *message
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+impl<U, G> ui::InteractiveWidget for ErrorBoundaryWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline(always)]
+    fn interaction(&mut self) -> &mut state::InteractionState {
+        &mut self.interaction
+    }
+
+    /// Bounds of the retry button.
+    #[inline]
+    fn mouse_bounds(&self) -> RelativeRect {
+        self.painter.mouse_hint(self.rect)
+    }
+
+    /// The retry button only exists while the fallback view is shown.
+    #[inline(always)]
+    fn disabled(&self) -> bool {
+        self.error.is_none()
+    }
+
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        Some(self.focus.id())
+    }
+
+    fn on_interaction_event(&mut self, event: ui::InteractionEvent) {
+        self.repaint();
+        if let ui::InteractionEvent::Released(_) = event {
+            // The child is rebuilt in `update`, where the update auxiliary is available.
+            self.retry = true;
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::ErrorBoundaryState> for ErrorBoundaryWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::ErrorBoundaryState {
+        state::ErrorBoundaryState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            error: self.error.clone().unwrap_or_default(),
+            interaction: self.interaction,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.update_child_position();
+        self.focus.set_rect(self.abs_rect());
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct ErrorBoundaryWidget {
+        widget::MAX,
+
+        <ErrorBoundaryEvent> EventQueue,
+        <ErrorBoundary> State,
+        <state::ErrorBoundaryState> Painter,
+
+        {
+            child: Option<BoundaryChild<U, G>>,
+            factory: Option<Box<dyn FnMut(&mut U, ErrorReporter) -> BoundaryChild<U, G>>>,
+            reporter: ErrorReporter,
+            error: Option<String>,
+            retry: bool,
+            clip_list: base::ClipList,
+            interaction: state::InteractionState,
+            focus: base::FocusHandle,
+        },
+    }
+}
+
+impl<U, G> ErrorBoundaryWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Sets the factory of the child subtree and builds the child.
+    ///
+    /// The factory is invoked again whenever the user retries after a failure.
+    /// The `ErrorReporter` given to the factory can be kept by the child to report errors.
+    pub fn set_child<C>(
+        &mut self,
+        mut factory: impl FnMut(&mut U, ErrorReporter) -> C + 'static,
+        u_aux: &mut U,
+    ) where
+        C: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+            + 'static,
+    {
+        self.factory = Some(Box::new(move |aux, reporter| Box::new(factory(aux, reporter)) as _));
+        self.rebuild(u_aux);
+    }
+
+    /// Returns the error which caused the fallback view to be shown, if any.
+    #[inline]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_ref().map(|error| error.as_str())
+    }
+
+    /// Returns the child, if it hasn't failed.
+    #[inline]
+    pub fn child(&self) -> Option<&BoundaryChild<U, G>> {
+        self.child.as_ref()
+    }
+
+    /// Returns the child mutably, if it hasn't failed.
+    #[inline]
+    pub fn child_mut(&mut self) -> Option<&mut BoundaryChild<U, G>> {
+        self.child.as_mut()
+    }
+
+    /// Drops the child and shows the fallback view.
+    pub fn fail(&mut self, error: impl Into<String>) {
+        let error = error.into();
+        self.child = None;
+        self.clip_list = Default::default();
+        self.error = Some(error.clone());
+        self.interaction = state::InteractionState::empty();
+        self.command_group.repaint();
+        self.event_queue.emit_owned(ErrorBoundaryEvent::Error(error));
+    }
+
+    /// The child isn't listed in `children_mut`, so its parent position has to be propagated manually.
+    fn update_child_position(&mut self) {
+        let position = self.abs_rect().origin;
+        if let Some(child) = &mut self.child {
+            child.set_parent_position(position);
+            let child_position = child.position();
+            child.set_ctxt_position(child_position.into());
+        }
+    }
+
+    fn rebuild(&mut self, u_aux: &mut U) {
+        let factory = match &mut self.factory {
+            Some(factory) => factory,
+            None => return,
+        };
+
+        self.reporter = ErrorReporter::default();
+        let reporter = self.reporter.clone();
+        match panic::catch_unwind(AssertUnwindSafe(|| factory(u_aux, reporter))) {
+            Ok(child) => {
+                self.child = Some(child);
+                self.update_child_position();
+                self.error = None;
+                self.command_group.repaint();
+            }
+            Err(payload) => self.fail(panic_message(payload)),
+        }
+    }
+}
+
+impl<U, G> Widget for ErrorBoundaryWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+
+        if self.retry {
+            self.retry = false;
+            self.rebuild(aux);
+            if self.error.is_none() {
+                self.event_queue.emit_owned(ErrorBoundaryEvent::Retry);
+            }
+        }
+
+        if let Some(child) = &mut self.child {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| child.update(aux)));
+            if let Err(payload) = result {
+                self.fail(panic_message(payload));
+            } else if let Some(error) = self.reporter.take() {
+                self.fail(error);
+            }
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) {
+        if let Some(child) = &mut self.child {
+            let clip_list = &mut self.clip_list;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                clip_list.draw(&mut **child, display, aux)
+            }));
+
+            // Command groups of the failed child are no longer maintained, so they're discarded by the display.
+            if let Err(payload) = result {
+                self.fail(panic_message(payload));
+            }
+        }
+
+        if self.error.is_some() {
+            let state = self.derive_state();
+            let painter = &mut self.painter;
+            self.command_group.push_with(
+                display,
+                || painter.draw(state),
+                Default::default(),
+                None,
+                None,
+            );
+        }
+    }
+}
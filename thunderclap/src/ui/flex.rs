@@ -0,0 +1,496 @@
+use {
+    super::Align,
+    crate::{base, draw, geom::*, ui},
+    indexmap::IndexMap,
+    reclutch::{
+        display::{DisplayCommand, Rect, Size},
+        event::{bidir_single::Queue as BidirSingleEventQueue, RcEventListener},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Direction of the main axis of a `Flex`, along which children are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlexDirection {
+    /// Left to right.
+    Row,
+    /// Right to left.
+    RowReverse,
+    /// Top to bottom.
+    Column,
+    /// Bottom to top.
+    ColumnReverse,
+}
+
+impl FlexDirection {
+    #[inline]
+    fn is_row(self) -> bool {
+        self == FlexDirection::Row || self == FlexDirection::RowReverse
+    }
+
+    #[inline]
+    fn is_reverse(self) -> bool {
+        self == FlexDirection::RowReverse || self == FlexDirection::ColumnReverse
+    }
+}
+
+/// Whether children of a `Flex` which overflow the main axis are moved onto a new line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlexWrap {
+    /// All the children are kept on a single line, shrinking them if needed.
+    NoWrap,
+    /// Overflowing children are moved onto a new line, after the previous line on the cross axis.
+    Wrap,
+    /// Overflowing children are moved onto a new line, before the previous line on the cross axis.
+    WrapReverse,
+}
+
+/// How free space on the main axis is distributed around children of a `Flex`.
+/// Only applies if no children on a line can grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JustifyContent {
+    /// Children are packed at the start of the line.
+    Start,
+    /// Children are packed at the end of the line.
+    End,
+    /// Children are packed in the center of the line.
+    Center,
+    /// The first and last children are at the edges, with equal space between the children.
+    SpaceBetween,
+    /// Equal space around each child, so the space at the edges is half the space between children.
+    SpaceAround,
+    /// Equal space between the children and at the edges.
+    SpaceEvenly,
+}
+
+/// Information about how a `Flex` child should be layed out.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct FlexItem {
+    /// How much of the free space on the main axis the child takes up, relative to its siblings.
+    pub grow: f32,
+    /// How much the child shrinks when its line overflows,
+    /// relative to its siblings (weighted by the basis).
+    pub shrink: f32,
+    /// Size of the child along the main axis before growing or shrinking.
+    /// If `None`, the size of the child is used.
+    pub basis: Option<f32>,
+    /// Overrides the `align_items` of the `Flex` for this child.
+    pub align_self: Option<Align>,
+}
+
+impl FlexItem {
+    /// Sets the `grow` value.
+    pub fn grow(self, grow: f32) -> FlexItem {
+        FlexItem { grow, ..self }
+    }
+
+    /// Sets the `shrink` value.
+    pub fn shrink(self, shrink: f32) -> FlexItem {
+        FlexItem { shrink, ..self }
+    }
+
+    /// Sets the `basis` value.
+    pub fn basis(self, basis: f32) -> FlexItem {
+        FlexItem { basis: Some(basis), ..self }
+    }
+
+    /// Sets the `align_self` value.
+    pub fn align_self(self, align: Align) -> FlexItem {
+        FlexItem { align_self: Some(align), ..self }
+    }
+}
+
+#[derive(Debug)]
+struct ChildData {
+    data: FlexItem,
+    evq: BidirSingleEventQueue<AbsoluteRect, AbsoluteRect>,
+    drop_listener: RcEventListener<base::DropEvent>,
+    /// Size of the child as decided by the child itself (before growing, shrinking or stretching).
+    size: Size,
+    /// Rectangle last assigned to the child.
+    rect: AbsoluteRect,
+    original_rect: AbsoluteRect,
+    id: u64,
+}
+
+/// Range of children which are layed out on the same line, and the cross size of the line.
+#[derive(Debug, Clone, Copy)]
+struct FlexLine {
+    start: usize,
+    end: usize,
+    cross: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flex {
+    pub direction: FlexDirection,
+    pub wrap: FlexWrap,
+    pub justify_content: JustifyContent,
+    pub align_items: Align,
+    /// Spacing between adjacent children and between lines.
+    pub gap: f32,
+    /// `grow` value of children pushed without a `FlexItem`.
+    pub grow: f32,
+    /// `shrink` value of children pushed without a `FlexItem`.
+    pub shrink: f32,
+    /// Binds the rectangle to the window, so that the layout fills the window and re-runs whenever it's resized.
+    /// This is intended for the root layout.
+    pub fill_window: bool,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Flex
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = FlexWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Flex
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(_theme: &dyn draw::Theme) -> Self {
+        Flex {
+            direction: FlexDirection::Row,
+            wrap: FlexWrap::NoWrap,
+            justify_content: JustifyContent::Start,
+            align_items: Align::Stretch,
+            gap: 0.0,
+            grow: 0.0,
+            shrink: 1.0,
+            fill_window: false,
+        }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, u_aux: &mut U) -> FlexWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            FlexWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.dirty = true; } }
+        };
+
+        graph = graph.add(
+            "window",
+            ui::window_fill_handler::<FlexWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+
+        FlexWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+
+            rects: IndexMap::new(),
+            next_rect_id: 0,
+            dirty: true,
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for FlexWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.dirty = true;
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+impl<U, G> ui::WindowFill for FlexWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn fill_window(&self) -> bool {
+        self.data.fill_window
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Abstract layout widget which arranges children following the CSS flexbox model (see `Flex` and `FlexItem`)."]
+    #[doc = "Unlike the stacks, the flex widget doesn't resize to fit its children; children are layed out within its rectangle."]
+    pub struct FlexWidget {
+        widget::MAX,
+
+        <Flex> State,
+
+        {
+            rects: IndexMap<u64, ChildData>,
+            next_rect_id: u64,
+            dirty: bool,
+        },
+    }
+}
+
+/// Returns the leading space and the space between children on a line with `free` space.
+fn justify(justify: JustifyContent, free: f32, count: usize) -> (f32, f32) {
+    let free = free.max(0.0);
+    let count = count as f32;
+    match justify {
+        JustifyContent::Start => (0.0, 0.0),
+        JustifyContent::End => (free, 0.0),
+        JustifyContent::Center => (free / 2.0, 0.0),
+        JustifyContent::SpaceBetween if count > 1.0 => (0.0, free / (count - 1.0)),
+        JustifyContent::SpaceBetween => (0.0, 0.0),
+        JustifyContent::SpaceAround => (free / count / 2.0, free / count),
+        JustifyContent::SpaceEvenly => (free / (count + 1.0), free / (count + 1.0)),
+    }
+}
+
+impl<U, G> FlexWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Returns the (main, cross) components of a size.
+    fn axes(&self, size: Size) -> (f32, f32) {
+        if self.data.direction.is_row() {
+            (size.width, size.height)
+        } else {
+            (size.height, size.width)
+        }
+    }
+
+    fn relayout(&mut self) {
+        let abs_rect = self.abs_rect();
+        let (main_size, cross_size) = self.axes(abs_rect.size.cast_unit());
+        let gap = self.data.gap;
+
+        // (base main size, cross size) of each child
+        let items: Vec<_> = self
+            .rects
+            .values()
+            .map(|child| {
+                let (main, cross) = self.axes(child.size);
+                (child.data.basis.unwrap_or(main), cross)
+            })
+            .collect();
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut used = 0.0;
+        for (i, (main, _)) in items.iter().enumerate() {
+            let advance = if i == start { *main } else { gap + *main };
+            if self.data.wrap != FlexWrap::NoWrap && i > start && used + advance > main_size {
+                lines.push(FlexLine { start, end: i, cross: 0.0 });
+                start = i;
+                used = *main;
+            } else {
+                used += advance;
+            }
+        }
+        if start < items.len() {
+            lines.push(FlexLine { start, end: items.len(), cross: 0.0 });
+        }
+
+        for line in &mut lines {
+            line.cross = if self.data.wrap == FlexWrap::NoWrap {
+                cross_size
+            } else {
+                items[line.start..line.end].iter().map(|(_, cross)| *cross).fold(0.0, f32::max)
+            };
+        }
+
+        let reverse_lines = self.data.wrap == FlexWrap::WrapReverse;
+        let reverse_main = self.data.direction.is_reverse();
+        let is_row = self.data.direction.is_row();
+        let align_items = self.data.align_items;
+        let justify_content = self.data.justify_content;
+
+        let mut children: Vec<_> = self.rects.values_mut().collect();
+        let mut cross_offset = 0.0;
+        for line in &lines {
+            let line_items = &items[line.start..line.end];
+            let line_children = &mut children[line.start..line.end];
+
+            let count = line_items.len();
+            let total: f32 = line_items.iter().map(|(main, _)| *main).sum();
+            let free = main_size - total - gap * (count - 1) as f32;
+
+            let total_grow: f32 = line_children.iter().map(|child| child.data.grow).sum();
+            let total_shrink: f32 = line_children
+                .iter()
+                .zip(line_items)
+                .map(|(child, (main, _))| child.data.shrink * *main)
+                .sum();
+
+            let mains: Vec<f32> = line_children
+                .iter()
+                .zip(line_items)
+                .map(|(child, (main, _))| {
+                    if free > 0.0 && total_grow > 0.0 {
+                        *main + free * child.data.grow / total_grow
+                    } else if free < 0.0 && total_shrink > 0.0 {
+                        (*main + free * child.data.shrink * *main / total_shrink).max(0.0)
+                    } else {
+                        *main
+                    }
+                })
+                .collect();
+
+            let remaining = if free > 0.0 && total_grow > 0.0 { 0.0 } else { free };
+            let (leading, between) = justify(justify_content, remaining, count);
+
+            let line_cross =
+                if reverse_lines { cross_size - cross_offset - line.cross } else { cross_offset };
+
+            let mut main_offset = leading;
+            for ((child, (_, cross)), main) in line_children.iter_mut().zip(line_items).zip(mains) {
+                let (cross, cross_pos) = match child.data.align_self.unwrap_or(align_items) {
+                    Align::Begin => (*cross, 0.0),
+                    Align::Middle => (*cross, (line.cross - *cross) / 2.0),
+                    Align::End => (*cross, line.cross - *cross),
+                    Align::Stretch => (line.cross, 0.0),
+                };
+
+                let main_pos =
+                    if reverse_main { main_size - main_offset - main } else { main_offset };
+
+                let rect = if is_row {
+                    Rect::new(
+                        abs_rect.origin.cast_unit() + Size::new(main_pos, line_cross + cross_pos),
+                        Size::new(main, cross),
+                    )
+                } else {
+                    Rect::new(
+                        abs_rect.origin.cast_unit() + Size::new(line_cross + cross_pos, main_pos),
+                        Size::new(cross, main),
+                    )
+                }
+                .cast_unit();
+
+                if rect != child.rect {
+                    child.evq.emit_owned(rect);
+                    child.rect = rect;
+                }
+
+                main_offset += main + gap + between;
+            }
+
+            cross_offset += line.cross + gap;
+        }
+
+        let rects: Vec<AbsoluteRect> = self.rects.values().map(|data| data.rect).collect();
+        check_arrangement(
+            self as *const Self as usize,
+            base::WidgetChildren::widget_kind(self),
+            abs_rect,
+            &rects,
+        );
+    }
+}
+
+impl<U, G> base::Layout for FlexWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type PushData = FlexItem;
+
+    fn push(&mut self, data: Option<FlexItem>, child: &mut impl base::LayableWidget) {
+        self.dirty = true;
+
+        let id = self.next_rect_id;
+        self.next_rect_id += 1;
+
+        let evq = BidirSingleEventQueue::new();
+
+        child.listen_to_layout(base::WidgetLayoutEventsInner { id, evq: evq.secondary() });
+
+        let rect = child.abs_rect();
+
+        self.rects.insert(
+            id,
+            ChildData {
+                data: data.unwrap_or(FlexItem {
+                    grow: self.data.grow,
+                    shrink: self.data.shrink,
+                    basis: None,
+                    align_self: None,
+                }),
+                evq,
+                drop_listener: child.drop_event().listen(),
+                size: rect.size.cast_unit(),
+                rect,
+                original_rect: rect,
+                id,
+            },
+        );
+    }
+
+    fn remove(&mut self, child: &mut impl base::LayableWidget, restore_original: bool) {
+        if let Some(data) = child.layout_id().and_then(|id| self.rects.remove(&id)) {
+            self.dirty = true;
+            child.listen_to_layout(None);
+            if restore_original {
+                child.set_ctxt_rect(data.original_rect);
+            }
+        }
+    }
+}
+
+impl<U, G> Widget for FlexWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.dirty = true;
+        }
+
+        {
+            let mut removals = Vec::new();
+            let dirty = &mut self.dirty;
+            for (_, data) in &mut self.rects {
+                if !data.drop_listener.peek().is_empty() {
+                    removals.push(data.id);
+                    *dirty = true;
+                    continue;
+                }
+
+                if let Some(new_ev) = data.evq.retrieve_newest() {
+                    // Only a change the child made itself affects the layout,
+                    // as opposed to the child acknowledging the rectangle it was assigned.
+                    if new_ev.size != data.rect.size {
+                        data.size = new_ev.size.cast_unit();
+                        *dirty = true;
+                    }
+                    data.rect = new_ev;
+                }
+            }
+            for removal in removals {
+                self.rects.remove(&removal);
+            }
+        }
+
+        if self.dirty {
+            self.relayout();
+            self.dirty = false;
+        }
+    }
+}
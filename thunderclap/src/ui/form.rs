@@ -0,0 +1,1457 @@
+//! Form widget generated from a plain struct, with an editor for each field.
+//!
+//! The struct is reflected through its `Serialize` and `Deserialize` implementations; each field gets a row with
+//! a label (derived from the field name) and an editor (derived from the field type):
+//!
+//! | Field type                        | Editor                                        |
+//! |-----------------------------------|-----------------------------------------------|
+//! | `bool`                            | Checkbox                                      |
+//! | Integers and floating point       | Numeric input                                 |
+//! | `String`, `char`                  | Text area                                     |
+//! | Enum of unit variants             | Button cycling through the variants           |
+//!
+//! Labels and editors can be overridden per field, e.g. to restrict a number to a range or a string to choices:
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Settings {
+//!     volume: f32,
+//!     muted: bool,
+//!     nickname: String,
+//!     quality: Quality,
+//! }
+//!
+//! let form = Form::from_theme(theme)
+//!     .range("volume", 0.0, 1.0)
+//!     .step("volume", 0.05)
+//!     .label("nickname", "Display name")
+//!     .construct(base::Observed::new(settings), theme, u_aux)?;
+//! ```
+//!
+//! Edits are written back to the `Observed` value, whose `on_change` is emitted as with any other change;
+//! conversely, changing the value through `FormWidget::value_mut` updates the editors.
+//!
+//! Fields can be validated as they're edited (see `FormWidget::set_validator`), in which case the form shows
+//! beneath the editor that asynchronous rules are pending, or the message of the rule the value breaks.
+//!
+//! The form keeps a snapshot of the fields as they were when it was constructed; fields which differ from the
+//! snapshot are modified, and their labels are marked as such. Modified fields can be reverted to the snapshot
+//! (`FormWidget::revert_field`, `FormWidget::revert_all`), and the snapshot moved on once the value is saved
+//! (`FormWidget::mark_clean`). Reverts are emitted alongside the value they replaced, so that they can be recorded
+//! in an undo history and undone through `FormWidget::set_field`.
+
+use {
+    crate::{
+        base::{self, Repaintable, Resizable, WidgetChildren},
+        draw,
+        error::FormError,
+        geom::*,
+        ui::{self, WidgetConstructor},
+        validate::{Validation, Validator, Validity},
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, DisplayText, GraphicsDisplay, Rect, Size},
+        event::RcEventListener,
+        prelude::*,
+        verbgraph as vg,
+    },
+    serde::{
+        de::{self, value::Error as ValueError, DeserializeOwned, IntoDeserializer},
+        ser::{self, Impossible},
+        Serialize,
+    },
+    std::{cell::RefCell, collections::BTreeMap},
+};
+
+/// Value of a field of a reflected struct.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    /// Name of a unit enum variant.
+    Variant(String),
+}
+
+impl FieldValue {
+    /// Returns the value as a string, if it's text or an enum variant.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FieldValue::Text(text) | FieldValue::Variant(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a number, if it's an integer or floating point.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            FieldValue::Integer(value) => Some(*value as _),
+            FieldValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Field of a reflected struct (see `reflect`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReflectedField {
+    pub name: &'static str,
+    pub value: FieldValue,
+    /// Names of the variants, if the field is an enum.
+    pub variants: Option<&'static [&'static str]>,
+}
+
+/// Returns the fields of `value`, which has to be a struct of fields of the types listed in the
+/// [module documentation](index.html), in declaration order.
+pub fn reflect<T: Serialize + DeserializeOwned>(
+    value: &T,
+) -> Result<Vec<ReflectedField>, FormError> {
+    let values = value.serialize(StructReflector).map_err(reflect_error)?;
+
+    // Enum variants are only known to `Deserialize`, which is given the variants as it deserializes the value back.
+    let variants = RefCell::new(BTreeMap::new());
+    deserialize_fields::<T>(&values, Some(&variants)).map_err(reflect_error)?;
+    let variants = variants.into_inner();
+
+    Ok(values
+        .into_iter()
+        .map(|(name, value)| ReflectedField { name, value, variants: variants.get(name).copied() })
+        .collect())
+}
+
+/// Builds a struct from the values of its fields, as returned by `reflect`.
+pub fn rebuild<T: DeserializeOwned>(fields: &[ReflectedField]) -> Result<T, FormError> {
+    let values: Vec<_> = fields.iter().map(|field| (field.name, field.value.clone())).collect();
+    deserialize_fields(&values, None).map_err(reflect_error)
+}
+
+fn reflect_error(error: ValueError) -> FormError {
+    FormError::ReflectError(error.to_string())
+}
+
+type Variants = RefCell<BTreeMap<&'static str, &'static [&'static str]>>;
+
+fn deserialize_fields<T: DeserializeOwned>(
+    values: &[(&'static str, FieldValue)],
+    variants: Option<&Variants>,
+) -> Result<T, ValueError> {
+    T::deserialize(de::value::MapDeserializer::new(values.iter().map(|&(name, ref value)| {
+        (name, ValueDeserializer { name, value: value.clone(), variants })
+    })))
+}
+
+/// Serializes a struct into the values of its fields.
+struct StructReflector;
+
+/// Serializes the value of a single field.
+struct ValueReflector;
+
+/// Fields serialized so far by `StructReflector`.
+struct FieldsReflector(Vec<(&'static str, FieldValue)>);
+
+fn not_a_struct<T>() -> Result<T, ValueError> {
+    Err(ser::Error::custom("the form value has to be a struct"))
+}
+
+fn unsupported<T>() -> Result<T, ValueError> {
+    Err(ser::Error::custom("unsupported field type"))
+}
+
+impl ser::Serializer for StructReflector {
+    type Ok = Vec<(&'static str, FieldValue)>;
+    type Error = ValueError;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = FieldsReflector;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<FieldsReflector, ValueError> {
+        Ok(FieldsReflector(Vec::with_capacity(len)))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, ValueError> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_none(self) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_unit(self) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, ValueError> {
+        not_a_struct()
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ValueError> {
+        not_a_struct()
+    }
+}
+
+impl ser::SerializeStruct for FieldsReflector {
+    type Ok = Vec<(&'static str, FieldValue)>;
+    type Error = ValueError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        let value = value
+            .serialize(ValueReflector)
+            .map_err(|error| ser::Error::custom(format!("field \"{}\": {}", key, error)))?;
+        self.0.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, ValueError> {
+        Ok(self.0)
+    }
+}
+
+impl ser::Serializer for ValueReflector {
+    type Ok = FieldValue;
+    type Error = ValueError;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<FieldValue, ValueError> {
+        Ok(FieldValue::Bool(v))
+    }
+    fn serialize_i8(self, v: i8) -> Result<FieldValue, ValueError> {
+        self.serialize_i64(v as _)
+    }
+    fn serialize_i16(self, v: i16) -> Result<FieldValue, ValueError> {
+        self.serialize_i64(v as _)
+    }
+    fn serialize_i32(self, v: i32) -> Result<FieldValue, ValueError> {
+        self.serialize_i64(v as _)
+    }
+    fn serialize_i64(self, v: i64) -> Result<FieldValue, ValueError> {
+        Ok(FieldValue::Integer(v))
+    }
+    fn serialize_u8(self, v: u8) -> Result<FieldValue, ValueError> {
+        self.serialize_i64(v as _)
+    }
+    fn serialize_u16(self, v: u16) -> Result<FieldValue, ValueError> {
+        self.serialize_i64(v as _)
+    }
+    fn serialize_u32(self, v: u32) -> Result<FieldValue, ValueError> {
+        self.serialize_i64(v as _)
+    }
+    fn serialize_u64(self, v: u64) -> Result<FieldValue, ValueError> {
+        if v > std::i64::MAX as u64 {
+            return Err(ser::Error::custom("integer too large"));
+        }
+        self.serialize_i64(v as _)
+    }
+    fn serialize_f32(self, v: f32) -> Result<FieldValue, ValueError> {
+        self.serialize_f64(v as _)
+    }
+    fn serialize_f64(self, v: f64) -> Result<FieldValue, ValueError> {
+        Ok(FieldValue::Float(v))
+    }
+    fn serialize_char(self, v: char) -> Result<FieldValue, ValueError> {
+        Ok(FieldValue::Text(v.to_string()))
+    }
+    fn serialize_str(self, v: &str) -> Result<FieldValue, ValueError> {
+        Ok(FieldValue::Text(v.to_string()))
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<FieldValue, ValueError> {
+        Ok(FieldValue::Variant(variant.to_string()))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<FieldValue, ValueError> {
+        value.serialize(self)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<FieldValue, ValueError> {
+        unsupported()
+    }
+    fn serialize_none(self) -> Result<FieldValue, ValueError> {
+        unsupported()
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<FieldValue, ValueError> {
+        unsupported()
+    }
+    fn serialize_unit(self) -> Result<FieldValue, ValueError> {
+        unsupported()
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<FieldValue, ValueError> {
+        unsupported()
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<FieldValue, ValueError> {
+        unsupported()
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, ValueError> {
+        unsupported()
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, ValueError> {
+        unsupported()
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ValueError> {
+        unsupported()
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ValueError> {
+        unsupported()
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, ValueError> {
+        unsupported()
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, ValueError> {
+        unsupported()
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ValueError> {
+        unsupported()
+    }
+}
+
+/// Deserializes the value of a single field, recording the variants of enums.
+struct ValueDeserializer<'a> {
+    name: &'static str,
+    value: FieldValue,
+    variants: Option<&'a Variants>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = ValueError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        match self.value {
+            FieldValue::Bool(value) => visitor.visit_bool(value),
+            FieldValue::Integer(value) => visitor.visit_i64(value),
+            FieldValue::Float(value) => visitor.visit_f64(value),
+            FieldValue::Text(value) | FieldValue::Variant(value) => visitor.visit_string(value),
+        }
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        // Text areas can hold any amount of characters.
+        match self.value.as_str().map(|text| text.chars()) {
+            Some(mut chars) => match (chars.next(), chars.next()) {
+                (Some(c), None) => visitor.visit_char(c),
+                _ => Err(de::Error::custom("expected a single character")),
+            },
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        if let Some(recorded) = self.variants {
+            recorded.borrow_mut().insert(self.name, variants);
+        }
+        match self.value.as_str() {
+            Some(variant) => visitor.visit_enum(variant.into_deserializer()),
+            None => Err(de::Error::custom("expected an enum variant")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, ValueError> for ValueDeserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Returns a label for a field named `name` in snake case, e.g. `"Max volume"` for `"max_volume"`.
+pub fn field_label(name: &str) -> String {
+    let mut label = name.trim_matches('_').replace('_', " ");
+    if let Some(first) = label.chars().next() {
+        label.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+    }
+    label
+}
+
+/// Editor of a field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldEditor {
+    /// Checkbox; only for `bool`.
+    Toggle,
+    /// Numeric input; for integers (which are rounded) and floating point.
+    Number { min: f32, max: f32, step: f32 },
+    /// Single-line text area; for strings.
+    Text,
+    /// Button which cycles through choices when pressed; for enums and strings.
+    Choice(Vec<String>),
+}
+
+impl FieldEditor {
+    /// Returns the editor of a field when it isn't overridden.
+    pub fn infer(field: &ReflectedField) -> Self {
+        match (&field.value, field.variants) {
+            (FieldValue::Bool(_), _) => FieldEditor::Toggle,
+            (FieldValue::Integer(_), _) => {
+                FieldEditor::Number { min: std::f32::MIN, max: std::f32::MAX, step: 1.0 }
+            }
+            (FieldValue::Float(_), _) => {
+                FieldEditor::Number { min: std::f32::MIN, max: std::f32::MAX, step: 0.1 }
+            }
+            (_, Some(variants)) => {
+                FieldEditor::Choice(variants.iter().map(|variant| variant.to_string()).collect())
+            }
+            (FieldValue::Text(_), None) | (FieldValue::Variant(_), None) => FieldEditor::Text,
+        }
+    }
+}
+
+/// Overrides of how a field is presented in a form.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldOptions {
+    /// Label of the field, instead of one derived from the field name (see `field_label`).
+    pub label: Option<String>,
+    /// Editor of the field, instead of one derived from the field type (see `FieldEditor::infer`).
+    pub editor: Option<FieldEditor>,
+    /// Whether the field is left out of the form, in which case it keeps its value.
+    pub hidden: bool,
+}
+
+/// Events emitted by a form.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum FormEvent {
+    /// The user has changed the field with a name, which has been written to the value.
+    #[event_key(user_modify)]
+    UserModify(String),
+    /// The user has entered a value the field with a name can't hold (e.g. a negative number for an unsigned integer),
+    /// which has been reverted.
+    #[event_key(reject)]
+    Reject(String),
+    /// The validity of the field with a name has changed (see `FormWidget::set_validator`).
+    #[event_key(validity_change)]
+    ValidityChange(String, Validity),
+    /// The field with a name has been reverted to its initial value, replacing the value it holds
+    /// (which `FormWidget::set_field` restores, e.g. to undo the revert).
+    #[event_key(revert)]
+    Revert(String, FieldValue),
+}
+
+/// Vertical list of labelled editors for the fields of a struct (see the [module documentation](index.html)).
+///
+/// The options of the fields are applied when the form is constructed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Form {
+    /// Options of fields by name.
+    pub fields: BTreeMap<String, FieldOptions>,
+    /// Width of the label column.
+    pub label_width: f32,
+    /// Height of a row.
+    pub row_height: f32,
+    /// Space between rows, as well as between the labels and the editors.
+    pub spacing: f32,
+    /// Color of the messages of invalid fields.
+    pub error: Color,
+    /// Color of the message of fields whose validity is pending.
+    pub pending: Color,
+    /// Message shown while the validity of a field is pending.
+    pub pending_message: String,
+    /// Color of the labels of modified fields.
+    pub modified: Color,
+    /// Appended to the labels of modified fields.
+    pub modified_marker: String,
+}
+
+impl Form {
+    pub fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Form {
+            fields: BTreeMap::new(),
+            label_width: 120.0,
+            row_height: 28.0,
+            spacing: 8.0,
+            error: data.scheme.error,
+            pending: data.scheme.focus,
+            pending_message: "Checking...".into(),
+            modified: data.scheme.primary,
+            modified_marker: " *".into(),
+        }
+    }
+
+    /// Changes the label of a field.
+    pub fn label(mut self, field: &str, label: impl Into<String>) -> Self {
+        self.options(field).label = Some(label.into());
+        self
+    }
+
+    /// Changes the editor of a field.
+    pub fn editor(mut self, field: &str, editor: FieldEditor) -> Self {
+        self.options(field).editor = Some(editor);
+        self
+    }
+
+    /// Restricts a numeric field to a range.
+    pub fn range(mut self, field: &str, min: f32, max: f32) -> Self {
+        match &mut self.options(field).editor {
+            Some(FieldEditor::Number { min: old_min, max: old_max, .. }) => {
+                *old_min = min;
+                *old_max = max;
+            }
+            editor => *editor = Some(FieldEditor::Number { min, max, step: 1.0 }),
+        }
+        self
+    }
+
+    /// Changes the amount a numeric field is nudged by.
+    pub fn step(mut self, field: &str, step: f32) -> Self {
+        match &mut self.options(field).editor {
+            Some(FieldEditor::Number { step: old_step, .. }) => *old_step = step,
+            editor => {
+                *editor = Some(FieldEditor::Number { min: std::f32::MIN, max: std::f32::MAX, step })
+            }
+        }
+        self
+    }
+
+    /// Restricts a string field to choices.
+    pub fn choices<S: Into<String>>(
+        mut self,
+        field: &str,
+        choices: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.options(field).editor =
+            Some(FieldEditor::Choice(choices.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Leaves a field out of the form.
+    pub fn hidden(mut self, field: &str) -> Self {
+        self.options(field).hidden = true;
+        self
+    }
+
+    fn options(&mut self, field: &str) -> &mut FieldOptions {
+        self.fields.entry(field.to_string()).or_default()
+    }
+
+    /// Constructs a form editing `value`.
+    ///
+    /// Returns `FormError::ReflectError` if `value` isn't a struct of supported field types.
+    pub fn construct<U, G, T>(
+        self,
+        value: base::Observed<T>,
+        theme: &dyn draw::Theme,
+        u_aux: &mut U,
+    ) -> Result<FormWidget<U, G, T>, FormError>
+    where
+        U: base::UpdateAuxiliary + 'static,
+        G: base::GraphicalAuxiliary + 'static,
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        let fields = reflect(value.get())?;
+        let rows = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| {
+                let options = self.fields.get(field.name).cloned().unwrap_or_default();
+                if options.hidden {
+                    return None;
+                }
+
+                let mut label = ui::Label::from_theme(theme);
+                let label_text = options.label.unwrap_or_else(|| field_label(field.name));
+                let label_color = label.color;
+                label.text = label_text.clone().into();
+                let editor = options.editor.unwrap_or_else(|| FieldEditor::infer(field));
+                Some(FormRow {
+                    field: index,
+                    label: label.construct(theme, u_aux),
+                    label_text,
+                    label_color,
+                    editor: Editor::new(editor, &field.value, theme, u_aux),
+                    status: ui::Label::from_theme(theme).construct(theme, u_aux),
+                    validation: None,
+                })
+            })
+            .collect();
+
+        let initial = fields.iter().map(|field| field.value.clone()).collect();
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            FormWidget<U, G, T> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.dirty = true; } }
+            "value" => _ev in &value.on_change => { change => { obj.value_changed = true; } }
+        };
+
+        Ok(FormWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+
+            value,
+            fields,
+            initial,
+            reverted: Vec::new(),
+            rows,
+            dirty: true,
+            value_changed: false,
+        }
+        .build())
+    }
+}
+
+/// Editor widget of a field, alongside a listener to its events.
+enum Editor<U, G>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+{
+    Toggle(ui::CheckboxWidget<U, G>, RcEventListener<ui::CheckboxEvent>),
+    Number(ui::NumericInputWidget<U, G>, RcEventListener<ui::NumericInputEvent>),
+    Text(ui::TextAreaWidget<U, G>, RcEventListener<ui::TextAreaEvent>),
+    Choice(ui::ButtonWidget<U, G>, RcEventListener<ui::ButtonEvent>, Vec<String>, String),
+}
+
+impl<U, G> Editor<U, G>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+{
+    fn new(
+        editor: FieldEditor,
+        value: &FieldValue,
+        theme: &dyn draw::Theme,
+        u_aux: &mut U,
+    ) -> Self {
+        match editor {
+            FieldEditor::Toggle => {
+                let mut data = ui::Checkbox::from_theme(theme);
+                data.checked = *value == FieldValue::Bool(true);
+                let widget: ui::CheckboxWidget<U, G> = data.construct(theme, u_aux);
+                let listener = widget.event_queue.listen();
+                Editor::Toggle(widget, listener)
+            }
+            FieldEditor::Number { min, max, step } => {
+                let mut data = ui::NumericInput::from_theme(theme);
+                data.min = min;
+                data.max = max;
+                data.step = step;
+                data.precision = if let FieldValue::Integer(_) = value { 0 } else { 2 };
+                data.value = value.as_f64().unwrap_or_default() as _;
+                let widget: ui::NumericInputWidget<U, G> = data.construct(theme, u_aux);
+                let listener = widget.event_queue.listen();
+                Editor::Number(widget, listener)
+            }
+            FieldEditor::Text => {
+                let mut data = ui::TextArea::from_theme(theme);
+                data.text = value.as_str().unwrap_or_default().to_string();
+                data.cursor = data.text.len();
+                let widget: ui::TextAreaWidget<U, G> = data.construct(theme, u_aux);
+                let listener = widget.event_queue.listen();
+                Editor::Text(widget, listener)
+            }
+            FieldEditor::Choice(choices) => {
+                let choice = value.as_str().unwrap_or_default().to_string();
+                let mut data = ui::Button::from_theme(theme);
+                data.text = choice.clone().into();
+                let widget: ui::ButtonWidget<U, G> = data.construct(theme, u_aux);
+                let listener = widget.event_queue.listen();
+                Editor::Choice(widget, listener, choices, choice)
+            }
+        }
+    }
+
+    /// Returns the value the user has changed the field to, converted to the kind of `current`.
+    fn poll(&mut self, current: &FieldValue) -> Option<FieldValue> {
+        match self {
+            Editor::Toggle(_, listener) => listener
+                .peek()
+                .into_iter()
+                .filter_map(|event| match event {
+                    ui::CheckboxEvent::Check(_) => Some(FieldValue::Bool(true)),
+                    ui::CheckboxEvent::Uncheck(_) => Some(FieldValue::Bool(false)),
+                    _ => None,
+                })
+                .last(),
+            Editor::Number(_, listener) => listener
+                .peek()
+                .into_iter()
+                .filter_map(|event| match event {
+                    ui::NumericInputEvent::UserModify(value) => Some(match current {
+                        FieldValue::Integer(_) => FieldValue::Integer(value.round() as _),
+                        _ => FieldValue::Float(value as _),
+                    }),
+                    _ => None,
+                })
+                .last(),
+            Editor::Text(_, listener) => listener
+                .peek()
+                .into_iter()
+                .filter_map(|event| match event {
+                    ui::TextAreaEvent::UserModify(text) => Some(with_text(current, text)),
+                    _ => None,
+                })
+                .last(),
+            Editor::Choice(_, listener, choices, choice) => {
+                let presses = listener
+                    .peek()
+                    .into_iter()
+                    .filter(|event| matches!(event, ui::ButtonEvent::Press(_)))
+                    .count();
+                if presses == 0 || choices.is_empty() {
+                    return None;
+                }
+                let index = choices.iter().position(|other| other == choice);
+                let next = index.map(|index| index + presses).unwrap_or(presses - 1);
+                Some(with_text(current, choices[next % choices.len()].clone()))
+            }
+        }
+    }
+
+    /// Shows `value` in the editor, unless it already does.
+    fn sync(&mut self, value: &FieldValue) {
+        match self {
+            Editor::Toggle(widget, _) => {
+                let checked = *value == FieldValue::Bool(true);
+                if widget.data.checked != checked {
+                    widget.data.checked = checked;
+                }
+            }
+            Editor::Number(widget, _) => {
+                let number = value.as_f64().unwrap_or_default() as f32;
+                if (widget.data.value - number).abs() > std::f32::EPSILON {
+                    widget.data.value = number;
+                }
+            }
+            Editor::Text(widget, _) => {
+                let text = value.as_str().unwrap_or_default();
+                if widget.data.text != text {
+                    let data = &mut widget.data;
+                    data.text = text.to_string();
+                    data.cursor = data.cursor.min(data.text.len());
+                    data.anchor = None;
+                }
+            }
+            Editor::Choice(widget, _, _, choice) => {
+                let text = value.as_str().unwrap_or_default();
+                if choice != text {
+                    *choice = text.to_string();
+                    widget.data.text = choice.clone().into();
+                }
+            }
+        }
+    }
+
+    fn as_children(
+        &self,
+    ) -> &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand> {
+        match self {
+            Editor::Toggle(widget, _) => widget,
+            Editor::Number(widget, _) => widget,
+            Editor::Text(widget, _) => widget,
+            Editor::Choice(widget, _, _, _) => widget,
+        }
+    }
+
+    fn as_children_mut(
+        &mut self,
+    ) -> &mut dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+    {
+        match self {
+            Editor::Toggle(widget, _) => widget,
+            Editor::Number(widget, _) => widget,
+            Editor::Text(widget, _) => widget,
+            Editor::Choice(widget, _, _, _) => widget,
+        }
+    }
+
+    /// Positions the editor within `rect` (relative to `parent`), vertically centered.
+    /// Every editor but checkboxes is stretched to the width of `rect`.
+    fn layout(&mut self, parent: AbsolutePoint, rect: RelativeRect) {
+        match self {
+            Editor::Toggle(..) => {}
+            Editor::Number(widget, _) => stretch(widget, rect.size.width),
+            Editor::Text(widget, _) => stretch(widget, rect.size.width),
+            Editor::Choice(widget, _, _, _) => stretch(widget, rect.size.width),
+        }
+
+        let widget = self.as_children_mut();
+        let height = widget.bounds().size.height;
+        widget.set_parent_position(parent);
+        widget.set_ctxt_position(
+            RelativePoint::new(
+                rect.origin.x,
+                rect.origin.y + ((rect.size.height - height) / 2.0).max(0.0),
+            )
+            .into(),
+        );
+    }
+}
+
+fn stretch(widget: &mut impl Resizable, width: f32) {
+    let height = widget.size().height;
+    widget.set_size(Size::new(width, height));
+}
+
+/// Returns `text` as the same kind of value as `current` (i.e. a string or an enum variant).
+fn with_text(current: &FieldValue, text: String) -> FieldValue {
+    match current {
+        FieldValue::Variant(_) => FieldValue::Variant(text),
+        _ => FieldValue::Text(text),
+    }
+}
+
+struct FormRow<U, G>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+{
+    /// Index of the field within the reflected fields.
+    field: usize,
+    label: ui::LabelWidget<U, G>,
+    /// Text and color of the label while the field is unmodified.
+    label_text: String,
+    label_color: Color,
+    editor: Editor<U, G>,
+    /// Message beneath the editor, shown unless the field is valid.
+    status: ui::LabelWidget<U, G>,
+    validation: Option<Validation<FieldValue>>,
+}
+
+impl<U, G> FormRow<U, G>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+{
+    fn validity(&self) -> Option<&Validity> {
+        self.validation.as_ref().map(Validation::validity)
+    }
+
+    /// Returns `true` if the status message is shown.
+    fn has_status(&self) -> bool {
+        self.validity().map(|validity| !validity.is_valid()).unwrap_or(false)
+    }
+
+    /// Shows the validity of the field in the status message.
+    fn show_validity(&mut self, data: &Form) {
+        let (text, color) = match self.validity() {
+            Some(Validity::Pending) => (data.pending_message.clone(), data.pending),
+            Some(Validity::Invalid(message)) => (message.clone(), data.error),
+            Some(Validity::Valid) | None => (String::new(), data.error),
+        };
+        let status = &mut self.status.data;
+        status.text = text.into();
+        status.color = color;
+    }
+
+    /// Marks the label if the field is modified, or unmarks it.
+    fn show_modified(&mut self, modified: bool, data: &Form) {
+        let (text, color) = if modified {
+            (format!("{}{}", self.label_text, data.modified_marker), data.modified)
+        } else {
+            (self.label_text.clone(), self.label_color)
+        };
+        let text: DisplayText = text.into();
+        if self.label.data.text != text || self.label.data.color != color {
+            let label = &mut self.label.data;
+            label.text = text;
+            label.color = color;
+        }
+    }
+}
+
+impl<U, G, T> ui::core::CoreWidget<()> for FormWidget<U, G, T>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+    T: Serialize + DeserializeOwned + 'static,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.dirty = true;
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Labelled editors for the fields of a struct, see `Form`."]
+    pub struct FormWidget<T: Serialize + DeserializeOwned + 'static> {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <FormEvent> EventQueue,
+        <Form> State,
+
+        {
+            value: base::Observed<T>,
+            fields: Vec<ReflectedField>,
+            /// Values of the fields when the form was constructed (or last marked clean).
+            initial: Vec<FieldValue>,
+            /// Fields reverted since the last update.
+            reverted: Vec<&'static str>,
+            rows: Vec<FormRow<U, G>>,
+            dirty: bool,
+            value_changed: bool,
+        },
+    }
+}
+
+impl<U, G, T> FormWidget<U, G, T>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+    T: Serialize + DeserializeOwned + 'static,
+{
+    /// Returns the edited value.
+    #[inline]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the edited value mutably; the editors are updated accordingly.
+    #[inline]
+    pub fn value_mut(&mut self) -> &mut base::Observed<T> {
+        &mut self.value
+    }
+
+    /// Returns the reflected fields of the value, as of the last update.
+    #[inline]
+    pub fn fields(&self) -> &[ReflectedField] {
+        &self.fields
+    }
+
+    /// Returns `true` if any field differs from its initial value, as of the last update.
+    pub fn is_dirty(&self) -> bool {
+        self.fields.iter().zip(&self.initial).any(|(field, initial)| field.value != *initial)
+    }
+
+    /// Returns `true` if a field differs from its initial value, as of the last update.
+    pub fn is_field_dirty(&self, field: &str) -> bool {
+        self.field_index(field)
+            .map(|index| self.fields[index].value != self.initial[index])
+            .unwrap_or(false)
+    }
+
+    /// Returns the initial value of a field, i.e. its value when the form was constructed or last marked clean.
+    pub fn initial_value(&self, field: &str) -> Option<&FieldValue> {
+        self.field_index(field).ok().map(|index| &self.initial[index])
+    }
+
+    /// Reverts a field to its initial value, emitting `FormEvent::Revert` if it was modified.
+    ///
+    /// Returns `FormError::UnknownField` if the value has no field of that name.
+    pub fn revert_field(&mut self, field: &str) -> Result<(), FormError> {
+        let index = self.field_index(field)?;
+        self.revert_fields(|other| other == index)
+    }
+
+    /// Reverts every modified field (including hidden ones) to its initial value,
+    /// emitting `FormEvent::Revert` for each.
+    pub fn revert_all(&mut self) -> Result<(), FormError> {
+        self.revert_fields(|_| true)
+    }
+
+    /// Changes the value of a field, as if the user had edited it (but without emitting `FormEvent::UserModify`).
+    ///
+    /// Returns `FormError::UnknownField` if the value has no field of that name, or `FormError::ReflectError` if the
+    /// field can't hold `value`.
+    pub fn set_field(&mut self, field: &str, value: FieldValue) -> Result<(), FormError> {
+        let index = self.field_index(field)?;
+        self.write_fields(|other| if other == index { Some(value.clone()) } else { None })?;
+        Ok(())
+    }
+
+    /// Takes the current value as the initial value of every field (e.g. once it has been saved),
+    /// such that no field is modified.
+    pub fn mark_clean(&mut self) {
+        // Reflection only fails for types which couldn't have been constructed in the first place.
+        if let Ok(fields) = reflect(self.value.get()) {
+            self.initial = fields.into_iter().map(|field| field.value).collect();
+            self.value_changed = true;
+        }
+    }
+
+    /// Reverts the fields for which `revert` (given the index of the field) returns `true`.
+    ///
+    /// Reverted fields are treated as untouched, i.e. their validity is reset by the next update rather than validated.
+    fn revert_fields(&mut self, mut revert: impl FnMut(usize) -> bool) -> Result<(), FormError> {
+        let initial = self.initial.clone();
+        let replaced =
+            self.write_fields(
+                |index| if revert(index) { Some(initial[index].clone()) } else { None },
+            )?;
+        for (name, value) in replaced {
+            self.reverted.push(name);
+            self.event_queue.emit_owned(FormEvent::Revert(name.to_string(), value));
+        }
+        Ok(())
+    }
+
+    fn field_index(&self, field: &str) -> Result<usize, FormError> {
+        self.fields
+            .iter()
+            .position(|other| other.name == field)
+            .ok_or_else(|| FormError::UnknownField(field.to_string()))
+    }
+
+    /// Replaces the fields of the current value for which `replace` (given the index of the field) returns a value,
+    /// returning the names and previous values of the fields which have changed.
+    ///
+    /// The editors, markers and validity are brought up to date by the next update, like any change to the value.
+    fn write_fields(
+        &mut self,
+        mut replace: impl FnMut(usize) -> Option<FieldValue>,
+    ) -> Result<Vec<(&'static str, FieldValue)>, FormError> {
+        let mut fields = reflect(self.value.get())?;
+        let mut replaced = Vec::new();
+        for (index, field) in fields.iter_mut().enumerate() {
+            if let Some(value) = replace(index) {
+                if value != field.value {
+                    replaced.push((field.name, std::mem::replace(&mut field.value, value)));
+                }
+            }
+        }
+
+        if !replaced.is_empty() {
+            self.value.set(rebuild(&fields)?);
+        }
+        Ok(replaced)
+    }
+
+    /// Validates a field with `validator` whenever the user edits it (or the value changes otherwise).
+    ///
+    /// The field isn't validated right away, so that untouched fields aren't flagged; see `validate_all`.
+    /// Returns `FormError::UnknownField` if the form has no (visible) field of that name.
+    pub fn set_validator(
+        &mut self,
+        field: &str,
+        validator: Validator<FieldValue>,
+    ) -> Result<(), FormError> {
+        let fields = &self.fields;
+        let row = self
+            .rows
+            .iter_mut()
+            .find(|row| fields[row.field].name == field)
+            .ok_or_else(|| FormError::UnknownField(field.to_string()))?;
+        row.validation = Some(Validation::new(validator));
+        row.show_validity(&self.data);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Validates every field which has a validator (e.g. before submitting the form),
+    /// returning `true` if they're all valid; pending fields aren't valid yet.
+    pub fn validate_all(&mut self, aux: &U) -> bool {
+        for index in 0..self.rows.len() {
+            self.validate_row(index, aux);
+        }
+        self.is_valid()
+    }
+
+    /// Returns the validity of a field as of the latest validation, or `None` if the field has no validator.
+    pub fn validity(&self, field: &str) -> Option<&Validity> {
+        self.rows.iter().find(|row| self.fields[row.field].name == field)?.validity()
+    }
+
+    /// Returns `true` if every validated field is valid.
+    pub fn is_valid(&self) -> bool {
+        self.rows.iter().filter_map(FormRow::validity).all(Validity::is_valid)
+    }
+
+    fn validate_row(&mut self, index: usize, aux: &U) {
+        let row = &mut self.rows[index];
+        let field = &self.fields[row.field];
+        let validation = match &mut row.validation {
+            Some(validation) => validation,
+            None => return,
+        };
+
+        let old = validation.validity().clone();
+        if *validation.validate(&field.value, aux.task_pool()) != old {
+            self.on_validity_change(index);
+        }
+    }
+
+    /// Forgets the validity of a field, as if it had never been validated.
+    fn reset_validation(&mut self, index: usize) {
+        if let Some(validation) = &mut self.rows[index].validation {
+            let old = validation.validity().clone();
+            validation.reset();
+            if *validation.validity() != old {
+                self.on_validity_change(index);
+            }
+        }
+    }
+
+    /// Receives the results of asynchronous rules.
+    fn poll_validations(&mut self) {
+        for index in 0..self.rows.len() {
+            let changed =
+                self.rows[index].validation.as_mut().map(Validation::poll).unwrap_or(false);
+            if changed {
+                self.on_validity_change(index);
+            }
+        }
+    }
+
+    fn on_validity_change(&mut self, index: usize) {
+        let row = &mut self.rows[index];
+        row.show_validity(&self.data);
+        if let Some(validity) = row.validity() {
+            let name = self.fields[row.field].name.to_string();
+            self.event_queue.emit_owned(FormEvent::ValidityChange(name, validity.clone()));
+        }
+        // The status message takes space beneath the editor.
+        self.dirty = true;
+    }
+
+    /// Writes the changes made by the user to the value.
+    fn apply_edits(&mut self, aux: &U) {
+        let mut edited = Vec::new();
+        for row in &mut self.rows {
+            let field = &mut self.fields[row.field];
+            if let Some(value) = row.editor.poll(&field.value) {
+                if value != field.value {
+                    field.value = value;
+                    edited.push(field.name);
+                }
+            }
+        }
+
+        if edited.is_empty() {
+            return;
+        }
+
+        match rebuild(&self.fields) {
+            Ok(value) => {
+                self.value.set(value);
+                for name in edited {
+                    self.event_queue.emit_owned(FormEvent::UserModify(name.to_string()));
+                    if let Some(index) =
+                        self.rows.iter().position(|row| self.fields[row.field].name == name)
+                    {
+                        self.validate_row(index, aux);
+                    }
+                }
+            }
+            Err(_) => {
+                for name in edited {
+                    self.event_queue.emit_owned(FormEvent::Reject(name.to_string()));
+                }
+                self.value_changed = true;
+            }
+        }
+    }
+
+    /// Shows the current value in the editors, validating the fields which have changed
+    /// (or resetting the validity of those which have been reverted).
+    fn sync_editors(&mut self, aux: &U) {
+        let reverted = std::mem::take(&mut self.reverted);
+        // Reflection only fails for types which couldn't have been constructed in the first place.
+        let fields = match reflect(self.value.get()) {
+            Ok(fields) => std::mem::replace(&mut self.fields, fields),
+            Err(_) => return,
+        };
+        for index in 0..self.rows.len() {
+            let field = self.rows[index].field;
+            self.rows[index].editor.sync(&self.fields[field].value);
+            if fields[field].value != self.fields[field].value {
+                if reverted.contains(&self.fields[field].name) {
+                    self.reset_validation(index);
+                } else {
+                    self.validate_row(index, aux);
+                }
+            }
+        }
+        self.show_modified();
+    }
+
+    fn show_modified(&mut self) {
+        for row in &mut self.rows {
+            row.show_modified(self.fields[row.field].value != self.initial[row.field], &self.data);
+        }
+    }
+
+    fn layout_rows(&mut self) {
+        let position = self.abs_rect().origin;
+        let data = &self.data;
+        let editor_width = (self.rect.size.width - data.label_width - data.spacing).max(0.0);
+
+        let mut y = 0.0;
+        for row in &mut self.rows {
+            let height = row.label.size().height;
+            row.label.set_size(Size::new(data.label_width, height));
+            row.label.set_parent_position(position);
+            row.label.set_ctxt_position(
+                RelativePoint::new(0.0, y + ((data.row_height - height) / 2.0).max(0.0)).into(),
+            );
+
+            row.editor.layout(
+                position,
+                RelativeRect::new(
+                    RelativePoint::new(data.label_width + data.spacing, y),
+                    Size::new(editor_width, data.row_height).cast_unit(),
+                ),
+            );
+            y += data.row_height;
+
+            let status_height = row.status.size().height;
+            row.status.set_size(Size::new(editor_width, status_height));
+            row.status.set_parent_position(position);
+            row.status
+                .set_ctxt_position(RelativePoint::new(data.label_width + data.spacing, y).into());
+            if row.has_status() {
+                y += status_height;
+            }
+
+            y += data.spacing;
+        }
+
+        let height = (y - data.spacing).max(0.0);
+        if (self.rect.size.height - height).abs() > std::f32::EPSILON {
+            let width = self.rect.size.width;
+            self.set_size(Size::new(width, height));
+        }
+    }
+}
+
+impl<U, G, T> Widget for FormWidget<U, G, T>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+    T: Serialize + DeserializeOwned + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        base::invoke_update(self, aux);
+        self.apply_edits(aux);
+        self.poll_validations();
+
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.dirty = true;
+        }
+
+        if self.value_changed {
+            self.value_changed = false;
+            self.sync_editors(aux);
+        }
+
+        if self.dirty {
+            self.dirty = false;
+            self.show_modified();
+            self.layout_rows();
+        }
+    }
+
+    fn draw(&mut self, _display: &mut dyn GraphicsDisplay, _aux: &mut G) {}
+}
+
+impl<U, G, T> WidgetChildren for FormWidget<U, G, T>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+    T: Serialize + DeserializeOwned + 'static,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        let mut children: Vec<
+            &dyn WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+        > = Vec::with_capacity(self.rows.len() * 3);
+        for row in &self.rows {
+            children.push(&row.label);
+            children.push(row.editor.as_children());
+            children.push(&row.status);
+        }
+        children
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        let mut children: Vec<
+            &mut dyn WidgetChildren<
+                UpdateAux = U,
+                GraphicalAux = G,
+                DisplayObject = DisplayCommand,
+            >,
+        > = Vec::with_capacity(self.rows.len() * 3);
+        for row in &mut self.rows {
+            let FormRow { label, editor, status, .. } = row;
+            children.push(label);
+            children.push(editor.as_children_mut());
+            children.push(status);
+        }
+        children
+    }
+}
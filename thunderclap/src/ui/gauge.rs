@@ -0,0 +1,186 @@
+//! Gauge widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// A colored range of values on a gauge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaugeZone {
+    /// Value at which the zone begins.
+    pub start: f32,
+    /// Value at which the zone ends.
+    pub end: f32,
+    /// Color of the zone.
+    pub color: Color,
+}
+
+/// Displays a value within a range with a needle over an arc.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gauge {
+    /// Current value.
+    pub value: f32,
+    /// Value at the start of the arc.
+    pub min: f32,
+    /// Value at the end of the arc.
+    pub max: f32,
+    /// Colored zones drawn along the arc.
+    pub zones: Vec<GaugeZone>,
+    /// Number of tick intervals along the arc, or 0 for no ticks.
+    pub ticks: usize,
+    /// Whether ticks are labelled with their value.
+    pub tick_labels: bool,
+    /// Typeface used for tick labels.
+    pub typeface: draw::TypefaceStyle,
+    /// Color of the needle, ticks and labels.
+    pub foreground: Color,
+    /// Color of the arc track.
+    pub background: Color,
+    /// Contrast mode of `background` and `foreground`.
+    pub contrast: draw::ThemeContrast,
+    /// Layout direction; the arc runs from the bottom-right to the bottom-left for `RightToLeft`.
+    pub direction: draw::LayoutDirection,
+}
+
+impl Gauge {
+    /// Maps `value` into the range 0.0 to 1.0 based on `min` and `max`.
+    pub fn normalize(&self, value: f32) -> f32 {
+        if (self.max - self.min).abs() <= std::f32::EPSILON {
+            0.0
+        } else {
+            ((value - self.min) / (self.max - self.min)).max(0.0).min(1.0)
+        }
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Gauge
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = GaugeWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Gauge
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Gauge {
+            value: 0.0,
+            min: 0.0,
+            max: 100.0,
+            zones: Vec::new(),
+            ticks: 10,
+            tick_labels: true,
+            typeface: data.typography.button.clone(),
+            foreground: data.scheme.over_control_inset,
+            background: data.scheme.control_outset,
+            contrast: data.contrast,
+            direction: data.direction,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, _u_aux: &mut U) -> GaugeWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            GaugeWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        let painter = theme.gauge();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::GaugeState { rect: Default::default(), data: data.clone() })
+                .cast_unit(),
+        );
+
+        GaugeWidgetBuilder { rect, graph: graph.into(), data, painter }.build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::GaugeState> for GaugeWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::GaugeState {
+        state::GaugeState { rect: self.abs_rect(), data: self.data.clone() }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct GaugeWidget {
+        widget::MAX,
+
+        <Gauge> State,
+        <state::GaugeState> Painter,
+    }
+}
+
+impl<U, G> Widget for GaugeWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
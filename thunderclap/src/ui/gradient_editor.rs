@@ -0,0 +1,459 @@
+//! Gradient editor widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state},
+        geom::*,
+        ui::{self, interaction},
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Point, Rect, Size, StyleColor},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Vertical distance the cursor has to be dragged away from the handles to remove a stop.
+const REMOVE_DISTANCE: f32 = 24.0;
+
+/// Color at an offset along a gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// Offset along the gradient, from 0.0 to 1.0.
+    pub offset: f64,
+    pub color: Color,
+}
+
+impl GradientStop {
+    /// Creates a stop of `color` at `offset`.
+    #[inline]
+    pub fn new(offset: f64, color: Color) -> Self {
+        GradientStop { offset, color }
+    }
+}
+
+/// Color stops of a gradient, independent of its geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorGradient {
+    /// Stops ordered by offset.
+    pub stops: Vec<GradientStop>,
+    /// Color space in which the colors between the stops are blended.
+    pub space: draw::ColorSpace,
+}
+
+impl ColorGradient {
+    /// Creates a gradient from stops in any order.
+    pub fn new(mut stops: Vec<GradientStop>, space: draw::ColorSpace) -> Self {
+        stops.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap_or(std::cmp::Ordering::Equal));
+        ColorGradient { stops, space }
+    }
+
+    /// Returns the color at `offset` (from 0.0 to 1.0), blended between the surrounding stops.
+    pub fn color_at(&self, offset: f64) -> Color {
+        let next = self.stops.iter().position(|stop| stop.offset > offset);
+        match next {
+            Some(0) => self.stops[0].color,
+            Some(next) => {
+                let (from, to) = (self.stops[next - 1], self.stops[next]);
+                let t = (offset - from.offset) / (to.offset - from.offset);
+                self.space.mix(from.color, to.color, t as f32)
+            }
+            None => self
+                .stops
+                .last()
+                .map(|stop| stop.color)
+                .unwrap_or_else(|| Color::new(0.0, 0.0, 0.0, 0.0)),
+        }
+    }
+
+    /// Moves the stop at `index` to `offset` (clamped from 0.0 to 1.0), returning its index after reordering the stops.
+    pub fn move_stop(&mut self, index: usize, offset: f64) -> usize {
+        let mut stop = self.stops.remove(index);
+        stop.offset = offset.max(0.0).min(1.0);
+        self.insert_stop(stop)
+    }
+
+    /// Inserts a stop in order, returning its index.
+    pub fn insert_stop(&mut self, stop: GradientStop) -> usize {
+        let index = self
+            .stops
+            .iter()
+            .position(|other| other.offset > stop.offset)
+            .unwrap_or(self.stops.len());
+        self.stops.insert(index, stop);
+        index
+    }
+
+    /// Returns a linear gradient from `start` to `end` with these stops (see `draw::linear_gradient_in`).
+    pub fn to_style(&self, start: Point, end: Point) -> StyleColor {
+        draw::linear_gradient_in(
+            start,
+            end,
+            self.stops.iter().map(|stop| (stop.offset, stop.color)).collect(),
+            self.space,
+        )
+    }
+}
+
+/// Events emitted by a gradient editor.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum GradientEditorEvent {
+    /// A stop has been added, moved, recolored or removed. Holds the edited gradient.
+    #[event_key(gradient_changed)]
+    GradientChanged(ColorGradient),
+    /// A stop has been selected (e.g. to show a color picker for it), or the selection has been cleared.
+    /// Holds the index of the selected stop.
+    #[event_key(stop_selected)]
+    StopSelected(Option<usize>),
+}
+
+/// Edits the stops of a gradient shown on a preview bar.
+///
+/// Stops are moved by dragging their handles, added by pressing the bar and removed by dragging their handles away
+/// from the bar. Selecting a stop emits `StopSelected`, after which its color is set with `GradientEditorWidget::set_stop_color`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientEditor {
+    pub gradient: ColorGradient,
+    /// Height of the preview bar.
+    pub bar_height: f32,
+    /// Width and height of the stop handles, which are shown below the bar.
+    pub handle_size: f32,
+    /// Color of the outlines of the bar and handles.
+    pub border: Color,
+    /// Color of the outline of the selected handle.
+    pub selection: Color,
+    /// Contrast mode of `border`.
+    pub contrast: draw::ThemeContrast,
+}
+
+impl GradientEditor {
+    /// Returns the rectangle of the preview bar within the widget rectangle `rect`.
+    ///
+    /// The bar is inset by half a handle on either side, so that the handles of the first and last stop fit.
+    pub fn bar_rect(&self, rect: AbsoluteRect) -> AbsoluteRect {
+        let inset = self.handle_size / 2.0;
+        AbsoluteRect::new(
+            AbsolutePoint::new(rect.origin.x + inset, rect.origin.y),
+            Size::new((rect.size.width - self.handle_size).max(0.0), self.bar_height).cast_unit(),
+        )
+    }
+
+    /// Returns the rectangle of the handle of the stop at `index` within the widget rectangle `rect`.
+    pub fn handle_rect(&self, rect: AbsoluteRect, index: usize) -> AbsoluteRect {
+        let bar = self.bar_rect(rect);
+        let x = bar.origin.x + bar.size.width * self.gradient.stops[index].offset as f32;
+        AbsoluteRect::new(
+            AbsolutePoint::new(x - self.handle_size / 2.0, bar.max_y() + 2.0),
+            Size::new(self.handle_size, self.handle_size).cast_unit(),
+        )
+    }
+
+    /// Returns the index of the stop whose handle contains `point`, preferring later stops where handles overlap.
+    pub fn handle_at(&self, rect: AbsoluteRect, point: AbsolutePoint) -> Option<usize> {
+        (0..self.gradient.stops.len())
+            .rev()
+            .find(|&index| self.handle_rect(rect, index).contains(point))
+    }
+
+    /// Returns the gradient offset under the horizontal position `x`.
+    pub fn offset_at(&self, rect: AbsoluteRect, x: f32) -> f64 {
+        let bar = self.bar_rect(rect);
+        if bar.size.width <= 0.0 {
+            0.0
+        } else {
+            f64::from(((x - bar.origin.x) / bar.size.width).max(0.0).min(1.0))
+        }
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for GradientEditor
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = GradientEditorWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for GradientEditor
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        GradientEditor {
+            gradient: ColorGradient::new(
+                vec![
+                    GradientStop::new(0.0, data.scheme.control_outset),
+                    GradientStop::new(1.0, data.scheme.primary),
+                ],
+                draw::ColorSpace::LinearSrgb,
+            ),
+            bar_height: 24.0,
+            handle_size: 12.0,
+            border: data.scheme.over_control_outset,
+            selection: data.scheme.focus,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> GradientEditorWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            GradientEditorWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    // The stops may have been replaced underneath the selection.
+                    if obj.selected.map(|selected| selected >= obj.data.gradient.stops.len()).unwrap_or(false) {
+                        obj.selected = None;
+                        obj.dragging = false;
+                    }
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph = graph
+            .add("gradient_editor", gradient_editor_handler::<U, G>().bind(u_aux.window_queue()));
+
+        let painter = theme.gradient_editor();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::GradientEditorState {
+                    rect: Default::default(),
+                    data: data.clone(),
+                    selected: None,
+                    removing: false,
+                })
+                .cast_unit(),
+        );
+
+        GradientEditorWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter,
+
+            selected: None,
+            dragging: false,
+            removing: false,
+            changed: false,
+        }
+        .build()
+    }
+}
+
+/// Selects, adds, moves and removes stops with the mouse.
+fn gradient_editor_handler<U, G>(
+) -> vg::UnboundQueueHandler<GradientEditorWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        GradientEditorWidget<U, G> as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let rect = obj.abs_rect();
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                *button == base::MouseButton::Left
+                    && (obj.data.handle_at(rect, *pos).is_some()
+                        || obj.data.bar_rect(rect).contains(*pos))
+                    && interaction::is_targeted(obj, aux, *pos)
+            }) {
+                let index = match obj.data.handle_at(rect, *pos) {
+                    Some(index) => index,
+                    None => {
+                        let offset = obj.data.offset_at(rect, pos.x);
+                        let color = obj.data.gradient.color_at(offset);
+                        obj.changed = true;
+                        obj.data.gradient.insert_stop(GradientStop::new(offset, color))
+                    }
+                };
+
+                obj.dragging = true;
+                obj.removing = false;
+                obj.select(Some(index));
+            }
+        }
+
+        mouse_move => {
+            if obj.dragging {
+                if let Some(selected) = obj.selected {
+                    let rect = obj.abs_rect();
+                    let pos = event.get().0;
+                    let offset = obj.data.offset_at(rect, pos.x);
+                    let handle_y = obj.data.handle_rect(rect, selected).center().y;
+                    let removing = obj.data.gradient.stops.len() > 2
+                        && (pos.y - handle_y).abs() > REMOVE_DISTANCE;
+
+                    if removing != obj.removing {
+                        obj.removing = removing;
+                        obj.command_group.repaint();
+                    }
+                    if (obj.data.gradient.stops[selected].offset - offset).abs() > std::f64::EPSILON {
+                        obj.selected = Some(obj.data.gradient.move_stop(selected, offset));
+                        obj.changed = true;
+                    }
+                }
+            }
+        }
+
+        mouse_release => {
+            if obj.dragging && event.get().1 == base::MouseButton::Left {
+                obj.dragging = false;
+                if obj.removing {
+                    obj.removing = false;
+                    if let Some(selected) = obj.selected {
+                        obj.remove_stop(selected);
+                    }
+                }
+                obj.emit_changed();
+            }
+        }
+    }
+}
+
+impl<U, G> GradientEditorWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Returns the index of the selected stop, if any.
+    #[inline]
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Selects the stop at `index` (or clears the selection), emitting `StopSelected` if the selection changed.
+    pub fn select(&mut self, index: Option<usize>) {
+        let index = index.filter(|&index| index < self.data.gradient.stops.len());
+        if index != self.selected {
+            self.selected = index;
+            self.command_group.repaint();
+            self.event_queue.emit_owned(GradientEditorEvent::StopSelected(index));
+        }
+    }
+
+    /// Sets the color of the stop at `index` (e.g. as picked for the selected stop), emitting `GradientChanged`.
+    pub fn set_stop_color(&mut self, index: usize, color: Color) {
+        let current = self.data.gradient.stops.get(index).map(|stop| stop.color);
+        if current.is_some() && current != Some(color) {
+            self.data.gradient.stops[index].color = color;
+            self.changed = true;
+            self.emit_changed();
+        }
+    }
+
+    /// Removes the stop at `index`, clearing the selection if it was selected and emitting `GradientChanged`.
+    ///
+    /// Gradients always keep at least two stops.
+    pub fn remove_stop(&mut self, index: usize) {
+        if index >= self.data.gradient.stops.len() || self.data.gradient.stops.len() <= 2 {
+            return;
+        }
+
+        self.data.gradient.stops.remove(index);
+        match self.selected {
+            Some(selected) if selected == index => self.select(None),
+            Some(selected) if selected > index => self.selected = Some(selected - 1),
+            _ => {}
+        }
+        self.changed = true;
+        self.emit_changed();
+    }
+
+    fn emit_changed(&mut self) {
+        if self.changed {
+            self.changed = false;
+            self.event_queue
+                .emit_owned(GradientEditorEvent::GradientChanged(self.data.gradient.clone()));
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::GradientEditorState> for GradientEditorWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::GradientEditorState {
+        state::GradientEditorState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            selected: self.selected,
+            removing: self.removing,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct GradientEditorWidget {
+        widget::MAX,
+
+        <GradientEditorEvent> EventQueue,
+        <GradientEditor> State,
+        <state::GradientEditorState> Painter,
+
+        {
+            selected: Option<usize>,
+            dragging: bool,
+            removing: bool,
+            changed: bool,
+        },
+    }
+}
+
+impl<U, G> Widget for GradientEditorWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
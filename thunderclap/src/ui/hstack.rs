@@ -11,6 +11,7 @@ use {
         display::{self, DisplayCommand, Rect, Size},
         event::{bidir_single::Queue as BidirSingleEventQueue, RcEventListener},
         prelude::*,
+        verbgraph as vg,
     },
 };
 
@@ -23,6 +24,9 @@ pub struct HStackItem {
     pub right_margin: f32,
     /// How the child should be vertically aligned within the `HStack`.
     pub alignment: Align,
+    /// Binds the rectangle to the window, so that the layout fills the window and re-runs whenever it's resized.
+    /// This is intended for the root layout.
+    pub fill_window: bool,
 }
 
 impl HStackItem {
@@ -73,13 +77,13 @@ where
     G: base::GraphicalAuxiliary,
 {
     fn from_theme(_theme: &dyn draw::Theme) -> Self {
-        HStack { left_margin: 0.0, right_margin: 0.0, alignment: Align::Begin }
+        HStack { left_margin: 0.0, right_margin: 0.0, alignment: Align::Begin, fill_window: false }
     }
 
     fn construct(
         self,
         _theme: &dyn draw::Theme,
-        _u_aux: &mut U,
+        u_aux: &mut U,
     ) -> HStackWidget<U, G>
     where
         U: base::UpdateAuxiliary,
@@ -87,9 +91,20 @@ where
     {
         let data = base::Observed::new(self);
 
+        let mut graph = vg::verbgraph! {
+            HStackWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.dirty = true; } }
+        };
+
+        graph = graph.add(
+            "window",
+            ui::window_fill_handler::<HStackWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+
         HStackWidgetBuilder {
             rect: Default::default(),
-            graph: None,
+            graph: graph.into(),
             data,
 
             rects: IndexMap::new(),
@@ -113,6 +128,17 @@ where
     }
 }
 
+impl<U, G> ui::WindowFill for HStackWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn fill_window(&self) -> bool {
+        self.data.fill_window
+    }
+}
+
 use crate as thunderclap;
 crate::widget! {
     #[doc = "Abstract layout widget which arranges children in a horizontal list, possibly with left/right margins and vertical alignment (see `HStackData`)."]
@@ -135,6 +161,10 @@ where
     G: base::GraphicalAuxiliary,
 {
     fn resize_to_fit(&mut self) {
+        if self.data.fill_window {
+            return;
+        }
+
         let mut max_size = Size::zero();
         for (_, child) in &self.rects {
             let size: Size = child.rect.size.cast_unit();
@@ -209,7 +239,11 @@ where
         self.rect.cast_unit()
     }
 
-    fn update(&mut self, _aux: &mut U) {
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
         if let Some(rect) = self.layout.receive() {
             self.set_ctxt_rect(rect);
             self.dirty = true;
@@ -267,6 +301,14 @@ where
                 }
             }
 
+            let rects: Vec<AbsoluteRect> = self.rects.values().map(|data| data.rect).collect();
+            check_arrangement(
+                self as *const Self as usize,
+                base::WidgetChildren::widget_kind(self),
+                abs_rect,
+                &rects,
+            );
+
             self.dirty = false;
         }
     }
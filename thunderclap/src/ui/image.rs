@@ -0,0 +1,386 @@
+//! Image widget, displaying encoded image bytes or an `image::DynamicImage`.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw,
+        geom::*,
+        resources::ImageResources,
+        ui,
+    },
+    reclutch::{
+        display::{
+            DisplayCommand, DisplayListBuilder, GraphicsDisplay, ImageData, Point,
+            RasterImageFormat, RasterImageInfo, Rect, ResourceDescriptor, ResourceReference,
+            SharedData, Size,
+        },
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{fmt, io::Cursor, sync::Arc},
+};
+
+/// How an image is scaled to fit the bounds of an [`Image`](struct.Image.html) widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageScaling {
+    /// Scales the image to fit entirely within the bounds, preserving the aspect ratio.
+    Contain,
+    /// Scales the image to cover the bounds, preserving the aspect ratio and clipping the overflow.
+    Cover,
+    /// Stretches the image to the bounds, ignoring the aspect ratio.
+    Stretch,
+}
+
+impl Default for ImageScaling {
+    fn default() -> Self {
+        ImageScaling::Contain
+    }
+}
+
+#[derive(Clone)]
+enum ImagePixels {
+    Encoded(Arc<Vec<u8>>),
+    Rgba8(Arc<Vec<u8>>),
+}
+
+/// Shared image data displayed by an [`Image`](struct.Image.html).
+///
+/// Cloning an image source is cheap, and two sources are only equal if they share the same data.
+#[derive(Clone)]
+pub struct ImageSource {
+    pixels: ImagePixels,
+    size: (u32, u32),
+}
+
+impl ImageSource {
+    /// Creates an image source from encoded image bytes (e.g. the contents of a PNG file).
+    ///
+    /// Only the image header is read here; the image is decoded by the display once it's first drawn.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self, ::image::ImageError> {
+        let bytes = bytes.into();
+        let size = ::image::io::Reader::new(Cursor::new(&bytes))
+            .with_guessed_format()?
+            .into_dimensions()?;
+        Ok(ImageSource { pixels: ImagePixels::Encoded(Arc::new(bytes)), size })
+    }
+
+    /// Creates an image source from an already decoded image, converting it to RGBA.
+    pub fn from_image(image: &::image::DynamicImage) -> Self {
+        let image = image.to_rgba();
+        let size = image.dimensions();
+        ImageSource { pixels: ImagePixels::Rgba8(Arc::new(image.into_raw())), size }
+    }
+
+    /// Returns the size of the image, in pixels.
+    #[inline]
+    pub fn size(&self) -> Size {
+        Size::new(self.size.0 as _, self.size.1 as _)
+    }
+
+    fn image_data(&self) -> ImageData {
+        match &self.pixels {
+            ImagePixels::Encoded(bytes) => ImageData::Encoded(SharedData::RefCount(bytes.clone())),
+            ImagePixels::Rgba8(pixels) => ImageData::Raw(
+                SharedData::RefCount(pixels.clone()),
+                RasterImageInfo { size: self.size, format: RasterImageFormat::Rgba8 },
+            ),
+        }
+    }
+
+    /// Identifies the shared data of the source.
+    pub(crate) fn id(&self) -> usize {
+        match &self.pixels {
+            ImagePixels::Encoded(data) | ImagePixels::Rgba8(data) => Arc::as_ptr(data) as usize,
+        }
+    }
+
+    /// Returns the image data alongside its size, downscaled (keeping the aspect ratio) to fit within
+    /// `max_dimension` if necessary.
+    ///
+    /// The original data is returned if the image fails to decode.
+    pub(crate) fn image_data_within(&self, max_dimension: Option<u32>) -> (ImageData, (u32, u32)) {
+        let max_dimension = match max_dimension {
+            Some(max_dimension) if self.size.0 > max_dimension || self.size.1 > max_dimension => {
+                max_dimension
+            }
+            _ => return (self.image_data(), self.size),
+        };
+
+        let image = match &self.pixels {
+            ImagePixels::Encoded(bytes) => ::image::load_from_memory(bytes).ok(),
+            ImagePixels::Rgba8(pixels) => {
+                ::image::RgbaImage::from_raw(self.size.0, self.size.1, pixels.to_vec())
+                    .map(::image::DynamicImage::ImageRgba8)
+            }
+        };
+
+        match image {
+            Some(image) => {
+                let image = image
+                    .resize(max_dimension, max_dimension, ::image::imageops::FilterType::Triangle)
+                    .to_rgba();
+                let size = image.dimensions();
+                (
+                    ImageData::Raw(
+                        SharedData::RefCount(Arc::new(image.into_raw())),
+                        RasterImageInfo { size, format: RasterImageFormat::Rgba8 },
+                    ),
+                    size,
+                )
+            }
+            None => (self.image_data(), self.size),
+        }
+    }
+}
+
+impl From<::image::DynamicImage> for ImageSource {
+    fn from(image: ::image::DynamicImage) -> Self {
+        ImageSource::from_image(&image)
+    }
+}
+
+impl PartialEq for ImageSource {
+    fn eq(&self, other: &ImageSource) -> bool {
+        match (&self.pixels, &other.pixels) {
+            (ImagePixels::Encoded(a), ImagePixels::Encoded(b))
+            | (ImagePixels::Rgba8(a), ImagePixels::Rgba8(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for ImageSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.pixels {
+            ImagePixels::Encoded(_) => "Encoded",
+            ImagePixels::Rgba8(_) => "Rgba8",
+        };
+        f.debug_struct("ImageSource").field("kind", &kind).field("size", &self.size).finish()
+    }
+}
+
+/// Displays an image, scaled to the bounds of the widget.
+///
+/// The image is registered as a display resource the first time it's drawn,
+/// and re-registered whenever `source` changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    /// Image to display, if any.
+    pub source: Option<ImageSource>,
+    pub scaling: ImageScaling,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Image
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = ImageWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Image
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(_theme: &dyn draw::Theme) -> Self {
+        Image { source: None, scaling: ImageScaling::Contain }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, _u_aux: &mut U) -> ImageWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let size = self.source.as_ref().map(ImageSource::size).unwrap_or_else(Size::zero);
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            ImageWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.repaint();
+                }
+            }
+        };
+
+        ImageWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), size.cast_unit()),
+            graph: graph.into(),
+            data,
+
+            resource: None,
+            managed: None,
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for ImageWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Image scaled to the bounds of the widget."]
+    #[doc = "Note that replaced images are only released from the display while drawing, and the current image is kept until `release` is called."]
+    #[doc = "If the graphical auxiliary provides image resources (see `GraphicalAuxiliary::image_resources`), the image is registered through them instead, which take care of releasing it."]
+    pub struct ImageWidget {
+        widget::MAX,
+
+        <Image> State,
+
+        {
+            // Source which is currently registered with the display, alongside its resource.
+            resource: Option<(ImageSource, ResourceReference)>,
+            // Resource last drawn from the image resources of the graphical auxiliary, if any.
+            managed: Option<ResourceReference>,
+        },
+    }
+}
+
+impl<U, G> ImageWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Removes the image from the display, without changing `source`.
+    ///
+    /// If the widget is drawn again, the image is re-registered.
+    /// Images registered through the image resources of the graphical auxiliary are left to them.
+    pub fn release(&mut self, display: &mut dyn GraphicsDisplay) {
+        if let Some((_, resource)) = self.resource.take() {
+            display.remove_resource(resource);
+        }
+        self.managed = None;
+        self.repaint();
+    }
+
+    /// Returns the rectangle the image is drawn in, given its size.
+    fn image_rect(&self, image: Size) -> Rect {
+        let bounds: Rect = self.abs_rect().cast_unit();
+        if image.width <= 0.0 || image.height <= 0.0 {
+            return bounds;
+        }
+
+        let (sx, sy) = (bounds.size.width / image.width, bounds.size.height / image.height);
+        let size = match self.data.scaling {
+            ImageScaling::Contain => image * sx.min(sy),
+            ImageScaling::Cover => image * sx.max(sy),
+            ImageScaling::Stretch => return bounds,
+        };
+
+        Rect::new(
+            Point::new(
+                bounds.origin.x + (bounds.size.width - size.width) / 2.0,
+                bounds.origin.y + (bounds.size.height - size.height) / 2.0,
+            ),
+            size,
+        )
+    }
+
+    /// Ensures the current source is registered with the display, replacing any outdated resource.
+    fn resolve_resource(&mut self, display: &mut dyn GraphicsDisplay) -> Option<ResourceReference> {
+        if self.resource.as_ref().map(|(source, _)| source) != self.data.source.as_ref() {
+            if let Some((_, resource)) = self.resource.take() {
+                display.remove_resource(resource);
+            }
+
+            if let Some(source) = self.data.source.clone() {
+                self.resource = display
+                    .new_resource(ResourceDescriptor::Image(source.image_data()))
+                    .ok()
+                    .map(|resource| (source, resource));
+            }
+        }
+
+        self.resource.as_ref().map(|(_, resource)| resource.clone())
+    }
+
+    /// Retrieves the resource of the current source from the image resources, repainting if it was replaced
+    /// (e.g. if it was evicted and uploaded again).
+    fn resolve_managed_resource(
+        &mut self,
+        display: &mut dyn GraphicsDisplay,
+        resources: &mut ImageResources,
+    ) -> Option<ResourceReference> {
+        // Images registered before the image resources were available are no longer needed.
+        if let Some((_, resource)) = self.resource.take() {
+            display.remove_resource(resource);
+        }
+
+        let resource =
+            self.data.source.as_ref().and_then(|source| resources.image(display, source));
+        if resource != self.managed {
+            self.managed = resource.clone();
+            self.command_group.repaint();
+        }
+
+        resource
+    }
+}
+
+impl<U, G> Widget for ImageWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) {
+        let resource = match aux.image_resources() {
+            Some(resources) => self.resolve_managed_resource(display, resources),
+            None => self.resolve_resource(display),
+        };
+        let bounds: Rect = self.abs_rect().cast_unit();
+        let image_rect = self.data.source.as_ref().map(|source| self.image_rect(source.size()));
+        let clip = self.data.scaling == ImageScaling::Cover;
+
+        self.command_group.push_with(
+            display,
+            || {
+                let mut builder = DisplayListBuilder::new();
+
+                if let (Some(resource), Some(image_rect)) = (resource, image_rect) {
+                    if clip {
+                        builder.push_rectangle_clip(bounds, true);
+                    }
+                    builder.push_image(None, image_rect, resource, None);
+                }
+
+                builder.build()
+            },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
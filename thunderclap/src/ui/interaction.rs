@@ -0,0 +1,381 @@
+//! Composable interaction terminal mixins.
+//!
+//! `basic_interaction_handler` handles hovering, pressing, focusing and scrolling all at once.
+//! Widgets which only need some of these (or need additional gestures) can instead bind the partial terminals
+//! of this module (`hover_handler`, `press_handler`, `click_focus_handler` and `drag_handler`),
+//! or combine the mixin functions within a terminal of their own:
+//!
+//! ```ignore
+//! unbound_queue_handler! {
+//!     MyWidget<U, G> as obj,
+//!     U as aux,
+//!     base::WindowEvent as event,
+//!
+//!     mouse_press => {
+//!         if let Some(pos) = interaction::press(obj, aux, event) {
+//!             interaction::begin_drag(obj, pos);
+//!         }
+//!     }
+//!
+//!     mouse_release => {
+//!         interaction::release(obj, aux, event);
+//!         interaction::end_drag(obj, event);
+//!     }
+//!
+//!     mouse_move => {
+//!         interaction::hover(obj, aux, event);
+//!         interaction::drag(obj, event);
+//!     }
+//! }
+//! ```
+//!
+//! Each mixin takes the event data of a single `base::WindowEvent` arm, consuming it where appropriate.
+//! Mouse events are only handled by the widget under the cursor if it isn't occluded by another widget,
+//! as resolved by the application (see `base::PointerTarget`).
+//! The latency of consumed events is recorded to the profiler, if any (see `profiling`).
+
+use {
+    super::{interaction_event, InteractionEvent, InteractiveWidget},
+    crate::{
+        base::{self, ConsumableEvent, KeyModifiers, MouseButton},
+        draw::state,
+        geom::*,
+        profiling,
+    },
+    reclutch::{
+        display::Vector,
+        verbgraph::{unbound_queue_handler, UnboundQueueHandler},
+    },
+};
+
+/// Distance the cursor has to move from where it was pressed before a drag starts.
+pub const DRAG_THRESHOLD: f32 = 3.0;
+
+/// Returns `true` if the widget receives mouse input at `pos`, i.e. it isn't occluded by another widget
+/// (see `base::UpdateAuxiliary::pointer_target`).
+pub fn is_targeted<W, U: base::UpdateAuxiliary>(obj: &W, aux: &U, pos: AbsolutePoint) -> bool {
+    aux.pointer_target().map(|target| target.accepts(obj, pos)).unwrap_or(true)
+}
+
+/// Consumes a left mouse press within the bounds of the widget, marking it as pressed and emitting `Pressed`.
+/// If the widget counts clicks (see `InteractiveWidget::click_counter`), `DoubleClick` is emitted for the second successive press.
+///
+/// Returns the press position if the press was consumed.
+pub fn press<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+    obj: &mut W,
+    aux: &mut U,
+    event: &ConsumableEvent<(AbsolutePoint, MouseButton, KeyModifiers)>,
+) -> Option<AbsolutePoint> {
+    let bounds = obj.abs_convert_rect(obj.mouse_bounds());
+    let &(pos, _, _) = event.with(|(pos, button, _)| {
+        !obj.disabled()
+            && *button == MouseButton::Left
+            && bounds.contains(*pos)
+            && is_targeted(obj, aux, *pos)
+    })?;
+    profiling::record_latency(aux, "mouse_press", event);
+    obj.interaction().insert(state::InteractionState::PRESSED);
+
+    let timeout = aux.double_click_time();
+    let clicks = obj.click_counter().map(|counter| counter.click(pos, timeout));
+
+    interaction_event(obj, aux, InteractionEvent::Pressed(pos));
+    if clicks == Some(2) {
+        interaction_event(obj, aux, InteractionEvent::DoubleClick(pos));
+    }
+    Some(pos)
+}
+
+/// Consumes a left mouse release if the widget is pressed, unmarking it as pressed and emitting `Released`.
+///
+/// Returns the release position if the release was consumed.
+pub fn release<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+    obj: &mut W,
+    aux: &mut U,
+    event: &ConsumableEvent<(AbsolutePoint, MouseButton, KeyModifiers)>,
+) -> Option<AbsolutePoint> {
+    let &(pos, _, _) = event.with(|(_, button, _)| {
+        !obj.disabled()
+            && *button == MouseButton::Left
+            && obj.interaction().contains(state::InteractionState::PRESSED)
+    })?;
+    profiling::record_latency(aux, "mouse_release", event);
+    obj.interaction().remove(state::InteractionState::PRESSED);
+    interaction_event(obj, aux, InteractionEvent::Released(pos));
+    Some(pos)
+}
+
+/// Marks the widget as focused, emitting `Focus` and notifying the focus tracker.
+pub fn focus<W: InteractiveWidget, U: base::UpdateAuxiliary>(obj: &mut W, aux: &mut U) {
+    obj.interaction().insert(state::InteractionState::FOCUSED);
+    interaction_event(obj, aux, InteractionEvent::Focus);
+    aux.focus_tracker_mut().notify_focused(obj.focus_id());
+}
+
+/// Unmarks the widget as focused, emitting `Blur` if it was focused.
+///
+/// This should be invoked on `clear_focus`.
+pub fn blur<W: InteractiveWidget, U: base::UpdateAuxiliary>(obj: &mut W, aux: &mut U) {
+    let was_focused = obj.interaction().contains(state::InteractionState::FOCUSED);
+    obj.interaction().remove(state::InteractionState::FOCUSED);
+    if was_focused {
+        interaction_event(obj, aux, InteractionEvent::Blur);
+    }
+}
+
+/// Consumes a left mouse press within the bounds of the widget and focuses it, without marking it as pressed.
+///
+/// Returns the press position if the press was consumed.
+pub fn click_focus<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+    obj: &mut W,
+    aux: &mut U,
+    event: &ConsumableEvent<(AbsolutePoint, MouseButton, KeyModifiers)>,
+) -> Option<AbsolutePoint> {
+    let bounds = obj.abs_convert_rect(obj.mouse_bounds());
+    let &(pos, _, _) = event.with(|(pos, button, _)| {
+        !obj.disabled()
+            && *button == MouseButton::Left
+            && bounds.contains(*pos)
+            && is_targeted(obj, aux, *pos)
+    })?;
+    profiling::record_latency(aux, "mouse_press", event);
+    focus(obj, aux);
+    Some(pos)
+}
+
+/// Tracks whether the cursor is over the widget, emitting `BeginHover` and `EndHover`.
+///
+/// The cursor movement is consumed while over the widget.
+pub fn hover<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+    obj: &mut W,
+    aux: &mut U,
+    event: &ConsumableEvent<(AbsolutePoint, KeyModifiers)>,
+) {
+    let bounds = obj.abs_convert_rect(obj.mouse_bounds());
+    if let Some(&(pos, _)) =
+        event.with(|(pos, _)| bounds.contains(*pos) && is_targeted(obj, aux, *pos))
+    {
+        profiling::record_latency(aux, "mouse_move", event);
+        if !obj.interaction().contains(state::InteractionState::HOVERED) {
+            obj.interaction().insert(state::InteractionState::HOVERED);
+            interaction_event(obj, aux, InteractionEvent::BeginHover(pos));
+        }
+    } else if obj.interaction().contains(state::InteractionState::HOVERED) {
+        obj.interaction().remove(state::InteractionState::HOVERED);
+        interaction_event(obj, aux, InteractionEvent::EndHover(event.get().0));
+    }
+}
+
+/// Forwards mouse wheel scrolling over the widget to `InteractiveWidget::on_mouse_wheel`,
+/// consuming the event if it was handled.
+pub fn wheel<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+    obj: &mut W,
+    aux: &mut U,
+    event: &ConsumableEvent<(AbsolutePoint, Vector, KeyModifiers)>,
+) {
+    let bounds = obj.abs_convert_rect(obj.mouse_bounds());
+    let handled = event.with(|(pos, delta, modifiers)| {
+        !obj.disabled()
+            && bounds.contains(*pos)
+            && is_targeted(obj, aux, *pos)
+            && obj.on_mouse_wheel(*delta, *modifiers)
+    });
+    if handled.is_some() {
+        profiling::record_latency(aux, "mouse_wheel", event);
+    }
+}
+
+/// Stage of a drag gesture, emitted through `DraggableWidget::on_drag_event`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragEvent {
+    /// The cursor moved past `DRAG_THRESHOLD` while pressed; holds where it was pressed.
+    Start(AbsolutePoint),
+    /// The cursor moved while dragging.
+    Move {
+        /// Where the drag started.
+        origin: AbsolutePoint,
+        /// Current cursor position.
+        position: AbsolutePoint,
+        /// Movement since the last `Start` or `Move`.
+        delta: Vector,
+    },
+    /// The mouse button was released, ending the drag.
+    End {
+        /// Where the drag started.
+        origin: AbsolutePoint,
+        /// Where the mouse button was released.
+        position: AbsolutePoint,
+    },
+}
+
+/// Progress of a drag gesture, stored by widgets using the drag mixin.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DragState {
+    origin: Option<AbsolutePoint>,
+    last: AbsolutePoint,
+    dragging: bool,
+}
+
+impl DragState {
+    /// Returns `true` if a drag is in progress (i.e. it has passed the threshold).
+    #[inline]
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Returns where the drag started, if the widget is pressed or being dragged.
+    #[inline]
+    pub fn origin(&self) -> Option<AbsolutePoint> {
+        self.origin
+    }
+}
+
+/// Widget which can be dragged, using the drag mixin (`begin_drag`, `drag` and `end_drag`, or `drag_handler`).
+pub trait DraggableWidget: InteractiveWidget {
+    /// Returns the progress of the current drag gesture.
+    fn drag_state(&mut self) -> &mut DragState;
+    /// Invoked at each stage of a drag gesture.
+    fn on_drag_event(&mut self, event: DragEvent);
+}
+
+/// Arms a drag from `position`, which starts once the cursor has moved past `DRAG_THRESHOLD`.
+///
+/// This is typically invoked with the position returned by `press`.
+pub fn begin_drag<W: DraggableWidget>(obj: &mut W, position: AbsolutePoint) {
+    *obj.drag_state() = DragState { origin: Some(position), last: position, dragging: false };
+}
+
+/// Advances an armed drag as the cursor moves, regardless of whether the cursor is over the widget.
+pub fn drag<W: DraggableWidget>(
+    obj: &mut W,
+    event: &ConsumableEvent<(AbsolutePoint, KeyModifiers)>,
+) {
+    let position = event.get().0;
+    let drag_state = *obj.drag_state();
+    let origin = match drag_state.origin {
+        Some(origin) => origin,
+        None => return,
+    };
+
+    if !drag_state.dragging {
+        if (position - origin).length() < DRAG_THRESHOLD {
+            return;
+        }
+        obj.drag_state().dragging = true;
+        obj.on_drag_event(DragEvent::Start(origin));
+    }
+
+    let delta = position - obj.drag_state().last;
+    obj.drag_state().last = position;
+    obj.on_drag_event(DragEvent::Move { origin, position, delta: delta.cast_unit() });
+}
+
+/// Ends an armed drag once the left mouse button is released, emitting `End` if the drag had started.
+pub fn end_drag<W: DraggableWidget>(
+    obj: &mut W,
+    event: &ConsumableEvent<(AbsolutePoint, MouseButton, KeyModifiers)>,
+) {
+    let &(position, button, _) = event.get();
+    if button != MouseButton::Left {
+        return;
+    }
+
+    let drag_state = std::mem::take(obj.drag_state());
+    if let (Some(origin), true) = (drag_state.origin, drag_state.dragging) {
+        obj.on_drag_event(DragEvent::End { origin, position });
+    }
+}
+
+/// Generates an unbound terminal which only tracks hovering (see `hover`).
+pub fn hover_handler<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::WindowEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_move => {
+            hover(obj, aux, event);
+        }
+    }
+}
+
+/// Generates an unbound terminal which only tracks pressing and releasing (see `press` and `release`),
+/// without giving focus.
+pub fn press_handler<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::WindowEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            press(obj, aux, event);
+        }
+
+        mouse_release => {
+            release(obj, aux, event);
+        }
+    }
+}
+
+/// Generates an unbound terminal which only gives focus on click and takes it away when focus is cleared
+/// (see `click_focus` and `blur`).
+pub fn click_focus_handler<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::WindowEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            click_focus(obj, aux, event);
+        }
+
+        clear_focus => {
+            blur(obj, aux);
+        }
+    }
+}
+
+/// Generates an unbound terminal which tracks drag gestures.
+///
+/// A drag is armed by a left mouse press within the bounds of the widget.
+/// If this is bound after a terminal which consumes presses (e.g. `basic_interaction_handler`),
+/// the drag is armed by the press which marked the widget as pressed instead.
+pub fn drag_handler<W: DraggableWidget, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::WindowEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let &(pos, button, _) = event.get();
+            let bounds = obj.abs_convert_rect(obj.mouse_bounds());
+            let pressed = button == MouseButton::Left
+                && bounds.contains(pos)
+                && obj.interaction().contains(state::InteractionState::PRESSED);
+            if pressed
+                || event
+                    .with(|(pos, button, _)| {
+                        !obj.disabled()
+                            && *button == MouseButton::Left
+                            && bounds.contains(*pos)
+                            && is_targeted(obj, aux, *pos)
+                    })
+                    .is_some()
+            {
+                begin_drag(obj, pos);
+            }
+        }
+
+        mouse_move => {
+            drag(obj, event);
+        }
+
+        mouse_release => {
+            end_drag(obj, event);
+        }
+    }
+}
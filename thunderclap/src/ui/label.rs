@@ -1,18 +1,20 @@
 use {
     crate::{
+        a11y,
         base::{self, Repaintable},
-        draw,
+        draw::{self, state},
         geom::*,
         ui,
     },
     reclutch::{
         display::{
-            center_horizontally, Color, DisplayCommand, DisplayListBuilder, DisplayText,
-            GraphicsDisplay, Rect, TextDisplayItem,
+            center_horizontally, Color, DisplayCommand, DisplayText, GraphicsDisplay, Point, Rect,
+            TextDisplayItem, Vector,
         },
         prelude::*,
         verbgraph as vg,
     },
+    std::ops::Range,
 };
 
 /// Horizontal alignment of text.
@@ -23,12 +25,55 @@ pub enum TextAlign {
     Right,
 }
 
+/// Which part of truncated text is replaced by an ellipsis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EllipsisPosition {
+    /// Keeps the start and end of the text, e.g. for file paths.
+    Middle,
+    /// Keeps the start of the text.
+    End,
+}
+
+/// How a label handles text which doesn't fit within its width.
+///
+/// Except for `Wrap`, the label keeps its width (once it has one) and the text stays on a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowBehavior {
+    /// Wraps the text onto new lines at word boundaries.
+    Wrap,
+    /// Clips the text at the edges of the label.
+    Clip,
+    /// Truncates the text with an ellipsis (rich text is clipped instead).
+    Ellipsis(EllipsisPosition),
+    /// Fades out the text towards the end of the label.
+    FadeOut,
+}
+
+/// Styled range of the text of a rich-text [`Label`](struct.Label.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSpan {
+    /// Byte range within the label text.
+    pub range: Range<usize>,
+    pub style: draw::TextStyle,
+    pub color: Color,
+    /// Text size in pixels.
+    pub size: f32,
+    /// Whether the span is a link, in which case it's drawn in the `link_color` of the label rather than `color`.
+    pub link: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Label {
     pub text: DisplayText,
     pub typeface: draw::TypefaceStyle,
     pub color: Color,
+    /// Color of link spans.
+    pub link_color: Color,
     pub align: TextAlign,
-    pub wrap: bool,
+    pub overflow: OverflowBehavior,
+    /// Styled ranges of `text`, which make the label rich text if not empty (only for simple text).
+    /// Text outside the spans keeps the style, color and size of the label.
+    pub spans: Vec<TextSpan>,
 }
 
 impl<U, G> ui::WidgetDataTarget<U, G> for Label
@@ -50,30 +95,30 @@ where
             text: "".to_string().into(),
             typeface: data.typography.body.clone(),
             color: data.scheme.over_control_outset,
+            link_color: data.scheme.link,
             align: TextAlign::Left,
-            wrap: true,
+            overflow: OverflowBehavior::Wrap,
+            spans: Vec::new(),
         }
     }
 
     fn construct(
         self,
-        _theme: &dyn draw::Theme,
+        theme: &dyn draw::Theme,
         _u_aux: &mut U,
     ) -> LabelWidget<U, G>
     where
         U: base::UpdateAuxiliary,
         G: base::GraphicalAuxiliary,
     {
+        let previous_data = self.clone();
         let data = base::Observed::new(self);
 
         let graph = vg::verbgraph! {
             LabelWidget<U, G> as obj,
             U as _aux,
             "bind" => _ev in &data.on_change => {
-                change => {
-                    obj.update_text_items();
-                    obj.repaint();
-                }
+                change => { obj.on_data_change(); }
             }
         };
 
@@ -81,9 +126,12 @@ where
             rect: Default::default(),
             graph: graph.into(),
             data,
+            painter: theme.label(),
 
             text_items: Vec::new(),
+            line_boxes: Vec::new(),
             previous_rect: Default::default(),
+            previous_data,
             dirty: true,
         }
         .build();
@@ -95,12 +143,19 @@ where
     }
 }
 
-impl<U, G> ui::core::CoreWidget<()> for LabelWidget<U, G>
+impl<U, G> ui::core::CoreWidget<state::LabelState> for LabelWidget<U, G>
 where
     U: base::UpdateAuxiliary,
     G: base::GraphicalAuxiliary,
 {
-    fn derive_state(&self) {}
+    fn derive_state(&self) -> state::LabelState {
+        state::LabelState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            runs: self.text_items.clone(),
+            lines: self.line_boxes.clone(),
+        }
+    }
 
     fn on_transform(&mut self) {
         if (self.previous_rect.size.width - self.rect.size.width).abs() > std::f32::EPSILON {
@@ -120,70 +175,413 @@ where
 
 use crate as thunderclap;
 crate::widget! {
+    #[widget_accessible]
     pub struct LabelWidget {
         widget::MAX,
 
         <Label> State,
+        <state::LabelState> Painter,
 
         {
             text_items: Vec<TextDisplayItem>,
+            line_boxes: Vec<AbsoluteRect>,
             previous_rect: RelativeRect,
+            // Data as of the last change, used to skip redundant re-measuring/repainting.
+            previous_data: Label,
             dirty: bool,
         },
     }
 }
 
+impl<U, G> a11y::Accessible for LabelWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn role(&self) -> a11y::Role {
+        a11y::Role::Label
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        a11y::text_name(&self.data.text)
+    }
+}
+
+/// Labels hold no transient state; rebinding data (see `WidgetPool::acquire_with`) is enough.
+impl<U: base::UpdateAuxiliary, G: base::GraphicalAuxiliary> ui::Recyclable for LabelWidget<U, G> {
+    #[inline]
+    fn recycle(&mut self) {}
+}
+
 impl<U: base::UpdateAuxiliary, G: base::GraphicalAuxiliary> LabelWidget<U, G> {
+    /// Returns the bounds of each line of text, as of the last layout.
+    ///
+    /// Since the label sizes itself to its text, this is mostly useful to layouts which need to know
+    /// how the text was broken into lines (e.g. to align baselines or to size a container to the widest line).
+    #[inline]
+    pub fn line_boxes(&self) -> &[AbsoluteRect] {
+        &self.line_boxes
+    }
+
+    /// Compares the data with the data of the last change, so that rebinding identical data
+    /// (e.g. every frame) is free, and a change of color doesn't re-measure the text.
+    fn on_data_change(&mut self) {
+        let previous = &self.previous_data;
+        let recolored = self.data.color != previous.color;
+        if self.data.text != previous.text
+            || self.data.typeface != previous.typeface
+            || self.data.align != previous.align
+            || self.data.overflow != previous.overflow
+            || self.data.spans != previous.spans
+            // Only unspanned rich text takes the label color, so it's re-laid out instead.
+            || ((recolored || self.data.link_color != previous.link_color)
+                && !self.data.spans.is_empty())
+        {
+            self.update_text_items();
+            self.repaint();
+        } else if recolored {
+            for item in &mut self.text_items {
+                item.color = self.data.color.into();
+            }
+            self.repaint();
+        } else {
+            return;
+        }
+
+        self.previous_data = self.data.clone();
+    }
+
     fn update_text_items(&mut self) {
+        let (text_items, line_boxes) = match &self.data.text {
+            DisplayText::Simple(text) if !self.data.spans.is_empty() => self.layout_rich_text(text),
+            _ => self.layout_text(),
+        };
+
+        let rect = self.abs_rect();
+        let mut bounds = line_boxes
+            .iter()
+            .fold(None, |total: Option<AbsoluteRect>, line| {
+                Some(total.map(|total| total.union(line)).unwrap_or(*line))
+            })
+            .unwrap_or_default();
+
+        // Text which doesn't wrap keeps the given width (if any), so that the overflow is clipped.
+        if self.data.overflow != OverflowBehavior::Wrap && rect.size.width > 0.0 {
+            bounds.origin.x = rect.origin.x;
+            bounds.size.width = rect.size.width;
+        }
+
+        self.text_items = text_items;
+        self.line_boxes = line_boxes;
+        self.set_ctxt_rect(bounds);
+    }
+
+    /// Lays out plain text into one run per line, wrapping or truncating it according to `overflow`.
+    /// Returns the runs alongside the bounds of each line.
+    fn layout_text(&self) -> (Vec<TextDisplayItem>, Vec<AbsoluteRect>) {
+        let rect = self.abs_rect();
         let font = self.data.typeface.typeface.pick(self.data.typeface.style);
+        let metrics = font.1.font.metrics();
+        let line_height = (metrics.ascent + metrics.line_gap) / metrics.units_per_em as f32
+            * self.data.typeface.size;
 
-        let mut text = TextDisplayItem {
-            text: self.data.text.clone(),
-            font: font.0,
+        let make_item = |text: DisplayText| TextDisplayItem {
+            text,
+            font: font.0.clone(),
             font_info: font.1.clone(),
             size: self.data.typeface.size,
             bottom_left: Default::default(),
             color: self.data.color.into(),
         };
+        let measure =
+            |text: &str| draw::text_cache::measure_width(&font, self.data.typeface.size, text);
 
-        text.set_top_left(self.abs_rect().origin.cast_unit());
+        let mut text_items: Vec<TextDisplayItem> = match &self.data.text {
+            DisplayText::Simple(text) => {
+                let lines = match self.data.overflow {
+                    OverflowBehavior::Wrap => wrap_words(text, rect.size.width, measure),
+                    OverflowBehavior::Ellipsis(position) if rect.size.width > 0.0 => {
+                        vec![ellipsize(text, rect.size.width, position, measure)]
+                    }
+                    _ => vec![text.clone()],
+                };
 
-        let metrics = font.1.font.metrics();
-        let mut text_items = if self.data.wrap {
-            text.linebreak(
-                self.abs_rect().size.width,
-                (metrics.ascent + metrics.line_gap) / metrics.units_per_em as f32
-                    * self.data.typeface.size,
-                true,
-            )
-            .unwrap()
-        } else {
-            vec![text]
+                lines
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, line)| {
+                        let mut item = make_item(line.into());
+                        item.set_top_left(
+                            rect.origin.cast_unit() + Vector::new(0.0, i as f32 * line_height),
+                        );
+                        item
+                    })
+                    .collect()
+            }
+            // Shaped text can't be split into words here, so it's left to Reclutch to break into lines.
+            text => {
+                let mut item = make_item(text.clone());
+                item.set_top_left(rect.origin.cast_unit());
+                if self.data.overflow == OverflowBehavior::Wrap {
+                    item.linebreak(rect.size.width, line_height, true).unwrap()
+                } else {
+                    vec![item]
+                }
+            }
         };
 
-        let mut total_bounds: Option<AbsoluteRect> = None;
+        let mut line_boxes = Vec::with_capacity(text_items.len());
         for text_item in &mut text_items {
-            let bounds = text_item.bounds().unwrap().cast_unit();
-            if let Some(ref mut total_bounds) = total_bounds {
-                *total_bounds = total_bounds.union(&bounds);
+            let mut bounds: AbsoluteRect = text_item.bounds().unwrap().cast_unit();
+            let left = if bounds.size.width > rect.size.width
+                && self.data.overflow != OverflowBehavior::Wrap
+            {
+                // Overflowing text starts at the left edge, so that it's clipped (or faded) at the end.
+                rect.origin.x
             } else {
-                total_bounds = Some(bounds);
-            }
-            let left = match self.data.align {
-                TextAlign::Left => text_item.bottom_left.x,
-                TextAlign::Middle => {
-                    center_horizontally(bounds.cast_unit(), self.abs_rect().cast_unit()).x
+                match self.data.align {
+                    TextAlign::Left => text_item.bottom_left.x,
+                    TextAlign::Middle => {
+                        center_horizontally(bounds.cast_unit(), rect.cast_unit()).x
+                    }
+                    TextAlign::Right => rect.max_x() - bounds.size.width,
                 }
-                TextAlign::Right => self.abs_rect().max_x() - bounds.size.width,
             };
+            bounds.origin.x += left - text_item.bottom_left.x;
             text_item.bottom_left.x = left;
+            line_boxes.push(bounds);
         }
 
-        self.text_items = text_items;
-        self.set_ctxt_rect(total_bounds.unwrap_or_default());
+        (text_items, line_boxes)
+    }
+
+    /// Lays out rich text into runs, splitting the text at span boundaries and wrapping it at word boundaries.
+    /// Returns the runs alongside the bounds of each line.
+    fn layout_rich_text(&self, text: &str) -> (Vec<TextDisplayItem>, Vec<AbsoluteRect>) {
+        let rect = self.abs_rect();
+
+        let mut lines: Vec<RichLine> = vec![Default::default()];
+        for (range, span) in rich_segments(text, &self.data.spans) {
+            let (style, color, size) = match span {
+                Some(span) => {
+                    let color = if span.link { self.data.link_color } else { span.color };
+                    (span.style, color, span.size)
+                }
+                None => (self.data.typeface.style, self.data.color, self.data.typeface.size),
+            };
+            let font = self.data.typeface.typeface.pick(style);
+            let metrics = font.1.font.metrics();
+            let scale = size / metrics.units_per_em as f32;
+
+            let measure = |text: &str| draw::text_cache::measure_width(&font, size, text);
+
+            for (i, segment_line) in text[range.clone()].split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Default::default());
+                }
+
+                for word in split_words(segment_line) {
+                    let mut line = lines.last_mut().unwrap();
+                    let mut word = word;
+                    if self.data.overflow == OverflowBehavior::Wrap
+                        && !line.runs.is_empty()
+                        && line.width + measure(word.trim_end()) > rect.size.width
+                    {
+                        lines.push(Default::default());
+                        line = lines.last_mut().unwrap();
+                        // Whitespace at the start of a wrapped line is dropped.
+                        word = word.trim_start();
+                        if word.is_empty() {
+                            continue;
+                        }
+                    }
+
+                    line.ascent = line.ascent.max(metrics.ascent * scale);
+                    line.height = line.height.max((metrics.ascent + metrics.line_gap) * scale);
+
+                    let width = measure(word);
+                    match line.runs.last_mut() {
+                        Some((start, run)) if *start == range.start => {
+                            if let DisplayText::Simple(ref mut run_text) = run.text {
+                                run_text.push_str(word);
+                            }
+                        }
+                        _ => line.runs.push((
+                            range.start,
+                            TextDisplayItem {
+                                text: word.to_string().into(),
+                                font: font.0.clone(),
+                                font_info: font.1.clone(),
+                                size,
+                                bottom_left: Point::new(line.width, 0.0),
+                                color: color.into(),
+                            },
+                        )),
+                    }
+                    line.width += width;
+                }
+            }
+        }
+
+        let mut text_items = Vec::new();
+        let mut line_boxes = Vec::new();
+        let mut top = rect.origin.y;
+        for line in lines {
+            let mut line_box: Option<AbsoluteRect> = None;
+            let left = match self.data.align {
+                TextAlign::Left => rect.origin.x,
+                TextAlign::Middle => rect.origin.x + (rect.size.width - line.width) / 2.0,
+                TextAlign::Right => rect.max_x() - line.width,
+            };
+
+            for (_, mut run) in line.runs {
+                run.bottom_left += Vector::new(left, top + line.ascent);
+                if let Ok(bounds) = run.bounds() {
+                    let bounds = bounds.cast_unit();
+                    line_box = Some(match line_box {
+                        Some(line_box) => line_box.union(&bounds),
+                        None => bounds,
+                    });
+                }
+                text_items.push(run);
+            }
+
+            line_boxes.extend(line_box);
+            top += line.height;
+        }
+
+        (text_items, line_boxes)
     }
 }
 
+/// Line of rich text being laid out, with runs positioned relative to the start of the line.
+#[derive(Default)]
+struct RichLine {
+    // Runs, alongside the start of the segment they were laid out from.
+    runs: Vec<(usize, TextDisplayItem)>,
+    width: f32,
+    ascent: f32,
+    height: f32,
+}
+
+/// Splits `text` into consecutive segments, each either within a single span or outside all spans.
+/// Out of range or overlapping spans are clamped.
+fn rich_segments<'a>(
+    text: &str,
+    spans: &'a [TextSpan],
+) -> Vec<(Range<usize>, Option<&'a TextSpan>)> {
+    let mut spans: Vec<&TextSpan> = spans.iter().collect();
+    spans.sort_by_key(|span| span.range.start);
+
+    let clamp = |idx: usize| {
+        let mut idx = idx.min(text.len());
+        while !text.is_char_boundary(idx) {
+            idx -= 1;
+        }
+        idx
+    };
+
+    let mut segments = Vec::new();
+    let mut end = 0;
+    for span in spans {
+        let start = clamp(span.range.start).max(end);
+        let span_end = clamp(span.range.end);
+        if span_end <= start {
+            continue;
+        }
+        if start > end {
+            segments.push((end..start, None));
+        }
+        segments.push((start..span_end, Some(span)));
+        end = span_end;
+    }
+    if end < text.len() {
+        segments.push((end..text.len(), None));
+    }
+
+    segments
+}
+
+/// Wraps `text` at word boundaries (and line breaks) into lines no wider than `width`, as measured by `measure`.
+/// Words wider than `width` are kept whole, on a line of their own.
+fn wrap_words(text: &str, width: f32, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in split_words(paragraph) {
+            if !line.trim().is_empty() && measure(&format!("{}{}", line, word.trim_end())) > width {
+                lines.push(line.trim_end().to_string());
+                line = word.to_string();
+            } else {
+                line.push_str(word);
+            }
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Returns the byte index of the character at `idx` (or the length of `text`, if out of range).
+fn char_index(text: &str, idx: usize) -> usize {
+    text.char_indices().nth(idx).map(|(i, _)| i).unwrap_or_else(|| text.len())
+}
+
+/// Truncates `text` with an ellipsis at `position` so that it's no wider than `width`, as measured by `measure`.
+/// Text which already fits is returned as-is.
+fn ellipsize(
+    text: &str,
+    width: f32,
+    position: EllipsisPosition,
+    measure: impl Fn(&str) -> f32,
+) -> String {
+    if measure(text) <= width {
+        return text.to_string();
+    }
+
+    let count = text.chars().count();
+    let truncate = |keep: usize| match position {
+        EllipsisPosition::End => format!("{}\u{2026}", text[..char_index(text, keep)].trim_end()),
+        EllipsisPosition::Middle => format!(
+            "{}\u{2026}{}",
+            text[..char_index(text, (keep + 1) / 2)].trim_end(),
+            text[char_index(text, count - keep / 2)..].trim_start(),
+        ),
+    };
+
+    // Find the most characters which can be kept, since the width grows with every character kept.
+    let (mut lo, mut hi) = (0, count);
+    while lo < hi {
+        let mid = (lo + hi + 1) / 2;
+        if measure(&truncate(mid)) <= width {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    truncate(lo)
+}
+
+/// Splits `text` after each run of whitespace, so that every word keeps its trailing whitespace.
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut whitespace = false;
+    for (i, c) in text.char_indices() {
+        if whitespace && !c.is_whitespace() {
+            words.push(&text[start..i]);
+            start = i;
+        }
+        whitespace = c.is_whitespace();
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
 impl<U, G> Widget for LabelWidget<U, G>
 where
     U: base::UpdateAuxiliary + 'static,
@@ -215,11 +613,14 @@ where
     }
 
     fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
-        let mut builder = DisplayListBuilder::new();
-        builder.push_rectangle_clip(self.abs_rect().cast_unit(), true);
-        for text_item in &self.text_items {
-            builder.push_text(text_item.clone(), None);
-        }
-        self.command_group.push(display, &builder.build(), Default::default(), None, None);
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
     }
 }
@@ -0,0 +1,215 @@
+use {
+    crate::{
+        base::{self, Rectangular, WidgetChildren},
+        draw,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{DisplayCommand, GraphicsDisplay},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::rc::Rc,
+};
+
+/// Events emitted by a lazy widget.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LazyEvent {
+    /// Emitted once the child has been constructed.
+    #[event_key(construct)]
+    Construct,
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Defers the construction of a (potentially expensive) child until it first becomes visible, or is explicitly activated."]
+    #[doc = "The child is constructed from its widget data through `WidgetConstructor`, using the stored theme."]
+    #[doc = "A lazy widget is deemed visible the first time it's drawn, in which case the child is constructed in the following `update`."]
+    pub struct LazyWidget<
+        D: ui::WidgetConstructor<U, G, Target = W> + 'static,
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static
+    > {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <LazyEvent> EventQueue,
+
+        {
+            pending: Option<D>,
+            theme: Rc<dyn draw::Theme>,
+            child: Option<W>,
+            requested: bool,
+        },
+    }
+}
+
+/// Lazy widget which constructs a child from the widget data `D`, e.g. `Lazy<U, G, Button>`.
+pub type Lazy<U, G, D> = LazyWidget<U, G, D, <D as ui::WidgetDataTarget<U, G>>::Target>;
+
+impl<U, G, D, W> ui::core::CoreWidget<()> for LazyWidget<U, G, D, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    D: ui::WidgetConstructor<U, G, Target = W> + 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+impl<U, G, D, W> LazyWidget<U, G, D, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    D: ui::WidgetConstructor<U, G, Target = W> + 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    /// Creates a new lazy widget which will construct a child from `data` with `theme`.
+    ///
+    /// Until the child is constructed, the lazy widget occupies `rect` (which may be changed by a layout).
+    /// If `rect` is empty then the lazy widget takes on the size of the child once constructed,
+    /// otherwise the child is fitted to `rect`.
+    pub fn new(data: D, theme: Rc<dyn draw::Theme>, rect: RelativeRect) -> Self {
+        LazyWidgetBuilder {
+            rect,
+            graph: vg::VerbGraph::default().into(),
+
+            pending: Some(data),
+            theme,
+            child: None,
+            requested: false,
+        }
+        .build()
+    }
+
+    /// Constructs the child immediately, if it hasn't been already (e.g. when a tab page is opened).
+    pub fn activate(&mut self, u_aux: &mut U) {
+        let data = match self.pending.take() {
+            Some(data) => data,
+            None => return,
+        };
+
+        self.requested = false;
+        let mut child = data.construct(&*self.theme, u_aux);
+
+        if self.rect.size.width <= 0.0 || self.rect.size.height <= 0.0 {
+            self.rect.size = child.rect().size;
+        } else {
+            child.set_ctxt_rect(RelativeRect::new(Default::default(), self.rect.size));
+        }
+
+        self.child = Some(child);
+        let rect = self.abs_rect();
+        self.set_ctxt_rect(rect);
+        self.event_queue.emit_owned(LazyEvent::Construct);
+    }
+
+    /// Returns `true` if the child has been constructed.
+    #[inline]
+    pub fn is_constructed(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Returns the child, if it has been constructed.
+    #[inline]
+    pub fn child(&self) -> Option<&W> {
+        self.child.as_ref()
+    }
+
+    /// Returns the child mutably, if it has been constructed.
+    #[inline]
+    pub fn child_mut(&mut self) -> Option<&mut W> {
+        self.child.as_mut()
+    }
+
+    /// Returns the widget data the child will be constructed from, if it hasn't been constructed yet.
+    #[inline]
+    pub fn pending_data_mut(&mut self) -> Option<&mut D> {
+        self.pending.as_mut()
+    }
+}
+
+impl<U, G, D, W> Widget for LazyWidget<U, G, D, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    D: ui::WidgetConstructor<U, G, Target = W> + 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            if let Some(child) = &mut self.child {
+                child.set_ctxt_rect(RelativeRect::new(Default::default(), rect.size.cast_unit()));
+            }
+        }
+
+        if self.requested {
+            self.activate(aux);
+        }
+
+        base::invoke_update(self, aux);
+    }
+
+    fn draw(&mut self, _display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        // Only invoked when visible.
+        if self.pending.is_some() {
+            self.requested = true;
+        }
+    }
+}
+
+impl<U, G, D, W> WidgetChildren for LazyWidget<U, G, D, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    D: ui::WidgetConstructor<U, G, Target = W> + 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        self.child.iter().map(|child| child as _).collect()
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        self.child.iter_mut().map(|child| child as _).collect()
+    }
+}
@@ -0,0 +1,237 @@
+//! Audio level meter widget.
+
+use {
+    super::Orientation,
+    crate::{
+        base::{self, Repaintable},
+        draw,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{
+            Color, DisplayCommand, DisplayListBuilder, GraphicsDisplay, GraphicsDisplayPaint,
+            Point, Rect, Size,
+        },
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::time::Instant,
+};
+
+/// Peak/RMS level meter with a decaying peak-hold indicator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelMeter {
+    /// Current peak level, from 0.0 to 1.0.
+    pub peak: f32,
+    /// Current RMS level, from 0.0 to 1.0.
+    pub rms: f32,
+    /// How fast the peak-hold indicator falls back to the peak level, in levels per second.
+    pub decay: f32,
+    /// Direction in which the meter fills.
+    pub orientation: Orientation,
+    /// Color of the meter track.
+    pub background: Color,
+    /// Color of the peak bar.
+    pub peak_color: Color,
+    /// Color of the RMS bar (drawn over the peak bar).
+    pub rms_color: Color,
+    /// Color of the peak-hold indicator.
+    pub hold_color: Color,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for LevelMeter
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = LevelMeterWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for LevelMeter
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        LevelMeter {
+            peak: 0.0,
+            rms: 0.0,
+            decay: 0.5,
+            orientation: Orientation::Vertical,
+            background: data.scheme.control_inset,
+            peak_color: draw::weaken(data.scheme.primary, 0.3, data.contrast),
+            rms_color: data.scheme.primary,
+            hold_color: data.scheme.error,
+        }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, _u_aux: &mut U) -> LevelMeterWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            LevelMeterWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.repaint();
+                }
+            }
+        };
+
+        LevelMeterWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), Size::new(10.0, 100.0).cast_unit()),
+            graph: graph.into(),
+            data,
+
+            held_peak: 0.0,
+            last_update: Instant::now(),
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for LevelMeterWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Audio level meter which displays peak and RMS levels, only repainting when the levels change."]
+    pub struct LevelMeterWidget {
+        widget::MAX,
+
+        <LevelMeter> State,
+
+        {
+            held_peak: f32,
+            last_update: Instant,
+        },
+    }
+}
+
+/// Returns the portion of `rect` filled by `level`, filling towards the end of `orientation`.
+fn level_rect(rect: AbsoluteRect, level: f32, orientation: Orientation) -> Rect {
+    let level = level.max(0.0).min(1.0);
+    match orientation {
+        Orientation::Horizontal => {
+            Rect::new(rect.origin.cast_unit(), Size::new(rect.size.width * level, rect.size.height))
+        }
+        Orientation::Vertical => {
+            let height = rect.size.height * level;
+            Rect::new(
+                Point::new(rect.origin.x, rect.max_y() - height),
+                Size::new(rect.size.width, height),
+            )
+        }
+    }
+}
+
+impl<U, G> Widget for LevelMeterWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        // The hold indicator jumps up immediately, but falls back down gradually.
+        let peak = self.data.peak.max(0.0).min(1.0);
+        if peak >= self.held_peak {
+            if peak > self.held_peak {
+                self.held_peak = peak;
+                self.command_group.repaint();
+            }
+        } else {
+            self.held_peak = (self.held_peak - self.data.decay * delta).max(peak);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let rect = self.abs_rect();
+        let data = *self.data;
+        let held_peak = self.held_peak;
+
+        self.command_group.push_with(
+            display,
+            || {
+                let mut builder = DisplayListBuilder::new();
+
+                builder.push_rectangle(
+                    rect.cast_unit(),
+                    GraphicsDisplayPaint::Fill(data.background.into()),
+                    None,
+                );
+
+                builder.push_rectangle(
+                    level_rect(rect, data.peak, data.orientation),
+                    GraphicsDisplayPaint::Fill(data.peak_color.into()),
+                    None,
+                );
+
+                builder.push_rectangle(
+                    level_rect(rect, data.rms, data.orientation),
+                    GraphicsDisplayPaint::Fill(data.rms_color.into()),
+                    None,
+                );
+
+                // Peak-hold indicator; a thin bar at the end of the held level.
+                let hold = level_rect(rect, held_peak, data.orientation);
+                let hold = match data.orientation {
+                    Orientation::Horizontal => Rect::new(
+                        Point::new(hold.max_x() - 2.0, hold.origin.y),
+                        Size::new(2.0, hold.size.height),
+                    ),
+                    Orientation::Vertical => {
+                        Rect::new(hold.origin, Size::new(hold.size.width, 2.0))
+                    }
+                };
+                builder.push_rectangle(
+                    hold,
+                    GraphicsDisplayPaint::Fill(data.hold_color.into()),
+                    None,
+                );
+
+                builder.build()
+            },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,479 @@
+use {
+    crate::{
+        base::{self, Repaintable, WidgetChildren},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect, Size},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::ops::Range,
+};
+
+/// Events emitted by a list view.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListViewEvent {
+    /// Emitted when the user selects an item. Holds the index of the item.
+    #[event_key(select)]
+    Select(usize),
+}
+
+/// Scrollable list which displays a (potentially very long) list of items as rows of widgets.
+///
+/// Row widgets are only constructed for items which intersect the visible area; rows scrolling out of view
+/// are released into a [`WidgetPool`](struct.WidgetPool.html) and rebound to items scrolling into view.
+/// All rows have the same height.
+///
+/// Since the list view is generic over the item and row widget types, it's constructed with
+/// [`construct`](struct.ListView.html#method.construct) rather than through `WidgetConstructor`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListView {
+    /// Height of every row.
+    pub row_height: f32,
+    /// Index of the selected item.
+    pub selected: Option<usize>,
+    pub background: Color,
+    /// Background color of the selected row.
+    pub selection: Color,
+    pub contrast: draw::ThemeContrast,
+    /// Maximum amount of released row widgets kept for reuse.
+    pub pool_capacity: usize,
+    pub disabled: bool,
+}
+
+impl ListView {
+    pub fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        ListView {
+            row_height: 24.0,
+            selected: None,
+            background: data.scheme.control_inset,
+            selection: data.scheme.selection,
+            contrast: data.contrast,
+            pool_capacity: 16,
+            disabled: false,
+        }
+    }
+
+    /// Constructs a list view which displays `items`.
+    ///
+    /// Row widgets are constructed by `create`. When a row widget is reused for a different item, it's updated by `bind`.
+    /// The rows are updated whenever `items` changes.
+    pub fn construct<U, G, T, W>(
+        self,
+        items: base::Observed<Vec<T>>,
+        create: impl FnMut(&T, &mut U) -> W + 'static,
+        bind: impl FnMut(&mut W, &T) + 'static,
+        theme: &dyn draw::Theme,
+        u_aux: &mut U,
+    ) -> ListViewWidget<U, G, T, W>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+        T: 'static,
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+            + base::Rectangular
+            + ui::Recyclable
+            + 'static,
+    {
+        let pool = ui::WidgetPool::new(self.pool_capacity);
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            ListViewWidget<U, G, T, W> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.pool.set_capacity(obj.data.pool_capacity);
+                    obj.dirty = true;
+                }
+            }
+            "items" => _ev in &items.on_change => { change => { obj.items_changed = true; } }
+        };
+
+        graph =
+            graph.add("list_view", list_view_handler::<U, G, T, W>().bind(u_aux.window_queue()));
+
+        ListViewWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+            painter: theme.list_view(),
+
+            items,
+            create: Box::new(create),
+            bind: Box::new(bind),
+            rows: Vec::new(),
+            pool,
+            scroll: 0.0,
+            hovered: None,
+            dirty: true,
+            items_changed: false,
+        }
+        .build()
+    }
+}
+
+/// Handles scrolling, selection and hovering.
+fn list_view_handler<U, G, T, W>(
+) -> vg::UnboundQueueHandler<ListViewWidget<U, G, T, W>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    T: 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + ui::Recyclable
+        + 'static,
+{
+    vg::unbound_queue_handler! {
+        ListViewWidget<U, G, T, W> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let bounds = obj.abs_rect();
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                !obj.data.disabled && *button == base::MouseButton::Left && bounds.contains(*pos)
+            }) {
+                if let Some(index) = obj.index_at(*pos) {
+                    obj.select(index);
+                }
+            }
+        }
+
+        mouse_move => {
+            let pos = event.get().0;
+            let hovered = if obj.abs_rect().contains(pos) { obj.index_at(pos) } else { None };
+            if hovered != obj.hovered {
+                obj.hovered = hovered;
+                obj.repaint();
+            }
+        }
+
+        mouse_wheel => {
+            let bounds = obj.abs_rect();
+            if let Some((_, delta, _)) = event.with(|(pos, _, _)| bounds.contains(*pos)) {
+                let offset = obj.scroll - delta.y;
+                obj.set_scroll_offset(offset);
+            }
+        }
+    }
+}
+
+impl<U, G, T, W> ui::core::CoreWidget<state::ListViewState> for ListViewWidget<U, G, T, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    T: 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + ui::Recyclable
+        + 'static,
+{
+    fn derive_state(&self) -> state::ListViewState {
+        state::ListViewState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            selected: self.data.selected.map(|index| self.row_rect(index)),
+            hovered: self.hovered.map(|index| self.row_rect(index)),
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.dirty = true;
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Virtualized list of items, see `ListView`."]
+    pub struct ListViewWidget<
+        T: 'static,
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + ui::Recyclable
+        + 'static
+    > {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <ListViewEvent> EventQueue,
+        <ListView> State,
+        <state::ListViewState> Painter,
+
+        {
+            items: base::Observed<Vec<T>>,
+            create: Box<dyn FnMut(&T, &mut U) -> W>,
+            bind: Box<dyn FnMut(&mut W, &T)>,
+            // Visible rows, as pairs of item index and row widget.
+            rows: Vec<(usize, W)>,
+            pool: ui::WidgetPool<W>,
+            scroll: f32,
+            hovered: Option<usize>,
+            dirty: bool,
+            items_changed: bool,
+        },
+    }
+}
+
+impl<U, G, T, W> ListViewWidget<U, G, T, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    T: 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + ui::Recyclable
+        + 'static,
+{
+    /// Returns the items.
+    #[inline]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the items mutably; the rows are updated accordingly.
+    #[inline]
+    pub fn items_mut(&mut self) -> &mut base::Observed<Vec<T>> {
+        &mut self.items
+    }
+
+    /// Returns the visible rows, as pairs of item index and row widget.
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &W)> {
+        self.rows.iter().map(|(index, row)| (*index, row))
+    }
+
+    /// Returns the visible rows mutably, as pairs of item index and row widget.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = (usize, &mut W)> {
+        self.rows.iter_mut().map(|(index, row)| (*index, row))
+    }
+
+    /// Returns the range of item indices intersecting the visible area.
+    pub fn visible_range(&self) -> Range<usize> {
+        let row_height = self.data.row_height.max(1.0);
+        let first = (self.scroll / row_height).floor() as usize;
+        let last = ((self.scroll + self.rect.size.height) / row_height).ceil() as usize;
+        first.min(self.items.len())..last.min(self.items.len())
+    }
+
+    /// Returns the vertical distance the list is scrolled by.
+    #[inline]
+    pub fn scroll_offset(&self) -> f32 {
+        self.scroll
+    }
+
+    /// Scrolls the list such that `offset` is at the top of the visible area (clamped to the content).
+    pub fn set_scroll_offset(&mut self, offset: f32) {
+        let offset = offset.min(self.max_scroll_offset()).max(0.0);
+        if (offset - self.scroll).abs() > std::f32::EPSILON {
+            self.scroll = offset;
+            self.dirty = true;
+        }
+    }
+
+    /// Scrolls the least amount such that the item at `index` is entirely visible.
+    pub fn scroll_to(&mut self, index: usize) {
+        let top = index as f32 * self.data.row_height;
+        let bottom = top + self.data.row_height;
+        if top < self.scroll {
+            self.set_scroll_offset(top);
+        } else if bottom > self.scroll + self.rect.size.height {
+            self.set_scroll_offset(bottom - self.rect.size.height);
+        }
+    }
+
+    /// Selects the item at `index`, emitting `ListViewEvent::Select`.
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.data.selected = Some(index);
+            self.event_queue.emit_owned(ListViewEvent::Select(index));
+        }
+    }
+
+    fn max_scroll_offset(&self) -> f32 {
+        (self.items.len() as f32 * self.data.row_height - self.rect.size.height).max(0.0)
+    }
+
+    /// Returns the index of the item at an absolute position.
+    fn index_at(&self, pos: AbsolutePoint) -> Option<usize> {
+        let y = pos.y - self.abs_rect().origin.y + self.scroll;
+        let index = (y / self.data.row_height.max(1.0)).floor();
+        if index >= 0.0 && (index as usize) < self.items.len() {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the absolute rectangle of the row of the item at `index`.
+    fn row_rect(&self, index: usize) -> AbsoluteRect {
+        let rect = self.abs_rect();
+        AbsoluteRect::new(
+            AbsolutePoint::new(
+                rect.origin.x,
+                rect.origin.y + index as f32 * self.data.row_height - self.scroll,
+            ),
+            Size::new(rect.size.width, self.data.row_height).cast_unit(),
+        )
+    }
+
+    /// Releases rows which have scrolled out of view, acquires rows which have scrolled into view and positions them.
+    fn update_rows(&mut self, aux: &mut U) {
+        self.scroll = self.scroll.min(self.max_scroll_offset()).max(0.0);
+        if self.data.selected.map(|selected| selected >= self.items.len()).unwrap_or(false) {
+            self.data.selected = None;
+        }
+        if self.hovered.map(|hovered| hovered >= self.items.len()).unwrap_or(false) {
+            self.hovered = None;
+        }
+
+        let range = self.visible_range();
+        let position = self.abs_rect().origin;
+        let size = Size::new(self.rect.size.width, self.data.row_height);
+        let scroll = self.scroll;
+        let row_height = self.data.row_height;
+
+        let ListViewWidget { items, create, bind, rows, pool, items_changed, .. } = self;
+
+        let mut visible: Vec<(usize, W)> = Vec::with_capacity(range.len());
+        for (index, mut row) in rows.drain(..) {
+            if range.contains(&index) {
+                // The item at this index may have been replaced.
+                if *items_changed {
+                    bind(&mut row, &items[index]);
+                }
+                visible.push((index, row));
+            } else {
+                pool.release(row);
+            }
+        }
+
+        for index in range {
+            if visible.iter().any(|(visible, _)| *visible == index) {
+                continue;
+            }
+
+            let item = &items[index];
+            let mut created = false;
+            let mut row = pool.acquire(|| {
+                created = true;
+                create(item, aux)
+            });
+            if !created {
+                bind(&mut row, item);
+            }
+            visible.push((index, row));
+        }
+
+        visible.sort_by_key(|(index, _)| *index);
+        for (index, row) in &mut visible {
+            row.set_parent_position(position);
+            row.set_ctxt_rect(RelativeRect::new(
+                RelativePoint::new(0.0, *index as f32 * row_height - scroll),
+                size.cast_unit(),
+            ));
+        }
+
+        *rows = visible;
+        *items_changed = false;
+    }
+}
+
+impl<U, G, T, W> Widget for ListViewWidget<U, G, T, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    T: 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + ui::Recyclable
+        + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        // Rows are updated first, so that they can consume mouse presses before the list view selects an item.
+        base::invoke_update(self, aux);
+
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.dirty = true;
+        }
+
+        if self.dirty || self.items_changed {
+            self.dirty = false;
+            self.update_rows(aux);
+            self.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
+
+impl<U, G, T, W> WidgetChildren for ListViewWidget<U, G, T, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    T: 'static,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + ui::Recyclable
+        + 'static,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        self.rows.iter().map(|(_, row)| row as _).collect()
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        self.rows.iter_mut().map(|(_, row)| row as _).collect()
+    }
+
+    /// Rows partially scrolled out of view are culled once they're entirely outside the list view.
+    #[inline]
+    fn viewport(&self) -> Option<AbsoluteRect> {
+        Some(self.abs_rect())
+    }
+}
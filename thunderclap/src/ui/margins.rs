@@ -11,6 +11,7 @@ use {
         euclid::SideOffsets2D,
         event::{bidir_single::Queue as BidirSingleEventQueue, RcEventListener},
         prelude::*,
+        verbgraph as vg,
     },
 };
 
@@ -29,6 +30,9 @@ pub type SideMargins = SideOffsets2D<f32, AbsoluteUnit>;
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Margins {
     pub margins: SideMargins,
+    /// Binds the rectangle to the window, so that the layout fills the window and re-runs whenever it's resized.
+    /// This is intended for the root layout.
+    pub fill_window: bool,
 }
 
 impl<U, G> ui::WidgetDataTarget<U, G> for Margins
@@ -45,13 +49,13 @@ where
     G: base::GraphicalAuxiliary,
 {
     fn from_theme(_theme: &dyn draw::Theme) -> Self {
-        Margins { margins: Default::default() }
+        Margins { margins: Default::default(), fill_window: false }
     }
 
     fn construct(
         self,
         _theme: &dyn draw::Theme,
-        _u_aux: &mut U,
+        u_aux: &mut U,
     ) -> MarginsWidget<U, G>
     where
         U: base::UpdateAuxiliary,
@@ -59,9 +63,20 @@ where
     {
         let data = base::Observed::new(self);
 
+        let mut graph = vg::verbgraph! {
+            MarginsWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.dirty = true; } }
+        };
+
+        graph = graph.add(
+            "window",
+            ui::window_fill_handler::<MarginsWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+
         MarginsWidgetBuilder {
             rect: Default::default(),
-            graph: None,
+            graph: graph.into(),
             data,
 
             rects: IndexMap::new(),
@@ -85,6 +100,17 @@ where
     }
 }
 
+impl<U, G> ui::WindowFill for MarginsWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn fill_window(&self) -> bool {
+        self.data.fill_window
+    }
+}
+
 use crate as thunderclap;
 crate::widget! {
     pub struct MarginsWidget {
@@ -106,6 +132,10 @@ where
     G: base::GraphicalAuxiliary,
 {
     fn resize_to_fit(&mut self) {
+        if self.data.fill_window {
+            return;
+        }
+
         let mut max_rect = AbsoluteRect::default();
         for (_, child) in &self.rects {
             max_rect = max_rect.union(&child.rect);
@@ -178,7 +208,11 @@ where
         self.rect.cast_unit()
     }
 
-    fn update(&mut self, _aux: &mut U) {
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
         if let Some(rect) = self.layout.receive() {
             self.set_ctxt_rect(rect);
             self.dirty = true;
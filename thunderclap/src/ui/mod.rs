@@ -1,22 +1,82 @@
 //! The main part of Thunderclap; a widget toolkit built atop Reclutch.
 
+pub mod blur_panel;
 pub mod button;
+pub mod card;
 pub mod checkbox;
+pub mod clip;
 pub mod container;
+pub mod error_boundary;
+pub mod flex;
 pub mod hstack;
+pub mod interaction;
 pub mod label;
+pub mod lazy;
+pub mod list_view;
 pub mod margins;
 pub mod max_fill;
+pub mod nudge;
+pub mod numeric_input;
+pub mod pool;
 pub mod scroll_bar;
+pub mod startup;
+pub mod surface;
+pub mod tab_view;
 pub mod text_area;
+pub mod text_edit;
+pub mod tooltip;
+pub mod tour;
+pub mod tree_view;
 pub mod vstack;
 
+#[cfg(feature = "forms")]
+pub mod form;
+#[cfg(feature = "images")]
+pub mod image;
+
+#[cfg(feature = "extra-widgets")]
+pub mod calendar_heatmap;
+#[cfg(feature = "extra-widgets")]
+pub mod dashboard;
+#[cfg(feature = "extra-widgets")]
+pub mod gauge;
+#[cfg(feature = "extra-widgets")]
+pub mod gradient_editor;
+#[cfg(feature = "extra-widgets")]
+pub mod level_meter;
+#[cfg(feature = "extra-widgets")]
+pub mod print_preview;
+#[cfg(feature = "extra-widgets")]
+pub mod radial_progress;
+#[cfg(feature = "extra-widgets")]
+pub mod ruler;
+#[cfg(feature = "extra-widgets")]
+pub mod tile_viewer;
+#[cfg(feature = "extra-widgets")]
+pub mod transform_gizmo;
+#[cfg(feature = "extra-widgets")]
+pub mod waveform;
+
 #[macro_use]
 pub mod core;
 
 pub use {
-    button::*, checkbox::*, container::*, hstack::*, label::*, margins::*, max_fill::*,
-    scroll_bar::*, text_area::*, vstack::*,
+    blur_panel::*, button::*, card::*, checkbox::*, clip::*, container::*, error_boundary::*,
+    flex::*, hstack::*, label::*, lazy::*, list_view::*, margins::*, max_fill::*, nudge::*,
+    numeric_input::*, pool::*, scroll_bar::*, startup::*, surface::*, tab_view::*, text_area::*,
+    text_edit::*, tooltip::*, tour::*, tree_view::*, vstack::*,
+};
+
+#[cfg(feature = "forms")]
+pub use form::*;
+#[cfg(feature = "images")]
+pub use image::*;
+
+#[cfg(feature = "extra-widgets")]
+pub use {
+    calendar_heatmap::*, dashboard::*, gauge::*, gradient_editor::*, level_meter::*,
+    print_preview::*, radial_progress::*, ruler::*, tile_viewer::*, transform_gizmo::*,
+    waveform::*,
 };
 
 use {
@@ -26,6 +86,7 @@ use {
         geom::*,
     },
     reclutch::{
+        display::Vector,
         event::RcEventQueue,
         verbgraph::{unbound_queue_handler, UnboundQueueHandler},
     },
@@ -94,21 +155,89 @@ impl Default for Align {
     }
 }
 
+/// The axis along which a widget is laid out or fills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// Left to right.
+    Horizontal,
+    /// Top to bottom.
+    Vertical,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InteractionEvent {
     Pressed(AbsolutePoint),
     Released(AbsolutePoint),
     BeginHover(AbsolutePoint),
     EndHover(AbsolutePoint),
+    /// Emitted after `Pressed` for the second of two successive presses,
+    /// if the widget counts clicks (see `InteractiveWidget::click_counter`).
+    DoubleClick(AbsolutePoint),
     Focus,
     Blur,
 }
 
+impl InteractionEvent {
+    /// Returns the event key, matching that of the equivalent widget events (e.g. `"press"`).
+    pub fn key(&self) -> &'static str {
+        match self {
+            InteractionEvent::Pressed(_) => "press",
+            InteractionEvent::Released(_) => "release",
+            InteractionEvent::BeginHover(_) => "begin_hover",
+            InteractionEvent::EndHover(_) => "end_hover",
+            InteractionEvent::DoubleClick(_) => "double_click",
+            InteractionEvent::Focus => "focus",
+            InteractionEvent::Blur => "blur",
+        }
+    }
+}
+
 pub trait InteractiveWidget: ContextuallyRectangular {
     fn interaction(&mut self) -> &mut state::InteractionState;
     fn mouse_bounds(&self) -> RelativeRect;
     fn disabled(&self) -> bool;
     fn on_interaction_event(&mut self, event: InteractionEvent);
+
+    /// ID of the widget within the focus order (see `base::FocusTracker`),
+    /// if it participates in keyboard focus.
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// Invoked by `basic_interaction_handler` when the mouse wheel is scrolled over the widget.
+    /// Returns `true` if the scroll was handled, in which case the event is consumed.
+    #[inline]
+    fn on_mouse_wheel(&mut self, _delta: Vector, _modifiers: base::KeyModifiers) -> bool {
+        false
+    }
+
+    /// Returns the counter of successive presses, if the widget detects double clicks.
+    ///
+    /// The interaction terminal registers each press with the counter, emitting `InteractionEvent::DoubleClick`
+    /// for the second press within `UpdateAuxiliary::double_click_time`. The click count of the last press
+    /// (e.g. `3` for a triple click) can be read from the counter when handling `Pressed` or `DoubleClick`.
+    #[inline]
+    fn click_counter(&mut self) -> Option<&mut base::ClickCounter> {
+        None
+    }
+
+    /// Kind of widget reported in telemetry records (see the [`telemetry`](../telemetry/index.html) module).
+    /// Defaults to the name of the widget type.
+    #[inline]
+    fn widget_kind(&self) -> &'static str {
+        crate::telemetry::kind_name::<Self>()
+    }
+}
+
+/// Invokes `InteractiveWidget::on_interaction_event` and records the interaction to telemetry.
+fn interaction_event<W: InteractiveWidget, U: base::UpdateAuxiliary>(
+    obj: &mut W,
+    aux: &mut U,
+    event: InteractionEvent,
+) {
+    crate::telemetry::record(aux, obj.widget_kind(), event.key());
+    obj.on_interaction_event(event);
 }
 
 pub trait WidgetDataTarget<U, G>
@@ -139,6 +268,11 @@ where
 /// Generates an unbound terminal which handles basic interactivity.
 /// This simply means it will appropriately modify a `state::InteractionState` and emit events
 /// when interactivity changes occur.
+///
+/// Presses are counted for widgets with a click counter, to emit `InteractionEvent::DoubleClick`.
+///
+/// This combines the hover, press, focus and scroll mixins of the [`interaction`](interaction/index.html) module,
+/// which can be used individually by widgets needing only some of them.
 pub fn basic_interaction_handler<W: InteractiveWidget, U: base::UpdateAuxiliary>(
 ) -> UnboundQueueHandler<W, U, base::WindowEvent> {
     unbound_queue_handler! {
@@ -147,48 +281,96 @@ pub fn basic_interaction_handler<W: InteractiveWidget, U: base::UpdateAuxiliary>
         base::WindowEvent as event,
 
         mouse_press => {
-            let bounds = obj.abs_convert_rect(obj.mouse_bounds());
-            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
-                !obj.disabled()
-                    && *button == base::MouseButton::Left
-                    && bounds.contains(*pos)
-            }) {
-                obj.interaction().insert(state::InteractionState::PRESSED);
-                obj.on_interaction_event(InteractionEvent::Pressed(*pos));
-            }
+            interaction::press(obj, aux, event);
         }
 
         mouse_release => {
-            if let Some((pos, _, _)) = event.with(|(_, button, _)| {
-                !obj.disabled()
-                    && *button == base::MouseButton::Left
-                    && obj.interaction().contains(state::InteractionState::PRESSED)
-            }) {
-                obj.interaction().remove(state::InteractionState::PRESSED);
-                obj.interaction().insert(state::InteractionState::FOCUSED);
-                obj.on_interaction_event(InteractionEvent::Released(*pos));
-                obj.on_interaction_event(InteractionEvent::Focus);
+            if interaction::release(obj, aux, event).is_some() {
+                interaction::focus(obj, aux);
             }
         }
 
         mouse_move => {
-            let bounds = obj.abs_convert_rect(obj.mouse_bounds());
-            if let Some((pos, _)) = event.with(|(pos, _)| bounds.contains(*pos)) {
-                if !obj.interaction().contains(state::InteractionState::HOVERED) {
-                    obj.interaction().insert(state::InteractionState::HOVERED);
-                    obj.on_interaction_event(InteractionEvent::BeginHover(*pos));
+            interaction::hover(obj, aux, event);
+        }
+
+        mouse_wheel => {
+            interaction::wheel(obj, aux, event);
+        }
+
+        clear_focus => {
+            interaction::blur(obj, aux);
+        }
+    }
+}
+
+/// Layout which can be bound to the window, such that it fills the window.
+///
+/// The rectangle of such a layout is updated by `window_fill_handler` whenever the window is resized,
+/// causing the layout to re-run.
+pub trait WindowFill: ContextuallyRectangular {
+    /// Returns `true` if the rectangle should be bound to the window.
+    fn fill_window(&self) -> bool;
+}
+
+/// Generates an unbound terminal which fits a `WindowFill` widget to the window whenever it's resized
+/// (if the widget is bound to the window). This should be bound to the window queue.
+pub fn window_fill_handler<W: WindowFill, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::WindowEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        resized => {
+            if obj.fill_window() {
+                obj.set_ctxt_rect(AbsoluteRect::new(AbsolutePoint::zero(), event.cast_unit()));
+            }
+        }
+    }
+}
+
+/// Generates an unbound terminal which gives focus to (or takes focus from) an `InteractiveWidget`
+/// as directed by the focus tracker. This should be bound to `base::FocusTracker::focus_queue`.
+///
+/// Disabled or hidden widgets pass focus onto the next widget in the focus order.
+///
+/// Activating the focused widget (through spatial navigation) presses and releases it at the center of its mouse bounds.
+pub fn focus_handler<W: InteractiveWidget + base::HasVisibility, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::FocusEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as aux,
+        base::FocusEvent as event,
+
+        focus => {
+            let id = *event;
+            if obj.focus_id() == Some(id) {
+                let hidden = obj.visibility() == base::Visibility::Invisible
+                    || obj.visibility() == base::Visibility::None;
+                if obj.disabled() || hidden {
+                    aux.focus_tracker_mut().skip(id);
+                } else if !obj.interaction().contains(state::InteractionState::FOCUSED) {
+                    obj.interaction().insert(state::InteractionState::FOCUSED);
+                    interaction_event(obj, aux, InteractionEvent::Focus);
                 }
-            } else if obj.interaction().contains(state::InteractionState::HOVERED) {
-                obj.interaction().remove(state::InteractionState::HOVERED);
-                obj.on_interaction_event(InteractionEvent::EndHover(event.get().0));
             }
         }
 
-        clear_focus => {
-            let was_focused = obj.interaction().contains(state::InteractionState::FOCUSED);
-            obj.interaction().remove(state::InteractionState::FOCUSED);
-            if was_focused {
-                obj.on_interaction_event(InteractionEvent::Blur);
+        blur => {
+            if obj.focus_id() == Some(*event)
+                && obj.interaction().contains(state::InteractionState::FOCUSED)
+            {
+                obj.interaction().remove(state::InteractionState::FOCUSED);
+                interaction_event(obj, aux, InteractionEvent::Blur);
+            }
+        }
+
+        activate => {
+            if obj.focus_id() == Some(*event) && !obj.disabled() {
+                let pos = obj.abs_convert_rect(obj.mouse_bounds()).center();
+                interaction_event(obj, aux, InteractionEvent::Pressed(pos));
+                interaction_event(obj, aux, InteractionEvent::Released(pos));
             }
         }
     }
@@ -0,0 +1,103 @@
+//! Keyboard-driven value nudging, shared by numeric widgets (e.g. sliders, spin boxes, color channels).
+//!
+//! While a nudgeable widget is focused, the following keys adjust its value:
+//!
+//! | Key                 | Effect                                    |
+//! |---------------------|-------------------------------------------|
+//! | Up / Right          | Increase by one step.                     |
+//! | Down / Left         | Decrease by one step.                     |
+//! | Shift + arrow       | Step multiplied by `NUDGE_COARSE_FACTOR`. |
+//! | Ctrl + arrow        | Step divided by `NUDGE_FINE_FACTOR`.      |
+//! | Page Up / Page Down | Increase/decrease by one page.            |
+//! | Home / End          | Jump to the minimum/maximum.              |
+//!
+//! Widgets implement `Nudgeable` and add `nudge_handler` to their verb graph (bound to the window queue),
+//! or use `nudge_for_key` directly if a single widget has multiple values (e.g. channels of a color).
+
+use {
+    crate::{base, draw::state, profiling, ui},
+    reclutch::verbgraph::{unbound_queue_handler, UnboundQueueHandler},
+};
+
+/// Factor by which the step is multiplied when Shift is held.
+pub const NUDGE_COARSE_FACTOR: f32 = 10.0;
+/// Factor by which the step is divided when Ctrl is held.
+pub const NUDGE_FINE_FACTOR: f32 = 10.0;
+
+/// Increments by which a value is nudged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NudgeSteps {
+    /// Amount nudged by the arrow keys.
+    pub step: f32,
+    /// Amount nudged by Page Up/Page Down.
+    pub page: f32,
+}
+
+/// An adjustment of a value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Nudge {
+    /// Adjust the value by an amount (which may be negative).
+    By(f32),
+    /// Jump to the minimum value.
+    ToMin,
+    /// Jump to the maximum value.
+    ToMax,
+}
+
+/// Widget with a numeric value which can be nudged from the keyboard.
+pub trait Nudgeable: ui::InteractiveWidget {
+    /// Returns the increments by which the value is nudged.
+    fn nudge_steps(&self) -> NudgeSteps;
+    /// Applies a nudge to the value. The widget is responsible for clamping the value.
+    fn nudge(&mut self, nudge: Nudge);
+}
+
+/// Maps a key press to a nudge, following the conventions listed in the [module documentation](index.html).
+pub fn nudge_for_key(
+    key: base::KeyInput,
+    modifiers: base::KeyModifiers,
+    steps: NudgeSteps,
+) -> Option<Nudge> {
+    let step = if modifiers.shift {
+        steps.step * NUDGE_COARSE_FACTOR
+    } else if modifiers.ctrl {
+        steps.step / NUDGE_FINE_FACTOR
+    } else {
+        steps.step
+    };
+
+    match key {
+        base::KeyInput::Up | base::KeyInput::Right => Some(Nudge::By(step)),
+        base::KeyInput::Down | base::KeyInput::Left => Some(Nudge::By(-step)),
+        base::KeyInput::PageUp => Some(Nudge::By(steps.page)),
+        base::KeyInput::PageDown => Some(Nudge::By(-steps.page)),
+        base::KeyInput::Home => Some(Nudge::ToMin),
+        base::KeyInput::End => Some(Nudge::ToMax),
+        _ => None,
+    }
+}
+
+/// Generates an unbound terminal which nudges a focused `Nudgeable` widget from key presses.
+/// This should be bound to the window queue.
+pub fn nudge_handler<W: Nudgeable, U: base::UpdateAuxiliary>(
+) -> UnboundQueueHandler<W, U, base::WindowEvent> {
+    unbound_queue_handler! {
+        W as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        key_press => {
+            let steps = obj.nudge_steps();
+            let active = !obj.disabled()
+                && obj.interaction().contains(state::InteractionState::FOCUSED);
+            if let Some((key, modifiers)) = event.with(|(key, modifiers)| {
+                active && nudge_for_key(*key, *modifiers, steps).is_some()
+            }) {
+                profiling::record_latency(aux, "key_press", event);
+                if let Some(nudge) = nudge_for_key(*key, *modifiers, steps) {
+                    obj.nudge(nudge);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use {
+    crate::{
+        a11y,
+        base::{self, Repaintable},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph::{self as vg, unbound_queue_handler, UnboundQueueHandler},
+    },
+};
+
+/// Distance (in logical pixels) the cursor has to be dragged before a press turns into a scrub.
+pub const SCRUB_THRESHOLD: f32 = 3.0;
+
+/// Events emitted by a numeric input.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum NumericInputEvent {
+    /// Emitted when the value has been changed by the user (by scrubbing or nudging).
+    #[event_key(user_modify)]
+    UserModify(f32),
+    /// Emitted when the user starts dragging to change the value.
+    #[event_key(begin_scrub)]
+    BeginScrub,
+    /// Emitted when the user stops dragging to change the value.
+    #[event_key(end_scrub)]
+    EndScrub,
+    /// Emitted when the mouse enters the numeric input boundaries.
+    #[event_key(begin_hover)]
+    BeginHover(AbsolutePoint),
+    /// Emitted when the mouse leaves the numeric input boundaries.
+    #[event_key(end_hover)]
+    EndHover(AbsolutePoint),
+    /// Emitted when focus is gained.
+    #[event_key(focus)]
+    Focus,
+    /// Emitted when focus is lost.
+    #[event_key(blur)]
+    Blur,
+}
+
+/// State of a press on a numeric input, which turns into a scrub once dragged far enough.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scrub {
+    last: AbsolutePoint,
+    distance: f32,
+    active: bool,
+}
+
+impl<U, G> ui::InteractiveWidget for NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline(always)]
+    fn interaction(&mut self) -> &mut state::InteractionState {
+        &mut self.interaction
+    }
+
+    #[inline]
+    fn mouse_bounds(&self) -> RelativeRect {
+        self.painter.mouse_hint(self.rect)
+    }
+
+    #[inline(always)]
+    fn disabled(&self) -> bool {
+        self.data.disabled
+    }
+
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        Some(self.focus.id())
+    }
+
+    fn on_interaction_event(&mut self, event: ui::InteractionEvent) {
+        self.repaint();
+        match event {
+            ui::InteractionEvent::BeginHover(pos) => {
+                self.event_queue.emit_owned(NumericInputEvent::BeginHover(pos));
+            }
+            ui::InteractionEvent::EndHover(pos) => {
+                self.event_queue.emit_owned(NumericInputEvent::EndHover(pos));
+            }
+            ui::InteractionEvent::Focus => {
+                self.event_queue.emit_owned(NumericInputEvent::Focus);
+            }
+            ui::InteractionEvent::Blur => {
+                self.event_queue.emit_owned(NumericInputEvent::Blur);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<U, G> ui::Nudgeable for NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn nudge_steps(&self) -> ui::NudgeSteps {
+        ui::NudgeSteps { step: self.data.step, page: self.data.step * ui::NUDGE_COARSE_FACTOR }
+    }
+
+    fn nudge(&mut self, nudge: ui::Nudge) {
+        let value = match nudge {
+            ui::Nudge::By(amount) => self.data.value + amount,
+            ui::Nudge::ToMin => self.data.min,
+            ui::Nudge::ToMax => self.data.max,
+        };
+        self.set_value(value);
+    }
+}
+
+impl<U, G> a11y::Accessible for NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn role(&self) -> a11y::Role {
+        a11y::Role::SpinButton
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        String::new()
+    }
+
+    #[inline]
+    fn value(&self) -> Option<String> {
+        Some(self.data.display_text())
+    }
+
+    fn state(&self) -> a11y::AccessibleState {
+        let mut state = a11y::AccessibleState::from_interaction(self.interaction);
+        state.set(a11y::AccessibleState::DISABLED, self.data.disabled);
+        state
+    }
+}
+
+impl<U, G> ui::Recyclable for NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn recycle(&mut self) {
+        self.interaction = state::InteractionState::empty();
+        self.scrub = None;
+        self.repaint();
+    }
+}
+
+/// Numeric value field which can be changed by dragging horizontally or from the keyboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericInput {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    /// Amount nudged by a single arrow key press.
+    pub step: f32,
+    /// Amount changed per logical pixel dragged.
+    pub scrub_speed: f32,
+    /// Amount of decimal places shown.
+    pub precision: usize,
+    pub typeface: draw::TypefaceStyle,
+    pub color: Color,
+    pub background: Color,
+    pub focus: Color,
+    pub contrast: draw::ThemeContrast,
+    pub disabled: bool,
+}
+
+impl NumericInput {
+    /// Returns the value formatted with `precision` decimal places.
+    #[inline]
+    pub fn display_text(&self) -> String {
+        format!("{:.*}", self.precision, self.value)
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for NumericInput
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = NumericInputWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for NumericInput
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        NumericInput {
+            value: 0.0,
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            scrub_speed: 0.1,
+            precision: 1,
+            typeface: data.typography.body.clone(),
+            color: data.scheme.over_control_inset,
+            background: data.scheme.control_inset,
+            focus: data.scheme.focus,
+            contrast: data.contrast,
+            disabled: false,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> NumericInputWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            NumericInputWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.command_group.repaint(); } }
+        };
+
+        graph = graph.add(
+            "interaction",
+            ui::basic_interaction_handler::<NumericInputWidget<U, G>, U>()
+                .bind(u_aux.window_queue()),
+        );
+        graph = graph.add("scrub", scrub_handler::<U, G>().bind(u_aux.window_queue()));
+        graph = graph.add(
+            "nudge",
+            ui::nudge_handler::<NumericInputWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+        graph = graph.add(
+            "focus",
+            ui::focus_handler::<NumericInputWidget<U, G>, U>()
+                .bind(u_aux.focus_tracker().focus_queue()),
+        );
+
+        let painter = theme.numeric_input();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::NumericInputState {
+                    rect: Default::default(),
+                    data: data.clone(),
+                    interaction: state::InteractionState::empty(),
+                    scrubbing: false,
+                    corner_radius: None,
+                })
+                .cast_unit(),
+        );
+
+        NumericInputWidgetBuilder {
+            rect,
+            graph: graph.into(),
+
+            data,
+            painter,
+
+            interaction: state::InteractionState::empty(),
+            focus: u_aux.focus_tracker_mut().register(),
+            scrub: None,
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::NumericInputState> for NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::NumericInputState {
+        state::NumericInputState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            interaction: self.interaction,
+            scrubbing: self.is_scrubbing(),
+            corner_radius: None,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.focus.set_rect(self.abs_rect());
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[widget_accessible]
+    pub struct NumericInputWidget {
+        widget::MAX,
+
+        <NumericInputEvent> EventQueue,
+        <NumericInput> State,
+        <state::NumericInputState> Painter,
+
+        {
+            interaction: state::InteractionState,
+            focus: base::FocusHandle,
+            scrub: Option<Scrub>,
+        },
+    }
+}
+
+impl<U, G> NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Returns `true` if the user is dragging to change the value.
+    #[inline]
+    pub fn is_scrubbing(&self) -> bool {
+        self.scrub.map(|scrub| scrub.active).unwrap_or(false)
+    }
+
+    /// Clamps and applies a value changed by the user, emitting `UserModify` if it changed.
+    fn set_value(&mut self, value: f32) {
+        let value = value.max(self.data.min).min(self.data.max);
+        if (value - self.data.value).abs() > std::f32::EPSILON {
+            self.data.value = value;
+            self.event_queue.emit_owned(NumericInputEvent::UserModify(value));
+        }
+    }
+
+    /// Handles horizontal movement of the cursor while pressed.
+    fn scrub_by(&mut self, dx: f32, modifiers: base::KeyModifiers, aux: &mut U) {
+        let mut scrub = match self.scrub {
+            Some(scrub) => scrub,
+            None => return,
+        };
+
+        scrub.distance += dx.abs();
+        if !scrub.active && scrub.distance >= SCRUB_THRESHOLD {
+            scrub.active = true;
+            aux.set_pointer_lock(true);
+            self.repaint();
+            self.event_queue.emit_owned(NumericInputEvent::BeginScrub);
+        }
+        self.scrub = Some(scrub);
+
+        if scrub.active {
+            let speed = if modifiers.shift {
+                self.data.scrub_speed * ui::NUDGE_COARSE_FACTOR
+            } else if modifiers.ctrl {
+                self.data.scrub_speed / ui::NUDGE_FINE_FACTOR
+            } else {
+                self.data.scrub_speed
+            };
+            self.set_value(self.data.value + dx * speed);
+        }
+    }
+
+    /// Ends the scrub (if any), releasing the pointer lock.
+    fn end_scrub(&mut self, aux: &mut U) {
+        if let Some(scrub) = self.scrub.take() {
+            if scrub.active {
+                aux.set_pointer_lock(false);
+                self.repaint();
+                self.event_queue.emit_owned(NumericInputEvent::EndScrub);
+            }
+        }
+    }
+}
+
+/// Changes the value as the numeric input is dragged.
+///
+/// The pointer is locked while dragging (if the update auxiliary supports it), so that the value keeps
+/// changing past the edges of the window. Otherwise, the movement of the cursor is used.
+fn scrub_handler<U, G>() -> UnboundQueueHandler<NumericInputWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    unbound_queue_handler! {
+        NumericInputWidget<U, G> as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let bounds = obj.abs_convert_rect(obj.painter.mouse_hint(obj.rect));
+            let (pos, button, _) = event.get();
+            if !obj.data.disabled && *button == base::MouseButton::Left && bounds.contains(*pos) {
+                obj.scrub = Some(Scrub { last: *pos, distance: 0.0, active: false });
+            }
+        }
+
+        mouse_release => {
+            if event.get().1 == base::MouseButton::Left {
+                obj.end_scrub(aux);
+            }
+        }
+
+        mouse_move => {
+            if let Some(mut scrub) = obj.scrub {
+                if !aux.pointer_locked() {
+                    let (pos, modifiers) = *event.get();
+                    let dx = pos.x - scrub.last.x;
+                    scrub.last = pos;
+                    obj.scrub = Some(scrub);
+                    obj.scrub_by(dx, modifiers, aux);
+                }
+            }
+        }
+
+        mouse_delta => {
+            if obj.scrub.is_some() {
+                let (delta, modifiers) = *event.get();
+                obj.scrub_by(delta.x, modifiers, aux);
+            }
+        }
+
+        clear_focus => {
+            // Emitted before any press and when the window loses focus; both interrupt a scrub.
+            obj.end_scrub(aux);
+        }
+    }
+}
+
+impl<U, G> Widget for NumericInputWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{base, ui};
+
+/// Widget which can be reused by a `WidgetPool` once it has scrolled out of view.
+pub trait Recyclable {
+    /// Resets transient state (e.g. hover, press and focus) so that the widget can be shown again as if new.
+    fn recycle(&mut self);
+}
+
+/// Stores widgets which have scrolled out of view in a virtualized widget (e.g. a list or table),
+/// so they can be reused for rows scrolling into view instead of constructing new widgets.
+///
+/// Released widgets are recycled and hidden (`Visibility::None`), which also excludes them
+/// from keyboard focus until they're acquired again.
+#[derive(Debug)]
+pub struct WidgetPool<W> {
+    free: Vec<W>,
+    capacity: usize,
+}
+
+impl<W: Recyclable + base::HasVisibility> WidgetPool<W> {
+    /// Creates an empty pool which holds onto at most `capacity` released widgets.
+    pub fn new(capacity: usize) -> Self {
+        WidgetPool { free: Vec::new(), capacity }
+    }
+
+    /// Returns a released widget if there is one, otherwise constructs a new widget with `create`.
+    pub fn acquire(&mut self, create: impl FnOnce() -> W) -> W {
+        match self.free.pop() {
+            Some(mut widget) => {
+                widget.set_visibility(base::Visibility::Normal);
+                widget
+            }
+            None => create(),
+        }
+    }
+
+    /// Same as `acquire`, except a released widget is rebound to `data`,
+    /// whereas a new widget is constructed from `data` with `create`.
+    pub fn acquire_with<D>(&mut self, data: D, create: impl FnOnce(D) -> W) -> W
+    where
+        W: ui::DefaultWidgetData<D>,
+    {
+        match self.free.pop() {
+            Some(mut widget) => {
+                widget.set_visibility(base::Visibility::Normal);
+                widget.default_data().set(data);
+                widget
+            }
+            None => create(data),
+        }
+    }
+
+    /// Returns a widget to the pool, recycling it.
+    /// If the pool is full then the widget is dropped.
+    pub fn release(&mut self, mut widget: W) {
+        if self.free.len() < self.capacity {
+            widget.recycle();
+            widget.set_visibility(base::Visibility::None);
+            self.free.push(widget);
+        }
+    }
+
+    /// Returns the amount of released widgets in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns `true` if there are no released widgets in the pool.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// Returns the maximum amount of released widgets the pool holds onto.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity, dropping released widgets past the new capacity.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.free.truncate(capacity);
+    }
+
+    /// Drops all the released widgets.
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+}
@@ -0,0 +1,764 @@
+//! Print preview widget, which paginates a document and feeds its pages to an exporter.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw, error,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{
+            Color, CommandGroupHandle, DisplayClip, DisplayCommand, DisplayListBuilder,
+            GraphicsDisplay, GraphicsDisplayPaint, GraphicsDisplayStroke, Rect, ResourceDescriptor,
+            ResourceReference, Size, Vector, ZOrder,
+        },
+        error::{DisplayError, ResourceError},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{collections::BTreeMap, ops::Range},
+};
+
+/// Size and margins of a printed page, in points (1/72 of an inch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSetup {
+    /// Size of the whole page.
+    pub size: Size,
+    /// Space between the edges of the page and the content.
+    pub margin: f32,
+}
+
+impl PageSetup {
+    /// A4 paper (210 × 297 mm) with 0.5 inch margins.
+    pub fn a4() -> Self {
+        PageSetup { size: Size::new(595.0, 842.0), margin: 36.0 }
+    }
+
+    /// US Letter paper (8.5 × 11 in) with 0.5 inch margins.
+    pub fn letter() -> Self {
+        PageSetup { size: Size::new(612.0, 792.0), margin: 36.0 }
+    }
+
+    /// Returns the size of the area of the page the content is printed within.
+    /// Documents should be laid out to the width of this area.
+    pub fn printable_size(&self) -> Size {
+        Size::new(
+            (self.size.width - self.margin * 2.0).max(0.0),
+            (self.size.height - self.margin * 2.0).max(0.0),
+        )
+    }
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSetup::a4()
+    }
+}
+
+/// Content which is paginated by a [`PrintPreviewWidget`](struct.PrintPreviewWidget.html).
+///
+/// The content is a single (typically tall) strip, laid out to the printable width of a page
+/// (see `PageSetup::printable_size`), which is cut into page-sized segments.
+pub trait PrintDocument<G> {
+    /// Returns the size of the content.
+    fn size(&self) -> Size;
+
+    /// Returns the offsets from the top of the content at which pages should preferably break
+    /// (e.g. between paragraphs or table rows), in any order.
+    ///
+    /// Pages which don't contain a break point are cut at the bottom of the page.
+    /// By default, there are no break points.
+    fn break_points(&self) -> Vec<f32> {
+        Vec::new()
+    }
+
+    /// Returns the commands which draw the content, with the top-left of the content at the origin.
+    ///
+    /// Any resources (e.g. fonts) the commands refer to are created in `display`.
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) -> Vec<DisplayCommand>;
+}
+
+/// Destination of printed pages, e.g. a PDF writer or a printer driver (see `PrintPreviewWidget::export`).
+///
+/// Pages are given as display commands in points, with the top-left of the page at the origin.
+/// The commands may refer to resources of the display the preview was drawn into.
+pub trait PageSink {
+    /// Starts a new page of a given size.
+    fn begin_page(&mut self, size: Size) -> Result<(), Box<dyn std::error::Error>>;
+    /// Draws onto the current page.
+    fn draw(&mut self, commands: &[DisplayCommand]) -> Result<(), Box<dyn std::error::Error>>;
+    /// Finishes the current page.
+    fn end_page(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    /// Finishes the document, once every page has been written.
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Splits content `content_height` tall into segments at most `page_height` tall, breaking at the last
+/// break point within each page, or at the bottom of the page if there isn't any.
+///
+/// Empty content results in a single empty page.
+pub fn paginate(content_height: f32, page_height: f32, break_points: &[f32]) -> Vec<Range<f32>> {
+    let content_height = content_height.max(0.0);
+    if page_height <= 0.0 || !page_height.is_finite() || content_height <= page_height {
+        return vec![0.0..content_height];
+    }
+
+    let mut pages = Vec::new();
+    let mut top = 0.0;
+    while content_height - top > page_height {
+        let limit = top + page_height;
+        let bottom = break_points
+            .iter()
+            .copied()
+            .filter(|&point| point > top && point <= limit)
+            .fold(None, |bottom: Option<f32>, point| Some(bottom.map_or(point, |b| b.max(point))))
+            .unwrap_or(limit);
+        pages.push(top..bottom);
+        top = bottom;
+    }
+    pages.push(top..content_height);
+    pages
+}
+
+/// Returns the commands which print the `segment` of `content` onto a page.
+fn page_commands(
+    content: &[DisplayCommand],
+    segment: &Range<f32>,
+    setup: PageSetup,
+) -> Vec<DisplayCommand> {
+    let printable = setup.printable_size();
+    let mut commands = Vec::with_capacity(content.len() + 4);
+    commands.push(DisplayCommand::Save);
+    commands.push(DisplayCommand::Clip(DisplayClip::Rectangle {
+        rect: Rect::new(
+            reclutch::display::Point::new(setup.margin, setup.margin),
+            Size::new(printable.width, segment.end - segment.start),
+        ),
+        antialias: false,
+    }));
+    commands
+        .push(DisplayCommand::Translate(Vector::new(setup.margin, setup.margin - segment.start)));
+    commands.extend(content.iter().cloned());
+    commands.push(DisplayCommand::Restore);
+    commands
+}
+
+/// Events emitted by a print preview.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum PrintPreviewEvent {
+    /// The page shown in the page view has changed.
+    #[event_key(page_change)]
+    PageChange(usize),
+    /// The page view has been zoomed by the user, to a new zoom factor.
+    #[event_key(zoom)]
+    Zoom(f32),
+}
+
+/// Shows a document (see [`PrintDocument`](trait.PrintDocument.html)) cut into pages, as a column of page thumbnails
+/// beside a zoomable view of the current page.
+///
+/// Clicking a thumbnail shows its page. Scrolling over the page view scrolls the page, or zooms it while holding Ctrl.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintPreview {
+    /// Size and margins of the pages.
+    pub page: PageSetup,
+    /// Index of the page shown in the page view.
+    pub current_page: usize,
+    /// Scale of the page view, where 1 shows a point as a logical pixel.
+    pub zoom: f32,
+    /// Lowest zoom factor the user can zoom out to.
+    pub min_zoom: f32,
+    /// Highest zoom factor the user can zoom in to.
+    pub max_zoom: f32,
+    /// Width of the page thumbnails.
+    pub thumbnail_width: f32,
+    /// Space around the thumbnails and the page.
+    pub spacing: f32,
+    /// Color drawn behind the pages.
+    pub background: Color,
+    /// Color of the paper.
+    pub paper: Color,
+    /// Color of the outline of the pages.
+    pub border: Color,
+    /// Color of the outline of the thumbnail of the current page.
+    pub selection: Color,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for PrintPreview
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = PrintPreviewWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for PrintPreview
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        PrintPreview {
+            page: Default::default(),
+            current_page: 0,
+            zoom: 1.0,
+            min_zoom: 0.25,
+            max_zoom: 4.0,
+            thumbnail_width: 96.0,
+            spacing: 16.0,
+            background: data.scheme.control_outset,
+            paper: Color::new(1.0, 1.0, 1.0, 1.0),
+            border: draw::weaken(data.scheme.over_control_outset, 0.8, data.contrast),
+            selection: data.scheme.focus,
+        }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, u_aux: &mut U) -> PrintPreviewWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            PrintPreviewWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph =
+            graph.add("print_preview", print_preview_handler::<U, G>().bind(u_aux.window_queue()));
+
+        PrintPreviewWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), Size::new(640.0, 480.0).cast_unit()),
+            graph: graph.into(),
+            data,
+
+            document: None,
+            content: None,
+            pages: Vec::new(),
+            paginated: None,
+            thumbnail_scroll: 0.0,
+            page_scroll: 0.0,
+        }
+        .build()
+    }
+}
+
+/// Handles selecting pages, scrolling and zooming.
+fn print_preview_handler<U, G>(
+) -> vg::UnboundQueueHandler<PrintPreviewWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        PrintPreviewWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let thumbnails = obj.thumbnail_rects();
+            let view = obj.thumbnail_view();
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                *button == base::MouseButton::Left
+                    && view.contains(*pos)
+                    && thumbnails.iter().any(|rect| rect.contains(*pos))
+            }) {
+                if let Some(page) = thumbnails.iter().position(|rect| rect.contains(*pos)) {
+                    obj.set_page(page);
+                }
+            }
+        }
+
+        mouse_wheel => {
+            let bounds = obj.abs_rect();
+            if let Some((pos, delta, modifiers)) = event.with(|(pos, _, _)| bounds.contains(*pos)) {
+                if obj.thumbnail_view().contains(*pos) {
+                    let scroll = obj.thumbnail_scroll - delta.y;
+                    obj.thumbnail_scroll = scroll.max(0.0).min(obj.max_thumbnail_scroll());
+                } else if modifiers.ctrl {
+                    let factor = if delta.y > 0.0 { 1.1 } else { 1.0 / 1.1 };
+                    let zoom = obj.data.zoom * factor;
+                    obj.set_zoom(zoom);
+                    obj.event_queue.emit_owned(PrintPreviewEvent::Zoom(obj.data.zoom));
+                } else {
+                    let scroll = obj.page_scroll - delta.y;
+                    obj.page_scroll = scroll.max(0.0).min(obj.max_page_scroll());
+                }
+                obj.command_group.repaint();
+            }
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for PrintPreviewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Paginated preview of a document, which exports its pages to a `PageSink`."]
+    #[doc = "The document is paginated when it's first drawn, and again whenever the page setup changes or `refresh` is invoked."]
+    pub struct PrintPreviewWidget {
+        widget::MAX,
+
+        <PrintPreviewEvent> EventQueue,
+        <PrintPreview> State,
+
+        {
+            document: Option<Box<dyn PrintDocument<G>>>,
+            content: Option<Vec<DisplayCommand>>,
+            pages: Vec<Range<f32>>,
+            // Page setup the pages were cut for.
+            paginated: Option<PageSetup>,
+            thumbnail_scroll: f32,
+            page_scroll: f32,
+        },
+    }
+}
+
+impl<U, G> PrintPreviewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Changes the previewed document, which is paginated when the preview is next drawn.
+    pub fn set_document(&mut self, document: impl PrintDocument<G> + 'static) {
+        self.document = Some(Box::new(document));
+        self.refresh();
+    }
+
+    /// Draws and paginates the document again when the preview is next drawn, e.g. after the document has changed.
+    pub fn refresh(&mut self) {
+        self.content = None;
+        self.command_group.repaint();
+    }
+
+    /// Returns the amount of pages, or 0 if the document hasn't been paginated yet.
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns the segments of the content printed on each page, as offsets from the top of the content.
+    #[inline]
+    pub fn pages(&self) -> &[Range<f32>] {
+        &self.pages
+    }
+
+    /// Shows a page in the page view, scrolled to its top.
+    pub fn set_page(&mut self, page: usize) {
+        let page = page.min(self.pages.len().saturating_sub(1));
+        if page != self.data.current_page {
+            self.data.current_page = page;
+            self.page_scroll = 0.0;
+            self.event_queue.emit_owned(PrintPreviewEvent::PageChange(page));
+        }
+    }
+
+    /// Changes the zoom factor of the page view, clamped to the zoom limits.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.data.zoom = zoom.max(self.data.min_zoom).min(self.data.max_zoom);
+        self.page_scroll = self.page_scroll.min(self.max_page_scroll());
+    }
+
+    /// Zooms the page view such that the whole page is visible.
+    pub fn fit_page(&mut self) {
+        let view = self.page_view();
+        let page = self.data.page.size;
+        let spacing = self.data.spacing * 2.0;
+        self.set_zoom(
+            ((view.size.width - spacing) / page.width)
+                .min((view.size.height - spacing) / page.height),
+        );
+    }
+
+    /// Zooms the page view such that the page fills the width of the view.
+    pub fn fit_width(&mut self) {
+        let view = self.page_view();
+        self.set_zoom((view.size.width - self.data.spacing * 2.0) / self.data.page.size.width);
+    }
+
+    /// Returns the commands which print a page, or `None` if there's no such page (see `PageSink`).
+    pub fn page_commands(&self, page: usize) -> Option<Vec<DisplayCommand>> {
+        let content = self.content.as_ref()?;
+        let segment = self.pages.get(page)?;
+        Some(page_commands(content, segment, self.data.page))
+    }
+
+    /// Writes every page to `sink`, e.g. to export the document as a PDF.
+    ///
+    /// The document has to have been paginated, i.e. the preview has to have been drawn since the document was set.
+    pub fn export(&self, sink: &mut dyn PageSink) -> Result<(), error::PrintError> {
+        if self.content.is_none() || self.pages.is_empty() {
+            return Err(error::PrintError::NotPaginated);
+        }
+
+        for page in 0..self.pages.len() {
+            let commands = self.page_commands(page).unwrap();
+            sink.begin_page(self.data.page.size).map_err(error::PrintError::ExportError)?;
+            sink.draw(&commands).map_err(error::PrintError::ExportError)?;
+            sink.end_page().map_err(error::PrintError::ExportError)?;
+        }
+        sink.finish().map_err(error::PrintError::ExportError)
+    }
+
+    fn thumbnail_view(&self) -> AbsoluteRect {
+        let rect = self.abs_rect();
+        let width = (self.data.thumbnail_width + self.data.spacing * 2.0).min(rect.size.width);
+        AbsoluteRect::new(rect.origin, Size::new(width, rect.size.height).cast_unit())
+    }
+
+    fn page_view(&self) -> AbsoluteRect {
+        let rect = self.abs_rect();
+        let thumbnails = self.thumbnail_view().size.width;
+        AbsoluteRect::new(
+            AbsolutePoint::new(rect.origin.x + thumbnails, rect.origin.y),
+            Size::new(rect.size.width - thumbnails, rect.size.height).cast_unit(),
+        )
+    }
+
+    fn thumbnail_height(&self) -> f32 {
+        let page = self.data.page.size;
+        if page.width > 0.0 {
+            self.data.thumbnail_width * page.height / page.width
+        } else {
+            0.0
+        }
+    }
+
+    fn thumbnail_rects(&self) -> Vec<AbsoluteRect> {
+        let view = self.thumbnail_view();
+        let height = self.thumbnail_height();
+        (0..self.pages.len())
+            .map(|page| {
+                AbsoluteRect::new(
+                    AbsolutePoint::new(
+                        view.origin.x + self.data.spacing,
+                        view.origin.y
+                            + self.data.spacing
+                            + page as f32 * (height + self.data.spacing)
+                            - self.thumbnail_scroll,
+                    ),
+                    Size::new(self.data.thumbnail_width, height).cast_unit(),
+                )
+            })
+            .collect()
+    }
+
+    fn max_thumbnail_scroll(&self) -> f32 {
+        let pages = self.pages.len() as f32;
+        let height = pages * (self.thumbnail_height() + self.data.spacing) + self.data.spacing;
+        (height - self.thumbnail_view().size.height).max(0.0)
+    }
+
+    fn page_rect(&self) -> AbsoluteRect {
+        let view = self.page_view();
+        let size = self.data.page.size * self.data.zoom;
+        AbsoluteRect::new(
+            AbsolutePoint::new(
+                view.origin.x + ((view.size.width - size.width) / 2.0).max(self.data.spacing),
+                view.origin.y + self.data.spacing - self.page_scroll,
+            ),
+            size.cast_unit(),
+        )
+    }
+
+    fn max_page_scroll(&self) -> f32 {
+        let height = self.data.page.size.height * self.data.zoom + self.data.spacing * 2.0;
+        (height - self.page_view().size.height).max(0.0)
+    }
+
+    /// Draws and paginates the document if it has changed or the page setup has changed.
+    fn paginate(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) {
+        let setup = self.data.page;
+        if self.content.is_some() && self.paginated == Some(setup) {
+            return;
+        }
+
+        let (content, pages) = match &mut self.document {
+            Some(document) => {
+                let size = document.size();
+                let pages =
+                    paginate(size.height, setup.printable_size().height, &document.break_points());
+                (document.draw(display, aux), pages)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        self.content = Some(content);
+        self.pages = pages;
+        self.paginated = Some(setup);
+        self.thumbnail_scroll = self.thumbnail_scroll.min(self.max_thumbnail_scroll());
+        if self.data.current_page >= self.pages.len() {
+            self.set_page(self.pages.len().saturating_sub(1));
+        }
+        self.command_group.repaint();
+    }
+}
+
+/// Pushes a page scaled into `rect`.
+fn push_page(
+    commands: &mut Vec<DisplayCommand>,
+    rect: AbsoluteRect,
+    page: Vec<DisplayCommand>,
+    data: &PrintPreview,
+    selected: bool,
+) {
+    let mut builder = DisplayListBuilder::new();
+    builder.push_rectangle(rect.cast_unit(), GraphicsDisplayPaint::Fill(data.paper.into()), None);
+    builder.push_rectangle(
+        base::sharp_align(rect.cast_unit()),
+        GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+            thickness: if selected { 2.0 } else { 1.0 },
+            color: if selected { data.selection } else { data.border }.into(),
+            ..Default::default()
+        }),
+        None,
+    );
+    commands.extend(builder.build());
+
+    let scale = rect.size.width / data.page.size.width;
+    commands.push(DisplayCommand::Save);
+    commands.push(DisplayCommand::Translate(rect.origin.to_vector().cast_unit()));
+    commands.push(DisplayCommand::Scale(Vector::new(scale, scale)));
+    commands.extend(page);
+    commands.push(DisplayCommand::Restore);
+}
+
+impl<U, G> Widget for PrintPreviewWidget<U, G>
+where
+    U: base::UpdateAuxiliary + 'static,
+    G: base::GraphicalAuxiliary + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) {
+        self.paginate(display, aux);
+
+        let rect = self.abs_rect();
+        let thumbnail_view = self.thumbnail_view();
+        let thumbnails = self.thumbnail_rects();
+        let page_view = self.page_view();
+        let page_rect = self.page_rect();
+        let data = *self.data;
+        let content = &self.content;
+        let pages = &self.pages;
+        self.command_group.push_with(
+            display,
+            || {
+                let mut builder = DisplayListBuilder::new();
+                builder.push_rectangle_clip(rect.cast_unit(), true);
+                builder.push_rectangle(
+                    rect.cast_unit(),
+                    GraphicsDisplayPaint::Fill(data.background.into()),
+                    None,
+                );
+                let mut commands = builder.build();
+
+                let content = match content {
+                    Some(content) => content,
+                    None => return commands,
+                };
+
+                let clip = |commands: &mut Vec<DisplayCommand>, view: AbsoluteRect| {
+                    commands.push(DisplayCommand::Save);
+                    commands.push(DisplayCommand::Clip(DisplayClip::Rectangle {
+                        rect: view.cast_unit(),
+                        antialias: false,
+                    }));
+                };
+
+                clip(&mut commands, thumbnail_view);
+                for (index, (thumbnail, segment)) in thumbnails.iter().zip(pages).enumerate() {
+                    if thumbnail.intersects(&thumbnail_view) {
+                        let page = page_commands(content, segment, data.page);
+                        push_page(
+                            &mut commands,
+                            *thumbnail,
+                            page,
+                            &data,
+                            index == data.current_page,
+                        );
+                    }
+                }
+                commands.push(DisplayCommand::Restore);
+
+                if let Some(segment) = pages.get(data.current_page) {
+                    clip(&mut commands, page_view);
+                    let page = page_commands(content, segment, data.page);
+                    push_page(&mut commands, page_rect, page, &data, false);
+                    commands.push(DisplayCommand::Restore);
+                }
+
+                commands
+            },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
+
+/// Records command groups pushed by a widget tree, while creating resources in another display.
+struct CaptureDisplay<'a> {
+    inner: &'a mut dyn GraphicsDisplay,
+    groups: &'a mut BTreeMap<u64, (ZOrder, Vec<DisplayCommand>)>,
+    next_group: &'a mut u64,
+}
+
+impl GraphicsDisplay for CaptureDisplay<'_> {
+    fn resize(&mut self, _size: (u32, u32)) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn new_resource(
+        &mut self,
+        descriptor: ResourceDescriptor,
+    ) -> Result<ResourceReference, ResourceError> {
+        self.inner.new_resource(descriptor)
+    }
+
+    fn remove_resource(&mut self, reference: ResourceReference) {
+        self.inner.remove_resource(reference)
+    }
+
+    fn push_command_group(
+        &mut self,
+        commands: &[DisplayCommand],
+        z_order: ZOrder,
+        _protected: Option<bool>,
+        _always_alive: Option<bool>,
+    ) -> Result<CommandGroupHandle, Box<dyn std::error::Error>> {
+        let id = *self.next_group;
+        *self.next_group += 1;
+        self.groups.insert(id, (z_order, commands.to_vec()));
+        Ok(CommandGroupHandle::new(id))
+    }
+
+    fn get_command_group(&self, handle: CommandGroupHandle) -> Option<Vec<DisplayCommand>> {
+        self.groups.get(&handle.id()).map(|(_, commands)| commands.clone())
+    }
+
+    fn modify_command_group(
+        &mut self,
+        handle: CommandGroupHandle,
+        commands: &[DisplayCommand],
+        z_order: ZOrder,
+        _protected: Option<bool>,
+        _always_alive: Option<bool>,
+    ) {
+        if let Some(group) = self.groups.get_mut(&handle.id()) {
+            *group = (z_order, commands.to_vec());
+        }
+    }
+
+    fn maintain_command_group(&mut self, _handle: CommandGroupHandle) {}
+
+    fn remove_command_group(&mut self, handle: CommandGroupHandle) -> Option<Vec<DisplayCommand>> {
+        self.groups.remove(&handle.id()).map(|(_, commands)| commands)
+    }
+
+    fn before_exit(&mut self) {}
+
+    fn present(&mut self, _cull: Option<Rect>) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+/// Prints a widget tree (see [`PrintDocument`](trait.PrintDocument.html)).
+///
+/// The widget tree is only ever drawn for printing, so it shouldn't also be a child of the window's widget tree.
+/// It should be laid out to the printable width of a page (see `PageSetup::printable_size`), and updated as usual.
+pub struct WidgetDocument<W> {
+    /// Root of the printed widget tree.
+    pub root: W,
+    /// Offsets from the top of the widget tree at which pages should preferably break.
+    pub break_points: Vec<f32>,
+    clip_list: base::ClipList,
+    groups: BTreeMap<u64, (ZOrder, Vec<DisplayCommand>)>,
+    next_group: u64,
+}
+
+impl<W> WidgetDocument<W> {
+    /// Creates a document printing the widget tree of `root`.
+    pub fn new(root: W) -> Self {
+        WidgetDocument {
+            root,
+            break_points: Vec::new(),
+            clip_list: Default::default(),
+            groups: BTreeMap::new(),
+            next_group: 0,
+        }
+    }
+}
+
+impl<W, G> PrintDocument<G> for WidgetDocument<W>
+where
+    W: base::WidgetChildren<GraphicalAux = G, DisplayObject = DisplayCommand>,
+    G: base::GraphicalAuxiliary,
+{
+    fn size(&self) -> Size {
+        self.root.bounds().size
+    }
+
+    fn break_points(&self) -> Vec<f32> {
+        self.break_points.clone()
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, aux: &mut G) -> Vec<DisplayCommand> {
+        let mut capture = CaptureDisplay {
+            inner: display,
+            groups: &mut self.groups,
+            next_group: &mut self.next_group,
+        };
+        self.clip_list.draw(&mut self.root, &mut capture, aux);
+
+        let mut groups: Vec<&(ZOrder, Vec<DisplayCommand>)> = self.groups.values().collect();
+        // Stable, so that groups of equal z-order remain in push order.
+        groups.sort_by_key(|(z_order, _)| z_order.0);
+
+        let origin = self.root.abs_bounds().origin;
+        let mut commands = vec![
+            DisplayCommand::Save,
+            DisplayCommand::Translate(Vector::new(-origin.x, -origin.y)),
+        ];
+        commands.extend(groups.into_iter().flat_map(|(_, group)| group.iter().cloned()));
+        commands.push(DisplayCommand::Restore);
+        commands
+    }
+}
@@ -0,0 +1,181 @@
+//! Radial progress widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::time::Instant,
+};
+
+/// Circular progress indicator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadialProgress {
+    /// Progress from 0.0 to 1.0, or `None` if the progress is indeterminate.
+    pub progress: Option<f32>,
+    /// Thickness of the progress ring.
+    pub thickness: f32,
+    /// How many revolutions per second the indeterminate indicator spins.
+    pub speed: f32,
+    /// Color of the progress arc.
+    pub foreground: Color,
+    /// Color of the ring track.
+    pub background: Color,
+    /// Contrast mode of `background` and `foreground`.
+    pub contrast: draw::ThemeContrast,
+    /// Layout direction; progress fills counter-clockwise for `RightToLeft`.
+    pub direction: draw::LayoutDirection,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for RadialProgress
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = RadialProgressWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for RadialProgress
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        RadialProgress {
+            progress: Some(0.0),
+            thickness: 4.0,
+            speed: 1.0,
+            foreground: data.scheme.primary,
+            background: data.scheme.control_outset,
+            contrast: data.contrast,
+            direction: data.direction,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, _u_aux: &mut U) -> RadialProgressWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            RadialProgressWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        let painter = theme.radial_progress();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::RadialProgressState {
+                    rect: Default::default(),
+                    data: *data,
+                    phase: 0.0,
+                })
+                .cast_unit(),
+        );
+
+        RadialProgressWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter,
+
+            phase: 0.0,
+            last_update: Instant::now(),
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::RadialProgressState> for RadialProgressWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::RadialProgressState {
+        state::RadialProgressState { rect: self.abs_rect(), data: *self.data, phase: self.phase }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct RadialProgressWidget {
+        widget::MAX,
+
+        <RadialProgress> State,
+        <state::RadialProgressState> Painter,
+
+        {
+            phase: f32,
+            last_update: Instant,
+        },
+    }
+}
+
+impl<U, G> Widget for RadialProgressWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        // Only indeterminate progress is animated.
+        if self.data.progress.is_none() {
+            self.phase = (self.phase + delta * self.data.speed).fract();
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
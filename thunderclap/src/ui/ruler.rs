@@ -0,0 +1,423 @@
+//! Ruler widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state},
+        geom::*,
+        snap,
+        ui::{self, interaction},
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Distance (in pixels) from a guide marker within which it's grabbed.
+const GUIDE_GRAB_DISTANCE: f32 = 3.0;
+
+/// Events emitted by a ruler.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum RulerEvent {
+    /// A guide has been dragged out of the ruler, at a position in canvas coordinates.
+    /// The guide is appended to `Ruler::guides`.
+    #[event_key(guide_added)]
+    GuideAdded(f32),
+    /// The guide at an index of `Ruler::guides` has been dragged to a position in canvas coordinates.
+    #[event_key(guide_moved)]
+    GuideMoved(usize, f32),
+    /// The guide at an index of `Ruler::guides` has been dragged back onto the ruler, removing it.
+    #[event_key(guide_removed)]
+    GuideRemoved(usize),
+}
+
+/// Measures a canvas along one axis, following its pan and zoom, and holds the guides dragged out of it.
+///
+/// The ruler is laid alongside the canvas (e.g. a horizontal ruler above it), and is kept in sync with the canvas
+/// by `RulerWidget::set_view`. Guides are dragged out of the ruler onto the canvas, and dragged back onto the ruler
+/// to remove them; they're drawn across the canvas for `guide_length`, and aligned with by snappers
+/// (see `RulerWidget::guide_lines`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ruler {
+    /// Axis which is measured; horizontal rulers are laid above (or below) the canvas.
+    pub orientation: ui::Orientation,
+    /// Canvas coordinate at the start of the ruler (i.e. how far the canvas is panned).
+    pub offset: f32,
+    /// Pixels per canvas unit (i.e. how far the canvas is zoomed).
+    pub scale: f32,
+    /// Minimum distance (in pixels) between labelled ticks.
+    pub label_spacing: f32,
+    /// Width of vertical rulers, and height of horizontal rulers.
+    pub thickness: f32,
+    /// Positions of the guides, in canvas coordinates.
+    pub guides: Vec<f32>,
+    /// Length of the guide lines drawn across the canvas, from the inner edge of the ruler.
+    pub guide_length: f32,
+    /// Typeface used for tick labels.
+    pub typeface: draw::TypefaceStyle,
+    /// Color of the ticks and labels.
+    pub foreground: Color,
+    pub background: Color,
+    /// Color of the cursor marker.
+    pub cursor: Color,
+    /// Color of the guides.
+    pub guide: Color,
+    /// Contrast mode of `background` and `foreground`.
+    pub contrast: draw::ThemeContrast,
+}
+
+impl Ruler {
+    /// Converts a canvas coordinate to an absolute coordinate along the axis of the ruler, given the absolute
+    /// rectangle of the ruler.
+    pub fn to_absolute(&self, rect: AbsoluteRect, position: f32) -> f32 {
+        self.start(rect) + (position - self.offset) * self.scale
+    }
+
+    /// Converts an absolute point to a canvas coordinate along the axis of the ruler, given the absolute rectangle
+    /// of the ruler.
+    pub fn to_canvas(&self, rect: AbsoluteRect, point: AbsolutePoint) -> f32 {
+        let along = match self.orientation {
+            ui::Orientation::Horizontal => point.x,
+            ui::Orientation::Vertical => point.y,
+        };
+        self.offset + (along - self.start(rect)) / self.scale.max(std::f32::EPSILON)
+    }
+
+    fn start(&self, rect: AbsoluteRect) -> f32 {
+        match self.orientation {
+            ui::Orientation::Horizontal => rect.origin.x,
+            ui::Orientation::Vertical => rect.origin.y,
+        }
+    }
+
+    /// Returns the interval (in canvas units) between labelled ticks; the smallest of 1, 2 or 5 times a power of ten
+    /// which is at least `label_spacing` pixels apart.
+    pub fn tick_step(&self) -> f32 {
+        let min_step = self.label_spacing / self.scale.max(std::f32::EPSILON);
+        let magnitude = 10f32.powf(min_step.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .iter()
+            .map(|factor| factor * magnitude)
+            .find(|&step| step >= min_step)
+            .unwrap_or(magnitude * 10.0)
+    }
+
+    /// Returns the index of the guide whose marker is at `point`, given the absolute rectangle of the ruler.
+    pub fn guide_at(&self, rect: AbsoluteRect, point: AbsolutePoint) -> Option<usize> {
+        if !rect.contains(point) {
+            return None;
+        }
+        let along = match self.orientation {
+            ui::Orientation::Horizontal => point.x,
+            ui::Orientation::Vertical => point.y,
+        };
+        self.guides
+            .iter()
+            .position(|&guide| (self.to_absolute(rect, guide) - along).abs() <= GUIDE_GRAB_DISTANCE)
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Ruler
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = RulerWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Ruler
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Ruler {
+            orientation: ui::Orientation::Horizontal,
+            offset: 0.0,
+            scale: 1.0,
+            label_spacing: 50.0,
+            thickness: 20.0,
+            guides: Vec::new(),
+            guide_length: 0.0,
+            typeface: draw::TypefaceStyle { size: 10.0, ..data.typography.button.clone() },
+            foreground: data.scheme.over_control_outset,
+            background: data.scheme.control_outset,
+            cursor: data.scheme.focus,
+            guide: data.scheme.primary,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> RulerWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            RulerWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph = graph.add("ruler", ruler_handler::<U, G>().bind(u_aux.window_queue()));
+
+        let painter = theme.ruler();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::RulerState {
+                    rect: Default::default(),
+                    data: data.clone(),
+                    cursor: None,
+                    dragged: None,
+                })
+                .cast_unit(),
+        );
+        let guide_overlay = snap::GuideOverlay::new(data.guide);
+
+        RulerWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter,
+
+            cursor: None,
+            dragged: None,
+            guide_overlay,
+        }
+        .build()
+    }
+}
+
+/// Tracks the cursor, and drags guides out of, along and back onto the ruler.
+fn ruler_handler<U, G>() -> vg::UnboundQueueHandler<RulerWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        RulerWidget<U, G> as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let rect = obj.abs_rect();
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                *button == base::MouseButton::Left
+                    && rect.contains(*pos)
+                    && interaction::is_targeted(obj, aux, *pos)
+            }) {
+                let index = match obj.data.guide_at(rect, *pos) {
+                    Some(index) => index,
+                    None => {
+                        let position = obj.data.to_canvas(rect, *pos);
+                        obj.data.guides.push(position);
+                        obj.event_queue.emit_owned(RulerEvent::GuideAdded(position));
+                        obj.data.guides.len() - 1
+                    }
+                };
+                obj.dragged = Some(index);
+                obj.command_group.repaint();
+            }
+        }
+
+        mouse_move => {
+            let rect = obj.abs_rect();
+            let pos = event.get().0;
+            let position = obj.data.to_canvas(rect, pos);
+
+            if let Some(index) = obj.dragged {
+                if (obj.data.guides[index] - position).abs() > std::f32::EPSILON {
+                    obj.data.guides[index] = position;
+                    obj.event_queue.emit_owned(RulerEvent::GuideMoved(index, position));
+                }
+            }
+
+            // The cursor is marked wherever it is alongside the ruler, e.g. over the canvas.
+            let along = match obj.data.orientation {
+                ui::Orientation::Horizontal => pos.x >= rect.min_x() && pos.x <= rect.max_x(),
+                ui::Orientation::Vertical => pos.y >= rect.min_y() && pos.y <= rect.max_y(),
+            };
+            let cursor = if along { Some(position) } else { None };
+            if cursor != obj.cursor {
+                obj.cursor = cursor;
+                obj.command_group.repaint();
+            }
+        }
+
+        mouse_release => {
+            if let Some(index) = obj.dragged {
+                let (pos, button, _) = *event.get();
+                if button == base::MouseButton::Left {
+                    obj.dragged = None;
+                    if obj.abs_rect().contains(pos) {
+                        obj.data.guides.remove(index);
+                        obj.event_queue.emit_owned(RulerEvent::GuideRemoved(index));
+                    }
+                    obj.command_group.repaint();
+                }
+            }
+        }
+    }
+}
+
+impl<U, G> RulerWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Follows the pan and zoom of the canvas; `offset` is the canvas coordinate at the start of the ruler,
+    /// and `scale` is the pixels per canvas unit.
+    pub fn set_view(&mut self, offset: f32, scale: f32) {
+        if (self.data.offset - offset).abs() > std::f32::EPSILON
+            || (self.data.scale - scale).abs() > std::f32::EPSILON
+        {
+            self.data.offset = offset;
+            self.data.scale = scale;
+        }
+    }
+
+    /// Returns the index of the guide being dragged, if any.
+    #[inline]
+    pub fn dragged_guide(&self) -> Option<usize> {
+        self.dragged
+    }
+
+    /// Returns the guides as lines in canvas coordinates, to be aligned with by a snapper
+    /// (see [`Snapper::lines`](../snap/struct.Snapper.html#structfield.lines)).
+    ///
+    /// The guide being dragged is excluded, so that it doesn't align with itself.
+    pub fn guide_lines(&self) -> Vec<snap::GuideLine> {
+        self.data
+            .guides
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| Some(index) != self.dragged)
+            .map(|(_, &position)| match self.data.orientation {
+                ui::Orientation::Horizontal => snap::GuideLine::vertical(position),
+                ui::Orientation::Vertical => snap::GuideLine::horizontal(position),
+            })
+            .collect()
+    }
+
+    /// Returns the guide lines drawn across the canvas, relative to the ruler.
+    fn canvas_guides(&self) -> Vec<snap::Guide> {
+        if self.data.guide_length <= 0.0 {
+            return Vec::new();
+        }
+
+        let rect = self.abs_rect();
+        let start = match self.data.orientation {
+            ui::Orientation::Horizontal => rect.origin.x,
+            ui::Orientation::Vertical => rect.origin.y,
+        };
+        self.data
+            .guides
+            .iter()
+            .map(|&guide| {
+                let position = self.data.to_absolute(rect, guide) - start;
+                match self.data.orientation {
+                    ui::Orientation::Horizontal => snap::Guide {
+                        line: snap::GuideLine::vertical(position),
+                        start: rect.size.height,
+                        end: rect.size.height + self.data.guide_length,
+                    },
+                    ui::Orientation::Vertical => snap::Guide {
+                        line: snap::GuideLine::horizontal(position),
+                        start: rect.size.width,
+                        end: rect.size.width + self.data.guide_length,
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::RulerState> for RulerWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::RulerState {
+        state::RulerState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            cursor: self.cursor,
+            dragged: self.dragged,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct RulerWidget {
+        widget::MAX,
+
+        <RulerEvent> EventQueue,
+        <Ruler> State,
+        <state::RulerState> Painter,
+
+        {
+            cursor: Option<f32>,
+            dragged: Option<usize>,
+            #[widget_child]
+            guide_overlay: snap::GuideOverlay<U, G>,
+        },
+    }
+}
+
+impl<U, G> Widget for RulerWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+
+        let (guides, color) = (self.canvas_guides(), self.data.guide);
+        self.guide_overlay.set_color(color);
+        self.guide_overlay.set_guides(guides);
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use {
     crate::{
+        anim,
         base::{self, Repaintable},
         draw::{self, state},
         geom::*,
@@ -10,8 +11,12 @@ use {
         verbgraph as vg,
         widget::Widget,
     },
+    std::time::Duration,
 };
 
+/// Duration of the fade between the idle and hovered opacity.
+const FADE_DURATION: Duration = Duration::from_millis(200);
+
 /// Information about how far a scroll bar has been scrolled.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ScrollPosition {
@@ -49,6 +54,9 @@ pub struct ScrollBar {
     pub foreground: Color,
     /// Color contrast.
     pub contrast: draw::ThemeContrast,
+    /// Opacity of the scroll bar while the mouse isn't over it.
+    /// The scroll bar fades in to full opacity on hover.
+    pub idle_opacity: f32,
 }
 
 impl<U, G> ui::WidgetDataTarget<U, G> for ScrollBar
@@ -73,21 +81,27 @@ where
             background: data.scheme.control_inset,
             foreground: data.scheme.over_control_inset,
             contrast: data.contrast,
+            idle_opacity: 0.6,
         }
     }
 
-    fn construct(self, theme: &dyn draw::Theme, _u_aux: &mut U) -> ScrollBarWidget<U, G>
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> ScrollBarWidget<U, G>
     where
         U: base::UpdateAuxiliary,
         G: base::GraphicalAuxiliary,
     {
         let data = base::Observed::new(self);
 
-        let graph = vg::verbgraph! {
+        let mut graph = vg::verbgraph! {
             ScrollBarWidget<U, G> as obj,
             U as _aux,
         };
 
+        graph = graph.add(
+            "interaction",
+            ui::basic_interaction_handler::<ScrollBarWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+
         let painter = theme.scroll_bar();
         let rect = RelativeRect::new(
             Default::default(),
@@ -97,6 +111,7 @@ where
                     data: *data,
                     scroll_bar: Default::default(),
                     interaction: state::InteractionState::empty(),
+                    opacity: 1.0,
                 })
                 .cast_unit(),
         );
@@ -110,6 +125,7 @@ where
             scroll_position: ScrollPosition { amount: 0.0, amount_range: (0.0, 0.0) },
             locked_width: rect.size.width,
             interaction: state::InteractionState::empty(),
+            opacity: anim::Animated::new(data.idle_opacity, FADE_DURATION, anim::Easing::EaseInOut),
         }
         .build()
     }
@@ -139,6 +155,7 @@ where
                 .cast_unit(),
             ),
             interaction: self.interaction,
+            opacity: self.opacity.get(),
         }
     }
 
@@ -168,10 +185,36 @@ crate::widget! {
             locked_width: f32,
 
             interaction: state::InteractionState,
+            opacity: anim::Animated<f32>,
         }
     }
 }
 
+impl<U, G> ui::InteractiveWidget for ScrollBarWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline(always)]
+    fn interaction(&mut self) -> &mut state::InteractionState {
+        &mut self.interaction
+    }
+
+    #[inline]
+    fn mouse_bounds(&self) -> RelativeRect {
+        self.painter.mouse_hint(self.rect)
+    }
+
+    #[inline(always)]
+    fn disabled(&self) -> bool {
+        false
+    }
+
+    fn on_interaction_event(&mut self, _event: ui::InteractionEvent) {
+        self.repaint();
+    }
+}
+
 impl<U, G> Widget for ScrollBarWidget<U, G>
 where
     U: base::UpdateAuxiliary,
@@ -189,6 +232,15 @@ where
         let mut graph = self.graph.take().unwrap();
         graph.update_all(self, aux);
         self.graph = Some(graph);
+
+        let active = self
+            .interaction
+            .intersects(state::InteractionState::HOVERED | state::InteractionState::PRESSED);
+        let opacity = if active { 1.0 } else { self.data.idle_opacity };
+        self.opacity.animate_to(opacity, aux.animator_mut());
+        if self.opacity.take_changed() {
+            self.repaint();
+        }
     }
 
     fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
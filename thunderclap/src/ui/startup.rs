@@ -0,0 +1,306 @@
+use {
+    crate::{
+        base::{self, Rectangular, WidgetChildren},
+        bridge, draw,
+        geom::*,
+    },
+    reclutch::{
+        display::{DisplayCommand, GraphicsDisplay},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{
+        cell::Cell,
+        rc::{Rc, Weak},
+        sync::mpsc,
+        thread,
+    },
+};
+
+/// Events emitted by a startup widget.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StartupEvent {
+    /// Emitted once the root has been constructed and has replaced the splash.
+    #[event_key(startup_complete)]
+    StartupComplete,
+    /// Emitted if loading ended without producing any data (e.g. the loading thread panicked),
+    /// in which case the splash stays in place.
+    #[event_key(startup_failed)]
+    StartupFailed,
+}
+
+/// Constructs the root from the loaded data.
+type ConstructRoot<U, T, R> = Box<dyn FnOnce(T, &dyn draw::Theme, &mut U) -> R>;
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Fills the window with a lightweight splash widget while the data of the real root widget (e.g. fonts, images or documents) is loaded in the background, then constructs the root and swaps it in place of the splash."]
+    #[doc = "This is intended to be the root widget of an application, so that the window can be shown immediately instead of once everything has loaded."]
+    #[doc = "Only the data is loaded in the background, as widgets can't be sent between threads; the root itself is constructed from the data in the following `update`."]
+    pub struct StartupWidget<
+        S: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+        R: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+        T: 'static
+    > {
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <StartupEvent> EventQueue,
+
+        {
+            splash: Option<S>,
+            root: Option<R>,
+            receiver: Option<mpsc::Receiver<T>>,
+            construct: Option<ConstructRoot<U, T, R>>,
+            theme: Rc<dyn draw::Theme>,
+            loading: Rc<Cell<bool>>,
+        },
+    }
+}
+
+impl<U, G, S, R, T> StartupWidget<U, G, S, R, T>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    S: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    R: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    T: 'static,
+{
+    /// Shows `splash` while `load` runs on a new thread, then constructs the root from the loaded data
+    /// with `construct` (and `theme`).
+    pub fn new(
+        splash: S,
+        load: impl FnOnce() -> T + Send + 'static,
+        construct: impl FnOnce(T, &dyn draw::Theme, &mut U) -> R + 'static,
+        theme: Rc<dyn draw::Theme>,
+        u_aux: &mut U,
+    ) -> Self
+    where
+        T: Send,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(load());
+        });
+        StartupWidget::with_receiver(splash, receiver, construct, theme, u_aux)
+    }
+
+    /// Shows `splash` until the data of the root arrives through `receiver` (e.g. from a job submitted to
+    /// the thread pool of the application), then constructs the root from it with `construct` (and `theme`).
+    ///
+    /// If the sending end is dropped without sending anything, `StartupFailed` is emitted.
+    pub fn with_receiver(
+        splash: S,
+        receiver: mpsc::Receiver<T>,
+        construct: impl FnOnce(T, &dyn draw::Theme, &mut U) -> R + 'static,
+        theme: Rc<dyn draw::Theme>,
+        u_aux: &mut U,
+    ) -> Self {
+        let loading = Rc::new(Cell::new(true));
+        u_aux.add_external_source(Box::new(LoadingSource(Rc::downgrade(&loading))));
+
+        let graph = vg::VerbGraph::default()
+            .add("startup", startup_handler::<U, G, S, R, T>().bind(u_aux.window_queue()));
+
+        StartupWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), splash.rect().size),
+            graph: graph.into(),
+
+            splash: Some(splash),
+            root: None,
+            receiver: Some(receiver),
+            construct: Some(Box::new(construct)),
+            theme,
+            loading,
+        }
+        .build()
+    }
+
+    /// Returns `true` once the root has replaced the splash.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// Returns the splash, if it's still shown.
+    #[inline]
+    pub fn splash(&self) -> Option<&S> {
+        self.splash.as_ref()
+    }
+
+    /// Returns the splash mutably, if it's still shown (e.g. to update a progress indicator).
+    #[inline]
+    pub fn splash_mut(&mut self) -> Option<&mut S> {
+        self.splash.as_mut()
+    }
+
+    /// Returns the root, if it has been constructed.
+    #[inline]
+    pub fn root(&self) -> Option<&R> {
+        self.root.as_ref()
+    }
+
+    /// Returns the root mutably, if it has been constructed.
+    #[inline]
+    pub fn root_mut(&mut self) -> Option<&mut R> {
+        self.root.as_mut()
+    }
+
+    /// Constructs the root if its data has arrived, swapping it in place of the splash.
+    fn poll(&mut self, aux: &mut U) {
+        let received = match &self.receiver {
+            Some(receiver) => receiver.try_recv(),
+            None => return,
+        };
+
+        match received {
+            Ok(data) => {
+                self.receiver = None;
+                self.loading.set(false);
+                if let Some(construct) = self.construct.take() {
+                    let mut root = construct(data, &*self.theme, aux);
+                    root.set_ctxt_rect(RelativeRect::new(Default::default(), self.rect.size));
+                    self.root = Some(root);
+                    self.splash = None;
+
+                    base::damage_all();
+                    self.event_queue.emit_owned(StartupEvent::StartupComplete);
+                }
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.receiver = None;
+                self.loading.set(false);
+                self.event_queue.emit_owned(StartupEvent::StartupFailed);
+            }
+        }
+    }
+}
+
+/// Fits the startup widget (and the widget it shows) to the window whenever it's resized.
+fn startup_handler<U, G, S, R, T>(
+) -> vg::UnboundQueueHandler<StartupWidget<U, G, S, R, T>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    S: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    R: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    T: 'static,
+{
+    vg::unbound_queue_handler! {
+        StartupWidget<U, G, S, R, T> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        resized => {
+            let rect = RelativeRect::new(Default::default(), event.cast_unit());
+            obj.set_ctxt_rect(rect);
+            if let Some(splash) = &mut obj.splash {
+                splash.set_ctxt_rect(rect);
+            }
+            if let Some(root) = &mut obj.root {
+                root.set_ctxt_rect(rect);
+            }
+        }
+    }
+}
+
+/// Keeps the application pumping external sources (and therefore updating) while the root is loading,
+/// so that the loaded data is picked up without waiting for user input.
+struct LoadingSource(Weak<Cell<bool>>);
+
+impl bridge::ExternalSource for LoadingSource {
+    #[inline]
+    fn pump(&mut self) -> usize {
+        0
+    }
+
+    fn is_connected(&self) -> bool {
+        self.0.upgrade().map(|loading| loading.get()).unwrap_or(false)
+    }
+}
+
+impl<U, G, S, R, T> Widget for StartupWidget<U, G, S, R, T>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    S: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    R: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    T: 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        self.poll(aux);
+
+        base::invoke_update(self, aux);
+    }
+
+    fn draw(&mut self, _display: &mut dyn GraphicsDisplay, _aux: &mut G) {}
+}
+
+impl<U, G, S, R, T> WidgetChildren for StartupWidget<U, G, S, R, T>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    S: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    R: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + Rectangular
+        + 'static,
+    T: 'static,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        match (&self.root, &self.splash) {
+            (Some(root), _) => vec![root as _],
+            (None, Some(splash)) => vec![splash as _],
+            (None, None) => Vec::new(),
+        }
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        match (&mut self.root, &mut self.splash) {
+            (Some(root), _) => vec![root as _],
+            (None, Some(splash)) => vec![splash as _],
+            (None, None) => Vec::new(),
+        }
+    }
+}
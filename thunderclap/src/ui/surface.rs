@@ -0,0 +1,413 @@
+//! Surface widget, displaying a texture which is updated externally every frame (e.g. video frames or the render
+//! target of a 3D viewport).
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        bridge, draw,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{
+            DisplayCommand, DisplayListBuilder, GraphicsDisplay, ImageData, Point,
+            RasterImageFormat, RasterImageInfo, Rect, ResourceDescriptor, ResourceReference,
+            SharedData, Size,
+        },
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::{
+        cell::RefCell,
+        fmt,
+        rc::{Rc, Weak},
+        sync::Arc,
+    },
+};
+
+/// How the frames of a [`Surface`](struct.Surface.html) are fitted to the bounds of the widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SurfaceFit {
+    /// Scales the frame to fit entirely within the bounds, preserving the aspect ratio.
+    Contain,
+    /// Scales the frame to cover the bounds, preserving the aspect ratio and clipping the overflow.
+    Cover,
+    /// Stretches the frame to the bounds, ignoring the aspect ratio.
+    Stretch,
+    /// Centers the frame at its own size, clipping the overflow.
+    Center,
+}
+
+impl Default for SurfaceFit {
+    fn default() -> Self {
+        SurfaceFit::Contain
+    }
+}
+
+#[derive(Clone)]
+enum SurfaceFrame {
+    /// RGBA pixels uploaded by the surface widget.
+    Pixels(Arc<Vec<u8>>, (u32, u32)),
+    /// Resource registered (and owned) by the application.
+    Resource(ResourceReference, Size),
+}
+
+struct SurfaceContent {
+    frame: Option<SurfaceFrame>,
+    generation: u64,
+}
+
+/// Handle through which the application presents frames to a [`Surface`](struct.Surface.html).
+///
+/// Cloning a handle is cheap; every clone presents to the same surfaces, and two handles are only equal if they're
+/// clones of each other.
+///
+/// Presenting a frame doesn't draw anything by itself; the surfaces showing the handle notice the new frame in their
+/// next update and repaint, damaging only their own bounds.
+#[derive(Clone)]
+pub struct SurfaceHandle(Rc<RefCell<SurfaceContent>>);
+
+impl SurfaceHandle {
+    /// Creates a handle without any frame.
+    pub fn new() -> Self {
+        SurfaceHandle(Rc::new(RefCell::new(SurfaceContent { frame: None, generation: 0 })))
+    }
+
+    /// Presents a frame of 8-bit RGBA pixels, `size` being its width and height in pixels.
+    ///
+    /// The pixels are shared with the display rather than copied; the application can keep the `Arc` around
+    /// (e.g. to recycle the buffer once the surface has moved on to another frame).
+    pub fn present_pixels(&self, pixels: Arc<Vec<u8>>, size: (u32, u32)) {
+        self.present(Some(SurfaceFrame::Pixels(pixels, size)));
+    }
+
+    /// Presents a resource which the application registered with the display itself (e.g. a render target),
+    /// `size` being its size in pixels.
+    ///
+    /// The resource stays owned by the application; the surface never removes it.
+    /// If the contents of the resource are updated in place, use `invalidate` to have it drawn again.
+    pub fn present_resource(&self, resource: ResourceReference, size: Size) {
+        self.present(Some(SurfaceFrame::Resource(resource, size)));
+    }
+
+    /// Marks the current frame as changed, so that the surfaces showing it are repainted.
+    pub fn invalidate(&self) {
+        self.0.borrow_mut().generation += 1;
+    }
+
+    /// Removes the current frame, leaving the surfaces empty.
+    pub fn clear(&self) {
+        self.present(None);
+    }
+
+    /// Returns the size of the current frame, in pixels.
+    pub fn size(&self) -> Option<Size> {
+        self.0.borrow().frame.as_ref().map(|frame| match frame {
+            SurfaceFrame::Pixels(_, size) => Size::new(size.0 as _, size.1 as _),
+            SurfaceFrame::Resource(_, size) => *size,
+        })
+    }
+
+    /// Returns the amount of frames presented (or invalidated) so far.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.0.borrow().generation
+    }
+
+    fn present(&self, frame: Option<SurfaceFrame>) {
+        let mut content = self.0.borrow_mut();
+        content.frame = frame;
+        content.generation += 1;
+    }
+
+    fn downgrade(&self) -> Weak<RefCell<SurfaceContent>> {
+        Rc::downgrade(&self.0)
+    }
+}
+
+impl Default for SurfaceHandle {
+    fn default() -> Self {
+        SurfaceHandle::new()
+    }
+}
+
+impl PartialEq for SurfaceHandle {
+    fn eq(&self, other: &SurfaceHandle) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for SurfaceHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let content = self.0.borrow();
+        let kind = match content.frame {
+            Some(SurfaceFrame::Pixels(..)) => Some("Pixels"),
+            Some(SurfaceFrame::Resource(..)) => Some("Resource"),
+            None => None,
+        };
+        f.debug_struct("SurfaceHandle")
+            .field("frame", &kind)
+            .field("generation", &content.generation)
+            .finish()
+    }
+}
+
+/// Keeps the application pumping external sources (and therefore updating the surfaces) while the application
+/// holds a handle to present frames with, so that new frames are picked up without waiting for user input.
+struct SurfaceSource(Weak<RefCell<SurfaceContent>>);
+
+impl bridge::ExternalSource for SurfaceSource {
+    #[inline]
+    fn pump(&mut self) -> usize {
+        0
+    }
+
+    fn is_connected(&self) -> bool {
+        // One handle is held by the surface itself.
+        self.0.strong_count() > 1
+    }
+}
+
+/// Displays the frames presented through a `SurfaceHandle`, fitted to the bounds of the widget.
+///
+/// Pixel frames are registered as a display resource when they're first drawn, replacing the previous frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    pub handle: SurfaceHandle,
+    pub fit: SurfaceFit,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Surface
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = SurfaceWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Surface
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(_theme: &dyn draw::Theme) -> Self {
+        Surface { handle: SurfaceHandle::new(), fit: SurfaceFit::Contain }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, _u_aux: &mut U) -> SurfaceWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let size = self.handle.size().unwrap_or_else(Size::zero);
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            SurfaceWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.repaint();
+                }
+            }
+        };
+
+        SurfaceWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), size.cast_unit()),
+            graph: graph.into(),
+            data,
+
+            generation: None,
+            uploaded: None,
+            polled: None,
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for SurfaceWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Texture which is updated externally, fitted to the bounds of the widget."]
+    #[doc = "New frames are noticed in `update`, which only repaints the surface; the rest of the window isn't redrawn."]
+    #[doc = "Note that the last uploaded pixel frame is kept in the display until `release` is called."]
+    pub struct SurfaceWidget {
+        widget::MAX,
+
+        <Surface> State,
+
+        {
+            // Generation of the handle as of the last repaint.
+            generation: Option<u64>,
+            // Pixel frame which is currently registered with the display, by generation.
+            uploaded: Option<(u64, ResourceReference)>,
+            // Handle which the application is being kept polling for.
+            polled: Option<Weak<RefCell<SurfaceContent>>>,
+        },
+    }
+}
+
+impl<U, G> SurfaceWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Removes the uploaded pixel frame from the display, without clearing the handle.
+    ///
+    /// If the widget is drawn again, the current frame is uploaded again.
+    pub fn release(&mut self, display: &mut dyn GraphicsDisplay) {
+        if let Some((_, resource)) = self.uploaded.take() {
+            display.remove_resource(resource);
+        }
+        self.generation = None;
+        self.repaint();
+    }
+
+    /// Returns the rectangle a frame is drawn in, given its size.
+    fn frame_rect(&self, frame: Size) -> Rect {
+        let bounds: Rect = self.abs_rect().cast_unit();
+        if frame.width <= 0.0 || frame.height <= 0.0 {
+            return bounds;
+        }
+
+        let (sx, sy) = (bounds.size.width / frame.width, bounds.size.height / frame.height);
+        let size = match self.data.fit {
+            SurfaceFit::Contain => frame * sx.min(sy),
+            SurfaceFit::Cover => frame * sx.max(sy),
+            SurfaceFit::Stretch => return bounds,
+            SurfaceFit::Center => frame,
+        };
+
+        Rect::new(
+            Point::new(
+                bounds.origin.x + (bounds.size.width - size.width) / 2.0,
+                bounds.origin.y + (bounds.size.height - size.height) / 2.0,
+            ),
+            size,
+        )
+    }
+
+    /// Returns the resource of the current frame alongside its size, uploading pixel frames which aren't yet
+    /// registered with the display (and removing the frame they replace).
+    fn resolve_frame(
+        &mut self,
+        display: &mut dyn GraphicsDisplay,
+    ) -> Option<(ResourceReference, Size)> {
+        let handle = self.data.handle.clone();
+        let content = handle.0.borrow();
+
+        let frame = match &content.frame {
+            Some(SurfaceFrame::Pixels(pixels, size)) => {
+                if self.uploaded.as_ref().map(|(generation, _)| *generation)
+                    != Some(content.generation)
+                {
+                    if let Some((_, resource)) = self.uploaded.take() {
+                        display.remove_resource(resource);
+                    }
+
+                    self.uploaded = display
+                        .new_resource(ResourceDescriptor::Image(ImageData::Raw(
+                            SharedData::RefCount(pixels.clone()),
+                            RasterImageInfo { size: *size, format: RasterImageFormat::Rgba8 },
+                        )))
+                        .ok()
+                        .map(|resource| (content.generation, resource));
+                }
+
+                self.uploaded
+                    .as_ref()
+                    .map(|(_, resource)| (resource.clone(), Size::new(size.0 as _, size.1 as _)))
+            }
+            Some(SurfaceFrame::Resource(resource, size)) => Some((resource.clone(), *size)),
+            None => None,
+        };
+
+        // Pixel frames which have been replaced by an application resource (or removed) are no longer needed.
+        if let Some(SurfaceFrame::Resource(..)) | None = content.frame {
+            if let Some((_, resource)) = self.uploaded.take() {
+                display.remove_resource(resource);
+            }
+        }
+
+        frame
+    }
+}
+
+impl<U, G> Widget for SurfaceWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        let handle = self.data.handle.downgrade();
+        if self.polled.as_ref().map(|polled| !polled.ptr_eq(&handle)).unwrap_or(true) {
+            aux.add_external_source(Box::new(SurfaceSource(handle.clone())));
+            self.polled = Some(handle);
+        }
+
+        let generation = self.data.handle.generation();
+        if self.generation != Some(generation) {
+            self.generation = Some(generation);
+            self.command_group.repaint();
+        }
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let frame = self.resolve_frame(display);
+        let bounds: Rect = self.abs_rect().cast_unit();
+        let frame_rect = frame.as_ref().map(|(_, size)| self.frame_rect(*size));
+        let clip = match self.data.fit {
+            SurfaceFit::Cover | SurfaceFit::Center => true,
+            SurfaceFit::Contain | SurfaceFit::Stretch => false,
+        };
+
+        self.command_group.push_with(
+            display,
+            || {
+                let mut builder = DisplayListBuilder::new();
+
+                if let (Some((resource, _)), Some(frame_rect)) = (frame, frame_rect) {
+                    if clip {
+                        builder.push_rectangle_clip(bounds, true);
+                    }
+                    builder.push_image(None, frame_rect, resource, None);
+                }
+
+                builder.build()
+            },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
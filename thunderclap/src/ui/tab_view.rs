@@ -0,0 +1,473 @@
+use {
+    crate::{
+        base::{self, HasVisibility, Repaintable, Resizable, WidgetChildren},
+        draw::{self, state},
+        factory::DynWidget,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect, Size},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Events emitted by a tab view.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabViewEvent {
+    /// Emitted when a different tab is selected. Holds the index of the selected tab.
+    #[event_key(switch)]
+    Switch(usize),
+    /// Emitted when the close button of a tab is clicked. Holds the index of the tab.
+    ///
+    /// The tab isn't removed automatically; remove it with [`remove_tab`](struct.TabViewWidget.html#method.remove_tab).
+    #[event_key(close)]
+    Close(usize),
+}
+
+/// A single tab within a tab view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tab {
+    pub label: String,
+    /// Whether the tab shows a close button.
+    pub closable: bool,
+    /// Whether the tab can be selected.
+    pub disabled: bool,
+}
+
+impl Tab {
+    /// Creates an enabled tab without a close button.
+    pub fn new(label: impl Into<String>) -> Self {
+        Tab { label: label.into(), closable: false, disabled: false }
+    }
+}
+
+/// Strip of tabs above a content area which shows the page of the selected tab.
+///
+/// Pages are added alongside their tab with [`add_tab`](struct.TabViewWidget.html#method.add_tab).
+/// Only the page of the selected tab is shown; the other pages are hidden with `Visibility::None`
+/// (i.e. they are neither drawn nor updated).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabView {
+    /// Tabs, in the same order as the pages.
+    ///
+    /// Tabs should be added and removed through the widget, so that they stay paired with their pages.
+    pub tabs: Vec<Tab>,
+    /// Index of the selected tab.
+    pub selected: usize,
+    /// Height of the tab strip.
+    pub tab_height: f32,
+    /// Maximum width of a single tab; tabs shrink to fit within the strip.
+    pub tab_width: f32,
+    /// Horizontal spacing between the label and the edges of a tab.
+    pub padding: f32,
+    pub typeface: draw::TypefaceStyle,
+    pub foreground: Color,
+    /// Background color of the tab strip.
+    pub background: Color,
+    /// Background color of the selected tab.
+    pub active: Color,
+    /// Color of the indicator underneath the selected tab.
+    pub indicator: Color,
+    pub contrast: draw::ThemeContrast,
+    pub disabled: bool,
+}
+
+impl TabView {
+    /// Returns the rectangle of the tab at `index`, where `bounds` is the rectangle of the tab view.
+    pub fn tab_rect(&self, bounds: AbsoluteRect, index: usize) -> AbsoluteRect {
+        let width = if self.tabs.is_empty() {
+            self.tab_width
+        } else {
+            self.tab_width.min(bounds.size.width / self.tabs.len() as f32)
+        };
+
+        AbsoluteRect::new(
+            AbsolutePoint::new(bounds.origin.x + index as f32 * width, bounds.origin.y),
+            Size::new(width, self.tab_height).cast_unit(),
+        )
+    }
+
+    /// Returns the rectangle of the close button within `tab` (the rectangle of a tab).
+    pub fn close_rect(&self, tab: AbsoluteRect) -> AbsoluteRect {
+        let size = self.typeface.size;
+        AbsoluteRect::new(
+            AbsolutePoint::new(
+                tab.max_x() - self.padding - size,
+                tab.origin.y + (tab.size.height - size) / 2.0,
+            ),
+            Size::new(size, size).cast_unit(),
+        )
+    }
+
+    /// Returns `true` if the tab at `index` can't be selected.
+    pub fn is_tab_disabled(&self, index: usize) -> bool {
+        self.disabled || self.tabs.get(index).map(|tab| tab.disabled).unwrap_or(true)
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for TabView
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = TabViewWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for TabView
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        TabView {
+            tabs: Vec::new(),
+            selected: 0,
+            tab_height: 32.0,
+            tab_width: 160.0,
+            padding: 12.0,
+            typeface: data.typography.button.clone(),
+            foreground: data.scheme.over_control_outset,
+            background: data.scheme.control_outset,
+            active: data.scheme.control_inset,
+            indicator: data.scheme.primary,
+            contrast: data.contrast,
+            disabled: false,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> TabViewWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            TabViewWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.dirty = true;
+                }
+            }
+        };
+
+        graph = graph.add("tab_view", tab_view_handler::<U, G>().bind(u_aux.window_queue()));
+
+        TabViewWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+            painter: theme.tab(),
+
+            pages: Vec::new(),
+            hovered: None,
+            close_hovered: false,
+            close_pressed: None,
+            dirty: true,
+        }
+        .build()
+    }
+}
+
+/// Handles tab switching, closing and hovering.
+fn tab_view_handler<U, G>() -> vg::UnboundQueueHandler<TabViewWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        TabViewWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let strip = obj.strip_rect();
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                !obj.data.disabled && *button == base::MouseButton::Left && strip.contains(*pos)
+            }) {
+                if let Some(index) = obj.tab_at(*pos) {
+                    if obj.is_over_close(index, *pos) {
+                        obj.close_pressed = Some(index);
+                    } else {
+                        obj.select(index);
+                    }
+                }
+            }
+        }
+
+        mouse_release => {
+            if let Some(index) = obj.close_pressed.take() {
+                let pos = event.get().0;
+                if obj.is_over_close(index, pos) {
+                    obj.event_queue.emit_owned(TabViewEvent::Close(index));
+                }
+            }
+        }
+
+        mouse_move => {
+            let pos = event.get().0;
+            let hovered = obj.tab_at(pos);
+            let close_hovered = hovered.map(|index| obj.is_over_close(index, pos)).unwrap_or(false);
+            if hovered != obj.hovered || close_hovered != obj.close_hovered {
+                obj.hovered = hovered;
+                obj.close_hovered = close_hovered;
+                obj.repaint();
+            }
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::TabState> for TabViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::TabState {
+        state::TabState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            hovered: self.hovered,
+            close_hovered: self.close_hovered,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.dirty = true;
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Tab strip with a content area showing one page at a time, see `TabView`."]
+    pub struct TabViewWidget {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <TabViewEvent> EventQueue,
+        <TabView> State,
+        <state::TabState> Painter,
+
+        {
+            pages: Vec<Box<dyn DynWidget<U, G>>>,
+            hovered: Option<usize>,
+            close_hovered: bool,
+            // Index of the tab whose close button was pressed, if any.
+            close_pressed: Option<usize>,
+            dirty: bool,
+        },
+    }
+}
+
+impl<U, G> TabViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Appends a tab, showing `page` while the tab is selected. Returns the index of the tab.
+    pub fn add_tab(&mut self, tab: Tab, page: impl DynWidget<U, G> + 'static) -> usize {
+        self.data.tabs.push(tab);
+        self.pages.push(Box::new(page));
+        self.dirty = true;
+        self.pages.len() - 1
+    }
+
+    /// Removes the tab at `index`, returning its page.
+    ///
+    /// If the selected tab is removed, the selection moves to the preceding tab (or the next tab, if it was the first),
+    /// emitting `TabViewEvent::Switch`.
+    pub fn remove_tab(&mut self, index: usize) -> Option<Box<dyn DynWidget<U, G>>> {
+        if index >= self.pages.len() {
+            return None;
+        }
+
+        self.data.tabs.remove(index);
+        let page = self.pages.remove(index);
+
+        if self.data.selected > index {
+            // The same page remains selected.
+            self.data.selected -= 1;
+        } else if self.data.selected == index && !self.pages.is_empty() {
+            let selected = index.saturating_sub(1);
+            self.data.selected = selected;
+            self.event_queue.emit_owned(TabViewEvent::Switch(selected));
+        }
+
+        self.hovered = None;
+        self.close_pressed = None;
+        self.dirty = true;
+        Some(page)
+    }
+
+    /// Selects the tab at `index`, emitting `TabViewEvent::Switch`.
+    ///
+    /// Does nothing if the tab is disabled or already selected.
+    pub fn select(&mut self, index: usize) {
+        if index < self.pages.len()
+            && index != self.data.selected
+            && !self.data.is_tab_disabled(index)
+        {
+            self.data.selected = index;
+            self.event_queue.emit_owned(TabViewEvent::Switch(index));
+        }
+    }
+
+    /// Returns the index of the selected tab, if there are any tabs.
+    #[inline]
+    pub fn selected(&self) -> Option<usize> {
+        if self.pages.is_empty() {
+            None
+        } else {
+            Some(self.data.selected)
+        }
+    }
+
+    /// Returns the amount of tabs.
+    #[inline]
+    pub fn tab_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns the page of the tab at `index`.
+    pub fn page(&self, index: usize) -> Option<&dyn DynWidget<U, G>> {
+        self.pages.get(index).map(|page| page.as_ref())
+    }
+
+    /// Returns the page of the tab at `index` mutably.
+    pub fn page_mut(&mut self, index: usize) -> Option<&mut (dyn DynWidget<U, G> + 'static)> {
+        self.pages.get_mut(index).map(|page| page.as_mut())
+    }
+
+    /// Returns the absolute rectangle of the tab strip.
+    pub fn strip_rect(&self) -> AbsoluteRect {
+        let rect = self.abs_rect();
+        AbsoluteRect::new(
+            rect.origin,
+            Size::new(rect.size.width, self.data.tab_height.min(rect.size.height)).cast_unit(),
+        )
+    }
+
+    /// Returns the absolute rectangle of the content area, which pages are sized to.
+    pub fn content_rect(&self) -> AbsoluteRect {
+        let rect = self.abs_rect();
+        let strip = self.data.tab_height.min(rect.size.height);
+        AbsoluteRect::new(
+            AbsolutePoint::new(rect.origin.x, rect.origin.y + strip),
+            Size::new(rect.size.width, rect.size.height - strip).cast_unit(),
+        )
+    }
+
+    /// Returns the index of the tab under `pos`, if any.
+    fn tab_at(&self, pos: AbsolutePoint) -> Option<usize> {
+        let rect = self.abs_rect();
+        (0..self.pages.len().min(self.data.tabs.len()))
+            .find(|&index| self.data.tab_rect(rect, index).contains(pos))
+    }
+
+    /// Returns `true` if `pos` is over the close button of the tab at `index`.
+    fn is_over_close(&self, index: usize, pos: AbsolutePoint) -> bool {
+        self.data.tabs.get(index).map(|tab| tab.closable).unwrap_or(false)
+            && self.data.close_rect(self.data.tab_rect(self.abs_rect(), index)).contains(pos)
+    }
+
+    /// Shows the page of the selected tab, hides the others and sizes them to the content area.
+    fn update_pages(&mut self) {
+        if self.data.selected >= self.pages.len() && !self.pages.is_empty() {
+            self.data.selected = self.pages.len() - 1;
+        }
+
+        let position = self.abs_rect().origin;
+        let strip = self.data.tab_height.min(self.rect.size.height);
+        let origin = RelativePoint::new(0.0, strip);
+        let size = Size::new(self.rect.size.width, self.rect.size.height - strip);
+        let selected = self.data.selected;
+
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            page.set_visibility(if index == selected {
+                base::Visibility::Normal
+            } else {
+                base::Visibility::None
+            });
+            page.set_parent_position(position);
+            page.set_size(size);
+            page.set_ctxt_position(origin.into());
+        }
+    }
+}
+
+impl<U, G> Widget for TabViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.dirty = true;
+        }
+
+        if self.dirty {
+            self.dirty = false;
+            self.update_pages();
+            self.repaint();
+        }
+
+        base::invoke_update(self, aux);
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
+
+impl<U, G> WidgetChildren for TabViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        self.pages.iter().map(|page| page.as_children()).collect()
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        self.pages.iter_mut().map(|page| page.as_children_mut()).collect()
+    }
+}
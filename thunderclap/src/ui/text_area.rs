@@ -1,12 +1,14 @@
 use {
     crate::{
+        a11y,
         base::{self, Repaintable},
+        clipboard::ClipboardKind,
         draw::{self, state},
         geom::*,
-        ui,
+        profiling, ui,
     },
     reclutch::{
-        display::{Color, CommandGroup, DisplayCommand, GraphicsDisplay, Rect},
+        display::{Color, CommandGroup, DisplayCommand, GraphicsDisplay, Rect, Size, Vector},
         event::RcEventQueue,
         prelude::*,
         verbgraph as vg,
@@ -34,33 +36,92 @@ where
 {
     vg::unbound_queue_handler! {
         T as obj,
-        U as _aux,
+        U as aux,
         base::WindowEvent as event,
 
         text_input => {
             if let Some(&c) = event.with(|_| obj.interaction().contains(state::InteractionState::FOCUSED)) {
-                if c.is_ascii_graphic() || c.is_ascii_whitespace() {
+                profiling::record_latency(aux, "text_input", event);
+                if c == '\r' || c == '\n' {
+                    if obj.is_multiline() {
+                        obj.push_char('\n');
+                    }
+                } else if c.is_ascii_graphic() || c.is_ascii_whitespace() {
                     obj.push_char(c);
                 }
             }
         }
 
         key_press => {
-            if let Some((key, _)) = event.with(|_| obj.interaction().contains(state::InteractionState::FOCUSED)) {
-                match key {
-                    base::KeyInput::Back => {
-                        obj.remove_char();
-                    }
-                    base::KeyInput::Left => {
-                        obj.move_cursor(-1);
+            if let Some((key, modifiers)) = event.with(|_| obj.interaction().contains(state::InteractionState::FOCUSED)) {
+                profiling::record_latency(aux, "key_press", event);
+                if let Some(action) = obj.text_action(*key, *modifiers) {
+                    obj.perform(action);
+                    if let ui::TextAction::Select(_) | ui::TextAction::SelectAll = action {
+                        copy_primary_selection(obj, aux);
                     }
-                    base::KeyInput::Right => {
-                        obj.move_cursor(1);
+                }
+            }
+        }
+
+        mouse_press => {
+            // The press itself is consumed by the interaction handler, which marks the text area as pressed.
+            let &(pos, button, modifiers) = event.get();
+            let bounds = obj.abs_convert_rect(obj.mouse_bounds());
+            if button == base::MouseButton::Left
+                && obj.interaction().contains(state::InteractionState::PRESSED)
+                && bounds.contains(pos)
+            {
+                obj.pointer_press(pos, modifiers);
+                copy_primary_selection(obj, aux);
+            } else if button == base::MouseButton::Middle && !obj.disabled() && bounds.contains(pos) {
+                // Only paste if the press wasn't consumed already (e.g. by an overlapping widget).
+                if let Some(text) = primary_selection(obj, aux) {
+                    if event.with(|_| true).is_some() {
+                        obj.paste_at(pos, &text);
                     }
-                    _ => {}
                 }
             }
         }
+
+        mouse_move => {
+            if obj.interaction().contains(state::InteractionState::PRESSED) {
+                obj.pointer_drag(event.get().0);
+                copy_primary_selection(obj, aux);
+            }
+        }
+    }
+}
+
+/// Returns the text of the primary selection, if the text area uses it and the clipboard supports it.
+fn primary_selection<T, U>(obj: &T, aux: &mut U) -> Option<String>
+where
+    T: LogicalTextArea,
+    U: base::UpdateAuxiliary,
+{
+    if !obj.uses_primary_selection() {
+        return None;
+    }
+    aux.clipboard()
+        .filter(|clipboard| clipboard.supports_primary_selection())
+        .and_then(|clipboard| clipboard.text(ClipboardKind::Primary))
+}
+
+/// Copies the selected text into the primary selection, if the text area uses it and the clipboard supports it.
+fn copy_primary_selection<T, U>(obj: &T, aux: &mut U)
+where
+    T: LogicalTextArea,
+    U: base::UpdateAuxiliary,
+{
+    if !obj.uses_primary_selection() {
+        return;
+    }
+    if let Some(text) = obj.selected_text() {
+        if let Some(clipboard) =
+            aux.clipboard().filter(|clipboard| clipboard.supports_primary_selection())
+        {
+            clipboard.set_text(ClipboardKind::Primary, text);
+        }
     }
 }
 
@@ -73,6 +134,67 @@ pub trait LogicalTextArea {
     fn remove_char(&mut self);
     /// Move text cursor by an offset.
     fn move_cursor(&mut self, offset: isize);
+    /// Whether line breaks can be entered.
+    fn is_multiline(&self) -> bool {
+        false
+    }
+    /// Move text cursor up (negative) or down (positive) by an amount of lines.
+    fn move_cursor_lines(&mut self, _lines: isize) {}
+    /// Move text cursor to the start or end of the current line.
+    fn move_cursor_to_line_edge(&mut self, _end: bool) {}
+    /// Returns the amount of lines which fit in the visible region.
+    fn page_lines(&self) -> usize {
+        1
+    }
+    /// Returns the editing action bound to a key press.
+    fn text_action(
+        &self,
+        key: base::KeyInput,
+        modifiers: base::KeyModifiers,
+    ) -> Option<ui::TextAction> {
+        ui::TextKeyBindings::platform().action(key, modifiers)
+    }
+    /// Performs an editing action.
+    ///
+    /// By default, this maps the actions onto the methods above; actions involving words or selections are ignored.
+    fn perform(&mut self, action: ui::TextAction) {
+        match action {
+            ui::TextAction::Move(motion) => match motion {
+                ui::TextMotion::Left => self.move_cursor(-1),
+                ui::TextMotion::Right => self.move_cursor(1),
+                ui::TextMotion::Up => self.move_cursor_lines(-1),
+                ui::TextMotion::Down => self.move_cursor_lines(1),
+                ui::TextMotion::PageUp => {
+                    let lines = self.page_lines() as isize;
+                    self.move_cursor_lines(-lines);
+                }
+                ui::TextMotion::PageDown => {
+                    let lines = self.page_lines() as isize;
+                    self.move_cursor_lines(lines);
+                }
+                ui::TextMotion::LineStart => self.move_cursor_to_line_edge(false),
+                ui::TextMotion::LineEnd => self.move_cursor_to_line_edge(true),
+                _ => {}
+            },
+            ui::TextAction::DeleteBack => self.remove_char(),
+            _ => {}
+        }
+    }
+    /// The left mouse button was pressed over the text area at `position`.
+    fn pointer_press(&mut self, _position: AbsolutePoint, _modifiers: base::KeyModifiers) {}
+    /// The cursor moved to `position` while the left mouse button is held down over the text area.
+    fn pointer_drag(&mut self, _position: AbsolutePoint) {}
+    /// Returns the selected text, if any.
+    fn selected_text(&self) -> Option<String> {
+        None
+    }
+    /// Whether selections are copied into the primary selection and middle-clicking pastes it
+    /// (only where the clipboard supports a primary selection).
+    fn uses_primary_selection(&self) -> bool {
+        false
+    }
+    /// Inserts text at the position closest to `position` (e.g. pasting the primary selection by middle-clicking).
+    fn paste_at(&mut self, _position: AbsolutePoint, _text: &str) {}
 }
 
 #[derive(
@@ -86,6 +208,7 @@ pub trait LogicalTextArea {
     OperatesVerbGraph,
 )]
 #[widget_children_trait(base::WidgetChildren)]
+#[widget_accessible]
 #[thunderclap_crate(crate)]
 #[widget_transform_callback(on_transform)]
 pub struct TextAreaWidget<U, G>
@@ -99,6 +222,11 @@ where
     graph: vg::OptionVerbGraph<Self, U>,
     painter: Box<dyn draw::Painter<state::TextAreaState>>,
     interaction: state::InteractionState,
+    focus: base::FocusHandle,
+    lines: Vec<state::TextLine>,
+    scroll: f32,
+    key_bindings: ui::TextKeyBindings,
+    clicks: base::ClickCounter,
     parent_position: AbsolutePoint,
 
     #[widget_rect]
@@ -135,6 +263,16 @@ where
         self.data.disabled
     }
 
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        Some(self.focus.id())
+    }
+
+    #[inline]
+    fn click_counter(&mut self) -> Option<&mut base::ClickCounter> {
+        Some(&mut self.clicks)
+    }
+
     fn on_interaction_event(&mut self, event: ui::InteractionEvent) {
         match event {
             ui::InteractionEvent::Focus => {
@@ -148,6 +286,63 @@ where
             _ => {}
         }
     }
+
+    fn on_mouse_wheel(&mut self, delta: Vector, _modifiers: base::KeyModifiers) -> bool {
+        if !self.data.multiline {
+            return false;
+        }
+
+        let max_scroll =
+            (self.lines.len() as f32 * self.line_height() - self.rect.size.height).max(0.0);
+        let scroll = (self.scroll - delta.y).min(max_scroll).max(0.0);
+
+        if (scroll - self.scroll).abs() > std::f32::EPSILON {
+            self.scroll = scroll;
+            self.repaint();
+        }
+
+        true
+    }
+}
+
+impl<U, G> a11y::Accessible for TextAreaWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn role(&self) -> a11y::Role {
+        a11y::Role::TextInput
+    }
+
+    /// The placeholder doubles as the name, as it describes what should be entered.
+    #[inline]
+    fn name(&self) -> String {
+        self.data.placeholder.clone()
+    }
+
+    #[inline]
+    fn value(&self) -> Option<String> {
+        Some(self.data.text.clone())
+    }
+
+    fn state(&self) -> a11y::AccessibleState {
+        let mut state = a11y::AccessibleState::from_interaction(self.interaction);
+        state.set(a11y::AccessibleState::DISABLED, self.data.disabled);
+        state
+    }
+}
+
+impl<U, G> ui::Recyclable for TextAreaWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn recycle(&mut self) {
+        self.interaction = state::InteractionState::empty();
+        self.clicks.reset();
+        self.repaint();
+    }
 }
 
 impl<U, G> LogicalTextArea for TextAreaWidget<U, G>
@@ -162,34 +357,152 @@ where
 
     #[inline]
     fn push_char(&mut self, c: char) {
+        self.delete_selection();
         {
             let cursor = self.data.cursor;
             self.data.text.insert(cursor, c);
         }
         self.repaint();
-        self.data.cursor += 1;
+        self.data.cursor += c.len_utf8();
+        self.update_lines();
     }
 
     #[inline]
     fn remove_char(&mut self) {
+        if self.delete_selection() {
+            return;
+        }
+
         self.repaint();
         if !self.data.text.is_empty() && self.data.cursor > 0 {
-            {
-                let cursor = self.data.cursor;
-                self.data.text.remove(cursor - 1);
-            }
-            self.data.cursor -= 1;
+            let cursor = self.prev_index(self.data.cursor);
+            self.data.text.remove(cursor);
+            self.data.cursor = cursor;
         }
+        self.update_lines();
     }
 
     #[inline]
     fn move_cursor(&mut self, offset: isize) {
-        self.repaint();
         let cursor = self.data.cursor as isize + offset;
         if cursor >= 0 && cursor <= self.data.text.len() as isize {
-            self.data.cursor = cursor as _;
+            self.set_cursor(cursor as _, false);
+        } else {
+            self.set_cursor(self.data.cursor, false);
+        }
+    }
+
+    #[inline]
+    fn is_multiline(&self) -> bool {
+        self.data.multiline
+    }
+
+    fn move_cursor_lines(&mut self, lines: isize) {
+        let cursor = self.line_target(lines);
+        self.set_cursor(cursor, false);
+    }
+
+    fn move_cursor_to_line_edge(&mut self, end: bool) {
+        let cursor = self.line_edge_target(end);
+        self.set_cursor(cursor, false);
+    }
+
+    fn page_lines(&self) -> usize {
+        ((self.rect.size.height / self.line_height()) as usize).max(1)
+    }
+
+    #[inline]
+    fn text_action(
+        &self,
+        key: base::KeyInput,
+        modifiers: base::KeyModifiers,
+    ) -> Option<ui::TextAction> {
+        self.key_bindings.action(key, modifiers)
+    }
+
+    fn perform(&mut self, action: ui::TextAction) {
+        match action {
+            ui::TextAction::Move(motion) => {
+                // Moving left or right collapses the selection to its start or end, respectively.
+                let cursor = match (motion, self.selection()) {
+                    (ui::TextMotion::Left, Some(selection)) => selection.start,
+                    (ui::TextMotion::Right, Some(selection)) => selection.end,
+                    _ => self.motion_target(motion),
+                };
+                self.set_cursor(cursor, false);
+            }
+            ui::TextAction::Select(motion) => {
+                let cursor = self.motion_target(motion);
+                self.set_cursor(cursor, true);
+            }
+            ui::TextAction::DeleteBack => self.remove_char(),
+            ui::TextAction::DeleteForward => {
+                if !self.delete_selection() && self.data.cursor < self.data.text.len() {
+                    let end = self.next_index(self.data.cursor);
+                    self.data.anchor = Some(end);
+                    self.delete_selection();
+                }
+            }
+            ui::TextAction::DeleteWordBack => {
+                if !self.delete_selection() {
+                    let start = ui::word_left(&self.data.text, self.data.cursor);
+                    self.data.anchor = Some(start);
+                    self.delete_selection();
+                }
+            }
+            ui::TextAction::SelectAll => {
+                let end = self.data.text.len();
+                self.select(0..end);
+            }
+        }
+    }
+
+    fn pointer_press(&mut self, position: AbsolutePoint, modifiers: base::KeyModifiers) {
+        let index = self.index_at_point(position);
+        // The press has already been counted by the interaction terminal.
+        match self.clicks.count() {
+            1 => self.set_cursor(index, modifiers.shift),
+            2 => {
+                let word = ui::word_range(&self.data.text, index);
+                self.select(word);
+            }
+            _ => {
+                let paragraph = ui::paragraph_range(&self.data.text, index);
+                self.select(paragraph);
+            }
+        }
+    }
+
+    fn pointer_drag(&mut self, position: AbsolutePoint) {
+        let index = self.index_at_point(position);
+        if index != self.data.cursor {
+            self.set_cursor(index, true);
         }
     }
+
+    fn selected_text(&self) -> Option<String> {
+        self.selection().map(|selection| self.data.text[selection].to_string())
+    }
+
+    #[inline]
+    fn uses_primary_selection(&self) -> bool {
+        self.data.primary_selection
+    }
+
+    fn paste_at(&mut self, position: AbsolutePoint, text: &str) {
+        // Unlike a regular paste, pasting the primary selection doesn't replace the selected text.
+        let text: String = if self.data.multiline {
+            text.into()
+        } else {
+            text.chars().filter(|&c| c != '\r' && c != '\n').collect()
+        };
+        let index = self.index_at_point(position);
+        self.repaint();
+        self.data.text.insert_str(index, &text);
+        self.data.anchor = None;
+        self.data.cursor = index + text.len();
+        self.update_lines();
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -202,6 +515,19 @@ pub struct TextArea {
     pub cursor_color: Color,
     pub disabled: bool,
     pub cursor: usize,
+    /// Other end of the selection, which spans from the anchor to `cursor`; `None` if nothing is selected.
+    pub anchor: Option<usize>,
+    /// Color of the selection highlight.
+    pub selection_color: Color,
+    /// Whether selecting text copies it into the primary selection, and middle-clicking pastes it.
+    /// Has no effect unless the clipboard supports a primary selection (i.e. on Linux and other X11 platforms).
+    pub primary_selection: bool,
+    /// Whether line breaks can be entered, in which case text is wrapped to the width of the text area,
+    /// and the visible region scrolls to follow the cursor.
+    pub multiline: bool,
+    /// If set (and `multiline` is enabled), the text area grows vertically to fit its lines up to this height,
+    /// beyond which it scrolls internally. Each change of height is notified to the parent layout.
+    pub max_height: Option<f32>,
 }
 
 impl<U, G> ui::WidgetDataTarget<U, G> for TextArea
@@ -225,9 +551,14 @@ where
             typeface: data.typography.body.clone(),
             color: data.scheme.over_control_inset,
             placeholder_color: draw::weaken(data.scheme.over_control_inset, 0.5, data.contrast),
-            cursor_color: draw::weaken(data.scheme.over_control_inset, 0.1, data.contrast),
+            cursor_color: data.scheme.caret,
             disabled: false,
             cursor: 0,
+            anchor: None,
+            selection_color: data.scheme.selection,
+            primary_selection: true,
+            multiline: false,
+            max_height: None,
         }
     }
 
@@ -241,7 +572,12 @@ where
         let mut graph = vg::verbgraph! {
             TextAreaWidget<U, G> as obj,
             U as _aux,
-            "bind" => _ev in &data.on_change => { change => { obj.repaint(); } }
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.update_lines();
+                    obj.repaint();
+                }
+            }
         };
 
         graph = graph.add(
@@ -252,6 +588,11 @@ where
             "text_area",
             text_area_handler::<TextAreaWidget<U, G>, U>().bind(u_aux.window_queue()),
         );
+        graph = graph.add(
+            "focus",
+            ui::focus_handler::<TextAreaWidget<U, G>, U>()
+                .bind(u_aux.focus_tracker().focus_queue()),
+        );
 
         let painter = theme.text_area();
         let rect = RelativeRect::new(
@@ -261,17 +602,25 @@ where
                     rect: Default::default(),
                     data: data.clone(),
                     interaction: state::InteractionState::empty(),
+                    lines: Vec::new(),
+                    cursor_line: 0,
+                    scroll: 0.0,
                 })
                 .cast_unit(),
         );
 
-        TextAreaWidget {
+        let mut text_area = TextAreaWidget {
             event_queue: Default::default(),
             data,
 
             graph: graph.into(),
             painter: theme.text_area(),
             interaction: state::InteractionState::empty(),
+            focus: u_aux.focus_tracker_mut().register_text_input(),
+            lines: Vec::new(),
+            scroll: 0.0,
+            key_bindings: ui::TextKeyBindings::platform(),
+            clicks: Default::default(),
             parent_position: Default::default(),
 
             rect,
@@ -281,7 +630,10 @@ where
             layout: Default::default(),
 
             phantom_g: Default::default(),
-        }
+        };
+
+        text_area.update_lines();
+        text_area
     }
 }
 
@@ -292,6 +644,8 @@ where
 {
     fn on_transform(&mut self) {
         self.repaint();
+        self.update_lines();
+        self.focus.set_rect(self.abs_rect());
         self.layout.notify(self.abs_rect());
     }
 
@@ -300,6 +654,297 @@ where
             rect: self.abs_rect(),
             data: self.data.clone(),
             interaction: self.interaction,
+            lines: self.lines.clone(),
+            cursor_line: self.cursor_line(),
+            scroll: self.scroll,
+        }
+    }
+
+    /// Returns the layout of the visual lines (only in multi-line mode, otherwise empty).
+    #[inline]
+    pub fn lines(&self) -> &[state::TextLine] {
+        &self.lines
+    }
+
+    /// Returns the selected range of the text, if any.
+    #[inline]
+    pub fn selection(&self) -> Option<std::ops::Range<usize>> {
+        ui::selection_range(self.data.anchor, self.data.cursor)
+    }
+
+    /// Selects a range of the text, placing the cursor at the end of the range.
+    pub fn select(&mut self, range: std::ops::Range<usize>) {
+        self.repaint();
+        self.data.anchor = Some(range.start).filter(|&start| start != range.end);
+        self.data.cursor = range.end;
+        self.scroll_to_cursor();
+    }
+
+    /// Returns the table of key bindings of the editing actions.
+    #[inline]
+    pub fn key_bindings(&self) -> &ui::TextKeyBindings {
+        &self.key_bindings
+    }
+
+    /// Returns the table of key bindings mutably, e.g. to rebind actions.
+    #[inline]
+    pub fn key_bindings_mut(&mut self) -> &mut ui::TextKeyBindings {
+        &mut self.key_bindings
+    }
+
+    /// Moves the cursor, either extending the selection (from the previous cursor position, if nothing was selected)
+    /// or clearing it.
+    fn set_cursor(&mut self, cursor: usize, extend: bool) {
+        self.repaint();
+        let anchor = if extend { self.data.anchor.or(Some(self.data.cursor)) } else { None };
+        self.data.anchor = anchor.filter(|&anchor| anchor != cursor);
+        self.data.cursor = cursor;
+        self.scroll_to_cursor();
+    }
+
+    /// Removes the selected text, returning `true` if anything was selected.
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some(selection) => {
+                self.repaint();
+                self.data.text.replace_range(selection.clone(), "");
+                self.data.cursor = selection.start;
+                self.data.anchor = None;
+                self.update_lines();
+                true
+            }
+            None => {
+                if self.data.anchor.is_some() {
+                    self.data.anchor = None;
+                }
+                false
+            }
+        }
+    }
+
+    /// Returns the position of the character before `index`.
+    fn prev_index(&self, index: usize) -> usize {
+        self.data.text[..index].chars().next_back().map(|c| index - c.len_utf8()).unwrap_or(0)
+    }
+
+    /// Returns the position of the character after `index`.
+    fn next_index(&self, index: usize) -> usize {
+        self.data.text[index..].chars().next().map(|c| index + c.len_utf8()).unwrap_or(index)
+    }
+
+    /// Returns the cursor position `lines` lines up (negative) or down (positive),
+    /// keeping the horizontal offset of the cursor as close as possible.
+    fn line_target(&self, lines: isize) -> usize {
+        if self.lines.is_empty() {
+            return self.data.cursor;
+        }
+
+        let current = self.cursor_line();
+        let line = &self.lines[current];
+        let x = self.measure(&self.data.text[line.range.start..self.data.cursor]);
+
+        let target = (current as isize + lines).max(0).min(self.lines.len() as isize - 1) as usize;
+        self.index_at_x(target, x)
+    }
+
+    /// Returns the cursor position at the start or end of the current line.
+    fn line_edge_target(&self, end: bool) -> usize {
+        if self.lines.is_empty() {
+            return if end { self.data.text.len() } else { 0 };
+        }
+
+        let line = self.cursor_line();
+        if end {
+            self.line_end(line)
+        } else {
+            self.lines[line].range.start
+        }
+    }
+
+    /// Returns the cursor position reached by a motion.
+    fn motion_target(&self, motion: ui::TextMotion) -> usize {
+        let text = &self.data.text;
+        let cursor = self.data.cursor;
+        let page = self.page_lines() as isize;
+        match motion {
+            ui::TextMotion::Left => self.prev_index(cursor),
+            ui::TextMotion::Right => self.next_index(cursor),
+            ui::TextMotion::WordLeft => ui::word_left(text, cursor),
+            ui::TextMotion::WordEnd => ui::word_end(text, cursor),
+            ui::TextMotion::NextWordStart => ui::next_word_start(text, cursor),
+            ui::TextMotion::LineStart => self.line_edge_target(false),
+            ui::TextMotion::LineEnd => self.line_edge_target(true),
+            ui::TextMotion::Up => self.line_target(-1),
+            ui::TextMotion::Down => self.line_target(1),
+            ui::TextMotion::PageUp => self.line_target(-page),
+            ui::TextMotion::PageDown => self.line_target(page),
+            ui::TextMotion::DocumentStart => 0,
+            ui::TextMotion::DocumentEnd => text.len(),
+        }
+    }
+
+    /// Returns the cursor position closest to a point (e.g. the mouse cursor).
+    fn index_at_point(&self, point: AbsolutePoint) -> usize {
+        let rect = self.abs_rect();
+        let x = point.x - rect.origin.x;
+        if self.lines.is_empty() {
+            return self.index_in_range(0, self.data.text.len(), x);
+        }
+
+        let line = ((point.y - rect.origin.y + self.scroll) / self.line_height()).max(0.0) as usize;
+        self.index_at_x(line.min(self.lines.len() - 1), x)
+    }
+
+    fn line_height(&self) -> f32 {
+        let font = self.data.typeface.typeface.pick(self.data.typeface.style);
+        let metrics = font.1.font.metrics();
+        (metrics.ascent + metrics.line_gap) / metrics.units_per_em as f32 * self.data.typeface.size
+    }
+
+    /// Returns the width of `text` rendered in the text area typeface.
+    fn measure(&self, text: &str) -> f32 {
+        let font = self.data.typeface.typeface.pick(self.data.typeface.style);
+        draw::text_cache::measure_width(&font, self.data.typeface.size, text)
+    }
+
+    /// Breaks the text into visual lines, at line breaks and by wrapping words to the width.
+    fn update_lines(&mut self) {
+        if !self.data.multiline {
+            self.lines.clear();
+            self.scroll = 0.0;
+            return;
+        }
+
+        let rect = self.abs_rect();
+        let line_height = self.line_height();
+        let text = &self.data.text;
+
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for paragraph in text.split('\n') {
+            let mut line_start = start;
+            let mut last_space = None;
+            for (i, c) in paragraph.char_indices() {
+                let index = start + i;
+                if c == ' ' {
+                    last_space = Some(index + 1);
+                }
+
+                let end = index + c.len_utf8();
+                if end - line_start > 1 && self.measure(&text[line_start..end]) > rect.size.width {
+                    // Break after the last space, or mid-word if the word doesn't fit on a line.
+                    let line_end = match last_space {
+                        Some(space) if space > line_start && space <= index => space,
+                        _ => index,
+                    };
+                    ranges.push(line_start..line_end);
+                    line_start = line_end;
+                }
+            }
+            ranges.push(line_start..start + paragraph.len());
+            start += paragraph.len() + 1;
+        }
+
+        self.lines = ranges
+            .into_iter()
+            .enumerate()
+            .map(|(i, range)| state::TextLine {
+                rect: AbsoluteRect::new(
+                    AbsolutePoint::new(rect.origin.x, rect.origin.y + i as f32 * line_height),
+                    Size::new(self.measure(&text[range.clone()]), line_height).cast_unit(),
+                ),
+                range,
+            })
+            .collect();
+
+        self.fit_height();
+        self.scroll_to_cursor();
+    }
+
+    /// Grows or shrinks the text area to fit its lines (up to `max_height`), notifying the parent layout of the new size.
+    fn fit_height(&mut self) {
+        let max_height = match self.data.max_height {
+            Some(max_height) => max_height,
+            None => return,
+        };
+
+        let line_height = self.line_height();
+        let content = self.lines.len().max(1) as f32 * line_height;
+        let height = content.min(max_height.max(line_height));
+
+        if (height - self.rect.size.height).abs() > std::f32::EPSILON {
+            // Only the height changes, so the lines don't have to be re-wrapped (i.e. this doesn't go through `on_transform`).
+            self.rect.size.height = height;
+            self.repaint();
+            self.focus.set_rect(self.abs_rect());
+            self.layout.notify(self.abs_rect());
+        }
+
+        self.scroll = self.scroll.min((content - height).max(0.0));
+    }
+
+    /// Returns the index of the line the cursor is on.
+    fn cursor_line(&self) -> usize {
+        let cursor = self.data.cursor;
+        self.lines.iter().rposition(|line| line.range.start <= cursor).unwrap_or(0)
+    }
+
+    /// Returns the furthest cursor position on a line, which excludes the wrapping point of wrapped lines.
+    fn line_end(&self, line: usize) -> usize {
+        let range = &self.lines[line].range;
+        let wrapped =
+            self.lines.get(line + 1).map(|next| next.range.start == range.end).unwrap_or(false);
+        if wrapped && range.end > range.start {
+            range.end - 1
+        } else {
+            range.end
+        }
+    }
+
+    /// Returns the cursor position on a line closest to a horizontal offset from the start of the line.
+    fn index_at_x(&self, line: usize, x: f32) -> usize {
+        self.index_in_range(self.lines[line].range.start, self.line_end(line), x)
+    }
+
+    /// Returns the cursor position between `start` and `end` closest to a horizontal offset from `start`.
+    fn index_in_range(&self, start: usize, end: usize, x: f32) -> usize {
+        let text = &self.data.text;
+
+        let mut closest = (start, std::f32::MAX);
+        for index in start..=end {
+            if text.is_char_boundary(index) {
+                let distance = (self.measure(&text[start..index]) - x).abs();
+                if distance < closest.1 {
+                    closest = (index, distance);
+                }
+            }
+        }
+
+        closest.0
+    }
+
+    /// Scrolls the visible region such that the line the cursor is on is visible.
+    fn scroll_to_cursor(&mut self) {
+        if self.lines.is_empty() {
+            return;
+        }
+
+        let line_height = self.line_height();
+        let top = self.cursor_line() as f32 * line_height;
+        let height = self.rect.size.height;
+
+        let scroll = if top < self.scroll {
+            top
+        } else if top + line_height > self.scroll + height {
+            top + line_height - height
+        } else {
+            self.scroll
+        }
+        .max(0.0);
+
+        if (scroll - self.scroll).abs() > std::f32::EPSILON {
+            self.scroll = scroll;
+            self.repaint();
         }
     }
 }
@@ -324,7 +969,7 @@ where
     type DisplayObject = DisplayCommand;
 
     fn bounds(&self) -> Rect {
-        self.rect.cast_unit()
+        self.painter.paint_hint(self.rect).cast_unit()
     }
 
     fn update(&mut self, aux: &mut U) {
@@ -0,0 +1,279 @@
+//! Text editing core, shared by text input widgets (e.g. `TextArea`).
+//!
+//! Key presses are mapped to editing actions by a key-binding table, `TextKeyBindings`,
+//! which defaults to the conventions of the platform (`TextKeyBindings::platform`):
+//!
+//! | Action               | Windows              | Linux              | macOS                       |
+//! |----------------------|----------------------|--------------------|-----------------------------|
+//! | Previous word start  | Ctrl + Left          | Ctrl + Left        | Alt + Left                  |
+//! | Next word            | Ctrl + Right (start) | Ctrl + Right (end) | Alt + Right (end)           |
+//! | Line start / end     | Home / End           | Home / End         | Cmd + Left / Right          |
+//! | Document start / end | Ctrl + Home / End    | Ctrl + Home / End  | Cmd + Up / Down, Home / End |
+//! | Delete previous word | Ctrl + Backspace     | Ctrl + Backspace   | Alt + Backspace             |
+//! | Select all           | Ctrl + A             | Ctrl + A           | Cmd + A                     |
+//!
+//! The arrow keys, Page Up/Page Down and Backspace/Delete behave the same on every platform.
+//! Holding Shift with any caret motion extends the selection instead of moving the caret alone.
+//!
+//! With the mouse, a single click places the caret (Shift + click extends the selection), a double click
+//! selects a word and a triple click selects a paragraph; dragging extends the selection.
+
+use {crate::base, std::ops::Range};
+
+/// Movement of the text caret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextMotion {
+    /// One character to the left.
+    Left,
+    /// One character to the right.
+    Right,
+    /// To the start of the current or previous word.
+    WordLeft,
+    /// To the end of the current or next word.
+    WordEnd,
+    /// To the start of the next word.
+    NextWordStart,
+    /// To the start of the current (visual) line.
+    LineStart,
+    /// To the end of the current (visual) line.
+    LineEnd,
+    /// One line up.
+    Up,
+    /// One line down.
+    Down,
+    /// One page (i.e. the visible lines) up.
+    PageUp,
+    /// One page down.
+    PageDown,
+    /// To the start of the text.
+    DocumentStart,
+    /// To the end of the text.
+    DocumentEnd,
+}
+
+/// Editing action performed in response to a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextAction {
+    /// Moves the caret, clearing the selection.
+    Move(TextMotion),
+    /// Moves the caret, extending the selection.
+    Select(TextMotion),
+    /// Deletes the selection, or otherwise the character before the caret.
+    DeleteBack,
+    /// Deletes the selection, or otherwise the character after the caret.
+    DeleteForward,
+    /// Deletes the selection, or otherwise from the start of the word before the caret to the caret.
+    DeleteWordBack,
+    /// Selects all the text.
+    SelectAll,
+}
+
+const fn modifiers(shift: bool, ctrl: bool, alt: bool, logo: bool) -> base::KeyModifiers {
+    base::KeyModifiers { shift, ctrl, alt, logo }
+}
+
+const NONE: base::KeyModifiers = modifiers(false, false, false, false);
+const CTRL: base::KeyModifiers = modifiers(false, true, false, false);
+const ALT: base::KeyModifiers = modifiers(false, false, true, false);
+const LOGO: base::KeyModifiers = modifiers(false, false, false, true);
+
+/// Table mapping key presses (a key and its modifiers) to editing actions.
+///
+/// Bindings of caret motions (`TextAction::Move`) also apply with Shift held, as `TextAction::Select`,
+/// unless a binding including Shift exists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextKeyBindings {
+    bindings: Vec<(base::KeyInput, base::KeyModifiers, TextAction)>,
+}
+
+impl TextKeyBindings {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        TextKeyBindings { bindings: Vec::new() }
+    }
+
+    /// Creates a table with the bindings shared by all platforms (arrow keys, Page Up/Page Down, Backspace/Delete).
+    fn common() -> Self {
+        let mut bindings = TextKeyBindings::new();
+        bindings
+            .bind(base::KeyInput::Left, NONE, TextAction::Move(TextMotion::Left))
+            .bind(base::KeyInput::Right, NONE, TextAction::Move(TextMotion::Right))
+            .bind(base::KeyInput::Up, NONE, TextAction::Move(TextMotion::Up))
+            .bind(base::KeyInput::Down, NONE, TextAction::Move(TextMotion::Down))
+            .bind(base::KeyInput::PageUp, NONE, TextAction::Move(TextMotion::PageUp))
+            .bind(base::KeyInput::PageDown, NONE, TextAction::Move(TextMotion::PageDown))
+            .bind(base::KeyInput::Back, NONE, TextAction::DeleteBack)
+            .bind(base::KeyInput::Delete, NONE, TextAction::DeleteForward);
+        bindings
+    }
+
+    /// Creates a table with the bindings of Windows.
+    pub fn windows() -> Self {
+        let mut bindings = TextKeyBindings::common();
+        bindings
+            .bind(base::KeyInput::Left, CTRL, TextAction::Move(TextMotion::WordLeft))
+            .bind(base::KeyInput::Right, CTRL, TextAction::Move(TextMotion::NextWordStart))
+            .bind(base::KeyInput::Home, NONE, TextAction::Move(TextMotion::LineStart))
+            .bind(base::KeyInput::End, NONE, TextAction::Move(TextMotion::LineEnd))
+            .bind(base::KeyInput::Home, CTRL, TextAction::Move(TextMotion::DocumentStart))
+            .bind(base::KeyInput::End, CTRL, TextAction::Move(TextMotion::DocumentEnd))
+            .bind(base::KeyInput::Back, CTRL, TextAction::DeleteWordBack)
+            .bind(base::KeyInput::A, CTRL, TextAction::SelectAll);
+        bindings
+    }
+
+    /// Creates a table with the bindings of Linux (and other Unix-like) desktops.
+    ///
+    /// These are the same as on Windows, except Ctrl + Right moves to the end of the word.
+    pub fn linux() -> Self {
+        let mut bindings = TextKeyBindings::windows();
+        bindings.bind(base::KeyInput::Right, CTRL, TextAction::Move(TextMotion::WordEnd));
+        bindings
+    }
+
+    /// Creates a table with the bindings of macOS, where Cmd is the logo key.
+    pub fn macos() -> Self {
+        let mut bindings = TextKeyBindings::common();
+        bindings
+            .bind(base::KeyInput::Left, ALT, TextAction::Move(TextMotion::WordLeft))
+            .bind(base::KeyInput::Right, ALT, TextAction::Move(TextMotion::WordEnd))
+            .bind(base::KeyInput::Left, LOGO, TextAction::Move(TextMotion::LineStart))
+            .bind(base::KeyInput::Right, LOGO, TextAction::Move(TextMotion::LineEnd))
+            .bind(base::KeyInput::Up, LOGO, TextAction::Move(TextMotion::DocumentStart))
+            .bind(base::KeyInput::Down, LOGO, TextAction::Move(TextMotion::DocumentEnd))
+            .bind(base::KeyInput::Home, NONE, TextAction::Move(TextMotion::DocumentStart))
+            .bind(base::KeyInput::End, NONE, TextAction::Move(TextMotion::DocumentEnd))
+            .bind(base::KeyInput::Back, ALT, TextAction::DeleteWordBack)
+            .bind(base::KeyInput::A, LOGO, TextAction::SelectAll);
+        bindings
+    }
+
+    /// Creates a table with the bindings of the target platform.
+    pub fn platform() -> Self {
+        if cfg!(target_os = "macos") {
+            TextKeyBindings::macos()
+        } else if cfg!(target_os = "windows") {
+            TextKeyBindings::windows()
+        } else {
+            TextKeyBindings::linux()
+        }
+    }
+
+    /// Binds a key press to an action, replacing any existing binding of the key press.
+    pub fn bind(
+        &mut self,
+        key: base::KeyInput,
+        modifiers: base::KeyModifiers,
+        action: TextAction,
+    ) -> &mut Self {
+        self.unbind(key, modifiers);
+        self.bindings.push((key, modifiers, action));
+        self
+    }
+
+    /// Removes the binding of a key press, if any.
+    pub fn unbind(&mut self, key: base::KeyInput, modifiers: base::KeyModifiers) -> &mut Self {
+        self.bindings.retain(|&(k, m, _)| k != key || m != modifiers);
+        self
+    }
+
+    /// Returns the action bound to a key press, if any.
+    pub fn action(&self, key: base::KeyInput, modifiers: base::KeyModifiers) -> Option<TextAction> {
+        let find = |modifiers| {
+            self.bindings.iter().find(|&&(k, m, _)| k == key && m == modifiers).map(|b| b.2)
+        };
+
+        find(modifiers).or_else(|| {
+            if !modifiers.shift {
+                return None;
+            }
+
+            match find(base::KeyModifiers { shift: false, ..modifiers }) {
+                Some(TextAction::Move(motion)) => Some(TextAction::Select(motion)),
+                _ => None,
+            }
+        })
+    }
+}
+
+impl Default for TextKeyBindings {
+    fn default() -> Self {
+        TextKeyBindings::platform()
+    }
+}
+
+/// Returns `true` if `c` is part of a word (i.e. alphanumeric or an underscore).
+#[inline]
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns the start of the word containing or before `index`, skipping any non-word characters in between.
+pub fn word_left(text: &str, index: usize) -> usize {
+    text[..index]
+        .char_indices()
+        .rev()
+        .skip_while(|&(_, c)| !is_word_char(c))
+        .take_while(|&(_, c)| is_word_char(c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Returns the end of the word containing or after `index`, skipping any non-word characters in between.
+pub fn word_end(text: &str, index: usize) -> usize {
+    text[index..]
+        .char_indices()
+        .skip_while(|&(_, c)| !is_word_char(c))
+        .find(|&(_, c)| !is_word_char(c))
+        .map(|(i, _)| index + i)
+        .unwrap_or_else(|| text.len())
+}
+
+/// Returns the start of the word after the one containing `index`.
+pub fn next_word_start(text: &str, index: usize) -> usize {
+    text[index..]
+        .char_indices()
+        .skip_while(|&(_, c)| is_word_char(c))
+        .find(|&(_, c)| is_word_char(c))
+        .map(|(i, _)| index + i)
+        .unwrap_or_else(|| text.len())
+}
+
+/// Returns the range of the word at `index`; if `index` isn't within a word, the range of the non-word characters
+/// around it is returned instead (e.g. a run of spaces).
+pub fn word_range(text: &str, index: usize) -> Range<usize> {
+    let is_word = text[index..]
+        .chars()
+        .next()
+        .or_else(|| text[..index].chars().next_back())
+        .map(is_word_char)
+        .unwrap_or(false);
+
+    let start = text[..index]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word_char(c) == is_word)
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(index);
+    let end = text[index..]
+        .char_indices()
+        .find(|&(_, c)| is_word_char(c) != is_word)
+        .map(|(i, _)| index + i)
+        .unwrap_or_else(|| text.len());
+
+    start..end
+}
+
+/// Returns the range of the paragraph (i.e. the text between line breaks) containing `index`, excluding the line break.
+pub fn paragraph_range(text: &str, index: usize) -> Range<usize> {
+    let start = text[..index].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = text[index..].find('\n').map(|i| index + i).unwrap_or_else(|| text.len());
+    start..end
+}
+
+/// Returns the selected range, spanning between `anchor` and `cursor`, if it isn't empty.
+pub fn selection_range(anchor: Option<usize>, cursor: usize) -> Option<Range<usize>> {
+    anchor.filter(|&anchor| anchor != cursor).map(|anchor| anchor.min(cursor)..anchor.max(cursor))
+}
@@ -0,0 +1,412 @@
+//! Pannable and zoomable tile viewer widget, suitable for maps and large image pyramids.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw,
+        geom::*,
+        ui,
+    },
+    indexmap::IndexMap,
+    reclutch::{
+        display::{
+            Color, DisplayCommand, DisplayListBuilder, GraphicsDisplay, GraphicsDisplayPaint,
+            GraphicsDisplayStroke, ImageData, Point, Rect, ResourceDescriptor, ResourceReference,
+            Size, Vector,
+        },
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Position of a single tile within a tile pyramid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+    /// Column of the tile.
+    pub x: u32,
+    /// Row of the tile.
+    pub y: u32,
+    /// Zoom level of the tile, where each level doubles the resolution of the previous.
+    pub zoom: u32,
+}
+
+/// Source of tile images for a [`TileViewer`](struct.TileViewer.html).
+pub trait TileProvider {
+    /// Size of a single tile, in pixels.
+    fn tile_size(&self) -> Size;
+
+    /// Number of tile columns and rows at a zoom level.
+    ///
+    /// By default, every zoom level doubles the amount of columns and rows.
+    fn tile_count(&self, zoom: u32) -> (u32, u32) {
+        (1 << zoom, 1 << zoom)
+    }
+
+    /// Requests the image of a tile.
+    ///
+    /// If the tile isn't available yet (e.g. it's still being downloaded), return `None`;
+    /// a placeholder will be drawn in its place and the tile will be requested again on the next frame.
+    fn request(&mut self, coord: TileCoord) -> Option<ImageData>;
+}
+
+/// Events emitted by a tile viewer.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum TileViewerEvent {
+    /// The view has been panned by the user, by the given amount.
+    #[event_key(pan)]
+    Pan(Vector),
+    /// The zoom level has changed.
+    #[event_key(zoom)]
+    Zoom(u32),
+}
+
+/// Displays a grid of image tiles which are lazily requested from a [`TileProvider`](trait.TileProvider.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileViewer {
+    /// Current zoom level.
+    pub zoom: u32,
+    /// Lowest zoom level the user can zoom out to.
+    pub min_zoom: u32,
+    /// Highest zoom level the user can zoom in to.
+    pub max_zoom: u32,
+    /// Position of the top-left of the view, in pixels at the current zoom level.
+    pub offset: Vector,
+    /// Maximum amount of tile images kept as display resources.
+    /// This is always at least the amount of visible tiles.
+    pub cache_capacity: usize,
+    /// Whether the view can be panned by dragging with the mouse.
+    pub draggable: bool,
+    /// Color drawn behind the tiles.
+    pub background: Color,
+    /// Fill color of tiles which haven't been loaded yet.
+    pub placeholder: Color,
+    /// Border color of tiles which haven't been loaded yet.
+    pub placeholder_border: Color,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for TileViewer
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = TileViewerWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for TileViewer
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        TileViewer {
+            zoom: 0,
+            min_zoom: 0,
+            max_zoom: 18,
+            offset: Vector::zero(),
+            cache_capacity: 256,
+            draggable: true,
+            background: data.scheme.background,
+            placeholder: data.scheme.control_outset,
+            placeholder_border: draw::weaken(data.scheme.over_control_outset, 0.8, data.contrast),
+        }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, u_aux: &mut U) -> TileViewerWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            TileViewerWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph = graph.add("tile_viewer", tile_viewer_handler::<U, G>().bind(u_aux.window_queue()));
+
+        TileViewerWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), Size::new(256.0, 256.0).cast_unit()),
+            graph: graph.into(),
+            data,
+
+            provider: None,
+            cache: IndexMap::new(),
+            drawn: Vec::new(),
+            drag: None,
+            flush_cache: false,
+        }
+        .build()
+    }
+}
+
+/// Handles panning the tile viewer by dragging.
+fn tile_viewer_handler<U, G>(
+) -> vg::UnboundQueueHandler<TileViewerWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        TileViewerWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let bounds = obj.abs_rect();
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                obj.data.draggable && *button == base::MouseButton::Left && bounds.contains(*pos)
+            }) {
+                obj.drag = Some(*pos);
+            }
+        }
+
+        mouse_move => {
+            if let Some(last) = obj.drag {
+                let pos = event.get().0;
+                let delta = (last - pos).cast_unit();
+                obj.drag = Some(pos);
+                obj.data.offset += delta;
+                obj.event_queue.emit_owned(TileViewerEvent::Pan(delta));
+            }
+        }
+
+        mouse_release => {
+            if obj.drag.is_some() && event.get().1 == base::MouseButton::Left {
+                obj.drag = None;
+            }
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for TileViewerWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Pannable, zoomable grid of lazily loaded image tiles."]
+    #[doc = "Note that cached tile resources are only released from the display while drawing (see `clear_cache`)."]
+    pub struct TileViewerWidget {
+        widget::MAX,
+
+        <TileViewerEvent> EventQueue,
+        <TileViewer> State,
+
+        {
+            provider: Option<Box<dyn TileProvider>>,
+            cache: IndexMap<TileCoord, ResourceReference>,
+            drawn: Vec<(Rect, Option<ResourceReference>)>,
+            drag: Option<AbsolutePoint>,
+            flush_cache: bool,
+        },
+    }
+}
+
+impl<U, G> TileViewerWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Changes the source of the tiles, clearing any cached tiles from the previous provider.
+    pub fn set_provider(&mut self, provider: impl TileProvider + 'static) {
+        self.provider = Some(Box::new(provider));
+        self.clear_cache();
+    }
+
+    /// Releases all cached tiles on the next draw, causing them to be requested again.
+    pub fn clear_cache(&mut self) {
+        self.flush_cache = true;
+        self.command_group.repaint();
+    }
+
+    /// Zooms in by one level, keeping `anchor` at the same position within the view.
+    pub fn zoom_in(&mut self, anchor: AbsolutePoint) {
+        if self.data.zoom < self.data.max_zoom {
+            let anchor = (anchor - self.abs_rect().origin).cast_unit();
+            self.data.zoom += 1;
+            self.data.offset = (self.data.offset + anchor) * 2.0 - anchor;
+            self.event_queue.emit_owned(TileViewerEvent::Zoom(self.data.zoom));
+        }
+    }
+
+    /// Zooms out by one level, keeping `anchor` at the same position within the view.
+    pub fn zoom_out(&mut self, anchor: AbsolutePoint) {
+        if self.data.zoom > self.data.min_zoom {
+            let anchor = (anchor - self.abs_rect().origin).cast_unit();
+            self.data.zoom -= 1;
+            self.data.offset = (self.data.offset + anchor) / 2.0 - anchor;
+            self.event_queue.emit_owned(TileViewerEvent::Zoom(self.data.zoom));
+        }
+    }
+
+    /// Resolves the visible tiles into display resources, requesting uncached tiles from the provider.
+    fn resolve_tiles(
+        &mut self,
+        display: &mut dyn GraphicsDisplay,
+    ) -> Vec<(Rect, Option<ResourceReference>)> {
+        if self.flush_cache {
+            self.flush_cache = false;
+            for (_, resource) in self.cache.drain(..) {
+                display.remove_resource(resource);
+            }
+        }
+
+        let provider = if let Some(provider) = &mut self.provider {
+            provider
+        } else {
+            return Vec::new();
+        };
+
+        let rect: Rect = self.abs_rect().cast_unit();
+        let zoom = self.data.zoom;
+        let offset = self.data.offset;
+        let tile_size = provider.tile_size();
+        if tile_size.width <= 0.0 || tile_size.height <= 0.0 {
+            return Vec::new();
+        }
+
+        let (columns, rows) = provider.tile_count(zoom);
+        let first_x = (offset.x / tile_size.width).floor().max(0.0) as u32;
+        let first_y = (offset.y / tile_size.height).floor().max(0.0) as u32;
+        let last_x =
+            (((offset.x + rect.size.width) / tile_size.width).ceil().max(0.0) as u32).min(columns);
+        let last_y =
+            (((offset.y + rect.size.height) / tile_size.height).ceil().max(0.0) as u32).min(rows);
+
+        let mut tiles = Vec::new();
+        for y in first_y..last_y {
+            for x in first_x..last_x {
+                let coord = TileCoord { x, y, zoom };
+                let tile_rect = Rect::new(
+                    Point::new(
+                        rect.origin.x + x as f32 * tile_size.width - offset.x,
+                        rect.origin.y + y as f32 * tile_size.height - offset.y,
+                    ),
+                    tile_size,
+                );
+
+                // Re-inserting moves the tile to the back, keeping the cache ordered by recent use.
+                let resource = if let Some(resource) = self.cache.shift_remove(&coord) {
+                    Some(resource)
+                } else {
+                    provider.request(coord).and_then(|image| {
+                        display.new_resource(ResourceDescriptor::Image(image)).ok()
+                    })
+                };
+
+                if let Some(resource) = resource.clone() {
+                    self.cache.insert(coord, resource);
+                }
+
+                tiles.push((tile_rect, resource));
+            }
+        }
+
+        // Evict the least recently drawn tiles, but never the ones currently visible.
+        let capacity = self.data.cache_capacity.max(tiles.len());
+        while self.cache.len() > capacity {
+            if let Some(coord) = self.cache.keys().next().cloned() {
+                if let Some(resource) = self.cache.shift_remove(&coord) {
+                    display.remove_resource(resource);
+                }
+            }
+        }
+
+        tiles
+    }
+}
+
+impl<U, G> Widget for TileViewerWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let tiles = self.resolve_tiles(display);
+
+        // Only repaint if a tile has moved or finished loading.
+        if tiles != self.drawn {
+            self.drawn = tiles;
+            self.command_group.repaint();
+        }
+
+        let rect = self.abs_rect();
+        let data = *self.data;
+        let tiles = &self.drawn;
+        self.command_group.push_with(
+            display,
+            || {
+                let mut builder = DisplayListBuilder::new();
+
+                builder.push_rectangle_clip(rect.cast_unit(), true);
+                builder.push_rectangle(
+                    rect.cast_unit(),
+                    GraphicsDisplayPaint::Fill(data.background.into()),
+                    None,
+                );
+
+                for (tile_rect, resource) in tiles {
+                    if let Some(resource) = resource {
+                        builder.push_image(None, *tile_rect, resource.clone(), None);
+                    } else {
+                        builder.push_rectangle(
+                            *tile_rect,
+                            GraphicsDisplayPaint::Fill(data.placeholder.into()),
+                            None,
+                        );
+                        builder.push_rectangle(
+                            base::sharp_align(*tile_rect),
+                            GraphicsDisplayPaint::Stroke(GraphicsDisplayStroke {
+                                thickness: 1.0,
+                                color: data.placeholder_border.into(),
+                                ..Default::default()
+                            }),
+                            None,
+                        );
+                    }
+                }
+
+                builder.build()
+            },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use {
+    crate::{
+        base::{self, HasVisibility, WidgetChildren},
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect, Size},
+        prelude::*,
+        verbgraph as vg,
+    },
+    std::time::Duration,
+};
+
+/// Events emitted by a tooltip.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TooltipEvent {
+    /// Emitted when the tooltip is shown.
+    #[event_key(show)]
+    Show,
+    /// Emitted when the tooltip is hidden.
+    #[event_key(hide)]
+    Hide,
+}
+
+/// Floating label shown after the cursor has rested over a child widget.
+///
+/// Since the tooltip wraps a child, it's constructed with [`wrap`](struct.Tooltip.html#method.wrap)
+/// rather than through `WidgetConstructor`.
+/// The tooltip is drawn in [`Layer::Tooltip`](../base/enum.Layer.html), above regular widgets and overlays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tooltip {
+    pub text: String,
+    /// Time the cursor has to rest over the child before the tooltip is shown.
+    pub delay: Duration,
+    /// Distance between the cursor and the tooltip.
+    pub offset: f32,
+    /// Spacing between the text and the edges of the tooltip.
+    pub padding: f32,
+    pub typeface: draw::TypefaceStyle,
+    pub foreground: Color,
+    pub background: Color,
+    pub contrast: draw::ThemeContrast,
+    /// Whether the tooltip can be shown.
+    pub disabled: bool,
+}
+
+impl Tooltip {
+    pub fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Tooltip {
+            text: Default::default(),
+            delay: Duration::from_millis(500),
+            offset: 16.0,
+            padding: 6.0,
+            typeface: data.typography.body.clone(),
+            foreground: data.scheme.over_control_outset,
+            background: data.scheme.control_outset,
+            contrast: data.contrast,
+            disabled: false,
+        }
+    }
+
+    /// Wraps `child`, which the tooltip takes the place of (i.e. the tooltip widget takes on the rectangle of `child`).
+    pub fn wrap<U, G, W>(
+        self,
+        child: W,
+        theme: &dyn draw::Theme,
+        u_aux: &mut U,
+    ) -> TooltipWidget<U, G, W>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+            + base::Rectangular
+            + 'static,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            TooltipWidget<U, G, W> as obj,
+            U as aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    if obj.data.disabled {
+                        obj.hide(aux);
+                    }
+                }
+            }
+        };
+
+        graph = graph.add("tooltip", tooltip_handler::<U, G, W>().bind(u_aux.window_queue()));
+
+        let rect = child.rect();
+        let mut child = child;
+        child.set_rect(RelativeRect::new(Default::default(), rect.size));
+
+        // The bubble doesn't take mouse input from the widgets underneath it.
+        let mut bubble = TooltipBubbleWidgetBuilder {
+            rect: Default::default(),
+            graph: vg::VerbGraph::default().into(),
+            painter: theme.tooltip(),
+
+            layer: base::Layer::Tooltip,
+            state: state::TooltipState { rect, data: data.clone(), tooltip: None },
+        }
+        .build();
+        bubble.set_visibility(base::Visibility::Static);
+
+        TooltipWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+
+            child,
+            bubble,
+            timer: None,
+            hovered: false,
+            cursor: Default::default(),
+            anchor: None,
+            window_size: None,
+        }
+        .build()
+    }
+}
+
+/// Shows the tooltip once the cursor has rested over the child, and hides it when the cursor leaves.
+fn tooltip_handler<U, G, W>(
+) -> vg::UnboundQueueHandler<TooltipWidget<U, G, W>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    vg::unbound_queue_handler! {
+        TooltipWidget<U, G, W> as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_move => {
+            let pos = event.get().0;
+            if obj.abs_rect().contains(pos) {
+                obj.cursor = pos;
+                if !obj.hovered {
+                    obj.hovered = true;
+                    if !obj.data.disabled {
+                        obj.timer = Some(aux.timers_mut().schedule_once(obj.data.delay));
+                    }
+                }
+            } else if obj.hovered {
+                obj.hovered = false;
+                obj.hide(aux);
+            }
+        }
+
+        // The tooltip stays hidden until the cursor re-enters the child.
+        mouse_press => {
+            obj.hide(aux);
+        }
+
+        timer => {
+            if obj.timer == Some(*event) {
+                obj.timer = None;
+                obj.anchor = Some(obj.cursor);
+                obj.event_queue.emit_owned(TooltipEvent::Show);
+            }
+        }
+
+        resized => {
+            obj.window_size = Some(*event);
+        }
+    }
+}
+
+impl<U, G, W> ui::core::CoreWidget<state::TooltipState> for TooltipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn derive_state(&self) -> state::TooltipState {
+        let mut state =
+            state::TooltipState { rect: self.abs_rect(), data: self.data.clone(), tooltip: None };
+
+        // The tooltip size depends on the text, which the painter measures.
+        if let Some(anchor) = self.anchor {
+            let size = self.bubble.painter.size_hint(state.clone());
+            let mut origin = AbsolutePoint::new(anchor.x, anchor.y + self.data.offset);
+
+            // Keep the tooltip within the window, flipping it above the cursor if there isn't enough room below.
+            if let Some(window) = self.window_size {
+                if origin.y + size.height > window.height {
+                    origin.y = anchor.y - self.data.offset - size.height;
+                }
+                origin.x = origin.x.min(window.width - size.width);
+            }
+            origin.x = origin.x.max(0.0);
+            origin.y = origin.y.max(0.0);
+
+            state.tooltip = Some(AbsoluteRect::new(origin, size.cast_unit()));
+        }
+
+        state
+    }
+
+    fn on_transform(&mut self) {
+        self.layout.notify(self.abs_rect());
+        self.update_bubble();
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Wraps a child widget, showing a tooltip when the cursor rests over the child."]
+    pub struct TooltipWidget<
+        W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static
+    > {
+        LayableWidget,
+        DropNotifier,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <TooltipEvent> EventQueue,
+        <Tooltip> State,
+
+        {
+            child: W,
+            bubble: TooltipBubbleWidget<U, G>,
+            timer: Option<base::TimerId>,
+            hovered: bool,
+            cursor: AbsolutePoint,
+            // Cursor position the tooltip was shown at, if shown.
+            anchor: Option<AbsolutePoint>,
+            window_size: Option<Size>,
+        },
+    }
+}
+
+impl<U, G, W> TooltipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    /// Returns the wrapped child.
+    #[inline]
+    pub fn child(&self) -> &W {
+        &self.child
+    }
+
+    /// Returns the wrapped child mutably.
+    #[inline]
+    pub fn child_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+
+    /// Returns `true` if the tooltip is shown.
+    #[inline]
+    pub fn is_shown(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Hides the tooltip (or stops it from being shown, if the cursor is resting over the child).
+    pub fn hide(&mut self, aux: &mut U) {
+        if let Some(timer) = self.timer.take() {
+            aux.timers_mut().cancel(timer);
+        }
+
+        if self.anchor.take().is_some() {
+            self.event_queue.emit_owned(TooltipEvent::Hide);
+        }
+    }
+
+    fn update_bubble(&mut self) {
+        let state = self.derive_state();
+        let position = self.abs_rect().origin.to_vector();
+        let rect = state
+            .tooltip
+            .map(|tooltip| tooltip.translate(-position).cast_unit())
+            .unwrap_or_default();
+        self.bubble.set_state(state, rect);
+    }
+}
+
+impl<U, G, W> Widget for TooltipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.child.set_ctxt_rect(RelativeRect::new(Default::default(), rect.size.cast_unit()));
+        }
+
+        self.update_bubble();
+
+        base::invoke_update(self, aux);
+    }
+}
+
+impl<U, G, W> WidgetChildren for TooltipWidget<U, G, W>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+    W: WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>
+        + base::Rectangular
+        + 'static,
+{
+    fn children(
+        &self,
+    ) -> Vec<
+        &dyn base::WidgetChildren<UpdateAux = U, GraphicalAux = G, DisplayObject = DisplayCommand>,
+    > {
+        vec![&self.child, &self.bubble]
+    }
+
+    fn children_mut(
+        &mut self,
+    ) -> Vec<
+        &mut dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    > {
+        vec![&mut self.child, &mut self.bubble]
+    }
+}
+
+crate::widget! {
+    #[doc = "Bubble of a tooltip, which is drawn in `Layer::Tooltip` while the wrapped child stays in its own layer."]
+    struct TooltipBubbleWidget {
+        WidgetChildren,
+        HasVisibility,
+        Repaintable,
+        Rectangular,
+        OperatesVerbGraph,
+        StoresParentPosition,
+
+        <state::TooltipState> Painter,
+
+        {
+            #[widget_layer]
+            layer: base::Layer,
+            state: state::TooltipState,
+        },
+    }
+}
+
+impl<U, G> TooltipBubbleWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Updates the tooltip state, along with the rectangle of the bubble (which is empty while hidden).
+    fn set_state(&mut self, state: state::TooltipState, rect: RelativeRect) {
+        if self.rect != rect {
+            self.set_ctxt_rect(rect);
+        }
+
+        if self.state != state {
+            self.state = state;
+            self.command_group.repaint();
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::TooltipState> for TooltipBubbleWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn derive_state(&self) -> state::TooltipState {
+        self.state.clone()
+    }
+}
+
+impl<U, G> Widget for TooltipBubbleWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use {
+    crate::{
+        base,
+        draw::{self, state},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect, Size},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Events emitted by a tour.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum TourEvent {
+    /// Emitted when the tour moves onto a step. Holds the index of the step.
+    #[event_key(step)]
+    Step(usize),
+    /// Emitted when the user skips the remainder of the tour. Holds the index of the step it was skipped from.
+    #[event_key(skip)]
+    Skip(usize),
+    /// Emitted when the user completes the last step of the tour.
+    #[event_key(finish)]
+    Finish,
+}
+
+/// A single step of a tour; explains a single target widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TourStep {
+    /// Widget ID of the target (see `WidgetChildren::id`), located by
+    /// [`locate_target`](struct.TourWidget.html#method.locate_target).
+    pub target: String,
+    pub title: String,
+    pub text: String,
+}
+
+impl TourStep {
+    pub fn new(
+        target: impl Into<String>,
+        title: impl Into<String>,
+        text: impl Into<String>,
+    ) -> Self {
+        TourStep { target: target.into(), title: title.into(), text: text.into() }
+    }
+}
+
+/// Rectangles of the parts of a tour popover.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TourLayout {
+    /// The highlighted target, if its rectangle is known.
+    pub cutout: Option<AbsoluteRect>,
+    pub popover: AbsoluteRect,
+    pub next_button: AbsoluteRect,
+    pub skip_button: AbsoluteRect,
+}
+
+impl TourLayout {
+    /// Positions a popover of `popover_size` underneath `target` (or above if there isn't enough room),
+    /// kept within `bounds`. Without a target the popover is centered.
+    pub fn compute(
+        bounds: AbsoluteRect,
+        target: Option<AbsoluteRect>,
+        popover_size: Size,
+        button_size: Size,
+        padding: f32,
+    ) -> Self {
+        let cutout = target.map(|target| target.inflate(padding, padding));
+        let size = popover_size.cast_unit();
+
+        let origin = match cutout {
+            Some(cutout) => {
+                let y = if cutout.max_y() + padding + size.height <= bounds.max_y() {
+                    cutout.max_y() + padding
+                } else {
+                    cutout.origin.y - padding - size.height
+                };
+                AbsolutePoint::new(cutout.origin.x, y)
+            }
+            None => AbsolutePoint::new(
+                bounds.origin.x + (bounds.size.width - size.width) / 2.0,
+                bounds.origin.y + (bounds.size.height - size.height) / 2.0,
+            ),
+        };
+
+        let origin = AbsolutePoint::new(
+            origin.x.min(bounds.max_x() - size.width).max(bounds.origin.x),
+            origin.y.min(bounds.max_y() - size.height).max(bounds.origin.y),
+        );
+        let popover = AbsoluteRect::new(origin, size);
+
+        let button_size = button_size.cast_unit();
+        let next_button = AbsoluteRect::new(
+            AbsolutePoint::new(
+                popover.max_x() - padding - button_size.width,
+                popover.max_y() - padding - button_size.height,
+            ),
+            button_size,
+        );
+        let skip_button = AbsoluteRect::new(
+            AbsolutePoint::new(
+                next_button.origin.x - padding - button_size.width,
+                next_button.origin.y,
+            ),
+            button_size,
+        );
+
+        TourLayout { cutout, popover, next_button, skip_button }
+    }
+}
+
+/// Guided walkthrough which highlights a sequence of target widgets, dimming the rest of the UI.
+///
+/// The tour widget should cover the area which is dimmed (typically the entire window).
+/// The target of each step is found by its widget ID, which the tour locates within a widget tree it needn't be part of:
+/// ```ignore
+/// // In the update of the widget holding both the tour and the content it explains, after updating them:
+/// self.tour.locate_target(&self.content);
+/// ```
+/// The tour is drawn in [`Layer::Overlay`](../base/enum.Layer.html), above regular widgets,
+/// and consumes mouse presses while active, so that the UI underneath can't be interacted with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tour {
+    pub steps: Vec<TourStep>,
+    /// Index of the current step, or `None` if the tour isn't active.
+    pub current: Option<usize>,
+    /// Color which dims everything except the target.
+    pub dim: Color,
+    pub background: Color,
+    pub foreground: Color,
+    /// Color of the cutout border and "next" button.
+    pub highlight: Color,
+    pub title_typeface: draw::TypefaceStyle,
+    pub typeface: draw::TypefaceStyle,
+    pub next_text: String,
+    pub finish_text: String,
+    pub skip_text: String,
+    /// Size of the "next" and "skip" buttons.
+    pub button_size: Size,
+    /// Spacing around the target and within the popover.
+    pub padding: f32,
+    pub contrast: draw::ThemeContrast,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Tour
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = TourWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Tour
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Tour {
+            steps: Vec::new(),
+            current: None,
+            dim: Color::new(0.0, 0.0, 0.0, 0.5),
+            background: data.scheme.control_outset,
+            foreground: data.scheme.over_control_outset,
+            highlight: data.scheme.primary,
+            title_typeface: data.typography.button.clone(),
+            typeface: data.typography.body.clone(),
+            next_text: "Next".into(),
+            finish_text: "Done".into(),
+            skip_text: "Skip".into(),
+            button_size: Size::new(64.0, 24.0),
+            padding: 8.0,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> TourWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            TourWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.update_layout(); } }
+        };
+
+        graph = graph.add("tour", tour_handler::<U, G>().bind(u_aux.window_queue()));
+
+        TourWidgetBuilder {
+            rect: Default::default(),
+            graph: graph.into(),
+            data,
+            painter: theme.tour(),
+
+            layer: base::Layer::Overlay,
+            target: None,
+            popover: None,
+            hovered: None,
+        }
+        .build()
+    }
+}
+
+/// Handles the popover buttons, and blocks mouse input to the rest of the UI while active.
+fn tour_handler<U, G>() -> vg::UnboundQueueHandler<TourWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        TourWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let bounds = obj.abs_rect();
+            // Consuming the event prevents widgets underneath from receiving it.
+            event.with(|(pos, _, _)| obj.data.current.is_some() && bounds.contains(*pos));
+        }
+
+        mouse_release => {
+            if let Some(layout) = obj.popover {
+                if let Some((pos, _, _)) =
+                    event.with(|(_, button, _)| *button == base::MouseButton::Left)
+                {
+                    if layout.next_button.contains(*pos) {
+                        obj.next();
+                    } else if layout.skip_button.contains(*pos) {
+                        obj.skip();
+                    }
+                }
+            }
+        }
+
+        mouse_move => {
+            if let Some(layout) = obj.popover {
+                let pos = event.get().0;
+                let hovered = if layout.next_button.contains(pos) {
+                    Some(state::TourButton::Next)
+                } else if layout.skip_button.contains(pos) {
+                    Some(state::TourButton::Skip)
+                } else {
+                    None
+                };
+
+                if hovered != obj.hovered {
+                    obj.hovered = hovered;
+                    obj.command_group.repaint();
+                }
+            }
+        }
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::TourState> for TourWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::TourState {
+        state::TourState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            layout: self.popover,
+            hovered: self.hovered,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.update_layout();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct TourWidget {
+        widget::MAX,
+
+        <TourEvent> EventQueue,
+        <Tour> State,
+        <state::TourState> Painter,
+
+        {
+            #[widget_layer]
+            layer: base::Layer,
+            // Rectangle of the current step's target, if located.
+            target: Option<AbsoluteRect>,
+            // Cached layout of the popover, recomputed by `update_layout`.
+            popover: Option<TourLayout>,
+            hovered: Option<state::TourButton>,
+        },
+    }
+}
+
+impl<U, G> TourWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Locates the target of the current step within the tree of `root` by its widget ID (see `base::find_by_id`),
+    /// moving the popover if the target moved.
+    ///
+    /// This should be invoked whenever the target may have moved (typically on every update).
+    /// If the target isn't found (or the tour isn't active), the popover is centered without highlighting anything.
+    pub fn locate_target(
+        &mut self,
+        root: &dyn base::WidgetChildren<
+            UpdateAux = U,
+            GraphicalAux = G,
+            DisplayObject = DisplayCommand,
+        >,
+    ) {
+        let target = self
+            .current_step()
+            .and_then(|step| base::find_by_id(root, &step.target))
+            .and_then(|path| path.resolve(root))
+            .map(|widget| widget.abs_bounds());
+
+        if target != self.target {
+            self.target = target;
+            self.update_layout();
+        }
+    }
+
+    /// Returns the current step, if the tour is active.
+    pub fn current_step(&self) -> Option<&TourStep> {
+        self.data.current.and_then(|current| self.data.steps.get(current))
+    }
+
+    /// Returns the rectangle of the current step's target, if the tour is active and the target has been located.
+    #[inline]
+    pub fn current_target(&self) -> Option<AbsoluteRect> {
+        self.target
+    }
+
+    /// Starts (or restarts) the tour from the first step.
+    pub fn start(&mut self) {
+        if !self.data.steps.is_empty() {
+            self.data.current = Some(0);
+            self.target = None;
+            self.update_layout();
+            self.event_queue.emit_owned(TourEvent::Step(0));
+        }
+    }
+
+    /// Advances to the next step, finishing the tour if this is the last step.
+    pub fn next(&mut self) {
+        if let Some(current) = self.data.current {
+            self.hovered = None;
+            self.target = None;
+            if current + 1 < self.data.steps.len() {
+                self.data.current = Some(current + 1);
+                self.update_layout();
+                self.event_queue.emit_owned(TourEvent::Step(current + 1));
+            } else {
+                self.data.current = None;
+                self.update_layout();
+                self.event_queue.emit_owned(TourEvent::Finish);
+            }
+        }
+    }
+
+    /// Ends the tour without completing the remaining steps.
+    pub fn skip(&mut self) {
+        if let Some(current) = self.data.current {
+            self.data.current = None;
+            self.hovered = None;
+            self.target = None;
+            self.update_layout();
+            self.event_queue.emit_owned(TourEvent::Skip(current));
+        }
+    }
+
+    /// Returns the layout of the popover, if the tour is active.
+    #[inline]
+    pub fn popover_layout(&self) -> Option<TourLayout> {
+        self.popover
+    }
+
+    /// Recomputes the layout of the popover (e.g. once the step, the text or the target changed), and repaints.
+    fn update_layout(&mut self) {
+        let mut state = self.derive_state();
+        state.layout = None;
+
+        // The popover size depends on the text of the current step, which the painter measures.
+        self.popover = if self.data.current.is_some() {
+            let popover_size = self.painter.size_hint(state.clone());
+            Some(TourLayout::compute(
+                state.rect,
+                self.target,
+                popover_size,
+                self.data.button_size,
+                self.data.padding,
+            ))
+        } else {
+            None
+        };
+
+        self.command_group.repaint();
+    }
+}
+
+impl<U, G> Widget for TourWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            // Moving the tour recomputes the popover layout (see `on_transform`).
+            self.set_ctxt_rect(rect);
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || if state.layout.is_some() { painter.draw(state) } else { Vec::new() },
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
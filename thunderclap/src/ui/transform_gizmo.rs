@@ -0,0 +1,621 @@
+//! Transform gizmo widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state},
+        geom::*,
+        snap,
+        ui::{self, interaction},
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Point, Rect, Size, Vector},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Edge or corner of the target rectangle which is dragged to resize it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResizeHandle {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+impl ResizeHandle {
+    /// All the resize handles, clockwise from the top-left corner.
+    pub const ALL: [ResizeHandle; 8] = [
+        ResizeHandle::TopLeft,
+        ResizeHandle::Top,
+        ResizeHandle::TopRight,
+        ResizeHandle::Right,
+        ResizeHandle::BottomRight,
+        ResizeHandle::Bottom,
+        ResizeHandle::BottomLeft,
+        ResizeHandle::Left,
+    ];
+
+    /// Returns the position of the handle within a unit square, where (0, 0) is the top-left corner.
+    pub fn anchor(self) -> (f32, f32) {
+        match self {
+            ResizeHandle::TopLeft => (0.0, 0.0),
+            ResizeHandle::Top => (0.5, 0.0),
+            ResizeHandle::TopRight => (1.0, 0.0),
+            ResizeHandle::Right => (1.0, 0.5),
+            ResizeHandle::BottomRight => (1.0, 1.0),
+            ResizeHandle::Bottom => (0.5, 1.0),
+            ResizeHandle::BottomLeft => (0.0, 1.0),
+            ResizeHandle::Left => (0.0, 0.5),
+        }
+    }
+
+    /// Returns `true` if the handle changes the width of the target.
+    #[inline]
+    pub fn resizes_width(self) -> bool {
+        !matches!(self, ResizeHandle::Top | ResizeHandle::Bottom)
+    }
+
+    /// Returns `true` if the handle changes the height of the target.
+    #[inline]
+    pub fn resizes_height(self) -> bool {
+        !matches!(self, ResizeHandle::Left | ResizeHandle::Right)
+    }
+}
+
+/// Part of a transform gizmo which is dragged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoHandle {
+    /// The inside of the target rectangle, which moves it.
+    Move,
+    /// An edge or corner, which resizes the target rectangle.
+    Resize(ResizeHandle),
+    /// The handle above the target rectangle, which rotates it around its center.
+    Rotate,
+}
+
+/// Increments transforms snap to while Ctrl is held.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoSnap {
+    /// Increment of the position of the target rectangle.
+    pub position: f32,
+    /// Increment of the width and height of the target rectangle.
+    pub size: f32,
+    /// Increment of the rotation, in radians.
+    pub rotation: f32,
+}
+
+impl Default for GizmoSnap {
+    fn default() -> Self {
+        GizmoSnap { position: 8.0, size: 8.0, rotation: std::f32::consts::PI / 12.0 }
+    }
+}
+
+/// Change of the target of a transform gizmo since the previous `Transform` event of the same drag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoTransform {
+    /// Handle being dragged.
+    pub handle: GizmoHandle,
+    /// Movement of the center of the target.
+    pub translation: Vector,
+    /// Horizontal and vertical scale factor of the target (before rotation).
+    pub scale: (f32, f32),
+    /// Rotation around the center of the target, in radians.
+    pub rotation: f32,
+    /// The transformed target, before rotation.
+    pub target: Rect,
+    /// The total rotation of the transformed target, in radians.
+    pub target_rotation: f32,
+}
+
+/// Events emitted by a transform gizmo.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum TransformGizmoEvent {
+    /// A handle has been pressed.
+    #[event_key(begin_transform)]
+    BeginTransform(GizmoHandle),
+    /// A handle has been dragged, transforming the target.
+    #[event_key(transform)]
+    Transform(GizmoTransform),
+    /// A handle has been released.
+    #[event_key(end_transform)]
+    EndTransform(GizmoHandle),
+}
+
+/// Handles around a target rectangle on a canvas, which move, resize and rotate it when dragged.
+///
+/// The gizmo is laid over the canvas (typically covering it) and the target is positioned relative to the gizmo.
+/// While resizing, Shift scales uniformly; while Ctrl is held, transforms snap to the increments of `snap`.
+/// Otherwise, the target is aligned by the snapper of the widget (if any, see `set_snapper`) unless Alt is held.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformGizmo {
+    /// Rectangle being transformed (before rotation), relative to the gizmo.
+    pub target: Rect,
+    /// Rotation of the target around its center, in radians.
+    pub rotation: f32,
+    /// Width and height of the resize handles.
+    pub handle_size: f32,
+    /// Distance of the rotate handle above the top edge of the target.
+    pub rotate_distance: f32,
+    /// Smallest width and height the target can be resized to.
+    pub min_size: f32,
+    pub snap: GizmoSnap,
+    /// Color of the outline and handle borders.
+    pub foreground: Color,
+    /// Fill color of the handles.
+    pub background: Color,
+    /// Contrast mode of `foreground` and `background`.
+    pub contrast: draw::ThemeContrast,
+}
+
+impl TransformGizmo {
+    /// Returns the absolute target (before rotation), given the absolute position of the gizmo.
+    pub fn abs_target(&self, origin: AbsolutePoint) -> AbsoluteRect {
+        AbsoluteRect::new(
+            origin + self.target.origin.to_vector().cast_unit(),
+            self.target.size.cast_unit(),
+        )
+    }
+
+    /// Returns the center of the target, given the absolute position of the gizmo.
+    #[inline]
+    pub fn center(&self, origin: AbsolutePoint) -> AbsolutePoint {
+        self.abs_target(origin).center()
+    }
+
+    /// Maps a point in the unrotated frame of the target to the absolute position on the canvas.
+    pub fn rotate_point(&self, origin: AbsolutePoint, point: AbsolutePoint) -> AbsolutePoint {
+        rotate_around(point, self.center(origin), self.rotation)
+    }
+
+    /// Returns the absolute position of a handle (the center of the target for `Move`).
+    pub fn handle_position(&self, origin: AbsolutePoint, handle: GizmoHandle) -> AbsolutePoint {
+        let target = self.abs_target(origin);
+        let unrotated = match handle {
+            GizmoHandle::Move => target.center(),
+            GizmoHandle::Resize(resize) => {
+                let (x, y) = resize.anchor();
+                AbsolutePoint::new(
+                    target.origin.x + target.size.width * x,
+                    target.origin.y + target.size.height * y,
+                )
+            }
+            GizmoHandle::Rotate => {
+                AbsolutePoint::new(target.center().x, target.origin.y - self.rotate_distance)
+            }
+        };
+        self.rotate_point(origin, unrotated)
+    }
+
+    /// Returns the handle at `point`, given the absolute position of the gizmo.
+    pub fn handle_at(&self, origin: AbsolutePoint, point: AbsolutePoint) -> Option<GizmoHandle> {
+        let reach = self.handle_size / 2.0 + 2.0;
+        let hits = |handle| {
+            let position = self.handle_position(origin, handle);
+            (point.x - position.x).abs() <= reach && (point.y - position.y).abs() <= reach
+        };
+
+        if hits(GizmoHandle::Rotate) {
+            return Some(GizmoHandle::Rotate);
+        }
+        if let Some(&resize) =
+            ResizeHandle::ALL.iter().find(|&&resize| hits(GizmoHandle::Resize(resize)))
+        {
+            return Some(GizmoHandle::Resize(resize));
+        }
+
+        let local = rotate_around(point, self.center(origin), -self.rotation);
+        if self.abs_target(origin).contains(local) {
+            Some(GizmoHandle::Move)
+        } else {
+            None
+        }
+    }
+}
+
+/// Rotates `point` around `center` by `angle` radians (clockwise, as the Y axis points down).
+fn rotate_around(point: AbsolutePoint, center: AbsolutePoint, angle: f32) -> AbsolutePoint {
+    let (sin, cos) = angle.sin_cos();
+    let offset = point - center;
+    AbsolutePoint::new(
+        center.x + offset.x * cos - offset.y * sin,
+        center.y + offset.x * sin + offset.y * cos,
+    )
+}
+
+fn snap_to(value: f32, increment: f32) -> f32 {
+    if increment > 0.0 {
+        (value / increment).round() * increment
+    } else {
+        value
+    }
+}
+
+/// State of the gizmo at the start of a drag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GizmoDrag {
+    handle: GizmoHandle,
+    start: AbsolutePoint,
+    target: Rect,
+    rotation: f32,
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for TransformGizmo
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = TransformGizmoWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for TransformGizmo
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        TransformGizmo {
+            target: Rect::new(Point::new(0.0, 0.0), Size::new(100.0, 100.0)),
+            rotation: 0.0,
+            handle_size: 8.0,
+            rotate_distance: 24.0,
+            min_size: 1.0,
+            snap: Default::default(),
+            foreground: data.scheme.focus,
+            background: data.scheme.background,
+            contrast: data.contrast,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> TransformGizmoWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            TransformGizmoWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph = graph
+            .add("transform_gizmo", transform_gizmo_handler::<U, G>().bind(u_aux.window_queue()));
+
+        let painter = theme.transform_gizmo();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::TransformGizmoState {
+                    rect: AbsoluteRect::new(
+                        Default::default(),
+                        Size::new(data.target.max_x(), data.target.max_y()).cast_unit(),
+                    ),
+                    data: data.clone(),
+                    hovered: None,
+                    active: None,
+                })
+                .cast_unit(),
+        );
+
+        TransformGizmoWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter,
+
+            hovered: None,
+            drag: None,
+            snapper: None,
+            guide_overlay: snap::GuideOverlay::new(data.foreground),
+        }
+        .build()
+    }
+}
+
+/// Tracks the hovered handle and transforms the target while a handle is dragged.
+fn transform_gizmo_handler<U, G>(
+) -> vg::UnboundQueueHandler<TransformGizmoWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        TransformGizmoWidget<U, G> as obj,
+        U as aux,
+        base::WindowEvent as event,
+
+        mouse_press => {
+            let origin = obj.abs_rect().origin;
+            if let Some((pos, _, _)) = event.with(|(pos, button, _)| {
+                *button == base::MouseButton::Left
+                    && obj.data.handle_at(origin, *pos).is_some()
+                    && interaction::is_targeted(obj, aux, *pos)
+            }) {
+                if let Some(handle) = obj.data.handle_at(origin, *pos) {
+                    obj.drag = Some(GizmoDrag {
+                        handle,
+                        start: *pos,
+                        target: obj.data.target,
+                        rotation: obj.data.rotation,
+                    });
+                    obj.command_group.repaint();
+                    obj.event_queue.emit_owned(TransformGizmoEvent::BeginTransform(handle));
+                }
+            }
+        }
+
+        mouse_move => {
+            let (pos, modifiers) = *event.get();
+            if let Some(drag) = obj.drag {
+                obj.drag_to(drag, pos, modifiers);
+            } else {
+                let hovered = obj.data.handle_at(obj.abs_rect().origin, pos);
+                if hovered != obj.hovered {
+                    obj.hovered = hovered;
+                    obj.command_group.repaint();
+                }
+            }
+        }
+
+        mouse_release => {
+            if let Some(drag) = obj.drag {
+                if event.get().1 == base::MouseButton::Left {
+                    obj.drag = None;
+                    obj.guide_overlay.clear();
+                    obj.command_group.repaint();
+                    obj.event_queue.emit_owned(TransformGizmoEvent::EndTransform(drag.handle));
+                }
+            }
+        }
+    }
+}
+
+impl<U, G> TransformGizmoWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Returns the handle being dragged, if any.
+    #[inline]
+    pub fn active_handle(&self) -> Option<GizmoHandle> {
+        self.drag.map(|drag| drag.handle)
+    }
+
+    /// Sets the snapper which aligns the target while it's moved or resized, with coordinates relative to the gizmo
+    /// (like `TransformGizmo::target`), or `None` to not align the target.
+    ///
+    /// The guides of alignments are drawn above regular widgets, in the foreground color of the gizmo.
+    pub fn set_snapper(&mut self, snapper: Option<snap::Snapper>) {
+        self.snapper = snapper;
+    }
+
+    /// Returns the snapper which aligns the target, if any.
+    #[inline]
+    pub fn snapper(&self) -> Option<&snap::Snapper> {
+        self.snapper.as_ref()
+    }
+
+    /// Returns the snapper which aligns the target mutably (e.g. to update the rectangles aligned with), if any.
+    #[inline]
+    pub fn snapper_mut(&mut self) -> Option<&mut snap::Snapper> {
+        self.snapper.as_mut()
+    }
+
+    /// Transforms the target for the cursor having been dragged from the start of `drag` to `pos`.
+    fn drag_to(&mut self, drag: GizmoDrag, pos: AbsolutePoint, modifiers: base::KeyModifiers) {
+        let origin = self.abs_rect().origin;
+        let snap = self.data.snap;
+        let center = origin + drag.target.center().to_vector().cast_unit();
+        // Alignment is meaningless for rotated targets.
+        let snapper = self.snapper.as_ref().filter(|_| {
+            !modifiers.ctrl && !modifiers.alt && drag.rotation.abs() <= std::f32::EPSILON
+        });
+        let mut guides = Vec::new();
+
+        let (target, rotation) = match drag.handle {
+            GizmoHandle::Move => {
+                let delta = pos - drag.start;
+                let mut position = drag.target.origin + Vector::new(delta.x, delta.y);
+                if modifiers.ctrl {
+                    position = Point::new(
+                        snap_to(position.x, snap.position),
+                        snap_to(position.y, snap.position),
+                    );
+                } else if let Some(snapper) = snapper {
+                    let snapped = snapper.snap_rect(Rect::new(position, drag.target.size));
+                    position = snapped.value.origin;
+                    guides = snapped.guides;
+                }
+                (Rect::new(position, drag.target.size), drag.rotation)
+            }
+            GizmoHandle::Resize(resize) => {
+                // Resized in the unrotated frame of the target, with the opposite edge or corner kept in place.
+                let mut local: Point =
+                    (rotate_around(pos, center, -drag.rotation) - origin.to_vector()).cast_unit();
+                if let Some(snapper) = snapper {
+                    let snapped = snapper.snap_point(local);
+                    local = snapped.value;
+                    guides = snapped.guides;
+                }
+                let (ax, ay) = resize.anchor();
+                let start = drag.target;
+                let fixed = Point::new(
+                    start.origin.x + start.size.width * (1.0 - ax),
+                    start.origin.y + start.size.height * (1.0 - ay),
+                );
+
+                // Dragging towards the fixed side shrinks the target, down to `min_size`.
+                let (resize_x, resize_y) = (resize.resizes_width(), resize.resizes_height());
+                let mut width = if resize_x {
+                    (local.x - fixed.x) * (2.0 * ax - 1.0)
+                } else {
+                    start.size.width
+                };
+                let mut height = if resize_y {
+                    (local.y - fixed.y) * (2.0 * ay - 1.0)
+                } else {
+                    start.size.height
+                };
+
+                if modifiers.shift && start.size.width > 0.0 && start.size.height > 0.0 {
+                    let scale = match (resize_x, resize_y) {
+                        (true, true) => (width / start.size.width).max(height / start.size.height),
+                        (true, false) => width / start.size.width,
+                        _ => height / start.size.height,
+                    };
+                    width = start.size.width * scale;
+                    height = start.size.height * scale;
+                }
+                if modifiers.ctrl {
+                    width = snap_to(width, snap.size);
+                    height = snap_to(height, snap.size);
+                }
+                width = width.max(self.data.min_size);
+                height = height.max(self.data.min_size);
+
+                // Edges which aren't dragged stay centered on the fixed handle.
+                let local_origin =
+                    Point::new(fixed.x - width * (1.0 - ax), fixed.y - height * (1.0 - ay));
+                let local_rect = Rect::new(local_origin, Size::new(width, height));
+
+                // The new center is rotated back around the previous center, so the fixed handle stays in place.
+                let rotated_center = rotate_around(
+                    origin + local_rect.center().to_vector().cast_unit(),
+                    center,
+                    drag.rotation,
+                ) - origin.to_vector();
+                (
+                    Rect::new(
+                        Point::new(rotated_center.x - width / 2.0, rotated_center.y - height / 2.0),
+                        Size::new(width, height),
+                    ),
+                    drag.rotation,
+                )
+            }
+            GizmoHandle::Rotate => {
+                let angle = |point: AbsolutePoint| (point.y - center.y).atan2(point.x - center.x);
+                let mut rotation = drag.rotation + angle(pos) - angle(drag.start);
+                if modifiers.ctrl {
+                    rotation = snap_to(rotation, snap.rotation);
+                }
+                (drag.target, rotation)
+            }
+        };
+
+        self.guide_overlay.set_guides(guides);
+
+        let (previous, previous_rotation) = (self.data.target, self.data.rotation);
+        if target == previous && (rotation - previous_rotation).abs() <= std::f32::EPSILON {
+            return;
+        }
+
+        let scale = |to: f32, from: f32| if from > 0.0 { to / from } else { 1.0 };
+        let transform = GizmoTransform {
+            handle: drag.handle,
+            translation: target.center() - previous.center(),
+            scale: (
+                scale(target.size.width, previous.size.width),
+                scale(target.size.height, previous.size.height),
+            ),
+            rotation: rotation - previous_rotation,
+            target,
+            target_rotation: rotation,
+        };
+
+        self.data.target = target;
+        self.data.rotation = rotation;
+        self.event_queue.emit_owned(TransformGizmoEvent::Transform(transform));
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::TransformGizmoState> for TransformGizmoWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::TransformGizmoState {
+        state::TransformGizmoState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            hovered: self.hovered,
+            active: self.active_handle(),
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct TransformGizmoWidget {
+        widget::MAX,
+
+        <TransformGizmoEvent> EventQueue,
+        <TransformGizmo> State,
+        <state::TransformGizmoState> Painter,
+
+        {
+            hovered: Option<GizmoHandle>,
+            drag: Option<GizmoDrag>,
+            snapper: Option<snap::Snapper>,
+            #[widget_child]
+            guide_overlay: snap::GuideOverlay<U, G>,
+        },
+    }
+}
+
+impl<U, G> Widget for TransformGizmoWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+
+        let foreground = self.data.foreground;
+        self.guide_overlay.set_color(foreground);
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
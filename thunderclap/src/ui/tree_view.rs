@@ -0,0 +1,507 @@
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw::{self, state, HasTheme},
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{Color, DisplayCommand, GraphicsDisplay, Rect},
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Events emitted by a tree view.
+///
+/// Nodes are identified by their path; the list of child indices leading from the roots to the node.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum TreeViewEvent {
+    /// Emitted when a node is expanded.
+    #[event_key(expand)]
+    Expand(Vec<usize>),
+    /// Emitted when a node is collapsed.
+    #[event_key(collapse)]
+    Collapse(Vec<usize>),
+    /// Emitted when a node is selected.
+    #[event_key(select)]
+    Select(Vec<usize>),
+    /// Emitted when focus is gained.
+    #[event_key(focus)]
+    Focus,
+    /// Emitted when focus is lost.
+    #[event_key(blur)]
+    Blur,
+}
+
+/// A single node within a tree view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub label: String,
+    pub children: Vec<TreeNode>,
+    /// Whether the children of this node are visible.
+    pub expanded: bool,
+}
+
+impl TreeNode {
+    /// Creates a collapsed node without any children.
+    pub fn new(label: impl Into<String>) -> Self {
+        TreeNode { label: label.into(), children: Vec::new(), expanded: false }
+    }
+
+    /// Sets the children of the node.
+    pub fn with_children(self, children: Vec<TreeNode>) -> Self {
+        TreeNode { children, ..self }
+    }
+}
+
+/// A visible row of a tree view; a flattened `TreeNode`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeViewRow {
+    pub path: Vec<usize>,
+    /// How many ancestors the node has.
+    pub depth: usize,
+    pub label: String,
+    /// Whether the node has children.
+    pub expandable: bool,
+    pub expanded: bool,
+}
+
+/// Hierarchical list of expandable/collapsible nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeView {
+    pub nodes: Vec<TreeNode>,
+    /// Path of the selected node.
+    pub selected: Option<Vec<usize>>,
+    /// Height of a single row.
+    pub row_height: f32,
+    /// Horizontal offset of each level of depth.
+    pub indent: f32,
+    pub typeface: draw::TypefaceStyle,
+    pub foreground: Color,
+    pub background: Color,
+    /// Background color of the selected row.
+    pub selection: Color,
+    pub focus: Color,
+    pub contrast: draw::ThemeContrast,
+    /// Layout direction; rows are indented from the right for `RightToLeft`.
+    pub direction: draw::LayoutDirection,
+    pub disabled: bool,
+}
+
+impl TreeView {
+    /// Returns the node at `path`, if it exists.
+    pub fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter().try_fold(self.nodes.get(*first)?, |node, &i| node.children.get(i))
+    }
+
+    /// Returns the node at `path` mutably, if it exists.
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter().try_fold(self.nodes.get_mut(*first)?, |node, &i| node.children.get_mut(i))
+    }
+
+    /// Returns the visible rows, i.e. all the nodes which don't have a collapsed ancestor, in display order.
+    pub fn rows(&self) -> Vec<TreeViewRow> {
+        fn flatten(nodes: &[TreeNode], parent: &[usize], rows: &mut Vec<TreeViewRow>) {
+            for (i, node) in nodes.iter().enumerate() {
+                let mut path = parent.to_vec();
+                path.push(i);
+
+                rows.push(TreeViewRow {
+                    path: path.clone(),
+                    depth: parent.len(),
+                    label: node.label.clone(),
+                    expandable: !node.children.is_empty(),
+                    expanded: node.expanded,
+                });
+
+                if node.expanded {
+                    flatten(&node.children, &path, rows);
+                }
+            }
+        }
+
+        let mut rows = Vec::new();
+        flatten(&self.nodes, &[], &mut rows);
+        rows
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for TreeView
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = TreeViewWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for TreeView
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        TreeView {
+            nodes: Vec::new(),
+            selected: None,
+            row_height: 24.0,
+            indent: 16.0,
+            typeface: data.typography.body.clone(),
+            foreground: data.scheme.over_control_inset,
+            background: data.scheme.control_inset,
+            selection: data.scheme.selection,
+            focus: data.scheme.focus,
+            contrast: data.contrast,
+            direction: data.direction,
+            disabled: false,
+        }
+    }
+
+    fn construct(self, theme: &dyn draw::Theme, u_aux: &mut U) -> TreeViewWidget<U, G> {
+        let data = base::Observed::new(self);
+
+        let mut graph = vg::verbgraph! {
+            TreeViewWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.resize_from_theme();
+                    obj.command_group.repaint();
+                }
+            }
+        };
+
+        graph = graph.add(
+            "handler",
+            ui::basic_interaction_handler::<TreeViewWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+
+        graph = graph.add("tree_view", tree_view_handler::<U, G>().bind(u_aux.window_queue()));
+
+        graph = graph.add(
+            "focus",
+            ui::focus_handler::<TreeViewWidget<U, G>, U>()
+                .bind(u_aux.focus_tracker().focus_queue()),
+        );
+
+        let painter = theme.tree_view();
+        let rect = RelativeRect::new(
+            Default::default(),
+            painter
+                .size_hint(state::TreeViewState {
+                    rect: Default::default(),
+                    data: data.clone(),
+                    hovered: None,
+                    interaction: state::InteractionState::empty(),
+                })
+                .cast_unit(),
+        );
+
+        TreeViewWidgetBuilder {
+            rect,
+            graph: graph.into(),
+            data,
+            painter,
+
+            interaction: state::InteractionState::empty(),
+            hovered: None,
+            focus: u_aux.focus_tracker_mut().register(),
+        }
+        .build()
+    }
+}
+
+/// Tracks the hovered row and handles keyboard navigation.
+fn tree_view_handler<U, G>() -> vg::UnboundQueueHandler<TreeViewWidget<U, G>, U, base::WindowEvent>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    vg::unbound_queue_handler! {
+        TreeViewWidget<U, G> as obj,
+        U as _aux,
+        base::WindowEvent as event,
+
+        mouse_move => {
+            let hovered = obj.row_at(event.get().0);
+            if hovered != obj.hovered {
+                obj.hovered = hovered;
+                obj.command_group.repaint();
+            }
+        }
+
+        key_press => {
+            if let Some((key, _)) = event.with(|_| {
+                !obj.data.disabled && obj.interaction.contains(state::InteractionState::FOCUSED)
+            }) {
+                // Left and right follow the layout direction (i.e. towards and away from the disclosure triangles).
+                let key = match (key, obj.data.direction.is_rtl()) {
+                    (base::KeyInput::Left, true) => base::KeyInput::Right,
+                    (base::KeyInput::Right, true) => base::KeyInput::Left,
+                    (key, _) => key,
+                };
+
+                let rows = obj.data.rows();
+                let current = obj
+                    .data
+                    .selected
+                    .as_ref()
+                    .and_then(|selected| rows.iter().position(|row| &row.path == selected));
+
+                match (key, current) {
+                    (base::KeyInput::Up, Some(current)) if current > 0 => {
+                        obj.select(rows[current - 1].path.clone());
+                    }
+                    (base::KeyInput::Down, Some(current)) if current + 1 < rows.len() => {
+                        obj.select(rows[current + 1].path.clone());
+                    }
+                    (base::KeyInput::Up, None) | (base::KeyInput::Down, None) => {
+                        if let Some(row) = rows.first() {
+                            obj.select(row.path.clone());
+                        }
+                    }
+                    (base::KeyInput::Left, Some(current)) => {
+                        // Collapse the node, or move up to the parent if already collapsed.
+                        let row = &rows[current];
+                        if row.expanded && row.expandable {
+                            obj.collapse(&row.path);
+                        } else if row.depth > 0 {
+                            obj.select(row.path[..row.path.len() - 1].to_vec());
+                        }
+                    }
+                    (base::KeyInput::Right, Some(current)) => {
+                        // Expand the node, or move down to the first child if already expanded.
+                        let row = &rows[current];
+                        if row.expandable && !row.expanded {
+                            obj.expand(&row.path);
+                        } else if row.expandable {
+                            let mut child = row.path.clone();
+                            child.push(0);
+                            obj.select(child);
+                        }
+                    }
+                    (base::KeyInput::Return, Some(current)) | (base::KeyInput::Space, Some(current)) => {
+                        let path = rows[current].path.clone();
+                        obj.toggle(&path);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl<U, G> ui::InteractiveWidget for TreeViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline(always)]
+    fn interaction(&mut self) -> &mut state::InteractionState {
+        &mut self.interaction
+    }
+
+    #[inline]
+    fn mouse_bounds(&self) -> RelativeRect {
+        self.painter.mouse_hint(self.rect)
+    }
+
+    #[inline(always)]
+    fn disabled(&self) -> bool {
+        self.data.disabled
+    }
+
+    #[inline]
+    fn focus_id(&self) -> Option<u64> {
+        Some(self.focus.id())
+    }
+
+    fn on_interaction_event(&mut self, event: ui::InteractionEvent) {
+        self.repaint();
+        match event {
+            ui::InteractionEvent::Released(pos) => {
+                if let Some(index) = self.row_at(pos) {
+                    let row = self.data.rows().swap_remove(index);
+                    let rect = self.abs_rect();
+                    let disclosure_start = rect.origin.x + row.depth as f32 * self.data.indent;
+                    let x = self.data.direction.mirror_x(pos.x, rect.cast_unit());
+
+                    // Clicking the disclosure triangle toggles, clicking anywhere else selects.
+                    if row.expandable
+                        && x >= disclosure_start
+                        && x < disclosure_start + self.data.indent
+                    {
+                        self.toggle(&row.path);
+                    } else {
+                        self.select(row.path);
+                    }
+                }
+            }
+            ui::InteractionEvent::Focus => {
+                self.event_queue.emit_owned(TreeViewEvent::Focus);
+            }
+            ui::InteractionEvent::Blur => {
+                self.event_queue.emit_owned(TreeViewEvent::Blur);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<U, G> ui::Recyclable for TreeViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn recycle(&mut self) {
+        self.interaction = state::InteractionState::empty();
+        self.hovered = None;
+        self.repaint();
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<state::TreeViewState> for TreeViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) -> state::TreeViewState {
+        state::TreeViewState {
+            rect: self.abs_rect(),
+            data: self.data.clone(),
+            hovered: self.hovered,
+            interaction: self.interaction,
+        }
+    }
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.focus.set_rect(self.abs_rect());
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    pub struct TreeViewWidget {
+        widget::MAX,
+
+        <TreeViewEvent> EventQueue,
+        <TreeView> State,
+        <state::TreeViewState> Painter,
+
+        {
+            interaction: state::InteractionState,
+            hovered: Option<usize>,
+            focus: base::FocusHandle,
+        },
+    }
+}
+
+impl<U, G> TreeViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    /// Returns the index of the visible row under `point`, if any.
+    pub fn row_at(&self, point: AbsolutePoint) -> Option<usize> {
+        let rect = self.abs_rect();
+        if !rect.contains(point) || self.data.row_height <= 0.0 {
+            return None;
+        }
+
+        let index = ((point.y - rect.origin.y) / self.data.row_height) as usize;
+        if index < self.data.rows().len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Expands the node at `path`, if it has children and isn't already expanded.
+    pub fn expand(&mut self, path: &[usize]) {
+        if let Some(node) = self.data.node(path) {
+            if !node.children.is_empty() && !node.expanded {
+                self.data.node_mut(path).unwrap().expanded = true;
+                self.event_queue.emit_owned(TreeViewEvent::Expand(path.to_vec()));
+            }
+        }
+    }
+
+    /// Collapses the node at `path`, if it is expanded.
+    ///
+    /// If the selected node is hidden by collapsing, the selection moves to the collapsed node.
+    pub fn collapse(&mut self, path: &[usize]) {
+        if self.data.node(path).map(|node| node.expanded).unwrap_or(false) {
+            self.data.node_mut(path).unwrap().expanded = false;
+            self.event_queue.emit_owned(TreeViewEvent::Collapse(path.to_vec()));
+
+            let hides_selection = self
+                .data
+                .selected
+                .as_ref()
+                .map(|selected| selected.len() > path.len() && selected.starts_with(path))
+                .unwrap_or(false);
+            if hides_selection {
+                self.select(path.to_vec());
+            }
+        }
+    }
+
+    /// Expands the node at `path` if it is collapsed, otherwise collapses it.
+    pub fn toggle(&mut self, path: &[usize]) {
+        if self.data.node(path).map(|node| node.expanded).unwrap_or(false) {
+            self.collapse(path);
+        } else {
+            self.expand(path);
+        }
+    }
+
+    /// Selects the node at `path`.
+    pub fn select(&mut self, path: Vec<usize>) {
+        if self.data.node(&path).is_some() && self.data.selected.as_ref() != Some(&path) {
+            self.data.selected = Some(path.clone());
+            self.event_queue.emit_owned(TreeViewEvent::Select(path));
+        }
+    }
+}
+
+impl<U, G> Widget for TreeViewWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    fn bounds(&self) -> Rect {
+        self.painter.paint_hint(self.rect).cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let state = self.derive_state();
+        let painter = &mut self.painter;
+        self.command_group.push_with(
+            display,
+            || painter.draw(state),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
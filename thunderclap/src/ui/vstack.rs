@@ -11,6 +11,7 @@ use {
         display::{self, DisplayCommand, Rect, Size},
         event::{bidir_single::Queue as BidirSingleEventQueue, RcEventListener},
         prelude::*,
+        verbgraph as vg,
     },
 };
 
@@ -23,6 +24,9 @@ pub struct VStackItem {
     pub bottom_margin: f32,
     /// How the child should be horizontally aligned within the `VStack`.
     pub alignment: Align,
+    /// Binds the rectangle to the window, so that the layout fills the window and re-runs whenever it's resized.
+    /// This is intended for the root layout.
+    pub fill_window: bool,
 }
 
 impl VStackItem {
@@ -73,19 +77,30 @@ where
     G: base::GraphicalAuxiliary,
 {
     fn from_theme(_theme: &dyn draw::Theme) -> Self {
-        VStack { top_margin: 0.0, bottom_margin: 0.0, alignment: Align::Begin }
+        VStack { top_margin: 0.0, bottom_margin: 0.0, alignment: Align::Begin, fill_window: false }
     }
 
-    fn construct(self, _theme: &dyn draw::Theme, _u_aux: &mut U) -> VStackWidget<U, G>
+    fn construct(self, _theme: &dyn draw::Theme, u_aux: &mut U) -> VStackWidget<U, G>
     where
         U: base::UpdateAuxiliary,
         G: base::GraphicalAuxiliary,
     {
         let data = base::Observed::new(self);
 
+        let mut graph = vg::verbgraph! {
+            VStackWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => { change => { obj.dirty = true; } }
+        };
+
+        graph = graph.add(
+            "window",
+            ui::window_fill_handler::<VStackWidget<U, G>, U>().bind(u_aux.window_queue()),
+        );
+
         VStackWidgetBuilder {
             rect: Default::default(),
-            graph: None,
+            graph: graph.into(),
             data,
 
             rects: IndexMap::new(),
@@ -109,6 +124,17 @@ where
     }
 }
 
+impl<U, G> ui::WindowFill for VStackWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    #[inline]
+    fn fill_window(&self) -> bool {
+        self.data.fill_window
+    }
+}
+
 use crate as thunderclap;
 crate::widget! {
     #[doc = "Abstract layout widget which arranges children in a vertical list, possibly with top/bottom margins and horizontal alignment (see `VStackData`)."]
@@ -131,6 +157,10 @@ where
     G: base::GraphicalAuxiliary,
 {
     fn resize_to_fit(&mut self) {
+        if self.data.fill_window {
+            return;
+        }
+
         let mut max_size = Size::zero();
         for (_, child) in &self.rects {
             let size: Size = child.rect.size.cast_unit();
@@ -205,7 +235,11 @@ where
         self.rect.cast_unit()
     }
 
-    fn update(&mut self, _aux: &mut U) {
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
         if let Some(rect) = self.layout.receive() {
             self.set_ctxt_rect(rect);
             self.dirty = true;
@@ -258,6 +292,14 @@ where
                 advance += rect.size.height + data.data.bottom_margin;
             }
 
+            let rects: Vec<AbsoluteRect> = self.rects.values().map(|data| data.rect).collect();
+            check_arrangement(
+                self as *const Self as usize,
+                base::WidgetChildren::widget_kind(self),
+                abs_rect,
+                &rects,
+            );
+
             self.dirty = false;
         }
     }
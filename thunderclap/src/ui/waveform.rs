@@ -0,0 +1,225 @@
+//! Audio waveform widget.
+
+use {
+    crate::{
+        base::{self, Repaintable},
+        draw,
+        geom::*,
+        ui,
+    },
+    reclutch::{
+        display::{
+            Color, DisplayCommand, DisplayListBuilder, GraphicsDisplay, GraphicsDisplayPaint,
+            GraphicsDisplayStroke, Point, Rect, Size, VectorPathBuilder,
+        },
+        prelude::*,
+        verbgraph as vg,
+    },
+};
+
+/// Renders a buffer of audio samples as a waveform envelope, with an optional playhead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    /// Audio samples, ranging from -1.0 to 1.0.
+    pub samples: Vec<f32>,
+    /// Horizontal zoom factor, where 1.0 fits the entire buffer within the widget.
+    /// Values below 1.0 are treated as 1.0.
+    pub zoom: f32,
+    /// Index of the first visible sample when zoomed in.
+    pub offset: usize,
+    /// Sample index of the playhead, if there is one.
+    pub playhead: Option<usize>,
+    /// Color of the waveform envelope.
+    pub color: Color,
+    /// Color of the playhead line.
+    pub playhead_color: Color,
+    /// Background color.
+    pub background: Color,
+}
+
+impl Waveform {
+    /// Returns the range of sample indices which are visible with the current zoom and offset.
+    pub fn visible_range(&self) -> std::ops::Range<usize> {
+        let len = self.samples.len();
+        let visible = ((len as f32 / self.zoom.max(1.0)).ceil() as usize).min(len);
+        let start = self.offset.min(len - visible);
+        start..start + visible
+    }
+}
+
+impl<U, G> ui::WidgetDataTarget<U, G> for Waveform
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type Target = WaveformWidget<U, G>;
+}
+
+impl<U, G> ui::WidgetConstructor<U, G> for Waveform
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn from_theme(theme: &dyn draw::Theme) -> Self {
+        let data = theme.data();
+        Waveform {
+            samples: Vec::new(),
+            zoom: 1.0,
+            offset: 0,
+            playhead: None,
+            color: data.scheme.primary,
+            playhead_color: data.scheme.error,
+            background: data.scheme.control_inset,
+        }
+    }
+
+    fn construct(self, _theme: &dyn draw::Theme, _u_aux: &mut U) -> WaveformWidget<U, G>
+    where
+        U: base::UpdateAuxiliary,
+        G: base::GraphicalAuxiliary,
+    {
+        let data = base::Observed::new(self);
+
+        let graph = vg::verbgraph! {
+            WaveformWidget<U, G> as obj,
+            U as _aux,
+            "bind" => _ev in &data.on_change => {
+                change => {
+                    obj.repaint();
+                }
+            }
+        };
+
+        WaveformWidgetBuilder {
+            rect: RelativeRect::new(Default::default(), Size::new(200.0, 50.0).cast_unit()),
+            graph: graph.into(),
+            data,
+        }
+        .build()
+    }
+}
+
+impl<U, G> ui::core::CoreWidget<()> for WaveformWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    fn derive_state(&self) {}
+
+    fn on_transform(&mut self) {
+        self.repaint();
+        self.layout.notify(self.abs_rect());
+    }
+}
+
+use crate as thunderclap;
+crate::widget! {
+    #[doc = "Audio waveform display with zoom and a playhead, only repainting when the sample data changes."]
+    pub struct WaveformWidget {
+        widget::MAX,
+
+        <Waveform> State,
+    }
+}
+
+/// Builds the display commands for a waveform within `rect`.
+fn waveform_commands(data: &Waveform, rect: AbsoluteRect) -> Vec<DisplayCommand> {
+    let mut builder = DisplayListBuilder::new();
+
+    builder.push_rectangle_clip(rect.cast_unit(), true);
+    builder.push_rectangle(
+        rect.cast_unit(),
+        GraphicsDisplayPaint::Fill(data.background.into()),
+        None,
+    );
+
+    let range = data.visible_range();
+    let columns = rect.size.width.floor() as usize;
+    if range.start == range.end || columns == 0 {
+        return builder.build();
+    }
+
+    let samples = &data.samples[range.clone()];
+    let per_column = samples.len() as f32 / columns as f32;
+    let middle = rect.origin.y + rect.size.height / 2.0;
+    let half_height = rect.size.height / 2.0;
+
+    // Compute the (min, max) envelope of the samples covered by each pixel column.
+    let envelope: Vec<(f32, f32)> = (0..columns)
+        .map(|column| {
+            let start = ((column as f32 * per_column) as usize).min(samples.len() - 1);
+            let end =
+                (((column + 1) as f32 * per_column) as usize).max(start + 1).min(samples.len());
+            samples[start..end].iter().fold((0.0f32, 0.0f32), |(min, max), &sample| {
+                let sample = sample.max(-1.0).min(1.0);
+                (min.min(sample), max.max(sample))
+            })
+        })
+        .collect();
+
+    let mut path = VectorPathBuilder::new();
+    path.move_to(Point::new(rect.origin.x, middle - envelope[0].1 * half_height));
+    for (column, (_, max)) in envelope.iter().enumerate() {
+        path.line_to(Point::new(rect.origin.x + column as f32, middle - max * half_height));
+    }
+    for (column, (min, _)) in envelope.iter().enumerate().rev() {
+        path.line_to(Point::new(rect.origin.x + column as f32, middle - min * half_height));
+    }
+
+    builder.push_path(path.build(), true, GraphicsDisplayPaint::Fill(data.color.into()), None);
+
+    if let Some(playhead) = data.playhead.filter(|playhead| range.contains(playhead)) {
+        let x = rect.origin.x
+            + (playhead - range.start) as f32 / (range.end - range.start) as f32 * rect.size.width;
+        builder.push_line(
+            Point::new(x, rect.origin.y),
+            Point::new(x, rect.max_y()),
+            GraphicsDisplayStroke {
+                thickness: 1.0,
+                color: data.playhead_color.into(),
+                ..Default::default()
+            },
+            None,
+        );
+    }
+
+    builder.build()
+}
+
+impl<U, G> Widget for WaveformWidget<U, G>
+where
+    U: base::UpdateAuxiliary,
+    G: base::GraphicalAuxiliary,
+{
+    type UpdateAux = U;
+    type GraphicalAux = G;
+    type DisplayObject = DisplayCommand;
+
+    #[inline]
+    fn bounds(&self) -> Rect {
+        self.rect.cast_unit()
+    }
+
+    fn update(&mut self, aux: &mut U) {
+        let mut graph = self.graph.take().unwrap();
+        graph.update_all(self, aux);
+        self.graph = Some(graph);
+
+        if let Some(rect) = self.layout.receive() {
+            self.set_ctxt_rect(rect);
+            self.command_group.repaint();
+        }
+    }
+
+    fn draw(&mut self, display: &mut dyn GraphicsDisplay, _aux: &mut G) {
+        let rect = self.abs_rect();
+        let data = &self.data;
+        self.command_group.push_with(
+            display,
+            || waveform_commands(data, rect),
+            Default::default(),
+            None,
+            None,
+        );
+    }
+}
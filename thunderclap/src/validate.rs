@@ -0,0 +1,479 @@
+//! Composable validation rules for user input, including asynchronous rules (e.g. checking that a username isn't taken).
+//!
+//! A `Validator` checks a value against its rules in order, reporting the message of the first rule the value breaks:
+//!
+//! ```ignore
+//! let username = Validator::new()
+//!     .rule(Required::new())
+//!     .rule(Length::new(3, 16))
+//!     .rule(Pattern::new("^[a-z0-9_]+$")?.message("Only lowercase letters, digits and underscores"))
+//!     .async_rule(move |name: &String| {
+//!         if server.is_taken(name) { Err("This username is taken".into()) } else { Ok(()) }
+//!     });
+//! ```
+//!
+//! Asynchronous rules only run once every synchronous rule passes, on the task pool of the application
+//! (see `UpdateAuxiliary::task_pool`). `Validation` tracks the resulting `Validity` of a field, which is pending until
+//! every asynchronous rule has finished; forms display the validity of their fields (see `ui::FormWidget::set_validator`).
+//!
+//! Every rule but `Required` lets empty values pass, so that optional fields can have rules as well.
+//! `Pattern` requires the `validation-regex` feature.
+
+use {
+    crate::bridge::TaskPool,
+    std::sync::{mpsc, Arc},
+};
+
+/// Outcome of validating a value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Validity {
+    /// Asynchronous rules are still running.
+    Pending,
+    /// The value passes every rule.
+    Valid,
+    /// The value breaks a rule, whose message is held.
+    Invalid(String),
+}
+
+impl Validity {
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        *self == Validity::Valid
+    }
+
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        *self == Validity::Pending
+    }
+
+    /// Returns the message of the broken rule, if the value is invalid.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Validity::Invalid(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Validity {
+    /// Values which haven't been validated yet are valid, so that untouched fields aren't flagged.
+    fn default() -> Self {
+        Validity::Valid
+    }
+}
+
+/// Value which the built-in rules can check.
+pub trait Validatable {
+    /// Returns the value as text, if it's textual.
+    fn text(&self) -> Option<&str> {
+        None
+    }
+    /// Returns the value as a number, if it's numeric.
+    fn number(&self) -> Option<f64> {
+        None
+    }
+    /// Returns `true` if no value has been entered.
+    fn is_blank(&self) -> bool;
+}
+
+impl Validatable for str {
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    fn is_blank(&self) -> bool {
+        self.trim().is_empty()
+    }
+}
+
+impl Validatable for String {
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    fn is_blank(&self) -> bool {
+        self.trim().is_empty()
+    }
+}
+
+macro_rules! validatable_number {
+    ($($t:ty),*) => {
+        $(
+            impl Validatable for $t {
+                fn number(&self) -> Option<f64> {
+                    Some(*self as _)
+                }
+
+                fn is_blank(&self) -> bool {
+                    false
+                }
+            }
+        )*
+    };
+}
+
+validatable_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T: Validatable> Validatable for Option<T> {
+    fn text(&self) -> Option<&str> {
+        self.as_ref().and_then(Validatable::text)
+    }
+
+    fn number(&self) -> Option<f64> {
+        self.as_ref().and_then(Validatable::number)
+    }
+
+    fn is_blank(&self) -> bool {
+        self.as_ref().map(Validatable::is_blank).unwrap_or(true)
+    }
+}
+
+#[cfg(feature = "forms")]
+impl Validatable for crate::ui::FieldValue {
+    fn text(&self) -> Option<&str> {
+        self.as_str()
+    }
+
+    fn number(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    fn is_blank(&self) -> bool {
+        self.as_str().map(|text| text.trim().is_empty()).unwrap_or(false)
+    }
+}
+
+/// Rule which a value has to follow.
+pub trait Rule<T: ?Sized> {
+    /// Returns the message to show if `value` breaks the rule.
+    fn check(&self, value: &T) -> Result<(), String>;
+}
+
+/// Requires a value to be entered (i.e. non-blank text or `Some`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Required {
+    message: String,
+}
+
+impl Required {
+    pub fn new() -> Self {
+        Required { message: "This field is required".into() }
+    }
+
+    /// Changes the message shown if no value is entered.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+}
+
+impl Default for Required {
+    fn default() -> Self {
+        Required::new()
+    }
+}
+
+impl<T: Validatable + ?Sized> Rule<T> for Required {
+    fn check(&self, value: &T) -> Result<(), String> {
+        if value.is_blank() {
+            Err(self.message.clone())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Limits the amount of characters of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Length {
+    min: usize,
+    max: Option<usize>,
+    message: Option<String>,
+}
+
+impl Length {
+    /// Requires between `min` and `max` characters (inclusive).
+    pub fn new(min: usize, max: usize) -> Self {
+        Length { min, max: Some(max), message: None }
+    }
+
+    pub fn at_least(min: usize) -> Self {
+        Length { min, max: None, message: None }
+    }
+
+    pub fn at_most(max: usize) -> Self {
+        Length { min: 0, max: Some(max), message: None }
+    }
+
+    /// Changes the message shown if the text is too short or too long.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+impl<T: Validatable + ?Sized> Rule<T> for Length {
+    fn check(&self, value: &T) -> Result<(), String> {
+        let length = match value.text() {
+            Some(text) if !text.is_empty() => text.chars().count(),
+            _ => return Ok(()),
+        };
+
+        let error = if length < self.min {
+            format!("Must be at least {} characters long", self.min)
+        } else if self.max.map(|max| length > max).unwrap_or(false) {
+            format!("Must be at most {} characters long", self.max.unwrap())
+        } else {
+            return Ok(());
+        };
+        Err(self.message.clone().unwrap_or(error))
+    }
+}
+
+/// Limits numbers to a range (inclusive).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    min: Option<f64>,
+    max: Option<f64>,
+    message: Option<String>,
+}
+
+impl Range {
+    pub fn new(min: f64, max: f64) -> Self {
+        Range { min: Some(min), max: Some(max), message: None }
+    }
+
+    pub fn at_least(min: f64) -> Self {
+        Range { min: Some(min), max: None, message: None }
+    }
+
+    pub fn at_most(max: f64) -> Self {
+        Range { min: None, max: Some(max), message: None }
+    }
+
+    /// Changes the message shown if the number is out of range.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+impl<T: Validatable + ?Sized> Rule<T> for Range {
+    fn check(&self, value: &T) -> Result<(), String> {
+        let number = match value.number() {
+            Some(number) => number,
+            None => return Ok(()),
+        };
+
+        let error = match (self.min, self.max) {
+            (Some(min), _) if number < min => format!("Must be at least {}", min),
+            (_, Some(max)) if number > max => format!("Must be at most {}", max),
+            _ => return Ok(()),
+        };
+        Err(self.message.clone().unwrap_or(error))
+    }
+}
+
+/// Requires text to match a regular expression.
+#[cfg(feature = "validation-regex")]
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: regex::Regex,
+    message: String,
+}
+
+#[cfg(feature = "validation-regex")]
+impl Pattern {
+    /// Returns `ValidationError::InvalidPattern` if `pattern` isn't a valid regular expression.
+    pub fn new(pattern: &str) -> Result<Self, crate::error::ValidationError> {
+        Ok(Pattern::from_regex(regex::Regex::new(pattern)?))
+    }
+
+    pub fn from_regex(regex: regex::Regex) -> Self {
+        Pattern { regex, message: "Invalid format".into() }
+    }
+
+    /// Changes the message shown if the text doesn't match.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+}
+
+#[cfg(feature = "validation-regex")]
+impl<T: Validatable + ?Sized> Rule<T> for Pattern {
+    fn check(&self, value: &T) -> Result<(), String> {
+        match value.text() {
+            Some(text) if !text.is_empty() && !self.regex.is_match(text) => {
+                Err(self.message.clone())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Rule of a closure (see `custom`).
+pub struct Custom<F>(F);
+
+/// Creates a rule from a closure, which returns the message to show if the value is invalid.
+pub fn custom<T, F>(check: F) -> Custom<F>
+where
+    T: ?Sized,
+    F: Fn(&T) -> Result<(), String>,
+{
+    Custom(check)
+}
+
+impl<T: ?Sized, F: Fn(&T) -> Result<(), String>> Rule<T> for Custom<F> {
+    #[inline]
+    fn check(&self, value: &T) -> Result<(), String> {
+        (self.0)(value)
+    }
+}
+
+type AsyncRule<T> = Arc<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+
+/// List of rules a value has to follow, checked in order.
+pub struct Validator<T> {
+    rules: Vec<Box<dyn Rule<T>>>,
+    async_rules: Vec<AsyncRule<T>>,
+}
+
+impl<T> Validator<T> {
+    pub fn new() -> Self {
+        Validator { rules: Vec::new(), async_rules: Vec::new() }
+    }
+
+    /// Adds a rule, which is checked after the rules added before it.
+    pub fn rule(mut self, rule: impl Rule<T> + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Adds a rule which runs on the task pool (see `Validation::validate`), once every synchronous rule passes.
+    ///
+    /// Asynchronous rules run concurrently; the first to fail decides the message.
+    pub fn async_rule(
+        mut self,
+        rule: impl Fn(&T) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.async_rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Checks `value` against the synchronous rules, returning the message of the first rule it breaks.
+    pub fn check(&self, value: &T) -> Result<(), String> {
+        self.rules.iter().try_for_each(|rule| rule.check(value))
+    }
+
+    /// Returns `true` if there are any asynchronous rules.
+    #[inline]
+    pub fn has_async_rules(&self) -> bool {
+        !self.async_rules.is_empty()
+    }
+}
+
+impl<T> Default for Validator<T> {
+    fn default() -> Self {
+        Validator::new()
+    }
+}
+
+/// Validity of a field, as of the latest value validated.
+///
+/// Results of asynchronous rules arrive through `poll`, which is to be invoked on every update while the validity is
+/// pending. Results for values which have since been validated again are discarded.
+pub struct Validation<T> {
+    validator: Validator<T>,
+    validity: Validity,
+    generation: u64,
+    remaining: usize,
+    sender: mpsc::Sender<(u64, Result<(), String>)>,
+    receiver: mpsc::Receiver<(u64, Result<(), String>)>,
+}
+
+impl<T: Clone + Send + 'static> Validation<T> {
+    pub fn new(validator: Validator<T>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Validation {
+            validator,
+            validity: Validity::default(),
+            generation: 0,
+            remaining: 0,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Validates `value`, running the asynchronous rules on `pool`, or in place if there's no pool.
+    pub fn validate(&mut self, value: &T, pool: Option<&TaskPool>) -> &Validity {
+        self.generation += 1;
+        self.remaining = 0;
+
+        self.validity = match self.validator.check(value) {
+            Err(message) => Validity::Invalid(message),
+            Ok(()) if !self.validator.has_async_rules() => Validity::Valid,
+            Ok(()) => match pool {
+                Some(pool) => {
+                    for rule in &self.validator.async_rules {
+                        let (rule, value, sender, generation) =
+                            (rule.clone(), value.clone(), self.sender.clone(), self.generation);
+                        pool.spawn(move || {
+                            let _ = sender.send((generation, rule(&value)));
+                        });
+                    }
+                    self.remaining = self.validator.async_rules.len();
+                    Validity::Pending
+                }
+                None => match self.validator.async_rules.iter().try_for_each(|rule| rule(value)) {
+                    Ok(()) => Validity::Valid,
+                    Err(message) => Validity::Invalid(message),
+                },
+            },
+        };
+
+        &self.validity
+    }
+
+    /// Receives the results of the asynchronous rules which have finished, returning `true` if the validity changed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((generation, result)) = self.receiver.try_recv() {
+            if generation != self.generation || !self.validity.is_pending() {
+                continue;
+            }
+
+            match result {
+                Ok(()) => {
+                    self.remaining -= 1;
+                    if self.remaining == 0 {
+                        self.validity = Validity::Valid;
+                        changed = true;
+                    }
+                }
+                Err(message) => {
+                    self.validity = Validity::Invalid(message);
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Forgets the validity of the latest value (e.g. when the field is reset), discarding pending results.
+    pub fn reset(&mut self) {
+        self.generation += 1;
+        self.remaining = 0;
+        self.validity = Validity::default();
+    }
+
+    #[inline]
+    pub fn validity(&self) -> &Validity {
+        &self.validity
+    }
+
+    #[inline]
+    pub fn validator(&self) -> &Validator<T> {
+        &self.validator
+    }
+}